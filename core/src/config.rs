@@ -3,33 +3,216 @@ use std::path::Path;
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::ErrorKind;
 use crate::input::mapping::InputMapping;
 use crate::Result;
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// The current version of the config file format. This should be incremented, and a migration
+/// step should be added to `migrate_config`, whenever fields are renamed or their meaning changes.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// This is appended to the path of a config file that could not be loaded, when it is backed up
+const CONFIG_BACKUP_EXTENSION: &str = ".bak";
+
+/// The default simulation tick rate, in ticks per second
+pub const DEFAULT_TICK_RATE: u32 = 60;
+/// The lowest simulation tick rate that is accepted
+pub const MIN_TICK_RATE: u32 = 20;
+/// The highest simulation tick rate that is accepted
+pub const MAX_TICK_RATE: u32 = 240;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
+    /// Config files that were created before this field was introduced are treated as version `0`
+    #[serde(default)]
+    pub version: u32,
     #[serde(default)]
     pub window: WindowConfig,
     #[serde(default)]
+    pub graphics: GraphicsConfig,
+    #[serde(default)]
     pub input: InputMapping,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default, rename = "match")]
+    pub match_settings: MatchConfig,
+    #[serde(default)]
+    pub accessibility: AccessibilityConfig,
+    #[serde(default)]
+    pub ui: UiConfig,
+    #[serde(default)]
+    pub editor: EditorConfig,
 }
 
 impl Config {
+    /// Load the config file at the specified path. If the file is from an older version, it will
+    /// be migrated, and rewritten with all fields populated. If the file does not exist, a
+    /// default config file will be created.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
 
         let mut res = if path.exists() {
             let bytes = fs::read(path)?;
-            toml::from_slice(&bytes)?
+            let mut value: toml::Value = toml::from_slice(&bytes)?;
+
+            let mut changes = migrate_config(&mut value);
+
+            let res: Config = value.clone().try_into()?;
+
+            if !changes.is_empty() {
+                let populated = toml::Value::try_from(&res)?;
+                get_added_fields(&value, &populated, "", &mut changes);
+
+                res.save(path)?;
+
+                for change in changes {
+                    println!("Config: {}", change);
+                }
+
+                println!(
+                    "Config: Migrated '{}' to version {}",
+                    path.display(),
+                    CONFIG_VERSION
+                );
+            }
+
+            res
         } else {
-            Config::default()
+            let res = Config::default();
+
+            res.save(path)?;
+
+            println!("Config: Created default config file '{}'", path.display());
+
+            res
         };
 
         res.input.verify()?;
+        res.network.verify()?;
 
         Ok(res)
     }
+
+    /// Load the config file at the specified path, like `load`, but if the file can not be parsed,
+    /// or if it is invalid, it will be backed up and replaced by a default config file, in stead
+    /// of returning an error.
+    pub fn load_or_recover<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
+
+        match Self::load(path) {
+            Ok(res) => res,
+            Err(err) => {
+                println!("Config: Unable to load '{}': {}", path.display(), err);
+
+                let mut backup_path = path.as_os_str().to_owned();
+                backup_path.push(CONFIG_BACKUP_EXTENSION);
+
+                if let Err(err) = fs::rename(path, &backup_path) {
+                    println!("Config: Unable to back up the invalid config file: {}", err);
+                } else {
+                    println!(
+                        "Config: The invalid config file was moved to '{}'",
+                        Path::new(&backup_path).display()
+                    );
+                }
+
+                let res = Config::default();
+
+                if let Err(err) = res.save(path) {
+                    println!("Config: Unable to write the default config file: {}", err);
+                }
+
+                res
+            }
+        }
+    }
+
+    #[cfg(any(target_family = "unix", target_family = "windows"))]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        // Serializing through a `Value` makes sure that plain values are emitted before tables
+        let value = toml::Value::try_from(self)?;
+        let str = toml::to_string_pretty(&value)?;
+        fs::write(path, str)?;
+        Ok(())
+    }
+
+    #[cfg(target_family = "wasm")]
+    pub fn save<P: AsRef<Path>>(&self, _: P) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: CONFIG_VERSION,
+            window: WindowConfig::default(),
+            graphics: GraphicsConfig::default(),
+            input: InputMapping::default(),
+            network: NetworkConfig::default(),
+            match_settings: MatchConfig::default(),
+            accessibility: AccessibilityConfig::default(),
+            ui: UiConfig::default(),
+            editor: EditorConfig::default(),
+        }
+    }
+}
+
+/// Apply all migration steps, from the version of the config in `value` up to `CONFIG_VERSION`.
+/// This returns a description of every change that was made, which will be empty if the config
+/// is already up to date.
+fn migrate_config(value: &mut toml::Value) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    let table = match value.as_table_mut() {
+        Some(table) => table,
+        None => return changes,
+    };
+
+    let version = table
+        .get("version")
+        .and_then(|version| version.as_integer())
+        .unwrap_or(0) as u32;
+
+    if version >= CONFIG_VERSION {
+        return changes;
+    }
+
+    table.insert(
+        "version".to_string(),
+        toml::Value::Integer(CONFIG_VERSION as i64),
+    );
+
+    changes.push(format!(
+        "Updated version from {} to {}",
+        version, CONFIG_VERSION
+    ));
+
+    changes
+}
+
+/// Add a description of every field in `populated` that is not in `original` to `changes`
+fn get_added_fields(
+    original: &toml::Value,
+    populated: &toml::Value,
+    prefix: &str,
+    changes: &mut Vec<String>,
+) {
+    if let (Some(original), Some(populated)) = (original.as_table(), populated.as_table()) {
+        for (key, value) in populated {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+
+            match original.get(key) {
+                Some(original_value) => get_added_fields(original_value, value, &path, changes),
+                None => changes.push(format!("Added '{}' with default value", path)),
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,3 +236,521 @@ impl Default for WindowConfig {
         }
     }
 }
+
+/// Rendering settings. If dynamic resolution is enabled, the game world is rendered at a lower
+/// resolution, and upscaled, whenever the frame time exceeds the budget of the target frame rate,
+/// while the HUD is always rendered at the native resolution.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GraphicsConfig {
+    #[serde(default, rename = "dynamic-resolution")]
+    pub is_dynamic_resolution_enabled: bool,
+    /// The frame rate that dynamic resolution tries to maintain
+    #[serde(default = "GraphicsConfig::default_target_fps", rename = "target-fps")]
+    pub target_fps: f32,
+    /// The lowest resolution scale that dynamic resolution will go down to, as a fraction of the
+    /// native resolution
+    #[serde(
+        default = "GraphicsConfig::default_min_resolution_scale",
+        rename = "min-resolution-scale"
+    )]
+    pub min_resolution_scale: f32,
+}
+
+impl GraphicsConfig {
+    pub fn default_target_fps() -> f32 {
+        60.0
+    }
+
+    pub fn default_min_resolution_scale() -> f32 {
+        0.5
+    }
+}
+
+impl Default for GraphicsConfig {
+    fn default() -> Self {
+        GraphicsConfig {
+            is_dynamic_resolution_enabled: false,
+            target_fps: Self::default_target_fps(),
+            min_resolution_scale: Self::default_min_resolution_scale(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// The number of simulation ticks per second. When hosting, this is communicated to clients
+    /// when they join, so LAN games can run at a higher rate than games played over the internet.
+    #[serde(default = "default_tick_rate", rename = "tick-rate")]
+    pub tick_rate: u32,
+    /// If this is `true`, clients will request the map from the host when their local copy does
+    /// not match the map hash of the lobby they are joining.
+    #[serde(
+        default = "NetworkConfig::default_is_map_transfer_enabled",
+        rename = "map-transfer"
+    )]
+    pub is_map_transfer_enabled: bool,
+}
+
+impl NetworkConfig {
+    /// Returns the duration of one simulation tick, in seconds
+    pub fn fixed_delta(&self) -> f32 {
+        1.0 / self.tick_rate as f32
+    }
+
+    /// This will return an error if the tick rate is outside of the accepted bounds
+    pub fn verify(&self) -> Result<()> {
+        verify_tick_rate(self.tick_rate)
+    }
+
+    pub fn default_is_map_transfer_enabled() -> bool {
+        true
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            tick_rate: DEFAULT_TICK_RATE,
+            is_map_transfer_enabled: Self::default_is_map_transfer_enabled(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatchConfig {
+    /// If specified, items that are dropped, and that were not placed on the map, will be
+    /// despawned after lying on the ground for this amount of seconds
+    #[serde(
+        default,
+        rename = "item-despawn-delay",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub item_despawn_delay: Option<f32>,
+    /// The amount of seconds before an item is despawned that it will start blinking
+    #[serde(
+        default = "MatchConfig::default_item_despawn_warning",
+        rename = "item-despawn-warning"
+    )]
+    pub item_despawn_warning: f32,
+    /// The despawn timer of an item will be reset if a player is within this distance of it
+    #[serde(
+        default = "MatchConfig::default_item_despawn_radius",
+        rename = "item-despawn-radius"
+    )]
+    pub item_despawn_radius: f32,
+    /// If this is `true`, an arrow will be drawn at the edge of the screen, pointing towards
+    /// players that are offscreen
+    #[serde(
+        default = "MatchConfig::default_has_offscreen_indicators",
+        rename = "offscreen-indicators"
+    )]
+    pub has_offscreen_indicators: bool,
+    /// Items within this distance of a player's collider can be picked up. If this is `0.0`, the
+    /// item and the player must overlap.
+    #[serde(default, rename = "pickup-radius")]
+    pub pickup_radius: f32,
+    #[serde(default, rename = "pickup-mode")]
+    pub pickup_mode: PickupMode,
+    /// If specified, thrown items that hit another player while moving at this speed, or faster,
+    /// will kill that player. Slower items will just drop harmlessly.
+    #[serde(
+        default,
+        rename = "thrown-item-impact-speed",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub thrown_item_impact_speed: Option<f32>,
+    /// The duration, in seconds, that the simulation is slowed down for, when a player is killed
+    /// by another player. If this is `0.0`, hitstop is disabled.
+    #[serde(
+        default = "MatchConfig::default_hitstop_duration",
+        rename = "hitstop-duration"
+    )]
+    pub hitstop_duration: f32,
+    /// The speed of the simulation during hitstop, where `1.0` is normal speed
+    #[serde(
+        default = "MatchConfig::default_hitstop_time_scale",
+        rename = "hitstop-time-scale"
+    )]
+    pub hitstop_time_scale: f32,
+    /// If this is `true`, the aim of local gamepad players will be nudged towards the nearest
+    /// opponent in front of them. This should be disabled for competitive play.
+    #[serde(
+        default = "MatchConfig::default_is_aim_assist_enabled",
+        rename = "aim-assist"
+    )]
+    pub is_aim_assist_enabled: bool,
+    /// The fraction of the angle between the aim and the direction of the target that is
+    /// corrected. This is capped below `1.0`, so aim assist will never lock on to a target.
+    #[serde(
+        default = "MatchConfig::default_aim_assist_strength",
+        rename = "aim-assist-strength"
+    )]
+    pub aim_assist_strength: f32,
+    /// Only targets within this angle, in degrees, to either side of the aim are considered
+    #[serde(
+        default = "MatchConfig::default_aim_assist_cone",
+        rename = "aim-assist-cone"
+    )]
+    pub aim_assist_cone: f32,
+    /// The maximum correction applied to the aim, in degrees
+    #[serde(
+        default = "MatchConfig::default_aim_assist_max_correction",
+        rename = "aim-assist-max-correction"
+    )]
+    pub aim_assist_max_correction: f32,
+    /// Only targets within this distance are considered
+    #[serde(
+        default = "MatchConfig::default_aim_assist_range",
+        rename = "aim-assist-range"
+    )]
+    pub aim_assist_range: f32,
+    /// If this is `false`, players will not be damaged by their own weapons and explosions
+    #[serde(
+        default = "MatchConfig::default_is_self_damage_enabled",
+        rename = "self-damage"
+    )]
+    pub is_self_damage_enabled: bool,
+    /// If this is `false`, players will not be damaged by players on the same team
+    #[serde(
+        default = "MatchConfig::default_is_friendly_fire_enabled",
+        rename = "friendly-fire"
+    )]
+    pub is_friendly_fire_enabled: bool,
+    /// The teams of the players, by player index. Players that do not have an entry here are not
+    /// on a team.
+    #[serde(
+        default,
+        rename = "player-teams",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub player_teams: Vec<u8>,
+}
+
+/// This determines how weapons are picked up. Other items are always picked up on contact.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PickupMode {
+    /// Weapons are picked up when the pickup button is pressed
+    Manual,
+    /// Weapons are picked up as soon as a player without a weapon is in range
+    Auto,
+}
+
+impl Default for PickupMode {
+    fn default() -> Self {
+        PickupMode::Manual
+    }
+}
+
+impl MatchConfig {
+    const DEFAULT_ITEM_DESPAWN_WARNING: f32 = 3.0;
+    const DEFAULT_ITEM_DESPAWN_RADIUS: f32 = 64.0;
+    const DEFAULT_HITSTOP_DURATION: f32 = 0.12;
+    const DEFAULT_HITSTOP_TIME_SCALE: f32 = 0.25;
+    const DEFAULT_AIM_ASSIST_STRENGTH: f32 = 0.3;
+    const DEFAULT_AIM_ASSIST_CONE: f32 = 20.0;
+    const DEFAULT_AIM_ASSIST_MAX_CORRECTION: f32 = 8.0;
+    const DEFAULT_AIM_ASSIST_RANGE: f32 = 480.0;
+
+    /// `aim_assist_strength` is clamped to this, so that aim is never fully locked on a target
+    pub const MAX_AIM_ASSIST_STRENGTH: f32 = 0.9;
+
+    pub fn default_item_despawn_warning() -> f32 {
+        Self::DEFAULT_ITEM_DESPAWN_WARNING
+    }
+
+    pub fn default_item_despawn_radius() -> f32 {
+        Self::DEFAULT_ITEM_DESPAWN_RADIUS
+    }
+
+    pub fn default_has_offscreen_indicators() -> bool {
+        true
+    }
+
+    pub fn default_hitstop_duration() -> f32 {
+        Self::DEFAULT_HITSTOP_DURATION
+    }
+
+    pub fn default_hitstop_time_scale() -> f32 {
+        Self::DEFAULT_HITSTOP_TIME_SCALE
+    }
+
+    pub fn default_is_aim_assist_enabled() -> bool {
+        true
+    }
+
+    pub fn default_aim_assist_strength() -> f32 {
+        Self::DEFAULT_AIM_ASSIST_STRENGTH
+    }
+
+    pub fn default_aim_assist_cone() -> f32 {
+        Self::DEFAULT_AIM_ASSIST_CONE
+    }
+
+    pub fn default_aim_assist_max_correction() -> f32 {
+        Self::DEFAULT_AIM_ASSIST_MAX_CORRECTION
+    }
+
+    pub fn default_aim_assist_range() -> f32 {
+        Self::DEFAULT_AIM_ASSIST_RANGE
+    }
+
+    pub fn default_is_self_damage_enabled() -> bool {
+        true
+    }
+
+    pub fn default_is_friendly_fire_enabled() -> bool {
+        true
+    }
+
+    /// Returns the team of the player with the specified index, if it is on one
+    pub fn get_player_team(&self, player_index: u8) -> Option<u8> {
+        self.player_teams.get(player_index as usize).copied()
+    }
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        MatchConfig {
+            item_despawn_delay: None,
+            item_despawn_warning: Self::DEFAULT_ITEM_DESPAWN_WARNING,
+            item_despawn_radius: Self::DEFAULT_ITEM_DESPAWN_RADIUS,
+            has_offscreen_indicators: Self::default_has_offscreen_indicators(),
+            pickup_radius: 0.0,
+            pickup_mode: PickupMode::default(),
+            thrown_item_impact_speed: None,
+            hitstop_duration: Self::DEFAULT_HITSTOP_DURATION,
+            hitstop_time_scale: Self::DEFAULT_HITSTOP_TIME_SCALE,
+            is_aim_assist_enabled: Self::default_is_aim_assist_enabled(),
+            aim_assist_strength: Self::DEFAULT_AIM_ASSIST_STRENGTH,
+            aim_assist_cone: Self::DEFAULT_AIM_ASSIST_CONE,
+            aim_assist_max_correction: Self::DEFAULT_AIM_ASSIST_MAX_CORRECTION,
+            aim_assist_range: Self::DEFAULT_AIM_ASSIST_RANGE,
+            is_self_damage_enabled: Self::default_is_self_damage_enabled(),
+            is_friendly_fire_enabled: Self::default_is_friendly_fire_enabled(),
+            player_teams: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccessibilityConfig {
+    /// If this is `true`, intense visual effects, like hitstop, will be disabled
+    #[serde(default, rename = "reduce-effects")]
+    pub reduce_effects: bool,
+    /// This is multiplied with the intensity of camera shake and zoom punches, so `0.0` will
+    /// disable them entirely
+    #[serde(
+        default = "AccessibilityConfig::default_shake_scale",
+        rename = "shake-scale"
+    )]
+    pub shake_scale: f32,
+    /// If this is `true`, the intensity of screen flashes will be reduced
+    #[serde(default, rename = "reduce-flashing")]
+    pub reduce_flashing: bool,
+    /// If this is `true`, players will disappear in a puff of smoke, in stead of breaking into
+    /// pieces, when they die
+    #[serde(default, rename = "reduce-gore")]
+    pub reduce_gore: bool,
+}
+
+impl AccessibilityConfig {
+    pub fn default_shake_scale() -> f32 {
+        1.0
+    }
+}
+
+impl Default for AccessibilityConfig {
+    fn default() -> Self {
+        AccessibilityConfig {
+            reduce_effects: false,
+            shake_scale: Self::default_shake_scale(),
+            reduce_flashing: false,
+            reduce_gore: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UiConfig {
+    /// The id of the theme used for the menus. Themes are loaded from `ui/themes/<id>`, in the
+    /// assets directory. If this is not specified, the default theme is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// The factor that font sizes and widget dimensions are multiplied by. If this is not
+    /// specified, it is detected from the DPI of the display, when `high-dpi` is enabled, and
+    /// `1.0` otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+}
+
+impl UiConfig {
+    pub const MIN_SCALE: f32 = 0.5;
+    pub const MAX_SCALE: f32 = 4.0;
+
+    /// Returns the scale of the UI, using `dpi_scale` if no scale is specified. If high DPI is
+    /// disabled, the window is scaled by the OS, so the DPI is ignored.
+    pub fn get_scale(&self, is_high_dpi: bool, dpi_scale: f32) -> f32 {
+        let scale = self
+            .scale
+            .unwrap_or(if is_high_dpi { dpi_scale } else { 1.0 });
+
+        scale.clamp(Self::MIN_SCALE, Self::MAX_SCALE)
+    }
+}
+
+/// Editor settings. The `max-*` values are the thresholds that the estimated runtime cost of a map
+/// is checked against. Maps that exceed any of these are flagged, as they are likely to run poorly.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EditorConfig {
+    #[serde(default = "EditorConfig::default_max_tiles", rename = "max-tiles")]
+    pub max_tiles: usize,
+    #[serde(
+        default = "EditorConfig::default_max_decorations",
+        rename = "max-decorations"
+    )]
+    pub max_decorations: usize,
+    #[serde(
+        default = "EditorConfig::default_max_particle_emitters",
+        rename = "max-particle-emitters"
+    )]
+    pub max_particle_emitters: usize,
+    #[serde(
+        default = "EditorConfig::default_max_draw_calls",
+        rename = "max-draw-calls"
+    )]
+    pub max_draw_calls: usize,
+    /// The time, in seconds, that a key must be held before discrete editor actions, like nudging
+    /// the selection, start to repeat
+    #[serde(
+        default = "EditorConfig::default_key_repeat_delay",
+        rename = "key-repeat-delay"
+    )]
+    pub key_repeat_delay: f32,
+    /// The number of times, per second, that discrete editor actions are repeated, while a key is
+    /// held, after the initial delay
+    #[serde(
+        default = "EditorConfig::default_key_repeat_rate",
+        rename = "key-repeat-rate"
+    )]
+    pub key_repeat_rate: f32,
+    /// The maximum number of steps that can be undone. The oldest steps are discarded once this
+    /// is exceeded.
+    #[serde(
+        default = "EditorConfig::default_history_depth",
+        rename = "history-depth"
+    )]
+    pub history_depth: usize,
+}
+
+impl EditorConfig {
+    pub fn default_max_tiles() -> usize {
+        10000
+    }
+
+    pub fn default_max_decorations() -> usize {
+        250
+    }
+
+    pub fn default_max_particle_emitters() -> usize {
+        50
+    }
+
+    pub fn default_max_draw_calls() -> usize {
+        100
+    }
+
+    pub fn default_key_repeat_delay() -> f32 {
+        0.4
+    }
+
+    pub fn default_key_repeat_rate() -> f32 {
+        15.0
+    }
+
+    pub fn default_history_depth() -> usize {
+        100
+    }
+}
+
+impl Default for EditorConfig {
+    fn default() -> Self {
+        EditorConfig {
+            max_tiles: Self::default_max_tiles(),
+            max_decorations: Self::default_max_decorations(),
+            max_particle_emitters: Self::default_max_particle_emitters(),
+            max_draw_calls: Self::default_max_draw_calls(),
+            key_repeat_delay: Self::default_key_repeat_delay(),
+            key_repeat_rate: Self::default_key_repeat_rate(),
+            history_depth: Self::default_history_depth(),
+        }
+    }
+}
+
+fn default_tick_rate() -> u32 {
+    DEFAULT_TICK_RATE
+}
+
+/// This will return an error if the tick rate is outside of the range defined by `MIN_TICK_RATE`
+/// and `MAX_TICK_RATE`
+pub fn verify_tick_rate(tick_rate: u32) -> Result<()> {
+    if !(MIN_TICK_RATE..=MAX_TICK_RATE).contains(&tick_rate) {
+        return Err(formaterr!(
+            ErrorKind::Config,
+            "Tick rate '{}' is out of bounds (must be between {} and {})!",
+            tick_rate,
+            MIN_TICK_RATE,
+            MAX_TICK_RATE
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_legacy_config() {
+        let original: toml::Value = toml::from_str(
+            r#"
+            [window]
+            width = 800
+            height = 600
+            fullscreen = true
+            high-dpi = true
+            "#,
+        )
+        .unwrap();
+
+        // Config files without a version are only stamped with the current version
+        let mut value = original.clone();
+        let changes = migrate_config(&mut value);
+        assert_eq!(
+            changes,
+            vec![format!("Updated version from 0 to {}", CONFIG_VERSION)]
+        );
+
+        let mut table = value.as_table().unwrap().clone();
+        assert_eq!(
+            table.remove("version"),
+            Some(toml::Value::Integer(CONFIG_VERSION as i64))
+        );
+        assert_eq!(&table, original.as_table().unwrap());
+
+        let config: Config = value.clone().try_into().unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(config.window.is_fullscreen);
+        assert!(config.window.is_high_dpi);
+
+        // A migrated config should not be migrated again
+        assert!(migrate_config(&mut value).is_empty());
+    }
+}
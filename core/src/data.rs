@@ -6,6 +6,7 @@ use macroquad::prelude::*;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use crate::error::ErrorKind;
 use crate::text::ToStringHelper;
 use crate::Result;
 
@@ -24,17 +25,52 @@ impl Error {
             err: err.into(),
         }
     }
+
+    /// Returns the line and column, both starting at `1`, of the location in the file that caused
+    /// the error, if it is a JSON or TOML parsing error that holds one
+    pub fn get_line_col(&self) -> Option<(usize, usize)> {
+        if let Some(err) = self.err.downcast_ref::<serde_json::Error>() {
+            if err.line() > 0 {
+                return Some((err.line(), err.column()));
+            }
+        } else if let Some(err) = self.err.downcast_ref::<toml::de::Error>() {
+            return err.line_col().map(|(line, col)| (line + 1, col + 1));
+        }
+
+        None
+    }
+
+    // Writes the error as `path:line:col: message`, if the location is known, or as
+    // `path: message`, if it is not. The location is removed from the end of the message, as
+    // both serde_json and toml append it there, in their own format.
+    fn write_to(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let message = self.err.to_string();
+
+        match self.get_line_col() {
+            Some((line, col)) => {
+                let suffix = format!(" at line {} column {}", line, col);
+                let message = message.strip_suffix(&suffix).unwrap_or(&message);
+
+                write!(
+                    f,
+                    "File error: {}:{}:{}: {}",
+                    &self.path, line, col, message
+                )
+            }
+            None => write!(f, "File error: {}: {}", &self.path, message),
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "File error: {}: {}", &self.path, &self.err)
+        self.write_to(f)
     }
 }
 
 impl Debug for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "File error: {}: {}", &self.path, &self.err)
+        self.write_to(f)
     }
 }
 
@@ -147,3 +183,251 @@ where
         Ok(res) => Ok(res),
     }
 }
+
+/// Serialize a value into a string of YAML.
+/// Will return a `serde_yaml::Error` if a parsing error is encountered.
+pub fn serialize_yaml_string<T>(value: &T) -> std::result::Result<String, serde_yaml::Error>
+where
+    T: Serialize,
+{
+    let res = serde_yaml::to_string(value)?;
+    Ok(res)
+}
+
+/// Serialize a value into a slice of YAML.
+/// Will return a `serde_yaml::Error` if a parsing error is encountered.
+pub fn serialize_yaml_bytes<T>(value: &T) -> std::result::Result<Vec<u8>, serde_yaml::Error>
+where
+    T: Serialize,
+{
+    let res = serde_yaml::to_vec(value)?;
+    Ok(res)
+}
+
+/// Deserialize a slice of YAML into a value.
+/// Will return a `serde_yaml::Error` if a parsing error is encountered.
+pub fn deserialize_yaml_bytes<'a, T>(value: &'a [u8]) -> std::result::Result<T, serde_yaml::Error>
+where
+    T: Deserialize<'a>,
+{
+    let res = serde_yaml::from_slice(value)?;
+    Ok(res)
+}
+
+/// Deserialize a string of YAML into a value.
+/// Will return a `serde_yaml::Error` if a parsing error is encountered.
+pub fn deserialize_yaml_string<'a, T>(value: &'a str) -> std::result::Result<T, serde_yaml::Error>
+where
+    T: Deserialize<'a>,
+{
+    let res = serde_yaml::from_str(value)?;
+    Ok(res)
+}
+
+/// Deserialize a YAML file into a value
+pub async fn deserialize_yaml_file<T, P: AsRef<Path>>(path: P) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let path_str = path.as_ref().to_string_helper();
+
+    let bytes = load_file(&path_str).await?;
+    match serde_yaml::from_slice(&bytes) {
+        Err(err) => Err(Error::new(path_str.as_str(), err).into()),
+        Ok(res) => Ok(res),
+    }
+}
+
+/// The text formats that `deserialize_file` can parse, determined by file extension
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum DataFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl DataFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "json" => Some(DataFormat::Json),
+            "toml" => Some(DataFormat::Toml),
+            "yaml" | "yml" => Some(DataFormat::Yaml),
+            _ => None,
+        }
+    }
+}
+
+// Returns the format of the file at `path`, or an error, if its extension is not supported
+fn get_data_format(path: &Path) -> std::result::Result<DataFormat, Error> {
+    let path_str = path.to_string_helper();
+
+    DataFormat::from_path(path).ok_or_else(|| {
+        Error::new(
+            &path_str,
+            "Unsupported file extension (expected 'json', 'toml', 'yaml' or 'yml')",
+        )
+    })
+}
+
+// Deserialize the contents of the file at `path`, in the format that matches its extension
+fn deserialize_file_bytes<T>(path: &Path, bytes: &[u8]) -> std::result::Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let path_str = path.to_string_helper();
+
+    match get_data_format(path)? {
+        DataFormat::Json => serde_json::from_slice(bytes).map_err(|err| Error::new(&path_str, err)),
+        DataFormat::Toml => toml::from_slice(bytes).map_err(|err| Error::new(&path_str, err)),
+        DataFormat::Yaml => serde_yaml::from_slice(bytes).map_err(|err| Error::new(&path_str, err)),
+    }
+}
+
+/// Deserialize a JSON, TOML or YAML file into a value, using the parser that matches the
+/// extension of the file. Will return an error, without loading the file, if the extension is
+/// not one of `json`, `toml`, `yaml` or `yml`.
+pub async fn deserialize_file<T, P: AsRef<Path>>(path: P) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let path = path.as_ref();
+
+    get_data_format(path)?;
+
+    let bytes = load_file(&path.to_string_helper()).await?;
+    let res = deserialize_file_bytes(path, &bytes)?;
+
+    Ok(res)
+}
+
+/// Serialize a value into a compact binary representation, using `bincode`.
+/// Note that this format is not self-describing, so types that rely on `skip_serializing_if`, or
+/// that are untagged enums, can not be round-tripped through it.
+/// Will return a `bincode::Error` if a serialization error is encountered.
+#[cfg(feature = "binary")]
+pub fn serialize_binary_bytes<T>(value: &T) -> std::result::Result<Vec<u8>, bincode::Error>
+where
+    T: Serialize,
+{
+    let res = bincode::serialize(value)?;
+    Ok(res)
+}
+
+/// Deserialize a slice of binary data, created with `serialize_binary_bytes`, into a value.
+/// Will return a `bincode::Error` if a parsing error is encountered.
+#[cfg(feature = "binary")]
+pub fn deserialize_binary_bytes<'a, T>(value: &'a [u8]) -> std::result::Result<T, bincode::Error>
+where
+    T: Deserialize<'a>,
+{
+    let res = bincode::deserialize(value)?;
+    Ok(res)
+}
+
+/// Serialize a grid of floats, like the one returned by `NoiseGenerator::sample_grid`, into a flat
+/// slice of little-endian `f32`s, in the same order, for use by external tools.
+pub fn serialize_f32_grid_bytes(grid: &[f32]) -> Vec<u8> {
+    grid.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// Deserialize a slice created with `serialize_f32_grid_bytes` back into a grid of floats.
+/// Will return an error if the length of the slice is not a multiple of the size of an `f32`.
+pub fn deserialize_f32_grid_bytes(bytes: &[u8]) -> Result<Vec<f32>> {
+    if bytes.len() % 4 != 0 {
+        return Err(crate::error::Error::new_const(
+            ErrorKind::Parsing,
+            &"Grid data length is not a multiple of 4 bytes",
+        ));
+    }
+
+    let res = bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect();
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::noise::NoiseGenerator;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct TestData {
+        name: String,
+        value: u32,
+    }
+
+    #[test]
+    fn test_deserialize_file_by_extension() {
+        let expected = TestData {
+            name: "sword".to_string(),
+            value: 3,
+        };
+
+        let files: [(&str, &[u8]); 4] = [
+            ("item.json", br#"{ "name": "sword", "value": 3 }"#),
+            ("item.toml", b"name = \"sword\"\nvalue = 3\n"),
+            ("item.yaml", b"name: sword\nvalue: 3\n"),
+            ("ITEM.YML", b"name: sword\nvalue: 3\n"),
+        ];
+
+        for (path, bytes) in files {
+            let res: TestData = deserialize_file_bytes(Path::new(path), bytes).unwrap();
+            assert_eq!(res, expected);
+        }
+
+        let res = deserialize_file_bytes::<TestData>(Path::new("item.xml"), b"<item />");
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .contains("Unsupported file extension"));
+
+        assert!(deserialize_file_bytes::<TestData>(Path::new("item"), b"").is_err());
+    }
+
+    #[test]
+    fn test_error_line_col() {
+        let bytes = b"{\n  \"name\": \"sword\",\n  \"value\": x\n}";
+        let err = deserialize_file_bytes::<TestData>(Path::new("item.json"), bytes).unwrap_err();
+
+        assert_eq!(err.get_line_col(), Some((3, 12)));
+        assert_eq!(
+            err.to_string(),
+            "File error: item.json:3:12: expected value"
+        );
+
+        let bytes = b"name = \"sword\"\nvalue = x\n";
+        let err = deserialize_file_bytes::<TestData>(Path::new("item.toml"), bytes).unwrap_err();
+
+        let (line, col) = err.get_line_col().unwrap();
+        assert_eq!(line, 2);
+        assert!(err
+            .to_string()
+            .starts_with(&format!("File error: item.toml:2:{}: ", col)));
+        assert!(!err.to_string().ends_with(&format!("column {}", col)));
+
+        // Errors without a location are left as they are
+        let err = Error::new("item.json", "File not found");
+        assert_eq!(err.get_line_col(), None);
+        assert_eq!(err.to_string(), "File error: item.json: File not found");
+    }
+
+    #[test]
+    fn test_f32_grid_round_trip() {
+        let grid = NoiseGenerator::new(5).sample_grid(16, 8, 0.1);
+        assert_eq!(grid.len(), 16 * 8);
+
+        let bytes = serialize_f32_grid_bytes(&grid);
+        assert_eq!(bytes.len(), grid.len() * 4);
+        assert_eq!(&bytes[..4], &grid[0].to_le_bytes());
+
+        assert_eq!(deserialize_f32_grid_bytes(&bytes).unwrap(), grid);
+
+        assert!(deserialize_f32_grid_bytes(&bytes[..5]).is_err());
+    }
+}
@@ -225,6 +225,13 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+#[cfg(feature = "binary")]
+impl From<bincode::Error> for Error {
+    fn from(err: bincode::Error) -> Self {
+        Error::new(ErrorKind::Parsing, err)
+    }
+}
+
 impl From<toml::ser::Error> for Error {
     fn from(err: toml::ser::Error) -> Self {
         Error::new(ErrorKind::Parsing, err)
@@ -237,6 +244,12 @@ impl From<toml::de::Error> for Error {
     }
 }
 
+impl From<serde_yaml::Error> for Error {
+    fn from(err: serde_yaml::Error) -> Self {
+        Error::new(ErrorKind::Parsing, err)
+    }
+}
+
 /// This will create an error based on the parameters you provide.
 /// It follows the same rules as `format!`, only this takes an optional `ErrorKind`, as its
 /// first argument (before the format string), which will be the kind of `Error` returned.
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+
+use macroquad::experimental::collections::storage;
+
+use fishsticks::GamepadContext;
+
+use crate::Config;
+
+/// The controller families that have their own button labels. This is used to show the correct
+/// button names in prompts, like "Press Circle to cancel" on a PlayStation controller.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControllerFamily {
+    Xbox,
+    #[serde(rename = "playstation")]
+    PlayStation,
+    Nintendo,
+    /// Used for unknown controllers. This labels the face buttons by their position.
+    Generic,
+}
+
+impl ControllerFamily {
+    /// Guess the controller family from the name that the OS reports for a gamepad. This will
+    /// return `Generic` if the name is not recognized.
+    pub fn from_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+
+        let matches = |patterns: &[&str]| patterns.iter().any(|pattern| name.contains(pattern));
+
+        if matches(&["xbox", "xinput", "microsoft"]) {
+            ControllerFamily::Xbox
+        } else if matches(&[
+            "playstation",
+            "dualshock",
+            "dualsense",
+            "sony",
+            "ps3",
+            "ps4",
+            "ps5",
+        ]) {
+            ControllerFamily::PlayStation
+        } else if matches(&["nintendo", "switch", "joy-con", "pro controller"]) {
+            ControllerFamily::Nintendo
+        } else {
+            ControllerFamily::Generic
+        }
+    }
+
+    /// Returns the label of the specified button, for this controller family
+    pub fn get_button_label(&self, button: fishsticks::Button) -> &'static str {
+        use fishsticks::Button::*;
+
+        match self {
+            ControllerFamily::Xbox => match button {
+                South => "A",
+                East => "B",
+                West => "X",
+                North => "Y",
+                Select => "VIEW",
+                Start => "MENU",
+                LeftTrigger => "LB",
+                RightTrigger => "RB",
+                LeftTrigger2 => "LT",
+                RightTrigger2 => "RT",
+                _ => get_generic_button_label(button),
+            },
+            ControllerFamily::PlayStation => match button {
+                South => "CROSS",
+                East => "CIRCLE",
+                West => "SQUARE",
+                North => "TRIANGLE",
+                Select => "SHARE",
+                Start => "OPTIONS",
+                LeftTrigger => "L1",
+                RightTrigger => "R1",
+                LeftTrigger2 => "L2",
+                RightTrigger2 => "R2",
+                LeftThumb => "L3",
+                RightThumb => "R3",
+                _ => get_generic_button_label(button),
+            },
+            // Nintendo controllers have A and B, and X and Y, swapped, compared to Xbox controllers
+            ControllerFamily::Nintendo => match button {
+                South => "B",
+                East => "A",
+                West => "Y",
+                North => "X",
+                Select => "-",
+                Start => "+",
+                LeftTrigger => "L",
+                RightTrigger => "R",
+                LeftTrigger2 => "ZL",
+                RightTrigger2 => "ZR",
+                _ => get_generic_button_label(button),
+            },
+            ControllerFamily::Generic => get_generic_button_label(button),
+        }
+    }
+}
+
+impl Default for ControllerFamily {
+    fn default() -> Self {
+        ControllerFamily::Xbox
+    }
+}
+
+fn get_generic_button_label(button: fishsticks::Button) -> &'static str {
+    use fishsticks::Button::*;
+
+    match button {
+        South => "SOUTH",
+        East => "EAST",
+        West => "WEST",
+        North => "NORTH",
+        Select => "SELECT",
+        Start => "START",
+        Mode => "HOME",
+        LeftTrigger => "L1",
+        RightTrigger => "R1",
+        LeftTrigger2 => "L2",
+        RightTrigger2 => "R2",
+        LeftThumb => "LEFT STICK",
+        RightThumb => "RIGHT STICK",
+        DPadUp => "D-PAD UP",
+        DPadDown => "D-PAD DOWN",
+        DPadLeft => "D-PAD LEFT",
+        DPadRight => "D-PAD RIGHT",
+        _ => "?",
+    }
+}
+
+/// Returns the controller family that button prompts should be shown for. This is the family
+/// selected in the config, if any, or else the family of the first connected gamepad, as detected
+/// from the name that it reports.
+pub fn get_controller_family() -> ControllerFamily {
+    if let Some(family) = storage::get::<Config>().input.gamepad_glyphs {
+        return family;
+    }
+
+    storage::try_get::<GamepadContext>()
+        .and_then(|context| {
+            context
+                .gamepads()
+                .next()
+                .map(|(_, gamepad)| ControllerFamily::from_name(gamepad.name()))
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the label of the specified button, for the controller family returned by
+/// `get_controller_family`
+pub fn get_button_label(button: fishsticks::Button) -> &'static str {
+    get_controller_family().get_button_label(button)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_controller_family_from_name() {
+        assert_eq!(
+            ControllerFamily::from_name("Xbox Wireless Controller"),
+            ControllerFamily::Xbox
+        );
+        assert_eq!(
+            ControllerFamily::from_name("Sony Interactive Entertainment DualSense"),
+            ControllerFamily::PlayStation
+        );
+        assert_eq!(
+            ControllerFamily::from_name("Nintendo Switch Pro Controller"),
+            ControllerFamily::Nintendo
+        );
+        assert_eq!(
+            ControllerFamily::from_name("USB Gamepad"),
+            ControllerFamily::Generic
+        );
+    }
+}
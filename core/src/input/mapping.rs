@@ -1,8 +1,11 @@
 use fishsticks::GamepadId;
 
+use macroquad::prelude::*;
+
 use serde::{Deserialize, Serialize};
 
 use crate::error::ErrorKind;
+use crate::input::{ControllerFamily, SavedInputScheme};
 use crate::Result;
 
 #[derive(Debug, Copy, Clone, PartialEq, Hash, Eq, Serialize, Deserialize)]
@@ -536,6 +539,200 @@ impl From<&GamepadId> for GamepadMapping {
     }
 }
 
+/// The keys and buttons that trigger a menu action. Any of them will trigger the action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MenuBinding {
+    #[serde(default)]
+    pub keys: Vec<KeyCode>,
+    #[serde(default)]
+    pub buttons: Vec<Button>,
+}
+
+impl MenuBinding {
+    pub fn new(keys: &[KeyCode], buttons: &[Button]) -> Self {
+        MenuBinding {
+            keys: keys.to_vec(),
+            buttons: buttons.to_vec(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.buttons.is_empty()
+    }
+}
+
+/// The menu navigation bindings. These are used by all menus, in stead of the player mappings,
+/// as menus can be navigated by any player.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MenuMapping {
+    #[serde(default = "MenuMapping::default_confirm")]
+    pub confirm: MenuBinding,
+    #[serde(default = "MenuMapping::default_cancel")]
+    pub cancel: MenuBinding,
+    #[serde(default = "MenuMapping::default_up")]
+    pub up: MenuBinding,
+    #[serde(default = "MenuMapping::default_down")]
+    pub down: MenuBinding,
+}
+
+impl MenuMapping {
+    pub fn default_confirm() -> MenuBinding {
+        MenuBinding::new(&[KeyCode::Enter], &[Button::A])
+    }
+
+    pub fn default_cancel() -> MenuBinding {
+        MenuBinding::new(&[KeyCode::Escape], &[Button::B])
+    }
+
+    pub fn default_up() -> MenuBinding {
+        MenuBinding::new(&[KeyCode::Up, KeyCode::W], &[Button::DPadUp])
+    }
+
+    pub fn default_down() -> MenuBinding {
+        MenuBinding::new(&[KeyCode::Down, KeyCode::S], &[Button::DPadDown])
+    }
+
+    /// This will return an error if an action has no bindings, or if confirm and cancel share an
+    /// input, as that would make it impossible to navigate the menus
+    pub fn verify(&self) -> Result<()> {
+        let bindings = [
+            ("confirm", &self.confirm),
+            ("cancel", &self.cancel),
+            ("up", &self.up),
+            ("down", &self.down),
+        ];
+
+        for (action, binding) in bindings {
+            if binding.is_empty() {
+                return Err(formaterr!(
+                    ErrorKind::Config,
+                    "Menu action '{}' has no bindings!",
+                    action
+                ));
+            }
+        }
+
+        if let Some(keycode) = self
+            .confirm
+            .keys
+            .iter()
+            .find(|keycode| self.cancel.keys.contains(keycode))
+        {
+            return Err(formaterr!(
+                ErrorKind::Config,
+                "Key '{:?}' is bound to both menu confirm and cancel!",
+                keycode
+            ));
+        }
+
+        if let Some(button) = self
+            .confirm
+            .buttons
+            .iter()
+            .find(|button| self.cancel.buttons.contains(button))
+        {
+            return Err(formaterr!(
+                ErrorKind::Config,
+                "Button '{:?}' is bound to both menu confirm and cancel!",
+                button
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for MenuMapping {
+    fn default() -> Self {
+        MenuMapping {
+            confirm: Self::default_confirm(),
+            cancel: Self::default_cancel(),
+            up: Self::default_up(),
+            down: Self::default_down(),
+        }
+    }
+}
+
+/// This determines how the raw values of the analog sticks of gamepads are processed, before they
+/// are used for movement, both in game and in the editor
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnalogStickConfig {
+    /// Stick positions closer to the center than this are treated as if the stick was centered.
+    /// The deadzone is radial, so it is applied to the length of the stick vector, in stead of to
+    /// each axis, and the remaining range is rescaled, so that output starts at zero at its edge.
+    #[serde(default = "AnalogStickConfig::default_deadzone")]
+    pub deadzone: f32,
+    /// The exponent of the response curve, applied to the length of the stick vector after the
+    /// deadzone is removed. `1.0` is linear, while higher values give finer control near the
+    /// center of the stick.
+    #[serde(
+        default = "AnalogStickConfig::default_sensitivity_curve",
+        rename = "sensitivity-curve"
+    )]
+    pub sensitivity_curve: f32,
+}
+
+impl AnalogStickConfig {
+    const DEFAULT_DEADZONE: f32 = 0.15;
+    const DEFAULT_SENSITIVITY_CURVE: f32 = 1.0;
+
+    pub fn default_deadzone() -> f32 {
+        Self::DEFAULT_DEADZONE
+    }
+
+    pub fn default_sensitivity_curve() -> f32 {
+        Self::DEFAULT_SENSITIVITY_CURVE
+    }
+
+    /// Apply the deadzone and the sensitivity curve to a raw stick value. The length of the
+    /// returned vector will be between `0.0` and `1.0`.
+    pub fn apply(&self, value: Vec2) -> Vec2 {
+        let len = value.length();
+
+        if len <= self.deadzone || len == 0.0 {
+            return Vec2::ZERO;
+        }
+
+        let normalized_len = ((len - self.deadzone) / (1.0 - self.deadzone)).min(1.0);
+
+        (value / len) * normalized_len.powf(self.sensitivity_curve)
+    }
+
+    /// This will return an error if the deadzone covers the entire range of the stick, or if the
+    /// sensitivity curve is not positive
+    pub fn verify(&self) -> Result<()> {
+        if !(0.0..1.0).contains(&self.deadzone) {
+            return Err(formaterr!(
+                ErrorKind::Config,
+                "Analog stick deadzone must be at least 0.0 and less than 1.0, but it is {}!",
+                self.deadzone
+            ));
+        }
+
+        if self.sensitivity_curve <= 0.0 {
+            return Err(formaterr!(
+                ErrorKind::Config,
+                "Analog stick sensitivity curve must be greater than 0.0, but it is {}!",
+                self.sensitivity_curve
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for AnalogStickConfig {
+    fn default() -> Self {
+        AnalogStickConfig {
+            deadzone: Self::DEFAULT_DEADZONE,
+            sensitivity_curve: Self::DEFAULT_SENSITIVITY_CURVE,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct InputMapping {
@@ -551,6 +748,26 @@ pub struct InputMapping {
     pub keyboard_secondary: KeyboardMapping,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub gamepads: Vec<GamepadMapping>,
+    /// The controller family that button prompts are shown for. If this is not set, it will be
+    /// detected from the connected gamepad.
+    #[serde(
+        default,
+        rename = "gamepad-glyphs",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub gamepad_glyphs: Option<ControllerFamily>,
+    #[serde(default)]
+    pub menu: MenuMapping,
+    #[serde(default, rename = "analog-stick")]
+    pub analog_stick: AnalogStickConfig,
+    /// The input devices that were assigned to the player slots, the last time a local game was
+    /// started, by slot
+    #[serde(
+        default,
+        rename = "player-slots",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub player_slots: Vec<SavedInputScheme>,
 }
 
 impl InputMapping {
@@ -616,7 +833,9 @@ impl InputMapping {
             }
         }
 
-        Ok(())
+        self.analog_stick.verify()?;
+
+        self.menu.verify()
     }
 }
 
@@ -626,6 +845,59 @@ impl Default for InputMapping {
             keyboard_primary: KeyboardMapping::default_primary(),
             keyboard_secondary: KeyboardMapping::default_secondary(),
             gamepads: Vec::new(),
+            gamepad_glyphs: None,
+            menu: MenuMapping::default(),
+            analog_stick: AnalogStickConfig::default(),
+            player_slots: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_mapping_verify() {
+        assert!(MenuMapping::default().verify().is_ok());
+
+        let mut mapping = MenuMapping::default();
+        mapping.cancel.keys.push(KeyCode::Enter);
+        assert!(mapping.verify().is_err());
+
+        let mut mapping = MenuMapping::default();
+        mapping.cancel.buttons = vec![Button::A];
+        assert!(mapping.verify().is_err());
+
+        let mut mapping = MenuMapping::default();
+        mapping.up = MenuBinding::new(&[], &[]);
+        assert!(mapping.verify().is_err());
+    }
+
+    #[test]
+    fn test_analog_stick_deadzone() {
+        let config = AnalogStickConfig {
+            deadzone: 0.2,
+            sensitivity_curve: 2.0,
+        };
+
+        assert_eq!(config.apply(Vec2::ZERO), Vec2::ZERO);
+        assert_eq!(config.apply(vec2(0.15, 0.0)), Vec2::ZERO);
+        assert_eq!(config.apply(vec2(-0.1, 0.1)), Vec2::ZERO);
+
+        let value = config.apply(vec2(0.6, 0.0));
+        assert!((value.x - 0.25).abs() < 0.0001);
+        assert_eq!(value.y, 0.0);
+
+        let value = config.apply(vec2(0.0, -1.5));
+        assert!((value.y + 1.0).abs() < 0.0001);
+
+        assert!(config.verify().is_ok());
+        assert!(AnalogStickConfig {
+            deadzone: 1.0,
+            ..config
         }
+        .verify()
+        .is_err());
     }
 }
@@ -1,6 +1,8 @@
+mod glyphs;
 pub mod mapping;
 
-pub use mapping::{Button, KeyCode};
+pub use glyphs::{get_button_label, get_controller_family, ControllerFamily};
+pub use mapping::{AnalogStickConfig, Button, KeyCode, MenuBinding, MenuMapping};
 
 use fishsticks::Axis;
 
@@ -34,6 +36,83 @@ pub enum GameInputScheme {
     KeyboardLeft,
     /// Gamepad index
     Gamepad(fishsticks::GamepadId),
+    /// Movement on the keys of `KeyboardLeft`, with fire also bound to the left mouse button. Aimed
+    /// weapons will aim towards the mouse cursor.
+    MouseKeyboard,
+}
+
+impl GameInputScheme {
+    /// Returns `true` if the scheme uses the mouse cursor for aiming
+    pub fn has_mouse_aim(&self) -> bool {
+        matches!(self, GameInputScheme::MouseKeyboard)
+    }
+
+    /// Returns `true` if the two schemes can not be used by different players at the same time,
+    /// as they read the same keys
+    pub fn conflicts_with(&self, other: &GameInputScheme) -> bool {
+        use GameInputScheme::*;
+
+        self == other
+            || matches!(
+                (self, other),
+                (KeyboardLeft, MouseKeyboard) | (MouseKeyboard, KeyboardLeft)
+            )
+    }
+}
+
+/// A `GameInputScheme` as it is saved in the config, to remember which input device was assigned
+/// to each player slot between sessions. Gamepads are identified by their id, as that is the only
+/// identity the gamepad context exposes, so identical controllers are told apart by the order in
+/// which they were connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SavedInputScheme {
+    KeyboardRight,
+    KeyboardLeft,
+    Gamepad { id: usize },
+    MouseKeyboard,
+}
+
+impl From<GameInputScheme> for SavedInputScheme {
+    fn from(scheme: GameInputScheme) -> Self {
+        match scheme {
+            GameInputScheme::KeyboardRight => SavedInputScheme::KeyboardRight,
+            GameInputScheme::KeyboardLeft => SavedInputScheme::KeyboardLeft,
+            GameInputScheme::Gamepad(id) => SavedInputScheme::Gamepad { id: id.into() },
+            GameInputScheme::MouseKeyboard => SavedInputScheme::MouseKeyboard,
+        }
+    }
+}
+
+/// Returns the input schemes of the saved player slots, in order, for the devices that are
+/// available. Slots with a gamepad that is not connected are skipped, as are slots that conflict
+/// with an earlier slot, so the result can always be used as is.
+pub fn restore_player_input(
+    saved: &[SavedInputScheme],
+    connected_gamepads: &[fishsticks::GamepadId],
+) -> Vec<GameInputScheme> {
+    let mut res: Vec<GameInputScheme> = Vec::new();
+
+    for scheme in saved {
+        let scheme = match *scheme {
+            SavedInputScheme::KeyboardRight => Some(GameInputScheme::KeyboardRight),
+            SavedInputScheme::KeyboardLeft => Some(GameInputScheme::KeyboardLeft),
+            SavedInputScheme::MouseKeyboard => Some(GameInputScheme::MouseKeyboard),
+            SavedInputScheme::Gamepad { id } => connected_gamepads
+                .iter()
+                .find(|&&gamepad_id| usize::from(gamepad_id) == id)
+                .map(|&gamepad_id| GameInputScheme::Gamepad(gamepad_id)),
+        };
+
+        if let Some(scheme) = scheme {
+            if !res.iter().any(|other| other.conflicts_with(&scheme)) {
+                res.push(scheme);
+            }
+        }
+    }
+
+    res
 }
 
 pub fn update_gamepad_context(context: Option<&mut GamepadContext>) -> Result<()> {
@@ -66,6 +145,101 @@ pub fn is_gamepad_btn_pressed(context: Option<&GamepadContext>, btn: fishsticks:
     }
 }
 
+/// The actions used to navigate menus. The bindings of these can be changed in the `menu` section
+/// of the input config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    Confirm,
+    Cancel,
+    Up,
+    Down,
+}
+
+fn get_menu_binding(action: MenuAction) -> MenuBinding {
+    let config = storage::get::<Config>();
+    let mapping = &config.input.menu;
+
+    match action {
+        MenuAction::Confirm => mapping.confirm.clone(),
+        MenuAction::Cancel => mapping.cancel.clone(),
+        MenuAction::Up => mapping.up.clone(),
+        MenuAction::Down => mapping.down.clone(),
+    }
+}
+
+/// This will return `true` if any of the bindings of the specified menu action was pressed this frame
+pub fn is_menu_action_pressed(action: MenuAction) -> bool {
+    let binding = get_menu_binding(action);
+
+    if binding
+        .keys
+        .iter()
+        .any(|&keycode| is_key_pressed(keycode.into()))
+    {
+        return true;
+    }
+
+    let context = storage::get::<GamepadContext>();
+
+    binding
+        .buttons
+        .iter()
+        .any(|&button| is_gamepad_btn_pressed(Some(&context), button.into()))
+}
+
+/// This will return `true` if any of the bindings of the specified menu action is held down.
+/// For up and down, the left stick of gamepads will also be checked.
+pub fn is_menu_action_down(action: MenuAction) -> bool {
+    let binding = get_menu_binding(action);
+
+    if binding
+        .keys
+        .iter()
+        .any(|&keycode| is_key_down(keycode.into()))
+    {
+        return true;
+    }
+
+    let context = storage::get::<GamepadContext>();
+
+    for (_, gamepad) in context.gamepads() {
+        let is_down = binding
+            .buttons
+            .iter()
+            .any(|&button| gamepad.digital_inputs.activated(button.into()));
+
+        let stick_value = gamepad.analog_inputs.digital_value(Axis::LeftStickY);
+
+        let is_stick_down = match action {
+            MenuAction::Up => stick_value < 0.0,
+            MenuAction::Down => stick_value > 0.0,
+            _ => false,
+        };
+
+        if is_down || is_stick_down {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Processed stick values with a component beyond this are treated as a press in that direction,
+/// for inputs that are digital, like in-game movement
+pub const ANALOG_STICK_DIGITAL_THRESHOLD: f32 = 0.5;
+
+/// Returns the value of the stick made up of the specified axes, with the deadzone and the
+/// sensitivity curve from the `analog-stick` section of the input config applied
+pub fn get_analog_stick_value(gamepad: &fishsticks::Gamepad, x_axis: Axis, y_axis: Axis) -> Vec2 {
+    let value = vec2(
+        gamepad.analog_inputs.value(x_axis),
+        gamepad.analog_inputs.value(y_axis),
+    );
+
+    let config = storage::get::<Config>();
+    config.input.analog_stick.apply(value)
+}
+
 pub fn collect_local_input(input_scheme: GameInputScheme) -> PlayerInput {
     let mut input = PlayerInput::default();
 
@@ -82,11 +256,13 @@ pub fn collect_local_input(input_scheme: GameInputScheme) -> PlayerInput {
                     .unwrap_or_else(|| ix.into())
             };
 
+            let stick = get_analog_stick_value(gamepad, Axis::LeftStickX, Axis::LeftStickY);
+
             input.left = gamepad.digital_inputs.activated(Button::DPadLeft.into())
-                || gamepad.analog_inputs.digital_value(Axis::LeftStickX) < 0.0;
+                || stick.x < -ANALOG_STICK_DIGITAL_THRESHOLD;
 
             input.right = gamepad.digital_inputs.activated(Button::DPadRight.into())
-                || gamepad.analog_inputs.digital_value(Axis::LeftStickX) > 0.0;
+                || stick.x > ANALOG_STICK_DIGITAL_THRESHOLD;
 
             input.fire = gamepad.digital_inputs.activated(input_mapping.fire.into());
 
@@ -101,7 +277,7 @@ pub fn collect_local_input(input_scheme: GameInputScheme) -> PlayerInput {
                 .just_activated(input_mapping.pickup.into());
 
             input.crouch = gamepad.digital_inputs.activated(Button::DPadDown.into())
-                || gamepad.analog_inputs.digital_value(Axis::LeftStickY) > 0.0;
+                || stick.y > ANALOG_STICK_DIGITAL_THRESHOLD;
 
             input.slide = input.crouch
                 && gamepad
@@ -127,7 +303,41 @@ pub fn collect_local_input(input_scheme: GameInputScheme) -> PlayerInput {
         input.float = is_key_down(input_mapping.jump.into());
         input.crouch = is_key_down(input_mapping.crouch.into());
         input.slide = input.crouch && is_key_pressed(input_mapping.slide.into());
+
+        if input_scheme.has_mouse_aim() {
+            input.fire = input.fire || is_mouse_button_down(MouseButton::Left);
+        }
     }
 
     input
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_player_input() {
+        let saved = [
+            SavedInputScheme::KeyboardLeft,
+            // This conflicts with the first slot
+            SavedInputScheme::MouseKeyboard,
+            // This gamepad is not connected
+            SavedInputScheme::Gamepad { id: 3 },
+            SavedInputScheme::KeyboardRight,
+        ];
+
+        assert_eq!(
+            restore_player_input(&saved, &[]),
+            [
+                GameInputScheme::KeyboardLeft,
+                GameInputScheme::KeyboardRight
+            ]
+        );
+
+        assert_eq!(
+            SavedInputScheme::from(GameInputScheme::MouseKeyboard),
+            SavedInputScheme::MouseKeyboard
+        );
+    }
+}
@@ -13,7 +13,10 @@ mod channel;
 mod transform;
 
 pub use channel::Channel;
-pub use config::{Config, WindowConfig};
+pub use config::{
+    AccessibilityConfig, Config, EditorConfig, GraphicsConfig, MatchConfig, NetworkConfig,
+    PickupMode, UiConfig, WindowConfig,
+};
 pub use error::{Error, Result};
 pub use transform::Transform;
 
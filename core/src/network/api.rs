@@ -2,6 +2,7 @@ use async_trait::async_trait;
 
 use crate::network::message::NetworkMessage;
 
+use crate::error::ErrorKind;
 use crate::Result;
 
 use super::NetworkEvent;
@@ -17,7 +18,6 @@ impl Api {
         unsafe { API_INSTANCE.take() }
     }
 
-    #[allow(dead_code)]
     fn try_get_instance() -> Option<&'static mut Api> {
         unsafe { API_INSTANCE.as_mut() }
     }
@@ -43,6 +43,23 @@ impl Api {
         Ok(())
     }
 
+    /// Dispatch a network message through the backend. This will return an error if the api has
+    /// not been initiated.
+    pub fn dispatch_message(message: NetworkMessage) -> Result<()> {
+        match Self::try_get_instance() {
+            Some(api) => api.backend.dispatch_message(message),
+            None => Err(formaterr!(
+                ErrorKind::Network,
+                "Unable to dispatch message as the api has not been initiated!"
+            )),
+        }
+    }
+
+    /// Get the next event from the queue of the backend, if the api has been initiated
+    pub fn next_event() -> Option<NetworkEvent> {
+        Self::try_get_instance().and_then(|api| api.backend.next_event())
+    }
+
     pub async fn close() -> Result<()> {
         if let Some(mut api) = Self::try_take_instance() {
             api.backend.close().await?;
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::PlayerId;
-use crate::network::Lobby;
+use crate::network::{Lobby, NetworkMessage};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -35,4 +35,9 @@ pub enum NetworkEvent {
     GameEnded {
         lobby_id: PlayerId,
     },
+    /// A message that was dispatched by another player, relayed by the backend
+    MessageReceived {
+        player_id: PlayerId,
+        message: NetworkMessage,
+    },
 }
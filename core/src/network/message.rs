@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::input::PlayerInput;
 
-use super::PlayerId;
+use super::{LobbyMapInfo, PlayerId};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -12,4 +12,19 @@ pub enum NetworkMessage {
         player_id: PlayerId,
         input: PlayerInput,
     },
+    /// Sent by the host when a network game is started, to update the settings of the lobby that
+    /// clients adopt when they join, as well as the map information shown when browsing lobbies
+    UpdateLobbySettings {
+        tick_rate: u32,
+        map_hash: Option<String>,
+        map_info: Option<LobbyMapInfo>,
+    },
+    /// Sent by a client whose map does not match the map hash of the lobby. The host will know
+    /// who sent it from the `NetworkEvent::MessageReceived` that it arrives in.
+    RequestMap,
+    /// Sent by the host in response to `RequestMap`. This holds the serialized map.
+    TransferMap { player_id: PlayerId, map: String },
+    /// Sent periodically by the host, if desync detection is enabled, with the hash of the game
+    /// state at the specified tick, to be compared with the state hash of the client
+    StateHash { tick: u64, hash: u64 },
 }
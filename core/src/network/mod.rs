@@ -12,6 +12,10 @@ use std::net::SocketAddr;
 
 use serde::{Deserialize, Serialize};
 
+use crate::config::DEFAULT_TICK_RATE;
+use crate::error::ErrorKind;
+use crate::Result;
+
 pub type LobbyId = String;
 pub type PlayerId = String;
 
@@ -44,44 +48,109 @@ pub struct Lobby {
     pub privacy: LobbyPrivacy,
     pub state: LobbyState,
     pub players: Vec<Player>,
+    /// The simulation tick rate chosen by the host. Clients will use this when they join.
+    #[serde(default = "default_tick_rate")]
+    pub tick_rate: u32,
+    /// The content hash of the map chosen by the host. Clients use this to verify that they have
+    /// loaded the same map.
+    #[serde(default)]
+    pub map_hash: Option<String>,
+    /// The authoring information of the map chosen by the host, shown when browsing lobbies
+    #[serde(default)]
+    pub map_info: Option<LobbyMapInfo>,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub enum LobbyState {
-    NotStarted,
-    Ready,
-    Starting,
-    Running,
-    Ending,
-    Ended,
+impl Lobby {
+    /// This will return an error if the specified local tick rate does not match the one of the
+    /// lobby, as clients and host must agree on the tick rate for the simulation to stay in sync.
+    pub fn verify_tick_rate(&self, tick_rate: u32) -> Result<()> {
+        if self.tick_rate != tick_rate {
+            return Err(formaterr!(
+                ErrorKind::Network,
+                "Tick rate mismatch: lobby '{}' runs at {} ticks per second but local tick rate is {}!",
+                self.id,
+                self.tick_rate,
+                tick_rate
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// This will return an error if the specified map hash does not match the one of the lobby.
+    /// If the lobby has no map hash, this will always succeed.
+    pub fn verify_map_hash(&self, map_hash: &str) -> Result<()> {
+        if let Some(lobby_map_hash) = &self.map_hash {
+            if lobby_map_hash != map_hash {
+                return Err(formaterr!(
+                    ErrorKind::Network,
+                    "Map mismatch: lobby '{}' uses a map with hash '{}' but local map hash is '{}'!",
+                    self.id,
+                    lobby_map_hash,
+                    map_hash
+                ));
+            }
+        }
+
+        Ok(())
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_tick_rate() -> u32 {
+    DEFAULT_TICK_RATE
+}
+
+/// This holds the authoring information of the map of a lobby, so that it can be shown in the
+/// server browser, and in the lobby, before the map has been loaded
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct Player {
-    pub id: PlayerId,
-    pub username: String,
-    pub state: ClientState,
+pub struct LobbyMapInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recommended_players: Option<u32>,
 }
 
-impl Player {
-    pub fn new(id: &PlayerId, username: &str) -> Self {
-        Player {
-            id: id.clone(),
-            username: username.to_string(),
-            state: ClientState::Unknown,
+impl LobbyMapInfo {
+    /// Returns a one line summary, like `"by Fishy (4 players) [ctf, small]"`, for lobby listings.
+    /// This will be empty if the map has no authoring information.
+    pub fn get_summary(&self) -> String {
+        let mut parts = Vec::new();
+
+        if let Some(author) = &self.author {
+            parts.push(format!("by {}", author));
+        }
+
+        if let Some(players) = self.recommended_players {
+            parts.push(format!("({} players)", players));
         }
+
+        if !self.tags.is_empty() {
+            parts.push(format!("[{}]", self.tags.join(", ")));
+        }
+
+        parts.join(" ")
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
-#[serde(deny_unknown_fields)]
-pub enum ClientState {
-    Unknown,
-    Joined,
-    Ready,
-    Playing,
-    Left,
-    Done,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lobby_map_summary() {
+        let mut info = LobbyMapInfo::default();
+
+        assert_eq!(info.get_summary(), "");
+
+        info.author = Some("Fishy".to_string());
+        info.recommended_players = Some(4);
+        info.tags = vec!["ctf".to_string(), "small".to_string()];
+
+        assert_eq!(info.get_summary(), "by Fishy (4 players) [ctf, small]");
+    }
 }
@@ -39,6 +39,9 @@ impl NoiseGenerator {
         (0, -1, -1),
     ];
 
+    /// `sqrt(2 / 3)`, which scales 3D noise to the range of 2D noise
+    const PERLIN_3D_SCALE: f32 = 0.816_496_6;
+
     pub fn new(seed: i32) -> NoiseGenerator {
         let mut n = NoiseGenerator {
             grad_p: [(0, 0, 0); 512],
@@ -49,17 +52,21 @@ impl NoiseGenerator {
         n
     }
 
+    /// Rebuild the permutation table from `seed`. The same seed will always produce the same
+    /// table, and so the same noise, on all platforms, which is required for deterministic replays
+    /// and networked games.
     pub fn seed(&mut self, seed: i32) {
-        let mut seed = seed;
-        if seed > 0 && seed < 1 {
-            // Scale the seed out
-            seed *= 65536;
-        }
+        // Only 16 bits are used to scramble the permutation table, so the upper bits are folded
+        // into them, in stead of being discarded
+        let mut seed = seed as u32;
+        seed = (seed ^ (seed >> 16)) & 0xffff;
 
         if seed < 256 {
             seed |= seed << 8;
         }
 
+        let seed = seed as i32;
+
         for i in 0..256 {
             let v = if i & 1 > 0 {
                 NoiseGenerator::P[i] ^ (seed & 255)
@@ -114,10 +121,123 @@ impl NoiseGenerator {
         )
     }
 
+    /// 3D perlin noise. An animated `z` coordinate can be used to make 2D noise that changes over
+    /// time, like for water or fog.
+    ///
+    /// Perlin noise peaks at `sqrt(n) / 2` times the length of the gradients, where `n` is the
+    /// number of dimensions, so the result is scaled by `sqrt(2 / 3)`, to keep it in the same range
+    /// as `perlin_2d`, mostly within `-0.5..0.5`.
+    pub fn perlin_3d(&mut self, x: f32, y: f32, z: f32) -> f32 {
+        let mut x_f = x.floor() as i32;
+        let mut y_f = y.floor() as i32;
+        let mut z_f = z.floor() as i32;
+
+        let x = x - x_f as f32;
+        let y = y - y_f as f32;
+        let z = z - z_f as f32;
+
+        x_f &= 255;
+        y_f &= 255;
+        z_f &= 255;
+
+        let x_f = x_f as usize;
+        let y_f = y_f as usize;
+        let z_f = z_f as usize;
+
+        let grad = |x_i: usize, y_i: usize, z_i: usize| {
+            self.grad_p[x_f + x_i + self.perm[y_f + y_i + self.perm[z_f + z_i]]]
+        };
+
+        let n000 = NoiseGenerator::dot3(grad(0, 0, 0), x, y, z);
+        let n001 = NoiseGenerator::dot3(grad(0, 0, 1), x, y, z - 1.0);
+        let n010 = NoiseGenerator::dot3(grad(0, 1, 0), x, y - 1.0, z);
+        let n011 = NoiseGenerator::dot3(grad(0, 1, 1), x, y - 1.0, z - 1.0);
+        let n100 = NoiseGenerator::dot3(grad(1, 0, 0), x - 1.0, y, z);
+        let n101 = NoiseGenerator::dot3(grad(1, 0, 1), x - 1.0, y, z - 1.0);
+        let n110 = NoiseGenerator::dot3(grad(1, 1, 0), x - 1.0, y - 1.0, z);
+        let n111 = NoiseGenerator::dot3(grad(1, 1, 1), x - 1.0, y - 1.0, z - 1.0);
+
+        let u = NoiseGenerator::fade(x);
+        let v = NoiseGenerator::fade(y);
+        let w = NoiseGenerator::fade(z);
+
+        let res = NoiseGenerator::lerp(
+            NoiseGenerator::lerp(
+                NoiseGenerator::lerp(n000, n100, u),
+                NoiseGenerator::lerp(n001, n101, u),
+                w,
+            ),
+            NoiseGenerator::lerp(
+                NoiseGenerator::lerp(n010, n110, u),
+                NoiseGenerator::lerp(n011, n111, u),
+                w,
+            ),
+            v,
+        );
+
+        res * Self::PERLIN_3D_SCALE
+    }
+
+    /// Multi-octave noise, or fractal Brownian motion, for terrain-like shapes. This sums
+    /// `octaves` layers of `perlin_2d`, each at `lacunarity` times the frequency and `persistence`
+    /// times the amplitude of the one before.
+    ///
+    /// The sum is normalized by dividing it by the sum of the amplitudes of the layers, which is
+    /// `(1 - persistence^octaves) / (1 - persistence)`, or `octaves` if `persistence` is `1.0`, so
+    /// the result is in the same range as `perlin_2d`, mostly within `-0.5..0.5`, regardless of the
+    /// number of octaves. As layers rarely peak at the same point, the result will, in practice,
+    /// stay closer to zero as octaves are added.
+    pub fn fractal_2d(
+        &mut self,
+        x: f32,
+        y: f32,
+        octaves: u32,
+        lacunarity: f32,
+        persistence: f32,
+    ) -> f32 {
+        let mut res = 0.0;
+        let mut total_amplitude = 0.0;
+
+        let mut amplitude = 1.0;
+        let mut frequency = 1.0;
+
+        for _ in 0..octaves {
+            res += self.perlin_2d(x * frequency, y * frequency) * amplitude;
+            total_amplitude += amplitude;
+
+            amplitude *= persistence;
+            frequency *= lacunarity;
+        }
+
+        if total_amplitude > 0.0 {
+            res / total_amplitude
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns `width * height` samples of `perlin_2d`, row by row, where the sample at column `x`
+    /// and row `y` is taken at `(x * scale, y * scale)`. A lower scale gives smoother noise.
+    pub fn sample_grid(&mut self, width: usize, height: usize, scale: f32) -> Vec<f32> {
+        let mut res = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                res.push(self.perlin_2d(x as f32 * scale, y as f32 * scale));
+            }
+        }
+
+        res
+    }
+
     fn dot2(tuple: (i32, i32, i32), x: f32, y: f32) -> f32 {
         tuple.0 as f32 * x + tuple.1 as f32 * y
     }
 
+    fn dot3(tuple: (i32, i32, i32), x: f32, y: f32, z: f32) -> f32 {
+        tuple.0 as f32 * x + tuple.1 as f32 * y + tuple.2 as f32 * z
+    }
+
     fn fade(t: f32) -> f32 {
         t * t * t * (t * (t * 6. - 15.) + 10.)
     }
@@ -126,3 +246,117 @@ impl NoiseGenerator {
         (1. - t) * a + t * b
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_POINTS: [(f32, f32); 5] = [
+        (0.5, 0.5),
+        (1.3, 2.7),
+        (-4.2, 17.9),
+        (100.1, -0.3),
+        (255.5, 256.5),
+    ];
+
+    fn sample(noise: &mut NoiseGenerator) -> Vec<f32> {
+        SAMPLE_POINTS
+            .iter()
+            .map(|&(x, y)| noise.perlin_2d(x, y))
+            .collect()
+    }
+
+    #[test]
+    fn test_seed_is_deterministic() {
+        let mut a = NoiseGenerator::new(5);
+        let mut b = NoiseGenerator::new(5);
+
+        assert_eq!(sample(&mut a), sample(&mut b));
+
+        // Reseeding gives the same result as a new generator
+        let mut c = NoiseGenerator::new(1234);
+        c.seed(5);
+
+        assert_eq!(sample(&mut a), sample(&mut c));
+
+        for seed in [6, -5, 65541] {
+            let mut other = NoiseGenerator::new(seed);
+            assert_ne!(sample(&mut a), sample(&mut other));
+        }
+    }
+
+    #[test]
+    fn test_fractal_2d() {
+        let mut noise = NoiseGenerator::new(5);
+
+        // A single octave is plain perlin noise
+        for &(x, y) in &SAMPLE_POINTS {
+            assert_eq!(noise.fractal_2d(x, y, 1, 2.0, 0.5), noise.perlin_2d(x, y));
+        }
+
+        for persistence in [0.25, 0.5, 1.0] {
+            let mut max_perlin: f32 = 0.0;
+            let mut max_fractal: f32 = 0.0;
+
+            for i in 0..1000 {
+                let x = i as f32 * 0.37;
+                let y = i as f32 * 0.61;
+
+                max_perlin = max_perlin.max(noise.perlin_2d(x, y).abs());
+                max_fractal = max_fractal.max(noise.fractal_2d(x, y, 6, 2.0, persistence).abs());
+            }
+
+            // The normalization keeps the result within the range of a single octave
+            assert!(max_fractal > 0.0 && max_fractal <= max_perlin);
+        }
+
+        // The octaves are layered from the base frequency, so the result is not just scaled
+        let a = noise.fractal_2d(1.3, 2.7, 3, 2.0, 0.5);
+        let b = noise.fractal_2d(1.3, 2.7, 3, 3.0, 0.5);
+        assert_ne!(a, b);
+
+        assert_eq!(noise.fractal_2d(1.3, 2.7, 0, 2.0, 0.5), 0.0);
+    }
+
+    #[test]
+    fn test_perlin_3d() {
+        let mut noise = NoiseGenerator::new(5);
+
+        // Noise is zero at the lattice points
+        assert_eq!(noise.perlin_3d(3.0, 4.0, 5.0), 0.0);
+
+        let mut max_2d: f32 = 0.0;
+        let mut max_3d: f32 = 0.0;
+        let mut outside_cnt = 0;
+
+        for i in 0..100 {
+            for j in 0..100 {
+                let x = i as f32 * 0.173;
+                let y = j as f32 * 0.131;
+                let z = (i + j) as f32 * 0.071;
+
+                let value = noise.perlin_3d(x, y, z);
+
+                max_2d = max_2d.max(noise.perlin_2d(x, y).abs());
+                max_3d = max_3d.max(value.abs());
+
+                if value.abs() > 0.5 {
+                    outside_cnt += 1;
+                }
+            }
+        }
+
+        // The range is consistent with the 2D version. It is mostly within -0.5..0.5, and does not
+        // peak beyond the 2D noise, but it is not squashed far below it either.
+        assert!(outside_cnt < 100 * 100 / 20);
+        assert!(max_3d <= max_2d);
+        assert!(max_3d > 0.5);
+
+        // Animating z changes the noise smoothly
+        let a = noise.perlin_3d(1.5, 2.25, 0.5);
+        let b = noise.perlin_3d(1.5, 2.25, 0.51);
+
+        assert_ne!(a, b);
+        assert!((a - b).abs() < 0.05);
+    }
+}
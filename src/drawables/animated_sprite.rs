@@ -14,16 +14,139 @@ use serde::{Deserialize, Serialize};
 
 use core::Transform;
 
+use crate::utils::ease::{Ease, EaseFunction};
 use crate::{Drawable, DrawableKind, Resources};
 
+/// The order that the frames of an animation are played in
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayDirection {
+    Forward,
+    /// Play the frames from the last to the first, so that a row can be reused for the reverse of
+    /// the animation, like a closing animation made from the row of an opening animation.
+    Reverse,
+    /// Play the frames from the first to the last, and then back down to the first. A cycle ends
+    /// when the first frame is reached again.
+    PingPong,
+}
+
+impl Default for PlayDirection {
+    fn default() -> Self {
+        PlayDirection::Forward
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Animation {
     pub id: String,
     pub row: u32,
+    /// The column, on `row`, of the first frame of the animation. This allows several animations
+    /// to be packed on the same row of a sprite sheet.
+    pub start_column: u32,
     pub frames: u32,
     pub fps: u32,
     pub tweens: HashMap<String, Tween>,
     pub is_looping: bool,
+    pub direction: PlayDirection,
+    /// The duration, in seconds, of each frame. If this is empty, or if it does not hold a duration
+    /// for every frame, all frames will have the same duration, determined by `fps`.
+    pub frame_durations: Vec<f32>,
+}
+
+impl Animation {
+    /// Returns the time, in seconds, that the specified frame is displayed for
+    pub fn frame_duration(&self, frame: u32) -> f32 {
+        if self.frame_durations.len() == self.frames as usize {
+            if let Some(&duration) = self.frame_durations.get(frame as usize) {
+                return duration;
+            }
+        }
+
+        1.0 / self.fps as f32
+    }
+
+    /// Returns the frame that the animation starts on
+    pub fn first_frame(&self) -> u32 {
+        match self.direction {
+            PlayDirection::Reverse => self.frames.saturating_sub(1),
+            _ => 0,
+        }
+    }
+
+    /// Returns `true` if `frame` is the last frame of a cycle of the animation. `is_reversing`
+    /// should be `true` if a ping-pong animation is on its way back down.
+    pub fn is_last_frame(&self, frame: u32, is_reversing: bool) -> bool {
+        match self.direction {
+            PlayDirection::Forward => frame + 1 >= self.frames,
+            PlayDirection::Reverse => frame == 0,
+            PlayDirection::PingPong => self.frames <= 1 || (frame == 0 && is_reversing),
+        }
+    }
+
+    /// Returns the frame after `frame`, and whether a ping-pong animation will be on its way back
+    /// down, on that frame. At the end of a cycle, this returns the start of the next cycle.
+    pub fn next_frame(&self, frame: u32, is_reversing: bool) -> (u32, bool) {
+        let last_frame = self.frames.saturating_sub(1);
+
+        match self.direction {
+            PlayDirection::Forward => ((frame + 1) % self.frames.max(1), false),
+            PlayDirection::Reverse => {
+                if frame == 0 || frame > last_frame {
+                    (last_frame, false)
+                } else {
+                    (frame - 1, false)
+                }
+            }
+            PlayDirection::PingPong => {
+                if last_frame == 0 {
+                    (0, false)
+                } else if is_reversing && frame > 0 {
+                    (frame - 1, true)
+                } else {
+                    let next = (frame + 1).min(last_frame);
+                    (next, next == last_frame)
+                }
+            }
+        }
+    }
+
+    /// Returns the time, in seconds, from the specified point in the animation, until it reaches
+    /// its last frame, or, if it is looping, until its next cycle starts. `frame_timer` is the
+    /// time that `frame` has already been displayed for.
+    pub fn get_time_remaining(&self, frame: u32, is_reversing: bool, frame_timer: f32) -> f32 {
+        let mut frame = frame;
+        let mut is_reversing = is_reversing;
+        let mut elapsed = frame_timer;
+
+        let mut res = 0.0;
+
+        // A cycle of a ping-pong animation is at most twice as long as its number of frames
+        for _ in 0..self.frames.max(1) * 2 {
+            let is_last_frame = self.is_last_frame(frame, is_reversing);
+
+            if !is_last_frame || self.is_looping {
+                res += (self.frame_duration(frame) - elapsed).max(0.0);
+            }
+
+            if is_last_frame {
+                break;
+            }
+
+            elapsed = 0.0;
+
+            let (next_frame, next_is_reversing) = self.next_frame(frame, is_reversing);
+            frame = next_frame;
+            is_reversing = next_is_reversing;
+        }
+
+        res
+    }
+
+    /// Returns the time, in seconds, from the start of the animation, until it reaches its last
+    /// frame, or, if it is looping, the duration of a full cycle
+    pub fn get_duration(&self) -> f32 {
+        self.get_time_remaining(self.first_frame(), false, 0.0)
+    }
 }
 
 impl From<AnimationMetadata> for Animation {
@@ -34,13 +157,26 @@ impl From<AnimationMetadata> for Animation {
                 .map(|meta| (meta.id.clone(), meta.into())),
         );
 
+        #[cfg(debug_assertions)]
+        if !meta.frame_durations.is_empty() && meta.frame_durations.len() != meta.frames as usize {
+            println!(
+                "WARNING: Animation '{}' has {} frames but {} frame durations. Using fps in stead...",
+                meta.id,
+                meta.frames,
+                meta.frame_durations.len()
+            );
+        }
+
         Animation {
             id: meta.id,
             row: meta.row,
+            start_column: meta.start_column,
             frames: meta.frames,
             fps: meta.fps,
             tweens,
             is_looping: meta.is_looping,
+            direction: meta.direction,
+            frame_durations: meta.frame_durations,
         }
     }
 }
@@ -143,11 +279,22 @@ pub struct AnimatedSprite {
     pub queued_action: Option<QueuedAnimationAction>,
     pub current_frame: u32,
     pub frame_timer: f32,
+    /// This is `true` while a ping-pong animation is on its way back down
+    pub is_reversing: bool,
     pub is_playing: bool,
     pub is_flipped_x: bool,
     pub is_flipped_y: bool,
     pub is_deactivated: bool,
     pub wait_timer: f32,
+    /// The id of a non-looping animation that `completed` should be set to, when it completes
+    pub on_completed: Option<String>,
+    /// The id of the animation, set through `notify_on_complete`, that has completed, if any
+    pub completed: Option<String>,
+    /// The color that the sprite is flashing, if `flash_timer` is above zero
+    pub flash_color: Color,
+    pub flash_duration: f32,
+    /// The time left of the current flash, in seconds
+    pub flash_timer: f32,
 }
 
 impl From<AnimatedSpriteMetadata> for AnimatedSprite {
@@ -186,6 +333,7 @@ impl AnimatedSprite {
 
         let mut is_playing = false;
         let mut current_index = 0;
+        let mut current_frame = 0;
 
         if let Some(autoplay_id) = &params.autoplay_id {
             is_playing = true;
@@ -193,6 +341,7 @@ impl AnimatedSprite {
             for (i, animation) in animations.iter().enumerate() {
                 if animation.id == *autoplay_id {
                     current_index = i;
+                    current_frame = animation.first_frame();
                     break;
                 }
             }
@@ -202,6 +351,23 @@ impl AnimatedSprite {
             .frame_size
             .unwrap_or_else(|| texture_res.frame_size());
 
+        #[cfg(debug_assertions)]
+        {
+            let column_cnt = (texture_res.texture.width() / frame_size.x) as u32;
+
+            for animation in &animations {
+                if animation.start_column + animation.frames > column_cnt {
+                    println!(
+                        "WARNING: Animation '{}' of texture '{}' ends at column {}, but the texture only has {} columns",
+                        animation.id,
+                        texture_id,
+                        animation.start_column + animation.frames,
+                        column_cnt
+                    );
+                }
+            }
+        }
+
         AnimatedSprite {
             texture: texture_res.texture,
             frame_size,
@@ -213,12 +379,18 @@ impl AnimatedSprite {
             frame_timer: 0.0,
             current_index,
             queued_action: None,
-            current_frame: 0,
+            current_frame,
+            is_reversing: false,
             is_playing,
             is_flipped_x: params.is_flipped_x,
             is_flipped_y: params.is_flipped_y,
             is_deactivated: false,
             wait_timer: 0.0,
+            on_completed: None,
+            completed: None,
+            flash_color: params.tint,
+            flash_duration: 0.0,
+            flash_timer: 0.0,
         }
     }
 
@@ -238,7 +410,7 @@ impl AnimatedSprite {
         let animation = self.animations.get(self.current_index).unwrap();
 
         Rect::new(
-            self.current_frame as f32 * self.frame_size.x,
+            (animation.start_column + self.current_frame) as f32 * self.frame_size.x,
             animation.row as f32 * self.frame_size.y,
             self.frame_size.x,
             self.frame_size.y,
@@ -257,8 +429,13 @@ impl AnimatedSprite {
         if should_restart || self.current_index != index {
             self.wait_timer = 0.0;
             self.current_index = index;
-            self.current_frame = 0;
+            self.current_frame = self
+                .animations
+                .get(index)
+                .map(|animation| animation.first_frame())
+                .unwrap_or(0);
             self.frame_timer = 0.0;
+            self.is_reversing = false;
             self.is_playing = true;
         }
     }
@@ -273,9 +450,62 @@ impl AnimatedSprite {
         self.queued_action = Some(action);
     }
 
+    /// Briefly tint the sprite with `color`, like a red flash when a player takes damage. The flash
+    /// fades out over `duration` seconds, after which the sprite is drawn with its own tint again.
+    pub fn flash(&mut self, color: Color, duration: f32) {
+        self.flash_color = color;
+        self.flash_duration = duration;
+        self.flash_timer = duration;
+    }
+
+    /// Returns the tint that the sprite should be drawn with, including any active flash
+    pub fn get_tint(&self) -> Color {
+        get_flash_tint(
+            self.tint,
+            self.flash_color,
+            self.flash_timer,
+            self.flash_duration,
+        )
+    }
+
+    /// Get notified, through `take_completed`, when the non-looping animation with the specified
+    /// id completes. This replaces any previous request, and fires only once.
+    pub fn notify_on_complete(&mut self, id: &str) {
+        self.on_completed = Some(id.to_string());
+    }
+
+    /// Returns the id of the animation, set through `notify_on_complete`, if it has completed
+    /// since the last call. This should be polled every frame.
+    pub fn take_completed(&mut self) -> Option<String> {
+        self.completed.take()
+    }
+
+    /// Returns the time, in seconds, until the current animation reaches its last frame, or, if it
+    /// is looping, until its next cycle starts
+    pub fn time_remaining(&self) -> f32 {
+        self.current_animation().get_time_remaining(
+            self.current_frame,
+            self.is_reversing,
+            self.frame_timer,
+        )
+    }
+
+    /// Returns the progress of the current animation, from `0.0` to `1.0`, towards its last frame,
+    /// or, if it is looping, through its current cycle
+    pub fn progress(&self) -> f32 {
+        let duration = self.current_animation().get_duration();
+
+        if duration > 0.0 {
+            (1.0 - self.time_remaining() / duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+
     pub fn restart(&mut self) {
-        self.current_frame = 0;
+        self.current_frame = self.current_animation().first_frame();
         self.frame_timer = 0.0;
+        self.is_reversing = false;
         self.is_playing = true;
     }
 }
@@ -300,16 +530,27 @@ pub fn update_animated_sprites(world: &mut World) {
 pub fn update_one_animated_sprite(sprite: &mut AnimatedSprite) {
     let dt = get_frame_time();
 
+    if sprite.flash_timer > 0.0 {
+        sprite.flash_timer = (sprite.flash_timer - dt).max(0.0);
+    }
+
     if !sprite.is_deactivated && sprite.is_playing {
-        let (is_last_frame, is_looping) = {
+        let (is_last_frame, is_looping, is_completion_watched) = {
             let animation = sprite.animations.get(sprite.current_index).unwrap();
             (
-                sprite.current_frame == animation.frames - 1,
+                animation.is_last_frame(sprite.current_frame, sprite.is_reversing),
                 animation.is_looping,
+                sprite.on_completed.as_ref() == Some(&animation.id),
             )
         };
 
         if is_last_frame {
+            // The watched id is taken, so that this fires only once, even if the sprite stays on
+            // its last frame, waiting for a queued action
+            if !is_looping && is_completion_watched {
+                sprite.completed = sprite.on_completed.take();
+            }
+
             let queued_action = sprite.queued_action.clone();
 
             if let Some(action) = queued_action {
@@ -339,22 +580,26 @@ pub fn update_one_animated_sprite(sprite: &mut AnimatedSprite) {
             }
         }
 
-        let (fps, frame_cnt, tweens) = {
-            let animation = sprite.animations.get_mut(sprite.current_index).unwrap();
-            (animation.fps, animation.frames, &mut animation.tweens)
-        };
+        let animation = sprite.animations.get_mut(sprite.current_index).unwrap();
 
         if sprite.is_playing {
             sprite.frame_timer += dt;
 
-            if sprite.frame_timer > 1.0 / fps as f32 {
-                sprite.current_frame += 1;
+            if sprite.frame_timer > animation.frame_duration(sprite.current_frame) {
+                let (next_frame, is_reversing) =
+                    animation.next_frame(sprite.current_frame, sprite.is_reversing);
+
+                sprite.current_frame = next_frame;
+                sprite.is_reversing = is_reversing;
                 sprite.frame_timer = 0.0;
             }
         }
 
+        let frame_cnt = animation.frames;
         sprite.current_frame %= frame_cnt;
 
+        let tweens = &mut animation.tweens;
+
         for tween in tweens.values_mut() {
             let mut current = tween.keyframes.first();
             let mut next = current;
@@ -411,7 +656,7 @@ pub fn draw_one_animated_sprite(transform: &Transform, sprite: &AnimatedSprite)
             sprite.texture,
             position.x,
             position.y,
-            sprite.tint,
+            sprite.get_tint(),
             DrawTextureParams {
                 flip_x: sprite.is_flipped_x,
                 flip_y: sprite.is_flipped_y,
@@ -424,12 +669,45 @@ pub fn draw_one_animated_sprite(transform: &Transform, sprite: &AnimatedSprite)
     }
 }
 
+/// Returns `tint`, blended towards `flash_color` by how much of the flash is left. The flash eases
+/// out, so that it fades smoothly, in stead of snapping off.
+fn get_flash_tint(tint: Color, flash_color: Color, flash_timer: f32, flash_duration: f32) -> Color {
+    if flash_timer <= 0.0 || flash_duration <= 0.0 {
+        return tint;
+    }
+
+    let ease = Ease {
+        ease_in: false,
+        ease_out: true,
+        function: EaseFunction::Quadratic,
+        progress: 1.0 - (flash_timer / flash_duration).clamp(0.0, 1.0),
+    };
+
+    let factor = 1.0 - ease.output();
+
+    Color::new(
+        tint.r + (flash_color.r - tint.r) * factor,
+        tint.g + (flash_color.g - tint.g) * factor,
+        tint.b + (flash_color.b - tint.b) * factor,
+        tint.a + (flash_color.a - tint.a) * factor,
+    )
+}
+
 pub fn debug_draw_one_animated_sprite(position: Vec2, sprite: &AnimatedSprite) {
     if !sprite.is_deactivated {
         let position = position + sprite.offset;
         let size = sprite.size();
 
-        draw_rectangle_lines(position.x, position.y, size.x, size.y, 2.0, color::BLUE)
+        draw_rectangle_lines(position.x, position.y, size.x, size.y, 2.0, color::BLUE);
+
+        // The progress of the current animation is drawn as a bar, along the bottom of the frame
+        draw_rectangle(
+            position.x,
+            position.y + size.y - 2.0,
+            size.x * sprite.progress(),
+            2.0,
+            color::SKYBLUE,
+        );
     }
 }
 
@@ -437,6 +715,10 @@ pub fn debug_draw_one_animated_sprite(position: Vec2, sprite: &AnimatedSprite) {
 pub struct AnimatedSpriteSet {
     pub draw_order: Vec<String>,
     pub map: HashMap<String, AnimatedSprite>,
+    /// The z-indices of sprites in the set, by id. Sprites are drawn in order of z-index, with
+    /// sprites that have the same z-index drawn in `draw_order`. Sprites that are not in this map
+    /// have a z-index of `0`.
+    pub z_indices: HashMap<String, i32>,
 }
 
 impl AnimatedSpriteSet {
@@ -444,6 +726,29 @@ impl AnimatedSpriteSet {
         self.draw_order.is_empty()
     }
 
+    pub fn get_z_index(&self, sprite_id: &str) -> i32 {
+        self.z_indices.get(sprite_id).copied().unwrap_or(0)
+    }
+
+    /// Set the z-index of a sprite in the set, like when a held item should be drawn in front of,
+    /// or behind, the body, depending on the direction a character is facing
+    pub fn set_z_index(&mut self, sprite_id: &str, z_index: i32) {
+        self.z_indices.insert(sprite_id.to_string(), z_index);
+    }
+
+    /// Returns the ids of the sprites in the set, in the order they should be drawn. This is
+    /// `draw_order`, sorted by z-index.
+    pub fn get_sorted_draw_order(&self) -> Vec<&String> {
+        let mut res = self.draw_order.iter().collect::<Vec<_>>();
+
+        if !self.z_indices.is_empty() {
+            // The sort is stable, so sprites with equal z-index keep their order
+            res.sort_by_key(|id| self.get_z_index(id));
+        }
+
+        res
+    }
+
     pub fn size(&self) -> Vec2 {
         let mut size = Vec2::ZERO;
 
@@ -504,6 +809,12 @@ impl AnimatedSpriteSet {
         }
     }
 
+    pub fn flash_all(&mut self, color: Color, duration: f32) {
+        for sprite in self.map.values_mut() {
+            sprite.flash(color, duration);
+        }
+    }
+
     pub fn flip_all_x(&mut self, state: bool) {
         for sprite in self.map.values_mut() {
             sprite.is_flipped_x = state;
@@ -551,7 +862,11 @@ impl From<&[(&str, AnimatedSprite)]> for AnimatedSpriteSet {
                 .map(|(id, sprite)| (id.to_string(), sprite.clone())),
         );
 
-        AnimatedSpriteSet { draw_order, map }
+        AnimatedSpriteSet {
+            draw_order,
+            map,
+            z_indices: HashMap::new(),
+        }
     }
 }
 
@@ -560,12 +875,20 @@ impl From<&[(&str, AnimatedSprite)]> for AnimatedSpriteSet {
 pub struct AnimationMetadata {
     pub id: String,
     pub row: u32,
+    /// The column, on `row`, of the first frame of the animation
+    #[serde(default)]
+    pub start_column: u32,
     pub frames: u32,
     pub fps: u32,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tweens: Vec<TweenMetadata>,
     #[serde(default)]
     pub is_looping: bool,
+    #[serde(default)]
+    pub direction: PlayDirection,
+    /// The duration, in seconds, of each frame. This overrides `fps`, if set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frame_durations: Vec<f32>,
 }
 
 impl From<AnimationMetadata> for MQAnimation {
@@ -613,3 +936,145 @@ pub struct AnimatedSpriteMetadata {
     #[serde(default)]
     pub is_deactivated: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_frames(direction: PlayDirection, frames: u32, cnt: usize) -> Vec<(u32, bool)> {
+        let animation = Animation {
+            id: "test".to_string(),
+            row: 0,
+            start_column: 0,
+            frames,
+            fps: 1,
+            tweens: HashMap::new(),
+            is_looping: true,
+            direction,
+            frame_durations: Vec::new(),
+        };
+
+        let mut frame = animation.first_frame();
+        let mut is_reversing = false;
+
+        let mut res = Vec::new();
+
+        for _ in 0..cnt {
+            res.push((frame, animation.is_last_frame(frame, is_reversing)));
+
+            let (next_frame, next_is_reversing) = animation.next_frame(frame, is_reversing);
+            frame = next_frame;
+            is_reversing = next_is_reversing;
+        }
+
+        res
+    }
+
+    #[test]
+    fn test_play_directions() {
+        assert_eq!(
+            get_frames(PlayDirection::Forward, 3, 4),
+            [(0, false), (1, false), (2, true), (0, false)]
+        );
+
+        assert_eq!(
+            get_frames(PlayDirection::Reverse, 3, 4),
+            [(2, false), (1, false), (0, true), (2, false)]
+        );
+
+        assert_eq!(
+            get_frames(PlayDirection::PingPong, 3, 7),
+            [
+                (0, false),
+                (1, false),
+                (2, false),
+                (1, false),
+                (0, true),
+                (1, false),
+                (2, false),
+            ]
+        );
+
+        assert_eq!(
+            get_frames(PlayDirection::PingPong, 1, 2),
+            [(0, true), (0, true)]
+        );
+    }
+
+    #[test]
+    fn test_frame_durations() {
+        let mut animation = Animation {
+            id: "test".to_string(),
+            row: 0,
+            start_column: 0,
+            frames: 3,
+            fps: 4,
+            tweens: HashMap::new(),
+            is_looping: false,
+            direction: PlayDirection::Forward,
+            frame_durations: vec![0.1, 0.5, 0.1],
+        };
+
+        assert_eq!(animation.frame_duration(0), 0.1);
+        assert_eq!(animation.frame_duration(1), 0.5);
+
+        // Fall back to fps if there is not a duration for every frame
+        animation.frame_durations.pop();
+        assert_eq!(animation.frame_duration(1), 0.25);
+    }
+
+    #[test]
+    fn test_time_remaining() {
+        let mut animation = Animation {
+            id: "test".to_string(),
+            row: 0,
+            start_column: 0,
+            frames: 3,
+            fps: 4,
+            tweens: HashMap::new(),
+            is_looping: false,
+            direction: PlayDirection::Forward,
+            frame_durations: vec![0.25, 0.5, 0.25],
+        };
+
+        // Non-looping animations end when their last frame is reached
+        assert_eq!(animation.get_duration(), 0.75);
+        assert_eq!(animation.get_time_remaining(1, false, 0.125), 0.375);
+        assert_eq!(animation.get_time_remaining(2, false, 0.0), 0.0);
+
+        // Looping animations end when their next cycle starts
+        animation.is_looping = true;
+        assert_eq!(animation.get_duration(), 1.0);
+        assert_eq!(animation.get_time_remaining(2, false, 0.125), 0.125);
+
+        animation.direction = PlayDirection::PingPong;
+        assert_eq!(animation.get_duration(), 1.75);
+        assert_eq!(animation.get_time_remaining(1, true, 0.0), 0.75);
+    }
+
+    #[test]
+    fn test_sprite_set_z_indices() {
+        let mut sprite_set = AnimatedSpriteSet {
+            draw_order: vec!["body".to_string(), "item".to_string(), "hat".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(sprite_set.get_sorted_draw_order(), ["body", "item", "hat"]);
+
+        sprite_set.set_z_index("item", -1);
+        assert_eq!(sprite_set.get_sorted_draw_order(), ["item", "body", "hat"]);
+
+        sprite_set.set_z_index("item", 0);
+        assert_eq!(sprite_set.get_sorted_draw_order(), ["body", "item", "hat"]);
+    }
+
+    #[test]
+    fn test_flash_tint_fades_out() {
+        assert_eq!(get_flash_tint(WHITE, RED, 0.5, 0.5), RED);
+        assert_eq!(get_flash_tint(WHITE, RED, 0.0, 0.5), WHITE);
+
+        let tint = get_flash_tint(WHITE, RED, 0.25, 0.5);
+        assert!(tint.g > 0.0 && tint.g < 1.0);
+        assert_eq!(tint.r, 1.0);
+    }
+}
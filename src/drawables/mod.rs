@@ -1,19 +1,36 @@
 mod animated_sprite;
 mod sprite;
+mod static_sprite;
+mod trail;
 
 pub use animated_sprite::*;
 pub use sprite::*;
+pub use static_sprite::*;
+pub use trail::*;
+
 use std::borrow::{Borrow, BorrowMut};
 
 use macroquad::prelude::*;
 
-use hecs::World;
+use hecs::{Entity, World};
 
 use core::Transform;
 
+/// Drawables with this z-index are drawn behind everything else, like map decorations
+pub const BACKGROUND_Z_INDEX: i32 = -10;
+/// The z-index of players, items and most other drawables
+pub const DEFAULT_Z_INDEX: i32 = 0;
+/// Drawables with this z-index are drawn in front of players and items, like effects
+pub const FOREGROUND_Z_INDEX: i32 = 10;
+
 /// This is a wrapper type for all the different types of drawable sprites, used so that we can
-/// access them all in one query and draw them, ordered, in one pass, according to `draw_order`.
+/// access them all in one query and draw them, ordered, in one pass, according to `z_index` and
+/// `draw_order`.
 pub struct Drawable {
+    /// This is the layer that the drawable belongs to. Drawables are sorted by this first, and by
+    /// `draw_order` second, so that things like decorations and effects will always be drawn in
+    /// the right order, relative to players and items, regardless of their `draw_order`.
+    pub z_index: i32,
     /// This is used to specify draw order on a sprite
     /// This will be used, primarily, by `Player` to draw equipped items in the right order, relative
     /// to its own sprite. This is done by multiplying the player id by ten and adding whatever offset
@@ -21,6 +38,9 @@ pub struct Drawable {
     /// `Player` component.
     pub draw_order: u32,
     pub kind: DrawableKind,
+    /// This is a cosmetic offset, applied to the position of the `Transform` when drawing, that
+    /// can be used for things like idle animations, without affecting the simulation
+    pub offset: Vec2,
 }
 
 impl Drawable {
@@ -28,8 +48,10 @@ impl Drawable {
         let sprite = Sprite::new(texture_id, params);
 
         Drawable {
+            z_index: DEFAULT_Z_INDEX,
             draw_order,
             kind: DrawableKind::Sprite(sprite),
+            offset: Vec2::ZERO,
         }
     }
 
@@ -37,8 +59,10 @@ impl Drawable {
         let sprite_set = SpriteSet::from(sprites);
 
         Drawable {
+            z_index: DEFAULT_Z_INDEX,
             draw_order,
             kind: DrawableKind::SpriteSet(sprite_set),
+            offset: Vec2::ZERO,
         }
     }
 
@@ -51,8 +75,10 @@ impl Drawable {
         let sprite = AnimatedSprite::new(texture_id, animations, params);
 
         Drawable {
+            z_index: DEFAULT_Z_INDEX,
             draw_order,
             kind: DrawableKind::AnimatedSprite(sprite),
+            offset: Vec2::ZERO,
         }
     }
 
@@ -60,11 +86,28 @@ impl Drawable {
         let sprite_set = AnimatedSpriteSet::from(sprites);
 
         Drawable {
+            z_index: DEFAULT_Z_INDEX,
             draw_order,
             kind: DrawableKind::AnimatedSpriteSet(sprite_set),
+            offset: Vec2::ZERO,
         }
     }
 
+    pub fn new_trail(draw_order: u32, trail: Trail) -> Self {
+        Drawable {
+            z_index: DEFAULT_Z_INDEX,
+            draw_order,
+            kind: DrawableKind::Trail(trail),
+            offset: Vec2::ZERO,
+        }
+    }
+
+    /// Set the z-index of the drawable
+    #[allow(dead_code)]
+    pub fn with_z_index(self, z_index: i32) -> Self {
+        Drawable { z_index, ..self }
+    }
+
     pub fn get_sprite(&self) -> Option<&Sprite> {
         match self.kind.borrow() {
             DrawableKind::Sprite(sprite) => Some(sprite),
@@ -120,32 +163,61 @@ impl Drawable {
             _ => None,
         }
     }
+
+    pub fn get_trail(&self) -> Option<&Trail> {
+        match self.kind.borrow() {
+            DrawableKind::Trail(trail) => Some(trail),
+            _ => None,
+        }
+    }
+
+    pub fn get_trail_mut(&mut self) -> Option<&mut Trail> {
+        match self.kind.borrow_mut() {
+            DrawableKind::Trail(trail) => Some(trail),
+            _ => None,
+        }
+    }
 }
 
 pub enum DrawableKind {
     Sprite(Sprite),
+    StaticSprite(StaticSprite),
     SpriteSet(SpriteSet),
     AnimatedSprite(AnimatedSprite),
     AnimatedSpriteSet(AnimatedSpriteSet),
+    Trail(Trail),
 }
 
-pub fn draw_drawables(world: &mut World) {
+/// Returns all entities with a `Drawable`, in the order that they should be drawn. The sort is
+/// stable, so drawables with equal `z_index` and `draw_order` keep the order of the query.
+fn get_ordered_drawables(world: &mut World) -> Vec<Entity> {
     let mut ordered = world
         .query_mut::<&Drawable>()
         .into_iter()
-        .map(|(e, drawable)| (e, drawable.draw_order))
+        .map(|(e, drawable)| (e, (drawable.z_index, drawable.draw_order)))
         .collect::<Vec<_>>();
 
-    ordered.sort_by(|&(_, a), &(_, b)| a.cmp(&b));
+    ordered.sort_by_key(|&(_, key)| key);
 
-    for e in ordered.into_iter().map(|(e, _)| e) {
-        let transform = world.get_mut::<Transform>(e).unwrap();
+    ordered.into_iter().map(|(e, _)| e).collect()
+}
+
+pub fn draw_drawables(world: &mut World) {
+    for e in get_ordered_drawables(world) {
         let mut drawable = world.get_mut::<Drawable>(e).unwrap();
 
+        let transform = world
+            .get::<Transform>(e)
+            .map(|t| Transform::new(t.position + drawable.offset, t.rotation))
+            .unwrap();
+
         match drawable.kind.borrow_mut() {
             DrawableKind::Sprite(sprite) => {
                 draw_one_sprite(&transform, sprite);
             }
+            DrawableKind::StaticSprite(sprite) => {
+                draw_one_static_sprite(&transform, sprite);
+            }
             DrawableKind::SpriteSet(sprite_set) => {
                 for id in sprite_set.draw_order.iter() {
                     let sprite = sprite_set.map.get(id).unwrap();
@@ -156,25 +228,20 @@ pub fn draw_drawables(world: &mut World) {
                 draw_one_animated_sprite(&transform, sprite);
             }
             DrawableKind::AnimatedSpriteSet(sprite_set) => {
-                for id in sprite_set.draw_order.iter() {
+                for id in sprite_set.get_sorted_draw_order() {
                     let sprite = sprite_set.map.get(id).unwrap();
                     draw_one_animated_sprite(&transform, sprite);
                 }
             }
+            DrawableKind::Trail(trail) => {
+                draw_one_trail(trail);
+            }
         }
     }
 }
 
 pub fn debug_draw_drawables(world: &mut World) {
-    let mut ordered = world
-        .query_mut::<&Drawable>()
-        .into_iter()
-        .map(|(e, drawable)| (e, drawable.draw_order))
-        .collect::<Vec<_>>();
-
-    ordered.sort_by(|&(_, a), &(_, b)| a.cmp(&b));
-
-    for e in ordered.into_iter().map(|(e, _)| e) {
+    for e in get_ordered_drawables(world) {
         let position = world.get_mut::<Transform>(e).map(|t| t.position).unwrap();
 
         let drawable = world.get_mut::<Drawable>(e).unwrap();
@@ -183,6 +250,9 @@ pub fn debug_draw_drawables(world: &mut World) {
             DrawableKind::Sprite(sprite) => {
                 debug_draw_one_sprite(position, sprite);
             }
+            DrawableKind::StaticSprite(sprite) => {
+                debug_draw_one_static_sprite(position, sprite);
+            }
             DrawableKind::SpriteSet(sprite_set) => {
                 for id in sprite_set.draw_order.iter() {
                     let sprite = sprite_set.map.get(id).unwrap();
@@ -193,11 +263,12 @@ pub fn debug_draw_drawables(world: &mut World) {
                 debug_draw_one_animated_sprite(position, sprite);
             }
             DrawableKind::AnimatedSpriteSet(sprite_set) => {
-                for id in sprite_set.draw_order.iter() {
+                for id in sprite_set.get_sorted_draw_order() {
                     let sprite = sprite_set.map.get(id).unwrap();
                     debug_draw_one_animated_sprite(position, sprite);
                 }
             }
+            DrawableKind::Trail(_) => {}
         }
     }
 }
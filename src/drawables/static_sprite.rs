@@ -0,0 +1,211 @@
+use macroquad::color;
+use macroquad::experimental::collections::storage;
+use macroquad::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use core::math::IsZero;
+use core::Transform;
+
+use crate::Resources;
+
+/// Parameters for `StaticSprite` component.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StaticSpriteMetadata {
+    /// The id of the texture that will be used
+    #[serde(rename = "texture")]
+    pub texture_id: String,
+    /// The part of the texture that will be drawn. If this is not specified, the whole texture
+    /// will be used.
+    #[serde(
+        default,
+        with = "core::json::rect_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub source_rect: Option<Rect>,
+    /// The number of times the source rect is repeated, along each axis. This can be fractional,
+    /// in which case the last tile on that axis is clipped.
+    #[serde(
+        default,
+        with = "core::json::vec2_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tiling: Option<Vec2>,
+    /// This is a scale factor that the size of each tile will be multiplied by before draw
+    #[serde(default)]
+    pub scale: Option<f32>,
+    /// The offset of the drawn sprite, relative to the position of the entity
+    #[serde(
+        default,
+        with = "core::json::vec2_def",
+        skip_serializing_if = "Vec2::is_zero"
+    )]
+    pub offset: Vec2,
+    /// An optional color to blend with the texture color
+    #[serde(
+        default,
+        with = "core::json::color_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tint: Option<Color>,
+    /// If this is true, the sprite will not be drawn.
+    #[serde(default)]
+    pub is_deactivated: bool,
+}
+
+#[derive(Clone)]
+pub struct StaticSpriteParams {
+    pub source_rect: Option<Rect>,
+    pub tiling: Option<Vec2>,
+    pub scale: f32,
+    pub offset: Vec2,
+    pub tint: Option<Color>,
+    pub is_deactivated: bool,
+}
+
+impl Default for StaticSpriteParams {
+    fn default() -> Self {
+        StaticSpriteParams {
+            source_rect: None,
+            tiling: None,
+            scale: 1.0,
+            offset: Vec2::ZERO,
+            tint: None,
+            is_deactivated: false,
+        }
+    }
+}
+
+impl From<StaticSpriteMetadata> for StaticSpriteParams {
+    fn from(meta: StaticSpriteMetadata) -> Self {
+        StaticSpriteParams {
+            source_rect: meta.source_rect,
+            tiling: meta.tiling,
+            scale: meta.scale.unwrap_or(1.0),
+            offset: meta.offset,
+            tint: meta.tint,
+            is_deactivated: meta.is_deactivated,
+        }
+    }
+}
+
+/// A sprite that is never animated, so it is skipped by the animation update. The source rect can
+/// be tiled, which makes this suitable for repeating backgrounds and borders. As textures may be
+/// packed in atlases, tiling is done by drawing the source rect once for every tile, rather than by
+/// wrapping the texture coordinates.
+#[derive(Debug, Clone)]
+pub struct StaticSprite {
+    pub texture: Texture2D,
+    pub source_rect: Rect,
+    /// The number of times `source_rect` is repeated along each axis
+    pub tiling: Vec2,
+    pub tint: Color,
+    pub scale: f32,
+    pub offset: Vec2,
+    pub is_deactivated: bool,
+}
+
+impl StaticSprite {
+    pub fn new(texture_id: &str, params: StaticSpriteParams) -> Self {
+        let texture = {
+            let resources = storage::get::<Resources>();
+            resources
+                .textures
+                .get(texture_id)
+                .map(|res| res.texture)
+                .unwrap_or_else(|| panic!("StaticSprite: Invalid texture ID '{}'", texture_id))
+        };
+
+        Self::from_texture(texture, params)
+    }
+
+    pub fn from_texture(texture: Texture2D, params: StaticSpriteParams) -> Self {
+        let source_rect = params
+            .source_rect
+            .unwrap_or_else(|| Rect::new(0.0, 0.0, texture.width(), texture.height()));
+
+        let tiling = params.tiling.unwrap_or(Vec2::ONE).max(Vec2::ZERO);
+
+        StaticSprite {
+            texture,
+            source_rect,
+            tiling,
+            tint: params.tint.unwrap_or(color::WHITE),
+            scale: params.scale,
+            offset: params.offset,
+            is_deactivated: params.is_deactivated,
+        }
+    }
+
+    pub fn tile_size(&self) -> Vec2 {
+        self.source_rect.size() * self.scale
+    }
+
+    /// The size of the area covered by all the tiles
+    pub fn size(&self) -> Vec2 {
+        self.tile_size() * self.tiling
+    }
+}
+
+pub fn draw_one_static_sprite(transform: &Transform, sprite: &StaticSprite) {
+    if sprite.is_deactivated {
+        return;
+    }
+
+    let position = transform.position + sprite.offset;
+    let tile_size = sprite.tile_size();
+    let size = sprite.size();
+
+    // All tiles are rotated around the center of the tiled area, so that they stay aligned
+    let pivot = position + size / 2.0;
+
+    let tile_cnt = sprite.tiling.ceil().as_u32();
+
+    for y in 0..tile_cnt.y {
+        let fraction_y = (sprite.tiling.y - y as f32).min(1.0);
+
+        for x in 0..tile_cnt.x {
+            let fraction_x = (sprite.tiling.x - x as f32).min(1.0);
+            let fraction = vec2(fraction_x, fraction_y);
+
+            let source = Rect::new(
+                sprite.source_rect.x,
+                sprite.source_rect.y,
+                sprite.source_rect.w * fraction.x,
+                sprite.source_rect.h * fraction.y,
+            );
+
+            let tile_position = position + vec2(x as f32, y as f32) * tile_size;
+
+            draw_texture_ex(
+                sprite.texture,
+                tile_position.x,
+                tile_position.y,
+                sprite.tint,
+                DrawTextureParams {
+                    rotation: transform.rotation,
+                    source: Some(source),
+                    dest_size: Some(tile_size * fraction),
+                    pivot: Some(pivot),
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
+
+pub fn debug_draw_one_static_sprite(position: Vec2, sprite: &StaticSprite) {
+    if !sprite.is_deactivated {
+        let size = sprite.size();
+
+        draw_rectangle_lines(
+            position.x + sprite.offset.x,
+            position.y + sprite.offset.y,
+            size.x,
+            size.y,
+            2.0,
+            color::BLUE,
+        )
+    }
+}
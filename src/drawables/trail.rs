@@ -0,0 +1,316 @@
+use std::collections::VecDeque;
+
+use macroquad::color;
+use macroquad::prelude::*;
+
+use hecs::{Entity, World};
+
+use serde::{Deserialize, Serialize};
+
+use core::math::IsZero;
+use core::Transform;
+
+use crate::game::get_fixed_delta;
+
+use super::Drawable;
+
+/// Trails are drawn before the drawables with the same z-index, so that they appear behind the
+/// sprites of the entities they follow
+const TRAIL_DRAW_ORDER: u32 = 0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrailMetadata {
+    /// The maximum amount of points recorded. A point is recorded every simulation tick.
+    #[serde(default = "TrailMetadata::default_length")]
+    pub length: usize,
+    /// Points older than this, in seconds, are removed
+    #[serde(default = "TrailMetadata::default_lifetime")]
+    pub lifetime: f32,
+    /// The width of the trail at its head. The trail tapers off to nothing at its tail.
+    pub width: f32,
+    #[serde(with = "core::json::ColorDef")]
+    pub color: Color,
+    /// This is added to the position of the entity, when recording points
+    #[serde(
+        default,
+        with = "core::json::vec2_def",
+        skip_serializing_if = "Vec2::is_zero"
+    )]
+    pub offset: Vec2,
+}
+
+impl TrailMetadata {
+    const DEFAULT_LENGTH: usize = 12;
+    const DEFAULT_LIFETIME: f32 = 0.25;
+
+    pub fn default_length() -> usize {
+        Self::DEFAULT_LENGTH
+    }
+
+    pub fn default_lifetime() -> f32 {
+        Self::DEFAULT_LIFETIME
+    }
+}
+
+pub struct TrailParams {
+    pub length: usize,
+    pub lifetime: f32,
+    pub width: f32,
+    pub color: Color,
+    pub offset: Vec2,
+}
+
+impl Default for TrailParams {
+    fn default() -> Self {
+        TrailParams {
+            length: TrailMetadata::DEFAULT_LENGTH,
+            lifetime: TrailMetadata::DEFAULT_LIFETIME,
+            width: 8.0,
+            color: color::WHITE,
+            offset: Vec2::ZERO,
+        }
+    }
+}
+
+impl From<TrailMetadata> for TrailParams {
+    fn from(meta: TrailMetadata) -> Self {
+        TrailParams {
+            length: meta.length,
+            lifetime: meta.lifetime,
+            width: meta.width,
+            color: meta.color,
+            offset: meta.offset,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct TrailPoint {
+    position: Vec2,
+    age: f32,
+}
+
+/// A ribbon that follows an entity, drawn through the positions that the entity has occupied over
+/// the last few ticks. Points are recorded in the fixed update, so a trail will look the same on
+/// every client, regardless of frame rate.
+///
+/// A trail is drawn by an entity of its own, as a `Drawable`, so that it can outlive the entity it
+/// follows and fade out, after that has been despawned.
+pub struct Trail {
+    /// The entity that the trail follows. This is set to `None` once it has been despawned.
+    pub target: Option<Entity>,
+    pub length: usize,
+    pub lifetime: f32,
+    pub width: f32,
+    pub color: Color,
+    pub offset: Vec2,
+    pub is_deactivated: bool,
+    /// The recorded points, newest first
+    points: VecDeque<TrailPoint>,
+}
+
+impl Trail {
+    pub fn new(target: Entity, params: TrailParams) -> Self {
+        Trail {
+            target: Some(target),
+            length: params.length,
+            lifetime: params.lifetime,
+            width: params.width,
+            color: params.color,
+            offset: params.offset,
+            is_deactivated: false,
+            points: VecDeque::with_capacity(params.length),
+        }
+    }
+
+    /// Age the existing points by `dt`, removing any that have expired, and record a new point
+    pub fn record(&mut self, position: Vec2, dt: f32) {
+        for point in self.points.iter_mut() {
+            point.age += dt;
+        }
+
+        let lifetime = self.lifetime;
+        self.points.retain(|point| point.age <= lifetime);
+
+        if !self.is_deactivated {
+            self.points.push_front(TrailPoint {
+                position: position + self.offset,
+                age: 0.0,
+            });
+        }
+
+        self.points.truncate(self.length);
+    }
+}
+
+/// Spawn a trail that follows `target`
+pub fn spawn_trail(world: &mut World, target: Entity, params: TrailParams) -> Entity {
+    let position = world
+        .get::<Transform>(target)
+        .map(|transform| transform.position)
+        .unwrap_or_default();
+
+    let drawable = Drawable::new_trail(TRAIL_DRAW_ORDER, Trail::new(target, params));
+
+    world.spawn((Transform::from(position), drawable))
+}
+
+/// Record the positions of the entities that trails follow. Once the entity that a trail follows
+/// has been despawned, the trail is deactivated, and it is despawned once all its points expire.
+pub fn fixed_update_trails(world: &mut World) {
+    let dt = get_fixed_delta();
+
+    let trails = world
+        .query_mut::<&Drawable>()
+        .into_iter()
+        .filter_map(|(entity, drawable)| drawable.get_trail().map(|trail| (entity, trail.target)))
+        .collect::<Vec<_>>();
+
+    let mut to_despawn = Vec::new();
+
+    for (entity, target) in trails {
+        let target_position = target.and_then(|target| {
+            world
+                .get::<Transform>(target)
+                .map(|transform| transform.position)
+                .ok()
+        });
+
+        let mut transform = world.get_mut::<Transform>(entity).unwrap();
+        let mut drawable = world.get_mut::<Drawable>(entity).unwrap();
+        let trail = drawable.get_trail_mut().unwrap();
+
+        match target_position {
+            Some(position) => transform.position = position,
+            None => {
+                trail.target = None;
+                trail.is_deactivated = true;
+            }
+        }
+
+        trail.record(transform.position, dt);
+
+        if trail.is_deactivated && trail.points.is_empty() {
+            to_despawn.push(entity);
+        }
+    }
+
+    for entity in to_despawn {
+        let _ = world.despawn(entity);
+    }
+}
+
+pub fn draw_one_trail(trail: &Trail) {
+    let segment_cnt = trail.points.len().saturating_sub(1);
+
+    if segment_cnt == 0 {
+        return;
+    }
+
+    let get_width = |i: usize| trail.width * (1.0 - i as f32 / segment_cnt as f32) / 2.0;
+
+    for i in 0..segment_cnt {
+        let start = trail.points[i].position;
+        let end = trail.points[i + 1].position;
+
+        let normal = (end - start).normalize_or_zero().perp();
+
+        let start_offset = normal * get_width(i);
+        let end_offset = normal * get_width(i + 1);
+
+        let mut color = trail.color;
+        color.a *= 1.0 - i as f32 / segment_cnt as f32;
+
+        draw_triangle(
+            start + start_offset,
+            start - start_offset,
+            end + end_offset,
+            color,
+        );
+
+        draw_triangle(
+            start - start_offset,
+            end - end_offset,
+            end + end_offset,
+            color,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trail_points_expire() {
+        let mut world = World::new();
+        let target = world.spawn(());
+
+        let mut trail = Trail::new(
+            target,
+            TrailParams {
+                length: 3,
+                lifetime: 0.1,
+                ..Default::default()
+            },
+        );
+
+        for i in 0..5 {
+            trail.record(vec2(i as f32, 0.0), 0.025);
+        }
+
+        assert_eq!(trail.points.len(), 3);
+        assert_eq!(trail.points[0].position, vec2(4.0, 0.0));
+
+        trail.is_deactivated = true;
+
+        for _ in 0..5 {
+            trail.record(Vec2::ZERO, 0.025);
+        }
+
+        assert!(trail.points.is_empty());
+    }
+
+    #[test]
+    fn test_trail_outlives_target() {
+        let mut world = World::new();
+
+        let target = world.spawn((Transform::from(vec2(0.0, 0.0)),));
+        let trail = spawn_trail(
+            &mut world,
+            target,
+            TrailParams {
+                length: 4,
+                ..Default::default()
+            },
+        );
+
+        for i in 1..=3 {
+            world.get_mut::<Transform>(target).unwrap().position = vec2(i as f32, 0.0);
+            fixed_update_trails(&mut world);
+        }
+
+        {
+            let drawable = world.get::<Drawable>(trail).unwrap();
+            let points = &drawable.get_trail().unwrap().points;
+            assert_eq!(points.len(), 3);
+            assert_eq!(points[0].position, vec2(3.0, 0.0));
+        }
+
+        // The trail fades out after the target is gone, and is then despawned
+        world.despawn(target).unwrap();
+        fixed_update_trails(&mut world);
+
+        {
+            let drawable = world.get::<Drawable>(trail).unwrap();
+            assert!(drawable.get_trail().unwrap().is_deactivated);
+        }
+
+        for _ in 0..100 {
+            fixed_update_trails(&mut world);
+        }
+
+        assert!(!world.contains(trail));
+    }
+}
@@ -1,31 +1,43 @@
 use std::any::TypeId;
-use std::cmp::Ordering;
+use std::collections::HashMap;
 
 use macroquad::experimental::collections::storage;
 use macroquad::prelude::*;
 
 use core::error::{Error, ErrorKind, Result};
+use core::math::URect;
 
 use crate::editor::gui::windows::Window;
-use crate::map::{MapBackgroundLayer, MapObject, MapObjectKind};
+use crate::editor::tile_selection::get_moved_region;
+use crate::map::{
+    MapBackgroundLayer, MapGenerationParams, MapInfo, MapObject, MapObjectKind, MapTileCollision,
+};
 use crate::{
     map::{Map, MapLayer, MapLayerKind, MapTile, MapTileset},
     Resources,
 };
 
 /// These are all the actions available for the GUI and other sub-systems of the editor.
-/// If you need to perform multiple actions in one call, use the `Batch` variant.
+/// If you need to perform multiple actions in one call, use the `Batch` variant. The actions in a
+/// batch are applied in order and all map changes they make are added to the history as a single
+/// step, so that they are undone, and redone, together.
 #[derive(Debug, Clone)]
 pub enum EditorAction {
     Batch(Vec<EditorAction>),
     Undo,
     Redo,
+    /// Undo or redo until the specified number of steps in the history are applied
+    JumpToHistory(usize),
+    OpenHistoryWindow,
+    OpenMapCostWindow,
     SelectTool(Option<TypeId>),
     OpenBackgroundPropertiesWindow,
     UpdateBackground {
         color: Color,
         layers: Vec<MapBackgroundLayer>,
     },
+    OpenMapPropertiesWindow,
+    UpdateMapInfo(MapInfo),
     OpenCreateLayerWindow,
     OpenCreateTilesetWindow,
     OpenTilesetPropertiesWindow(String),
@@ -42,10 +54,17 @@ pub enum EditorAction {
         index: usize,
     },
     CloseWindow(TypeId),
+    /// Open a dialog that will apply `action` if the user confirms
+    OpenConfirmDialog {
+        body: Vec<String>,
+        action: Box<EditorAction>,
+    },
     SelectTile {
         id: u32,
         tileset_id: String,
     },
+    /// Set the tiles, of the selected tileset, that the tile placement tool picks from at random
+    SetRandomTiles(Vec<u32>),
     UpdateTileAttributes {
         index: usize,
         layer_id: String,
@@ -63,6 +82,20 @@ pub enum EditorAction {
         index: Option<usize>,
     },
     DeleteLayer(String),
+    ConvertLayer {
+        id: String,
+        new_kind: MapLayerKind,
+    },
+    /// Place a tile in every cell of a tile layer. The cells are looked up when this is applied,
+    /// so that it can be held by a confirm dialog while the map changes.
+    FillLayer {
+        layer_id: String,
+        tileset_id: String,
+        tile_id: u32,
+    },
+    /// Remove all tiles, or all objects, from a layer. Like `FillLayer`, the contents of the
+    /// layer are looked up when this is applied, and not when it is created.
+    ClearLayer(String),
     UpdateLayer {
         id: String,
         is_visible: bool,
@@ -83,6 +116,7 @@ pub enum EditorAction {
         id: String,
         texture_id: String,
         autotile_mask: Vec<bool>,
+        tile_collisions: HashMap<u32, MapTileCollision>,
     },
     SelectObject {
         index: usize,
@@ -94,6 +128,14 @@ pub enum EditorAction {
         position: Vec2,
         layer_id: String,
     },
+    /// Set the decoration that is placed by the decoration placement tool
+    SelectDecoration(String),
+    /// Create a decoration object. This will fail if `id` is not a valid decoration id.
+    CreateDecoration {
+        id: String,
+        position: Vec2,
+        layer_id: String,
+    },
     DeleteObject {
         index: usize,
         layer_id: String,
@@ -105,12 +147,30 @@ pub enum EditorAction {
         kind: MapObjectKind,
         position: Vec2,
     },
+    GroupObjects {
+        layer_id: String,
+        indices: Vec<usize>,
+    },
+    UngroupObjects {
+        layer_id: String,
+        group: u32,
+    },
+    DuplicateObjects {
+        layer_id: String,
+        indices: Vec<usize>,
+    },
     CreateSpawnPoint(Vec2),
     DeleteSpawnPoint(usize),
     MoveSpawnPoint {
         index: usize,
         position: Vec2,
     },
+    /// Change the order of a spawn point. The other spawn points are shifted, so that the order
+    /// stays contiguous.
+    ReorderSpawnPoint {
+        index: usize,
+        new_index: usize,
+    },
     PlaceTile {
         id: u32,
         layer_id: String,
@@ -121,6 +181,16 @@ pub enum EditorAction {
         layer_id: String,
         coords: UVec2,
     },
+    /// Move the tiles in `region` by `offset`, leaving the source empty. Tiles that are moved
+    /// outside of the map are discarded.
+    MoveTiles {
+        layer_id: String,
+        region: URect,
+        offset: IVec2,
+    },
+    /// Drop a physics test dummy at the specified world position, replacing any existing dummy
+    SpawnTestDummy(Vec2),
+    ClearTestDummy,
     CreateMap {
         name: String,
         description: Option<String>,
@@ -128,6 +198,11 @@ pub enum EditorAction {
         grid_size: UVec2,
     },
     OpenCreateMapWindow,
+    GenerateMap {
+        name: String,
+        params: MapGenerationParams,
+    },
+    OpenGenerateMapWindow,
     OpenMap(usize),
     OpenLoadMapWindow,
     SaveMap {
@@ -135,6 +210,18 @@ pub enum EditorAction {
         is_user_map: Option<bool>,
     },
     OpenSaveMapWindow,
+    /// Save a snapshot of the map to the specified quick-save slot
+    QuickSave(usize),
+    /// Replace the map with the snapshot in the specified quick-save slot
+    RestoreQuickSave(usize),
+    /// Copy the selected tiles, or the selected objects, to the clipboard file
+    CopyToClipboard,
+    /// Paste the contents of the clipboard file on the specified layer, with the top left at
+    /// `position`, if everything it refers to exists in the map and the resources
+    PasteFromClipboard {
+        layer_id: String,
+        position: Vec2,
+    },
     DeleteMap(usize),
     ExitToMainMenu,
     QuitToDesktop,
@@ -153,6 +240,120 @@ impl EditorAction {
     pub fn then(self, action: EditorAction) -> Self {
         Self::batch(&[self, action])
     }
+
+    pub fn confirm(body: &[&str], action: EditorAction) -> Self {
+        EditorAction::OpenConfirmDialog {
+            body: body.iter().map(|line| line.to_string()).collect(),
+            action: Box::new(action),
+        }
+    }
+
+    /// This will resolve actions that depend on the state of the map at the time they are
+    /// applied, like `FillLayer` and `ClearLayer`, to the batches that carry them out. Other
+    /// actions are returned as they are.
+    pub fn resolve(self, map: &Map) -> Self {
+        match self {
+            EditorAction::FillLayer {
+                layer_id,
+                tileset_id,
+                tile_id,
+            } => Self::fill_layer(map, &layer_id, &tileset_id, tile_id),
+            EditorAction::ClearLayer(layer_id) => Self::clear_layer(map, &layer_id),
+            action => action,
+        }
+    }
+
+    /// This will create a batch that places the specified tile in every cell of a tile layer
+    pub fn fill_layer(map: &Map, layer_id: &str, tileset_id: &str, tile_id: u32) -> Self {
+        let mut actions = Vec::new();
+
+        for y in 0..map.grid_size.y {
+            for x in 0..map.grid_size.x {
+                actions.push(EditorAction::PlaceTile {
+                    id: tile_id,
+                    layer_id: layer_id.to_string(),
+                    tileset_id: tileset_id.to_string(),
+                    coords: uvec2(x, y),
+                });
+            }
+        }
+
+        Self::Batch(actions)
+    }
+
+    /// This will create a batch that places a tile in every cell of `region`, row by row. The id
+    /// of each tile is returned by `get_tile_id`, so that a set of random tiles can be used.
+    pub fn fill_region<F>(
+        layer_id: &str,
+        tileset_id: &str,
+        region: URect,
+        mut get_tile_id: F,
+    ) -> Self
+    where
+        F: FnMut() -> u32,
+    {
+        let mut actions = Vec::new();
+
+        for y in region.top()..region.bottom() {
+            for x in region.left()..region.right() {
+                actions.push(EditorAction::PlaceTile {
+                    id: get_tile_id(),
+                    layer_id: layer_id.to_string(),
+                    tileset_id: tileset_id.to_string(),
+                    coords: uvec2(x, y),
+                });
+            }
+        }
+
+        Self::Batch(actions)
+    }
+
+    /// This will create a batch that removes all tiles, or all objects, from a layer
+    pub fn clear_layer(map: &Map, layer_id: &str) -> Self {
+        let layer = match map.layers.get(layer_id) {
+            Some(layer) => layer,
+            None => return Self::Batch(Vec::new()),
+        };
+
+        match layer.kind {
+            MapLayerKind::TileLayer => {
+                let actions = layer
+                    .tiles
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, tile)| tile.is_some())
+                    .map(|(i, _)| EditorAction::RemoveTile {
+                        layer_id: layer_id.to_string(),
+                        coords: uvec2(i as u32 % map.grid_size.x, i as u32 / map.grid_size.x),
+                    })
+                    .collect();
+
+                Self::Batch(actions)
+            }
+            MapLayerKind::ObjectLayer => {
+                let indices = (0..layer.objects.len()).collect::<Vec<_>>();
+                Self::delete_objects(layer_id, &indices)
+            }
+        }
+    }
+
+    /// This will create a batch that deletes all the objects at the specified indices. The objects
+    /// are deleted in descending order, so that the indices stay valid throughout the batch.
+    pub fn delete_objects(layer_id: &str, indices: &[usize]) -> Self {
+        let mut indices = indices.to_vec();
+        indices.sort_unstable_by(|a, b| b.cmp(a));
+        indices.dedup();
+
+        let actions = indices
+            .into_iter()
+            .map(|index| EditorAction::DeleteObject {
+                index,
+                layer_id: layer_id.to_string(),
+            })
+            .collect();
+
+        Self::Batch(actions)
+    }
 }
 
 /// All actions that modify map data should implement this trait
@@ -177,6 +378,90 @@ pub trait UndoableAction {
     fn is_redundant(&self, _map: &Map) -> bool {
         false
     }
+
+    /// A short description of the action, like "Create spawn point", that is shown in the history
+    fn description(&self) -> String;
+
+    /// A description of `cnt` actions like this one, applied as one step in the history, where
+    /// `cnt` is more than one. Implement this for actions that are commonly batched, like placing
+    /// tiles.
+    fn description_of(&self, cnt: usize) -> String {
+        format!("{} (x{})", self.description(), cnt)
+    }
+}
+
+/// This holds multiple actions that are applied, undone and redone as one step in the history.
+/// Actions are applied and redone in order and undone in reverse order. If one of the actions
+/// fails to apply, the ones already applied will be undone before the error is returned.
+pub struct BatchAction {
+    actions: Vec<Box<dyn UndoableAction>>,
+}
+
+impl BatchAction {
+    pub fn new(actions: Vec<Box<dyn UndoableAction>>) -> Self {
+        BatchAction { actions }
+    }
+
+    fn undo_actions(actions: &mut [Box<dyn UndoableAction>], map: &mut Map) -> Result<()> {
+        for action in actions.iter_mut().rev() {
+            action.undo(map)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl UndoableAction for BatchAction {
+    fn description(&self) -> String {
+        let first = match self.actions.first() {
+            Some(first) => first,
+            None => return "Empty batch".to_string(),
+        };
+
+        let description = first.description();
+
+        if self.actions.len() == 1 {
+            description
+        } else if self
+            .actions
+            .iter()
+            .all(|action| action.description() == description)
+        {
+            first.description_of(self.actions.len())
+        } else {
+            format!("{} actions", self.actions.len())
+        }
+    }
+
+    fn apply(&mut self, map: &mut Map) -> Result<()> {
+        for i in 0..self.actions.len() {
+            if let Err(err) = self.actions[i].apply(map) {
+                Self::undo_actions(&mut self.actions[..i], map)?;
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn undo(&mut self, map: &mut Map) -> Result<()> {
+        Self::undo_actions(&mut self.actions, map)
+    }
+
+    fn redo(&mut self, map: &mut Map) -> Result<()> {
+        for i in 0..self.actions.len() {
+            if let Err(err) = self.actions[i].redo(map) {
+                Self::undo_actions(&mut self.actions[..i], map)?;
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_redundant(&self, map: &Map) -> bool {
+        self.actions.iter().all(|action| action.is_redundant(map))
+    }
 }
 
 #[derive(Debug)]
@@ -199,6 +484,10 @@ impl UpdateBackgroundAction {
 }
 
 impl UndoableAction for UpdateBackgroundAction {
+    fn description(&self) -> String {
+        "Update background".to_string()
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         self.old_color = Some(map.background_color);
 
@@ -228,6 +517,49 @@ impl UndoableAction for UpdateBackgroundAction {
     }
 }
 
+#[derive(Debug)]
+pub struct UpdateMapInfoAction {
+    info: MapInfo,
+    old_info: Option<MapInfo>,
+}
+
+impl UpdateMapInfoAction {
+    pub fn new(info: MapInfo) -> Self {
+        UpdateMapInfoAction {
+            info,
+            old_info: None,
+        }
+    }
+}
+
+impl UndoableAction for UpdateMapInfoAction {
+    fn description(&self) -> String {
+        "Update map properties".to_string()
+    }
+
+    fn apply(&mut self, map: &mut Map) -> Result<()> {
+        self.old_info = Some(map.info.clone());
+
+        map.info = self.info.clone();
+
+        Ok(())
+    }
+
+    fn undo(&mut self, map: &mut Map) -> Result<()> {
+        if let Some(info) = self.old_info.take() {
+            map.info = info;
+        } else {
+            return Err(Error::new_const(ErrorKind::EditorAction, &"UpdateMapInfoAction (Undo): No old map info was found. Undo was probably called on an action that was never applied"));
+        }
+
+        Ok(())
+    }
+
+    fn is_redundant(&self, map: &Map) -> bool {
+        map.info == self.info
+    }
+}
+
 #[derive(Debug)]
 pub struct SetLayerDrawOrderIndexAction {
     id: String,
@@ -246,6 +578,10 @@ impl SetLayerDrawOrderIndexAction {
 }
 
 impl UndoableAction for SetLayerDrawOrderIndexAction {
+    fn description(&self) -> String {
+        format!("Reorder layer '{}'", self.id)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         for i in 0..map.draw_order.len() {
             let id = map.draw_order.get(i).unwrap();
@@ -320,6 +656,10 @@ impl UpdateTileAttributesAction {
 }
 
 impl UndoableAction for UpdateTileAttributesAction {
+    fn description(&self) -> String {
+        "Update tile attributes".to_string()
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         if let Some(layer) = map.layers.get_mut(&self.layer_id) {
             if let Some(Some(tile)) = layer.tiles.get_mut(self.index) {
@@ -402,16 +742,16 @@ impl CreateLayerAction {
 }
 
 impl UndoableAction for CreateLayerAction {
+    fn description(&self) -> String {
+        format!("Create layer '{}'", self.id)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         if map.layers.contains_key(&self.id) {
-            let len = map.draw_order.len();
-            for i in 0..len {
-                let id = map.draw_order.get(i).unwrap();
-                if id == &self.id {
-                    map.draw_order.remove(i);
-                    break;
-                }
-            }
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"CreateLayerAction: A layer with the specified id already exists",
+            ));
         }
 
         let layer = MapLayer::new(&self.id, self.kind, self.has_collision, map.grid_size);
@@ -438,14 +778,7 @@ impl UndoableAction for CreateLayerAction {
             ));
         }
 
-        if let Some(i) = self.index {
-            let id = map.draw_order.remove(i);
-            if id != self.id {
-                return Err(Error::new_const(ErrorKind::EditorAction, &"CreateLayerAction (Undo): There is a mismatch between the actions layer id and the one found at the actions draw order index"));
-            }
-        } else {
-            map.draw_order.retain(|id| id != &self.id);
-        }
+        map.draw_order.retain(|id| id != &self.id);
 
         Ok(())
     }
@@ -469,6 +802,10 @@ impl DeleteLayerAction {
 }
 
 impl UndoableAction for DeleteLayerAction {
+    fn description(&self) -> String {
+        format!("Delete layer '{}'", self.id)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         if let Some(layer) = map.layers.remove(&self.id) {
             self.layer = Some(layer);
@@ -519,6 +856,76 @@ impl UndoableAction for DeleteLayerAction {
     }
 }
 
+#[derive(Debug)]
+pub struct ConvertLayerAction {
+    id: String,
+    kind: MapLayerKind,
+    old_layer: Option<MapLayer>,
+}
+
+impl ConvertLayerAction {
+    pub fn new(id: String, kind: MapLayerKind) -> Self {
+        ConvertLayerAction {
+            id,
+            kind,
+            old_layer: None,
+        }
+    }
+}
+
+impl UndoableAction for ConvertLayerAction {
+    fn description(&self) -> String {
+        format!("Convert layer '{}'", self.id)
+    }
+
+    fn apply(&mut self, map: &mut Map) -> Result<()> {
+        if let Some(layer) = map.layers.get_mut(&self.id) {
+            if !layer.can_convert_to(self.kind) {
+                return Err(Error::new_const(ErrorKind::EditorAction, &"ConvertLayerAction: The specified layer can not be converted without losing data. Only empty layers can be converted"));
+            }
+
+            // Collision is only kept if the new kind of layer can have it
+            let mut new_layer =
+                MapLayer::new(&self.id, self.kind, layer.has_collision, map.grid_size);
+            new_layer.is_visible = layer.is_visible;
+            new_layer.properties = layer.properties.clone();
+
+            self.old_layer = Some(std::mem::replace(layer, new_layer));
+        } else {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"ConvertLayerAction: The specified layer does not exist",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn undo(&mut self, map: &mut Map) -> Result<()> {
+        if let Some(old_layer) = self.old_layer.take() {
+            if let Some(layer) = map.layers.get_mut(&self.id) {
+                *layer = old_layer;
+            } else {
+                return Err(Error::new_const(
+                    ErrorKind::EditorAction,
+                    &"ConvertLayerAction (Undo): The specified layer does not exist",
+                ));
+            }
+        } else {
+            return Err(Error::new_const(ErrorKind::EditorAction, &"ConvertLayerAction (Undo): No old layer stored in action. Undo was probably called on an action that was never applied"));
+        }
+
+        Ok(())
+    }
+
+    fn is_redundant(&self, map: &Map) -> bool {
+        map.layers
+            .get(&self.id)
+            .map(|layer| layer.kind == self.kind)
+            .unwrap_or(false)
+    }
+}
+
 #[derive(Debug)]
 pub struct UpdateLayerAction {
     id: String,
@@ -537,6 +944,14 @@ impl UpdateLayerAction {
 }
 
 impl UndoableAction for UpdateLayerAction {
+    fn description(&self) -> String {
+        if self.is_visible {
+            format!("Show layer '{}'", self.id)
+        } else {
+            format!("Hide layer '{}'", self.id)
+        }
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         if let Some(layer) = map.layers.get_mut(&self.id) {
             self.old_is_visible = Some(layer.is_visible);
@@ -595,6 +1010,10 @@ impl ImportAction {
 }
 
 impl UndoableAction for ImportAction {
+    fn description(&self) -> String {
+        "Import".to_string()
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         for tileset in &self.tilesets {
             let mut first_tile_id = 1;
@@ -616,6 +1035,8 @@ impl UndoableAction for ImportAction {
                 tile_subdivisions: tileset.tile_subdivisions,
                 autotile_mask: tileset.autotile_mask.clone(),
                 tile_attributes: tileset.tile_attributes.clone(),
+                tile_collisions: tileset.tile_collisions.clone(),
+                tile_animations: tileset.tile_animations.clone(),
                 properties: tileset.properties.clone(),
                 bitmasks: None,
             };
@@ -666,6 +1087,10 @@ impl CreateTilesetAction {
 }
 
 impl UndoableAction for CreateTilesetAction {
+    fn description(&self) -> String {
+        format!("Create tileset '{}'", self.id)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         let resources = storage::get::<Resources>();
         if let Some(texture_entry) = resources.textures.get(&self.texture_id).cloned() {
@@ -682,7 +1107,7 @@ impl UndoableAction for CreateTilesetAction {
                 }
             }
 
-            let tileset = MapTileset::new(
+            let mut tileset = MapTileset::new(
                 &self.id,
                 &self.texture_id,
                 texture_size,
@@ -690,6 +1115,14 @@ impl UndoableAction for CreateTilesetAction {
                 first_tile_id,
             );
 
+            if let Some(meta) = resources.tilesets.get(&self.texture_id) {
+                tileset.tile_collisions = meta
+                    .tile_collisions
+                    .iter()
+                    .map(|(&tile_id, &collision)| (tile_id, collision))
+                    .collect();
+            }
+
             map.tilesets.insert(self.id.clone(), tileset);
         } else {
             return Err(Error::new_const(
@@ -723,6 +1156,10 @@ impl DeleteTilesetAction {
 }
 
 impl UndoableAction for DeleteTilesetAction {
+    fn description(&self) -> String {
+        format!("Delete tileset '{}'", self.id)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         if let Some(tileset) = map.tilesets.remove(&self.id) {
             self.tileset = Some(tileset);
@@ -754,21 +1191,34 @@ pub struct UpdateTilesetAction {
     old_texture_id: Option<String>,
     autotile_mask: Vec<bool>,
     old_autotile_mask: Option<Vec<bool>>,
+    tile_collisions: HashMap<u32, MapTileCollision>,
+    old_tile_collisions: Option<HashMap<u32, MapTileCollision>>,
 }
 
 impl UpdateTilesetAction {
-    pub fn new(id: String, texture_id: String, autotile_mask: Vec<bool>) -> Self {
+    pub fn new(
+        id: String,
+        texture_id: String,
+        autotile_mask: Vec<bool>,
+        tile_collisions: HashMap<u32, MapTileCollision>,
+    ) -> Self {
         UpdateTilesetAction {
             id,
             texture_id,
             old_texture_id: None,
             autotile_mask,
             old_autotile_mask: None,
+            tile_collisions,
+            old_tile_collisions: None,
         }
     }
 }
 
 impl UndoableAction for UpdateTilesetAction {
+    fn description(&self) -> String {
+        format!("Update tileset '{}'", self.id)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         if let Some(tileset) = map.tilesets.get_mut(&self.id) {
             self.old_texture_id = Some(tileset.texture_id.clone());
@@ -781,6 +1231,9 @@ impl UndoableAction for UpdateTilesetAction {
             self.old_autotile_mask = Some(tileset.autotile_mask.clone());
             tileset.autotile_mask = self.autotile_mask.clone();
 
+            self.old_tile_collisions = Some(tileset.tile_collisions.clone());
+            tileset.tile_collisions = self.tile_collisions.clone();
+
             tileset.bitmasks = tileset.get_bitmasks();
         } else {
             return Err(Error::new_const(
@@ -811,6 +1264,12 @@ impl UndoableAction for UpdateTilesetAction {
                 return Err(Error::new_const(ErrorKind::EditorAction, &"UpdateTilesetAction (Undo): No old autotile mask stored in action. Undo was probably called on an action that was never applied"));
             }
 
+            if let Some(old_tile_collisions) = self.old_tile_collisions.take() {
+                tileset.tile_collisions = old_tile_collisions;
+            } else {
+                return Err(Error::new_const(ErrorKind::EditorAction, &"UpdateTilesetAction (Undo): No old tile collisions stored in action. Undo was probably called on an action that was never applied"));
+            }
+
             tileset.bitmasks = tileset.get_bitmasks();
         } else {
             return Err(Error::new_const(
@@ -843,6 +1302,10 @@ impl CreateObjectAction {
 }
 
 impl UndoableAction for CreateObjectAction {
+    fn description(&self) -> String {
+        format!("Create object '{}'", self.id)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         if let Some(layer) = map.layers.get_mut(&self.layer_id) {
             let object = MapObject::new(&self.id, self.kind, self.position);
@@ -860,6 +1323,13 @@ impl UndoableAction for CreateObjectAction {
 
     fn undo(&mut self, map: &mut Map) -> Result<()> {
         if let Some(layer) = map.layers.get_mut(&self.layer_id) {
+            if layer.objects.is_empty() {
+                return Err(Error::new_const(
+                    ErrorKind::EditorAction,
+                    &"CreateObjectAction (Undo): The specified layer has no objects",
+                ));
+            }
+
             layer.objects.remove(0);
         } else {
             return Err(Error::new_const(
@@ -890,8 +1360,23 @@ impl DeleteObjectAction {
 }
 
 impl UndoableAction for DeleteObjectAction {
+    fn description(&self) -> String {
+        "Delete object".to_string()
+    }
+
+    fn description_of(&self, cnt: usize) -> String {
+        format!("Delete {} objects", cnt)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         if let Some(layer) = map.layers.get_mut(&self.layer_id) {
+            if self.index >= layer.objects.len() {
+                return Err(Error::new_const(
+                    ErrorKind::EditorAction,
+                    &"DeleteObjectAction: The specified object index does not exist",
+                ));
+            }
+
             let object = layer.objects.remove(self.index);
             self.object = Some(object);
         } else {
@@ -952,6 +1437,10 @@ impl UpdateObjectAction {
 }
 
 impl UndoableAction for UpdateObjectAction {
+    fn description(&self) -> String {
+        format!("Update object '{}'", self.id)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         if let Some(layer) = map.layers.get_mut(&self.layer_id) {
             if let Some(object) = layer.objects.get_mut(self.index) {
@@ -979,7 +1468,14 @@ impl UndoableAction for UpdateObjectAction {
     fn undo(&mut self, map: &mut Map) -> Result<()> {
         if let Some(layer) = map.layers.get_mut(&self.layer_id) {
             if let Some(object) = self.object.take() {
-                layer.objects[self.index] = object;
+                if let Some(current) = layer.objects.get_mut(self.index) {
+                    *current = object;
+                } else {
+                    return Err(Error::new_const(
+                        ErrorKind::EditorAction,
+                        &"UpdateObjectAction (Undo): The specified object index does not exist",
+                    ));
+                }
             } else {
                 return Err(Error::new_const(ErrorKind::EditorAction, &"UpdateObjectAction: No object found on action. Undo was probably called on an action that was never applied"));
             }
@@ -995,32 +1491,315 @@ impl UndoableAction for UpdateObjectAction {
 }
 
 #[derive(Debug)]
-pub struct CreateSpawnPointAction {
-    position: Vec2,
+pub struct GroupObjectsAction {
+    layer_id: String,
+    indices: Vec<usize>,
+    old_groups: Option<Vec<Option<u32>>>,
 }
 
-impl CreateSpawnPointAction {
-    pub fn new(position: Vec2) -> Self {
-        CreateSpawnPointAction { position }
+impl GroupObjectsAction {
+    pub fn new(layer_id: String, mut indices: Vec<usize>) -> Self {
+        indices.sort_unstable();
+        indices.dedup();
+
+        GroupObjectsAction {
+            layer_id,
+            indices,
+            old_groups: None,
+        }
     }
 }
 
-impl UndoableAction for CreateSpawnPointAction {
+impl UndoableAction for GroupObjectsAction {
+    fn description(&self) -> String {
+        format!("Group {} objects", self.indices.len())
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
-        map.spawn_points.push(self.position);
+        if let Some(layer) = map.layers.get_mut(&self.layer_id) {
+            if self.indices.iter().any(|&i| i >= layer.objects.len()) {
+                return Err(Error::new_const(
+                    ErrorKind::EditorAction,
+                    &"GroupObjectsAction: One or more of the specified object indices does not exist",
+                ));
+            }
 
-        Ok(())
-    }
+            let group = layer.get_next_group_id();
 
-    fn undo(&mut self, map: &mut Map) -> Result<()> {
-        map.spawn_points.pop();
+            let mut old_groups = Vec::new();
+
+            for &i in &self.indices {
+                let object = &mut layer.objects[i];
+
+                old_groups.push(object.group);
+                object.group = Some(group);
+            }
+
+            self.old_groups = Some(old_groups);
+        } else {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"GroupObjectsAction: The specified layer does not exist",
+            ));
+        }
 
         Ok(())
     }
-}
 
-#[derive(Debug)]
-pub struct DeleteSpawnPointAction {
+    fn undo(&mut self, map: &mut Map) -> Result<()> {
+        if let Some(layer) = map.layers.get_mut(&self.layer_id) {
+            if let Some(old_groups) = self.old_groups.take() {
+                for (&i, group) in self.indices.iter().zip(old_groups) {
+                    if let Some(object) = layer.objects.get_mut(i) {
+                        object.group = group;
+                    }
+                }
+            } else {
+                return Err(Error::new_const(ErrorKind::EditorAction, &"GroupObjectsAction (Undo): No old groups stored in action. Undo was probably called on an action that was never applied"));
+            }
+        } else {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"GroupObjectsAction (Undo): The specified layer does not exist",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn is_redundant(&self, map: &Map) -> bool {
+        if self.indices.len() < 2 {
+            return true;
+        }
+
+        if let Some(layer) = map.layers.get(&self.layer_id) {
+            if let Some(group) = layer
+                .objects
+                .get(self.indices[0])
+                .and_then(|object| object.group)
+            {
+                return layer.get_group_members(group) == self.indices;
+            }
+        }
+
+        false
+    }
+}
+
+#[derive(Debug)]
+pub struct UngroupObjectsAction {
+    layer_id: String,
+    group: u32,
+    indices: Option<Vec<usize>>,
+}
+
+impl UngroupObjectsAction {
+    pub fn new(layer_id: String, group: u32) -> Self {
+        UngroupObjectsAction {
+            layer_id,
+            group,
+            indices: None,
+        }
+    }
+}
+
+impl UndoableAction for UngroupObjectsAction {
+    fn description(&self) -> String {
+        "Ungroup objects".to_string()
+    }
+
+    fn apply(&mut self, map: &mut Map) -> Result<()> {
+        if let Some(layer) = map.layers.get_mut(&self.layer_id) {
+            let indices = layer.get_group_members(self.group);
+
+            for &i in &indices {
+                layer.objects[i].group = None;
+            }
+
+            self.indices = Some(indices);
+        } else {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"UngroupObjectsAction: The specified layer does not exist",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn undo(&mut self, map: &mut Map) -> Result<()> {
+        if let Some(layer) = map.layers.get_mut(&self.layer_id) {
+            if let Some(indices) = self.indices.take() {
+                for i in indices {
+                    if let Some(object) = layer.objects.get_mut(i) {
+                        object.group = Some(self.group);
+                    }
+                }
+            } else {
+                return Err(Error::new_const(ErrorKind::EditorAction, &"UngroupObjectsAction (Undo): No indices stored in action. Undo was probably called on an action that was never applied"));
+            }
+        } else {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"UngroupObjectsAction (Undo): The specified layer does not exist",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn is_redundant(&self, map: &Map) -> bool {
+        if let Some(layer) = map.layers.get(&self.layer_id) {
+            return layer.get_group_members(self.group).is_empty();
+        }
+
+        false
+    }
+}
+
+#[derive(Debug)]
+pub struct DuplicateObjectsAction {
+    layer_id: String,
+    indices: Vec<usize>,
+    offset: Vec2,
+    duplicate_cnt: Option<usize>,
+}
+
+impl DuplicateObjectsAction {
+    pub fn new(layer_id: String, mut indices: Vec<usize>, offset: Vec2) -> Self {
+        indices.sort_unstable();
+        indices.dedup();
+
+        DuplicateObjectsAction {
+            layer_id,
+            indices,
+            offset,
+            duplicate_cnt: None,
+        }
+    }
+}
+
+impl UndoableAction for DuplicateObjectsAction {
+    fn description(&self) -> String {
+        if self.indices.len() == 1 {
+            "Duplicate object".to_string()
+        } else {
+            format!("Duplicate {} objects", self.indices.len())
+        }
+    }
+
+    fn apply(&mut self, map: &mut Map) -> Result<()> {
+        if let Some(layer) = map.layers.get_mut(&self.layer_id) {
+            let mut next_group = layer.get_next_group_id();
+            let mut new_groups = HashMap::new();
+
+            let mut duplicates = Vec::new();
+
+            for &i in &self.indices {
+                if let Some(object) = layer.objects.get(i) {
+                    let mut duplicate = object.clone();
+                    duplicate.position += self.offset;
+
+                    if let Some(group) = object.group {
+                        let new_group = match new_groups.get(&group) {
+                            Some(&new_group) => new_group,
+                            None => {
+                                let new_group = next_group;
+                                next_group += 1;
+
+                                new_groups.insert(group, new_group);
+
+                                new_group
+                            }
+                        };
+
+                        duplicate.group = Some(new_group);
+                    }
+
+                    duplicates.push(duplicate);
+                } else {
+                    return Err(Error::new_const(
+                        ErrorKind::EditorAction,
+                        &"DuplicateObjectsAction: The specified object index does not exist",
+                    ));
+                }
+            }
+
+            self.duplicate_cnt = Some(duplicates.len());
+
+            layer.objects.splice(0..0, duplicates);
+        } else {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"DuplicateObjectsAction: The specified layer does not exist",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn undo(&mut self, map: &mut Map) -> Result<()> {
+        if let Some(layer) = map.layers.get_mut(&self.layer_id) {
+            if let Some(duplicate_cnt) = self.duplicate_cnt.take() {
+                layer.objects.drain(0..duplicate_cnt);
+            } else {
+                return Err(Error::new_const(ErrorKind::EditorAction, &"DuplicateObjectsAction (Undo): No duplicate count stored in action. Undo was probably called on an action that was never applied"));
+            }
+        } else {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"DuplicateObjectsAction (Undo): The specified layer does not exist",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn is_redundant(&self, _map: &Map) -> bool {
+        self.indices.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub struct CreateSpawnPointAction {
+    position: Vec2,
+}
+
+impl CreateSpawnPointAction {
+    pub fn new(position: Vec2) -> Self {
+        CreateSpawnPointAction { position }
+    }
+}
+
+impl UndoableAction for CreateSpawnPointAction {
+    fn description(&self) -> String {
+        "Create spawn point".to_string()
+    }
+
+    fn description_of(&self, cnt: usize) -> String {
+        format!("Create {} spawn points", cnt)
+    }
+
+    fn apply(&mut self, map: &mut Map) -> Result<()> {
+        map.spawn_points.push(self.position);
+
+        Ok(())
+    }
+
+    fn undo(&mut self, map: &mut Map) -> Result<()> {
+        if map.spawn_points.pop().is_none() {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"CreateSpawnPointAction (Undo): There are no spawn points",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct DeleteSpawnPointAction {
     index: usize,
     spawn_point: Option<Vec2>,
 }
@@ -1035,7 +1814,22 @@ impl DeleteSpawnPointAction {
 }
 
 impl UndoableAction for DeleteSpawnPointAction {
+    fn description(&self) -> String {
+        "Delete spawn point".to_string()
+    }
+
+    fn description_of(&self, cnt: usize) -> String {
+        format!("Delete {} spawn points", cnt)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
+        if self.index >= map.spawn_points.len() {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"DeleteSpawnPointAction: Index out of bounds",
+            ));
+        }
+
         let spawn_point = map.spawn_points.remove(self.index);
         self.spawn_point = Some(spawn_point);
 
@@ -1075,35 +1869,90 @@ impl MoveSpawnPointAction {
 }
 
 impl UndoableAction for MoveSpawnPointAction {
+    fn description(&self) -> String {
+        "Move spawn point".to_string()
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
-        let old_position = map.spawn_points.remove(self.index);
-        self.old_position = Some(old_position);
+        if let Some(spawn_point) = map.spawn_points.get_mut(self.index) {
+            self.old_position = Some(*spawn_point);
+            *spawn_point = self.position;
+        } else {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"MoveSpawnPointAction: Index out of bounds",
+            ));
+        }
+
+        Ok(())
+    }
 
-        match map.spawn_points.len().cmp(&self.index) {
-            Ordering::Equal => map.spawn_points.push(self.position),
-            Ordering::Greater => map.spawn_points.insert(self.index, self.position),
-            _ => {
+    fn undo(&mut self, map: &mut Map) -> Result<()> {
+        if let Some(old_position) = self.old_position.take() {
+            if let Some(spawn_point) = map.spawn_points.get_mut(self.index) {
+                *spawn_point = old_position;
+            } else {
                 return Err(Error::new_const(
                     ErrorKind::EditorAction,
-                    &"MoveSpawnPointAction: Index out of bounds",
-                ))
+                    &"MoveSpawnPointAction (Undo): Index out of bounds",
+                ));
             }
+        } else {
+            return Err(Error::new_const(ErrorKind::EditorAction, &"MoveSpawnPointAction (Undo): No old position saved in action. Undo was probably called on an action that was never applied"));
         }
 
         Ok(())
     }
 
-    fn undo(&mut self, map: &mut Map) -> Result<()> {
-        if let Some(old_position) = self.old_position {
-            map.spawn_points[self.index] = old_position;
-        } else {
-            return Err(Error::new_const(ErrorKind::EditorAction, &"MoveSpawnPointAction (Undo): No old position saved in action. Undo was probably called on an action that was never applied"));
+    fn is_redundant(&self, map: &Map) -> bool {
+        map.spawn_points.get(self.index) == Some(&self.position)
+    }
+}
+
+#[derive(Debug)]
+pub struct ReorderSpawnPointAction {
+    index: usize,
+    new_index: usize,
+}
+
+impl ReorderSpawnPointAction {
+    pub fn new(index: usize, new_index: usize) -> Self {
+        ReorderSpawnPointAction { index, new_index }
+    }
+
+    fn reorder(map: &mut Map, from: usize, to: usize) -> Result<()> {
+        if from >= map.spawn_points.len() || to >= map.spawn_points.len() {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"ReorderSpawnPointAction: Index out of bounds",
+            ));
         }
 
+        let spawn_point = map.spawn_points.remove(from);
+        map.spawn_points.insert(to, spawn_point);
+
         Ok(())
     }
 }
 
+impl UndoableAction for ReorderSpawnPointAction {
+    fn description(&self) -> String {
+        "Reorder spawn point".to_string()
+    }
+
+    fn apply(&mut self, map: &mut Map) -> Result<()> {
+        Self::reorder(map, self.index, self.new_index)
+    }
+
+    fn undo(&mut self, map: &mut Map) -> Result<()> {
+        Self::reorder(map, self.new_index, self.index)
+    }
+
+    fn is_redundant(&self, _map: &Map) -> bool {
+        self.index == self.new_index
+    }
+}
+
 #[derive(Debug)]
 pub struct PlaceTileAction {
     id: u32,
@@ -1126,6 +1975,14 @@ impl PlaceTileAction {
 }
 
 impl UndoableAction for PlaceTileAction {
+    fn description(&self) -> String {
+        "Place tile".to_string()
+    }
+
+    fn description_of(&self, cnt: usize) -> String {
+        format!("Place {} tiles", cnt)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         if let Some(tileset) = map.tilesets.get(&self.tileset_id) {
             let texture_id = tileset.texture_id.clone();
@@ -1228,6 +2085,14 @@ impl RemoveTileAction {
 }
 
 impl UndoableAction for RemoveTileAction {
+    fn description(&self) -> String {
+        "Remove tile".to_string()
+    }
+
+    fn description_of(&self, cnt: usize) -> String {
+        format!("Remove {} tiles", cnt)
+    }
+
     fn apply(&mut self, map: &mut Map) -> Result<()> {
         let i = map.to_index(self.coords);
 
@@ -1297,3 +2162,517 @@ impl UndoableAction for RemoveTileAction {
         false
     }
 }
+
+#[derive(Debug)]
+pub struct MoveTilesAction {
+    layer_id: String,
+    region: URect,
+    offset: IVec2,
+    /// The previous state of all the cells that were changed, by index
+    replaced_tiles: Vec<(usize, Option<MapTile>)>,
+}
+
+impl MoveTilesAction {
+    pub fn new(layer_id: String, region: URect, offset: IVec2) -> Self {
+        MoveTilesAction {
+            layer_id,
+            region,
+            offset,
+            replaced_tiles: Vec::new(),
+        }
+    }
+}
+
+impl UndoableAction for MoveTilesAction {
+    fn description(&self) -> String {
+        "Move tiles".to_string()
+    }
+
+    fn apply(&mut self, map: &mut Map) -> Result<()> {
+        let source = match get_moved_region(self.region, IVec2::ZERO, map.grid_size) {
+            Some(source) => source,
+            None => {
+                return Err(Error::new_const(
+                    ErrorKind::EditorAction,
+                    &"MoveTilesAction: The specified region is outside of the map",
+                ))
+            }
+        };
+
+        let grid_size = map.grid_size;
+
+        let to_index = |x: u32, y: u32| (y * grid_size.x + x) as usize;
+
+        if let Some(layer) = map.layers.get_mut(&self.layer_id) {
+            if layer.kind != MapLayerKind::TileLayer {
+                return Err(Error::new_const(
+                    ErrorKind::EditorAction,
+                    &"MoveTilesAction: The specified layer is not a tile layer",
+                ));
+            }
+
+            self.replaced_tiles.clear();
+
+            let mut moved_tiles = Vec::new();
+
+            for y in source.top()..source.bottom() {
+                for x in source.left()..source.right() {
+                    let i = to_index(x, y);
+
+                    if let Some(tile) = layer.tiles[i].take() {
+                        self.replaced_tiles.push((i, Some(tile.clone())));
+
+                        let coords = ivec2(x as i32, y as i32) + self.offset;
+                        if coords.x >= 0
+                            && coords.y >= 0
+                            && (coords.x as u32) < grid_size.x
+                            && (coords.y as u32) < grid_size.y
+                        {
+                            moved_tiles.push((to_index(coords.x as u32, coords.y as u32), tile));
+                        }
+                    }
+                }
+            }
+
+            for (i, tile) in moved_tiles {
+                let replaced_tile = layer.tiles[i].replace(tile);
+
+                // Cells that were emptied above already hold their previous state
+                if !self.replaced_tiles.iter().any(|&(other, _)| other == i) {
+                    self.replaced_tiles.push((i, replaced_tile));
+                }
+            }
+        } else {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"MoveTilesAction: The specified layer does not exist",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn undo(&mut self, map: &mut Map) -> Result<()> {
+        if let Some(layer) = map.layers.get_mut(&self.layer_id) {
+            for (i, tile) in self.replaced_tiles.drain(..) {
+                if let Some(entry) = layer.tiles.get_mut(i) {
+                    *entry = tile;
+                }
+            }
+        } else {
+            return Err(Error::new_const(
+                ErrorKind::EditorAction,
+                &"MoveTilesAction (Undo): The specified layer does not exist",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn is_redundant(&self, map: &Map) -> bool {
+        if self.offset == IVec2::ZERO {
+            return true;
+        }
+
+        if let Some(layer) = map.layers.get(&self.layer_id) {
+            for y in self.region.top()..self.region.bottom() {
+                for x in self.region.left()..self.region.right() {
+                    if let Some(Some(_)) = layer.tiles.get(map.to_index(uvec2(x, y))) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::editor::history::EditorHistory;
+
+    use super::*;
+
+    const LAYER_ID: &str = "objects";
+
+    fn create_map() -> Map {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(10, 10));
+
+        let layer = MapLayer::new(LAYER_ID, MapLayerKind::ObjectLayer, false, map.grid_size);
+        map.layers.insert(LAYER_ID.to_string(), layer);
+        map.draw_order.push(LAYER_ID.to_string());
+
+        map
+    }
+
+    fn get_object_ids(map: &Map) -> Vec<String> {
+        map.layers[LAYER_ID]
+            .objects
+            .iter()
+            .map(|object| object.id.clone())
+            .collect()
+    }
+
+    fn create_object(history: &mut EditorHistory, map: &mut Map, id: &str, position: Vec2) {
+        let action = CreateObjectAction::new(
+            id.to_string(),
+            MapObjectKind::Item,
+            position,
+            LAYER_ID.to_string(),
+        );
+
+        history.apply(Box::new(action), map).unwrap();
+    }
+
+    #[test]
+    fn test_undo_create_and_delete_spawn_point() {
+        let mut map = create_map();
+        let mut history = EditorHistory::default();
+
+        let action = CreateSpawnPointAction::new(vec2(1.0, 2.0));
+        history.apply(Box::new(action), &mut map).unwrap();
+
+        let action = CreateSpawnPointAction::new(vec2(3.0, 4.0));
+        history.apply(Box::new(action), &mut map).unwrap();
+
+        let action = DeleteSpawnPointAction::new(0);
+        history.apply(Box::new(action), &mut map).unwrap();
+        assert_eq!(map.spawn_points, vec![vec2(3.0, 4.0)]);
+
+        history.undo(&mut map).unwrap();
+        assert_eq!(map.spawn_points, vec![vec2(1.0, 2.0), vec2(3.0, 4.0)]);
+
+        history.undo(&mut map).unwrap();
+        history.undo(&mut map).unwrap();
+        assert!(map.spawn_points.is_empty());
+
+        history.redo(&mut map).unwrap();
+        history.redo(&mut map).unwrap();
+        history.redo(&mut map).unwrap();
+        assert_eq!(map.spawn_points, vec![vec2(3.0, 4.0)]);
+    }
+
+    #[test]
+    fn test_undo_move_spawn_point() {
+        let mut map = create_map();
+        let mut history = EditorHistory::default();
+
+        map.spawn_points = vec![vec2(1.0, 2.0), vec2(3.0, 4.0)];
+
+        let action = MoveSpawnPointAction::new(0, vec2(5.0, 6.0));
+        history.apply(Box::new(action), &mut map).unwrap();
+        assert_eq!(map.spawn_points, vec![vec2(5.0, 6.0), vec2(3.0, 4.0)]);
+
+        history.undo(&mut map).unwrap();
+        assert_eq!(map.spawn_points, vec![vec2(1.0, 2.0), vec2(3.0, 4.0)]);
+    }
+
+    #[test]
+    fn test_undo_reorder_spawn_point() {
+        let mut map = create_map();
+        let mut history = EditorHistory::default();
+
+        map.spawn_points = vec![vec2(1.0, 0.0), vec2(2.0, 0.0), vec2(3.0, 0.0)];
+
+        let action = ReorderSpawnPointAction::new(2, 0);
+        history.apply(Box::new(action), &mut map).unwrap();
+        assert_eq!(
+            map.spawn_points,
+            vec![vec2(3.0, 0.0), vec2(1.0, 0.0), vec2(2.0, 0.0)]
+        );
+
+        let action = DeleteSpawnPointAction::new(1);
+        history.apply(Box::new(action), &mut map).unwrap();
+        assert_eq!(map.spawn_points, vec![vec2(3.0, 0.0), vec2(2.0, 0.0)]);
+
+        history.undo(&mut map).unwrap();
+        history.undo(&mut map).unwrap();
+        assert_eq!(
+            map.spawn_points,
+            vec![vec2(1.0, 0.0), vec2(2.0, 0.0), vec2(3.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_undo_create_delete_and_move_object() {
+        let mut map = create_map();
+        let mut history = EditorHistory::default();
+
+        create_object(&mut history, &mut map, "sword", vec2(0.0, 0.0));
+        create_object(&mut history, &mut map, "cannon", vec2(16.0, 0.0));
+        assert_eq!(get_object_ids(&map), vec!["cannon", "sword"]);
+
+        let action = UpdateObjectAction::new(
+            LAYER_ID.to_string(),
+            1,
+            "sword".to_string(),
+            MapObjectKind::Item,
+            vec2(32.0, 32.0),
+        );
+        history.apply(Box::new(action), &mut map).unwrap();
+        assert_eq!(map.layers[LAYER_ID].objects[1].position, vec2(32.0, 32.0));
+
+        let action = DeleteObjectAction::new(1, LAYER_ID.to_string());
+        history.apply(Box::new(action), &mut map).unwrap();
+        assert_eq!(get_object_ids(&map), vec!["cannon"]);
+
+        history.undo(&mut map).unwrap();
+        assert_eq!(get_object_ids(&map), vec!["cannon", "sword"]);
+
+        history.undo(&mut map).unwrap();
+        assert_eq!(map.layers[LAYER_ID].objects[1].position, Vec2::ZERO);
+
+        history.undo(&mut map).unwrap();
+        history.undo(&mut map).unwrap();
+        assert!(map.layers[LAYER_ID].objects.is_empty());
+    }
+
+    #[test]
+    fn test_undo_create_and_delete_layer() {
+        let mut map = create_map();
+        let mut history = EditorHistory::default();
+
+        let action =
+            CreateLayerAction::new("tiles".to_string(), MapLayerKind::TileLayer, true, Some(0));
+        history.apply(Box::new(action), &mut map).unwrap();
+        assert_eq!(map.draw_order, vec!["tiles", LAYER_ID]);
+
+        let action = DeleteLayerAction::new(LAYER_ID.to_string());
+        history.apply(Box::new(action), &mut map).unwrap();
+        assert!(!map.layers.contains_key(LAYER_ID));
+        assert_eq!(map.draw_order, vec!["tiles"]);
+
+        history.undo(&mut map).unwrap();
+        assert!(map.layers.contains_key(LAYER_ID));
+        assert_eq!(map.draw_order, vec!["tiles", LAYER_ID]);
+
+        history.undo(&mut map).unwrap();
+        assert!(!map.layers.contains_key("tiles"));
+        assert_eq!(map.draw_order, vec![LAYER_ID]);
+    }
+
+    #[test]
+    fn test_convert_layer() {
+        let mut map = create_map();
+        let mut history = EditorHistory::default();
+
+        let action = ConvertLayerAction::new(LAYER_ID.to_string(), MapLayerKind::TileLayer);
+        history.apply(Box::new(action), &mut map).unwrap();
+        assert_eq!(map.layers[LAYER_ID].kind, MapLayerKind::TileLayer);
+
+        history.undo(&mut map).unwrap();
+        assert_eq!(map.layers[LAYER_ID].kind, MapLayerKind::ObjectLayer);
+
+        create_object(&mut history, &mut map, "sword", Vec2::ZERO);
+
+        let action = ConvertLayerAction::new(LAYER_ID.to_string(), MapLayerKind::TileLayer);
+        assert!(history.apply(Box::new(action), &mut map).is_err());
+        assert_eq!(get_object_ids(&map), vec!["sword"]);
+    }
+
+    #[test]
+    fn test_convert_collision_layer() {
+        let mut map = create_map();
+        let mut history = EditorHistory::default();
+
+        let action =
+            CreateLayerAction::new("tiles".to_string(), MapLayerKind::TileLayer, true, None);
+        history.apply(Box::new(action), &mut map).unwrap();
+
+        let action = ConvertLayerAction::new("tiles".to_string(), MapLayerKind::ObjectLayer);
+        history.apply(Box::new(action), &mut map).unwrap();
+        assert!(!map.layers["tiles"].has_collision);
+
+        history.undo(&mut map).unwrap();
+        assert_eq!(map.layers["tiles"].kind, MapLayerKind::TileLayer);
+        assert!(map.layers["tiles"].has_collision);
+
+        history.redo(&mut map).unwrap();
+        assert_eq!(map.layers["tiles"].kind, MapLayerKind::ObjectLayer);
+    }
+
+    #[test]
+    fn test_create_existing_layer_fails() {
+        let mut map = create_map();
+        let mut history = EditorHistory::default();
+
+        let action =
+            CreateLayerAction::new(LAYER_ID.to_string(), MapLayerKind::ObjectLayer, false, None);
+        assert!(history.apply(Box::new(action), &mut map).is_err());
+        assert_eq!(map.draw_order, vec![LAYER_ID]);
+    }
+
+    /// Applies the actions in a resolved batch, of the kinds that fill and clear layers are made of
+    fn apply_resolved(history: &mut EditorHistory, map: &mut Map, action: EditorAction) {
+        let actions = match action.resolve(map) {
+            EditorAction::Batch(actions) => actions,
+            _ => panic!("Expected a batch"),
+        };
+
+        history.begin_batch();
+
+        for action in actions {
+            let action: Box<dyn UndoableAction> = match action {
+                EditorAction::PlaceTile {
+                    id,
+                    layer_id,
+                    tileset_id,
+                    coords,
+                } => Box::new(PlaceTileAction::new(id, layer_id, tileset_id, coords)),
+                EditorAction::RemoveTile { layer_id, coords } => {
+                    Box::new(RemoveTileAction::new(layer_id, coords))
+                }
+                EditorAction::DeleteObject { index, layer_id } => {
+                    Box::new(DeleteObjectAction::new(index, layer_id))
+                }
+                action => panic!("Unexpected action {:?}", action),
+            };
+
+            history.apply(action, map).unwrap();
+        }
+
+        history.end_batch();
+    }
+
+    #[test]
+    fn test_fill_layer() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(4, 3));
+        let mut history = EditorHistory::default();
+
+        let tileset = MapTileset::new("tileset", "tileset", uvec2(64, 32), vec2(16.0, 16.0), 1);
+        map.tilesets.insert("tileset".to_string(), tileset);
+
+        let layer = MapLayer::new("tiles", MapLayerKind::TileLayer, false, map.grid_size);
+        map.layers.insert("tiles".to_string(), layer);
+
+        let action = EditorAction::FillLayer {
+            layer_id: "tiles".to_string(),
+            tileset_id: "tileset".to_string(),
+            tile_id: 2,
+        };
+
+        apply_resolved(&mut history, &mut map, action);
+
+        let tiles = &map.layers["tiles"].tiles;
+        assert_eq!(tiles.len(), 12);
+        assert!(tiles
+            .iter()
+            .all(|tile| matches!(tile, Some(tile) if tile.tile_id == 2)));
+
+        // The whole fill is one step in the history
+        let (entries, _) = history.get_entries();
+        assert_eq!(entries.len(), 1);
+
+        history.undo(&mut map).unwrap();
+        assert!(map.layers["tiles"].is_empty());
+    }
+
+    #[test]
+    fn test_undo_clear_object_layer() {
+        let mut map = create_map();
+        let mut history = EditorHistory::default();
+
+        for id in ["sword", "musket", "cannon"] {
+            create_object(&mut history, &mut map, id, Vec2::ZERO);
+        }
+
+        // This is created when the confirm dialog is opened, but the objects of the layer are
+        // looked up when it is confirmed, so that changes in between are taken into account
+        let action = EditorAction::ClearLayer(LAYER_ID.to_string());
+
+        let delete_action = DeleteObjectAction::new(1, LAYER_ID.to_string());
+        history.apply(Box::new(delete_action), &mut map).unwrap();
+        create_object(&mut history, &mut map, "grenades", Vec2::ZERO);
+
+        apply_resolved(&mut history, &mut map, action);
+
+        assert!(map.layers[LAYER_ID].is_empty());
+
+        history.undo(&mut map).unwrap();
+        assert_eq!(get_object_ids(&map), vec!["sword", "cannon", "grenades"]);
+    }
+
+    #[test]
+    fn test_undo_move_tiles() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(4, 4));
+        let mut history = EditorHistory::default();
+
+        let mut layer = MapLayer::new("tiles", MapLayerKind::TileLayer, false, map.grid_size);
+        for (i, tile_id) in [(0, 1), (1, 2), (5, 3), (15, 4)] {
+            layer.tiles[i] = Some(MapTile {
+                tile_id,
+                tileset_id: "tileset".to_string(),
+                texture_id: "tileset".to_string(),
+                texture_coords: Vec2::ZERO,
+                attributes: Vec::new(),
+            });
+        }
+
+        map.layers.insert("tiles".to_string(), layer);
+
+        let get_tile_ids = |map: &Map| -> Vec<u32> {
+            map.layers["tiles"]
+                .tiles
+                .iter()
+                .map(|tile| tile.as_ref().map(|tile| tile.tile_id).unwrap_or(0))
+                .collect()
+        };
+
+        let original = get_tile_ids(&map);
+
+        // Move the top left 2x2 region, down and to the right, so that it overlaps itself
+        let region = URect::new(0, 0, 2, 2);
+        let action = MoveTilesAction::new("tiles".to_string(), region, ivec2(1, 1));
+        history.apply(Box::new(action), &mut map).unwrap();
+
+        let expected = [0, 0, 0, 0, 0, 1, 2, 0, 0, 0, 3, 0, 0, 0, 0, 4];
+        assert_eq!(get_tile_ids(&map), expected);
+
+        history.undo(&mut map).unwrap();
+        assert_eq!(get_tile_ids(&map), original);
+
+        // Tiles that are moved outside of the map are discarded
+        let action = MoveTilesAction::new("tiles".to_string(), region, ivec2(3, 3));
+        history.apply(Box::new(action), &mut map).unwrap();
+        assert_eq!(get_tile_ids(&map)[15], 1);
+        assert_eq!(get_tile_ids(&map).iter().filter(|&&id| id != 0).count(), 1);
+
+        history.undo(&mut map).unwrap();
+        assert_eq!(get_tile_ids(&map), original);
+    }
+
+    #[test]
+    fn test_fill_region() {
+        let region = URect::new(2, 1, 3, 2);
+
+        let mut tile_id = 0;
+        let action = EditorAction::fill_region("tiles", "tileset", region, || {
+            tile_id += 1;
+            tile_id
+        });
+
+        let placed = match action {
+            EditorAction::Batch(actions) => actions
+                .into_iter()
+                .map(|action| match action {
+                    EditorAction::PlaceTile { id, coords, .. } => (id, coords),
+                    _ => panic!("Expected only PlaceTile actions"),
+                })
+                .collect::<Vec<_>>(),
+            _ => panic!("Expected a batch"),
+        };
+
+        assert_eq!(
+            placed,
+            vec![
+                (1, uvec2(2, 1)),
+                (2, uvec2(3, 1)),
+                (3, uvec2(4, 1)),
+                (4, uvec2(2, 2)),
+                (5, uvec2(3, 2)),
+                (6, uvec2(4, 2)),
+            ]
+        );
+    }
+}
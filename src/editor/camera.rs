@@ -3,16 +3,49 @@ use macroquad::{experimental::scene::RefMut, prelude::*};
 pub struct EditorCamera {
     pub position: Vec2,
     pub scale: f32,
+    /// The position that the camera is traveling to, after a call to `focus_on`
+    focus_target: Option<Vec2>,
 }
 
 impl EditorCamera {
     const FRUSTUM_PADDING: f32 = 64.0;
     const DEFAULT_SCALE: f32 = 1.0;
 
+    /// The fraction of the remaining distance to the focus target that is traveled each update
+    const FOCUS_TRAVEL_FACTOR: f32 = 0.15;
+    /// The camera stops traveling when it is this close to the focus target
+    const FOCUS_STOP_DISTANCE: f32 = 0.5;
+
     pub fn new(position: Vec2) -> Self {
         EditorCamera {
             position,
             scale: Self::DEFAULT_SCALE,
+            focus_target: None,
+        }
+    }
+
+    /// Smoothly move the camera so that it is centered on `position`
+    pub fn focus_on(&mut self, position: Vec2) {
+        self.focus_target = Some(position);
+    }
+
+    /// Stop traveling to the focus target, like when the camera is moved manually
+    pub fn cancel_focus(&mut self) {
+        self.focus_target = None;
+    }
+
+    /// This moves the camera towards the focus target, if any, and should be called every fixed
+    /// update
+    pub fn update_focus(&mut self) {
+        if let Some(target) = self.focus_target {
+            let distance = target - self.position;
+
+            if distance.length() <= Self::FOCUS_STOP_DISTANCE {
+                self.position = target;
+                self.focus_target = None;
+            } else {
+                self.position += distance * Self::FOCUS_TRAVEL_FACTOR;
+            }
         }
     }
 
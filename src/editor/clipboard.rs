@@ -0,0 +1,303 @@
+//! The editor clipboard holds objects, or a region of tiles, that have been copied in the editor.
+//! It is stored in a file, next to the editor sessions, in stead of in memory, so that what has
+//! been copied can be pasted into a different map, that is opened later, or in another session.
+
+use std::fs;
+use std::path::PathBuf;
+
+use macroquad::experimental::collections::storage;
+use macroquad::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use core::data::{deserialize_json_bytes, serialize_json_string};
+use core::error::ErrorKind;
+use core::{formaterr, Result};
+
+use crate::map::{Map, MapLayerKind, MapObject, MapObjectKind};
+use crate::paths::get_saves_dir;
+use crate::Resources;
+
+use super::tile_selection::TileSelection;
+use super::EditorAction;
+
+/// The name of the clipboard file, relative to the saves directory. If the saves directory can
+/// not be determined, this will be relative to the assets directory.
+pub const EDITOR_CLIPBOARD_FILE: &str = "editor_clipboard";
+pub const EDITOR_CLIPBOARD_EXTENSION: &str = "json";
+
+/// The ids of the environment objects, which are not defined in the resources
+const ENVIRONMENT_OBJECT_IDS: &[&str] = &["sproinger", "crab", "fish_school"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardTile {
+    pub tileset_id: String,
+    pub tile_id: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EditorClipboard {
+    /// The copied objects, with positions relative to the top left of the copied objects
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub objects: Vec<MapObject>,
+    /// The size, in cells, of the copied region of tiles
+    #[serde(default, with = "core::json::uvec2_def")]
+    pub tiles_size: UVec2,
+    /// The copied tiles, row by row
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tiles: Vec<Option<ClipboardTile>>,
+}
+
+impl EditorClipboard {
+    pub fn from_objects(objects: &[MapObject]) -> Self {
+        let origin = objects
+            .iter()
+            .map(|object| object.position)
+            .reduce(|a, b| a.min(b))
+            .unwrap_or_default();
+
+        let objects = objects
+            .iter()
+            .cloned()
+            .map(|mut object| {
+                object.position -= origin;
+                object
+            })
+            .collect();
+
+        EditorClipboard {
+            objects,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_tile_selection(selection: &TileSelection) -> Self {
+        let tiles = selection
+            .tiles
+            .iter()
+            .map(|tile| {
+                tile.as_ref().map(|tile| ClipboardTile {
+                    tileset_id: tile.tileset_id.clone(),
+                    tile_id: tile.tile_id,
+                })
+            })
+            .collect();
+
+        EditorClipboard {
+            tiles_size: selection.region.size(),
+            tiles,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty() && self.tiles.iter().all(|tile| tile.is_none())
+    }
+
+    /// Returns an error if any of the copied objects or tiles refer to something that does not
+    /// exist, either in the resources, as checked by `is_valid_object`, or in the tilesets of
+    /// `map`, which it is about to be pasted into
+    pub fn validate<F>(&self, map: &Map, is_valid_object: F) -> Result<()>
+    where
+        F: Fn(&MapObject) -> bool,
+    {
+        for object in &self.objects {
+            if !is_valid_object(object) {
+                return Err(formaterr!(
+                    ErrorKind::EditorAction,
+                    "The object '{}' does not exist",
+                    &object.id
+                ));
+            }
+        }
+
+        for tile in self.tiles.iter().flatten() {
+            let tileset = map.tilesets.get(&tile.tileset_id).ok_or_else(|| {
+                formaterr!(
+                    ErrorKind::EditorAction,
+                    "The tileset '{}' does not exist in this map",
+                    &tile.tileset_id
+                )
+            })?;
+
+            if tile.tile_id >= tileset.tile_cnt {
+                return Err(formaterr!(
+                    ErrorKind::EditorAction,
+                    "The tile {} does not exist in the tileset '{}'",
+                    tile.tile_id,
+                    &tile.tileset_id
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns a batch that pastes the contents of the clipboard on the specified layer, with the
+    /// top left at `position`. Tiles are only pasted on tile layers and objects on object layers,
+    /// and any tiles that would end up outside of the map are discarded. Pasted objects are
+    /// created like new objects, so they will not keep their properties or groups.
+    pub fn get_paste_action(&self, map: &Map, layer_id: &str, position: Vec2) -> EditorAction {
+        let mut actions = Vec::new();
+
+        match map.layers.get(layer_id).map(|layer| layer.kind) {
+            Some(MapLayerKind::TileLayer) => {
+                let origin = map.to_coords(position);
+
+                for (i, tile) in self.tiles.iter().enumerate() {
+                    let tile = match tile {
+                        Some(tile) => tile,
+                        None => continue,
+                    };
+
+                    let coords = origin
+                        + uvec2(
+                            i as u32 % self.tiles_size.x.max(1),
+                            i as u32 / self.tiles_size.x.max(1),
+                        );
+
+                    if coords.x < map.grid_size.x && coords.y < map.grid_size.y {
+                        actions.push(EditorAction::PlaceTile {
+                            id: tile.tile_id,
+                            layer_id: layer_id.to_string(),
+                            tileset_id: tile.tileset_id.clone(),
+                            coords,
+                        });
+                    }
+                }
+            }
+            Some(MapLayerKind::ObjectLayer) => {
+                for object in &self.objects {
+                    actions.push(EditorAction::CreateObject {
+                        id: object.id.clone(),
+                        kind: object.kind,
+                        position: position + object.position,
+                        layer_id: layer_id.to_string(),
+                    });
+                }
+            }
+            None => {}
+        }
+
+        EditorAction::Batch(actions)
+    }
+}
+
+/// Returns `true` if the id of `object` exists in the resources
+pub fn is_valid_object(resources: &Resources, object: &MapObject) -> bool {
+    match object.kind {
+        MapObjectKind::Item => resources.items.contains_key(&object.id),
+        MapObjectKind::Decoration => resources.decoration.contains_key(&object.id),
+        MapObjectKind::Environment => ENVIRONMENT_OBJECT_IDS.contains(&object.id.as_str()),
+    }
+}
+
+pub fn get_clipboard_path() -> PathBuf {
+    let dir = get_saves_dir().unwrap_or_else(|| {
+        let resources = storage::get::<Resources>();
+        PathBuf::from(&resources.assets_dir)
+    });
+
+    dir.join(EDITOR_CLIPBOARD_FILE)
+        .with_extension(EDITOR_CLIPBOARD_EXTENSION)
+}
+
+/// Write `clipboard` to the clipboard file, replacing whatever was copied before
+pub fn save_clipboard(clipboard: &EditorClipboard) -> Result<()> {
+    let path = get_clipboard_path();
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let json = serialize_json_string(clipboard)?;
+    fs::write(path, json)?;
+
+    Ok(())
+}
+
+pub fn load_clipboard() -> Result<EditorClipboard> {
+    let bytes = fs::read(get_clipboard_path())?;
+
+    let clipboard = deserialize_json_bytes(&bytes)?;
+
+    Ok(clipboard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::map::{MapLayer, MapTileset};
+
+    #[test]
+    fn test_paste_clipboard() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(4, 4));
+
+        let tileset = MapTileset::new("tileset", "tileset", uvec2(64, 32), vec2(16.0, 16.0), 1);
+        map.tilesets.insert("tileset".to_string(), tileset);
+
+        for (id, kind) in [
+            ("tiles", MapLayerKind::TileLayer),
+            ("objects", MapLayerKind::ObjectLayer),
+        ] {
+            let layer = MapLayer::new(id, kind, false, map.grid_size);
+            map.layers.insert(id.to_string(), layer);
+        }
+
+        let tile = |tile_id| {
+            Some(ClipboardTile {
+                tileset_id: "tileset".to_string(),
+                tile_id,
+            })
+        };
+
+        let clipboard = EditorClipboard {
+            objects: Vec::new(),
+            tiles_size: uvec2(2, 2),
+            tiles: vec![tile(1), None, tile(2), tile(3)],
+        };
+
+        assert!(clipboard.validate(&map, |_| true).is_ok());
+
+        // The right column ends up outside of the map, so only the tiles in the left are pasted
+        let action = clipboard.get_paste_action(&map, "tiles", vec2(56.0, 40.0));
+        match action {
+            EditorAction::Batch(actions) => {
+                assert_eq!(actions.len(), 2);
+                assert!(matches!(
+                    actions[0],
+                    EditorAction::PlaceTile { id: 1, coords, .. } if coords == uvec2(3, 2)
+                ));
+            }
+            _ => panic!("Expected a batch"),
+        }
+
+        // Tiles that do not exist in the target map can not be pasted
+        let mut invalid = clipboard.clone();
+        invalid.tiles[0] = tile(8);
+        assert!(invalid.validate(&map, |_| true).is_err());
+
+        invalid.tiles[0].as_mut().unwrap().tileset_id = "other".to_string();
+        assert!(invalid.validate(&map, |_| true).is_err());
+
+        let clipboard = EditorClipboard::from_objects(&[
+            MapObject::new("sword", MapObjectKind::Item, vec2(40.0, 20.0)),
+            MapObject::new("musket", MapObjectKind::Item, vec2(10.0, 30.0)),
+        ]);
+
+        assert_eq!(clipboard.objects[0].position, vec2(30.0, 0.0));
+        assert_eq!(clipboard.objects[1].position, vec2(0.0, 10.0));
+
+        assert!(clipboard
+            .validate(&map, |object| object.id == "sword")
+            .is_err());
+
+        // Objects are not pasted on tile layers
+        let action = clipboard.get_paste_action(&map, "tiles", Vec2::ZERO);
+        assert!(matches!(action, EditorAction::Batch(actions) if actions.is_empty()));
+
+        let action = clipboard.get_paste_action(&map, "objects", vec2(5.0, 5.0));
+        assert!(matches!(action, EditorAction::Batch(actions) if actions.len() == 2));
+    }
+}
@@ -9,11 +9,12 @@ use crate::gui::{Menu, MenuEntry, MenuResult};
 const MENU_WIDTH: f32 = 300.0;
 
 pub const EDITOR_MENU_RESULT_NEW: usize = 0;
-pub const EDITOR_MENU_RESULT_OPEN_IMPORT: usize = 1;
-pub const EDITOR_MENU_RESULT_SAVE: usize = 2;
-pub const EDITOR_MENU_RESULT_SAVE_AS: usize = 3;
-pub const EDITOR_MENU_RESULT_MAIN_MENU: usize = 4;
-pub const EDITOR_MENU_RESULT_QUIT: usize = 5;
+pub const EDITOR_MENU_RESULT_GENERATE: usize = 1;
+pub const EDITOR_MENU_RESULT_OPEN_IMPORT: usize = 2;
+pub const EDITOR_MENU_RESULT_SAVE: usize = 3;
+pub const EDITOR_MENU_RESULT_SAVE_AS: usize = 4;
+pub const EDITOR_MENU_RESULT_MAIN_MENU: usize = 5;
+pub const EDITOR_MENU_RESULT_QUIT: usize = 6;
 
 static mut EDITOR_MENU_INSTANCE: Option<Menu> = None;
 
@@ -29,6 +30,11 @@ pub fn open_editor_menu(ctx: &EditorContext) {
                         title: "New".to_string(),
                         ..Default::default()
                     },
+                    MenuEntry {
+                        index: EDITOR_MENU_RESULT_GENERATE,
+                        title: "Generate Map".to_string(),
+                        ..Default::default()
+                    },
                     MenuEntry {
                         index: EDITOR_MENU_RESULT_OPEN_IMPORT,
                         title: "Open/Import".to_string(),
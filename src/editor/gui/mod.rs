@@ -12,8 +12,9 @@ pub use combobox::{ComboBoxBuilder, ComboBoxValue};
 
 pub use editor_menu::{
     close_editor_menu, draw_editor_menu, is_editor_menu_open, open_editor_menu, toggle_editor_menu,
-    EDITOR_MENU_RESULT_MAIN_MENU, EDITOR_MENU_RESULT_NEW, EDITOR_MENU_RESULT_OPEN_IMPORT,
-    EDITOR_MENU_RESULT_QUIT, EDITOR_MENU_RESULT_SAVE, EDITOR_MENU_RESULT_SAVE_AS,
+    EDITOR_MENU_RESULT_GENERATE, EDITOR_MENU_RESULT_MAIN_MENU, EDITOR_MENU_RESULT_NEW,
+    EDITOR_MENU_RESULT_OPEN_IMPORT, EDITOR_MENU_RESULT_QUIT, EDITOR_MENU_RESULT_SAVE,
+    EDITOR_MENU_RESULT_SAVE_AS,
 };
 
 use macroquad::{
@@ -23,7 +24,7 @@ use macroquad::{
     ui::{root_ui, widgets},
 };
 
-use super::{EditorAction, EditorCamera, EditorContext};
+use super::{session::EditorSessionWindow, EditorAction, EditorCamera, EditorContext};
 
 use crate::{
     gui::{GuiResources, ELEMENT_MARGIN},
@@ -40,7 +41,9 @@ pub use windows::{
     TilesetPropertiesWindow, Window, WINDOW_BUTTON_MAX_WIDTH, WINDOW_BUTTON_MIN_WIDTH,
 };
 
-use crate::gui::{BUTTON_FONT_SIZE, BUTTON_MARGIN_V, WINDOW_MARGIN_H, WINDOW_MARGIN_V};
+use crate::gui::{
+    get_ui_scale, scaled, scaled_vec2, StyleMetrics, WINDOW_MARGIN_H, WINDOW_MARGIN_V,
+};
 use crate::map::MapLayerKind;
 use context_menu::{ContextMenu, ContextMenuEntry};
 
@@ -71,6 +74,7 @@ impl EditorGui {
     pub const TILESET_LIST_HEIGHT_FACTOR: f32 = 0.2;
     pub const TILESET_DETAILS_HEIGHT_FACTOR: f32 = 0.5;
     pub const OBJECT_LIST_HEIGHT_FACTOR: f32 = 0.7;
+    pub const DECORATION_LIST_HEIGHT_FACTOR: f32 = 0.7;
 
     pub fn new() -> Self {
         EditorGui {
@@ -155,12 +159,147 @@ impl EditorGui {
                         layer_id: layer_id.clone(),
                     },
                 ));
+
+                if !ctx.selected_objects.is_empty() {
+                    entries.push(ContextMenuEntry::action(
+                        "Duplicate",
+                        EditorAction::DuplicateObjects {
+                            layer_id: layer_id.clone(),
+                            indices: ctx.selected_objects.clone(),
+                        },
+                    ));
+                }
+
+                let selected_groups = ctx
+                    .selected_objects
+                    .iter()
+                    .map(|&i| layer.objects.get(i).and_then(|object| object.group))
+                    .collect::<Vec<_>>();
+
+                let is_single_group = selected_groups
+                    .first()
+                    .map(|first| first.is_some() && selected_groups.iter().all(|g| g == first))
+                    .unwrap_or_default();
+
+                if selected_groups.len() > 1 && !is_single_group {
+                    entries.push(ContextMenuEntry::action(
+                        "Group",
+                        EditorAction::GroupObjects {
+                            layer_id: layer_id.clone(),
+                            indices: ctx.selected_objects.clone(),
+                        },
+                    ));
+                }
+
+                let group = ctx
+                    .selected_object
+                    .and_then(|i| layer.objects.get(i))
+                    .and_then(|object| object.group);
+
+                if let Some(group) = group {
+                    entries.push(ContextMenuEntry::action(
+                        "Ungroup",
+                        EditorAction::UngroupObjects {
+                            layer_id: layer_id.clone(),
+                            group,
+                        },
+                    ));
+                }
+            }
+        }
+
+        if let Some(index) = ctx.selected_spawn_point {
+            if index > 0 {
+                entries.push(ContextMenuEntry::action(
+                    "Move Spawn Earlier",
+                    EditorAction::ReorderSpawnPoint {
+                        index,
+                        new_index: index - 1,
+                    },
+                ));
+            }
+
+            if index + 1 < map.spawn_points.len() {
+                entries.push(ContextMenuEntry::action(
+                    "Move Spawn Later",
+                    EditorAction::ReorderSpawnPoint {
+                        index,
+                        new_index: index + 1,
+                    },
+                ));
+            }
+        }
+
+        if let Some(layer_id) = &ctx.selected_layer {
+            let layer = &map.layers.get(layer_id).unwrap();
+
+            if layer.kind == MapLayerKind::TileLayer {
+                if let (Some(tileset_id), Some(tile_id)) =
+                    (&ctx.selected_tileset, ctx.selected_tile)
+                {
+                    let tile_cnt = map.grid_size.x * map.grid_size.y;
+                    let body = format!(
+                        "This will place the selected tile in all {} cells of '{}'",
+                        tile_cnt, layer_id
+                    );
+
+                    entries.push(ContextMenuEntry::action(
+                        "Fill Layer",
+                        EditorAction::confirm(
+                            &[&body],
+                            EditorAction::FillLayer {
+                                layer_id: layer_id.clone(),
+                                tileset_id: tileset_id.clone(),
+                                tile_id,
+                            },
+                        ),
+                    ));
+                }
+            }
+
+            if !layer.is_empty() {
+                let body = format!("This will clear all contents of '{}'", layer_id);
+
+                entries.push(ContextMenuEntry::action(
+                    "Clear Layer",
+                    EditorAction::confirm(&[&body], EditorAction::ClearLayer(layer_id.clone())),
+                ));
+            }
+
+            let (label, new_kind) = match layer.kind {
+                MapLayerKind::TileLayer => ("Convert to Object Layer", MapLayerKind::ObjectLayer),
+                MapLayerKind::ObjectLayer => ("Convert to Tile Layer", MapLayerKind::TileLayer),
+            };
+
+            if layer.can_convert_to(new_kind) {
+                entries.push(ContextMenuEntry::action(
+                    label,
+                    EditorAction::ConvertLayer {
+                        id: layer_id.clone(),
+                        new_kind,
+                    },
+                ));
             }
         }
 
+        entries.push(ContextMenuEntry::action(
+            "Drop Test Dummy",
+            EditorAction::SpawnTestDummy(object_world_position),
+        ));
+
+        if ctx.has_test_dummy {
+            entries.push(ContextMenuEntry::action(
+                "Reset Test Dummy",
+                EditorAction::ClearTestDummy,
+            ));
+        }
+
         entries.append(&mut vec![
             ContextMenuEntry::action("Add Layer", EditorAction::OpenCreateLayerWindow),
             ContextMenuEntry::action("Background", EditorAction::OpenBackgroundPropertiesWindow),
+            ContextMenuEntry::action("Map Properties", EditorAction::OpenMapPropertiesWindow),
+            ContextMenuEntry::action("History", EditorAction::OpenHistoryWindow),
+            ContextMenuEntry::action("Map Cost", EditorAction::OpenMapCostWindow),
         ]);
 
         self.context_menu = Some(ContextMenu::new(position, &entries));
@@ -177,6 +316,10 @@ impl EditorGui {
             .or_insert_with(|| Box::new(window));
     }
 
+    pub fn is_window_open<W: Window + 'static>(&self) -> bool {
+        self.open_windows.contains_key(&TypeId::of::<W>())
+    }
+
     pub fn remove_window<W: Window + 'static>(&mut self) {
         let key = TypeId::of::<W>();
         self.open_windows.remove(&key).unwrap();
@@ -186,6 +329,13 @@ impl EditorGui {
         self.open_windows.remove(&id).unwrap();
     }
 
+    pub fn get_session_windows(&self) -> Vec<EditorSessionWindow> {
+        self.open_windows
+            .values()
+            .filter_map(|window| window.get_session_window())
+            .collect()
+    }
+
     pub fn draw(&mut self, map: &Map, ctx: EditorContext) -> Option<EditorAction> {
         let mut res = None;
 
@@ -212,14 +362,14 @@ impl EditorGui {
             let params = window.get_params().clone();
 
             let position = params.get_absolute_position();
-            let size = params.size;
+            let size = params.get_scaled_size();
 
             widgets::Window::new(hash!(id), position, size)
                 .titlebar(false)
                 .movable(!params.is_static)
                 .ui(ui, |ui| {
                     let mut content_size =
-                        size - vec2(WINDOW_MARGIN_H * 2.0, WINDOW_MARGIN_V * 2.0);
+                        size - scaled_vec2(vec2(WINDOW_MARGIN_H * 2.0, WINDOW_MARGIN_V * 2.0));
 
                     let mut content_position = Vec2::ZERO;
 
@@ -237,10 +387,11 @@ impl EditorGui {
                         ui.pop_skin();
                     }
 
-                    let button_height = BUTTON_FONT_SIZE + (BUTTON_MARGIN_V * 2.0);
+                    let button_height = StyleMetrics::button(get_ui_scale()).get_widget_size(0.0).y;
+                    let element_margin = scaled(element_margin);
 
                     if params.has_buttons {
-                        content_size.y -= button_height + ELEMENT_MARGIN;
+                        content_size.y -= button_height + element_margin;
                     }
 
                     widgets::Group::new(hash!(id, "content"), content_size)
@@ -259,14 +410,16 @@ impl EditorGui {
                         widgets::Group::new(hash!(id, "buttons"), button_area_size)
                             .position(button_area_position)
                             .ui(ui, |ui| {
-                                let mut button_position = vec2(ELEMENT_MARGIN, 0.0);
+                                let mut button_position = vec2(element_margin, 0.0);
 
                                 let buttons = window.get_buttons(map, &ctx);
 
                                 let button_cnt = buttons.len();
-                                let margins = button_cnt as f32 * ELEMENT_MARGIN;
-                                let width = ((size.x - margins) / button_cnt as f32)
-                                    .clamp(WINDOW_BUTTON_MIN_WIDTH, WINDOW_BUTTON_MAX_WIDTH);
+                                let margins = button_cnt as f32 * element_margin;
+                                let width = ((size.x - margins) / button_cnt as f32).clamp(
+                                    scaled(WINDOW_BUTTON_MIN_WIDTH),
+                                    scaled(WINDOW_BUTTON_MAX_WIDTH),
+                                );
 
                                 let button_size = vec2(width, button_height);
 
@@ -289,7 +442,7 @@ impl EditorGui {
                                         ui.pop_skin();
                                     }
 
-                                    button_position.x += button_size.x + ELEMENT_MARGIN;
+                                    button_position.x += button_size.x + element_margin;
                                 }
                             });
                     }
@@ -312,6 +465,10 @@ impl EditorGui {
                         let action = EditorAction::OpenCreateMapWindow;
                         res = Some(action);
                     }
+                    EDITOR_MENU_RESULT_GENERATE => {
+                        let action = EditorAction::OpenGenerateMapWindow;
+                        res = Some(action);
+                    }
                     EDITOR_MENU_RESULT_OPEN_IMPORT => {
                         let action = EditorAction::OpenLoadMapWindow;
                         res = Some(action);
@@ -0,0 +1,109 @@
+use std::any::TypeId;
+
+use macroquad::{
+    experimental::collections::storage,
+    prelude::*,
+    ui::{widgets, Ui},
+};
+
+use super::{
+    EditorAction, EditorContext, GuiResources, Map, Toolbar, ToolbarElement, ToolbarElementParams,
+};
+
+use crate::{editor::DecorationPlacementTool, map::MapLayerKind, Resources};
+
+/// This lists all the decorations in resources, so that one can be selected for the decoration
+/// placement tool. It replaces the object list while that tool is selected.
+pub struct DecorationListElement {
+    params: ToolbarElementParams,
+}
+
+impl DecorationListElement {
+    pub fn new() -> Self {
+        let params = ToolbarElementParams {
+            header: Some("Decorations".to_string()),
+            has_buttons: false,
+            has_margins: false,
+        };
+
+        DecorationListElement { params }
+    }
+}
+
+impl ToolbarElement for DecorationListElement {
+    fn get_params(&self) -> &ToolbarElementParams {
+        &self.params
+    }
+
+    fn draw(
+        &mut self,
+        ui: &mut Ui,
+        size: Vec2,
+        _map: &Map,
+        ctx: &EditorContext,
+    ) -> Option<EditorAction> {
+        let mut res = None;
+
+        let entry_size = vec2(size.x, Toolbar::LIST_ENTRY_HEIGHT);
+        let mut position = Vec2::ZERO;
+
+        let gui_resources = storage::get::<GuiResources>();
+        ui.push_skin(&gui_resources.skins.list_box);
+
+        let mut decoration_ids = {
+            let resources = storage::get::<Resources>();
+            resources.decoration.keys().cloned().collect::<Vec<_>>()
+        };
+
+        decoration_ids.sort();
+
+        for id in decoration_ids {
+            let is_selected = ctx.selected_decoration.as_ref() == Some(&id);
+
+            if is_selected {
+                ui.push_skin(&gui_resources.skins.list_box_selected);
+            }
+
+            let was_clicked = widgets::Button::new("")
+                .size(entry_size)
+                .position(position)
+                .ui(ui);
+
+            ui.label(position, &id);
+
+            if was_clicked {
+                res = Some(EditorAction::SelectDecoration(id));
+            }
+
+            if is_selected {
+                ui.pop_skin();
+            }
+
+            position.y += entry_size.y;
+        }
+
+        ui.pop_skin();
+
+        res
+    }
+
+    fn is_drawn(&self, map: &Map, ctx: &EditorContext) -> bool {
+        if ctx.selected_tool != Some(TypeId::of::<DecorationPlacementTool>()) {
+            return false;
+        }
+
+        if let Some(layer_id) = &ctx.selected_layer {
+            if let Some(layer) = map.layers.get(layer_id) {
+                return layer.kind == MapLayerKind::ObjectLayer;
+            }
+        }
+
+        false
+    }
+}
+
+impl Default for DecorationListElement {
+    fn default() -> Self {
+        Self::new()
+    }
+}
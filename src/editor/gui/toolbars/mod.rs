@@ -33,6 +33,10 @@ mod object_list;
 
 pub use object_list::ObjectListElement;
 
+mod decoration_list;
+
+pub use decoration_list::DecorationListElement;
+
 #[derive(Debug, Default, Clone)]
 pub struct ToolbarElementParams {
     header: Option<String>,
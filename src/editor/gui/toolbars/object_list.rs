@@ -1,3 +1,5 @@
+use std::any::TypeId;
+
 use macroquad::{
     experimental::collections::storage,
     prelude::*,
@@ -9,7 +11,7 @@ use super::{
 };
 
 use crate::{
-    editor::{gui::ButtonParams, EditorCamera},
+    editor::{gui::ButtonParams, DecorationPlacementTool, EditorCamera},
     map::MapLayerKind,
 };
 
@@ -55,11 +57,7 @@ impl ToolbarElement for ObjectListElement {
         let layer = map.layers.get(layer_id).unwrap();
 
         for (i, object) in layer.objects.iter().enumerate() {
-            let is_selected = if let Some(selected_index) = ctx.selected_object {
-                selected_index == i
-            } else {
-                false
-            };
+            let is_selected = ctx.selected_objects.contains(&i);
 
             if is_selected {
                 let gui_resources = storage::get::<GuiResources>();
@@ -111,10 +109,12 @@ impl ToolbarElement for ObjectListElement {
         let mut properties_action = None;
 
         if let Some(index) = ctx.selected_object {
-            delete_action = Some(EditorAction::DeleteObject {
-                index,
-                layer_id: layer_id.clone(),
-            });
+            let mut indices = ctx.selected_objects.clone();
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+
+            delete_action = Some(EditorAction::delete_objects(&layer_id, &indices));
 
             properties_action = Some(EditorAction::OpenObjectPropertiesWindow { layer_id, index });
         }
@@ -139,6 +139,11 @@ impl ToolbarElement for ObjectListElement {
     }
 
     fn is_drawn(&self, map: &Map, ctx: &EditorContext) -> bool {
+        // The decoration list is drawn in place of the object list, while placing decorations
+        if ctx.selected_tool == Some(TypeId::of::<DecorationPlacementTool>()) {
+            return false;
+        }
+
         if let Some(layer_id) = &ctx.selected_layer {
             if let Some(layer) = map.layers.get(layer_id) {
                 return layer.kind == MapLayerKind::ObjectLayer;
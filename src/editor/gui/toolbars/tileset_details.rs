@@ -74,11 +74,8 @@ impl ToolbarElement for TilesetDetailsElement {
                 for x in 0..tileset.grid_size.x {
                     let tile_id = y * tileset.grid_size.x + x;
 
-                    let is_selected = if let Some(selected) = ctx.selected_tile {
-                        selected == tile_id
-                    } else {
-                        false
-                    };
+                    let is_selected =
+                        ctx.selected_tile == Some(tile_id) || ctx.random_tiles.contains(&tile_id);
 
                     if is_selected {
                         let gui_resources = storage::get::<GuiResources>();
@@ -93,10 +90,30 @@ impl ToolbarElement for TilesetDetailsElement {
                         .ui(ui);
 
                     if button {
-                        res = Some(EditorAction::SelectTile {
-                            id: tile_id,
-                            tileset_id: tileset.id.clone(),
-                        });
+                        // Holding the extend selection key will add or remove the tile from the
+                        // set of tiles that the tile placement tool picks from at random
+                        if ctx.is_extending_selection {
+                            let mut tiles = ctx.random_tiles.clone();
+
+                            if tiles.is_empty() {
+                                tiles.extend(ctx.selected_tile);
+                            }
+
+                            if let Some(i) = tiles.iter().position(|&id| id == tile_id) {
+                                tiles.remove(i);
+                            } else {
+                                tiles.push(tile_id);
+                            }
+
+                            res = Some(EditorAction::SetRandomTiles(tiles));
+                        } else {
+                            let action = EditorAction::SelectTile {
+                                id: tile_id,
+                                tileset_id: tileset.id.clone(),
+                            };
+
+                            res = Some(action.then(EditorAction::SetRandomTiles(Vec::new())));
+                        }
                     }
 
                     if is_selected {
@@ -7,9 +7,11 @@ use macroquad::{
 use crate::map::MapBackgroundLayer;
 use crate::{map::Map, Resources};
 
-use crate::gui::{GuiResources, ELEMENT_MARGIN, LIST_BOX_ENTRY_HEIGHT};
+use crate::gui::{scaled, GuiResources, ELEMENT_MARGIN, LIST_BOX_ENTRY_HEIGHT};
 use crate::resources::TextureKind;
 
+use crate::editor::session::EditorSessionWindow;
+
 use super::{ButtonParams, EditorAction, EditorContext, Window, WindowParams};
 
 pub struct BackgroundPropertiesWindow {
@@ -45,6 +47,10 @@ impl Window for BackgroundPropertiesWindow {
         &self.params
     }
 
+    fn get_session_window(&self) -> Option<EditorSessionWindow> {
+        Some(EditorSessionWindow::BackgroundProperties)
+    }
+
     fn get_buttons(&self, _map: &Map, _ctx: &EditorContext) -> Vec<ButtonParams> {
         let mut res = Vec::new();
 
@@ -124,8 +130,8 @@ impl Window for BackgroundPropertiesWindow {
                 }
             });
 
-        let layer_list_size = vec2((size.x * 0.6) - ELEMENT_MARGIN, size.y * 0.5);
-        let layer_list_entry_size = vec2(layer_list_size.x, LIST_BOX_ENTRY_HEIGHT);
+        let layer_list_size = vec2((size.x * 0.6) - scaled(ELEMENT_MARGIN), size.y * 0.5);
+        let layer_list_entry_size = vec2(layer_list_size.x, scaled(LIST_BOX_ENTRY_HEIGHT));
 
         {
             let gui_resources = storage::get::<GuiResources>();
@@ -133,12 +139,12 @@ impl Window for BackgroundPropertiesWindow {
         }
 
         widgets::Group::new(hash!(id, "layer_list"), layer_list_size)
-            .position(vec2((size.x * 0.4) + ELEMENT_MARGIN, 0.0))
+            .position(vec2((size.x * 0.4) + scaled(ELEMENT_MARGIN), 0.0))
             .ui(ui, |ui| {
                 let layers = self.layers.clone();
                 for (i, layer) in layers.iter().enumerate() {
                     widgets::Group::new(hash!(id, "layer_list_entry", i), layer_list_entry_size)
-                        .position(vec2(0.0, i as f32 * LIST_BOX_ENTRY_HEIGHT))
+                        .position(vec2(0.0, i as f32 * scaled(LIST_BOX_ENTRY_HEIGHT)))
                         .ui(ui, |ui| {
                             let mut is_selected = false;
                             if let Some(index) = self.selected_layer {
@@ -179,9 +185,9 @@ impl Window for BackgroundPropertiesWindow {
 
         widgets::Group::new(
             hash!(id, "layer_attributes"),
-            vec2(size.x, (size.y * 0.5) - ELEMENT_MARGIN),
+            vec2(size.x, (size.y * 0.5) - scaled(ELEMENT_MARGIN)),
         )
-        .position(vec2(0.0, (size.y * 0.5) + ELEMENT_MARGIN))
+        .position(vec2(0.0, (size.y * 0.5) + scaled(ELEMENT_MARGIN)))
         .ui(ui, |ui| {
             let resources = storage::get::<Resources>();
             let mut texture_ids = resources
@@ -0,0 +1,309 @@
+use macroquad::{
+    experimental::collections::storage,
+    prelude::*,
+    ui::{hash, widgets, Ui},
+};
+
+use super::{ButtonParams, EditorAction, EditorContext, Window, WindowParams};
+
+use crate::editor::gui::combobox::ComboBoxVec;
+use crate::editor::gui::{ComboBoxBuilder, ComboBoxValue};
+use crate::gui::Checkbox;
+use crate::map::{
+    Map, MapGenerationParams, DEFAULT_GENERATED_MAP_FILL_TILE_ID,
+    DEFAULT_GENERATED_MAP_PLATFORM_TILE_ID, DEFAULT_GENERATED_MAP_SURFACE_TILE_ID,
+    DEFAULT_GENERATED_MAP_TEXTURE_ID,
+};
+use crate::resources::TextureKind;
+use crate::Resources;
+
+const DEFAULT_TILE_SIZE: f32 = 32.0;
+
+pub struct GenerateMapWindow {
+    params: WindowParams,
+    name: String,
+    seed: String,
+    grid_size: UVec2,
+    texture: ComboBoxVec,
+    surface_tile_id: u32,
+    fill_tile_id: u32,
+    platform_tile_id: u32,
+    platform_density: f32,
+    item_density: f32,
+    spawn_point_cnt: u32,
+    is_symmetric: bool,
+}
+
+impl GenerateMapWindow {
+    pub fn new() -> Self {
+        let params = WindowParams {
+            title: Some("Generate Map".to_string()),
+            size: vec2(350.0, 520.0),
+            ..Default::default()
+        };
+
+        let texture = {
+            let resources = storage::get::<Resources>();
+
+            let mut texture_ids = resources
+                .textures
+                .iter()
+                .filter_map(|(k, v)| {
+                    if v.meta.kind == Some(TextureKind::Tileset) {
+                        return Some(k.as_str());
+                    }
+
+                    None
+                })
+                .collect::<Vec<_>>();
+
+            texture_ids.sort_unstable();
+
+            let mut texture = ComboBoxVec::new(0, &texture_ids);
+            texture.set_value(DEFAULT_GENERATED_MAP_TEXTURE_ID);
+
+            texture
+        };
+
+        GenerateMapWindow {
+            params,
+            name: "generated_map".to_string(),
+            seed: rand::rand().to_string(),
+            grid_size: uvec2(40, 24),
+            texture,
+            surface_tile_id: DEFAULT_GENERATED_MAP_SURFACE_TILE_ID,
+            fill_tile_id: DEFAULT_GENERATED_MAP_FILL_TILE_ID,
+            platform_tile_id: DEFAULT_GENERATED_MAP_PLATFORM_TILE_ID,
+            platform_density: 0.4,
+            item_density: 0.1,
+            spawn_point_cnt: 4,
+            is_symmetric: true,
+        }
+    }
+
+    fn get_generation_params(&self) -> Option<MapGenerationParams> {
+        let seed = self.seed.trim().parse::<u64>().ok()?;
+
+        if self.grid_size.x == 0 || self.grid_size.y == 0 {
+            return None;
+        }
+
+        let resources = storage::get::<Resources>();
+
+        let texture_res = resources.textures.get(&self.texture.get_value())?;
+
+        let tile_size = texture_res
+            .meta
+            .frame_size
+            .unwrap_or_else(|| vec2(DEFAULT_TILE_SIZE, DEFAULT_TILE_SIZE));
+
+        let tileset_texture_size = uvec2(
+            texture_res.texture.width() as u32,
+            texture_res.texture.height() as u32,
+        );
+
+        let mut item_ids = resources.items.keys().cloned().collect::<Vec<_>>();
+        // Sort the ids, so that the same seed produces the same map, regardless of load order
+        item_ids.sort_unstable();
+
+        Some(MapGenerationParams {
+            seed,
+            grid_size: self.grid_size,
+            tile_size,
+            tileset_texture_id: texture_res.meta.id.clone(),
+            tileset_texture_size,
+            surface_tile_id: self.surface_tile_id,
+            fill_tile_id: self.fill_tile_id,
+            platform_tile_id: self.platform_tile_id,
+            platform_density: self.platform_density.clamp(0.0, 1.0),
+            item_density: self.item_density.clamp(0.0, 1.0),
+            item_ids,
+            spawn_point_cnt: self.spawn_point_cnt,
+            is_symmetric: self.is_symmetric,
+        })
+    }
+}
+
+impl Window for GenerateMapWindow {
+    fn get_params(&self) -> &WindowParams {
+        &self.params
+    }
+
+    fn draw(
+        &mut self,
+        ui: &mut Ui,
+        _size: Vec2,
+        _map: &Map,
+        _ctx: &EditorContext,
+    ) -> Option<EditorAction> {
+        let id = hash!("generate_map_window");
+
+        let size = vec2(150.0, 25.0);
+
+        widgets::InputText::new(hash!(id, "name_input"))
+            .size(size)
+            .ratio(1.0)
+            .label("Name")
+            .ui(ui, &mut self.name);
+
+        widgets::InputText::new(hash!(id, "seed_input"))
+            .size(size)
+            .ratio(1.0)
+            .label("Seed")
+            .ui(ui, &mut self.seed);
+
+        ui.separator();
+
+        {
+            let mut grid_width = self.grid_size.x.to_string();
+            let mut grid_height = self.grid_size.y.to_string();
+
+            let size = vec2(75.0, 25.0);
+
+            widgets::InputText::new(hash!(id, "grid_width_input"))
+                .size(size)
+                .ratio(1.0)
+                .label("x")
+                .ui(ui, &mut grid_width);
+
+            ui.same_line(size.x + 25.0);
+
+            widgets::InputText::new(hash!(id, "grid_height_input"))
+                .size(size)
+                .ratio(1.0)
+                .label("Grid size")
+                .ui(ui, &mut grid_height);
+
+            if let Ok(width) = grid_width.parse::<u32>() {
+                self.grid_size.x = width;
+            }
+
+            if let Ok(height) = grid_height.parse::<u32>() {
+                self.grid_size.y = height;
+            }
+        }
+
+        ui.separator();
+
+        ComboBoxBuilder::new(hash!(id, "texture_input"))
+            .with_ratio(0.8)
+            .with_label("Tileset")
+            .build(ui, &mut self.texture);
+
+        {
+            let mut surface_tile_id = self.surface_tile_id.to_string();
+            let mut fill_tile_id = self.fill_tile_id.to_string();
+            let mut platform_tile_id = self.platform_tile_id.to_string();
+
+            let size = vec2(75.0, 25.0);
+
+            widgets::InputText::new(hash!(id, "surface_tile_input"))
+                .size(size)
+                .ratio(1.0)
+                .label("Surface tile")
+                .ui(ui, &mut surface_tile_id);
+
+            widgets::InputText::new(hash!(id, "fill_tile_input"))
+                .size(size)
+                .ratio(1.0)
+                .label("Fill tile")
+                .ui(ui, &mut fill_tile_id);
+
+            widgets::InputText::new(hash!(id, "platform_tile_input"))
+                .size(size)
+                .ratio(1.0)
+                .label("Platform tile")
+                .ui(ui, &mut platform_tile_id);
+
+            if let Ok(tile_id) = surface_tile_id.parse::<u32>() {
+                self.surface_tile_id = tile_id;
+            }
+
+            if let Ok(tile_id) = fill_tile_id.parse::<u32>() {
+                self.fill_tile_id = tile_id;
+            }
+
+            if let Ok(tile_id) = platform_tile_id.parse::<u32>() {
+                self.platform_tile_id = tile_id;
+            }
+        }
+
+        ui.separator();
+
+        {
+            let mut platform_density = format!("{:.2}", self.platform_density);
+            let mut item_density = format!("{:.2}", self.item_density);
+            let mut spawn_point_cnt = self.spawn_point_cnt.to_string();
+
+            let size = vec2(75.0, 25.0);
+
+            widgets::InputText::new(hash!(id, "platform_density_input"))
+                .size(size)
+                .ratio(1.0)
+                .label("Platform density")
+                .ui(ui, &mut platform_density);
+
+            widgets::InputText::new(hash!(id, "item_density_input"))
+                .size(size)
+                .ratio(1.0)
+                .label("Item density")
+                .ui(ui, &mut item_density);
+
+            widgets::InputText::new(hash!(id, "spawn_points_input"))
+                .size(size)
+                .ratio(1.0)
+                .label("Spawn points")
+                .ui(ui, &mut spawn_point_cnt);
+
+            if let Ok(density) = platform_density.parse::<f32>() {
+                self.platform_density = density;
+            }
+
+            if let Ok(density) = item_density.parse::<f32>() {
+                self.item_density = density;
+            }
+
+            if let Ok(cnt) = spawn_point_cnt.parse::<u32>() {
+                self.spawn_point_cnt = cnt;
+            }
+        }
+
+        ui.separator();
+
+        Checkbox::new(hash!(id, "symmetric_input"), None, "Symmetric")
+            .ui(ui, &mut self.is_symmetric);
+
+        None
+    }
+
+    fn get_buttons(&self, _map: &Map, _ctx: &EditorContext) -> Vec<ButtonParams> {
+        let mut res = Vec::new();
+
+        let action = self.get_generation_params().map(|params| {
+            self.get_close_action().then(EditorAction::GenerateMap {
+                name: self.name.clone(),
+                params,
+            })
+        });
+
+        res.push(ButtonParams {
+            label: "Generate",
+            action,
+            ..Default::default()
+        });
+
+        res.push(ButtonParams {
+            label: "Cancel",
+            action: Some(self.get_close_action()),
+            ..Default::default()
+        });
+
+        res
+    }
+}
+
+impl Default for GenerateMapWindow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
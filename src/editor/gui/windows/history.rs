@@ -0,0 +1,98 @@
+use macroquad::{
+    experimental::collections::storage,
+    prelude::*,
+    ui::{hash, widgets, Ui},
+};
+
+use crate::gui::{scaled, GuiResources, LIST_BOX_ENTRY_HEIGHT};
+
+use crate::map::Map;
+
+use super::{EditorAction, EditorContext, Window, WindowParams};
+
+/// Lists the steps in the undo history and lets the user jump to any of them. Steps that have been
+/// undone are kept in the list, until a new action is applied, so that they can be jumped back to.
+pub struct HistoryWindow {
+    params: WindowParams,
+}
+
+impl HistoryWindow {
+    pub fn new() -> Self {
+        let params = WindowParams {
+            title: Some("History".to_string()),
+            size: vec2(300.0, 350.0),
+            ..Default::default()
+        };
+
+        HistoryWindow { params }
+    }
+}
+
+impl Window for HistoryWindow {
+    fn get_params(&self) -> &WindowParams {
+        &self.params
+    }
+
+    fn draw(
+        &mut self,
+        ui: &mut Ui,
+        size: Vec2,
+        _map: &Map,
+        ctx: &EditorContext,
+    ) -> Option<EditorAction> {
+        let id = hash!("history_window");
+
+        let mut res = None;
+
+        {
+            let gui_resources = storage::get::<GuiResources>();
+            ui.push_skin(&gui_resources.skins.list_box_no_bg);
+        }
+
+        widgets::Group::new(hash!(id, "list_box"), size)
+            .position(Vec2::ZERO)
+            .ui(ui, |ui| {
+                let entry_size = vec2(size.x, scaled(LIST_BOX_ENTRY_HEIGHT));
+
+                // The first row is the state before any of the steps in the history were applied
+                let rows = std::iter::once("Initial state").chain(
+                    ctx.history_entries
+                        .iter()
+                        .map(|description| description.as_str()),
+                );
+
+                for (i, description) in rows.enumerate() {
+                    let is_selected = i == ctx.history_index;
+
+                    if is_selected {
+                        let gui_resources = storage::get::<GuiResources>();
+                        ui.push_skin(&gui_resources.skins.list_box_selected);
+                    }
+
+                    let entry_position = vec2(0.0, i as f32 * entry_size.y);
+
+                    let entry_btn = widgets::Button::new("")
+                        .size(entry_size)
+                        .position(entry_position);
+
+                    if entry_btn.ui(ui) && !is_selected {
+                        res = Some(EditorAction::JumpToHistory(i));
+                    }
+
+                    if i > ctx.history_index {
+                        ui.label(entry_position, &format!("({})", description));
+                    } else {
+                        ui.label(entry_position, description);
+                    }
+
+                    if is_selected {
+                        ui.pop_skin();
+                    }
+                }
+            });
+
+        ui.pop_skin();
+
+        res
+    }
+}
@@ -4,7 +4,7 @@ use macroquad::{
     ui::{hash, widgets, Ui},
 };
 
-use crate::gui::{Checkbox, GuiResources, ELEMENT_MARGIN, LIST_BOX_ENTRY_HEIGHT};
+use crate::gui::{scaled, Checkbox, GuiResources, ELEMENT_MARGIN, LIST_BOX_ENTRY_HEIGHT};
 
 use crate::map::{Map, MapBackgroundLayer, MapTileset};
 
@@ -76,7 +76,7 @@ impl Window for ImportWindow {
                     ui.push_skin(&gui_resources.skins.list_box_no_bg);
                 }
 
-                let entry_size = vec2(size.x, LIST_BOX_ENTRY_HEIGHT);
+                let entry_size = vec2(size.x, scaled(LIST_BOX_ENTRY_HEIGHT));
 
                 for (i, tileset) in self.tilesets.iter().enumerate() {
                     let is_selected = self.selected_tilesets.contains(&i);
@@ -111,7 +111,7 @@ impl Window for ImportWindow {
             });
 
         {
-            let position = vec2(0.0, (size.y * 0.8) + ELEMENT_MARGIN);
+            let position = vec2(0.0, (size.y * 0.8) + scaled(ELEMENT_MARGIN));
 
             let checkbox = Checkbox::new(
                 hash!(id, "background_checkbox"),
@@ -120,7 +120,7 @@ impl Window for ImportWindow {
             );
 
             checkbox
-                .with_margin(ELEMENT_MARGIN)
+                .with_margin(scaled(ELEMENT_MARGIN))
                 .ui(ui, &mut self.should_import_background);
         }
 
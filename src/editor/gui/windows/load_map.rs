@@ -4,16 +4,20 @@ use macroquad::{
     ui::{hash, widgets, Ui},
 };
 
-use crate::gui::{GuiResources, ELEMENT_MARGIN, LIST_BOX_ENTRY_HEIGHT};
+use crate::gui::{scaled, GuiResources, ELEMENT_MARGIN, LIST_BOX_ENTRY_HEIGHT};
 
-use crate::map::Map;
+use crate::map::{load_or_generate_map_thumbnail, Map};
 
 use super::{ButtonParams, EditorAction, EditorContext, Window, WindowParams};
+use crate::resources::MAP_PREVIEW_PLACEHOLDER_PATH;
 use crate::Resources;
 
 pub struct LoadMapWindow {
     params: WindowParams,
     index: Option<usize>,
+    tag_filter: String,
+    /// The generated thumbnail of the selected map, if it has no preview image
+    thumbnail: Option<(usize, Texture2D)>,
 }
 
 impl LoadMapWindow {
@@ -27,6 +31,8 @@ impl LoadMapWindow {
         LoadMapWindow {
             params,
             index: None,
+            tag_filter: String::new(),
+            thumbnail: None,
         }
     }
 }
@@ -51,7 +57,7 @@ impl Window for LoadMapWindow {
         }
 
         if let Some(index) = self.index {
-            let btn_size = vec2(size.x, LIST_BOX_ENTRY_HEIGHT);
+            let btn_size = vec2(size.x, scaled(LIST_BOX_ENTRY_HEIGHT));
             let btn_position = vec2(0.0, 0.0);
 
             let back_btn = widgets::Button::new("")
@@ -70,34 +76,100 @@ impl Window for LoadMapWindow {
                 let resources = storage::get::<Resources>();
                 let map_resource = resources.maps.get(index).unwrap();
 
+                let preview = if map_resource.meta.preview_path == MAP_PREVIEW_PLACEHOLDER_PATH {
+                    match self.thumbnail {
+                        Some((thumbnail_index, texture)) if thumbnail_index == index => texture,
+                        _ => {
+                            let texture = load_or_generate_map_thumbnail(
+                                &map_resource.map,
+                                &map_resource.meta.path,
+                            );
+
+                            self.thumbnail = Some((index, texture));
+
+                            texture
+                        }
+                    }
+                } else {
+                    map_resource.preview
+                };
+
                 let mut width = size.x;
-                let mut height =
-                    (width / map_resource.preview.width()) * map_resource.preview.height();
+                let mut height = (width / preview.width()) * preview.height();
 
-                let max_height = size.y - LIST_BOX_ENTRY_HEIGHT - (ELEMENT_MARGIN * 2.0);
+                let info_height = scaled(LIST_BOX_ENTRY_HEIGHT) * 3.0;
+                let max_height = size.y
+                    - scaled(LIST_BOX_ENTRY_HEIGHT)
+                    - info_height
+                    - (scaled(ELEMENT_MARGIN) * 3.0);
 
                 if height > max_height {
                     height = max_height;
-                    width = (height / map_resource.preview.height()) * map_resource.preview.width();
+                    width = (height / preview.height()) * preview.width();
                 }
 
-                let preview_position = vec2((size.x - width) / 2.0, btn_size.y + ELEMENT_MARGIN);
+                let preview_position =
+                    vec2((size.x - width) / 2.0, btn_size.y + scaled(ELEMENT_MARGIN));
 
-                widgets::Texture::new(map_resource.preview)
+                widgets::Texture::new(preview)
                     .size(width, height)
                     .position(preview_position)
                     .ui(ui);
+
+                let info = &map_resource.map.info;
+
+                let author = info.author.as_deref().unwrap_or("Unknown");
+                let players = info
+                    .recommended_players
+                    .map(|players| players.to_string())
+                    .unwrap_or_else(|| "Any".to_string());
+
+                let info_position = vec2(0.0, preview_position.y + height + scaled(ELEMENT_MARGIN));
+
+                ui.label(info_position, &format!("Author: {}", author));
+                ui.label(
+                    info_position + vec2(0.0, scaled(LIST_BOX_ENTRY_HEIGHT)),
+                    &format!("Recommended players: {}", players),
+                );
+                ui.label(
+                    info_position + vec2(0.0, scaled(LIST_BOX_ENTRY_HEIGHT) * 2.0),
+                    &format!("Tags: {}", info.tags.join(", ")),
+                );
             }
         } else {
-            let size = vec2(size.x, size.y - ELEMENT_MARGIN);
+            ui.pop_skin();
+
+            widgets::InputText::new(hash!(id, "tag_filter_input"))
+                .size(vec2(size.x * 0.6, scaled(LIST_BOX_ENTRY_HEIGHT)))
+                .ratio(1.0)
+                .label("Filter by tag")
+                .ui(ui, &mut self.tag_filter);
+
+            {
+                let gui_resources = storage::get::<GuiResources>();
+                ui.push_skin(&gui_resources.skins.list_box_no_bg);
+            }
+
+            let list_position = vec2(0.0, scaled(LIST_BOX_ENTRY_HEIGHT) + scaled(ELEMENT_MARGIN));
+
+            let size = vec2(size.x, size.y - list_position.y - scaled(ELEMENT_MARGIN));
             widgets::Group::new(hash!(id, "list_box"), size)
-                .position(Vec2::ZERO)
+                .position(list_position)
                 .ui(ui, |ui| {
                     let resources = storage::get::<Resources>();
 
-                    let entry_size = vec2(size.x, LIST_BOX_ENTRY_HEIGHT);
+                    let entry_size = vec2(size.x, scaled(LIST_BOX_ENTRY_HEIGHT));
+
+                    let map_resources =
+                        resources
+                            .maps
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, map_resource)| {
+                                map_resource.map.info.matches_tag_filter(&self.tag_filter)
+                            });
 
-                    for (i, map_resource) in resources.maps.iter().enumerate() {
+                    for (row, (i, map_resource)) in map_resources.enumerate() {
                         let mut is_selected = false;
                         if let Some(index) = self.index {
                             is_selected = index == i;
@@ -108,7 +180,7 @@ impl Window for LoadMapWindow {
                             ui.push_skin(&gui_resources.skins.list_box_selected);
                         }
 
-                        let entry_position = vec2(0.0, i as f32 * entry_size.y);
+                        let entry_position = vec2(0.0, row as f32 * entry_size.y);
 
                         let entry_btn = widgets::Button::new("")
                             .size(entry_size)
@@ -0,0 +1,87 @@
+use macroquad::{experimental::collections::storage, prelude::*, ui::Ui};
+
+use crate::gui::{scaled, LIST_BOX_ENTRY_HEIGHT};
+
+use crate::map::{Map, MapCostEstimate};
+use crate::{Config, Resources};
+
+use super::{EditorAction, EditorContext, Window, WindowParams};
+
+/// Shows an estimate of the runtime cost of the map, and flags any part of it that exceeds the
+/// thresholds in the editor config. The estimate is recomputed from the map data on every draw, so
+/// it is always up to date with the edits made while the window is open.
+pub struct MapCostWindow {
+    params: WindowParams,
+}
+
+impl MapCostWindow {
+    pub fn new() -> Self {
+        let params = WindowParams {
+            title: Some("Map Cost".to_string()),
+            size: vec2(300.0, 250.0),
+            ..Default::default()
+        };
+
+        MapCostWindow { params }
+    }
+}
+
+impl Window for MapCostWindow {
+    fn get_params(&self) -> &WindowParams {
+        &self.params
+    }
+
+    fn draw(
+        &mut self,
+        ui: &mut Ui,
+        _size: Vec2,
+        map: &Map,
+        _ctx: &EditorContext,
+    ) -> Option<EditorAction> {
+        let estimate = {
+            let resources = storage::get::<Resources>();
+            MapCostEstimate::new(map, &resources)
+        };
+
+        let config = storage::get::<Config>().editor.clone();
+
+        let rows = [
+            ("Tiles", estimate.tile_cnt, config.max_tiles),
+            (
+                "Decorations",
+                estimate.decoration_cnt,
+                config.max_decorations,
+            ),
+            (
+                "Particle emitters",
+                estimate.particle_emitter_cnt,
+                config.max_particle_emitters,
+            ),
+            ("Draw calls", estimate.draw_call_cnt, config.max_draw_calls),
+        ];
+
+        let entry_height = scaled(LIST_BOX_ENTRY_HEIGHT);
+        let mut position = Vec2::ZERO;
+
+        for (label, cnt, max_cnt) in rows {
+            let marker = if cnt > max_cnt { " (!)" } else { "" };
+
+            ui.label(
+                position,
+                &format!("{}: {} / {}{}", label, cnt, max_cnt, marker),
+            );
+
+            position.y += entry_height;
+        }
+
+        position.y += entry_height;
+
+        if estimate.exceeds(&config) {
+            ui.label(position, "This map is likely to run poorly!");
+        } else {
+            ui.label(position, "This map is within the cost thresholds");
+        }
+
+        None
+    }
+}
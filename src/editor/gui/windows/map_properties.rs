@@ -0,0 +1,145 @@
+use macroquad::{
+    prelude::*,
+    ui::{hash, widgets, Ui},
+};
+
+use crate::map::{Map, MapInfo};
+
+use crate::editor::session::EditorSessionWindow;
+
+use super::{ButtonParams, EditorAction, EditorContext, Window, WindowParams};
+
+pub struct MapPropertiesWindow {
+    params: WindowParams,
+    author: String,
+    description: String,
+    tags: String,
+    recommended_players: String,
+}
+
+impl MapPropertiesWindow {
+    pub fn new(info: &MapInfo) -> Self {
+        let params = WindowParams {
+            title: Some("Map Properties".to_string()),
+            size: vec2(350.0, 350.0),
+            ..Default::default()
+        };
+
+        MapPropertiesWindow {
+            params,
+            author: info.author.clone().unwrap_or_default(),
+            description: info.description.clone().unwrap_or_default(),
+            tags: info.tags.join(", "),
+            recommended_players: info
+                .recommended_players
+                .map(|players| players.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn get_info(&self) -> Option<MapInfo> {
+        let recommended_players = if self.recommended_players.trim().is_empty() {
+            None
+        } else {
+            let players = self.recommended_players.trim().parse::<u32>().ok()?;
+            Some(players)
+        };
+
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.tags.split(',') {
+            let tag = tag.trim();
+            if !tag.is_empty() && !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                tags.push(tag.to_string());
+            }
+        }
+
+        let author = Some(self.author.trim().to_string()).filter(|str| !str.is_empty());
+        let description = Some(self.description.clone()).filter(|str| !str.is_empty());
+
+        Some(MapInfo {
+            author,
+            description,
+            tags,
+            recommended_players,
+        })
+    }
+}
+
+impl Window for MapPropertiesWindow {
+    fn get_params(&self) -> &WindowParams {
+        &self.params
+    }
+
+    fn get_session_window(&self) -> Option<EditorSessionWindow> {
+        Some(EditorSessionWindow::MapProperties)
+    }
+
+    fn draw(
+        &mut self,
+        ui: &mut Ui,
+        _size: Vec2,
+        _map: &Map,
+        _ctx: &EditorContext,
+    ) -> Option<EditorAction> {
+        let id = hash!("map_properties_window");
+
+        let size = vec2(200.0, 25.0);
+
+        widgets::InputText::new(hash!(id, "author_input"))
+            .size(size)
+            .ratio(1.0)
+            .label("Author")
+            .ui(ui, &mut self.author);
+
+        ui.separator();
+
+        widgets::InputText::new(hash!(id, "description_input"))
+            .size(vec2(size.x, 75.0))
+            .ratio(1.0)
+            .label("Description")
+            .ui(ui, &mut self.description);
+
+        ui.separator();
+
+        widgets::InputText::new(hash!(id, "tags_input"))
+            .size(size)
+            .ratio(1.0)
+            .label("Tags")
+            .ui(ui, &mut self.tags);
+
+        ui.label(None, "Separate tags with commas");
+
+        ui.separator();
+
+        widgets::InputText::new(hash!(id, "recommended_players_input"))
+            .size(vec2(75.0, 25.0))
+            .ratio(1.0)
+            .label("Recommended players")
+            .ui(ui, &mut self.recommended_players);
+
+        None
+    }
+
+    fn get_buttons(&self, _map: &Map, _ctx: &EditorContext) -> Vec<ButtonParams> {
+        let mut res = Vec::new();
+
+        let action = self.get_info().map(|info| {
+            self.get_close_action()
+                .then(EditorAction::UpdateMapInfo(info))
+        });
+
+        res.push(ButtonParams {
+            label: "Save",
+            action,
+            ..Default::default()
+        });
+
+        res.push(ButtonParams {
+            label: "Cancel",
+            action: Some(self.get_close_action()),
+            ..Default::default()
+        });
+
+        res
+    }
+}
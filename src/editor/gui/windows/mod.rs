@@ -9,8 +9,12 @@ mod background_properties;
 mod create_layer;
 mod create_map;
 mod create_object;
+mod generate_map;
+mod history;
 mod import;
 mod load_map;
+mod map_cost;
+mod map_properties;
 mod object_properties;
 mod save_map;
 mod tile_properties;
@@ -22,8 +26,12 @@ pub use create_layer::CreateLayerWindow;
 pub use create_map::CreateMapWindow;
 pub use create_object::CreateObjectWindow;
 pub use create_tileset::CreateTilesetWindow;
+pub use generate_map::GenerateMapWindow;
+pub use history::HistoryWindow;
 pub use import::ImportWindow;
 pub use load_map::LoadMapWindow;
+pub use map_cost::MapCostWindow;
+pub use map_properties::MapPropertiesWindow;
 pub use object_properties::ObjectPropertiesWindow;
 pub use save_map::SaveMapWindow;
 pub use tile_properties::TilePropertiesWindow;
@@ -31,6 +39,9 @@ pub use tileset_properties::TilesetPropertiesWindow;
 
 use super::{ButtonParams, EditorAction, EditorContext, Map};
 
+use crate::editor::session::EditorSessionWindow;
+use crate::gui::scaled_vec2;
+
 pub const WINDOW_BUTTON_MIN_WIDTH: f32 = 64.0;
 pub const WINDOW_BUTTON_MAX_WIDTH: f32 = 96.0;
 
@@ -62,8 +73,13 @@ pub struct WindowParams {
 }
 
 impl WindowParams {
+    /// Returns the size of the window, multiplied by the UI scale
+    pub fn get_scaled_size(&self) -> Vec2 {
+        scaled_vec2(self.size)
+    }
+
     pub fn get_absolute_position(&self) -> Vec2 {
-        self.position.to_absolute(self.size)
+        self.position.to_absolute(self.get_scaled_size())
     }
 }
 
@@ -107,14 +123,14 @@ pub trait Window {
     ) -> Option<EditorAction>;
 
     fn get_absolute_position(&self) -> Vec2 {
-        let params = self.get_params();
-        params.position.to_absolute(params.size)
+        self.get_params().get_absolute_position()
     }
 
     fn get_rect(&self) -> Rect {
         let params = self.get_params();
-        let position = params.position.to_absolute(params.size);
-        Rect::new(position.x, position.y, params.size.x, params.size.y)
+        let position = params.get_absolute_position();
+        let size = params.get_scaled_size();
+        Rect::new(position.x, position.y, size.x, size.y)
     }
 
     fn contains(&self, point: Vec2) -> bool {
@@ -129,4 +145,9 @@ pub trait Window {
         let id = TypeId::of::<Self>();
         EditorAction::CloseWindow(id)
     }
+
+    // Implement this for windows that should be reopened when the editor session is restored
+    fn get_session_window(&self) -> Option<EditorSessionWindow> {
+        None
+    }
 }
@@ -12,6 +12,8 @@ use crate::{
     Resources,
 };
 
+use crate::editor::session::EditorSessionWindow;
+
 use super::{ButtonParams, EditorAction, EditorContext, Window, WindowParams};
 
 pub struct ObjectPropertiesWindow {
@@ -43,6 +45,13 @@ impl Window for ObjectPropertiesWindow {
         &self.params
     }
 
+    fn get_session_window(&self) -> Option<EditorSessionWindow> {
+        Some(EditorSessionWindow::ObjectProperties {
+            layer_id: self.layer_id.clone(),
+            index: self.index,
+        })
+    }
+
     fn get_buttons(&self, _map: &Map, _ctx: &EditorContext) -> Vec<ButtonParams> {
         let mut res = Vec::new();
 
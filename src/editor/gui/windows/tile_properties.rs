@@ -7,6 +7,8 @@ use crate::map::Map;
 
 use crate::gui::Checkbox;
 
+use crate::editor::session::EditorSessionWindow;
+
 use super::{ButtonParams, EditorAction, EditorContext, Window, WindowParams};
 
 const JUMPTHROUGH_ATTRIBUTE: &str = "jumpthrough";
@@ -40,6 +42,13 @@ impl Window for TilePropertiesWindow {
         &self.params
     }
 
+    fn get_session_window(&self) -> Option<EditorSessionWindow> {
+        Some(EditorSessionWindow::TileProperties {
+            layer_id: self.layer_id.clone(),
+            index: self.index,
+        })
+    }
+
     fn get_buttons(&self, _map: &Map, _ctx: &EditorContext) -> Vec<ButtonParams> {
         let mut res = Vec::new();
 
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use macroquad::{
     experimental::collections::storage,
     prelude::*,
@@ -8,14 +10,17 @@ use crate::editor::gui::combobox::ComboBoxVec;
 use crate::editor::gui::{ComboBoxBuilder, ComboBoxValue};
 use crate::{gui::GuiResources, Resources};
 
+use crate::editor::session::EditorSessionWindow;
+
 use super::{ButtonParams, EditorAction, EditorContext, Map, Window, WindowParams};
-use crate::map::MapTileset;
+use crate::map::{MapTileCollision, MapTileset};
 use crate::resources::TextureKind;
 
 pub struct TilesetPropertiesWindow {
     params: WindowParams,
     tileset_id: String,
     autotile_mask: Vec<bool>,
+    tile_collisions: HashMap<u32, MapTileCollision>,
     texture: ComboBoxVec,
     has_data: bool,
 }
@@ -49,6 +54,7 @@ impl TilesetPropertiesWindow {
             params,
             tileset_id: tileset_id.to_string(),
             autotile_mask: Vec::new(),
+            tile_collisions: HashMap::new(),
             texture,
             has_data: false,
         }
@@ -70,10 +76,118 @@ impl TilesetPropertiesWindow {
                 }
             }
 
+            self.tile_collisions = tileset.tile_collisions.clone();
+
             self.has_data = true;
         }
     }
 
+    fn get_tile_collision(&self, tileset: &MapTileset, tile_id: u32) -> MapTileCollision {
+        self.tile_collisions
+            .get(&tile_id)
+            .copied()
+            .unwrap_or_else(|| {
+                let is_platform = tileset
+                    .tile_attributes
+                    .get(&tile_id)
+                    .map(|attributes| {
+                        attributes.contains(&Map::PLATFORM_TILE_ATTRIBUTE.to_string())
+                    })
+                    .unwrap_or(false);
+
+                if is_platform {
+                    MapTileCollision::Platform
+                } else {
+                    MapTileCollision::Solid
+                }
+            })
+    }
+
+    fn draw_collision_settings(
+        &mut self,
+        ui: &mut Ui,
+        position: Vec2,
+        size: Vec2,
+        tileset: &MapTileset,
+    ) {
+        let texture_entry = {
+            let resources = storage::get::<Resources>();
+            resources
+                .textures
+                .get(&tileset.texture_id)
+                .cloned()
+                .unwrap()
+        };
+
+        let tileset_texture_size = vec2(
+            texture_entry.texture.width(),
+            texture_entry.texture.height(),
+        );
+
+        let mut scaled_width = size.x;
+        let mut scaled_height = (scaled_width / tileset_texture_size.x) * tileset_texture_size.y;
+
+        if scaled_height > size.y {
+            scaled_height = size.y;
+            scaled_width = (scaled_height / tileset_texture_size.y) * tileset_texture_size.x;
+        }
+
+        let scaled_tile_size = vec2(
+            scaled_width / tileset.grid_size.x as f32,
+            scaled_height / tileset.grid_size.y as f32,
+        );
+
+        widgets::Texture::new(texture_entry.texture)
+            .size(scaled_width, scaled_height)
+            .position(position)
+            .ui(ui);
+
+        {
+            let gui_resources = storage::get::<GuiResources>();
+            ui.push_skin(&gui_resources.skins.tileset_subtile_grid);
+        }
+
+        for y in 0..tileset.grid_size.y {
+            for x in 0..tileset.grid_size.x {
+                let tile_id = y * tileset.grid_size.x + x;
+
+                let collision = self.get_tile_collision(tileset, tile_id);
+
+                let is_highlighted = collision != MapTileCollision::Solid;
+
+                if is_highlighted {
+                    let gui_resources = storage::get::<GuiResources>();
+                    ui.push_skin(&gui_resources.skins.tileset_subtile_grid_selected);
+                }
+
+                let tile_position = position + vec2(x as f32, y as f32) * scaled_tile_size;
+
+                let was_clicked = widgets::Button::new("")
+                    .size(scaled_tile_size)
+                    .position(tile_position)
+                    .ui(ui);
+
+                let label = match collision {
+                    MapTileCollision::Solid => "S",
+                    MapTileCollision::Platform => "P",
+                    MapTileCollision::None => "-",
+                };
+
+                ui.label(tile_position, label);
+
+                if is_highlighted {
+                    ui.pop_skin();
+                }
+
+                if was_clicked {
+                    self.tile_collisions.insert(tile_id, collision.next());
+                }
+            }
+        }
+
+        ui.pop_skin();
+    }
+
     #[allow(dead_code)]
     fn draw_autotile_settings(
         &mut self,
@@ -160,14 +274,20 @@ impl Window for TilesetPropertiesWindow {
         &self.params
     }
 
+    fn get_session_window(&self) -> Option<EditorSessionWindow> {
+        Some(EditorSessionWindow::TilesetProperties {
+            tileset_id: self.tileset_id.clone(),
+        })
+    }
+
     fn draw(
         &mut self,
         ui: &mut Ui,
-        _size: Vec2,
+        size: Vec2,
         map: &Map,
         _ctx: &EditorContext,
     ) -> Option<EditorAction> {
-        if let Some(_tileset) = map.tilesets.get(&self.tileset_id) {
+        if let Some(tileset) = map.tilesets.get(&self.tileset_id) {
             let id = hash!("tileset_properties_window");
 
             if !self.has_data {
@@ -194,6 +314,19 @@ impl Window for TilesetPropertiesWindow {
                     .with_ratio(0.8)
                     .with_label("Texture")
                     .build(ui, &mut self.texture);
+
+                ui.separator();
+
+                ui.label(
+                    None,
+                    "Tile collisions (click to cycle): S = solid, P = platform, - = none",
+                );
+
+                let group_size = vec2(size.x, size.y * 0.75);
+
+                widgets::Group::new(hash!(id, "tile_collisions"), group_size).ui(ui, |ui| {
+                    self.draw_collision_settings(ui, Vec2::ZERO, group_size, tileset);
+                });
             }
         }
 
@@ -209,6 +342,7 @@ impl Window for TilesetPropertiesWindow {
             id: self.tileset_id.clone(),
             texture_id: self.texture.get_value(),
             autotile_mask,
+            tile_collisions: self.tile_collisions.clone(),
         });
 
         res.push(ButtonParams {
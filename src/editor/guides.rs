@@ -0,0 +1,183 @@
+use macroquad::prelude::*;
+
+use super::EditorCamera;
+
+/// The distance, in screen pixels, at which a dragged object will snap to a guide
+pub const SNAP_GUIDE_TOLERANCE: f32 = 8.0;
+
+pub const SNAP_GUIDE_LINE_WIDTH: f32 = 1.0;
+pub const SNAP_GUIDE_COLOR: Color = Color {
+    r: 0.95,
+    g: 0.25,
+    b: 0.75,
+    a: 0.9,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapGuideAxis {
+    /// A vertical line, at a position on the x axis
+    Vertical,
+    /// A horizontal line, at a position on the y axis
+    Horizontal,
+}
+
+/// An alignment guide that a dragged object has been snapped to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapGuide {
+    pub axis: SnapGuideAxis,
+    pub position: f32,
+}
+
+impl SnapGuide {
+    pub fn new(axis: SnapGuideAxis, position: f32) -> Self {
+        SnapGuide { axis, position }
+    }
+
+    /// Draw the guide across the specified bounds
+    pub fn draw(&self, bounds: Rect) {
+        let (begin, end) = match self.axis {
+            SnapGuideAxis::Vertical => (
+                vec2(self.position, bounds.y),
+                vec2(self.position, bounds.y + bounds.h),
+            ),
+            SnapGuideAxis::Horizontal => (
+                vec2(bounds.x, self.position),
+                vec2(bounds.x + bounds.w, self.position),
+            ),
+        };
+
+        draw_line(
+            begin.x,
+            begin.y,
+            end.x,
+            end.y,
+            SNAP_GUIDE_LINE_WIDTH,
+            SNAP_GUIDE_COLOR,
+        );
+    }
+}
+
+/// The tile grid of a map, used to get snap candidates along tile boundaries
+#[derive(Debug, Clone, Copy)]
+pub struct SnapGrid {
+    pub offset: Vec2,
+    pub tile_size: Vec2,
+}
+
+impl SnapGrid {
+    fn nearest_line(offset: f32, tile_size: f32, position: f32) -> f32 {
+        offset + ((position - offset) / tile_size).round() * tile_size
+    }
+}
+
+/// Returns the snap tolerance in world units, for `SNAP_GUIDE_TOLERANCE` screen pixels, at the
+/// current zoom level of the camera.
+pub fn get_snap_tolerance(camera: &EditorCamera) -> f32 {
+    let unit = camera.to_screen_space(vec2(1.0, 1.0)) - camera.to_screen_space(Vec2::ZERO);
+
+    if unit.x > 0.0 {
+        SNAP_GUIDE_TOLERANCE / unit.x
+    } else {
+        SNAP_GUIDE_TOLERANCE
+    }
+}
+
+fn get_edges(min: f32, size: f32) -> [f32; 3] {
+    [min, min + size / 2.0, min + size]
+}
+
+fn snap_axis(edges: [f32; 3], candidates: &[f32], tolerance: f32) -> Option<(f32, Vec<f32>)> {
+    let mut best: Option<f32> = None;
+
+    for edge in edges {
+        for &candidate in candidates {
+            let delta = candidate - edge;
+
+            if delta.abs() <= tolerance && best.map(|d| delta.abs() < d.abs()).unwrap_or(true) {
+                best = Some(delta);
+            }
+        }
+    }
+
+    best.map(|delta| {
+        let mut lines = Vec::new();
+
+        for edge in edges {
+            let edge = edge + delta;
+
+            for &candidate in candidates {
+                if (candidate - edge).abs() <= f32::EPSILON * edge.abs().max(1.0)
+                    && !lines.contains(&candidate)
+                {
+                    lines.push(candidate);
+                }
+            }
+        }
+
+        (delta, lines)
+    })
+}
+
+/// Snap `rect` to the edges and centers of `targets` and, if a grid is specified, the tile
+/// boundaries of that grid. Each axis is snapped independently, to the closest candidate within
+/// `tolerance`. Returns the snapped position of the rect and the guides that it was snapped to.
+pub fn snap_to_guides(
+    rect: Rect,
+    targets: &[Rect],
+    grid: Option<SnapGrid>,
+    tolerance: f32,
+) -> (Vec2, Vec<SnapGuide>) {
+    let mut position = rect.point();
+    let mut guides = Vec::new();
+
+    let edges_x = get_edges(rect.x, rect.w);
+    let edges_y = get_edges(rect.y, rect.h);
+
+    let mut candidates_x = Vec::new();
+    let mut candidates_y = Vec::new();
+
+    for target in targets {
+        candidates_x.extend(get_edges(target.x, target.w));
+        candidates_y.extend(get_edges(target.y, target.h));
+    }
+
+    if let Some(grid) = grid {
+        for edge in [edges_x[0], edges_x[2]] {
+            candidates_x.push(SnapGrid::nearest_line(
+                grid.offset.x,
+                grid.tile_size.x,
+                edge,
+            ));
+        }
+
+        for edge in [edges_y[0], edges_y[2]] {
+            candidates_y.push(SnapGrid::nearest_line(
+                grid.offset.y,
+                grid.tile_size.y,
+                edge,
+            ));
+        }
+    }
+
+    if let Some((delta, lines)) = snap_axis(edges_x, &candidates_x, tolerance) {
+        position.x += delta;
+
+        guides.extend(
+            lines
+                .into_iter()
+                .map(|x| SnapGuide::new(SnapGuideAxis::Vertical, x)),
+        );
+    }
+
+    if let Some((delta, lines)) = snap_axis(edges_y, &candidates_y, tolerance) {
+        position.y += delta;
+
+        guides.extend(
+            lines
+                .into_iter()
+                .map(|y| SnapGuide::new(SnapGuideAxis::Horizontal, y)),
+        );
+    }
+
+    (position, guides)
+}
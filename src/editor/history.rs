@@ -1,32 +1,107 @@
+use core::config::EditorConfig;
 use core::Result;
 
 use crate::Map;
 
-use super::UndoableAction;
+use super::{BatchAction, UndoableAction};
 
 pub struct EditorHistory {
     undo_stack: Vec<Box<dyn UndoableAction>>,
     redo_stack: Vec<Box<dyn UndoableAction>>,
+    /// The actions applied since the outermost call to `begin_batch`, if a batch is open
+    batch: Option<Vec<Box<dyn UndoableAction>>>,
+    batch_depth: usize,
+    /// The maximum number of steps that can be undone. The oldest steps are discarded once this
+    /// is exceeded.
+    max_depth: usize,
 }
 
 impl EditorHistory {
-    pub fn new() -> Self {
+    pub fn with_max_depth(max_depth: usize) -> Self {
         EditorHistory {
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            batch: None,
+            batch_depth: 0,
+            max_depth,
         }
     }
 
     pub fn apply(&mut self, mut action: Box<dyn UndoableAction>, map: &mut Map) -> Result<()> {
         if !action.is_redundant(map) {
-            action.apply(map)?;
-            self.undo_stack.push(action);
-            self.redo_stack.clear();
+            if let Some(batch) = &mut self.batch {
+                if let Err(err) = action.apply(map) {
+                    // Roll back the rest of the batch, so that it is applied atomically, and close
+                    // it, so that callers can tell that they should not apply any more of it
+                    let actions = std::mem::take(batch);
+                    self.batch = None;
+                    self.batch_depth = 0;
+
+                    BatchAction::new(actions).undo(map)?;
+
+                    return Err(err);
+                }
+
+                batch.push(action);
+            } else {
+                action.apply(map)?;
+                self.push(action);
+            }
         }
 
         Ok(())
     }
 
+    /// Begin a batch. All actions applied until the matching call to `end_batch` will be added to
+    /// the history as one step, that is undone and redone as a whole. Batches can be nested, in
+    /// which case the inner batches become part of the outermost one.
+    pub fn begin_batch(&mut self) {
+        if self.batch_depth == 0 {
+            self.batch = Some(Vec::new());
+        }
+
+        self.batch_depth += 1;
+    }
+
+    /// Returns `true` if a batch is open. A batch is closed early if one of its actions fails.
+    pub fn is_batch_open(&self) -> bool {
+        self.batch.is_some()
+    }
+
+    /// End a batch started with `begin_batch`. This does nothing if the batch has already been
+    /// closed, because one of its actions failed.
+    pub fn end_batch(&mut self) {
+        if self.batch_depth == 0 {
+            return;
+        }
+
+        self.batch_depth -= 1;
+
+        if self.batch_depth == 0 {
+            if let Some(mut actions) = self.batch.take() {
+                if !actions.is_empty() {
+                    let action: Box<dyn UndoableAction> = if actions.len() == 1 {
+                        actions.remove(0)
+                    } else {
+                        Box::new(BatchAction::new(actions))
+                    };
+
+                    self.push(action);
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, action: Box<dyn UndoableAction>) {
+        self.undo_stack.push(action);
+        self.redo_stack.clear();
+
+        if self.undo_stack.len() > self.max_depth {
+            let excess = self.undo_stack.len() - self.max_depth;
+            self.undo_stack.drain(..excess);
+        }
+    }
+
     pub fn undo(&mut self, map: &mut Map) -> Result<()> {
         if let Some(mut action) = self.undo_stack.pop() {
             action.undo(map)?;
@@ -45,8 +120,286 @@ impl EditorHistory {
         Ok(())
     }
 
+    /// Returns the descriptions of all steps in the history, oldest first, and the index of the
+    /// current state. Steps before the index are applied, while the rest can be redone.
+    pub fn get_entries(&self) -> (Vec<String>, usize) {
+        let entries = self
+            .undo_stack
+            .iter()
+            .chain(self.redo_stack.iter().rev())
+            .map(|action| action.description())
+            .collect();
+
+        (entries, self.undo_stack.len())
+    }
+
+    /// Undo or redo steps until `index` steps are applied. Indices beyond the end of the history
+    /// will redo all steps.
+    pub fn jump_to(&mut self, index: usize, map: &mut Map) -> Result<()> {
+        while self.undo_stack.len() > index {
+            self.undo(map)?;
+        }
+
+        while self.undo_stack.len() < index && !self.redo_stack.is_empty() {
+            self.redo(map)?;
+        }
+
+        Ok(())
+    }
+
     pub fn clear(&mut self) {
         self.undo_stack.clear();
         self.redo_stack.clear();
+
+        if let Some(batch) = &mut self.batch {
+            batch.clear();
+        }
+    }
+}
+
+impl Default for EditorHistory {
+    fn default() -> Self {
+        Self::with_max_depth(EditorConfig::default_history_depth())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::prelude::*;
+
+    use core::error::{Error, ErrorKind};
+
+    use super::*;
+
+    /// Adds a spawn point at the specified x position, so that the order of applied actions can
+    /// be read back from the map.
+    struct PushSpawnPointAction {
+        x: f32,
+        should_fail: bool,
+    }
+
+    impl PushSpawnPointAction {
+        fn new(x: f32) -> Box<Self> {
+            Box::new(PushSpawnPointAction {
+                x,
+                should_fail: false,
+            })
+        }
+
+        fn failing() -> Box<Self> {
+            Box::new(PushSpawnPointAction {
+                x: 0.0,
+                should_fail: true,
+            })
+        }
+    }
+
+    impl UndoableAction for PushSpawnPointAction {
+        fn apply(&mut self, map: &mut Map) -> Result<()> {
+            if self.should_fail {
+                return Err(Error::new_const(ErrorKind::EditorAction, &"Test failure"));
+            }
+
+            map.spawn_points.push(vec2(self.x, 0.0));
+
+            Ok(())
+        }
+
+        fn undo(&mut self, map: &mut Map) -> Result<()> {
+            assert_eq!(map.spawn_points.pop(), Some(vec2(self.x, 0.0)));
+
+            Ok(())
+        }
+
+        fn description(&self) -> String {
+            format!("Push {}", self.x)
+        }
+    }
+
+    fn spawn_points_x(map: &Map) -> Vec<f32> {
+        map.spawn_points.iter().map(|point| point.x).collect()
+    }
+
+    #[test]
+    fn test_batch_is_one_undo_step() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(10, 10));
+        let mut history = EditorHistory::default();
+
+        history
+            .apply(PushSpawnPointAction::new(0.0), &mut map)
+            .unwrap();
+
+        history.begin_batch();
+        for x in [1.0, 2.0, 3.0] {
+            history
+                .apply(PushSpawnPointAction::new(x), &mut map)
+                .unwrap();
+        }
+        history.end_batch();
+
+        assert_eq!(spawn_points_x(&map), vec![0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(history.undo_stack.len(), 2);
+
+        history.undo(&mut map).unwrap();
+        assert_eq!(spawn_points_x(&map), vec![0.0]);
+
+        history.redo(&mut map).unwrap();
+        assert_eq!(spawn_points_x(&map), vec![0.0, 1.0, 2.0, 3.0]);
+
+        history.undo(&mut map).unwrap();
+        history.undo(&mut map).unwrap();
+        assert!(map.spawn_points.is_empty());
+    }
+
+    #[test]
+    fn test_nested_batches_are_merged() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(10, 10));
+        let mut history = EditorHistory::default();
+
+        history.begin_batch();
+        history
+            .apply(PushSpawnPointAction::new(1.0), &mut map)
+            .unwrap();
+        history.begin_batch();
+        history
+            .apply(PushSpawnPointAction::new(2.0), &mut map)
+            .unwrap();
+        history.end_batch();
+        assert!(history.undo_stack.is_empty());
+        history
+            .apply(PushSpawnPointAction::new(3.0), &mut map)
+            .unwrap();
+        history.end_batch();
+
+        assert_eq!(history.undo_stack.len(), 1);
+
+        history.undo(&mut map).unwrap();
+        assert!(map.spawn_points.is_empty());
+
+        history.redo(&mut map).unwrap();
+        assert_eq!(spawn_points_x(&map), vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_failed_batch_is_rolled_back() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(10, 10));
+        let mut history = EditorHistory::default();
+
+        history.begin_batch();
+        history
+            .apply(PushSpawnPointAction::new(1.0), &mut map)
+            .unwrap();
+        history
+            .apply(PushSpawnPointAction::new(2.0), &mut map)
+            .unwrap();
+        assert!(history
+            .apply(PushSpawnPointAction::failing(), &mut map)
+            .is_err());
+        assert!(!history.is_batch_open());
+        history.end_batch();
+
+        assert!(map.spawn_points.is_empty());
+        assert!(history.undo_stack.is_empty());
+    }
+
+    #[test]
+    fn test_failed_batch_is_closed() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(10, 10));
+        let mut history = EditorHistory::default();
+
+        // The failure happens in the middle of a nested batch, so the actions that come after it,
+        // in both the inner and the outer batch, should not be applied by the caller
+        let actions = [
+            PushSpawnPointAction::new(1.0),
+            PushSpawnPointAction::failing(),
+            PushSpawnPointAction::new(2.0),
+        ];
+
+        history.begin_batch();
+        history.begin_batch();
+
+        let mut applied_cnt = 0;
+        for action in actions {
+            if history.apply(action, &mut map).is_err() || !history.is_batch_open() {
+                break;
+            }
+
+            applied_cnt += 1;
+        }
+
+        assert_eq!(applied_cnt, 1);
+        assert!(!history.is_batch_open());
+
+        history.end_batch();
+        history.end_batch();
+
+        assert!(map.spawn_points.is_empty());
+        assert!(history.undo_stack.is_empty());
+
+        // Actions applied after the failed batch are not added to it, but to the history as usual
+        history
+            .apply(PushSpawnPointAction::new(3.0), &mut map)
+            .unwrap();
+
+        assert_eq!(spawn_points_x(&map), vec![3.0]);
+        assert_eq!(history.undo_stack.len(), 1);
+    }
+
+    #[test]
+    fn test_empty_batch_keeps_redo_stack() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(10, 10));
+        let mut history = EditorHistory::default();
+
+        history
+            .apply(PushSpawnPointAction::new(1.0), &mut map)
+            .unwrap();
+        history.undo(&mut map).unwrap();
+
+        history.begin_batch();
+        history.end_batch();
+
+        assert_eq!(history.redo_stack.len(), 1);
+    }
+
+    #[test]
+    fn test_jump_to_history_index() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(10, 10));
+        let mut history = EditorHistory::default();
+
+        for x in [1.0, 2.0, 3.0] {
+            history
+                .apply(PushSpawnPointAction::new(x), &mut map)
+                .unwrap();
+        }
+
+        history.jump_to(1, &mut map).unwrap();
+        assert_eq!(spawn_points_x(&map), vec![1.0]);
+
+        let (entries, index) = history.get_entries();
+        assert_eq!(entries, vec!["Push 1", "Push 2", "Push 3"]);
+        assert_eq!(index, 1);
+
+        history.jump_to(3, &mut map).unwrap();
+        assert_eq!(spawn_points_x(&map), vec![1.0, 2.0, 3.0]);
+
+        history.jump_to(0, &mut map).unwrap();
+        assert!(map.spawn_points.is_empty());
+    }
+
+    #[test]
+    fn test_history_depth_is_bounded() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(10, 10));
+        let mut history = EditorHistory::with_max_depth(10);
+
+        for i in 0..15 {
+            history
+                .apply(PushSpawnPointAction::new(i as f32), &mut map)
+                .unwrap();
+        }
+
+        let (entries, index) = history.get_entries();
+        assert_eq!(entries.len(), 10);
+        assert_eq!(index, 10);
+        assert_eq!(entries[0], "Push 5");
     }
 }
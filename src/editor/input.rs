@@ -2,12 +2,25 @@ use macroquad::{experimental::collections::storage, prelude::*};
 
 use fishsticks::{Axis, Button};
 
+use core::input::get_analog_stick_value;
+
+use crate::map::MapObjectKind;
+
+use super::quick_save::QUICK_SAVE_SLOT_CNT;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EditorInputScheme {
     Mouse,
     Gamepad(fishsticks::GamepadId),
 }
 
+/// The things that the camera can be moved to, one after the other, to review them on large maps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorFocusTarget {
+    SpawnPoint,
+    Object(MapObjectKind),
+}
+
 #[derive(Debug, Default, Clone, Copy)]
 pub struct EditorInput {
     pub action: bool,
@@ -17,16 +30,37 @@ pub struct EditorInput {
     pub camera_mouse_move: bool,
     pub camera_zoom: f32,
     pub cursor_move_direction: Vec2,
+    /// The direction that the selection is nudged in, while the keys are held. This is repeated
+    /// through a `KeyRepeat`, so it should not be applied every frame.
+    pub nudge_direction: Vec2,
+    /// The direction, `-1` or `1`, that the selected tile is cycled in, while the keys are held.
+    /// This is repeated through a `KeyRepeat`, like `nudge_direction`.
+    pub cycle_tile: i32,
     pub undo: bool,
     pub redo: bool,
     pub toggle_menu: bool,
     pub toggle_draw_grid: bool,
     pub toggle_snap_to_grid: bool,
     pub toggle_disable_parallax: bool,
+    pub disable_snap_guides: bool,
+    pub extend_selection: bool,
+    /// While this is held, dragging on a tile layer will box-select a region of tiles
+    pub box_select: bool,
+    /// While this is held, dragging with the tile placement tool will fill a rectangle of tiles
+    pub rect_fill: bool,
     pub save: bool,
     pub save_as: bool,
     pub load: bool,
+    pub quick_save: bool,
+    pub quick_load: bool,
+    /// The quick-save slot selected this frame, if any
+    pub quick_save_slot: Option<usize>,
+    /// Copy the selection to the clipboard file, so that it can be pasted in any map
+    pub copy: bool,
+    pub paste: bool,
     pub delete: bool,
+    /// Move the camera to the next spawn point, or object of a kind, if any
+    pub focus_next: Option<EditorFocusTarget>,
 }
 
 pub fn collect_editor_input(scheme: EditorInputScheme) -> EditorInput {
@@ -38,6 +72,11 @@ pub fn collect_editor_input(scheme: EditorInputScheme) -> EditorInput {
             input.camera_mouse_move = is_mouse_button_down(MouseButton::Middle);
             input.context_menu = is_mouse_button_pressed(MouseButton::Right);
 
+            input.disable_snap_guides = is_key_down(KeyCode::LeftAlt);
+            input.extend_selection = is_key_down(KeyCode::LeftShift);
+            input.box_select = is_key_down(KeyCode::LeftControl);
+            input.rect_fill = is_key_down(KeyCode::LeftShift);
+
             let (_, zoom) = mouse_wheel();
             if zoom < 0.0 {
                 input.camera_zoom = -1.0;
@@ -56,6 +95,18 @@ pub fn collect_editor_input(scheme: EditorInputScheme) -> EditorInput {
 
                 input.toggle_snap_to_grid = is_key_pressed(KeyCode::G);
 
+                if is_key_down(KeyCode::Left) {
+                    input.nudge_direction.x = -1.0;
+                } else if is_key_down(KeyCode::Right) {
+                    input.nudge_direction.x = 1.0;
+                }
+
+                if is_key_down(KeyCode::Up) {
+                    input.nudge_direction.y = -1.0;
+                } else if is_key_down(KeyCode::Down) {
+                    input.nudge_direction.y = 1.0;
+                }
+
                 if is_key_pressed(KeyCode::S) {
                     if is_key_down(KeyCode::LeftShift) {
                         input.save_as = true;
@@ -67,6 +118,13 @@ pub fn collect_editor_input(scheme: EditorInputScheme) -> EditorInput {
                 if is_key_pressed(KeyCode::L) {
                     input.load = true;
                 }
+
+                input.copy = is_key_pressed(KeyCode::C);
+                input.paste = is_key_pressed(KeyCode::V);
+
+                let slot_keys: [KeyCode; QUICK_SAVE_SLOT_CNT] =
+                    [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+                input.quick_save_slot = slot_keys.iter().position(|&key| is_key_pressed(key));
             } else {
                 if is_key_pressed(KeyCode::Escape) {
                     input.toggle_menu = true;
@@ -89,7 +147,26 @@ pub fn collect_editor_input(scheme: EditorInputScheme) -> EditorInput {
 
                 input.toggle_disable_parallax = is_key_pressed(KeyCode::P);
 
+                if is_key_down(KeyCode::LeftBracket) {
+                    input.cycle_tile = -1;
+                } else if is_key_down(KeyCode::RightBracket) {
+                    input.cycle_tile = 1;
+                }
+
                 input.delete = is_key_pressed(KeyCode::Delete);
+
+                input.quick_save = is_key_pressed(KeyCode::F5);
+                input.quick_load = is_key_pressed(KeyCode::F9);
+
+                if is_key_pressed(KeyCode::Tab) {
+                    input.focus_next = Some(EditorFocusTarget::SpawnPoint);
+                } else if is_key_pressed(KeyCode::Key1) {
+                    input.focus_next = Some(EditorFocusTarget::Object(MapObjectKind::Item));
+                } else if is_key_pressed(KeyCode::Key2) {
+                    input.focus_next = Some(EditorFocusTarget::Object(MapObjectKind::Decoration));
+                } else if is_key_pressed(KeyCode::Key3) {
+                    input.focus_next = Some(EditorFocusTarget::Object(MapObjectKind::Environment));
+                }
             }
         }
         EditorInputScheme::Gamepad(ix) => {
@@ -101,23 +178,11 @@ pub fn collect_editor_input(scheme: EditorInputScheme) -> EditorInput {
                 input.back = gamepad.digital_inputs.activated(Button::South);
                 input.context_menu = gamepad.digital_inputs.activated(Button::West);
 
-                input.camera_move_direction = {
-                    let direction_x = gamepad.analog_inputs.value(Axis::LeftStickX);
-                    let direction_y = gamepad.analog_inputs.value(Axis::LeftStickY);
-
-                    let direction = vec2(direction_x, direction_y);
-
-                    direction.normalize_or_zero()
-                };
-
-                input.cursor_move_direction = {
-                    let direction_x = gamepad.analog_inputs.value(Axis::RightStickX);
-                    let direction_y = gamepad.analog_inputs.value(Axis::RightStickY);
-
-                    let direction = vec2(direction_x, direction_y);
+                input.camera_move_direction =
+                    get_analog_stick_value(gamepad, Axis::LeftStickX, Axis::LeftStickY);
 
-                    direction.normalize_or_zero()
-                };
+                input.cursor_move_direction =
+                    get_analog_stick_value(gamepad, Axis::RightStickX, Axis::RightStickY);
             }
         }
     }
@@ -0,0 +1,83 @@
+/// This is used to trigger a discrete editor action, like nudging the selection, repeatedly while
+/// a key is held down. The action is triggered once when the key is pressed and then, after an
+/// initial delay, repeatedly at a faster rate, like typing in a text field.
+#[derive(Debug, Clone, Copy)]
+pub struct KeyRepeat {
+    /// The time, in seconds, that a key must be held before the action starts repeating
+    pub delay: f32,
+    /// The number of times, per second, that the action is repeated, once the delay has passed
+    pub rate: f32,
+    timer: f32,
+    is_down: bool,
+}
+
+impl KeyRepeat {
+    pub fn new(delay: f32, rate: f32) -> Self {
+        KeyRepeat {
+            delay,
+            rate,
+            timer: 0.0,
+            is_down: false,
+        }
+    }
+
+    /// This should be called every frame, with the current state of the key. It returns the number
+    /// of times the action should be triggered this frame.
+    pub fn update(&mut self, is_down: bool, dt: f32) -> u32 {
+        if !is_down {
+            self.is_down = false;
+            return 0;
+        }
+
+        if !self.is_down {
+            self.is_down = true;
+            self.timer = 0.0;
+            return 1;
+        }
+
+        let interval = 1.0 / self.rate.max(f32::EPSILON);
+
+        let previous_cnt = self.get_repeat_cnt(self.timer, interval);
+        self.timer += dt;
+
+        self.get_repeat_cnt(self.timer, interval) - previous_cnt
+    }
+
+    // Returns the number of repeats that have been triggered after the key has been held down for
+    // the specified time
+    fn get_repeat_cnt(&self, time: f32, interval: f32) -> u32 {
+        if time < self.delay {
+            0
+        } else {
+            ((time - self.delay) / interval) as u32 + 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_repeat() {
+        let mut repeat = KeyRepeat::new(0.5, 4.0);
+
+        // Triggered once, on press
+        assert_eq!(repeat.update(true, 0.125), 1);
+
+        // Nothing, until the delay has passed
+        assert_eq!(repeat.update(true, 0.25), 0);
+        assert_eq!(repeat.update(true, 0.125), 0);
+
+        // Then at the repeat rate
+        assert_eq!(repeat.update(true, 0.125), 1);
+        assert_eq!(repeat.update(true, 0.125), 0);
+        assert_eq!(repeat.update(true, 0.125), 1);
+        assert_eq!(repeat.update(true, 0.75), 3);
+
+        // Releasing the key resets the delay
+        assert_eq!(repeat.update(false, 0.125), 0);
+        assert_eq!(repeat.update(true, 0.125), 1);
+        assert_eq!(repeat.update(true, 0.125), 0);
+    }
+}
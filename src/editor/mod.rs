@@ -3,8 +3,10 @@ use std::path::Path;
 
 use crate::{
     exit_to_main_menu,
-    map::{CRAB_TEXTURE_ID, FISH_SCHOOL_ICON_TEXTURE_ID},
-    quit_to_desktop, Resources,
+    map::{
+        draw_decoration_preview, get_decoration_size, CRAB_TEXTURE_ID, FISH_SCHOOL_ICON_TEXTURE_ID,
+    },
+    quit_to_desktop, Config, Resources,
 };
 
 mod camera;
@@ -16,42 +18,61 @@ pub mod gui;
 use gui::{
     toggle_editor_menu,
     toolbars::{
-        LayerListElement, ObjectListElement, TilesetDetailsElement, TilesetListElement,
-        ToolSelectorElement, Toolbar, ToolbarPosition,
+        DecorationListElement, LayerListElement, ObjectListElement, TilesetDetailsElement,
+        TilesetListElement, ToolSelectorElement, Toolbar, ToolbarPosition,
     },
-    CreateLayerWindow, CreateObjectWindow, CreateTilesetWindow, EditorGui, TilesetPropertiesWindow,
+    ConfirmDialog, CreateLayerWindow, CreateObjectWindow, CreateTilesetWindow, EditorGui,
+    TilesetPropertiesWindow,
 };
 
 mod actions;
 
 use actions::{
-    CreateLayerAction, CreateObjectAction, CreateTilesetAction, DeleteLayerAction,
-    DeleteObjectAction, DeleteTilesetAction, EditorAction, PlaceTileAction, RemoveTileAction,
-    SetLayerDrawOrderIndexAction, UndoableAction, UpdateTilesetAction,
+    BatchAction, ConvertLayerAction, CreateLayerAction, CreateObjectAction, CreateTilesetAction,
+    DeleteLayerAction, DeleteObjectAction, DeleteTilesetAction, DuplicateObjectsAction,
+    EditorAction, GroupObjectsAction, MoveTilesAction, PlaceTileAction, RemoveTileAction,
+    SetLayerDrawOrderIndexAction, UndoableAction, UngroupObjectsAction, UpdateTilesetAction,
 };
 
 mod input;
 
+mod clipboard;
+mod guides;
 mod history;
+mod key_repeat;
+mod quick_save;
+mod session;
+mod test_dummy;
+mod tile_selection;
 mod tools;
 
 pub use tools::{
-    add_tool_instance, get_tool_instance, get_tool_instance_of_id, EraserTool, ObjectPlacementTool,
+    add_tool_instance, get_tool_id_of_name, get_tool_instance, get_tool_instance_of_id,
+    DecorationPlacementTool, EraserTool, FillTool, LineTool, ObjectPlacementTool,
     TilePlacementTool, DEFAULT_TOOL_ICON_TEXTURE_ID,
 };
 
+use clipboard::{is_valid_object, load_clipboard, save_clipboard, EditorClipboard};
+use guides::{get_snap_tolerance, snap_to_guides, SnapGrid, SnapGuide};
 use history::EditorHistory;
 pub use input::EditorInputScheme;
+use key_repeat::KeyRepeat;
+use quick_save::{get_map_file_path, has_quick_save, load_quick_save, save_quick_save};
+use session::{EditorSession, EditorSessionWindow};
+use test_dummy::TestDummy;
+use tile_selection::TileSelection;
 
 use crate::editor::actions::{
     CreateSpawnPointAction, DeleteSpawnPointAction, ImportAction, MoveSpawnPointAction,
-    UpdateBackgroundAction, UpdateLayerAction, UpdateObjectAction, UpdateTileAttributesAction,
+    ReorderSpawnPointAction, UpdateBackgroundAction, UpdateLayerAction, UpdateMapInfoAction,
+    UpdateObjectAction, UpdateTileAttributesAction,
 };
 use crate::editor::gui::windows::{
-    BackgroundPropertiesWindow, CreateMapWindow, ImportWindow, LoadMapWindow,
-    ObjectPropertiesWindow, SaveMapWindow, TilePropertiesWindow,
+    BackgroundPropertiesWindow, CreateMapWindow, GenerateMapWindow, HistoryWindow, ImportWindow,
+    LoadMapWindow, MapCostWindow, MapPropertiesWindow, ObjectPropertiesWindow, SaveMapWindow,
+    TilePropertiesWindow,
 };
-use crate::editor::input::{collect_editor_input, EditorInput};
+use crate::editor::input::{collect_editor_input, EditorFocusTarget, EditorInput};
 use crate::editor::tools::SpawnPointPlacementTool;
 use crate::gui::SELECTION_HIGHLIGHT_COLOR;
 use crate::map::{MapObject, MapObjectKind};
@@ -69,7 +90,8 @@ use core::text::{draw_aligned_text, HorizontalAlignment, VerticalAlignment};
 
 use super::map::{Map, MapLayerKind};
 use crate::resources::{
-    map_name_to_filename, MapResource, MAP_EXPORTS_DEFAULT_DIR, MAP_EXPORTS_EXTENSION,
+    map_name_to_filename, MapResource, TilesetMetadata, MAP_EXPORTS_DEFAULT_DIR,
+    MAP_EXPORTS_EXTENSION,
 };
 
 #[derive(Debug, Clone)]
@@ -78,12 +100,28 @@ pub struct EditorContext {
     pub selected_layer: Option<String>,
     pub selected_tileset: Option<String>,
     pub selected_tile: Option<u32>,
+    /// The tiles that the tile placement tool picks from at random. This is only used if it holds
+    /// more than one tile.
+    pub random_tiles: Vec<u32>,
     pub selected_object: Option<usize>,
+    /// This holds the indices of all selected objects, on the selected layer, including
+    /// `selected_object`. Selecting a grouped object will select the entire group.
+    pub selected_objects: Vec<usize>,
+    pub selected_spawn_point: Option<usize>,
+    /// The decoration that is placed by the decoration placement tool
+    pub selected_decoration: Option<String>,
     pub input_scheme: EditorInputScheme,
     pub cursor_position: Vec2,
     pub is_user_map: bool,
     pub is_tiled_map: bool,
     pub should_snap_to_grid: bool,
+    pub is_extending_selection: bool,
+    pub is_rect_filling: bool,
+    pub has_test_dummy: bool,
+    /// The descriptions of the steps in the undo history, oldest first
+    pub history_entries: Vec<String>,
+    /// The number of steps in `history_entries` that are currently applied
+    pub history_index: usize,
 }
 
 impl Default for EditorContext {
@@ -93,12 +131,21 @@ impl Default for EditorContext {
             selected_layer: None,
             selected_tileset: None,
             selected_tile: None,
+            random_tiles: Vec::new(),
             selected_object: None,
+            selected_objects: Vec::new(),
+            selected_spawn_point: None,
+            selected_decoration: None,
             input_scheme: EditorInputScheme::Mouse,
             cursor_position: Vec2::ZERO,
             is_user_map: false,
             is_tiled_map: false,
             should_snap_to_grid: false,
+            is_extending_selection: false,
+            is_rect_filling: false,
+            has_test_dummy: false,
+            history_entries: Vec::new(),
+            history_index: 0,
         }
     }
 }
@@ -129,12 +176,22 @@ pub struct Editor {
     selected_tileset: Option<String>,
     // Selected tile in tileset
     selected_tile: Option<u32>,
+    random_tiles: Vec<u32>,
     selected_object: Option<usize>,
+    selected_objects: Vec<usize>,
     selected_spawn_point: Option<usize>,
+    selected_decoration: Option<String>,
 
     // Selected tile in map
     selected_map_tile_index: Option<usize>,
 
+    /// The box-selected region of tiles, if any
+    tile_selection: Option<TileSelection>,
+    /// The cell that a box selection was started from, while it is being dragged out
+    tile_selection_anchor: Option<UVec2>,
+    /// The cell that the tile selection was grabbed at, while it is being dragged to a new location
+    tile_selection_drag_origin: Option<UVec2>,
+
     input_scheme: EditorInputScheme,
     previous_cursor_position: Vec2,
     cursor_position: Vec2,
@@ -148,12 +205,35 @@ pub struct Editor {
 
     dragged_object: Option<DraggedObject>,
 
+    test_dummy: Option<TestDummy>,
+
+    /// The slot that quick-saves are saved to and restored from
+    quick_save_slot: usize,
+
+    /// This is `true` while a continuous tool is being held down. All the actions applied by the
+    /// tool, during a stroke, are batched into one step in the history.
+    is_stroke_active: bool,
+
+    nudge_repeat: KeyRepeat,
+    cycle_tile_repeat: KeyRepeat,
+    /// This is `true` while the selection is being nudged. All the nudges made while the keys are
+    /// held are batched into one step in the history, like the strokes of continuous tools.
+    is_nudge_active: bool,
+
+    /// The spawn point, or object, that the camera was last moved to, with `focus_next`, as the
+    /// target and its index among the things of that target
+    focused: Option<(EditorFocusTarget, usize)>,
+
     info_message_timer: f32,
     double_click_timer: f32,
 
     should_draw_grid: bool,
     should_snap_to_grid: bool,
     is_parallax_disabled: bool,
+
+    /// The textures of the tilesets that have had their tile collisions edited, since the map was
+    /// last saved. The collisions of these are written to the tilesets file, when the map is saved.
+    edited_tileset_textures: Vec<String>,
 }
 
 impl Editor {
@@ -169,6 +249,8 @@ impl Editor {
     const OBJECT_SELECTION_RECT_SIZE: f32 = 75.0;
     const OBJECT_SELECTION_RECT_PADDING: f32 = 8.0;
 
+    const SPAWN_POINT_NUMBER_FONT_SIZE: u16 = 24;
+
     const GRID_LINE_WIDTH: f32 = 1.0;
     const GRID_COLOR: Color = Color {
         r: 1.0,
@@ -181,11 +263,17 @@ impl Editor {
 
     const MESSAGE_TIMEOUT: f32 = 2.5;
 
+    const CONFIRM_DIALOG_WIDTH: f32 = 350.0;
+    const CONFIRM_DIALOG_HEIGHT: f32 = 150.0;
+
     pub fn new(input_scheme: EditorInputScheme, map_resource: MapResource) -> Self {
         add_tool_instance(TilePlacementTool::new());
         add_tool_instance(ObjectPlacementTool::new());
+        add_tool_instance(DecorationPlacementTool::new());
         add_tool_instance(SpawnPointPlacementTool::new());
         add_tool_instance(EraserTool::new());
+        add_tool_instance(FillTool::new());
+        add_tool_instance(LineTool::new());
 
         let selected_tool = None;
 
@@ -199,8 +287,11 @@ impl Editor {
         let tool_selector_element = ToolSelectorElement::new()
             .with_tool::<TilePlacementTool>()
             .with_tool::<ObjectPlacementTool>()
+            .with_tool::<DecorationPlacementTool>()
             .with_tool::<SpawnPointPlacementTool>()
-            .with_tool::<EraserTool>();
+            .with_tool::<EraserTool>()
+            .with_tool::<FillTool>()
+            .with_tool::<LineTool>();
 
         let left_toolbar = Toolbar::new(ToolbarPosition::Left, EditorGui::LEFT_TOOLBAR_WIDTH)
             .with_element(
@@ -221,6 +312,10 @@ impl Editor {
             .with_element(
                 EditorGui::OBJECT_LIST_HEIGHT_FACTOR,
                 ObjectListElement::new(),
+            )
+            .with_element(
+                EditorGui::DECORATION_LIST_HEIGHT_FACTOR,
+                DecorationListElement::new(),
             );
 
         let gui = EditorGui::new()
@@ -229,21 +324,29 @@ impl Editor {
 
         storage::store(gui);
 
-        Editor {
+        let editor_config = storage::get::<Config>().editor.clone();
+
+        let mut editor = Editor {
             map_resource,
             selected_tool,
             selected_layer,
             selected_tileset: None,
             selected_tile: None,
+            random_tiles: Vec::new(),
             selected_object: None,
+            selected_objects: Vec::new(),
             selected_spawn_point: None,
+            selected_decoration: None,
 
             selected_map_tile_index: None,
+            tile_selection: None,
+            tile_selection_anchor: None,
+            tile_selection_drag_origin: None,
 
             input_scheme,
             previous_cursor_position: cursor_position,
             cursor_position,
-            history: EditorHistory::new(),
+            history: EditorHistory::with_max_depth(editor_config.history_depth),
 
             previous_input: EditorInput::default(),
             input: EditorInput::default(),
@@ -253,16 +356,144 @@ impl Editor {
 
             dragged_object: None,
 
+            test_dummy: None,
+
+            quick_save_slot: 0,
+
+            is_stroke_active: false,
+
+            nudge_repeat: KeyRepeat::new(
+                editor_config.key_repeat_delay,
+                editor_config.key_repeat_rate,
+            ),
+            cycle_tile_repeat: KeyRepeat::new(
+                editor_config.key_repeat_delay,
+                editor_config.key_repeat_rate,
+            ),
+            is_nudge_active: false,
+
+            focused: None,
+
             info_message_timer: 0.0,
             double_click_timer: Self::DOUBLE_CLICK_THRESHOLD,
 
             should_draw_grid: true,
             should_snap_to_grid: false,
             is_parallax_disabled: false,
+            edited_tileset_textures: Vec::new(),
+        };
+
+        editor.restore_session();
+
+        editor
+    }
+
+    fn get_session(&self) -> EditorSession {
+        let (camera_position, camera_scale) = scene::find_node_by_type::<EditorCamera>()
+            .map(|camera| (Some(camera.position), Some(camera.scale)))
+            .unwrap_or_default();
+
+        let selected_tool = self
+            .selected_tool
+            .as_ref()
+            .map(|id| get_tool_instance_of_id(id).get_params().name.clone());
+
+        let open_windows = storage::get::<EditorGui>().get_session_windows();
+
+        EditorSession {
+            selected_tool,
+            selected_layer: self.selected_layer.clone(),
+            selected_tileset: self.selected_tileset.clone(),
+            selected_tile: self.selected_tile,
+            camera_position,
+            camera_scale,
+            should_draw_grid: self.should_draw_grid,
+            should_snap_to_grid: self.should_snap_to_grid,
+            is_parallax_disabled: self.is_parallax_disabled,
+            open_windows,
         }
     }
 
-    #[allow(dead_code)]
+    // This saves the session state for the current map, so that it can be restored the next time
+    // the map is opened in the editor.
+    fn save_session(&self) {
+        let session = self.get_session();
+
+        if let Err(err) = session.save(&self.map_resource.meta.path) {
+            #[cfg(debug_assertions)]
+            println!("WARNING: Unable to save editor session: {}", err);
+        }
+    }
+
+    // This restores the session state for the current map, if a session file exists. Any state
+    // that is no longer valid for the map, like a layer that has since been deleted, is ignored.
+    fn restore_session(&mut self) {
+        let session = match EditorSession::load(&self.map_resource.meta.path) {
+            Some(session) => session,
+            None => return,
+        };
+
+        self.selected_tool = session
+            .selected_tool
+            .as_deref()
+            .and_then(get_tool_id_of_name);
+
+        if let Some(layer_id) = session.selected_layer {
+            if self.get_map().layers.contains_key(&layer_id) {
+                self.selected_layer = Some(layer_id);
+            }
+        }
+
+        if let Some(tileset_id) = &session.selected_tileset {
+            self.select_tileset(tileset_id, session.selected_tile);
+        }
+
+        if let Some(mut camera) = scene::find_node_by_type::<EditorCamera>() {
+            if let Some(position) = session.camera_position {
+                camera.position = position.clamp(Vec2::ZERO, self.get_map().get_size());
+            }
+
+            if let Some(scale) = session.camera_scale {
+                camera.scale = scale.clamp(Self::CAMERA_ZOOM_MIN, Self::CAMERA_ZOOM_MAX);
+            }
+        }
+
+        self.should_draw_grid = session.should_draw_grid;
+        self.should_snap_to_grid = session.should_snap_to_grid;
+        self.is_parallax_disabled = session.is_parallax_disabled;
+
+        for window in session.open_windows {
+            let map = self.get_map();
+
+            let is_valid = match &window {
+                EditorSessionWindow::BackgroundProperties | EditorSessionWindow::MapProperties => {
+                    true
+                }
+                EditorSessionWindow::TilesetProperties { tileset_id } => {
+                    map.tilesets.contains_key(tileset_id)
+                }
+                EditorSessionWindow::ObjectProperties { layer_id, index } => map
+                    .layers
+                    .get(layer_id)
+                    .map(|layer| *index < layer.objects.len())
+                    .unwrap_or_default(),
+                EditorSessionWindow::TileProperties { layer_id, index } => map
+                    .layers
+                    .get(layer_id)
+                    .map(|layer| {
+                        layer.kind == MapLayerKind::TileLayer && *index < layer.tiles.len()
+                    })
+                    .unwrap_or_default(),
+            };
+
+            if is_valid {
+                self.apply_action(window.to_action());
+            }
+        }
+
+        self.update_context();
+    }
+
     fn get_selected_tile(&self) -> Option<(String, u32)> {
         if let Some(tileset_id) = self.selected_tileset.clone() {
             if let Some(tile_id) = self.selected_tile {
@@ -283,18 +514,29 @@ impl Editor {
         &mut self.map_resource.map
     }
 
+    /// Returns the context that tools and windows are drawn and updated with. The history is only
+    /// listed in the history window, so it is left empty here, and added when that is drawn.
     fn get_context(&self) -> EditorContext {
         EditorContext {
             selected_tool: self.selected_tool,
             selected_layer: self.selected_layer.clone(),
             selected_tileset: self.selected_tileset.clone(),
             selected_tile: self.selected_tile,
+            random_tiles: self.random_tiles.clone(),
             selected_object: self.selected_object,
+            selected_objects: self.selected_objects.clone(),
+            selected_spawn_point: self.selected_spawn_point,
+            selected_decoration: self.selected_decoration.clone(),
             input_scheme: self.input_scheme,
             cursor_position: self.cursor_position,
             is_user_map: self.map_resource.meta.is_user_map,
             is_tiled_map: self.map_resource.meta.is_tiled_map,
             should_snap_to_grid: self.should_snap_to_grid,
+            is_extending_selection: self.input.extend_selection,
+            is_rect_filling: self.input.rect_fill,
+            has_test_dummy: self.test_dummy.is_some(),
+            history_entries: Vec::new(),
+            history_index: 0,
         }
     }
 
@@ -313,6 +555,7 @@ impl Editor {
             match layer.kind {
                 MapLayerKind::TileLayer => {
                     self.selected_object = None;
+                    self.selected_objects.clear();
                 }
                 MapLayerKind::ObjectLayer => {
                     self.selected_tileset = None;
@@ -321,6 +564,26 @@ impl Editor {
             }
         }
 
+        if let Some(index) = self.selected_object {
+            let layer = self
+                .selected_layer
+                .as_ref()
+                .and_then(|layer_id| self.map_resource.map.layers.get(layer_id));
+
+            if let Some(layer) = layer {
+                let len = layer.objects.len();
+
+                if index >= len {
+                    self.selected_object = None;
+                    self.selected_objects.clear();
+                } else {
+                    self.selected_objects.retain(|&i| i < len);
+                }
+            }
+        } else {
+            self.selected_objects.clear();
+        }
+
         if let Some(tileset_id) = &self.selected_tileset {
             if let Some(tileset) = self.get_map().tilesets.get(tileset_id) {
                 if let Some(tile_id) = self.selected_tile {
@@ -328,12 +591,19 @@ impl Editor {
                         self.selected_tile = None;
                     }
                 }
+
+                let tile_cnt = tileset.tile_cnt;
+                self.random_tiles.retain(|&tile_id| tile_id < tile_cnt);
             } else {
                 self.selected_tileset = None;
                 self.selected_tile = None;
             }
         }
 
+        if self.selected_tileset.is_none() {
+            self.random_tiles.clear();
+        }
+
         if let Some(tool_id) = &self.selected_tool {
             let tool = get_tool_instance_of_id(tool_id);
             let ctx = self.get_context();
@@ -348,11 +618,18 @@ impl Editor {
         self.selected_layer = None;
         self.selected_tileset = None;
         self.selected_tile = None;
+        self.random_tiles.clear();
         self.selected_object = None;
+        self.selected_objects.clear();
+        self.test_dummy = None;
     }
 
     fn select_tileset(&mut self, tileset_id: &str, tile_id: Option<u32>) {
         if let Some(tileset) = self.map_resource.map.tilesets.get(tileset_id) {
+            if self.selected_tileset.as_deref() != Some(tileset_id) {
+                self.random_tiles.clear();
+            }
+
             self.selected_tileset = Some(tileset_id.to_string());
 
             if let Some(tile_id) = tile_id {
@@ -366,6 +643,355 @@ impl Editor {
         }
     }
 
+    // This moves the selected objects, or the selected spawn point, one step in `direction`. A step
+    // is one tile, if snap to grid is enabled, or else a single pixel.
+    fn nudge_selection(&mut self, direction: Vec2) {
+        let step = if self.should_snap_to_grid {
+            self.get_map().tile_size
+        } else {
+            Vec2::ONE
+        };
+
+        let offset = direction * step;
+
+        if let Some(index) = self.selected_object {
+            let layer_id = self.selected_layer.clone().unwrap();
+
+            let mut indices = self.selected_objects.clone();
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+
+            let actions = match self.get_map().layers.get(&layer_id) {
+                Some(layer) => indices
+                    .into_iter()
+                    .filter_map(|i| layer.objects.get(i).map(|object| (i, object)))
+                    .map(|(i, object)| EditorAction::UpdateObject {
+                        id: object.id.clone(),
+                        kind: object.kind,
+                        index: i,
+                        layer_id: layer_id.clone(),
+                        position: object.position + offset,
+                    })
+                    .collect(),
+                None => return,
+            };
+
+            self.apply_action(EditorAction::Batch(actions));
+        } else if let Some(index) = self.selected_spawn_point {
+            let position = self.get_map().spawn_points[index] + offset;
+            self.apply_action(EditorAction::MoveSpawnPoint { index, position });
+        }
+    }
+
+    // This selects the next, or previous, tile in the selected tileset, wrapping around at the ends
+    fn cycle_selected_tile(&mut self, direction: i32) {
+        let (tileset_id, tile_id) = match self.get_selected_tile() {
+            Some(selected) => selected,
+            None => return,
+        };
+
+        if let Some(tileset) = self.get_map().tilesets.get(&tileset_id) {
+            let tile_cnt = tileset.tile_cnt as i32;
+
+            if tile_cnt > 0 {
+                let i = (tile_id as i32 - tileset.first_tile_id as i32 + direction)
+                    .rem_euclid(tile_cnt);
+
+                let id = tileset.first_tile_id + i as u32;
+
+                self.apply_action(EditorAction::SelectTile { id, tileset_id });
+            }
+        }
+    }
+
+    // This moves the camera to the spawn point, or object of the specified kind, after the one it
+    // was last moved to, cycling back to the first one after the last. The spawn point, or object,
+    // is selected and named in the info message.
+    fn focus_next(&mut self, target: EditorFocusTarget) {
+        let map = self.get_map();
+
+        // The layer id, if the target is an object, the index and the position to center on
+        let targets = match target {
+            EditorFocusTarget::SpawnPoint => map
+                .spawn_points
+                .iter()
+                .enumerate()
+                .map(|(i, &position)| {
+                    let size = vec2(SPAWN_POINT_COLLIDER_WIDTH, SPAWN_POINT_COLLIDER_HEIGHT);
+                    (None, i, position + size / 2.0)
+                })
+                .collect::<Vec<_>>(),
+            EditorFocusTarget::Object(kind) => map
+                .draw_order
+                .iter()
+                .filter_map(|layer_id| map.layers.get(layer_id))
+                .filter(|layer| layer.kind == MapLayerKind::ObjectLayer)
+                .flat_map(|layer| {
+                    layer
+                        .objects
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, object)| object.kind == kind)
+                        .map(|(i, object)| {
+                            let size = get_object_size(object);
+                            (Some(layer.id.clone()), i, object.position + size / 2.0)
+                        })
+                })
+                .collect::<Vec<_>>(),
+        };
+
+        let label = match target {
+            EditorFocusTarget::SpawnPoint => "Spawn point",
+            EditorFocusTarget::Object(MapObjectKind::Item) => "Item",
+            EditorFocusTarget::Object(MapObjectKind::Decoration) => "Decoration",
+            EditorFocusTarget::Object(MapObjectKind::Environment) => "Environment object",
+        };
+
+        if targets.is_empty() {
+            self.info_message = Some(format!("No {}s in the map", label.to_lowercase()));
+            return;
+        }
+
+        let i = match self.focused {
+            Some((focused_target, i)) if focused_target == target => (i + 1) % targets.len(),
+            _ => 0,
+        };
+
+        self.focused = Some((target, i));
+
+        let (layer_id, index, position) = targets[i].clone();
+
+        let world_offset = self.get_map().world_offset;
+
+        let mut camera = scene::find_node_by_type::<EditorCamera>().unwrap();
+        camera.focus_on(position + world_offset);
+
+        if let Some(layer_id) = layer_id {
+            let id = self.get_map().layers[&layer_id].objects[index].id.clone();
+
+            self.info_message = Some(format!("{} '{}' ({}/{})", label, id, i + 1, targets.len()));
+
+            self.apply_action(EditorAction::SelectObject { index, layer_id });
+        } else {
+            self.info_message = Some(format!("{} {}/{}", label, i + 1, targets.len()));
+
+            self.selected_object = None;
+            self.selected_objects.clear();
+            self.selected_spawn_point = Some(index);
+        }
+    }
+
+    // This handles box-selecting a region of tiles, on the selected tile layer, by dragging while
+    // the box select key is held, and moving the selected region, by dragging it. This returns
+    // `true` if the input was used for this, in which case it should not be used for anything else.
+    fn update_tile_selection(
+        &mut self,
+        cursor_world_position: Vec2,
+        is_cursor_over_gui: bool,
+    ) -> bool {
+        let coords = self.get_map().to_coords(cursor_world_position);
+
+        if !self.input.action {
+            self.tile_selection_anchor = None;
+
+            if let Some(drag_origin) = self.tile_selection_drag_origin.take() {
+                let offset = coords.as_i32() - drag_origin.as_i32();
+
+                if let Some(selection) = self.tile_selection.take() {
+                    if offset != IVec2::ZERO {
+                        let action = EditorAction::MoveTiles {
+                            layer_id: selection.layer_id.clone(),
+                            region: selection.region,
+                            offset,
+                        };
+
+                        self.apply_action(action);
+                    }
+
+                    // The selection follows the moved tiles
+                    let map = self.get_map();
+                    self.tile_selection = selection
+                        .get_moved_region(offset, map.grid_size)
+                        .map(|region| TileSelection::new(map, &selection.layer_id, region));
+                }
+
+                return true;
+            }
+
+            return false;
+        }
+
+        if let Some(anchor) = self.tile_selection_anchor {
+            if let Some(selection) = &self.tile_selection {
+                let region = TileSelection::get_region(anchor, coords);
+                let selection = TileSelection::new(self.get_map(), &selection.layer_id, region);
+                self.tile_selection = Some(selection);
+            }
+
+            return true;
+        }
+
+        if self.tile_selection_drag_origin.is_some() {
+            return true;
+        }
+
+        let is_pressed = !self.previous_input.action;
+
+        if !is_pressed
+            || is_cursor_over_gui
+            || self.selected_tool.is_some()
+            || !self.get_map().contains(cursor_world_position)
+        {
+            return false;
+        }
+
+        if self.input.box_select {
+            if let Some(layer_id) = self.selected_layer.clone() {
+                if self.get_map().get_layer_kind(&layer_id) == Some(MapLayerKind::TileLayer) {
+                    let region = TileSelection::get_region(coords, coords);
+                    let selection = TileSelection::new(self.get_map(), &layer_id, region);
+
+                    self.tile_selection = Some(selection);
+                    self.tile_selection_anchor = Some(coords);
+
+                    return true;
+                }
+            }
+        } else if let Some(selection) = &self.tile_selection {
+            if selection.region.contains(coords) {
+                // The tiles are copied again, in case the map has changed since they were selected
+                let selection =
+                    TileSelection::new(self.get_map(), &selection.layer_id, selection.region);
+
+                self.tile_selection = Some(selection);
+                self.tile_selection_drag_origin = Some(coords);
+
+                return true;
+            }
+        }
+
+        self.tile_selection = None;
+
+        false
+    }
+
+    // This returns the indices of the objects that will be moved along with the dragged object.
+    // If the dragged object is part of the current selection, the entire selection is moved.
+    fn get_dragged_objects(&self, dragged_object: &DraggedObject) -> Vec<usize> {
+        match dragged_object {
+            DraggedObject::MapObject { index, .. } => {
+                if self.selected_objects.contains(index) {
+                    self.selected_objects.clone()
+                } else {
+                    vec![*index]
+                }
+            }
+            DraggedObject::SpawnPoint { .. } => Vec::new(),
+        }
+    }
+
+    // This returns the position that the dragged object would be moved to, if it was released at
+    // the current cursor position, as well as any alignment guides that it has been snapped to.
+    fn get_dragged_object_position(
+        &self,
+        dragged_object: &DraggedObject,
+    ) -> (Vec2, Vec<SnapGuide>) {
+        let map = self.get_map();
+        let camera = scene::find_node_by_type::<EditorCamera>().unwrap();
+
+        let click_offset = match dragged_object {
+            DraggedObject::MapObject { click_offset, .. }
+            | DraggedObject::SpawnPoint { click_offset, .. } => *click_offset,
+        };
+
+        let cursor_world_position = camera.to_world_space(self.cursor_position - click_offset);
+
+        let position = cursor_world_position.clamp(
+            map.world_offset,
+            map.world_offset + (map.grid_size.as_f32() * map.tile_size),
+        );
+
+        if self.should_snap_to_grid {
+            let coords = map.to_coords(position);
+            return (map.to_position(coords), Vec::new());
+        }
+
+        if self.input.disable_snap_guides {
+            return (position, Vec::new());
+        }
+
+        let padding = vec2(
+            Self::OBJECT_SELECTION_RECT_PADDING,
+            Self::OBJECT_SELECTION_RECT_PADDING,
+        ) * 2.0;
+
+        let spawn_point_size = vec2(SPAWN_POINT_COLLIDER_WIDTH, SPAWN_POINT_COLLIDER_HEIGHT);
+
+        let dragged_objects = self.get_dragged_objects(dragged_object);
+
+        let mut size = spawn_point_size;
+        let mut targets = Vec::new();
+
+        for layer in map.layers.values() {
+            if layer.is_visible && layer.kind == MapLayerKind::ObjectLayer {
+                for (i, object) in layer.objects.iter().enumerate() {
+                    let object_size = get_object_size(object) - padding;
+
+                    if let DraggedObject::MapObject {
+                        layer_id, index, ..
+                    } = dragged_object
+                    {
+                        if *layer_id == layer.id {
+                            if *index == i {
+                                size = object_size;
+                            }
+
+                            if dragged_objects.contains(&i) {
+                                continue;
+                            }
+                        }
+                    }
+
+                    let object_position = map.world_offset + object.position;
+
+                    targets.push(Rect::new(
+                        object_position.x,
+                        object_position.y,
+                        object_size.x,
+                        object_size.y,
+                    ));
+                }
+            }
+        }
+
+        for (i, spawn_point) in map.spawn_points.iter().enumerate() {
+            if let DraggedObject::SpawnPoint { index, .. } = dragged_object {
+                if *index == i {
+                    continue;
+                }
+            }
+
+            targets.push(Rect::new(
+                spawn_point.x,
+                spawn_point.y,
+                spawn_point_size.x,
+                spawn_point_size.y,
+            ));
+        }
+
+        let grid = SnapGrid {
+            offset: map.world_offset,
+            tile_size: map.tile_size,
+        };
+
+        let rect = Rect::new(position.x, position.y, size.x, size.y);
+
+        let tolerance = get_snap_tolerance(&camera);
+
+        snap_to_guides(rect, &targets, Some(grid), tolerance)
+    }
+
     // This applies an `EditorAction`. This is to be used, exclusively, in stead of, for example,
     // applying `UndoableActions` directly on the `History` of `Editor`.
     fn apply_action(&mut self, action: EditorAction) {
@@ -375,9 +1001,26 @@ impl Editor {
 
         match action {
             EditorAction::Batch(actions) => {
+                self.history.begin_batch();
+
                 for action in actions {
-                    self.apply_action(action)
+                    self.apply_action(action);
+
+                    // The batch is closed if one of its actions failed, in which case the rest
+                    // of it should not be applied
+                    if !self.history.is_batch_open() {
+                        break;
+                    }
                 }
+
+                self.history.end_batch();
+            }
+            EditorAction::Undo | EditorAction::Redo | EditorAction::JumpToHistory(_)
+                if self.is_stroke_active =>
+            {
+                // The history can not be navigated while a stroke is being drawn, as the batch
+                // that holds the stroke is still open, and would end up on top of the history
+                // that was navigated to
             }
             EditorAction::Undo => {
                 res = self.history.undo(&mut self.map_resource.map);
@@ -385,6 +1028,17 @@ impl Editor {
             EditorAction::Redo => {
                 res = self.history.redo(&mut self.map_resource.map);
             }
+            EditorAction::JumpToHistory(index) => {
+                res = self.history.jump_to(index, &mut self.map_resource.map);
+            }
+            EditorAction::OpenHistoryWindow => {
+                let mut gui = storage::get_mut::<EditorGui>();
+                gui.add_window(HistoryWindow::new());
+            }
+            EditorAction::OpenMapCostWindow => {
+                let mut gui = storage::get_mut::<EditorGui>();
+                gui.add_window(MapCostWindow::new());
+            }
             EditorAction::SelectTool(id) => {
                 self.selected_tool = id;
             }
@@ -403,6 +1057,16 @@ impl Editor {
                     map.background_layers.clone(),
                 ));
             }
+            EditorAction::OpenMapPropertiesWindow => {
+                let mut gui = storage::get_mut::<EditorGui>();
+                gui.add_window(MapPropertiesWindow::new(&self.map_resource.map.info));
+            }
+            EditorAction::UpdateMapInfo(info) => {
+                let action = UpdateMapInfoAction::new(info);
+                res = self
+                    .history
+                    .apply(Box::new(action), &mut self.map_resource.map);
+            }
             EditorAction::OpenCreateLayerWindow => {
                 let mut gui = storage::get_mut::<EditorGui>();
                 gui.add_window(CreateLayerWindow::new());
@@ -431,9 +1095,19 @@ impl Editor {
                 let mut gui = storage::get_mut::<EditorGui>();
                 gui.remove_window_id(id);
             }
+            EditorAction::OpenConfirmDialog { body, action } => {
+                let body = body.iter().map(|line| line.as_str()).collect::<Vec<_>>();
+                let size = vec2(Self::CONFIRM_DIALOG_WIDTH, Self::CONFIRM_DIALOG_HEIGHT);
+
+                let mut gui = storage::get_mut::<EditorGui>();
+                gui.add_window(ConfirmDialog::new(size, &body, *action));
+            }
             EditorAction::SelectTile { id, tileset_id } => {
                 self.select_tileset(&tileset_id, Some(id));
             }
+            EditorAction::SetRandomTiles(tiles) => {
+                self.random_tiles = tiles;
+            }
             EditorAction::UpdateTileAttributes {
                 index,
                 layer_id,
@@ -472,6 +1146,16 @@ impl Editor {
                     .history
                     .apply(Box::new(action), &mut self.map_resource.map);
             }
+            EditorAction::ConvertLayer { id, new_kind } => {
+                let action = ConvertLayerAction::new(id, new_kind);
+                res = self
+                    .history
+                    .apply(Box::new(action), &mut self.map_resource.map);
+            }
+            action @ (EditorAction::FillLayer { .. } | EditorAction::ClearLayer(..)) => {
+                let action = action.resolve(self.get_map());
+                self.apply_action(action);
+            }
             EditorAction::UpdateLayer { id, is_visible } => {
                 let action = UpdateLayerAction::new(id, is_visible);
                 res = self
@@ -497,13 +1181,45 @@ impl Editor {
                 id,
                 texture_id,
                 autotile_mask,
+                tile_collisions,
             } => {
-                let action = UpdateTilesetAction::new(id, texture_id, autotile_mask);
+                let action = UpdateTilesetAction::new(
+                    id,
+                    texture_id.clone(),
+                    autotile_mask,
+                    tile_collisions,
+                );
+
                 res = self
                     .history
                     .apply(Box::new(action), &mut self.map_resource.map);
+
+                if res.is_ok() && !self.edited_tileset_textures.contains(&texture_id) {
+                    self.edited_tileset_textures.push(texture_id);
+                }
             }
             EditorAction::SelectObject { index, layer_id } => {
+                let objects = self
+                    .get_map()
+                    .layers
+                    .get(&layer_id)
+                    .map(|layer| layer.get_object_with_group(index))
+                    .unwrap_or_else(|| vec![index]);
+
+                let should_extend = self.input.extend_selection
+                    && self.selected_object.is_some()
+                    && self.selected_layer.as_ref() == Some(&layer_id);
+
+                if should_extend {
+                    for i in objects {
+                        if !self.selected_objects.contains(&i) {
+                            self.selected_objects.push(i);
+                        }
+                    }
+                } else {
+                    self.selected_objects = objects;
+                }
+
                 self.selected_layer = Some(layer_id);
                 self.selected_object = Some(index);
             }
@@ -518,6 +1234,29 @@ impl Editor {
                     .history
                     .apply(Box::new(action), &mut self.map_resource.map);
             }
+            EditorAction::SelectDecoration(id) => {
+                self.selected_decoration = Some(id);
+            }
+            EditorAction::CreateDecoration {
+                id,
+                position,
+                layer_id,
+            } => {
+                let is_valid = {
+                    let resources = storage::get::<Resources>();
+                    resources.decoration.contains_key(&id)
+                };
+
+                if is_valid {
+                    let kind = MapObjectKind::Decoration;
+                    let action = CreateObjectAction::new(id, kind, position, layer_id);
+                    res = self
+                        .history
+                        .apply(Box::new(action), &mut self.map_resource.map);
+                } else {
+                    self.info_message = Some(format!("Invalid decoration id '{}'", id));
+                }
+            }
             EditorAction::DeleteObject { index, layer_id } => {
                 let action = DeleteObjectAction::new(index, layer_id);
                 res = self
@@ -536,6 +1275,33 @@ impl Editor {
                     .history
                     .apply(Box::new(action), &mut self.map_resource.map);
             }
+            EditorAction::GroupObjects { layer_id, indices } => {
+                let action = GroupObjectsAction::new(layer_id, indices);
+                res = self
+                    .history
+                    .apply(Box::new(action), &mut self.map_resource.map);
+            }
+            EditorAction::UngroupObjects { layer_id, group } => {
+                let action = UngroupObjectsAction::new(layer_id, group);
+                res = self
+                    .history
+                    .apply(Box::new(action), &mut self.map_resource.map);
+            }
+            EditorAction::DuplicateObjects { layer_id, indices } => {
+                let offset = vec2(self.get_map().tile_size.x, 0.0);
+                let duplicate_cnt = indices.len();
+
+                let action = DuplicateObjectsAction::new(layer_id.clone(), indices, offset);
+                res = self
+                    .history
+                    .apply(Box::new(action), &mut self.map_resource.map);
+
+                if res.is_ok() && duplicate_cnt > 0 {
+                    self.selected_layer = Some(layer_id);
+                    self.selected_object = Some(0);
+                    self.selected_objects = (0..duplicate_cnt).collect();
+                }
+            }
             EditorAction::CreateSpawnPoint(position) => {
                 let action = CreateSpawnPointAction::new(position);
                 res = self
@@ -554,6 +1320,16 @@ impl Editor {
                     .history
                     .apply(Box::new(action), &mut self.map_resource.map);
             }
+            EditorAction::ReorderSpawnPoint { index, new_index } => {
+                let action = ReorderSpawnPointAction::new(index, new_index);
+                res = self
+                    .history
+                    .apply(Box::new(action), &mut self.map_resource.map);
+
+                if res.is_ok() && self.selected_spawn_point == Some(index) {
+                    self.selected_spawn_point = Some(new_index);
+                }
+            }
             EditorAction::PlaceTile {
                 id,
                 layer_id,
@@ -571,6 +1347,22 @@ impl Editor {
                     .history
                     .apply(Box::new(action), &mut self.map_resource.map);
             }
+            EditorAction::MoveTiles {
+                layer_id,
+                region,
+                offset,
+            } => {
+                let action = MoveTilesAction::new(layer_id, region, offset);
+                res = self
+                    .history
+                    .apply(Box::new(action), &mut self.map_resource.map);
+            }
+            EditorAction::SpawnTestDummy(position) => {
+                self.test_dummy = Some(TestDummy::new(self.get_map(), position));
+            }
+            EditorAction::ClearTestDummy => {
+                self.test_dummy = None;
+            }
             EditorAction::OpenImportWindow(map_index) => {
                 let mut gui = storage::get_mut::<EditorGui>();
                 gui.add_window(ImportWindow::new(map_index));
@@ -596,8 +1388,11 @@ impl Editor {
                 match res {
                     Err(err) => println!("Create Map: {}", err),
                     Ok(map_resource) => {
+                        self.save_session();
+
                         self.map_resource = map_resource;
                         self.history.clear();
+                        self.edited_tileset_textures.clear();
                         self.clear_context();
                     }
                 }
@@ -606,13 +1401,40 @@ impl Editor {
                 let mut gui = storage::get_mut::<EditorGui>();
                 gui.add_window(CreateMapWindow::new());
             }
+            EditorAction::GenerateMap { name, params } => {
+                let resources = storage::get::<Resources>();
+                let res = resources.create_map(&name, None, params.tile_size, params.grid_size);
+                match res {
+                    Err(err) => println!("Generate Map: {}", err),
+                    Ok(mut map_resource) => {
+                        map_resource.map = Map::generate(&params);
+
+                        self.save_session();
+
+                        self.map_resource = map_resource;
+                        self.history.clear();
+                        self.edited_tileset_textures.clear();
+                        self.clear_context();
+                    }
+                }
+            }
+            EditorAction::OpenGenerateMapWindow => {
+                let mut gui = storage::get_mut::<EditorGui>();
+                gui.add_window(GenerateMapWindow::new());
+            }
             EditorAction::OpenMap(index) => {
                 let resources = storage::get::<Resources>();
                 let map_resource = resources.maps.get(index).cloned().unwrap();
+                drop(resources);
+
+                self.save_session();
 
                 self.map_resource = map_resource;
                 self.history.clear();
+                self.edited_tileset_textures.clear();
                 self.clear_context();
+
+                self.restore_session();
             }
             EditorAction::OpenLoadMapWindow => {
                 let mut gui = storage::get_mut::<EditorGui>();
@@ -637,21 +1459,107 @@ impl Editor {
 
                 let mut resources = storage::get_mut::<Resources>();
                 if resources.save_map(&map_resource).is_ok() {
+                    // Tile collisions belong to the tileset texture, so they are written back to
+                    // the resources as well, for other maps that use it. This is done when the map
+                    // is saved, so that edits that were undone are not written.
+                    let metadata = get_tileset_metadata(
+                        &map_resource.map,
+                        self.edited_tileset_textures.drain(..),
+                    );
+
+                    if let Err(err) = resources.save_tileset_metadata(metadata) {
+                        self.info_message = Some(format!("Unable to save tilesets: {}", err));
+                    }
+
+                    drop(resources);
+
                     self.map_resource = map_resource;
+                    self.save_session();
                 }
             }
             EditorAction::OpenSaveMapWindow => {
                 let mut gui = storage::get_mut::<EditorGui>();
                 gui.add_window(SaveMapWindow::new(&self.map_resource.meta.name));
             }
+            EditorAction::QuickSave(slot) => {
+                let map_file_path = get_map_file_path(&self.map_resource.meta.path);
+
+                let res = save_quick_save(&self.map_resource.map, &map_file_path, slot);
+
+                self.info_message = match res {
+                    Ok(()) => Some(format!("Quick saved to slot {}", slot + 1)),
+                    Err(err) => Some(format!("Unable to quick save: {}", err)),
+                };
+            }
+            EditorAction::RestoreQuickSave(slot) => {
+                let map_file_path = get_map_file_path(&self.map_resource.meta.path);
+
+                match load_quick_save(&map_file_path, slot) {
+                    Ok(map) => {
+                        self.map_resource.map = map;
+                        self.history.clear();
+                        self.clear_context();
+
+                        self.info_message = Some(format!("Restored quick save slot {}", slot + 1));
+                    }
+                    Err(err) => {
+                        self.info_message = Some(format!("Unable to restore quick save: {}", err));
+                    }
+                }
+            }
+            EditorAction::CopyToClipboard => {
+                let clipboard = if let Some(selection) = &self.tile_selection {
+                    EditorClipboard::from_tile_selection(selection)
+                } else if let Some(layer) = self
+                    .selected_layer
+                    .as_ref()
+                    .and_then(|layer_id| self.get_map().layers.get(layer_id))
+                {
+                    let objects = self
+                        .selected_objects
+                        .iter()
+                        .filter_map(|&i| layer.objects.get(i).cloned())
+                        .collect::<Vec<_>>();
+
+                    EditorClipboard::from_objects(&objects)
+                } else {
+                    EditorClipboard::default()
+                };
+
+                self.info_message = if clipboard.is_empty() {
+                    Some("Nothing to copy".to_string())
+                } else {
+                    match save_clipboard(&clipboard) {
+                        Ok(()) => Some("Copied to clipboard".to_string()),
+                        Err(err) => Some(format!("Unable to copy: {}", err)),
+                    }
+                };
+            }
+            EditorAction::PasteFromClipboard { layer_id, position } => {
+                let action = load_clipboard().and_then(|clipboard| {
+                    let resources = storage::get::<Resources>();
+                    let map = self.get_map();
+
+                    clipboard.validate(map, |object| is_valid_object(&resources, object))?;
+
+                    Ok(clipboard.get_paste_action(map, &layer_id, position))
+                });
+
+                match action {
+                    Ok(action) => self.apply_action(action),
+                    Err(err) => self.info_message = Some(format!("Unable to paste: {}", err)),
+                }
+            }
             EditorAction::DeleteMap(index) => {
                 let mut resources = storage::get_mut::<Resources>();
                 resources.delete_map(index).unwrap();
             }
             EditorAction::ExitToMainMenu => {
+                self.save_session();
                 exit_to_main_menu();
             }
             EditorAction::QuitToDesktop => {
+                self.save_session();
                 quit_to_desktop();
             }
         }
@@ -715,6 +1623,48 @@ impl Node for Editor {
             node.apply_action(action);
         }
 
+        if let Some(slot) = node.input.quick_save_slot {
+            node.quick_save_slot = slot;
+            node.info_message = Some(format!("Quick save slot: {}", slot + 1));
+        }
+
+        if node.input.quick_save {
+            let action = EditorAction::QuickSave(node.quick_save_slot);
+            node.apply_action(action);
+        }
+
+        if node.input.copy {
+            node.apply_action(EditorAction::CopyToClipboard);
+        }
+
+        if node.input.paste {
+            if let Some(layer_id) = node.selected_layer.clone() {
+                let position = scene::find_node_by_type::<EditorCamera>()
+                    .unwrap()
+                    .to_world_space(node.cursor_position);
+
+                node.apply_action(EditorAction::PasteFromClipboard { layer_id, position });
+            }
+        }
+
+        if node.input.quick_load {
+            let slot = node.quick_save_slot;
+
+            if has_quick_save(&get_map_file_path(&node.map_resource.meta.path), slot) {
+                let action = EditorAction::OpenConfirmDialog {
+                    body: vec![
+                        format!("Restore quick save slot {}?", slot + 1),
+                        "Unsaved changes and the undo history will be lost.".to_string(),
+                    ],
+                    action: Box::new(EditorAction::RestoreQuickSave(slot)),
+                };
+
+                node.apply_action(action);
+            } else {
+                node.info_message = Some(format!("Quick save slot {} is empty", slot + 1));
+            }
+        }
+
         if !node.input.action && node.double_click_timer < Self::DOUBLE_CLICK_THRESHOLD {
             node.double_click_timer =
                 (node.double_click_timer + dt).clamp(0.0, Self::DOUBLE_CLICK_THRESHOLD);
@@ -768,6 +1718,38 @@ impl Node for Editor {
             node.apply_action(EditorAction::Redo);
         }
 
+        {
+            let direction = node.input.nudge_direction;
+            let nudge_cnt = node.nudge_repeat.update(direction != Vec2::ZERO, dt);
+
+            if nudge_cnt > 0 && !node.is_nudge_active {
+                node.history.begin_batch();
+                node.is_nudge_active = true;
+            }
+
+            for _ in 0..nudge_cnt {
+                node.nudge_selection(direction);
+            }
+
+            if direction == Vec2::ZERO && node.is_nudge_active {
+                node.history.end_batch();
+                node.is_nudge_active = false;
+            }
+        }
+
+        {
+            let direction = node.input.cycle_tile;
+            let cycle_cnt = node.cycle_tile_repeat.update(direction != 0, dt);
+
+            for _ in 0..cycle_cnt {
+                node.cycle_selected_tile(direction);
+            }
+        }
+
+        if let Some(target) = node.input.focus_next {
+            node.focus_next(target);
+        }
+
         let cursor_world_position = scene::find_node_by_type::<EditorCamera>()
             .unwrap()
             .to_world_space(node.cursor_position);
@@ -797,7 +1779,15 @@ impl Node for Editor {
             }
         }
 
-        if node.input.action {
+        if !node.input.action && node.is_stroke_active {
+            node.history.end_batch();
+            node.is_stroke_active = false;
+        }
+
+        let is_tile_selection_input =
+            node.update_tile_selection(cursor_world_position, is_cursor_over_gui);
+
+        if node.input.action && !is_tile_selection_input {
             if !is_cursor_over_context_menu {
                 let mut gui = storage::get_mut::<EditorGui>();
                 gui.close_context_menu();
@@ -808,6 +1798,12 @@ impl Node for Editor {
                     let ctx = node.get_context();
                     let tool = get_tool_instance_of_id(id);
                     let params = tool.get_params();
+
+                    if params.is_continuous && !node.is_stroke_active {
+                        node.history.begin_batch();
+                        node.is_stroke_active = true;
+                    }
+
                     if !node.previous_input.action || params.is_continuous {
                         if let Some(action) = tool.get_action(node.get_map(), &ctx) {
                             node.apply_action(action);
@@ -940,6 +1936,7 @@ impl Node for Editor {
                                     node.apply_action(action);
                                 } else {
                                     node.selected_object = None;
+                                    node.selected_objects.clear();
                                 }
                             }
                         }
@@ -1052,31 +2049,14 @@ impl Node for Editor {
                     if !is_selecting_tile && !is_selecting_object && !is_selecting_spawn_point {
                         node.selected_map_tile_index = None;
                         node.selected_object = None;
+                        node.selected_objects.clear();
                         node.selected_spawn_point = None;
                     }
                 }
             }
         } else if let Some(dragged_object) = node.dragged_object.take() {
-            let map = node.get_map();
-
-            let click_offset = match dragged_object {
-                DraggedObject::MapObject { click_offset, .. }
-                | DraggedObject::SpawnPoint { click_offset, .. } => click_offset,
-            };
-
-            let cursor_world_position = scene::find_node_by_type::<EditorCamera>()
-                .unwrap()
-                .to_world_space(node.cursor_position - click_offset);
-
-            let mut position = (cursor_world_position).clamp(
-                map.world_offset,
-                map.world_offset + (map.grid_size.as_f32() * map.tile_size),
-            );
-
-            if node.should_snap_to_grid {
-                let coords = map.to_coords(position);
-                position = map.to_position(coords);
-            }
+            let (position, _) = node.get_dragged_object_position(&dragged_object);
+            let dragged_objects = node.get_dragged_objects(&dragged_object);
 
             match dragged_object {
                 DraggedObject::MapObject {
@@ -1086,15 +2066,37 @@ impl Node for Editor {
                     layer_id,
                     ..
                 } => {
-                    let action = EditorAction::UpdateObject {
+                    let mut actions = vec![EditorAction::UpdateObject {
                         id,
                         kind,
                         index,
-                        layer_id,
+                        layer_id: layer_id.clone(),
                         position,
-                    };
+                    }];
 
-                    node.apply_action(action);
+                    let map = node.get_map();
+
+                    if let Some(layer) = map.layers.get(&layer_id) {
+                        // The dragged position is in world space, while object positions are
+                        // relative to the map, so the offset is calculated in world space
+                        let offset = position - (map.world_offset + layer.objects[index].position);
+
+                        for i in dragged_objects {
+                            if i != index {
+                                let object = &layer.objects[i];
+
+                                actions.push(EditorAction::UpdateObject {
+                                    id: object.id.clone(),
+                                    kind: object.kind,
+                                    index: i,
+                                    layer_id: layer_id.clone(),
+                                    position: object.position + offset,
+                                });
+                            }
+                        }
+                    }
+
+                    node.apply_action(EditorAction::Batch(actions));
                 }
                 DraggedObject::SpawnPoint { index, .. } => {
                     let action = EditorAction::MoveSpawnPoint { index, position };
@@ -1108,7 +2110,12 @@ impl Node for Editor {
             if let Some(index) = node.selected_object.take() {
                 let layer_id = node.selected_layer.clone().unwrap();
 
-                let action = EditorAction::DeleteObject { index, layer_id };
+                let mut indices = std::mem::take(&mut node.selected_objects);
+                if !indices.contains(&index) {
+                    indices.push(index);
+                }
+
+                let action = EditorAction::delete_objects(&layer_id, &indices);
 
                 node.apply_action(action);
             } else if let Some(index) = node.selected_map_tile_index.take() {
@@ -1177,12 +2184,23 @@ impl Node for Editor {
 
         node.mouse_movement = Vec2::ZERO;
 
+        // Moving the camera manually stops any travel started by `focus_next`
+        if movement != Vec2::ZERO {
+            camera.cancel_focus();
+        }
+
+        camera.update_focus();
+
         camera.position = (camera.position + movement).clamp(Vec2::ZERO, node.get_map().get_size());
 
         if is_cursor_over_map {
             camera.scale = (camera.scale + node.input.camera_zoom * Self::CAMERA_ZOOM_STEP)
                 .clamp(Self::CAMERA_ZOOM_MIN, Self::CAMERA_ZOOM_MAX);
         }
+
+        if let Some(test_dummy) = &mut node.test_dummy {
+            test_dummy.fixed_update();
+        }
     }
 
     fn draw(mut node: RefMut<Self>) {
@@ -1256,26 +2274,13 @@ impl Node for Editor {
 
                 let mut position = *spawn_point;
 
-                if let Some(DraggedObject::SpawnPoint {
-                    index,
-                    click_offset,
-                }) = node.dragged_object.clone()
-                {
-                    if index == i {
-                        let map = node.get_map();
+                if let Some(dragged_object) = &node.dragged_object {
+                    if let DraggedObject::SpawnPoint { index, .. } = dragged_object {
+                        if *index == i {
+                            let (dragged_position, _) =
+                                node.get_dragged_object_position(dragged_object);
 
-                        let cursor_world_position = scene::find_node_by_type::<EditorCamera>()
-                            .unwrap()
-                            .to_world_space(node.cursor_position - click_offset);
-
-                        position = (cursor_world_position).clamp(
-                            map.world_offset,
-                            map.world_offset + (map.grid_size.as_f32() * map.tile_size),
-                        );
-
-                        if node.should_snap_to_grid {
-                            let coords = map.to_coords(position);
-                            position = map.to_position(coords);
+                            position = dragged_position;
                         }
                     }
                 }
@@ -1304,6 +2309,18 @@ impl Node for Editor {
                     },
                 );
 
+                // Spawn points are numbered by their order, starting at 1
+                draw_aligned_text(
+                    &(i + 1).to_string(),
+                    position + vec2(SPAWN_POINT_COLLIDER_WIDTH / 2.0, 0.0),
+                    HorizontalAlignment::Center,
+                    VerticalAlignment::Bottom,
+                    TextParams {
+                        font_size: Self::SPAWN_POINT_NUMBER_FONT_SIZE,
+                        ..Default::default()
+                    },
+                );
+
                 if is_selected {
                     draw_rectangle_lines(
                         position.x,
@@ -1316,6 +2333,28 @@ impl Node for Editor {
                 }
             }
 
+            // This holds the layer, indices and offset of any objects that are moved along with the
+            // dragged object
+            let mut dragged_objects = None;
+
+            if let Some(
+                dragged_object @ DraggedObject::MapObject {
+                    layer_id, index, ..
+                },
+            ) = &node.dragged_object
+            {
+                let map = node.get_map();
+
+                if let Some(layer) = map.layers.get(layer_id) {
+                    let (position, _) = node.get_dragged_object_position(dragged_object);
+                    let offset = position - (map.world_offset + layer.objects[*index].position);
+
+                    let indices = node.get_dragged_objects(dragged_object);
+
+                    dragged_objects = Some((layer_id.clone(), indices, offset));
+                }
+            }
+
             let len = node.get_map().draw_order.len();
             for i in 0..len {
                 let i = len as i32 - i as i32 - 1;
@@ -1329,38 +2368,38 @@ impl Node for Editor {
 
                             let mut is_selected = false;
                             if let Some(layer_id) = &node.selected_layer {
-                                if let Some(index) = node.selected_object {
-                                    is_selected = *layer_id == layer.id && index == i;
-                                }
+                                is_selected =
+                                    *layer_id == layer.id && node.selected_objects.contains(&i);
                             }
 
                             let mut object_position =
                                 node.map_resource.map.world_offset + object.position;
 
-                            if let Some(DraggedObject::MapObject {
-                                layer_id,
-                                index,
-                                click_offset,
-                                ..
-                            }) = node.dragged_object.clone()
+                            if let Some(
+                                dragged_object @ DraggedObject::MapObject {
+                                    layer_id, index, ..
+                                },
+                            ) = &node.dragged_object
                             {
-                                if layer.id == layer_id && index == i {
-                                    let map = node.get_map();
-
-                                    let cursor_world_position =
-                                        scene::find_node_by_type::<EditorCamera>()
-                                            .unwrap()
-                                            .to_world_space(node.cursor_position - click_offset);
-
-                                    object_position = (cursor_world_position).clamp(
-                                        map.world_offset,
-                                        map.world_offset + (map.grid_size.as_f32() * map.tile_size),
-                                    );
-
-                                    if node.should_snap_to_grid {
-                                        let coords = map.to_coords(object_position);
-                                        object_position = map.to_position(coords);
-                                    }
+                                if layer.id == *layer_id && *index == i {
+                                    let (dragged_position, _) =
+                                        node.get_dragged_object_position(dragged_object);
+
+                                    object_position = dragged_position;
+                                }
+                            }
+
+                            if let Some((layer_id, indices, offset)) = &dragged_objects {
+                                let is_dragged_object = matches!(
+                                    &node.dragged_object,
+                                    Some(DraggedObject::MapObject { index, .. }) if *index == i
+                                );
+
+                                if layer.id == *layer_id
+                                    && !is_dragged_object
+                                    && indices.contains(&i)
+                                {
+                                    object_position += *offset;
                                 }
                             }
 
@@ -1416,38 +2455,13 @@ impl Node for Editor {
                                 MapObjectKind::Decoration => {
                                     if let Some(params) = resources.decoration.get(&object.id) {
                                         if let Some(texture_res) =
-                                            resources.textures.get(&params.sprite.texture_id)
+                                            resources.textures.get(params.sprite.texture_id())
                                         {
-                                            let position = object_position + params.sprite.offset;
-
-                                            let tint = params.sprite.tint.unwrap_or(color::WHITE);
-
-                                            let (texture, frame_size) =
-                                                (texture_res.texture, texture_res.frame_size());
-
-                                            let dest_size =
-                                                params.sprite.scale.map(|s| s * frame_size);
-
-                                            let source =
-                                                params.sprite.animations.first().map(|a| {
-                                                    Rect::new(
-                                                        0.0,
-                                                        a.row as f32 * frame_size.y,
-                                                        frame_size.x,
-                                                        frame_size.y,
-                                                    )
-                                                });
-
-                                            draw_texture_ex(
-                                                texture,
-                                                position.x,
-                                                position.y,
-                                                tint,
-                                                DrawTextureParams {
-                                                    dest_size,
-                                                    source,
-                                                    ..Default::default()
-                                                },
+                                            draw_decoration_preview(
+                                                texture_res,
+                                                &params.sprite,
+                                                object_position,
+                                                1.0,
                                             );
                                         } else {
                                             label = Some("INVALID TEXTURE ID".to_string());
@@ -1526,6 +2540,30 @@ impl Node for Editor {
             }
         }
 
+        if let Some(dragged_object) = &node.dragged_object {
+            let (_, guides) = node.get_dragged_object_position(dragged_object);
+
+            if !guides.is_empty() {
+                let map = node.get_map();
+                let map_size = map.grid_size.as_f32() * map.tile_size;
+
+                let bounds = Rect::new(
+                    map.world_offset.x,
+                    map.world_offset.y,
+                    map_size.x,
+                    map_size.y,
+                );
+
+                for guide in guides {
+                    guide.draw(bounds);
+                }
+            }
+        }
+
+        if let Some(test_dummy) = &node.test_dummy {
+            test_dummy.draw(node.get_map().world_offset);
+        }
+
         if let Some(tile_index) = node.selected_map_tile_index {
             let grid_size = node.get_map().grid_size;
             let tile_size = node.get_map().tile_size;
@@ -1546,6 +2584,22 @@ impl Node for Editor {
             )
         }
 
+        if let Some(selection) = &node.tile_selection {
+            let offset = match node.tile_selection_drag_origin {
+                Some(drag_origin) => {
+                    let cursor_world_position = scene::find_node_by_type::<EditorCamera>()
+                        .unwrap()
+                        .to_world_space(node.cursor_position);
+
+                    let coords = node.get_map().to_coords(cursor_world_position);
+                    coords.as_i32() - drag_origin.as_i32()
+                }
+                None => IVec2::ZERO,
+            };
+
+            selection.draw(node.get_map(), offset);
+        }
+
         if let Some(label) = &node.info_message {
             push_camera_state();
             set_default_camera();
@@ -1574,8 +2628,15 @@ impl Node for Editor {
         }
 
         {
-            let ctx = node.get_context();
+            let mut ctx = node.get_context();
             let mut gui = storage::get_mut::<EditorGui>();
+
+            if gui.is_window_open::<HistoryWindow>() {
+                let (history_entries, history_index) = node.history.get_entries();
+                ctx.history_entries = history_entries;
+                ctx.history_index = history_index;
+            }
+
             if let Some(action) = gui.draw(node.get_map(), ctx) {
                 res = Some(action);
             }
@@ -1608,8 +2669,8 @@ fn get_object_size(object: &MapObject) -> Vec2 {
         }
         MapObjectKind::Decoration => {
             if let Some(meta) = resources.decoration.get(&object.id) {
-                if let Some(texture_res) = resources.textures.get(&meta.sprite.texture_id) {
-                    res = Some(texture_res.frame_size());
+                if let Some(texture_res) = resources.textures.get(meta.sprite.texture_id()) {
+                    res = Some(get_decoration_size(texture_res, &meta.sprite));
                 } else {
                     label = Some("INVALID TEXTURE ID".to_string());
                 }
@@ -1655,3 +2716,69 @@ fn get_object_size(object: &MapObject) -> Vec2 {
         Editor::OBJECT_SELECTION_RECT_PADDING,
     ) * 2.0)
 }
+
+/// Returns the metadata of the tileset textures in `texture_ids`, from the first tileset in `map`
+/// that uses each texture, so that it can be written back to the resources
+fn get_tileset_metadata<I>(map: &Map, texture_ids: I) -> Vec<(String, TilesetMetadata)>
+where
+    I: IntoIterator<Item = String>,
+{
+    texture_ids
+        .into_iter()
+        .filter_map(|texture_id| {
+            let tileset = map
+                .tilesets
+                .values()
+                .find(|tileset| tileset.texture_id == texture_id)?;
+
+            let meta = TilesetMetadata {
+                tile_collisions: tileset
+                    .tile_collisions
+                    .iter()
+                    .map(|(&tile_id, &collision)| (tile_id, collision))
+                    .collect(),
+            };
+
+            Some((texture_id, meta))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::map::{MapTileCollision, MapTileset};
+
+    #[test]
+    fn test_tileset_metadata() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(4, 4));
+
+        let mut tileset = MapTileset::new("tileset", "texture", uvec2(64, 32), vec2(16.0, 16.0), 1);
+        tileset
+            .tile_collisions
+            .insert(2, MapTileCollision::Platform);
+        map.tilesets.insert("tileset".to_string(), tileset);
+
+        let metadata =
+            get_tileset_metadata(&map, ["texture".to_string(), "missing_texture".to_string()]);
+
+        // Only the textures that are used by the map are written back
+        assert_eq!(metadata.len(), 1);
+        assert_eq!(metadata[0].0, "texture");
+        assert_eq!(
+            metadata[0].1.tile_collisions.get(&2),
+            Some(&MapTileCollision::Platform)
+        );
+
+        // The current state of the map is written, so edits that were undone are not
+        map.tilesets
+            .get_mut("tileset")
+            .unwrap()
+            .tile_collisions
+            .clear();
+
+        let metadata = get_tileset_metadata(&map, ["texture".to_string()]);
+        assert!(metadata[0].1.tile_collisions.is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+//! Quick-save slots, that hold snapshots of a map that is being edited, so that a work in progress
+//! can be restored without overwriting the map file. Slots are stored next to the map file, so
+//! they stay with the map, and are only written when the user quick-saves.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use macroquad::experimental::collections::storage;
+
+use core::data::{deserialize_json_bytes, serialize_json_string};
+use core::Result;
+
+use crate::map::Map;
+use crate::Resources;
+
+pub const EDITOR_QUICK_SAVE_EXTENSION: &str = "json";
+
+/// The number of quick-save slots available for each map
+pub const QUICK_SAVE_SLOT_CNT: usize = 4;
+
+/// Returns the path of the file of the map at `map_path`, which is relative to the assets dir
+pub fn get_map_file_path(map_path: &str) -> PathBuf {
+    let resources = storage::get::<Resources>();
+    Path::new(&resources.assets_dir).join(map_path)
+}
+
+/// Returns the path of the quick-save in `slot`, for the map file at `map_file_path`. This is a
+/// file in the same directory as the map file, named after it.
+pub fn get_quick_save_path(map_file_path: &Path, slot: usize) -> PathBuf {
+    let file_stem = map_file_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    map_file_path
+        .with_file_name(format!("{}_quick_save_{}", file_stem, slot + 1))
+        .with_extension(EDITOR_QUICK_SAVE_EXTENSION)
+}
+
+pub fn has_quick_save(map_file_path: &Path, slot: usize) -> bool {
+    get_quick_save_path(map_file_path, slot).exists()
+}
+
+/// Save a snapshot of `map` to `slot`, overwriting any existing snapshot in that slot
+pub fn save_quick_save(map: &Map, map_file_path: &Path, slot: usize) -> Result<()> {
+    let path = get_quick_save_path(map_file_path, slot);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let json = serialize_json_string(map)?;
+    fs::write(path, json)?;
+
+    Ok(())
+}
+
+/// Load the snapshot in `slot`, for the map file at `map_file_path`
+pub fn load_quick_save(map_file_path: &Path, slot: usize) -> Result<Map> {
+    let path = get_quick_save_path(map_file_path, slot);
+
+    let bytes = fs::read(path)?;
+
+    let map = deserialize_json_bytes(&bytes)?;
+
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use macroquad::prelude::*;
+
+    use crate::map::{MapLayer, MapLayerKind, MapObject, MapObjectKind};
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn test_quick_save() {
+        let map_file_path = std::env::temp_dir()
+            .join("fishfight_test_quick_save")
+            .join("map.json");
+
+        let path = get_quick_save_path(&map_file_path, 1);
+        assert_eq!(path.parent(), map_file_path.parent());
+        assert!(path.ends_with("map_quick_save_2.json"));
+
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(4, 4));
+        let mut layer = MapLayer::new("objects", MapLayerKind::ObjectLayer, false, map.grid_size);
+        layer
+            .objects
+            .push(MapObject::new("sword", MapObjectKind::Item, vec2(8.0, 8.0)));
+        map.layers.insert("objects".to_string(), layer);
+
+        save_quick_save(&map, &map_file_path, 1).unwrap();
+        assert!(has_quick_save(&map_file_path, 1));
+        assert!(!has_quick_save(&map_file_path, 0));
+
+        let restored = load_quick_save(&map_file_path, 1).unwrap();
+        assert_eq!(
+            restored.get_content_hash().unwrap(),
+            map.get_content_hash().unwrap()
+        );
+        assert_eq!(restored.layers["objects"].objects[0].id, "sword");
+
+        assert!(load_quick_save(&map_file_path, 0).is_err());
+
+        fs::remove_dir_all(map_file_path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,146 @@
+//! This implements `EditorSession`, which holds the state of the editor UI for a specific map, so
+//! that the context it was last edited in can be restored when the map is opened again.
+
+use std::fs;
+use std::path::PathBuf;
+
+use macroquad::{experimental::collections::storage, prelude::*};
+
+use serde::{Deserialize, Serialize};
+
+use core::data::{deserialize_json_bytes, serialize_json_string};
+use core::Result;
+
+use super::EditorAction;
+
+use crate::paths::get_saves_dir;
+use crate::resources::map_name_to_filename;
+use crate::Resources;
+
+/// The directory, relative to the saves directory, that session files are stored in. If the saves
+/// directory can not be determined, this will be relative to the assets directory.
+pub const EDITOR_SESSIONS_DIR: &str = "editor_sessions";
+pub const EDITOR_SESSION_EXTENSION: &str = "json";
+
+/// These are the windows that will be reopened when a session is restored.
+/// Windows that hold unsaved input, like the create windows, are not persisted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EditorSessionWindow {
+    BackgroundProperties,
+    MapProperties,
+    TilesetProperties { tileset_id: String },
+    ObjectProperties { layer_id: String, index: usize },
+    TileProperties { layer_id: String, index: usize },
+}
+
+impl EditorSessionWindow {
+    /// Returns the action that will open the window
+    pub fn to_action(&self) -> EditorAction {
+        match self.clone() {
+            Self::BackgroundProperties => EditorAction::OpenBackgroundPropertiesWindow,
+            Self::MapProperties => EditorAction::OpenMapPropertiesWindow,
+            Self::TilesetProperties { tileset_id } => {
+                EditorAction::OpenTilesetPropertiesWindow(tileset_id)
+            }
+            Self::ObjectProperties { layer_id, index } => {
+                EditorAction::OpenObjectPropertiesWindow { layer_id, index }
+            }
+            Self::TileProperties { layer_id, index } => {
+                EditorAction::OpenTilePropertiesWindow { layer_id, index }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorSession {
+    /// The name of the selected tool, as specified in its `EditorToolParams`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_tool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_layer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_tileset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selected_tile: Option<u32>,
+    #[serde(with = "core::json::vec2_opt", skip_serializing_if = "Option::is_none")]
+    pub camera_position: Option<Vec2>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_scale: Option<f32>,
+    pub should_draw_grid: bool,
+    pub should_snap_to_grid: bool,
+    pub is_parallax_disabled: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub open_windows: Vec<EditorSessionWindow>,
+}
+
+impl EditorSession {
+    /// Returns the path of the session file for the map at the specified path
+    pub fn get_path(map_path: &str) -> PathBuf {
+        let file_name = map_name_to_filename(&map_path.replace(['/', '\\'], "_"));
+
+        let dir = get_saves_dir().unwrap_or_else(|| {
+            let resources = storage::get::<Resources>();
+            PathBuf::from(&resources.assets_dir)
+        });
+
+        dir.join(EDITOR_SESSIONS_DIR)
+            .join(file_name)
+            .with_extension(EDITOR_SESSION_EXTENSION)
+    }
+
+    /// Load the session for the map at the specified path. This will return `None` if there is
+    /// no session file for the map or if the file could not be parsed.
+    pub fn load(map_path: &str) -> Option<Self> {
+        let path = Self::get_path(map_path);
+
+        let bytes = fs::read(&path).ok()?;
+
+        match deserialize_json_bytes(&bytes) {
+            Ok(session) => Some(session),
+            Err(err) => {
+                #[cfg(debug_assertions)]
+                println!(
+                    "WARNING: Ignoring invalid editor session file '{}': {}",
+                    path.display(),
+                    err
+                );
+
+                None
+            }
+        }
+    }
+
+    /// Save the session for the map at the specified path
+    pub fn save(&self, map_path: &str) -> Result<()> {
+        let path = Self::get_path(map_path);
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let json = serialize_json_string(self)?;
+        fs::write(path, json)?;
+
+        Ok(())
+    }
+}
+
+impl Default for EditorSession {
+    fn default() -> Self {
+        EditorSession {
+            selected_tool: None,
+            selected_layer: None,
+            selected_tileset: None,
+            selected_tile: None,
+            camera_position: None,
+            camera_scale: None,
+            should_draw_grid: true,
+            should_snap_to_grid: false,
+            is_parallax_disabled: false,
+            open_windows: Vec::new(),
+        }
+    }
+}
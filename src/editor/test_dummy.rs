@@ -0,0 +1,107 @@
+use macroquad::color;
+use macroquad::prelude::*;
+
+use core::Transform;
+
+use crate::game::get_fixed_delta;
+use crate::physics::{create_collision_world, fixed_update_physics_body};
+use crate::player::PlayerCharacterMetadata;
+use crate::{CollisionWorld, Map, PhysicsBody, PhysicsBodyParams};
+
+/// A physics body that can be dropped into the map in the editor, to preview how a body will
+/// behave with the current map collisions. The dummy is simulated in its own collision world,
+/// built from the map at the time it was spawned, so it will not react to later edits.
+pub struct TestDummy {
+    collision_world: CollisionWorld,
+    transform: Transform,
+    body: PhysicsBody,
+    /// The positions of the dummy, one for each simulated tick
+    path: Vec<Vec2>,
+    timer: f32,
+}
+
+impl TestDummy {
+    /// The length of the preview, in seconds. The dummy stops being simulated after this.
+    const PREVIEW_DURATION: f32 = 5.0;
+
+    const PATH_COLOR: Color = Color {
+        r: 1.0,
+        g: 1.0,
+        b: 1.0,
+        a: 0.5,
+    };
+
+    /// Spawn a dummy with its top center at `position`, in world space
+    pub fn new(map: &Map, position: Vec2) -> Self {
+        let mut collision_world = create_collision_world(map);
+
+        // The dummy has the same collider as a player with the default character
+        let size = PlayerCharacterMetadata::default_collider_size();
+        let offset = vec2(-size.x / 2.0, 0.0);
+
+        let position = position - map.world_offset;
+
+        let actor = collision_world.add_actor(position + offset, size.x as i32, size.y as i32);
+
+        let body = PhysicsBody::new(
+            actor,
+            None,
+            PhysicsBodyParams {
+                size,
+                offset,
+                can_rotate: false,
+                ..Default::default()
+            },
+        );
+
+        TestDummy {
+            collision_world,
+            transform: Transform::from(position),
+            body,
+            path: vec![position],
+            timer: 0.0,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.timer >= Self::PREVIEW_DURATION
+    }
+
+    pub fn fixed_update(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+
+        fixed_update_physics_body(
+            &mut self.collision_world,
+            &mut self.transform,
+            &mut self.body,
+        );
+
+        self.path.push(self.transform.position);
+
+        self.timer += get_fixed_delta();
+    }
+
+    pub fn draw(&self, world_offset: Vec2) {
+        for points in self.path.windows(2) {
+            let (begin, end) = (points[0] + world_offset, points[1] + world_offset);
+            draw_line(begin.x, begin.y, end.x, end.y, 2.0, Self::PATH_COLOR);
+        }
+
+        let rect = self
+            .body
+            .as_rect(self.transform.position)
+            .offset(world_offset);
+
+        let color = if self.body.is_on_platform {
+            color::YELLOW
+        } else if self.body.is_on_ground {
+            color::RED
+        } else {
+            color::GREEN
+        };
+
+        draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 2.0, color);
+    }
+}
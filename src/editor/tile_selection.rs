@@ -0,0 +1,178 @@
+//! A tile selection is a rectangular region of tiles, on a tile layer, that is box-selected in the
+//! editor, by dragging while the box select key is held. The selected region can then be dragged
+//! to a new location, which moves the tiles, leaving the source empty, as one step in the history.
+//!
+//! The selection holds a copy of the tiles in the region, so that a ghost of them can be drawn at
+//! the location that they are being dragged to.
+
+use macroquad::{experimental::collections::storage, prelude::*};
+
+use core::math::URect;
+
+use crate::gui::SELECTION_HIGHLIGHT_COLOR;
+use crate::map::{Map, MapTile};
+use crate::Resources;
+
+/// The tint that the ghost of the moved tiles is drawn with
+const GHOST_COLOR: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 0.5,
+};
+
+const OUTLINE_WIDTH: f32 = 3.0;
+
+#[derive(Debug, Clone)]
+pub struct TileSelection {
+    pub layer_id: String,
+    pub region: URect,
+    /// The tiles in `region`, row by row, as they were when the selection was made
+    pub tiles: Vec<Option<MapTile>>,
+}
+
+impl TileSelection {
+    /// Select the tiles in `region`, on the specified layer
+    pub fn new(map: &Map, layer_id: &str, region: URect) -> Self {
+        let mut tiles = Vec::new();
+
+        if let Some(layer) = map.layers.get(layer_id) {
+            for y in region.top()..region.bottom() {
+                for x in region.left()..region.right() {
+                    let tile = layer.tiles.get(map.to_index(uvec2(x, y))).cloned();
+                    tiles.push(tile.flatten());
+                }
+            }
+        }
+
+        TileSelection {
+            layer_id: layer_id.to_string(),
+            region,
+            tiles,
+        }
+    }
+
+    /// Returns the region that holds both of the specified cells, at opposite corners
+    pub fn get_region(a: UVec2, b: UVec2) -> URect {
+        let min = a.min(b);
+        let max = a.max(b);
+
+        URect::new(min.x, min.y, max.x - min.x + 1, max.y - min.y + 1)
+    }
+
+    /// Returns the region moved by `offset`, clipped to a map of the specified grid size, or
+    /// `None`, if no part of it is within the map
+    pub fn get_moved_region(&self, offset: IVec2, grid_size: UVec2) -> Option<URect> {
+        get_moved_region(self.region, offset, grid_size)
+    }
+
+    /// Draw the outline of the selection, and, if `offset` is not zero, a ghost of the selected
+    /// tiles, moved by `offset`, clipped to the map
+    pub fn draw(&self, map: &Map, offset: IVec2) {
+        if offset != IVec2::ZERO {
+            let resources = storage::get::<Resources>();
+
+            for (i, tile) in self.tiles.iter().enumerate() {
+                let tile = match tile {
+                    Some(tile) => tile,
+                    None => continue,
+                };
+
+                let coords = ivec2(
+                    (self.region.x + i as u32 % self.region.w) as i32,
+                    (self.region.y + i as u32 / self.region.w) as i32,
+                ) + offset;
+
+                if !is_in_grid(coords, map.grid_size) {
+                    continue;
+                }
+
+                if let Some(texture_entry) = resources.textures.get(&tile.texture_id) {
+                    let position = map.to_position(coords.as_u32());
+
+                    draw_texture_ex(
+                        texture_entry.texture,
+                        position.x,
+                        position.y,
+                        GHOST_COLOR,
+                        DrawTextureParams {
+                            source: Some(Rect::new(
+                                tile.texture_coords.x,
+                                tile.texture_coords.y,
+                                map.tile_size.x,
+                                map.tile_size.y,
+                            )),
+                            dest_size: Some(map.tile_size),
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+        }
+
+        if let Some(region) = self.get_moved_region(offset, map.grid_size) {
+            let position = map.to_position(region.point());
+            let size = region.size().as_f32() * map.tile_size;
+
+            draw_rectangle_lines(
+                position.x,
+                position.y,
+                size.x,
+                size.y,
+                OUTLINE_WIDTH,
+                SELECTION_HIGHLIGHT_COLOR,
+            );
+        }
+    }
+}
+
+fn is_in_grid(coords: IVec2, grid_size: UVec2) -> bool {
+    coords.x >= 0
+        && coords.y >= 0
+        && (coords.x as u32) < grid_size.x
+        && (coords.y as u32) < grid_size.y
+}
+
+/// Returns `region` moved by `offset`, clipped to a map of the specified grid size, or `None`, if
+/// no part of it is within the map
+pub fn get_moved_region(region: URect, offset: IVec2, grid_size: UVec2) -> Option<URect> {
+    let min = (region.point().as_i32() + offset).max(IVec2::ZERO);
+    let max = (region.point().as_i32() + region.size().as_i32() + offset).min(grid_size.as_i32());
+
+    if min.x >= max.x || min.y >= max.y {
+        return None;
+    }
+
+    let size = max - min;
+
+    Some(URect::new(
+        min.x as u32,
+        min.y as u32,
+        size.x as u32,
+        size.y as u32,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moved_region_is_clipped() {
+        let grid_size = uvec2(10, 8);
+        let region = TileSelection::get_region(uvec2(4, 5), uvec2(2, 3));
+
+        assert_eq!(region.point(), uvec2(2, 3));
+        assert_eq!(region.size(), uvec2(3, 3));
+
+        let moved = get_moved_region(region, ivec2(6, -2), grid_size).unwrap();
+        assert_eq!(moved.point(), uvec2(8, 1));
+        assert_eq!(moved.size(), uvec2(2, 3));
+
+        let moved = get_moved_region(region, ivec2(-4, 4), grid_size).unwrap();
+        assert_eq!(moved.point(), uvec2(0, 7));
+        assert_eq!(moved.size(), uvec2(1, 1));
+
+        assert!(get_moved_region(region, ivec2(8, 0), grid_size).is_none());
+    }
+}
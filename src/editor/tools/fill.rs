@@ -0,0 +1,197 @@
+use macroquad::{color, prelude::*};
+
+use super::{EditorAction, EditorContext, EditorTool, EditorToolParams};
+
+use crate::{
+    editor::EditorCamera,
+    map::{Map, MapLayerKind},
+};
+
+/// Returns the coordinates of all the cells, on the specified tile layer, that are connected to
+/// `start`, horizontally or vertically, through cells that hold the same tile, or no tile, as the
+/// cell at `start`. This is done iteratively, so large regions will not overflow the stack.
+pub fn get_fill_cells(map: &Map, layer_id: &str, start: UVec2) -> Vec<UVec2> {
+    let layer = match map.layers.get(layer_id) {
+        Some(layer) => layer,
+        None => return Vec::new(),
+    };
+
+    let get_tile = |coords: UVec2| {
+        layer
+            .tiles
+            .get(map.to_index(coords))
+            .and_then(|tile| tile.as_ref())
+            .map(|tile| (tile.tileset_id.as_str(), tile.tile_id))
+    };
+
+    let target = get_tile(start);
+
+    let mut is_visited = vec![false; (map.grid_size.x * map.grid_size.y) as usize];
+    is_visited[map.to_index(start)] = true;
+
+    let mut cells = Vec::new();
+    let mut stack = vec![start];
+
+    while let Some(coords) = stack.pop() {
+        cells.push(coords);
+
+        let mut neighbors = Vec::with_capacity(4);
+
+        if coords.x > 0 {
+            neighbors.push(uvec2(coords.x - 1, coords.y));
+        }
+
+        if coords.x + 1 < map.grid_size.x {
+            neighbors.push(uvec2(coords.x + 1, coords.y));
+        }
+
+        if coords.y > 0 {
+            neighbors.push(uvec2(coords.x, coords.y - 1));
+        }
+
+        if coords.y + 1 < map.grid_size.y {
+            neighbors.push(uvec2(coords.x, coords.y + 1));
+        }
+
+        for neighbor in neighbors {
+            let i = map.to_index(neighbor);
+
+            if !is_visited[i] && get_tile(neighbor) == target {
+                is_visited[i] = true;
+                stack.push(neighbor);
+            }
+        }
+    }
+
+    cells
+}
+
+#[derive(Default)]
+pub struct FillTool {
+    params: EditorToolParams,
+}
+
+impl FillTool {
+    pub fn new() -> Self {
+        let params = EditorToolParams {
+            name: "Fill Tiles".to_string(),
+            icon_texture_id: "fill_tool_icon".to_string(),
+            ..Default::default()
+        };
+
+        FillTool { params }
+    }
+}
+
+impl EditorTool for FillTool {
+    fn get_params(&self) -> &EditorToolParams {
+        &self.params
+    }
+
+    fn get_action(&mut self, map: &Map, ctx: &EditorContext) -> Option<EditorAction> {
+        let cursor_world_position = scene::find_node_by_type::<EditorCamera>()
+            .unwrap()
+            .to_world_space(ctx.cursor_position);
+
+        if !map.contains(cursor_world_position) || !self.is_available(map, ctx) {
+            return None;
+        }
+
+        let layer_id = ctx.selected_layer.as_ref()?;
+        let tileset_id = ctx.selected_tileset.as_ref()?;
+        let tile_id = ctx.selected_tile?;
+
+        let coords = map.to_coords(cursor_world_position);
+
+        // Filling a region with the tile it already holds would only add an empty step to the
+        // history
+        if let Some(tile) = map.get_tile(layer_id, coords.x, coords.y) {
+            if &tile.tileset_id == tileset_id && tile.tile_id == tile_id {
+                return None;
+            }
+        }
+
+        let actions = get_fill_cells(map, layer_id, coords)
+            .into_iter()
+            .map(|coords| EditorAction::PlaceTile {
+                id: tile_id,
+                layer_id: layer_id.clone(),
+                tileset_id: tileset_id.clone(),
+                coords,
+            })
+            .collect();
+
+        Some(EditorAction::Batch(actions))
+    }
+
+    fn is_available(&self, map: &Map, ctx: &EditorContext) -> bool {
+        if let Some(layer_id) = &ctx.selected_layer {
+            let layer = map.layers.get(layer_id).unwrap();
+            return layer.kind == MapLayerKind::TileLayer;
+        }
+
+        false
+    }
+
+    fn draw_cursor(&mut self, map: &Map, ctx: &EditorContext) -> Option<EditorAction> {
+        let cursor_world_position = scene::find_node_by_type::<EditorCamera>()
+            .unwrap()
+            .to_world_space(ctx.cursor_position);
+
+        if map.contains(cursor_world_position) && self.is_available(map, ctx) {
+            let coords = map.to_coords(cursor_world_position);
+            let position = map.to_position(coords);
+
+            draw_rectangle_lines(
+                position.x,
+                position.y,
+                map.tile_size.x,
+                map.tile_size.y,
+                2.0,
+                color::YELLOW,
+            );
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::map::{MapLayer, MapTile};
+
+    #[test]
+    fn test_fill_cells() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(4, 3));
+
+        // A wall of tiles, with a diagonal gap, that splits the empty cells in two regions
+        let mut layer = MapLayer::new("tiles", MapLayerKind::TileLayer, false, map.grid_size);
+        for i in [1, 6, 9] {
+            layer.tiles[i] = Some(MapTile {
+                tile_id: 0,
+                tileset_id: "tileset".to_string(),
+                texture_id: "tileset".to_string(),
+                texture_coords: Vec2::ZERO,
+                attributes: Vec::new(),
+            });
+        }
+
+        map.layers.insert("tiles".to_string(), layer);
+
+        let mut cells = get_fill_cells(&map, "tiles", uvec2(0, 0));
+        cells.sort_by_key(|coords| map.to_index(*coords));
+        assert_eq!(
+            cells,
+            vec![uvec2(0, 0), uvec2(0, 1), uvec2(1, 1), uvec2(0, 2)]
+        );
+
+        let cells = get_fill_cells(&map, "tiles", uvec2(3, 2));
+        assert_eq!(cells.len(), 5);
+
+        // Tiles are only connected horizontally and vertically
+        let cells = get_fill_cells(&map, "tiles", uvec2(2, 1));
+        assert_eq!(cells, vec![uvec2(2, 1)]);
+    }
+}
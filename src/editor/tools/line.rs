@@ -0,0 +1,208 @@
+use macroquad::prelude::*;
+
+use super::{EditorAction, EditorContext, EditorTool, EditorToolParams};
+
+use crate::{
+    editor::EditorCamera,
+    gui::SELECTION_HIGHLIGHT_COLOR,
+    map::{Map, MapLayerKind},
+};
+
+/// Returns the cells on the line between `start` and `end`, both included, in order, using
+/// Bresenham's line algorithm
+pub fn get_line_cells(start: UVec2, end: UVec2) -> Vec<UVec2> {
+    let (mut x, mut y) = (start.x as i32, start.y as i32);
+    let (end_x, end_y) = (end.x as i32, end.y as i32);
+
+    let dx = (end_x - x).abs();
+    let dy = -(end_y - y).abs();
+    let step_x = if x < end_x { 1 } else { -1 };
+    let step_y = if y < end_y { 1 } else { -1 };
+
+    let mut err = dx + dy;
+    let mut cells = Vec::new();
+
+    loop {
+        cells.push(uvec2(x as u32, y as u32));
+
+        if x == end_x && y == end_y {
+            break;
+        }
+
+        let err2 = err * 2;
+
+        if err2 >= dy {
+            err += dy;
+            x += step_x;
+        }
+
+        if err2 <= dx {
+            err += dx;
+            y += step_y;
+        }
+    }
+
+    cells
+}
+
+pub struct LineTool {
+    params: EditorToolParams,
+    /// The cell that the line was started in, while one is being dragged
+    start: Option<UVec2>,
+    /// The cell that the line currently ends in
+    end: UVec2,
+    /// This is set by `get_action`, which is called every frame while the action is held, so that
+    /// the end of the line can be detected in `update`, on the frame it is released
+    is_dragged: bool,
+}
+
+impl LineTool {
+    pub fn new() -> Self {
+        let params = EditorToolParams {
+            name: "Draw Lines".to_string(),
+            icon_texture_id: "line_tool_icon".to_string(),
+            is_continuous: true,
+        };
+
+        LineTool {
+            params,
+            start: None,
+            end: UVec2::ZERO,
+            is_dragged: false,
+        }
+    }
+}
+
+impl Default for LineTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EditorTool for LineTool {
+    fn get_params(&self) -> &EditorToolParams {
+        &self.params
+    }
+
+    fn get_action(&mut self, map: &Map, ctx: &EditorContext) -> Option<EditorAction> {
+        let cursor_world_position = scene::find_node_by_type::<EditorCamera>()
+            .unwrap()
+            .to_world_space(ctx.cursor_position);
+
+        if self.start.is_some() {
+            self.is_dragged = true;
+
+            if map.contains(cursor_world_position) {
+                self.end = map.to_coords(cursor_world_position);
+            }
+        } else if map.contains(cursor_world_position) && self.is_available(map, ctx) {
+            let coords = map.to_coords(cursor_world_position);
+
+            self.start = Some(coords);
+            self.end = coords;
+            self.is_dragged = true;
+        }
+
+        // The tiles are placed when the line is released, in `update`
+        None
+    }
+
+    fn update(&mut self, map: &Map, ctx: &EditorContext) -> Option<EditorAction> {
+        let start = self.start?;
+
+        if self.is_dragged {
+            self.is_dragged = false;
+            return None;
+        }
+
+        self.start = None;
+
+        if !self.is_available(map, ctx) {
+            return None;
+        }
+
+        let layer_id = ctx.selected_layer.as_ref()?;
+        let tileset_id = ctx.selected_tileset.as_ref()?;
+        let tile_id = ctx.selected_tile?;
+
+        let actions = get_line_cells(start, self.end)
+            .into_iter()
+            .map(|coords| EditorAction::PlaceTile {
+                id: tile_id,
+                layer_id: layer_id.clone(),
+                tileset_id: tileset_id.clone(),
+                coords,
+            })
+            .collect();
+
+        Some(EditorAction::Batch(actions))
+    }
+
+    fn is_available(&self, map: &Map, ctx: &EditorContext) -> bool {
+        if let Some(layer_id) = &ctx.selected_layer {
+            let layer = map.layers.get(layer_id).unwrap();
+            return layer.kind == MapLayerKind::TileLayer;
+        }
+
+        false
+    }
+
+    fn draw_cursor(&mut self, map: &Map, ctx: &EditorContext) -> Option<EditorAction> {
+        let cursor_world_position = scene::find_node_by_type::<EditorCamera>()
+            .unwrap()
+            .to_world_space(ctx.cursor_position);
+
+        let cells = match self.start {
+            Some(start) => get_line_cells(start, self.end),
+            None if map.contains(cursor_world_position) && self.is_available(map, ctx) => {
+                vec![map.to_coords(cursor_world_position)]
+            }
+            None => Vec::new(),
+        };
+
+        for coords in cells {
+            let position = map.to_position(coords);
+
+            draw_rectangle_lines(
+                position.x,
+                position.y,
+                map.tile_size.x,
+                map.tile_size.y,
+                2.0,
+                SELECTION_HIGHLIGHT_COLOR,
+            );
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_cells() {
+        assert_eq!(get_line_cells(uvec2(2, 3), uvec2(2, 3)), vec![uvec2(2, 3)]);
+
+        assert_eq!(
+            get_line_cells(uvec2(0, 0), uvec2(4, 2)),
+            vec![
+                uvec2(0, 0),
+                uvec2(1, 1),
+                uvec2(2, 1),
+                uvec2(3, 2),
+                uvec2(4, 2),
+            ]
+        );
+
+        // This line is the same in both directions, apart from the order of the cells
+        let mut cells = get_line_cells(uvec2(5, 1), uvec2(1, 6));
+        assert_eq!(cells.first(), Some(&uvec2(5, 1)));
+        assert_eq!(cells.last(), Some(&uvec2(1, 6)));
+        assert_eq!(cells.len(), 6);
+
+        cells.reverse();
+        assert_eq!(cells, get_line_cells(uvec2(1, 6), uvec2(5, 1)));
+    }
+}
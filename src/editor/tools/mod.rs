@@ -1,10 +1,16 @@
 use std::{any::TypeId, collections::HashMap};
 
 mod eraser;
+mod fill;
+mod line;
 mod placement;
 
 pub use eraser::EraserTool;
-pub use placement::{ObjectPlacementTool, SpawnPointPlacementTool, TilePlacementTool};
+pub use fill::FillTool;
+pub use line::LineTool;
+pub use placement::{
+    DecorationPlacementTool, ObjectPlacementTool, SpawnPointPlacementTool, TilePlacementTool,
+};
 
 use macroquad::prelude::*;
 
@@ -33,6 +39,18 @@ pub fn get_tool_instance_of_id(id: &TypeId) -> &'static mut dyn EditorTool {
         .as_mut()
 }
 
+pub fn get_tool_id_of_name(name: &str) -> Option<TypeId> {
+    unsafe { get_tool_instance_directory() }
+        .iter()
+        .find_map(|(id, tool)| {
+            if tool.get_params().name == name {
+                Some(*id)
+            } else {
+                None
+            }
+        })
+}
+
 // TODO: Cast to T
 pub fn get_tool_instance<T: EditorTool + 'static>() -> &'static mut dyn EditorTool {
     let id = TypeId::of::<T>();
@@ -3,16 +3,34 @@ use macroquad::{color, experimental::collections::storage, prelude::*};
 use super::{EditorAction, EditorContext, EditorTool, EditorToolParams};
 
 use crate::{
-    editor::EditorCamera,
-    map::{Map, MapLayerKind},
-    rand::ChooseRandom,
+    editor::{tile_selection::TileSelection, EditorCamera},
+    gui::SELECTION_HIGHLIGHT_COLOR,
+    map::{draw_decoration_preview, Map, MapLayerKind, MapRng},
+    rand::{self, ChooseRandom},
     Resources,
 };
 
-#[derive(Default)]
+/// Returns a random tile from `tiles`, which must not be empty
+fn pick_random_tile(rng: &mut MapRng, tiles: &[u32]) -> u32 {
+    let i = rng.gen_range(0, tiles.len() as u32) as usize;
+    tiles[i]
+}
+
+/// The width of the outline of a rectangle fill, while it is being dragged
+const RECT_FILL_OUTLINE_WIDTH: f32 = 3.0;
+
 pub struct TilePlacementTool {
     params: EditorToolParams,
     coords: Option<UVec2>,
+    /// This is used to pick tiles when a set of random tiles is selected
+    rng: MapRng,
+    /// The cell that a rectangle fill was started in, while one is being dragged
+    rect_origin: Option<UVec2>,
+    /// The cell, at the opposite corner of `rect_origin`, that the rectangle currently extends to
+    rect_end: UVec2,
+    /// This is set by `get_action`, which is called every frame while the action is held, so that
+    /// the end of a rectangle fill can be detected in `update`, on the frame it is released
+    is_rect_dragged: bool,
 }
 
 impl TilePlacementTool {
@@ -26,7 +44,49 @@ impl TilePlacementTool {
         TilePlacementTool {
             params,
             coords: None,
+            rng: MapRng(rand::rand() as u64),
+            rect_origin: None,
+            rect_end: UVec2::ZERO,
+            is_rect_dragged: false,
+        }
+    }
+
+    /// Returns a batch that places the selected tile, or random tiles, in every cell of the
+    /// rectangle that has been dragged out from `origin`
+    fn get_rect_fill_action(
+        &mut self,
+        origin: UVec2,
+        map: &Map,
+        ctx: &EditorContext,
+    ) -> Option<EditorAction> {
+        if !self.is_available(map, ctx) {
+            return None;
         }
+
+        let layer_id = ctx.selected_layer.as_ref()?;
+        let tileset_id = ctx.selected_tileset.as_ref()?;
+        let tile_id = ctx.selected_tile?;
+
+        let region = TileSelection::get_region(origin, self.rect_end);
+
+        let rng = &mut self.rng;
+        let random_tiles = &ctx.random_tiles;
+
+        let action = EditorAction::fill_region(layer_id, tileset_id, region, || {
+            if random_tiles.len() > 1 {
+                pick_random_tile(rng, random_tiles)
+            } else {
+                tile_id
+            }
+        });
+
+        Some(action)
+    }
+}
+
+impl Default for TilePlacementTool {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -40,15 +100,48 @@ impl EditorTool for TilePlacementTool {
             .unwrap()
             .to_world_space(ctx.cursor_position);
 
+        // The tiles are placed when a rectangle fill is released, in `update`
+        if self.rect_origin.is_some() {
+            self.is_rect_dragged = true;
+
+            if map.contains(cursor_world_position) {
+                self.rect_end = map.to_coords(cursor_world_position);
+            }
+
+            return None;
+        }
+
         if map.contains(cursor_world_position) {
             if let Some(layer_id) = &ctx.selected_layer {
                 let camera = scene::find_node_by_type::<EditorCamera>().unwrap();
                 let world_position = camera.to_world_space(ctx.cursor_position);
 
                 if let Some(tileset_id) = &ctx.selected_tileset {
-                    if let Some(tile_id) = ctx.selected_tile {
+                    if let Some(mut tile_id) = ctx.selected_tile {
                         let coords = map.to_coords(world_position);
 
+                        if ctx.is_rect_filling {
+                            self.rect_origin = Some(coords);
+                            self.rect_end = coords;
+                            self.is_rect_dragged = true;
+
+                            return None;
+                        }
+
+                        if ctx.random_tiles.len() > 1 {
+                            // Tiles that are already from the random set are not replaced, so
+                            // that holding the brush over a tile will not keep re-rolling it
+                            if let Some(tile) = map.get_tile(layer_id, coords.x, coords.y) {
+                                if &tile.tileset_id == tileset_id
+                                    && ctx.random_tiles.contains(&tile.tile_id)
+                                {
+                                    return None;
+                                }
+                            }
+
+                            tile_id = pick_random_tile(&mut self.rng, &ctx.random_tiles);
+                        }
+
                         return Some(EditorAction::PlaceTile {
                             id: tile_id,
                             layer_id: layer_id.clone(),
@@ -64,6 +157,15 @@ impl EditorTool for TilePlacementTool {
     }
 
     fn update(&mut self, map: &Map, ctx: &EditorContext) -> Option<EditorAction> {
+        if let Some(origin) = self.rect_origin {
+            if !self.is_rect_dragged {
+                self.rect_origin = None;
+                return self.get_rect_fill_action(origin, map, ctx);
+            }
+
+            self.is_rect_dragged = false;
+        }
+
         #[allow(unused_mut)]
         let mut res = None;
 
@@ -141,6 +243,22 @@ impl EditorTool for TilePlacementTool {
     }
 
     fn draw_cursor(&mut self, map: &Map, ctx: &EditorContext) -> Option<EditorAction> {
+        if let Some(origin) = self.rect_origin {
+            let region = TileSelection::get_region(origin, self.rect_end);
+
+            let position = map.to_position(region.point());
+            let size = region.size().as_f32() * map.tile_size;
+
+            draw_rectangle_lines(
+                position.x,
+                position.y,
+                size.x,
+                size.y,
+                RECT_FILL_OUTLINE_WIDTH,
+                SELECTION_HIGHLIGHT_COLOR,
+            );
+        }
+
         let cursor_world_position = scene::find_node_by_type::<EditorCamera>()
             .unwrap()
             .to_world_space(ctx.cursor_position);
@@ -306,3 +424,121 @@ impl EditorTool for SpawnPointPlacementTool {
         Some(action)
     }
 }
+
+/// The opacity of the decoration that is drawn at the cursor, before it is placed
+const DECORATION_PREVIEW_ALPHA: f32 = 0.5;
+
+pub struct DecorationPlacementTool {
+    params: EditorToolParams,
+}
+
+impl DecorationPlacementTool {
+    pub fn new() -> Self {
+        let params = EditorToolParams {
+            name: "Place Decorations".to_string(),
+            icon_texture_id: "decoration_placement_tool_icon".to_string(),
+            ..Default::default()
+        };
+
+        DecorationPlacementTool { params }
+    }
+
+    /// Returns the world position that a decoration will be placed at, if the cursor is over the
+    /// map and an object layer is selected
+    fn get_placement_position(map: &Map, ctx: &EditorContext) -> Option<Vec2> {
+        let mut position = scene::find_node_by_type::<EditorCamera>()
+            .unwrap()
+            .to_world_space(ctx.cursor_position);
+
+        if !map.contains(position) {
+            return None;
+        }
+
+        let layer_id = ctx.selected_layer.as_ref()?;
+        let layer = map.layers.get(layer_id).unwrap();
+
+        if layer.kind != MapLayerKind::ObjectLayer {
+            return None;
+        }
+
+        if ctx.should_snap_to_grid {
+            let coords = map.to_coords(position);
+            position = map.to_position(coords);
+        }
+
+        Some(position)
+    }
+}
+
+impl EditorTool for DecorationPlacementTool {
+    fn get_params(&self) -> &EditorToolParams {
+        &self.params
+    }
+
+    fn get_action(&mut self, map: &Map, ctx: &EditorContext) -> Option<EditorAction> {
+        let id = ctx.selected_decoration.clone()?;
+        let position = Self::get_placement_position(map, ctx)?;
+        let layer_id = ctx.selected_layer.clone().unwrap();
+
+        let action = EditorAction::CreateDecoration {
+            id,
+            position: position - map.world_offset,
+            layer_id,
+        };
+
+        Some(action)
+    }
+
+    fn is_available(&self, map: &Map, ctx: &EditorContext) -> bool {
+        if let Some(layer_id) = &ctx.selected_layer {
+            let layer = map.layers.get(layer_id).unwrap();
+            return layer.kind == MapLayerKind::ObjectLayer;
+        }
+
+        false
+    }
+
+    fn draw_cursor(&mut self, map: &Map, ctx: &EditorContext) -> Option<EditorAction> {
+        if let Some(id) = &ctx.selected_decoration {
+            if let Some(position) = Self::get_placement_position(map, ctx) {
+                let resources = storage::get::<Resources>();
+
+                if let Some(meta) = resources.decoration.get(id) {
+                    if let Some(texture_res) = resources.textures.get(meta.sprite.texture_id()) {
+                        draw_decoration_preview(
+                            texture_res,
+                            &meta.sprite,
+                            position,
+                            DECORATION_PREVIEW_ALPHA,
+                        );
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_tiles_are_reproducible() {
+        let tiles = [3, 7, 12];
+
+        let picks = |seed: u64| {
+            let mut rng = MapRng(seed);
+            (0..32)
+                .map(|_| pick_random_tile(&mut rng, &tiles))
+                .collect::<Vec<_>>()
+        };
+
+        let first = picks(42);
+
+        assert_eq!(first, picks(42));
+        assert!(first.iter().all(|id| tiles.contains(id)));
+        assert!(tiles.iter().all(|id| first.contains(id)));
+    }
+}
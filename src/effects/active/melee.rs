@@ -0,0 +1,307 @@
+use macroquad::color;
+use macroquad::prelude::*;
+
+use hecs::{Entity, World};
+
+use core::Transform;
+
+use crate::game::get_fixed_delta;
+use crate::player::{on_player_damage, Player, PlayerState};
+use crate::{PassiveEffectInstance, PassiveEffectMetadata, PhysicsBody};
+
+#[derive(Clone)]
+pub struct MeleeSwingParams {
+    pub range: f32,
+    /// The arc of the swing, in radians
+    pub arc: f32,
+    pub duration: f32,
+    pub knockback: f32,
+    pub is_lethal: bool,
+    pub passive_effects: Vec<PassiveEffectMetadata>,
+}
+
+/// A transient hitbox, shaped as a circle sector in front of its owner, that sweeps from the top of
+/// its arc to the bottom, over its duration. Every entity it overlaps will only be hit once per
+/// swing. The duration should match the length of the attack animation of the weapon, for the
+/// hitbox to be in sync with what is drawn.
+pub struct MeleeSwing {
+    pub owner: Entity,
+    /// The offset from the owners position to the origin of the swing
+    pub offset: Vec2,
+    pub is_facing_left: bool,
+    pub range: f32,
+    /// The arc of the swing, in radians
+    pub arc: f32,
+    pub duration: f32,
+    pub knockback: f32,
+    pub is_lethal: bool,
+    pub passive_effects: Vec<PassiveEffectMetadata>,
+    pub timer: f32,
+    pub hit_entities: Vec<Entity>,
+}
+
+impl MeleeSwing {
+    pub fn new(owner: Entity, offset: Vec2, is_facing_left: bool, params: MeleeSwingParams) -> Self {
+        MeleeSwing {
+            owner,
+            offset,
+            is_facing_left,
+            range: params.range,
+            arc: params.arc,
+            duration: params.duration,
+            knockback: params.knockback,
+            is_lethal: params.is_lethal,
+            passive_effects: params.passive_effects,
+            timer: 0.0,
+            hit_entities: Vec::new(),
+        }
+    }
+
+    /// Return the progress of the swing, as a number between 0 and 1
+    pub fn progress(&self) -> f32 {
+        if self.duration > 0.0 {
+            (self.timer / self.duration).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.timer >= self.duration
+    }
+
+    /// Returns the angle of the leading edge of the swing, in radians, relative to the direction
+    /// the owner is facing. Negative values are above the horizontal.
+    pub fn current_angle(&self) -> f32 {
+        -self.arc / 2.0 + self.arc * self.progress()
+    }
+
+    /// Returns `true` if the specified rect is within the part of the arc that has been swept so far
+    pub fn overlaps(&self, origin: Vec2, rect: &Rect) -> bool {
+        let closest = vec2(
+            origin.x.clamp(rect.x, rect.x + rect.w),
+            origin.y.clamp(rect.y, rect.y + rect.h),
+        );
+
+        if closest.distance(origin) > self.range {
+            return false;
+        }
+
+        if rect.contains(origin) {
+            return true;
+        }
+
+        let mut direction = rect.point() + rect.size() / 2.0 - origin;
+        if self.is_facing_left {
+            direction.x = -direction.x;
+        }
+
+        let angle = direction.y.atan2(direction.x);
+
+        angle >= -self.arc / 2.0 && angle <= self.current_angle()
+    }
+
+    /// Returns the entities, among the specified targets, that are hit by the swing, excluding the
+    /// owner and anything that has already been hit by this swing.
+    pub fn collect_hits(&mut self, origin: Vec2, targets: &[(Entity, Rect)]) -> Vec<Entity> {
+        let mut res = Vec::new();
+
+        for (entity, rect) in targets {
+            if *entity != self.owner
+                && !self.hit_entities.contains(entity)
+                && self.overlaps(origin, rect)
+            {
+                self.hit_entities.push(*entity);
+                res.push(*entity);
+            }
+        }
+
+        res
+    }
+}
+
+pub fn spawn_melee_swing(
+    world: &mut World,
+    owner: Entity,
+    origin: Vec2,
+    is_facing_left: bool,
+    params: MeleeSwingParams,
+) -> Entity {
+    let owner_position = world
+        .get::<Transform>(owner)
+        .map(|transform| transform.position)
+        .unwrap_or(origin);
+
+    let offset = origin - owner_position;
+
+    world.spawn((
+        Transform::from(origin),
+        MeleeSwing::new(owner, offset, is_facing_left, params),
+    ))
+}
+
+pub fn fixed_update_melee_swings(world: &mut World) {
+    let dt = get_fixed_delta();
+
+    let targets = world
+        .query::<(&Player, &Transform, &PhysicsBody)>()
+        .iter()
+        .filter_map(|(e, (player, transform, body))| {
+            if player.state == PlayerState::Dead {
+                None
+            } else {
+                Some((e, body.as_rect(transform.position)))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut hits = Vec::new();
+    let mut to_remove = Vec::new();
+
+    for (e, (swing, transform)) in world.query::<(&mut MeleeSwing, &mut Transform)>().iter() {
+        if let Ok(owner_transform) = world.get::<Transform>(swing.owner) {
+            transform.position = owner_transform.position + swing.offset;
+        }
+
+        swing.timer += dt;
+
+        for target in swing.collect_hits(transform.position, &targets) {
+            hits.push((
+                swing.owner,
+                target,
+                transform.position,
+                swing.knockback,
+                swing.is_lethal,
+                swing.passive_effects.clone(),
+            ));
+        }
+
+        if swing.is_finished() {
+            to_remove.push(e);
+        }
+    }
+
+    for (owner, target, origin, knockback, is_lethal, passive_effects) in hits {
+        if knockback != 0.0 {
+            if let Ok((transform, mut body)) =
+                world.query_one_mut::<(&Transform, &mut PhysicsBody)>(target)
+            {
+                let rect = body.as_rect(transform.position);
+                let direction = (rect.point() + rect.size() / 2.0 - origin).normalize_or_zero();
+
+                body.velocity += direction * knockback;
+            }
+        }
+
+        if let Ok(mut player) = world.get_mut::<Player>(target) {
+            for meta in passive_effects.into_iter() {
+                let effect_instance = PassiveEffectInstance::new(None, meta);
+                player.passive_effects.push(effect_instance);
+            }
+        }
+
+        if is_lethal {
+            on_player_damage(world, owner, target);
+        }
+    }
+
+    for e in to_remove {
+        if let Err(err) = world.despawn(e) {
+            #[cfg(debug_assertions)]
+            println!("WARNING: {}", err);
+        }
+    }
+}
+
+pub fn debug_draw_melee_swings(world: &mut World) {
+    for (_, (swing, transform)) in world.query::<(&MeleeSwing, &Transform)>().iter() {
+        let origin = transform.position;
+
+        for angle in [-swing.arc / 2.0, swing.current_angle()] {
+            let mut direction = vec2(angle.cos(), angle.sin());
+            if swing.is_facing_left {
+                direction.x = -direction.x;
+            }
+
+            let end = origin + direction * swing.range;
+
+            draw_line(origin.x, origin.y, end.x, end.y, 2.0, color::RED);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swing(owner: Entity) -> MeleeSwing {
+        MeleeSwing::new(
+            owner,
+            Vec2::ZERO,
+            false,
+            MeleeSwingParams {
+                range: 32.0,
+                arc: std::f32::consts::PI,
+                duration: 0.2,
+                knockback: 0.0,
+                is_lethal: true,
+                passive_effects: Vec::new(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_target_in_arc_is_hit_once_per_swing() {
+        let mut world = World::new();
+        let owner = world.spawn(());
+        let target = world.spawn(());
+
+        let mut swing = swing(owner);
+
+        let targets = vec![(target, Rect::new(8.0, -4.0, 8.0, 8.0))];
+
+        let mut hits = Vec::new();
+        while !swing.is_finished() {
+            swing.timer += 1.0 / 60.0;
+            hits.append(&mut swing.collect_hits(Vec2::ZERO, &targets));
+        }
+
+        assert_eq!(hits, vec![target]);
+    }
+
+    #[test]
+    fn test_targets_outside_arc_are_not_hit() {
+        let mut world = World::new();
+        let owner = world.spawn(());
+        let behind = world.spawn(());
+        let out_of_range = world.spawn(());
+
+        let mut swing = swing(owner);
+        swing.timer = swing.duration;
+
+        let targets = vec![
+            (owner, Rect::new(-4.0, -4.0, 8.0, 8.0)),
+            (behind, Rect::new(-24.0, -4.0, 8.0, 8.0)),
+            (out_of_range, Rect::new(64.0, -4.0, 8.0, 8.0)),
+        ];
+
+        assert!(swing.collect_hits(Vec2::ZERO, &targets).is_empty());
+    }
+
+    #[test]
+    fn test_swing_sweeps_from_top() {
+        let mut world = World::new();
+        let owner = world.spawn(());
+        let below = world.spawn(());
+
+        let mut swing = swing(owner);
+
+        let targets = vec![(below, Rect::new(8.0, 16.0, 8.0, 8.0))];
+
+        assert!(swing.collect_hits(Vec2::ZERO, &targets).is_empty());
+
+        swing.timer = swing.duration;
+
+        assert_eq!(swing.collect_hits(Vec2::ZERO, &targets), vec![below]);
+    }
+}
@@ -9,21 +9,25 @@ use serde::{Deserialize, Serialize};
 use core::math::{deg_to_rad, rotate_vector, IsZero};
 use core::Result;
 
-use crate::game::play_sound_effect;
-use crate::items::spawn_item;
+use crate::game::{play_sound_effect, trigger_screen_flash, ScreenFlashMetadata};
+use crate::items::{spawn_item, ItemDespawnTimer};
 use crate::Resources;
 use crate::{PassiveEffectInstance, PassiveEffectMetadata};
 
+pub mod melee;
 pub mod projectiles;
 pub mod triggered;
 
 pub use triggered::{TriggeredEffectMetadata, TriggeredEffectTrigger};
 
+use crate::effects::active::melee::{spawn_melee_swing, MeleeSwingParams};
 use crate::effects::active::projectiles::{spawn_projectile, ProjectileParams};
-use crate::effects::active::triggered::{spawn_triggered_effect, TriggeredEffect};
+use crate::effects::active::triggered::{
+    get_cluster_children, spawn_triggered_effect, TriggeredEffect,
+};
 use crate::particles::ParticleEmitterMetadata;
-use crate::player::{on_player_damage, Player};
-use crate::PhysicsBody;
+use crate::player::{on_player_damage, Player, PlayerController};
+use crate::{PhysicsBody, TrailMetadata};
 use core::Transform;
 pub use projectiles::ProjectileKind;
 
@@ -56,6 +60,10 @@ pub fn spawn_active_effect(
         play_sound_effect(id, 1.0);
     }
 
+    if let Some(meta) = &params.screen_flash {
+        trigger_screen_flash(meta);
+    }
+
     let mut damage = Vec::new();
 
     match *params.kind {
@@ -145,17 +153,67 @@ pub fn spawn_active_effect(
                 }
             }
         }
+        ActiveEffectKind::ArcCollider {
+            range,
+            arc,
+            duration,
+            knockback,
+            is_lethal,
+            passive_effects,
+        } => {
+            spawn_melee_swing(
+                world,
+                owner,
+                origin,
+                is_facing_left,
+                MeleeSwingParams {
+                    range,
+                    arc: deg_to_rad(arc),
+                    duration,
+                    knockback,
+                    is_lethal,
+                    passive_effects,
+                },
+            );
+        }
         ActiveEffectKind::TriggeredEffect { meta } => {
             spawn_triggered_effect(world, owner, origin, is_facing_left, *meta)?;
         }
+        ActiveEffectKind::Cluster {
+            count,
+            spread,
+            distance,
+            child,
+        } => {
+            let children =
+                get_cluster_children(count, deg_to_rad(spread), distance, child.velocity);
+
+            for (mut offset, velocity) in children {
+                if is_facing_left {
+                    offset.x = -offset.x;
+                }
+
+                let meta = TriggeredEffectMetadata {
+                    velocity,
+                    ..(*child).clone()
+                };
+
+                spawn_triggered_effect(world, owner, origin + offset, is_facing_left, meta)?;
+            }
+        }
         ActiveEffectKind::Projectile {
             kind,
             speed,
             range,
             spread,
+            is_aimed,
             is_lethal,
             passive_effects,
             particles,
+            trail,
+            ricochet,
+            ricochet_speed_factor,
+            is_ricochet_lethal,
         } => {
             let mut velocity = Vec2::ZERO;
             if is_facing_left {
@@ -164,6 +222,12 @@ pub fn spawn_active_effect(
                 velocity.x = speed
             }
 
+            if is_aimed {
+                if let Some(direction) = get_aimed_direction(world, owner, origin, is_facing_left) {
+                    velocity = direction * speed;
+                }
+            }
+
             if spread != 0.0 {
                 let rad = deg_to_rad(spread);
                 let spread = rand::gen_range(-rad, rad);
@@ -182,6 +246,10 @@ pub fn spawn_active_effect(
                     is_lethal,
                     passive_effects,
                     particle_effects: particles,
+                    trail,
+                    ricochet,
+                    ricochet_speed_factor,
+                    is_ricochet_lethal,
                 },
             );
         }
@@ -195,6 +263,10 @@ pub fn spawn_active_effect(
 
             match spawn_item(world, origin + offset, item_meta.clone()) {
                 Ok(entity) => {
+                    world
+                        .insert_one(entity, ItemDespawnTimer::default())
+                        .unwrap();
+
                     if inherit_spawner_velocity {
                         let spawner_velocity = {
                             let mut spawner_body_query =
@@ -239,6 +311,10 @@ pub struct ActiveEffectMetadata {
         skip_serializing_if = "Option::is_none"
     )]
     pub sound_effect_id: Option<String>,
+    /// This specifies a flash of the entire screen, like a white flash for a flashbang, that is
+    /// triggered when the effect is instantiated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen_flash: Option<ScreenFlashMetadata>,
     /// The delay between instantiation of the effect is requested and the actual instantiation.
     /// This will delay the entire effect, including sound effects, particle effects and anything
     /// drawn as a result of the effect, so in most cases it is probably better to use a
@@ -285,11 +361,48 @@ pub enum ActiveEffectKind {
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         passive_effects: Vec<PassiveEffectMetadata>,
     },
+    /// Sweep an arc shaped hitbox in front of the player, from the top of the arc to the bottom,
+    /// over the specified duration. Any player it overlaps will only be hit once per swing.
+    /// This would typically be used for melee weapons, like a sword.
+    ArcCollider {
+        range: f32,
+        /// The arc of the swing, in degrees
+        arc: f32,
+        /// The duration of the swing, in seconds. This should match the attack animation.
+        #[serde(default, skip_serializing_if = "f32::is_zero")]
+        duration: f32,
+        /// The force applied to the velocity of a player that is hit, away from the origin
+        #[serde(default, skip_serializing_if = "f32::is_zero")]
+        knockback: f32,
+        /// If `true` the effect will do damage to any player it hits
+        #[serde(
+            default = "core::json::default_true",
+            skip_serializing_if = "core::json::is_true"
+        )]
+        is_lethal: bool,
+        /// This contains any passive effects that will be spawned on collision
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        passive_effects: Vec<PassiveEffectMetadata>,
+    },
     /// Spawn a trigger that will set of another effect if its trigger conditions are met.
     TriggeredEffect {
         #[serde(flatten)]
         meta: Box<TriggeredEffectMetadata>,
     },
+    /// Spawn a number of triggered effects, spread out evenly over an arc, like a cluster bomb.
+    /// The children are spread around the direction of the velocity of `child`, or straight up,
+    /// if it has no velocity.
+    Cluster {
+        /// The number of triggered effects to spawn
+        count: u32,
+        /// The arc that the children are spread over, in degrees
+        spread: f32,
+        /// The distance from the origin that the children are spawned at
+        #[serde(default, skip_serializing_if = "f32::is_zero")]
+        distance: f32,
+        /// The triggered effect that is spawned for each child
+        child: Box<TriggeredEffectMetadata>,
+    },
     /// Spawn a projectile.
     /// This would typically be used for things like a gun.
     Projectile {
@@ -299,6 +412,10 @@ pub enum ActiveEffectKind {
         range: f32,
         #[serde(default, skip_serializing_if = "f32::is_zero")]
         spread: f32,
+        /// If `true` the projectile will be fired towards the aim of the player, if it has one,
+        /// in stead of straight ahead
+        #[serde(default, skip_serializing_if = "core::json::is_false")]
+        is_aimed: bool,
         /// If `true` the effect will do damage to any player it hits
         #[serde(
             default = "core::json::default_true",
@@ -311,6 +428,22 @@ pub enum ActiveEffectKind {
         /// Particle effects that will be attached to the projectile
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         particles: Vec<ParticleEmitterMetadata>,
+        /// If specified, a trail will be drawn behind the projectile
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        trail: Option<TrailMetadata>,
+        /// If specified, the projectile will bounce off solid tiles this many times, in stead of
+        /// being despawned when it hits them
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ricochet: Option<u32>,
+        /// The factor that the speed of the projectile will be multiplied by, on each bounce
+        #[serde(default = "default_ricochet_speed_factor")]
+        ricochet_speed_factor: f32,
+        /// If `false` the projectile will no longer do damage after it has bounced
+        #[serde(
+            default = "core::json::default_true",
+            skip_serializing_if = "core::json::is_true"
+        )]
+        is_ricochet_lethal: bool,
     },
     SpawnItem {
         item: String,
@@ -321,6 +454,25 @@ pub enum ActiveEffectKind {
     },
 }
 
+fn default_ricochet_speed_factor() -> f32 {
+    1.0
+}
+
+/// Returns the normalized direction from `origin` towards the aim of the player `owner`, if it has
+/// one. The aim is ignored if it points behind the player, so that weapons are not fired backwards.
+fn get_aimed_direction(
+    world: &World,
+    owner: Entity,
+    origin: Vec2,
+    is_facing_left: bool,
+) -> Option<Vec2> {
+    let controller = world.get::<PlayerController>(owner).ok()?;
+
+    controller
+        .get_aim_direction(origin)
+        .filter(|direction| direction.x == 0.0 || (direction.x < 0.0) == is_facing_left)
+}
+
 pub fn debug_draw_active_effects(world: &mut World) {
     let mut to_remove = Vec::new();
 
@@ -372,3 +524,48 @@ pub fn debug_draw_active_effects(world: &mut World) {
         world.despawn(e).unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::input::GameInputScheme;
+
+    use crate::player::PlayerControllerKind;
+
+    #[test]
+    fn test_aimed_direction() {
+        let mut world = World::new();
+
+        let mut controller = PlayerController::from(PlayerControllerKind::LocalInput(
+            GameInputScheme::KeyboardLeft,
+        ));
+
+        controller.aim_position = Some(vec2(100.0, 100.0));
+
+        let owner = world.spawn((controller,));
+
+        let direction = get_aimed_direction(&world, owner, vec2(0.0, 100.0), false);
+        assert_eq!(direction, Some(vec2(1.0, 0.0)));
+
+        // The aim is behind the player
+        let direction = get_aimed_direction(&world, owner, vec2(0.0, 100.0), true);
+        assert_eq!(direction, None);
+
+        // Gamepad players aim with a direction, which may have been adjusted by aim assist
+        {
+            let mut controller = world.get_mut::<PlayerController>(owner).unwrap();
+            controller.aim_position = None;
+            controller.aim_direction = Some(vec2(-0.6, -0.8));
+        }
+
+        let direction = get_aimed_direction(&world, owner, Vec2::ZERO, true);
+        assert_eq!(direction, Some(vec2(-0.6, -0.8)));
+
+        let owner = world.spawn((PlayerController::from(PlayerControllerKind::LocalInput(
+            GameInputScheme::KeyboardRight,
+        )),));
+
+        assert_eq!(get_aimed_direction(&world, owner, Vec2::ZERO, false), None);
+    }
+}
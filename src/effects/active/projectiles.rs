@@ -9,8 +9,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::effects::active::triggered::TriggeredEffect;
 use crate::effects::TriggeredEffectTrigger;
+use crate::game::get_tick_scale;
 use crate::particles::{ParticleEmitter, ParticleEmitterMetadata};
 use crate::player::{on_player_damage, Player, PlayerState};
+use crate::{spawn_trail, TrailMetadata};
 use crate::{CollisionWorld, PhysicsBody, Resources, RigidBody, RigidBodyParams, SpriteMetadata};
 use crate::{Drawable, PassiveEffectInstance, PassiveEffectMetadata, SpriteParams};
 use core::Transform;
@@ -49,6 +51,12 @@ pub struct Projectile {
     pub range: f32,
     pub is_lethal: bool,
     pub passive_effects: Vec<PassiveEffectMetadata>,
+    /// The number of bounces off solid tiles that remain before the projectile is despawned
+    pub ricochet: u32,
+    /// The factor that the speed of the projectile is multiplied by, on each bounce
+    pub ricochet_speed_factor: f32,
+    /// If `false` the projectile will no longer be lethal after it has bounced
+    pub is_ricochet_lethal: bool,
 }
 
 impl Projectile {
@@ -67,6 +75,9 @@ impl Projectile {
             range,
             is_lethal,
             passive_effects: passive_effects.to_vec(),
+            ricochet: 0,
+            ricochet_speed_factor: 1.0,
+            is_ricochet_lethal: true,
         }
     }
 }
@@ -76,6 +87,12 @@ pub struct ProjectileParams {
     pub is_lethal: bool,
     pub passive_effects: Vec<PassiveEffectMetadata>,
     pub particle_effects: Vec<ParticleEmitterMetadata>,
+    pub trail: Option<TrailMetadata>,
+    /// If this is `Some` and more than zero, the projectile will bounce off solid tiles this many
+    /// times, before it is despawned
+    pub ricochet: Option<u32>,
+    pub ricochet_speed_factor: f32,
+    pub is_ricochet_lethal: bool,
 }
 
 impl Default for ProjectileParams {
@@ -84,6 +101,10 @@ impl Default for ProjectileParams {
             is_lethal: true,
             passive_effects: Vec::new(),
             particle_effects: Vec::new(),
+            trail: None,
+            ricochet: None,
+            ricochet_speed_factor: 1.0,
+            is_ricochet_lethal: true,
         }
     }
 }
@@ -98,19 +119,21 @@ pub fn spawn_projectile(
     params: ProjectileParams,
 ) -> Entity {
     let entity = world.spawn(());
-    world
-        .insert_one(
-            entity,
-            Projectile::new(
-                owner,
-                kind.clone(),
-                origin,
-                range,
-                params.is_lethal,
-                &params.passive_effects,
-            ),
-        )
-        .unwrap();
+
+    let mut projectile = Projectile::new(
+        owner,
+        kind.clone(),
+        origin,
+        range,
+        params.is_lethal,
+        &params.passive_effects,
+    );
+
+    projectile.ricochet = params.ricochet.unwrap_or(0);
+    projectile.ricochet_speed_factor = params.ricochet_speed_factor;
+    projectile.is_ricochet_lethal = params.is_ricochet_lethal;
+
+    world.insert_one(entity, projectile).unwrap();
 
     let mut transform = Transform::from(origin);
 
@@ -141,19 +164,7 @@ pub fn spawn_projectile(
             let is_flipped_x = velocity.x < 0.0;
 
             if can_rotate {
-                let mut direction = Vec2::ZERO;
-
-                if is_flipped_x {
-                    direction.x = 1.0;
-                } else {
-                    direction.x = -1.0;
-                }
-
-                transform.rotation = (velocity.y - direction.y).atan2(velocity.x - direction.x);
-
-                if is_flipped_x {
-                    transform.rotation += PI;
-                }
+                transform.rotation = get_sprite_rotation(velocity);
             }
 
             world
@@ -195,9 +206,80 @@ pub fn spawn_projectile(
         world.insert_one(entity, particle_emitters).unwrap();
     }
 
+    if let Some(meta) = params.trail {
+        spawn_trail(world, entity, meta.into());
+    }
+
     entity
 }
 
+// Returns the rotation of a rotating projectile sprite, moving at the specified velocity
+fn get_sprite_rotation(velocity: Vec2) -> f32 {
+    let is_flipped_x = velocity.x < 0.0;
+
+    let mut direction = Vec2::ZERO;
+
+    if is_flipped_x {
+        direction.x = 1.0;
+    } else {
+        direction.x = -1.0;
+    }
+
+    let mut rotation = (velocity.y - direction.y).atan2(velocity.x - direction.x);
+
+    if is_flipped_x {
+        rotation += PI;
+    }
+
+    rotation
+}
+
+/// Returns the normal of the surface of the solid tiles that a body, moving at the specified
+/// velocity, has collided with at `position`. This is found by undoing the movement of the last
+/// tick on one axis at a time, to find which of them caused the collision. If neither does, on
+/// their own, the body hit a corner, and the normal will point straight back at it.
+pub fn get_surface_normal(
+    collision_world: &CollisionWorld,
+    position: Vec2,
+    velocity: Vec2,
+    size: IVec2,
+) -> Vec2 {
+    let previous_position = position - velocity * get_tick_scale();
+
+    let is_solid =
+        |position: Vec2| collision_world.collide_solids(position, size.x, size.y) == Tile::Solid;
+
+    let is_x_blocked = is_solid(vec2(position.x, previous_position.y));
+    let is_y_blocked = is_solid(vec2(previous_position.x, position.y));
+
+    get_normal_from_blocked_axes(velocity, is_x_blocked, is_y_blocked)
+}
+
+fn get_normal_from_blocked_axes(velocity: Vec2, is_x_blocked: bool, is_y_blocked: bool) -> Vec2 {
+    // A corner blocks both axes, so the normal points straight back along the velocity, which
+    // reflects it back the way it came
+    if is_x_blocked == is_y_blocked {
+        return -velocity.normalize_or_zero();
+    }
+
+    let mut normal = Vec2::ZERO;
+
+    if is_x_blocked && velocity.x != 0.0 {
+        normal.x = -velocity.x.signum();
+    }
+
+    if is_y_blocked && velocity.y != 0.0 {
+        normal.y = -velocity.y.signum();
+    }
+
+    normal
+}
+
+/// Returns `velocity` reflected off a surface with the specified normal
+pub fn reflect_velocity(velocity: Vec2, normal: Vec2) -> Vec2 {
+    velocity - 2.0 * velocity.dot(normal) * normal
+}
+
 enum ProjectileCollision {
     Player(Entity),
     Trigger(Entity),
@@ -214,6 +296,7 @@ pub fn fixed_update_projectiles(world: &mut World) {
     let collision_world = storage::get::<CollisionWorld>();
 
     let mut events = Vec::new();
+    let mut ricochets = Vec::new();
 
     'projectiles: for (e, (projectile, transform, body)) in world
         .query::<(&Projectile, &Transform, &RigidBody)>()
@@ -227,8 +310,16 @@ pub fn fixed_update_projectiles(world: &mut World) {
         let size = body.size.as_i32();
         let map_collision = collision_world.collide_solids(transform.position, size.x, size.y);
         if map_collision == Tile::Solid {
-            let res = (projectile.owner, e, Some(ProjectileCollision::Map));
-            events.push(res);
+            if projectile.ricochet > 0 {
+                let normal =
+                    get_surface_normal(&collision_world, transform.position, body.velocity, size);
+
+                ricochets.push((e, normal));
+            } else {
+                let res = (projectile.owner, e, Some(ProjectileCollision::Map));
+                events.push(res);
+            }
+
             continue 'projectiles;
         }
 
@@ -269,6 +360,38 @@ pub fn fixed_update_projectiles(world: &mut World) {
         }
     }
 
+    for (entity, normal) in ricochets {
+        let (projectile, transform, body, drawable) = world
+            .query_one_mut::<(
+                &mut Projectile,
+                &mut Transform,
+                &mut RigidBody,
+                Option<&mut Drawable>,
+            )>(entity)
+            .unwrap();
+
+        // Move the projectile back out of the wall, before it is sent off in the new direction
+        transform.position -= body.velocity * get_tick_scale();
+
+        body.velocity = reflect_velocity(body.velocity, normal) * projectile.ricochet_speed_factor;
+
+        projectile.ricochet -= 1;
+
+        if !projectile.is_ricochet_lethal {
+            projectile.is_lethal = false;
+        }
+
+        if let ProjectileKind::Sprite { can_rotate, .. } = projectile.kind {
+            if can_rotate {
+                transform.rotation = get_sprite_rotation(body.velocity);
+            }
+
+            if let Some(sprite) = drawable.and_then(|drawable| drawable.get_sprite_mut()) {
+                sprite.is_flipped_x = body.velocity.x < 0.0;
+            }
+        }
+    }
+
     for (damage_from_entity, projectile_entity, collision) in events {
         if let Some(collision_kind) = collision {
             match collision_kind {
@@ -314,3 +437,42 @@ pub fn draw_projectiles(world: &mut World) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec2_eq(a: Vec2, b: Vec2) {
+        assert!(a.distance(b) < 0.0001, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_ricochet_reflection() {
+        let velocity = vec2(3.0, 4.0);
+
+        // Hitting a floor, which is a horizontal surface
+        let normal = get_normal_from_blocked_axes(velocity, false, true);
+        assert_eq!(normal, vec2(0.0, -1.0));
+        assert_vec2_eq(reflect_velocity(velocity, normal), vec2(3.0, -4.0));
+
+        // Hitting a wall, which is a vertical surface
+        let normal = get_normal_from_blocked_axes(velocity, true, false);
+        assert_eq!(normal, vec2(-1.0, 0.0));
+        assert_vec2_eq(reflect_velocity(velocity, normal), vec2(-3.0, 4.0));
+
+        // The angle of incidence equals the angle of reflection
+        let reflected = reflect_velocity(velocity, normal);
+        assert!(
+            (velocity.angle_between(normal) - (-reflected).angle_between(normal)).abs() < 0.0001
+        );
+        assert_eq!(reflected.length(), velocity.length());
+
+        // Hitting a corner sends it straight back
+        let normal = get_normal_from_blocked_axes(velocity, true, true);
+        assert_vec2_eq(reflect_velocity(velocity, normal), vec2(-3.0, -4.0));
+
+        // If neither axis is blocked on its own, the body hit the corner of a tile
+        let normal = get_normal_from_blocked_axes(velocity, false, false);
+        assert_vec2_eq(reflect_velocity(velocity, normal), vec2(-3.0, -4.0));
+    }
+}
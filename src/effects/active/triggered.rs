@@ -1,23 +1,36 @@
+use std::collections::HashMap;
+
+use macroquad::color;
 use macroquad::experimental::collections::storage;
 use macroquad::prelude::*;
 
 use hecs::{Entity, World};
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
+use core::error::ErrorKind;
 use core::math::{deg_to_rad, rotate_vector};
-use core::{Result, Transform};
+use core::{formaterr, Result, Transform};
 
 use crate::effects::active::spawn_active_effect;
-use crate::items::spawn_item;
+use crate::game::{dispatch_game_event, get_fixed_delta, GameEvent};
+use crate::items::{spawn_item, ItemDespawnTimer};
 use crate::particles::{ParticleEmitter, ParticleEmitterMetadata};
-use crate::player::{Player, PlayerController, PlayerInventory, PlayerState};
+use crate::player::{is_same_team, Player, PlayerController, PlayerInventory, PlayerState};
 use crate::{physics, Resources};
 use crate::{ActiveEffectMetadata, AnimatedSpriteMetadata, CollisionWorld, PhysicsBody};
-use crate::{Drawable, DrawableKind, PhysicsBodyParams};
+use crate::{Drawable, DrawableKind, PhysicsBodyParams, FOREGROUND_Z_INDEX};
 
 const TRIGGERED_EFFECT_DRAW_ORDER: u32 = 5;
 
+/// Targets within this many times the proximity radius of an armed effect will cause it to blink
+const PROXIMITY_WARNING_DISTANCE_FACTOR: f32 = 2.5;
+/// The blink frequency of a proximity effect, when a target is at the edge of the warning distance
+const PROXIMITY_BLINK_MIN_FREQUENCY: f32 = 2.0;
+/// The blink frequency of a proximity effect, when a target is at the edge of the proximity radius
+const PROXIMITY_BLINK_MAX_FREQUENCY: f32 = 12.0;
+const PROXIMITY_WARNING_TINT: Color = Color::new(1.0, 0.35, 0.35, 1.0);
+
 /// The various collision types that can trigger a `TriggeredEffect`.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -25,8 +38,11 @@ const TRIGGERED_EFFECT_DRAW_ORDER: u32 = 5;
 pub enum TriggeredEffectTrigger {
     /// The player that deployed the effect
     Player,
-    /// Enemy players
+    /// Enemy players. If the player that deployed the effect is on a team, this will not include
+    /// players on the same team.
     Enemy,
+    /// Players on the same team as the player that deployed the effect
+    Teammate,
     /// Ground tiles (all tiles with collision, except platforms)
     Ground,
     /// Explosion effects
@@ -35,6 +51,142 @@ pub enum TriggeredEffectTrigger {
     Projectile,
 }
 
+/// A trigger, or the name of a trigger group, as it is specified in JSON. Trigger groups are
+/// named sets of triggers, defined in the `trigger_groups` resource file, that can be used in
+/// stead of listing the same triggers on every effect.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TriggeredEffectTriggerParams {
+    Trigger(TriggeredEffectTrigger),
+    Named(String),
+}
+
+/// This holds the trigger groups that have been loaded, resolved to lists of triggers. It is
+/// stored before any items are loaded, so that trigger groups can be expanded as the metadata of
+/// triggered effects is deserialized.
+#[derive(Debug, Default)]
+pub struct TriggerGroups(HashMap<String, Vec<TriggeredEffectTrigger>>);
+
+impl TriggerGroups {
+    pub fn get(&self, name: &str) -> Option<&Vec<TriggeredEffectTrigger>> {
+        self.0.get(name)
+    }
+
+    /// Add the specified trigger groups. Groups can include other groups, both from the same
+    /// file and ones that have already been added, as long as they do not include themselves.
+    /// Groups with the same name as an existing group will replace it.
+    pub fn extend(
+        &mut self,
+        groups: HashMap<String, Vec<TriggeredEffectTriggerParams>>,
+    ) -> Result<()> {
+        let mut resolved = HashMap::new();
+
+        for name in groups.keys() {
+            let triggers = self.resolve(name, &groups, &mut Vec::new())?;
+            resolved.insert(name.clone(), triggers);
+        }
+
+        self.0.extend(resolved);
+
+        Ok(())
+    }
+
+    /// Expand a list of triggers and trigger groups to a list of triggers
+    pub fn expand(
+        &self,
+        params: &[TriggeredEffectTriggerParams],
+    ) -> Result<Vec<TriggeredEffectTrigger>> {
+        self.expand_with(params, &HashMap::new(), &mut Vec::new())
+    }
+
+    // Expand `params`, looking up groups in `groups` before the groups that have already been
+    // added. `stack` holds the names of the groups that are being expanded, to detect cycles.
+    fn expand_with(
+        &self,
+        params: &[TriggeredEffectTriggerParams],
+        groups: &HashMap<String, Vec<TriggeredEffectTriggerParams>>,
+        stack: &mut Vec<String>,
+    ) -> Result<Vec<TriggeredEffectTrigger>> {
+        let mut triggers = Vec::new();
+
+        for param in params {
+            let expanded = match param {
+                TriggeredEffectTriggerParams::Trigger(trigger) => vec![*trigger],
+                TriggeredEffectTriggerParams::Named(name) => self.resolve(name, groups, stack)?,
+            };
+
+            for trigger in expanded {
+                if !triggers.contains(&trigger) {
+                    triggers.push(trigger);
+                }
+            }
+        }
+
+        Ok(triggers)
+    }
+
+    fn resolve(
+        &self,
+        name: &str,
+        groups: &HashMap<String, Vec<TriggeredEffectTriggerParams>>,
+        stack: &mut Vec<String>,
+    ) -> Result<Vec<TriggeredEffectTrigger>> {
+        if stack.iter().any(|other| other == name) {
+            return Err(formaterr!(
+                ErrorKind::Parsing,
+                "The trigger group '{}' includes itself",
+                name
+            ));
+        }
+
+        let params = match groups.get(name) {
+            Some(params) => params,
+            None => {
+                return self.get(name).cloned().ok_or_else(|| {
+                    formaterr!(ErrorKind::Parsing, "Unknown trigger group '{}'", name)
+                })
+            }
+        };
+
+        stack.push(name.to_string());
+        let triggers = self.expand_with(params, groups, stack)?;
+        stack.pop();
+
+        Ok(triggers)
+    }
+}
+
+/// Add the specified trigger groups to the ones that have already been loaded
+pub fn load_trigger_groups(
+    groups: HashMap<String, Vec<TriggeredEffectTriggerParams>>,
+) -> Result<()> {
+    if storage::try_get::<TriggerGroups>().is_none() {
+        storage::store(TriggerGroups::default());
+    }
+
+    storage::get_mut::<TriggerGroups>().extend(groups)
+}
+
+// Deserialize a list of triggers, expanding any trigger groups, so that unknown groups are
+// reported when the metadata is loaded
+fn deserialize_triggers<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Vec<TriggeredEffectTrigger>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let params = Vec::<TriggeredEffectTriggerParams>::deserialize(deserializer)?;
+
+    let groups = storage::try_get::<TriggerGroups>();
+    let empty = TriggerGroups::default();
+
+    groups
+        .as_deref()
+        .unwrap_or(&empty)
+        .expand(&params)
+        .map_err(|err| serde::de::Error::custom(err.to_string()))
+}
+
 pub struct TriggeredEffect {
     pub owner: Entity,
     pub trigger: Vec<TriggeredEffectTrigger>,
@@ -42,9 +194,13 @@ pub struct TriggeredEffect {
     pub activation_delay: f32,
     pub expire_time: Option<f32>,
     pub expire_effects: Vec<ActiveEffectMetadata>,
+    /// If this is `true` the regular effects will also be activated when the effect expires
+    pub should_fire_on_expire: bool,
     pub trigger_delay: f32,
     pub timed_trigger: Option<f32>,
     pub is_kickable: bool,
+    pub kick_force: f32,
+    pub kick_delay: f32,
     /// This can be used to trigger the effect immediately, ignoring delay timers.
     /// Also requires `is_triggered` to be set to `true`, for this to work.
     pub should_override_delay: bool,
@@ -53,10 +209,24 @@ pub struct TriggeredEffect {
     /// This holds a handle to the player that triggered the effect, if applicable.
     pub triggered_by: Option<Entity>,
     pub grab_options: Option<TriggeredEffectGrabOptions>,
+    pub proximity_radius: Option<f32>,
+    /// This is `0.0` when no target is within the warning distance of a proximity effect and
+    /// approaches `1.0` as a target approaches the proximity radius.
+    pub proximity_warning: f32,
+    pub proximity_blink_timer: f32,
     pub kick_delay_timer: f32,
     pub activation_timer: f32,
     pub trigger_delay_timer: f32,
     pub timed_trigger_timer: f32,
+    /// If specified, the effect will stop bouncing, and come to rest, after it has bounced this
+    /// many times
+    pub max_bounces: Option<u32>,
+    /// If this is `true` the effect will stick to the first player that triggers it
+    pub is_sticky: bool,
+    /// This holds a handle to the player that a sticky effect is stuck to, if any
+    pub stuck_to: Option<Entity>,
+    /// The offset from the position of the player that the effect is stuck to
+    pub stuck_offset: Vec2,
 }
 
 impl TriggeredEffect {
@@ -68,20 +238,110 @@ impl TriggeredEffect {
             activation_delay: meta.activation_delay,
             expire_time: meta.expire_time,
             expire_effects: meta.expire_effects,
+            should_fire_on_expire: meta.should_fire_on_expire,
             trigger_delay: meta.trigger_delay,
             timed_trigger: meta.timed_trigger,
             is_kickable: meta.is_kickable,
+            kick_force: meta.kick_force,
+            kick_delay: meta.kick_delay,
             should_override_delay: false,
             should_collide_with_platforms: meta.should_collide_with_platforms,
             is_triggered: false,
             triggered_by: None,
             grab_options: meta.grab_options,
+            proximity_radius: meta.proximity_radius.filter(|&radius| radius > 0.0),
+            proximity_warning: 0.0,
+            proximity_blink_timer: 0.0,
             kick_delay_timer: 0.0,
             activation_timer: 0.0,
             trigger_delay_timer: 0.0,
             timed_trigger_timer: 0.0,
+            max_bounces: meta.max_bounces,
+            is_sticky: meta.is_sticky,
+            stuck_to: None,
+            stuck_offset: Vec2::ZERO,
+        }
+    }
+
+    /// Stick the effect to the specified player, at `offset` from its position, and trigger it.
+    /// It will follow the player until it goes off, after the trigger delay, like any other
+    /// triggered effect.
+    pub fn stick_to(&mut self, player: Entity, offset: Vec2) {
+        self.stuck_to = Some(player);
+        self.stuck_offset = offset;
+
+        self.is_triggered = true;
+        self.triggered_by = Some(player);
+    }
+
+    /// Returns `true` if the effect should keep bouncing, after having bounced `bounce_cnt` times
+    pub fn has_bounces_left(&self, bounce_cnt: u32) -> bool {
+        self.max_bounces
+            .map(|max_bounces| bounce_cnt < max_bounces)
+            .unwrap_or(true)
+    }
+
+    /// Returns `true` if the effect has outlived its `expire_time`, if any, and should be removed
+    pub fn is_expired(&self) -> bool {
+        self.expire_time
+            .map(|expire_time| self.activation_timer >= expire_time)
+            .unwrap_or(false)
+    }
+
+    /// Returns the effects that should be activated when the effect expires
+    pub fn get_expire_effects(&self) -> Vec<ActiveEffectMetadata> {
+        let mut effects = self.expire_effects.clone();
+
+        if self.should_fire_on_expire {
+            effects.extend(self.effects.iter().cloned());
+        }
+
+        effects
+    }
+
+    /// Returns the trigger that a player, on the specified team, counts as, when colliding with the
+    /// effect. If the owner is not on a team, all other players count as enemies.
+    pub fn get_player_trigger(
+        &self,
+        player: Entity,
+        owner_team: Option<u8>,
+        team: Option<u8>,
+    ) -> TriggeredEffectTrigger {
+        if player == self.owner {
+            TriggeredEffectTrigger::Player
+        } else if is_same_team(owner_team, team) {
+            TriggeredEffectTrigger::Teammate
+        } else {
+            TriggeredEffectTrigger::Enemy
         }
     }
+
+    /// Returns `true` if the activation delay has passed, so that the effect can be triggered
+    pub fn is_armed(&self) -> bool {
+        self.activation_timer >= self.activation_delay
+    }
+
+    /// Returns the proximity of a target, with the collider `target`, to an effect with its center
+    /// at `center`. This is `1.0` if the target is within the proximity radius and the effect
+    /// should be triggered, and it falls off to `0.0` at the edge of the warning distance.
+    /// This will return `None` if the effect is not a proximity effect.
+    pub fn get_proximity(&self, center: Vec2, target: &Rect) -> Option<f32> {
+        let radius = self.proximity_radius?;
+
+        let closest = center
+            .max(target.point())
+            .min(target.point() + target.size());
+        let distance = center.distance(closest);
+
+        if distance <= radius {
+            return Some(1.0);
+        }
+
+        let warning_distance = radius * (PROXIMITY_WARNING_DISTANCE_FACTOR - 1.0);
+        let proximity = 1.0 - (distance - radius) / warning_distance;
+
+        Some(proximity.clamp(0.0, 1.0))
+    }
 }
 
 pub fn spawn_triggered_effect(
@@ -134,8 +394,10 @@ pub fn spawn_triggered_effect(
         let animated_sprite = meta.into();
 
         let mut drawable = Drawable {
+            z_index: FOREGROUND_Z_INDEX,
             draw_order: TRIGGERED_EFFECT_DRAW_ORDER,
             kind: DrawableKind::AnimatedSprite(animated_sprite),
+            offset: Vec2::ZERO,
         };
 
         {
@@ -163,11 +425,54 @@ pub fn spawn_triggered_effect(
     Ok(entity)
 }
 
+/// Returns the offset and velocity of each child of a cluster effect. The children are spread out
+/// evenly over an arc of `spread` radians, centered on the direction of `velocity`, or straight
+/// up, if `velocity` is zero. A full circle is divided into `count` equal parts.
+pub fn get_cluster_children(
+    count: u32,
+    spread: f32,
+    distance: f32,
+    velocity: Vec2,
+) -> Vec<(Vec2, Vec2)> {
+    let direction = if velocity == Vec2::ZERO {
+        vec2(0.0, -1.0)
+    } else {
+        velocity.normalize()
+    };
+
+    let step = if spread >= std::f32::consts::TAU {
+        std::f32::consts::TAU / count as f32
+    } else if count > 1 {
+        spread / (count - 1) as f32
+    } else {
+        0.0
+    };
+
+    let start = if count > 1 && spread < std::f32::consts::TAU {
+        -spread / 2.0
+    } else {
+        0.0
+    };
+
+    (0..count)
+        .map(|i| {
+            let angle = start + step * i as f32;
+
+            (
+                rotate_vector(direction, angle) * distance,
+                rotate_vector(velocity, angle),
+            )
+        })
+        .collect()
+}
+
+/// The default horizontal velocity of a kicked effect
 const KICK_FORCE: f32 = 15.0;
+/// The default delay, after an effect has been deployed, before it can be kicked
 const KICK_DELAY: f32 = 0.22;
 
 pub fn fixed_update_triggered_effects(world: &mut World) {
-    let dt = get_frame_time();
+    let dt = get_fixed_delta();
 
     let mut to_trigger = Vec::new();
 
@@ -178,13 +483,19 @@ pub fn fixed_update_triggered_effects(world: &mut World) {
             if player.state == PlayerState::Dead {
                 None
             } else {
-                Some((e, player.is_facing_left, transform.position, body.size))
+                Some((
+                    e,
+                    player.is_facing_left,
+                    transform.position,
+                    body.size,
+                    player.team,
+                ))
             }
         })
         .collect::<Vec<_>>();
 
     for (entity, (effect, transform, body)) in world
-        .query::<(&mut TriggeredEffect, &Transform, &mut PhysicsBody)>()
+        .query::<(&mut TriggeredEffect, &mut Transform, &mut PhysicsBody)>()
         .iter()
     {
         if !effect.should_collide_with_platforms {
@@ -206,7 +517,31 @@ pub fn fixed_update_triggered_effects(world: &mut World) {
             effect.trigger_delay_timer += dt;
         }
 
-        if !effect.is_triggered && effect.activation_timer >= effect.activation_delay {
+        effect.proximity_warning = 0.0;
+
+        if !effect.has_bounces_left(body.bounce_cnt) {
+            body.bouncyness = 0.0;
+        }
+
+        if let Some(stuck_to) = effect.stuck_to {
+            let player_position = players
+                .iter()
+                .find(|&&(pe, ..)| pe == stuck_to)
+                .map(|&(_, _, position, ..)| position);
+
+            // The body is deactivated while stuck, so that it is moved with the player, in stead
+            // of by physics, and it is dropped if the player dies
+            if let Some(player_position) = player_position {
+                transform.position = player_position + effect.stuck_offset;
+                body.velocity = Vec2::ZERO;
+                body.is_deactivated = true;
+            } else {
+                effect.stuck_to = None;
+                body.is_deactivated = false;
+            }
+        }
+
+        if !effect.is_triggered && effect.is_armed() {
             let collider = Rect::new(
                 transform.position.x,
                 transform.position.y,
@@ -217,39 +552,94 @@ pub fn fixed_update_triggered_effects(world: &mut World) {
             let can_be_triggered_by_player =
                 effect.trigger.contains(&TriggeredEffectTrigger::Player);
             let can_be_triggered_by_enemy = effect.trigger.contains(&TriggeredEffectTrigger::Enemy);
+            let can_be_triggered_by_teammate =
+                effect.trigger.contains(&TriggeredEffectTrigger::Teammate);
             let can_be_triggered_by_ground =
                 effect.trigger.contains(&TriggeredEffectTrigger::Ground);
 
-            if can_be_triggered_by_player || can_be_triggered_by_enemy {
+            if can_be_triggered_by_player
+                || can_be_triggered_by_enemy
+                || can_be_triggered_by_teammate
+            {
                 let should_exclude_owner = (effect.is_kickable
-                    && effect.kick_delay_timer < KICK_DELAY)
+                    && effect.kick_delay_timer < effect.kick_delay)
                     || (!can_be_triggered_by_player && !effect.is_kickable);
 
-                'players: for (pe, is_facing_left, position, size) in players.clone() {
-                    if !should_exclude_owner || pe != effect.owner {
+                let owner_team = world
+                    .get::<Player>(effect.owner)
+                    .ok()
+                    .and_then(|player| player.team);
+
+                // Only players that count as one of the triggers of the effect are considered,
+                // apart from the owner of a kickable effect, which can always kick it
+                let players = players
+                    .iter()
+                    .filter(|&&(pe, _, _, _, team)| {
+                        (effect.is_kickable && pe == effect.owner)
+                            || effect
+                                .trigger
+                                .contains(&effect.get_player_trigger(pe, owner_team, team))
+                    })
+                    .copied()
+                    .collect::<Vec<_>>();
+
+                if effect.proximity_radius.is_some() {
+                    let rect = body.as_rect(transform.position);
+                    let center = rect.point() + rect.size() / 2.0;
+
+                    for (pe, _, position, size, _) in players {
+                        if should_exclude_owner && pe == effect.owner {
+                            continue;
+                        }
+
                         let player_collider = Rect::new(position.x, position.y, size.x, size.y);
+                        let proximity = effect.get_proximity(center, &player_collider).unwrap();
+
+                        if proximity >= 1.0 {
+                            if effect.is_sticky {
+                                effect.stick_to(pe, transform.position - position);
+                            } else {
+                                effect.is_triggered = true;
+                                effect.triggered_by = Some(pe);
+                            }
+
+                            break;
+                        }
 
-                        if collider.overlaps(&player_collider) {
-                            let mut should_trigger = false;
+                        effect.proximity_warning = effect.proximity_warning.max(proximity);
+                    }
+                } else {
+                    'players: for (pe, is_facing_left, position, size, _) in players {
+                        if !should_exclude_owner || pe != effect.owner {
+                            let player_collider = Rect::new(position.x, position.y, size.x, size.y);
+
+                            if collider.overlaps(&player_collider) {
+                                let mut should_trigger = false;
 
-                            if effect.is_kickable && effect.kick_delay_timer >= KICK_DELAY {
-                                if is_facing_left && transform.position.x < position.x + size.x {
-                                    body.velocity.x = -KICK_FORCE;
-                                } else if !is_facing_left && transform.position.x > position.x {
-                                    body.velocity.x = KICK_FORCE;
+                                if effect.is_sticky {
+                                    effect.stick_to(pe, transform.position - position);
+                                } else if effect.is_kickable
+                                    && effect.kick_delay_timer >= effect.kick_delay
+                                {
+                                    if is_facing_left && transform.position.x < position.x + size.x
+                                    {
+                                        body.velocity.x = -effect.kick_force;
+                                    } else if !is_facing_left && transform.position.x > position.x {
+                                        body.velocity.x = effect.kick_force;
+                                    } else {
+                                        should_trigger = true;
+                                    }
                                 } else {
                                     should_trigger = true;
                                 }
-                            } else {
-                                should_trigger = true;
-                            }
 
-                            if should_trigger {
-                                effect.is_triggered = true;
-                                effect.triggered_by = Some(pe);
-                            }
+                                if should_trigger {
+                                    effect.is_triggered = true;
+                                    effect.triggered_by = Some(pe);
+                                }
 
-                            break 'players;
+                                break 'players;
+                            }
                         }
                     }
                 }
@@ -269,23 +659,31 @@ pub fn fixed_update_triggered_effects(world: &mut World) {
                 effect.owner,
                 transform.position,
                 effect.effects.clone(),
+                true,
+            );
+            to_trigger.push(params);
+        } else if effect.is_expired() {
+            let params = (
+                entity,
+                effect.triggered_by,
+                effect.owner,
+                transform.position,
+                effect.get_expire_effects(),
+                effect.should_fire_on_expire,
             );
             to_trigger.push(params);
-        } else if let Some(lifetime) = effect.expire_time {
-            if effect.activation_timer >= lifetime {
-                let params = (
-                    entity,
-                    effect.triggered_by,
-                    effect.owner,
-                    transform.position,
-                    effect.expire_effects.clone(),
-                );
-                to_trigger.push(params);
-            }
         }
     }
 
-    for (e, _, owner, origin, effects) in to_trigger.drain(0..) {
+    for (e, triggered_by, owner, origin, effects, is_detonation) in to_trigger.drain(0..) {
+        if is_detonation {
+            dispatch_game_event(GameEvent::TriggeredEffectDetonated {
+                owner,
+                triggered_by,
+                position: origin,
+            });
+        }
+
         for params in effects {
             if let Err(err) = spawn_active_effect(world, owner, e, origin, params) {
                 #[cfg(debug_assertions)]
@@ -301,6 +699,35 @@ pub fn fixed_update_triggered_effects(world: &mut World) {
 }
 
 pub fn update_triggered_effects(world: &mut World) {
+    let dt = get_frame_time();
+
+    for (_, (effect, drawable)) in world.query_mut::<(&mut TriggeredEffect, &mut Drawable)>() {
+        if effect.proximity_radius.is_none() {
+            continue;
+        }
+
+        let tint = if effect.proximity_warning > 0.0 {
+            let frequency = PROXIMITY_BLINK_MIN_FREQUENCY
+                + (PROXIMITY_BLINK_MAX_FREQUENCY - PROXIMITY_BLINK_MIN_FREQUENCY)
+                    * effect.proximity_warning;
+
+            effect.proximity_blink_timer = (effect.proximity_blink_timer + dt * frequency).fract();
+
+            if effect.proximity_blink_timer < 0.5 {
+                PROXIMITY_WARNING_TINT
+            } else {
+                WHITE
+            }
+        } else {
+            effect.proximity_blink_timer = 0.0;
+            WHITE
+        };
+
+        if let Some(sprite) = drawable.get_animated_sprite_mut() {
+            sprite.tint = tint;
+        }
+    }
+
     let mut to_grab = Vec::new();
 
     let players = world
@@ -366,6 +793,10 @@ pub fn update_triggered_effects(world: &mut World) {
         )
         .unwrap();
 
+        world
+            .insert_one(item, ItemDespawnTimer::default())
+            .unwrap();
+
         let player_inventory = world
             .query_one_mut::<&mut PlayerInventory>(*player_entity)
             .unwrap();
@@ -393,8 +824,14 @@ pub struct TriggeredEffectMetadata {
     pub size: Vec2,
     #[serde(default)]
     pub grab_options: Option<TriggeredEffectGrabOptions>,
-    /// This specifies the valid trigger conditions for the trigger.
-    #[serde(default)]
+    /// If this is specified, the effect will be triggered by players that come within this
+    /// distance of its center, in stead of by players that overlap it, once it is armed. It will
+    /// also blink, as players approach it. A radius of `0.0` is the same as not specifying one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proximity_radius: Option<f32>,
+    /// This specifies the valid trigger conditions for the trigger. Trigger groups can be
+    /// specified by name, in which case they are expanded to the triggers in the group.
+    #[serde(default, deserialize_with = "deserialize_triggers")]
     pub trigger: Vec<TriggeredEffectTrigger>,
     /// This specifies the velocity of the triggers body when it is instantiated.
     #[serde(default, with = "core::json::vec2_def")]
@@ -429,15 +866,32 @@ pub struct TriggeredEffectMetadata {
     /// and trigger the effect immediately.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub timed_trigger: Option<f32>,
-    /// An optional lifetime after which the effect with despawn, _without_ triggering
+    /// An optional lifetime after which the effect will despawn, _without_ triggering, unless
+    /// `fire_on_expire` is `true`. This should be set on effects that might never be triggered,
+    /// like mines that fall into a pit, so that they don't linger for the rest of the match.
     #[serde(default)]
     pub expire_time: Option<f32>,
     /// A list of effects that will activate when the item expires, if `expire_time` is not null
     #[serde(default)]
     pub expire_effects: Vec<ActiveEffectMetadata>,
+    /// If this is `true` the effects in `effects` will also be activated when the effect expires,
+    /// as if it was triggered
+    #[serde(
+        default,
+        rename = "fire_on_expire",
+        skip_serializing_if = "core::json::is_false"
+    )]
+    pub should_fire_on_expire: bool,
     /// If this is `true` the trigger is kicked by a player, if it hits him while he is facing it
     #[serde(default)]
     pub is_kickable: bool,
+    /// The horizontal velocity of the effect when it is kicked. Heavier objects should have a
+    /// lower value than lighter ones.
+    #[serde(default = "default_kick_force")]
+    pub kick_force: f32,
+    /// The delay, after the effect has been deployed, before it can be kicked
+    #[serde(default = "default_kick_delay")]
+    pub kick_delay: f32,
     /// If this is `true` the effect will collide with platforms. This will also trigger it on
     /// collisions with platforms, if `ground` is selected as one of the trigger criteria
     #[serde(default, rename = "collide_with_platforms")]
@@ -449,6 +903,16 @@ pub struct TriggeredEffectMetadata {
     pub gravity: f32,
     #[serde(default)]
     pub bouncyness: f32,
+    /// If specified, the effect will stop bouncing, and come to rest, after it has bounced off
+    /// the ground or walls this many times. If not, it will keep bouncing, as long as
+    /// `bouncyness` is above zero.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bounces: Option<u32>,
+    /// If this is `true` the effect will stick to the first player that triggers it, and follow
+    /// that player until it goes off. This requires `player` or `enemy` to be selected as one of
+    /// the trigger criteria.
+    #[serde(default, skip_serializing_if = "core::json::is_false")]
+    pub is_sticky: bool,
 }
 
 impl Default for TriggeredEffectMetadata {
@@ -458,6 +922,7 @@ impl Default for TriggeredEffectMetadata {
             particles: Vec::new(),
             size: vec2(6.0, 6.0),
             grab_options: None,
+            proximity_radius: None,
             trigger: Vec::new(),
             velocity: Vec2::ZERO,
             spread: 0.0,
@@ -469,11 +934,16 @@ impl Default for TriggeredEffectMetadata {
             timed_trigger: None,
             expire_time: None,
             expire_effects: Vec::new(),
+            should_fire_on_expire: false,
             is_kickable: false,
+            kick_force: default_kick_force(),
+            kick_delay: default_kick_delay(),
             should_collide_with_platforms: false,
             can_rotate: false,
             gravity: default_physics_gravity(),
             bouncyness: 0.0,
+            max_bounces: None,
+            is_sticky: false,
         }
     }
 }
@@ -520,6 +990,33 @@ impl TriggeredEffectGrabOptions {
     }
 }
 
+pub fn debug_draw_triggered_effects(world: &mut World) {
+    for (_, (effect, transform, body)) in world
+        .query::<(&TriggeredEffect, &Transform, &PhysicsBody)>()
+        .iter()
+    {
+        if let Some(radius) = effect.proximity_radius {
+            let rect = body.as_rect(transform.position);
+            let center = rect.point() + rect.size() / 2.0;
+
+            let color = if effect.is_armed() {
+                color::RED
+            } else {
+                color::GRAY
+            };
+
+            draw_circle_lines(center.x, center.y, radius, 2.0, color);
+            draw_circle_lines(
+                center.x,
+                center.y,
+                radius * PROXIMITY_WARNING_DISTANCE_FACTOR,
+                1.0,
+                color::ORANGE,
+            );
+        }
+    }
+}
+
 fn default_true() -> bool {
     true
 }
@@ -527,3 +1024,437 @@ fn default_true() -> bool {
 fn default_physics_gravity() -> f32 {
     physics::GRAVITY
 }
+
+fn default_kick_force() -> f32 {
+    KICK_FORCE
+}
+
+fn default_kick_delay() -> f32 {
+    KICK_DELAY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::get_tick_rate;
+    use crate::ActiveEffectKind;
+
+    fn assert_vec2_eq(a: Vec2, b: Vec2) {
+        assert!(a.distance(b) < 0.001, "{:?} != {:?}", a, b);
+    }
+
+    fn get_test_body(
+        collision_world: &mut CollisionWorld,
+        position: Vec2,
+        size: Vec2,
+    ) -> PhysicsBody {
+        let actor = collision_world.add_actor(position, size.x as i32, size.y as i32);
+
+        PhysicsBody::new(
+            actor,
+            None,
+            PhysicsBodyParams {
+                size,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn spawn_test_player(
+        world: &mut World,
+        collision_world: &mut CollisionWorld,
+        position: Vec2,
+    ) -> Entity {
+        let body = get_test_body(collision_world, position, vec2(16.0, 32.0));
+
+        world.spawn((Player::new(0, position), Transform::from(position), body))
+    }
+
+    fn spawn_test_effect(
+        world: &mut World,
+        collision_world: &mut CollisionWorld,
+        owner: Entity,
+        position: Vec2,
+        meta: TriggeredEffectMetadata,
+    ) -> Entity {
+        let body = get_test_body(collision_world, position, meta.size);
+
+        world.spawn((
+            TriggeredEffect::new(owner, meta),
+            Transform::from(position),
+            body,
+        ))
+    }
+
+    #[test]
+    fn test_proximity_trigger() {
+        let mut world = World::new();
+        let owner = world.spawn(());
+
+        let mut effect = TriggeredEffect::new(
+            owner,
+            TriggeredEffectMetadata {
+                activation_delay: 1.0,
+                proximity_radius: Some(32.0),
+                ..Default::default()
+            },
+        );
+
+        assert!(!effect.is_armed());
+        effect.activation_timer += 1.0;
+        assert!(effect.is_armed());
+
+        let center = Vec2::ZERO;
+
+        let target = Rect::new(24.0, -8.0, 16.0, 16.0);
+        assert_eq!(effect.get_proximity(center, &target), Some(1.0));
+
+        let target = Rect::new(64.0, -8.0, 16.0, 16.0);
+        let proximity = effect.get_proximity(center, &target).unwrap();
+        assert!(proximity > 0.0 && proximity < 1.0);
+
+        let target = Rect::new(128.0, -8.0, 16.0, 16.0);
+        assert_eq!(effect.get_proximity(center, &target), Some(0.0));
+
+        effect.proximity_radius = None;
+        assert_eq!(effect.get_proximity(center, &target), None);
+
+        let effect = TriggeredEffect::new(
+            owner,
+            TriggeredEffectMetadata {
+                proximity_radius: Some(0.0),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(effect.proximity_radius, None);
+    }
+
+    #[test]
+    fn test_player_trigger() {
+        let mut world = World::new();
+        let owner = world.spawn(());
+        let other = world.spawn(());
+
+        let effect = TriggeredEffect::new(owner, Default::default());
+
+        assert_eq!(
+            effect.get_player_trigger(owner, Some(0), Some(0)),
+            TriggeredEffectTrigger::Player
+        );
+        assert_eq!(
+            effect.get_player_trigger(other, Some(0), Some(0)),
+            TriggeredEffectTrigger::Teammate
+        );
+        assert_eq!(
+            effect.get_player_trigger(other, Some(0), Some(1)),
+            TriggeredEffectTrigger::Enemy
+        );
+
+        // Without team info, everyone else is an enemy
+        assert_eq!(
+            effect.get_player_trigger(other, None, None),
+            TriggeredEffectTrigger::Enemy
+        );
+    }
+
+    #[test]
+    fn test_teammate_trigger() {
+        let mut world = World::new();
+        let mut collision_world = CollisionWorld::new();
+
+        let mut spawn_player = |position: Vec2, team: Option<u8>| {
+            let entity = spawn_test_player(&mut world, &mut collision_world, position);
+            world.get_mut::<Player>(entity).unwrap().team = team;
+            entity
+        };
+
+        let owner = spawn_player(vec2(500.0, 0.0), Some(0));
+        let teammate = spawn_player(vec2(-500.0, 0.0), Some(0));
+        spawn_player(Vec2::ZERO, Some(1));
+
+        let effect = spawn_test_effect(
+            &mut world,
+            &mut collision_world,
+            owner,
+            vec2(4.0, 8.0),
+            TriggeredEffectMetadata {
+                trigger: vec![TriggeredEffectTrigger::Teammate],
+                trigger_delay: 100.0,
+                should_collide_with_platforms: true,
+                ..Default::default()
+            },
+        );
+
+        // Enemies do not trigger an effect that is only triggered by teammates
+        fixed_update_triggered_effects(&mut world);
+        assert!(!world.get::<TriggeredEffect>(effect).unwrap().is_triggered);
+
+        world.get_mut::<Transform>(teammate).unwrap().position = Vec2::ZERO;
+        fixed_update_triggered_effects(&mut world);
+
+        let effect = world.get::<TriggeredEffect>(effect).unwrap();
+        assert!(effect.is_triggered);
+        assert_eq!(effect.triggered_by, Some(teammate));
+    }
+
+    #[test]
+    fn test_trigger_groups() {
+        use TriggeredEffectTrigger::{Enemy, Explosion, Ground, Player, Teammate};
+        use TriggeredEffectTriggerParams::{Named, Trigger};
+
+        let mut groups = TriggerGroups::default();
+
+        groups
+            .extend(HashMap::from([
+                (
+                    "all_players".to_string(),
+                    vec![Trigger(Player), Trigger(Enemy), Trigger(Teammate)],
+                ),
+                (
+                    "everything".to_string(),
+                    vec![Named("all_players".to_string()), Trigger(Ground)],
+                ),
+            ]))
+            .unwrap();
+
+        // Groups can also include groups that were added earlier
+        groups
+            .extend(HashMap::from([(
+                "volatile".to_string(),
+                vec![Named("everything".to_string()), Trigger(Explosion)],
+            )]))
+            .unwrap();
+
+        let triggers = groups
+            .expand(&[Named("volatile".to_string()), Trigger(Player)])
+            .unwrap();
+
+        assert_eq!(triggers, vec![Player, Enemy, Teammate, Ground, Explosion]);
+
+        assert!(groups.expand(&[Named("unknown".to_string())]).is_err());
+
+        let result = groups.extend(HashMap::from([
+            ("a".to_string(), vec![Named("b".to_string())]),
+            ("b".to_string(), vec![Named("a".to_string())]),
+        ]));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_max_bounces() {
+        let mut world = World::new();
+        let owner = world.spawn(());
+
+        let effect = TriggeredEffect::new(
+            owner,
+            TriggeredEffectMetadata {
+                bouncyness: 0.5,
+                ..Default::default()
+            },
+        );
+
+        assert!(effect.has_bounces_left(100));
+
+        let effect = TriggeredEffect::new(
+            owner,
+            TriggeredEffectMetadata {
+                bouncyness: 0.5,
+                max_bounces: Some(2),
+                ..Default::default()
+            },
+        );
+
+        assert!(effect.has_bounces_left(0));
+        assert!(effect.has_bounces_left(1));
+        assert!(!effect.has_bounces_left(2));
+    }
+
+    #[test]
+    fn test_sticky_trigger() {
+        let mut world = World::new();
+        let mut collision_world = CollisionWorld::new();
+
+        let owner = world.spawn(());
+        let player = spawn_test_player(&mut world, &mut collision_world, vec2(0.0, 0.0));
+
+        let effect = spawn_test_effect(
+            &mut world,
+            &mut collision_world,
+            owner,
+            vec2(4.0, 8.0),
+            TriggeredEffectMetadata {
+                trigger: vec![TriggeredEffectTrigger::Enemy],
+                is_sticky: true,
+                trigger_delay: 100.0,
+                should_collide_with_platforms: true,
+                ..Default::default()
+            },
+        );
+
+        fixed_update_triggered_effects(&mut world);
+
+        {
+            let effect = world.get::<TriggeredEffect>(effect).unwrap();
+            assert_eq!(effect.stuck_to, Some(player));
+            assert_eq!(effect.triggered_by, Some(player));
+            assert!(effect.is_triggered);
+        }
+
+        // The effect follows the player, with its body deactivated, until the trigger delay runs out
+        world.get_mut::<Transform>(player).unwrap().position = vec2(100.0, 50.0);
+        fixed_update_triggered_effects(&mut world);
+
+        assert_vec2_eq(
+            world.get::<Transform>(effect).unwrap().position,
+            vec2(104.0, 58.0),
+        );
+
+        {
+            let body = world.get::<PhysicsBody>(effect).unwrap();
+            assert!(body.is_deactivated);
+            assert_eq!(body.velocity, Vec2::ZERO);
+        }
+
+        // When the player dies, the effect is dropped and left to physics
+        world.get_mut::<Player>(player).unwrap().state = PlayerState::Dead;
+        fixed_update_triggered_effects(&mut world);
+
+        assert_eq!(world.get::<TriggeredEffect>(effect).unwrap().stuck_to, None);
+        assert!(!world.get::<PhysicsBody>(effect).unwrap().is_deactivated);
+        assert!(world.contains(effect));
+    }
+
+    #[test]
+    fn test_sticky_proximity_trigger() {
+        let mut world = World::new();
+        let mut collision_world = CollisionWorld::new();
+
+        let owner = world.spawn(());
+        let player = spawn_test_player(&mut world, &mut collision_world, vec2(0.0, 0.0));
+
+        // The player is within the proximity radius, but does not overlap the effect
+        let effect = spawn_test_effect(
+            &mut world,
+            &mut collision_world,
+            owner,
+            vec2(40.0, 8.0),
+            TriggeredEffectMetadata {
+                trigger: vec![TriggeredEffectTrigger::Enemy],
+                proximity_radius: Some(32.0),
+                is_sticky: true,
+                trigger_delay: 100.0,
+                should_collide_with_platforms: true,
+                ..Default::default()
+            },
+        );
+
+        fixed_update_triggered_effects(&mut world);
+
+        let effect = world.get::<TriggeredEffect>(effect).unwrap();
+        assert_eq!(effect.stuck_to, Some(player));
+        assert_eq!(effect.stuck_offset, vec2(40.0, 8.0));
+        assert_eq!(effect.triggered_by, Some(player));
+        assert!(effect.is_triggered);
+    }
+
+    #[test]
+    fn test_expire_time() {
+        let mut world = World::new();
+        let mut collision_world = CollisionWorld::new();
+
+        let owner = world.spawn(());
+
+        let explosion = ActiveEffectMetadata {
+            kind: Box::new(ActiveEffectKind::CircleCollider {
+                radius: 32.0,
+                passive_effects: Vec::new(),
+                is_lethal: true,
+                is_explosion: true,
+            }),
+            sound_effect_id: None,
+            screen_flash: None,
+            delay: 0.0,
+        };
+
+        let meta = TriggeredEffectMetadata {
+            effects: vec![explosion],
+            expire_time: Some(5.0),
+            ..Default::default()
+        };
+
+        // Removed silently, by default
+        let effect = TriggeredEffect::new(owner, meta.clone());
+        assert!(effect.get_expire_effects().is_empty());
+
+        let effect = TriggeredEffect::new(
+            owner,
+            TriggeredEffectMetadata {
+                should_fire_on_expire: true,
+                ..meta
+            },
+        );
+
+        assert_eq!(effect.get_expire_effects().len(), 1);
+
+        let effect = spawn_test_effect(
+            &mut world,
+            &mut collision_world,
+            owner,
+            Vec2::ZERO,
+            TriggeredEffectMetadata {
+                expire_time: Some(1.0),
+                should_collide_with_platforms: true,
+                ..Default::default()
+            },
+        );
+
+        // Effects without an expire time never expire
+        let immortal = spawn_test_effect(
+            &mut world,
+            &mut collision_world,
+            owner,
+            Vec2::ZERO,
+            TriggeredEffectMetadata {
+                should_collide_with_platforms: true,
+                ..Default::default()
+            },
+        );
+
+        fixed_update_triggered_effects(&mut world);
+        assert!(world.contains(effect));
+
+        for _ in 0..get_tick_rate() {
+            fixed_update_triggered_effects(&mut world);
+        }
+
+        assert!(!world.contains(effect));
+        assert!(world.contains(immortal));
+    }
+
+    #[test]
+    fn test_cluster_children() {
+        let children = get_cluster_children(3, deg_to_rad(90.0), 10.0, vec2(0.0, -5.0));
+
+        assert_eq!(children.len(), 3);
+
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+
+        assert_vec2_eq(children[0].0, vec2(-half * 10.0, -half * 10.0));
+        assert_vec2_eq(children[1].0, vec2(0.0, -10.0));
+        assert_vec2_eq(children[2].0, vec2(half * 10.0, -half * 10.0));
+
+        assert_vec2_eq(children[1].1, vec2(0.0, -5.0));
+        assert_vec2_eq(children[2].1, vec2(half * 5.0, -half * 5.0));
+
+        let children = get_cluster_children(4, deg_to_rad(360.0), 10.0, Vec2::ZERO);
+
+        assert_eq!(children.len(), 4);
+        assert_vec2_eq(children[0].0, vec2(0.0, -10.0));
+        assert_vec2_eq(children[1].0, vec2(10.0, 0.0));
+        assert_vec2_eq(children[2].0, vec2(0.0, 10.0));
+        assert_vec2_eq(children[3].0, vec2(-10.0, 0.0));
+    }
+}
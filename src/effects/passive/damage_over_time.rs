@@ -0,0 +1,216 @@
+use macroquad::experimental::collections::storage;
+use macroquad::prelude::*;
+
+use hecs::World;
+
+use serde::{Deserialize, Serialize};
+
+use core::Transform;
+
+use crate::game::get_fixed_delta;
+use crate::particles::{ParticleBlendMode, Particles};
+use crate::player::{Player, PlayerEventQueue, PlayerState};
+use crate::PlayerEvent;
+
+/// Parameters for the periodic damage of a passive effect, like poison.
+/// Every passive effect instance ticks on its own, so applying the same effect more than once
+/// will stack the damage.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DamageOverTimeMetadata {
+    /// The time between damage ticks, in seconds. The first tick happens one interval after the
+    /// effect is applied.
+    pub tick_interval: f32,
+    /// The damage dealt on each tick. As players do not have health, every point of damage is
+    /// received as a separate hit, so higher damage wears down effects that are used up by hits,
+    /// like shields, faster. Any hit that is not blocked will kill the player.
+    #[serde(default = "DamageOverTimeMetadata::default_damage")]
+    pub damage: u32,
+    /// This is the particle effect that will be spawned on the player, on each tick
+    #[serde(
+        default,
+        rename = "particle_effect",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub particle_effect_id: Option<String>,
+    #[serde(default, skip_serializing_if = "ParticleBlendMode::is_alpha")]
+    pub blend_mode: ParticleBlendMode,
+}
+
+impl DamageOverTimeMetadata {
+    pub fn default_damage() -> u32 {
+        1
+    }
+}
+
+pub struct DamageOverTime {
+    pub tick_interval: f32,
+    pub damage: u32,
+    pub particle_effect_id: Option<String>,
+    pub blend_mode: ParticleBlendMode,
+    /// The duration of the effect that this belongs to. No ticks happen after this.
+    pub duration: Option<f32>,
+    pub timer: f32,
+    pub tick_cnt: u32,
+}
+
+impl DamageOverTime {
+    pub fn new(duration: Option<f32>, meta: DamageOverTimeMetadata) -> Self {
+        DamageOverTime {
+            tick_interval: meta.tick_interval,
+            damage: meta.damage,
+            particle_effect_id: meta.particle_effect_id,
+            blend_mode: meta.blend_mode,
+            duration,
+            timer: 0.0,
+            tick_cnt: 0,
+        }
+    }
+
+    /// Advance the timer by `dt` and return the number of ticks that happened. This should be
+    /// called with the fixed delta, so that ticks happen at the same time on every client.
+    pub fn update(&mut self, dt: f32) -> u32 {
+        if self.tick_interval <= 0.0 {
+            return 0;
+        }
+
+        self.timer += dt;
+
+        if let Some(duration) = self.duration {
+            self.timer = self.timer.min(duration);
+        }
+
+        let total_cnt = (self.timer / self.tick_interval).floor() as u32;
+        let tick_cnt = total_cnt.saturating_sub(self.tick_cnt);
+
+        self.tick_cnt = total_cnt;
+
+        tick_cnt
+    }
+}
+
+pub fn fixed_update_damage_over_time(world: &mut World) {
+    let dt = get_fixed_delta();
+
+    let mut particle_spawns = Vec::new();
+
+    for (_, (player, transform, events)) in
+        world.query_mut::<(&mut Player, &Transform, &mut PlayerEventQueue)>()
+    {
+        if player.state == PlayerState::Dead {
+            continue;
+        }
+
+        let is_facing_left = player.is_facing_left;
+
+        for effect in &mut player.passive_effects {
+            if effect.is_depleted() {
+                continue;
+            }
+
+            if let Some(dot) = &mut effect.damage_over_time {
+                for _ in 0..dot.update(dt) {
+                    if let Some(particle_effect_id) = &dot.particle_effect_id {
+                        particle_spawns.push((
+                            particle_effect_id.clone(),
+                            dot.blend_mode,
+                            transform.position,
+                        ));
+                    }
+
+                    // The damage is dealt from the front, so that it is not blocked by effects
+                    // that protect the back of the player, like the turtle shell
+                    for _ in 0..dot.damage {
+                        events.queue.push(PlayerEvent::ReceiveDamage {
+                            is_from_left: is_facing_left,
+                            damage_from: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    if !particle_spawns.is_empty() {
+        let mut particles = storage::get_mut::<Particles>();
+
+        for (particle_effect_id, blend_mode, position) in particle_spawns {
+            if let Some(cache) = particles.get_cache_mut(&particle_effect_id, blend_mode) {
+                cache.spawn(position);
+            } else {
+                #[cfg(debug_assertions)]
+                println!(
+                    "WARNING: Invalid particle effect id '{}'",
+                    &particle_effect_id
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::effects::passive::{PassiveEffectInstance, PassiveEffectMetadata};
+    use crate::player::PlayerEventKind;
+
+    #[test]
+    fn test_damage_over_time_total() {
+        let meta = PassiveEffectMetadata {
+            name: "poison".to_string(),
+            function_id: None,
+            activated_on: vec![PlayerEventKind::ReceiveDamage],
+            particle_effect_id: None,
+            event_particle_effect_id: None,
+            blocks_damage: false,
+            freezes_player: false,
+            breaks_on_hit: false,
+            uses: None,
+            duration: Some(2.0),
+            damage_over_time: Some(DamageOverTimeMetadata {
+                tick_interval: 0.5,
+                damage: 3,
+                particle_effect_id: None,
+                blend_mode: ParticleBlendMode::Alpha,
+            }),
+            sprite: None,
+        };
+
+        let mut player = Player::new(0, Vec2::ZERO);
+        player
+            .passive_effects
+            .push(PassiveEffectInstance::new(None, meta));
+
+        let mut world = World::new();
+        let entity = world.spawn((player, Transform::from(Vec2::ZERO), PlayerEventQueue::new()));
+
+        let get_hit_cnt = |world: &World| {
+            let events = world.get::<PlayerEventQueue>(entity).unwrap();
+            events
+                .queue
+                .iter()
+                .filter(|event| matches!(event, PlayerEvent::ReceiveDamage { .. }))
+                .count()
+        };
+
+        // Run the simulation for a bit more than two tick intervals
+        let tick_cnt = (1.25 / get_fixed_delta()).round() as u32;
+        for _ in 0..tick_cnt {
+            fixed_update_damage_over_time(&mut world);
+        }
+
+        assert_eq!(get_hit_cnt(&world), 2 * 3);
+
+        // No damage is dealt after the duration of the effect
+        for _ in 0..tick_cnt * 2 {
+            fixed_update_damage_over_time(&mut world);
+        }
+
+        assert_eq!(get_hit_cnt(&world), 4 * 3);
+
+        let player = world.get::<Player>(entity).unwrap();
+        let dot = player.passive_effects[0].damage_over_time.as_ref().unwrap();
+        assert_eq!(dot.tick_cnt, 4);
+    }
+}
@@ -6,8 +6,11 @@ use serde::{Deserialize, Serialize};
 
 use hecs::{Entity, World};
 
+mod damage_over_time;
 mod turtle_shell;
 
+pub use damage_over_time::{fixed_update_damage_over_time, DamageOverTime, DamageOverTimeMetadata};
+
 use crate::player::PlayerEventKind;
 use crate::{AnimatedSprite, AnimatedSpriteMetadata, PlayerEvent};
 
@@ -51,11 +54,15 @@ pub struct PassiveEffectInstance {
     pub particle_effect_id: Option<String>,
     pub event_particle_effect_id: Option<String>,
     pub blocks_damage: bool,
+    pub freezes_player: bool,
+    pub breaks_on_hit: bool,
     pub uses: Option<u32>,
     pub item: Option<Entity>,
     pub use_cnt: u32,
     pub duration: Option<f32>,
     pub duration_timer: f32,
+    pub is_broken: bool,
+    pub damage_over_time: Option<DamageOverTime>,
 }
 
 impl PassiveEffectInstance {
@@ -71,11 +78,17 @@ impl PassiveEffectInstance {
             particle_effect_id: meta.particle_effect_id,
             event_particle_effect_id: meta.event_particle_effect_id,
             blocks_damage: meta.blocks_damage,
+            freezes_player: meta.freezes_player,
+            breaks_on_hit: meta.breaks_on_hit,
             uses: meta.uses,
             item,
             use_cnt: 0,
             duration: meta.duration,
             duration_timer: 0.0,
+            is_broken: false,
+            damage_over_time: meta
+                .damage_over_time
+                .map(|dot| DamageOverTime::new(meta.duration, dot)),
         }
     }
 
@@ -83,7 +96,24 @@ impl PassiveEffectInstance {
         self.duration_timer += dt;
     }
 
+    /// This should be called when the player that has the effect receives damage. Effects that
+    /// break on hit will be depleted.
+    pub fn on_hit(&mut self) {
+        if self.breaks_on_hit {
+            self.is_broken = true;
+        }
+    }
+
+    /// Returns `true` if the effect is active and it prevents the player from acting
+    pub fn is_freezing(&self) -> bool {
+        self.freezes_player && !self.is_depleted()
+    }
+
     pub fn is_depleted(&self) -> bool {
+        if self.is_broken {
+            return true;
+        }
+
         if let Some(duration) = self.duration {
             if self.duration_timer >= duration {
                 return true;
@@ -126,14 +156,30 @@ pub struct PassiveEffectMetadata {
     /// If this is true damage will be blocked on a player that has the item equipped
     #[serde(default)]
     pub blocks_damage: bool,
+    /// If this is true the player will be frozen while the effect is active. A frozen player
+    /// ignores all input, is tinted blue and falls faster.
+    #[serde(default)]
+    pub freezes_player: bool,
+    /// If this is true the effect will be depleted when the player receives damage, even if the
+    /// damage is blocked.
+    #[serde(default)]
+    pub breaks_on_hit: bool,
     /// This is the amount of times the coroutine can be called, before the effect is depleted
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub uses: Option<u32>,
     /// This is the duration of the effect.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub duration: Option<f32>,
+    /// If this is specified, the player will be damaged periodically, while the effect is active
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub damage_over_time: Option<DamageOverTimeMetadata>,
 
     /// An optional sprite to add to the player along with the effect
     #[serde(alias = "animation")]
     pub sprite: Option<AnimatedSpriteMetadata>,
 }
+
+/// Returns `true` if any of the specified effects freezes the player
+pub fn is_frozen(effects: &[PassiveEffectInstance]) -> bool {
+    effects.iter().any(|effect| effect.is_freezing())
+}
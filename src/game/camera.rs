@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use macroquad::prelude::collections::storage;
 use macroquad::prelude::*;
 use macroquad::rand::gen_range;
@@ -5,6 +7,7 @@ use macroquad::rand::gen_range;
 use core::noise::NoiseGenerator;
 
 use crate::map::Map;
+use crate::Config;
 
 struct Shake {
     direction: (f32, f32),
@@ -16,6 +19,48 @@ struct Shake {
     frequency: f32, // 1 is pretty standard, .2 is a punch (with 10 frames of shake it oscillates about max twice). With .5 it's more of a rumble
 }
 
+/// A transient zoom offset, that eases back to zero over its length
+struct ZoomPunch {
+    amount: f32,
+    length: f32, //in frames, like shake
+    age: f32,
+}
+
+/// A manual target and zoom that the camera is eased towards, in stead of snapping to it
+struct SmoothManual {
+    /// This is `None` while control is being handed back to player-following
+    target: Option<(Vec2, f32)>,
+    position: Vec2,
+    zoom: f32,
+    lerp_speed: f32,
+}
+
+impl SmoothManual {
+    /// The distance, in world units, and the difference in zoom, within which easing is finished
+    const EPSILON: f32 = 0.1;
+
+    /// Ease towards the manual target or, if it has been cleared, `follow_target`. This returns
+    /// `true` once control has been handed back to player-following.
+    fn update(&mut self, follow_target: (Vec2, f32), dt: f32) -> bool {
+        let (target, target_zoom) = self.target.unwrap_or(follow_target);
+
+        let t = (self.lerp_speed * dt).clamp(0.0, 1.0);
+        self.position = self.position.lerp(target, t);
+        self.zoom += (target_zoom - self.zoom) * t;
+
+        if self.position.distance(target) < Self::EPSILON
+            && (target_zoom - self.zoom).abs() < Self::EPSILON
+        {
+            self.position = target;
+            self.zoom = target_zoom;
+
+            return self.target.is_none();
+        }
+
+        false
+    }
+}
+
 #[allow(dead_code)]
 enum ShakeType {
     Noise,
@@ -23,39 +68,99 @@ enum ShakeType {
     Rotational,
 }
 
+/// Tuned combinations of shakes, for common events, so that weapons and effects do not have to
+/// pick the parameters of the low-level shake methods themselves
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ShakePreset {
+    /// A strong, rumbling shake, with a jolt of rotation
+    Explosion,
+    /// A short punch, like when a player is hit
+    Hit,
+    /// A long, gentle rumble, like an earthquake or heavy machinery
+    Rumble,
+    /// A short, vertical bump, like when something heavy lands
+    Landing,
+}
+
 pub struct GameCamera {
     bounds: Rect,
     follow_buffer: Vec<(Vec2, f32)>,
     shake: Vec<Shake>,
+    zoom_punches: Vec<ZoomPunch>,
+    /// This is applied to shake and zoom punches, and is set from the accessibility config
+    shake_scale: f32,
     noisegen: NoiseGenerator,
     noisegen_position: f32,
 
     pub manual: Option<(Vec2, f32)>,
-    player_rects: Vec<Rect>,
+    manual_smooth: Option<SmoothManual>,
+    /// The size of the dead-zone, as a fraction of the view size. Player movement within the
+    /// dead-zone does not move the camera.
+    dead_zone: Vec2,
+    /// The target that the dead-zone is centered on, from the last update
+    dead_zone_target: Option<Vec2>,
+    /// The rects of the players added this frame, with the weight of each player
+    player_rects: Vec<(Rect, f32)>,
+    /// The weights of players, by player index, that determine how much each player pulls the
+    /// camera towards it. Players that are not in this map have a weight of `1.0`.
+    player_weights: HashMap<u8, f32>,
+    /// The camera that was applied in the last update, including smoothing and shake
+    macroquad_camera: Camera2D,
 }
 
 impl GameCamera {
     const BUFFER_CAPACITY: usize = 20;
+    /// Zoom punches are limited to this fraction of the view size, when combined
+    const MAX_ZOOM_PUNCH: f32 = 0.5;
 
     pub fn new(map_size: Vec2) -> GameCamera {
         let bounds = Rect::new(0.0, 0.0, map_size.x, map_size.y);
 
+        let shake_scale = {
+            let config = storage::get::<Config>();
+            config.accessibility.shake_scale.max(0.0)
+        };
+
         GameCamera {
             bounds,
             follow_buffer: vec![],
             shake: vec![],
+            zoom_punches: vec![],
+            shake_scale,
             manual: None,
+            manual_smooth: None,
+            dead_zone: Vec2::ZERO,
+            dead_zone_target: None,
             noisegen: NoiseGenerator::new(5),
             noisegen_position: 5.0,
             player_rects: Vec::new(),
+            player_weights: HashMap::new(),
+            macroquad_camera: Camera2D::default(),
         }
     }
 
-    pub fn add_player_rect(&mut self, rect: Rect) {
+    /// Set the weight of the player with the specified index. A player with a higher weight will
+    /// pull the camera more towards it, like a focused player in a tutorial, or when spectating.
+    /// All players have a weight of `1.0`, by default.
+    pub fn set_player_weight(&mut self, player_index: u8, weight: f32) {
+        self.player_weights.insert(player_index, weight.max(0.0));
+    }
+
+    pub fn get_player_weight(&self, player_index: u8) -> f32 {
+        self.player_weights
+            .get(&player_index)
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    pub fn add_player_rect(&mut self, player_index: u8, rect: Rect) {
+        let weight = self.get_player_weight(player_index);
+
         let map = storage::get::<Map>();
         let playable = map.get_playable_area();
         if playable.overlaps(&rect) {
-            self.player_rects.push(rect);
+            self.player_rects.push((rect, weight));
 
         // We don't want to try to follow the player out of the playable area, so set the
         // effective player rect to the closest spot to the player that is still touching the
@@ -64,11 +169,14 @@ impl GameCamera {
             let min = Vec2::new(playable.x, playable.y);
             let max = min + Vec2::new(playable.w, playable.h);
 
-            self.player_rects.push(Rect::new(
-                rect.x.max(min.x).min(max.x),
-                rect.y.max(min.y).min(max.y),
-                rect.w,
-                rect.h,
+            self.player_rects.push((
+                Rect::new(
+                    rect.x.max(min.x).min(max.x),
+                    rect.y.max(min.y).min(max.y),
+                    rect.w,
+                    rect.h,
+                ),
+                weight,
             ));
         }
     }
@@ -76,6 +184,96 @@ impl GameCamera {
 
 #[allow(dead_code)]
 impl GameCamera {
+    /// Set the factor that the intensity of camera shake and zoom punches is multiplied by, like
+    /// from an accessibility menu. A scale of `0.0` disables them entirely.
+    pub fn set_shake_scale(&mut self, scale: f32) {
+        self.shake_scale = scale.max(0.0);
+    }
+
+    pub fn get_shake_scale(&self) -> f32 {
+        self.shake_scale
+    }
+
+    /// Set the size of the dead-zone, as a fraction of the view size. The camera will not move,
+    /// to follow the players, until they move out of the dead-zone. This is zero, by default.
+    pub fn set_dead_zone(&mut self, size: Vec2) {
+        self.dead_zone = size.clamp(Vec2::ZERO, Vec2::ONE);
+
+        if self.dead_zone == Vec2::ZERO {
+            self.dead_zone_target = None;
+        }
+    }
+
+    /// Ease the camera towards the specified target and zoom, at `lerp_speed`, like a cutscene
+    /// pan. Once it arrives, it stays on the target, like `manual`, until this is cleared.
+    pub fn set_manual_smooth(&mut self, target: Vec2, zoom: f32, lerp_speed: f32) {
+        if let Some(manual) = &mut self.manual_smooth {
+            manual.target = Some((target, zoom));
+            manual.lerp_speed = lerp_speed;
+        } else {
+            let (position, current_zoom) = self
+                .follow_buffer
+                .first()
+                .copied()
+                .unwrap_or((target, zoom));
+
+            self.manual_smooth = Some(SmoothManual {
+                target: Some((target, zoom)),
+                position,
+                zoom: current_zoom,
+                lerp_speed,
+            });
+        }
+    }
+
+    /// Ease the camera back to following the players, from the target set by `set_manual_smooth`
+    pub fn clear_manual_smooth(&mut self) {
+        if let Some(manual) = &mut self.manual_smooth {
+            manual.target = None;
+        }
+    }
+
+    /// Returns the area of the world that is currently on screen. If the camera is rotated, by
+    /// shake, this will be the bounding box of the visible area.
+    pub fn get_view_rect(&self) -> Rect {
+        let corners = [
+            vec2(0.0, 0.0),
+            vec2(screen_width(), 0.0),
+            vec2(0.0, screen_height()),
+            vec2(screen_width(), screen_height()),
+        ]
+        .map(|corner| self.screen_to_world(corner));
+
+        let mut min = corners[0];
+        let mut max = corners[0];
+
+        for corner in &corners[1..] {
+            min = min.min(*corner);
+            max = max.max(*corner);
+        }
+
+        Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+
+    /// Returns the camera that was applied in the last update
+    pub fn get_macroquad_camera(&self) -> Camera2D {
+        self.macroquad_camera
+    }
+
+    pub fn world_to_screen(&self, position: Vec2) -> Vec2 {
+        self.macroquad_camera.world_to_screen(position)
+    }
+
+    pub fn screen_to_world(&self, position: Vec2) -> Vec2 {
+        self.macroquad_camera.screen_to_world(position)
+    }
+
+    /// Returns the position of the mouse cursor in the world, taking the rotation of the camera,
+    /// from shake, into account
+    pub fn get_cursor_world_position(&self) -> Vec2 {
+        self.screen_to_world(mouse_position().into())
+    }
+
     pub fn shake_noise(&mut self, magnitude: f32, length: i32, frequency: f32) {
         self.shake.push(Shake {
             direction: (1.0, 1.0),
@@ -130,6 +328,53 @@ impl GameCamera {
         });
     }
 
+    /// Trigger the shakes of the specified preset
+    pub fn shake_preset(&mut self, preset: ShakePreset) {
+        match preset {
+            ShakePreset::Explosion => {
+                self.shake_noise(2.0, 20, 0.5);
+                self.shake_rotational(1.0, 10);
+            }
+            ShakePreset::Hit => {
+                self.shake_noise(0.6, 8, 0.2);
+            }
+            ShakePreset::Rumble => {
+                self.shake_noise(0.3, 40, 0.5);
+            }
+            ShakePreset::Landing => {
+                self.shake_sinusoidal(0.5, 6, 1.0, std::f32::consts::FRAC_PI_2);
+            }
+        }
+    }
+
+    /// Quickly zoom in, by `amount`, and ease back out over `duration` frames. The amount is a
+    /// fraction of the view size, so `0.1` will zoom in by 10%, while a negative amount will zoom
+    /// out. Punches are combined with each other, and with shake.
+    pub fn zoom_punch(&mut self, amount: f32, duration: i32) {
+        self.zoom_punches.push(ZoomPunch {
+            amount,
+            length: duration.max(1) as f32,
+            age: 0.0,
+        });
+    }
+
+    /// Returns the combined zoom offset of all active zoom punches, as a fraction of the view size
+    pub fn get_zoom_punch(&mut self) -> f32 {
+        let mut offset = 0.0;
+
+        for punch in &mut self.zoom_punches {
+            let strength = 1.0 - punch.age / punch.length;
+            // Ease out, so the punch is snappy at the start and settles smoothly
+            offset += punch.amount * strength * strength;
+
+            punch.age += 1.0;
+        }
+
+        self.zoom_punches.retain(|punch| punch.age < punch.length);
+
+        (offset * self.shake_scale).clamp(-Self::MAX_ZOOM_PUNCH, Self::MAX_ZOOM_PUNCH)
+    }
+
     pub fn get_shake(&mut self) -> (Vec2, f32) {
         //(x translate, y translate, rotation)
         self.noisegen_position += 0.5;
@@ -184,28 +429,46 @@ impl GameCamera {
         shake_offset.x = (shake_offset.x.abs() + 1.0).log2() * shake_offset.x.signum(); // log2(x+1) is almost linear from 0-1, but then flattens out. Limits the screenshake so if there is lots at the same time, the scene won't fly away
         shake_offset.y = (shake_offset.y.abs() + 1.0).log2() * shake_offset.y.signum();
 
-        (shake_offset, shake_rotation)
+        (
+            shake_offset * self.shake_scale,
+            shake_rotation * self.shake_scale,
+        )
+    }
+
+    /// Returns the target and zoom that should be added to the follow buffer, from the target that
+    /// follows the players, by applying the manual targets, if any
+    fn get_target(&mut self, follow_target: (Vec2, f32), dt: f32) -> (Vec2, f32) {
+        let mut target = follow_target;
+
+        if let Some(manual) = &mut self.manual_smooth {
+            if manual.update(follow_target, dt) {
+                self.manual_smooth = None;
+            } else {
+                target = (manual.position, manual.zoom);
+            }
+        }
+
+        self.manual.unwrap_or(target)
     }
 
     pub fn update(&mut self) {
         {
             let aspect = screen_width() / screen_height();
 
-            let mut middle_point = vec2(0.0, 0.0);
             let mut min = vec2(10000.0, 10000.0);
             let mut max = vec2(-10000.0, -10000.0);
 
-            let player_cnt = self.player_rects.len();
-            for rect in self.player_rects.drain(0..player_cnt) {
+            let mut points = Vec::with_capacity(self.player_rects.len());
+
+            for (rect, weight) in self.player_rects.drain(..) {
                 let camera_pox_middle = rect.point() + rect.size() / 2.0;
-                //let k = if player.controller_id == 1 { 0.8 } else { 0.2 };
-                middle_point += camera_pox_middle; // * k;
+                points.push((camera_pox_middle, weight));
 
                 min = min.min(camera_pox_middle);
                 max = max.max(camera_pox_middle);
             }
 
-            middle_point /= player_cnt as f32;
+            let mut middle_point = get_weighted_middle_point(&points);
 
             let border_x = 150.0;
             let border_y = 200.0;
@@ -215,19 +478,24 @@ impl GameCamera {
                 scale.y = scale.x / aspect;
             }
 
-            let mut zoom = scale.y;
+            let zoom = scale.y;
 
-            // bottom camera bound
-            if scale.y / 2. + middle_point.y > self.bounds.h {
-                middle_point.y = self.bounds.h - scale.y / 2.0;
-            }
+            if self.dead_zone != Vec2::ZERO && !points.is_empty() {
+                let size = self.dead_zone * vec2(zoom * aspect, zoom);
+
+                if let Some(target) = self.dead_zone_target {
+                    middle_point = get_dead_zone_target(target, size, middle_point, min, max);
+                }
 
-            if let Some((override_target, override_zoom)) = self.manual {
-                middle_point = override_target;
-                zoom = override_zoom;
+                self.dead_zone_target = Some(middle_point);
             }
 
-            self.follow_buffer.insert(0, (middle_point, zoom));
+            let view_size = vec2(zoom * aspect, zoom);
+            middle_point = clamp_to_bounds(middle_point, view_size, self.bounds);
+
+            let target = self.get_target((middle_point, zoom), get_frame_time());
+
+            self.follow_buffer.insert(0, target);
             self.follow_buffer.truncate(Self::BUFFER_CAPACITY);
         }
         let mut sum_pos = (0.0f64, 0.0f64);
@@ -241,7 +509,8 @@ impl GameCamera {
             (sum_pos.0 / self.follow_buffer.len() as f64) as f32,
             (sum_pos.1 / self.follow_buffer.len() as f64) as f32,
         );
-        let zoom = (sum_zoom / self.follow_buffer.len() as f64) as f32;
+        let zoom =
+            (sum_zoom / self.follow_buffer.len() as f64) as f32 * (1.0 - self.get_zoom_punch());
 
         let shake = self.get_shake();
         middle_point += shake.0;
@@ -258,6 +527,276 @@ impl GameCamera {
             ..Camera2D::default()
         };
 
+        self.macroquad_camera = macroquad_camera;
+
         scene::set_camera(0, Some(macroquad_camera));
     }
 }
+
+// Returns the weighted average of `points`. If the weights add up to zero, all points are weighted
+// equally, in stead.
+fn get_weighted_middle_point(points: &[(Vec2, f32)]) -> Vec2 {
+    let total_weight: f32 = points.iter().map(|&(_, weight)| weight).sum();
+
+    if total_weight > 0.0 {
+        points
+            .iter()
+            .fold(Vec2::ZERO, |sum, &(point, weight)| sum + point * weight)
+            / total_weight
+    } else {
+        points
+            .iter()
+            .fold(Vec2::ZERO, |sum, &(point, _)| sum + point)
+            / points.len() as f32
+    }
+}
+
+// Returns `middle_point` moved so that a view of the specified size, centered on it, stays within
+// `bounds`. If the view is larger than the bounds, on an axis, it is centered on that axis.
+fn clamp_to_bounds(middle_point: Vec2, view_size: Vec2, bounds: Rect) -> Vec2 {
+    let get_axis = |middle_point: f32, view_size: f32, min: f32, size: f32| {
+        if view_size >= size {
+            min + size / 2.0
+        } else {
+            let half_size = view_size / 2.0;
+            middle_point.clamp(min + half_size, min + size - half_size)
+        }
+    };
+
+    vec2(
+        get_axis(middle_point.x, view_size.x, bounds.x, bounds.w),
+        get_axis(middle_point.y, view_size.y, bounds.y, bounds.h),
+    )
+}
+
+// Returns the target of the camera, with a dead-zone of the specified size, that was centered on
+// `target` in the last update. The target is only moved, on each axis, by as much as is needed
+// to keep the bounding box of the players, from `min` to `max`, within the dead-zone. If the
+// bounding box is larger than the dead-zone, on an axis, `middle_point` is used on that axis.
+fn get_dead_zone_target(
+    target: Vec2,
+    size: Vec2,
+    middle_point: Vec2,
+    min: Vec2,
+    max: Vec2,
+) -> Vec2 {
+    let get_axis = |target: f32, size: f32, middle_point: f32, min: f32, max: f32| {
+        let half_size = size / 2.0;
+
+        if max - min > size {
+            middle_point
+        } else if min < target - half_size {
+            min + half_size
+        } else if max > target + half_size {
+            max - half_size
+        } else {
+            target
+        }
+    };
+
+    vec2(
+        get_axis(target.x, size.x, middle_point.x, min.x, max.x),
+        get_axis(target.y, size.y, middle_point.y, min.y, max.y),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_camera() -> GameCamera {
+        GameCamera {
+            bounds: Rect::new(0.0, 0.0, 1000.0, 1000.0),
+            follow_buffer: vec![],
+            shake: vec![],
+            zoom_punches: vec![],
+            shake_scale: 1.0,
+            manual: None,
+            manual_smooth: None,
+            dead_zone: Vec2::ZERO,
+            dead_zone_target: None,
+            noisegen: NoiseGenerator::new(5),
+            noisegen_position: 5.0,
+            player_rects: Vec::new(),
+            player_weights: HashMap::new(),
+            macroquad_camera: Camera2D::default(),
+        }
+    }
+
+    #[test]
+    fn test_shake_scale() {
+        let mut camera = new_camera();
+        camera.set_shake_scale(0.0);
+
+        camera.shake_noise(1.0, 10, 1.0);
+        camera.shake_sinusoidal(1.0, 10, 1.0, 0.5);
+        camera.shake_rotational(1.0, 10);
+
+        for _ in 0..10 {
+            assert_eq!(camera.get_shake(), (Vec2::ZERO, 0.0));
+        }
+
+        // Finished shakes are still retired, when the scale is zero
+        assert!(camera.shake.is_empty());
+
+        camera.set_shake_scale(0.5);
+        camera.shake_rotational(1.0, 10);
+
+        let (_, rotation) = camera.get_shake();
+        assert_eq!(rotation.abs(), 1.5);
+    }
+
+    #[test]
+    fn test_weighted_middle_point() {
+        let a = vec2(0.0, 0.0);
+        let b = vec2(100.0, 40.0);
+
+        assert_eq!(
+            get_weighted_middle_point(&[(a, 1.0), (b, 1.0)]),
+            vec2(50.0, 20.0)
+        );
+        assert_eq!(
+            get_weighted_middle_point(&[(a, 3.0), (b, 1.0)]),
+            vec2(25.0, 10.0)
+        );
+        assert_eq!(
+            get_weighted_middle_point(&[(a, 0.0), (b, 0.0)]),
+            vec2(50.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn test_clamp_to_bounds() {
+        let bounds = Rect::new(0.0, 0.0, 1000.0, 600.0);
+        let view_size = vec2(400.0, 200.0);
+
+        assert_eq!(
+            clamp_to_bounds(vec2(500.0, 300.0), view_size, bounds),
+            vec2(500.0, 300.0)
+        );
+
+        // Every edge is clamped
+        assert_eq!(
+            clamp_to_bounds(vec2(100.0, 50.0), view_size, bounds),
+            vec2(200.0, 100.0)
+        );
+        assert_eq!(
+            clamp_to_bounds(vec2(950.0, 580.0), view_size, bounds),
+            vec2(800.0, 500.0)
+        );
+
+        // A map that is smaller than the view is centered on that axis
+        assert_eq!(
+            clamp_to_bounds(vec2(100.0, 50.0), vec2(1200.0, 200.0), bounds),
+            vec2(500.0, 100.0)
+        );
+    }
+
+    #[test]
+    fn test_dead_zone() {
+        let target = vec2(100.0, 100.0);
+        let size = vec2(80.0, 40.0);
+
+        // Movement within the dead-zone does not move the target
+        let (min, max) = (vec2(70.0, 90.0), vec2(130.0, 110.0));
+        let middle_point = vec2(100.0, 100.0);
+        assert_eq!(
+            get_dead_zone_target(target, size, middle_point, min, max),
+            target
+        );
+
+        let (min, max) = (vec2(65.0, 85.0), vec2(135.0, 115.0));
+        let middle_point = vec2(100.0, 100.0);
+        assert_eq!(
+            get_dead_zone_target(target, size, middle_point, min, max),
+            target
+        );
+
+        // Leaving it moves the target just enough to keep the players within it
+        let (min, max) = (vec2(90.0, 110.0), vec2(150.0, 125.0));
+        let middle_point = vec2(120.0, 117.5);
+        assert_eq!(
+            get_dead_zone_target(target, size, middle_point, min, max),
+            vec2(110.0, 105.0)
+        );
+
+        // Players that are spread out more than the dead-zone are followed like without one
+        let (min, max) = (vec2(0.0, 100.0), vec2(200.0, 100.0));
+        let middle_point = vec2(100.0, 100.0);
+        assert_eq!(
+            get_dead_zone_target(vec2(120.0, 100.0), size, middle_point, min, max),
+            vec2(100.0, 100.0)
+        );
+    }
+
+    #[test]
+    fn test_smooth_manual() {
+        let mut camera = new_camera();
+        camera.follow_buffer.push((Vec2::ZERO, 400.0));
+
+        let follow_target = (Vec2::ZERO, 400.0);
+        let dt = 1.0 / 60.0;
+
+        camera.set_manual_smooth(vec2(100.0, 0.0), 200.0, 30.0);
+
+        assert_eq!(
+            camera.get_target(follow_target, dt),
+            (vec2(50.0, 0.0), 300.0)
+        );
+
+        // Within the epsilon, it snaps to the target and is identical to a hard manual target
+        let mut target = follow_target;
+        for _ in 0..20 {
+            target = camera.get_target(follow_target, dt);
+        }
+
+        assert_eq!(target, (vec2(100.0, 0.0), 200.0));
+
+        camera.manual = Some((vec2(100.0, 0.0), 200.0));
+        assert_eq!(camera.get_target(follow_target, dt), target);
+        camera.manual = None;
+
+        // A hard manual target overrides the smooth one
+        camera.manual = Some((vec2(-50.0, 0.0), 100.0));
+        assert_eq!(
+            camera.get_target(follow_target, dt),
+            (vec2(-50.0, 0.0), 100.0)
+        );
+        camera.manual = None;
+
+        assert!(camera.manual_smooth.is_some());
+    }
+
+    #[test]
+    fn test_clear_smooth_manual() {
+        let mut camera = new_camera();
+
+        let follow_target = (Vec2::ZERO, 400.0);
+        let dt = 1.0 / 60.0;
+
+        // Without anything in the follow buffer, it starts on the target
+        camera.set_manual_smooth(vec2(100.0, 0.0), 200.0, 30.0);
+        assert_eq!(
+            camera.get_target(follow_target, dt),
+            (vec2(100.0, 0.0), 200.0)
+        );
+
+        // Clearing it eases back to the players, in stead of jumping
+        camera.clear_manual_smooth();
+        assert_eq!(
+            camera.get_target(follow_target, dt),
+            (vec2(50.0, 0.0), 300.0)
+        );
+        assert!(camera.manual_smooth.is_some());
+
+        for _ in 0..20 {
+            camera.get_target(follow_target, dt);
+        }
+
+        // Control is handed back to player-following, which moves the camera freely again
+        assert!(camera.manual_smooth.is_none());
+
+        let follow_target = (vec2(300.0, 200.0), 500.0);
+        assert_eq!(camera.get_target(follow_target, dt), follow_target);
+    }
+}
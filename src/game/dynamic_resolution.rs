@@ -0,0 +1,264 @@
+//! Dynamic resolution keeps the frame rate stable on weak hardware, by rendering the game world to
+//! a render target, at a fraction of the native resolution, and upscaling it to the screen, while
+//! the frame time exceeds the budget of the target frame rate. The HUD is drawn afterwards, at the
+//! native resolution, so it stays sharp.
+//!
+//! The resolution scale is lowered in steps, as long as the game is over budget, and raised again,
+//! one step at a time, once the game has been within budget for a while. This is disabled by
+//! default, and is enabled by the `dynamic-resolution` option in the graphics config.
+
+use macroquad::experimental::collections::storage;
+use macroquad::prelude::*;
+
+use core::GraphicsConfig;
+
+use crate::Config;
+
+/// The amount that the resolution scale is lowered or raised by, in each adjustment
+const SCALE_STEP: f32 = 0.1;
+
+/// The game is considered over budget if the average frame time exceeds the budget by this factor
+const OVER_BUDGET_FACTOR: f32 = 1.1;
+
+/// The game is considered within budget if the average frame time is below the budget multiplied
+/// by this factor. This is a bit above `1.0`, as the frame time will never drop far below the
+/// budget with vsync enabled.
+const WITHIN_BUDGET_FACTOR: f32 = 1.02;
+
+/// The weight of the latest frame in the average frame time
+const FRAME_TIME_SMOOTHING: f32 = 0.1;
+
+/// The time, in seconds, after an adjustment, before the scale is lowered again, so that the
+/// average frame time has time to respond to the previous adjustment
+const ADJUSTMENT_COOLDOWN: f32 = 0.5;
+
+/// The time, in seconds, that the game must be within budget, before the scale is raised
+const RECOVERY_DELAY: f32 = 2.0;
+
+pub struct DynamicResolution {
+    is_enabled: bool,
+    /// The frame time budget, in seconds
+    frame_time_budget: f32,
+    min_scale: f32,
+    scale: f32,
+    average_frame_time: f32,
+    cooldown_timer: f32,
+    recovery_timer: f32,
+    render_target: Option<RenderTarget>,
+}
+
+impl DynamicResolution {
+    pub fn new(config: &GraphicsConfig) -> Self {
+        let frame_time_budget = 1.0 / config.target_fps.max(1.0);
+
+        DynamicResolution {
+            is_enabled: config.is_dynamic_resolution_enabled,
+            frame_time_budget,
+            min_scale: config.min_resolution_scale.clamp(SCALE_STEP, 1.0),
+            scale: 1.0,
+            average_frame_time: frame_time_budget,
+            cooldown_timer: 0.0,
+            recovery_timer: 0.0,
+            render_target: None,
+        }
+    }
+
+    /// Returns the current resolution scale, as a fraction of the native resolution
+    pub fn get_scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// This should be called once every frame, with the frame time, to adjust the scale
+    pub fn update(&mut self, frame_time: f32) {
+        if !self.is_enabled {
+            return;
+        }
+
+        self.average_frame_time += (frame_time - self.average_frame_time) * FRAME_TIME_SMOOTHING;
+
+        if self.cooldown_timer > 0.0 {
+            self.cooldown_timer -= frame_time;
+            return;
+        }
+
+        if self.average_frame_time > self.frame_time_budget * OVER_BUDGET_FACTOR {
+            self.recovery_timer = 0.0;
+
+            if self.scale > self.min_scale {
+                self.scale = (self.scale - SCALE_STEP).max(self.min_scale);
+                self.cooldown_timer = ADJUSTMENT_COOLDOWN;
+            }
+        } else if self.average_frame_time < self.frame_time_budget * WITHIN_BUDGET_FACTOR {
+            self.recovery_timer += frame_time;
+
+            if self.recovery_timer >= RECOVERY_DELAY && self.scale < 1.0 {
+                self.scale = (self.scale + SCALE_STEP).min(1.0);
+                self.recovery_timer = 0.0;
+                self.cooldown_timer = ADJUSTMENT_COOLDOWN;
+            }
+        } else {
+            self.recovery_timer = 0.0;
+        }
+    }
+
+    // Returns a render target at the current scale of the screen, creating a new one if the scale,
+    // or the size of the screen, has changed
+    fn get_render_target(&mut self) -> RenderTarget {
+        let width = ((screen_width() * self.scale).round() as u32).max(1);
+        let height = ((screen_height() * self.scale).round() as u32).max(1);
+
+        if let Some(render_target) = self.render_target {
+            let texture = render_target.texture;
+            if texture.width() as u32 == width && texture.height() as u32 == height {
+                return render_target;
+            }
+
+            render_target.delete();
+        }
+
+        let render_target = render_target(width, height);
+        render_target.texture.set_filter(FilterMode::Nearest);
+
+        self.render_target = Some(render_target);
+
+        render_target
+    }
+}
+
+/// This should be called when a game is created
+pub fn init_dynamic_resolution() {
+    let dynamic_resolution = {
+        let config = storage::get::<Config>();
+        DynamicResolution::new(&config.graphics)
+    };
+
+    storage::store(dynamic_resolution);
+}
+
+/// This should be called before the game world is drawn, with the camera of the game. If the world
+/// should be drawn at a lower resolution, the camera is set up to draw to a render target, cleared
+/// with `clear_color`, and `true` is returned, in which case `end_dynamic_resolution` must be
+/// called, once the world has been drawn.
+pub fn begin_dynamic_resolution(camera: &Camera2D, clear_color: Color) -> bool {
+    let mut dynamic_resolution = storage::get_mut::<DynamicResolution>();
+
+    dynamic_resolution.update(get_frame_time());
+
+    if dynamic_resolution.get_scale() >= 1.0 {
+        return false;
+    }
+
+    let render_target = dynamic_resolution.get_render_target();
+
+    set_camera(&get_render_target_camera(camera, Some(render_target)));
+
+    clear_background(clear_color);
+
+    true
+}
+
+/// Returns a copy of `camera` that draws to `render_target`. It is flipped on the y-axis, as the
+/// texture of a render target is upside down, relative to the screen, so that the world is the
+/// right way up when the texture is drawn to the screen.
+fn get_render_target_camera(camera: &Camera2D, render_target: Option<RenderTarget>) -> Camera2D {
+    Camera2D {
+        zoom: vec2(camera.zoom.x, -camera.zoom.y),
+        offset: vec2(camera.offset.x, -camera.offset.y),
+        render_target,
+        ..*camera
+    }
+}
+
+/// Upscale the world, drawn since `begin_dynamic_resolution`, to the screen, and set `camera`, so
+/// that the HUD can be drawn on top of it, at the native resolution.
+pub fn end_dynamic_resolution(camera: &Camera2D) {
+    let render_target = match storage::get::<DynamicResolution>().render_target {
+        Some(render_target) => render_target,
+        None => return,
+    };
+
+    set_default_camera();
+
+    draw_texture_ex(
+        render_target.texture,
+        0.0,
+        0.0,
+        WHITE,
+        DrawTextureParams {
+            dest_size: Some(vec2(screen_width(), screen_height())),
+            ..Default::default()
+        },
+    );
+
+    set_camera(camera);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dynamic_resolution_scale() {
+        let config = GraphicsConfig {
+            is_dynamic_resolution_enabled: true,
+            target_fps: 50.0,
+            min_resolution_scale: 0.75,
+        };
+
+        let mut dynamic_resolution = DynamicResolution::new(&config);
+
+        // Over budget, so the scale is lowered, down to the minimum
+        for _ in 0..100 {
+            dynamic_resolution.update(0.05);
+        }
+
+        assert_eq!(dynamic_resolution.get_scale(), 0.75);
+
+        // Within budget, so the scale recovers, one step at a time
+        for _ in 0..200 {
+            dynamic_resolution.update(0.02);
+        }
+
+        assert!((dynamic_resolution.get_scale() - 0.85).abs() < 0.001);
+
+        for _ in 0..1000 {
+            dynamic_resolution.update(0.02);
+        }
+
+        assert_eq!(dynamic_resolution.get_scale(), 1.0);
+
+        // Nothing happens when disabled
+        let mut dynamic_resolution = DynamicResolution::new(&GraphicsConfig::default());
+
+        for _ in 0..100 {
+            dynamic_resolution.update(0.05);
+        }
+
+        assert_eq!(dynamic_resolution.get_scale(), 1.0);
+    }
+
+    #[test]
+    fn test_render_target_camera() {
+        let camera = Camera2D {
+            target: vec2(400.0, 300.0),
+            zoom: vec2(1.0 / 1.5, -1.0) / 400.0 * 2.0,
+            rotation: 12.0,
+            offset: vec2(0.1, 0.2),
+            ..Default::default()
+        };
+
+        let render_target_camera = get_render_target_camera(&camera, None);
+
+        // Everything is mirrored vertically in the render target, so that it is the right way up
+        // when the texture is drawn to the screen
+        for position in [vec2(0.0, 0.0), vec2(400.0, 300.0), vec2(520.0, 180.0)] {
+            let position = vec3(position.x, position.y, 0.0);
+
+            let on_screen = camera.matrix().transform_point3(position);
+            let in_texture = render_target_camera.matrix().transform_point3(position);
+
+            assert!((on_screen.x - in_texture.x).abs() < 0.0001);
+            assert!((on_screen.y + in_texture.y).abs() < 0.0001);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Typed gameplay events. Systems that produce events, like player deaths or item pickups,
+//! dispatch them with `dispatch_game_event`, while consumers, like the HUD or audio, subscribe to
+//! the event bus and drain their own queue whenever they run.
+//! As each subscriber has its own queue, consumers that run in the fixed update loop will see
+//! every event exactly once, regardless of how many fixed updates are run each frame.
+
+use macroquad::experimental::collections::storage;
+use macroquad::prelude::*;
+
+use hecs::{Entity, World};
+
+use crate::game::play_sound_effect;
+
+pub const PLAYER_DIED_SOUND_ID: &str = "death";
+pub const ITEM_PICKED_UP_SOUND_ID: &str = "pickup";
+
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+    PlayerDied {
+        player: Entity,
+        killed_by: Option<Entity>,
+    },
+    ItemPickedUp {
+        player: Entity,
+        item: Entity,
+    },
+    /// A passive effect was triggered by a player event
+    EffectTriggered {
+        player: Entity,
+        item: Option<Entity>,
+        effect: String,
+    },
+    /// A triggered effect, like a mine or a grenade, went off. This is also dispatched when an
+    /// effect expires, if it is set to fire on expiry, but not if it is removed silently.
+    TriggeredEffectDetonated {
+        owner: Entity,
+        triggered_by: Option<Entity>,
+        /// The position of the effect at the time it went off
+        position: Vec2,
+    },
+}
+
+/// This is a handle to a subscribers queue on the event bus
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct GameEventSubscriber(usize);
+
+/// This holds the event queues of all subscribers. A new bus is created for every game, so any
+/// subscriptions should be made after the game has been created.
+#[derive(Debug, Default)]
+pub struct GameEventBus {
+    queues: Vec<Option<Vec<GameEvent>>>,
+}
+
+impl GameEventBus {
+    pub fn new() -> Self {
+        GameEventBus { queues: Vec::new() }
+    }
+
+    pub fn subscribe(&mut self) -> GameEventSubscriber {
+        if let Some(i) = self.queues.iter().position(|queue| queue.is_none()) {
+            self.queues[i] = Some(Vec::new());
+            return GameEventSubscriber(i);
+        }
+
+        self.queues.push(Some(Vec::new()));
+        GameEventSubscriber(self.queues.len() - 1)
+    }
+
+    #[allow(dead_code)]
+    pub fn unsubscribe(&mut self, subscriber: GameEventSubscriber) {
+        if let Some(queue) = self.queues.get_mut(subscriber.0) {
+            *queue = None;
+        }
+    }
+
+    pub fn dispatch(&mut self, event: GameEvent) {
+        for queue in self.queues.iter_mut().flatten() {
+            queue.push(event.clone());
+        }
+    }
+
+    /// Returns all the events that have been dispatched since the last time the queue of the
+    /// specified subscriber was drained, in the order they were dispatched
+    pub fn drain(&mut self, subscriber: GameEventSubscriber) -> Vec<GameEvent> {
+        self.queues
+            .get_mut(subscriber.0)
+            .and_then(|queue| queue.as_mut())
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+}
+
+/// Dispatch an event to all subscribers. This does nothing if no game is running.
+pub fn dispatch_game_event(event: GameEvent) {
+    if let Some(mut bus) = storage::try_get_mut::<GameEventBus>() {
+        bus.dispatch(event);
+    }
+}
+
+pub fn subscribe_game_events() -> GameEventSubscriber {
+    storage::get_mut::<GameEventBus>().subscribe()
+}
+
+pub fn drain_game_events(subscriber: GameEventSubscriber) -> Vec<GameEvent> {
+    storage::get_mut::<GameEventBus>().drain(subscriber)
+}
+
+struct GameEventSounds(GameEventSubscriber);
+
+/// Subscribe the sound effects to the event bus. This should be called after the bus has been
+/// created.
+pub fn init_game_event_sounds() {
+    storage::store(GameEventSounds(subscribe_game_events()));
+}
+
+pub fn update_game_event_sounds(_world: &mut World) {
+    let subscriber = storage::get::<GameEventSounds>().0;
+
+    for event in drain_game_events(subscriber) {
+        match event {
+            GameEvent::PlayerDied { .. } => play_sound_effect(PLAYER_DIED_SOUND_ID, 1.0),
+            GameEvent::ItemPickedUp { .. } => play_sound_effect(ITEM_PICKED_UP_SOUND_ID, 1.0),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscribers_drain_independently() {
+        let mut world = World::new();
+        let player = world.spawn(());
+        let item = world.spawn(());
+
+        let mut bus = GameEventBus::new();
+        let a = bus.subscribe();
+        let b = bus.subscribe();
+
+        bus.dispatch(GameEvent::ItemPickedUp { player, item });
+
+        assert_eq!(bus.drain(a).len(), 1);
+        assert!(bus.drain(a).is_empty());
+
+        bus.dispatch(GameEvent::PlayerDied {
+            player,
+            killed_by: None,
+        });
+
+        assert_eq!(bus.drain(b).len(), 2);
+
+        bus.unsubscribe(a);
+        assert_eq!(bus.subscribe(), a);
+        assert!(bus.drain(a).is_empty());
+    }
+}
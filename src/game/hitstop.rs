@@ -0,0 +1,111 @@
+//! Hitstop briefly slows down the simulation on impactful events, like a player being hit by
+//! another player. This is done by scaling the time that is added to the fixed update accumulator,
+//! so every tick still uses the same fixed delta, but fewer ticks are run per frame.
+//!
+//! As the number of ticks run per frame is not synchronized between peers, hitstop is only
+//! applied in local games, and it is disabled if the `reduce-effects` accessibility option is set.
+
+use macroquad::experimental::collections::storage;
+use macroquad::prelude::*;
+
+use hecs::World;
+
+use crate::game::GameMode;
+use crate::Config;
+
+pub struct Hitstop {
+    is_enabled: bool,
+    duration: f32,
+    time_scale: f32,
+    timer: f32,
+}
+
+impl Hitstop {
+    pub fn new(is_enabled: bool, config: &Config) -> Self {
+        Hitstop {
+            is_enabled: is_enabled && config.match_settings.hitstop_duration > 0.0,
+            duration: config.match_settings.hitstop_duration,
+            time_scale: config.match_settings.hitstop_time_scale.clamp(0.0, 1.0),
+            timer: 0.0,
+        }
+    }
+
+    /// Start a hitstop, or extend the current one. The duration is multiplied by `factor`, so that
+    /// more impactful events can cause a longer hitstop.
+    pub fn trigger(&mut self, factor: f32) {
+        if self.is_enabled {
+            self.timer = self.timer.max(self.duration * factor);
+        }
+    }
+
+    pub fn get_time_scale(&self) -> f32 {
+        if self.timer > 0.0 {
+            self.time_scale
+        } else {
+            1.0
+        }
+    }
+
+    /// Advance the hitstop timer by `dt`, which should be in real time, not scaled time
+    pub fn update(&mut self, dt: f32) {
+        self.timer = (self.timer - dt).max(0.0);
+    }
+}
+
+/// This should be called when a game is created
+pub fn init_hitstop(mode: &GameMode) {
+    let hitstop = {
+        let config = storage::get::<Config>();
+        let is_enabled = *mode == GameMode::Local && !config.accessibility.reduce_effects;
+
+        Hitstop::new(is_enabled, &config)
+    };
+
+    storage::store(hitstop);
+}
+
+/// Trigger a hitstop, with its duration multiplied by `factor`. This does nothing if no game is
+/// running, or if hitstop is disabled.
+pub fn trigger_hitstop(factor: f32) {
+    if let Some(mut hitstop) = storage::try_get_mut::<Hitstop>() {
+        hitstop.trigger(factor);
+    }
+}
+
+/// Returns the speed that the simulation should run at, where `1.0` is normal speed
+pub fn get_time_scale() -> f32 {
+    storage::try_get::<Hitstop>()
+        .map(|hitstop| hitstop.get_time_scale())
+        .unwrap_or(1.0)
+}
+
+pub fn update_hitstop(_world: &mut World) {
+    let mut hitstop = storage::get_mut::<Hitstop>();
+    hitstop.update(get_frame_time());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hitstop_time_scale() {
+        let config = Config::default();
+
+        let mut hitstop = Hitstop::new(true, &config);
+        assert_eq!(hitstop.get_time_scale(), 1.0);
+
+        hitstop.trigger(1.0);
+        assert_eq!(
+            hitstop.get_time_scale(),
+            config.match_settings.hitstop_time_scale
+        );
+
+        hitstop.update(config.match_settings.hitstop_duration);
+        assert_eq!(hitstop.get_time_scale(), 1.0);
+
+        let mut hitstop = Hitstop::new(false, &config);
+        hitstop.trigger(1.0);
+        assert_eq!(hitstop.get_time_scale(), 1.0);
+    }
+}
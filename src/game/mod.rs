@@ -1,8 +1,16 @@
 mod camera;
+pub mod dynamic_resolution;
+pub mod events;
+pub mod hitstop;
 mod music;
+pub mod screen_flash;
 pub mod sound;
+pub mod tick;
 
-pub use camera::GameCamera;
+pub use camera::{GameCamera, ShakePreset};
+pub use events::{dispatch_game_event, GameEvent};
+pub use hitstop::trigger_hitstop;
+pub use screen_flash::{trigger_screen_flash, ScreenFlashMetadata};
 
 use fishsticks::{Button, GamepadContext};
 
@@ -14,38 +22,56 @@ use macroquad::ui::root_ui;
 use hecs::{Entity, World};
 
 use core::input::is_gamepad_btn_pressed;
-use core::Result;
+use core::{Result, Transform};
 
 use crate::debug;
 use crate::ecs::Scheduler;
 use crate::gui::{self, GAME_MENU_RESULT_MAIN_MENU, GAME_MENU_RESULT_QUIT};
 use crate::physics::{debug_draw_physics_bodies, fixed_update_physics_bodies};
 use crate::player::{
-    draw_weapons_hud, spawn_player, update_player_animations, update_player_camera_box,
+    draw_offscreen_indicators, draw_weapons_hud, fixed_update_frozen_players, init_player_gibs,
+    spawn_player, spawn_player_gibs, update_gibs, update_offscreen_indicators,
+    update_player_aim_assist, update_player_animations, update_player_camera_box,
     update_player_controllers, update_player_events, update_player_inventory,
-    update_player_passive_effects, update_player_states, PlayerParams,
+    update_player_passive_effects, update_player_states, Player, PlayerInventory, PlayerParams,
 };
 use crate::{
     create_collision_world, debug_draw_drawables, debug_draw_rigid_bodies, draw_drawables,
-    exit_to_main_menu, fixed_update_rigid_bodies, quit_to_desktop, update_animated_sprites, Map,
-    MapLayerKind, MapObjectKind, Resources,
+    exit_to_main_menu, fixed_update_rigid_bodies, fixed_update_trails, quit_to_desktop,
+    update_animated_sprites, Config, Map, MapLayerKind, MapObjectKind, PhysicsBody, Resources,
 };
 
 use crate::effects::active::debug_draw_active_effects;
+use crate::effects::active::melee::{debug_draw_melee_swings, fixed_update_melee_swings};
 use crate::effects::active::projectiles::fixed_update_projectiles;
-use crate::effects::active::triggered::{fixed_update_triggered_effects, update_triggered_effects};
-use crate::items::{spawn_item, update_respawning_items};
+use crate::effects::active::triggered::{
+    debug_draw_triggered_effects, fixed_update_triggered_effects, update_triggered_effects,
+};
+use crate::effects::passive::fixed_update_damage_over_time;
+use crate::items::{
+    draw_item_glows, fixed_update_thrown_items, spawn_item, update_item_bobs,
+    update_item_despawn_timers, update_respawning_items,
+};
 use crate::map::{
     debug_draw_fish_schools, fixed_update_sproingers, spawn_crab, spawn_decoration,
     spawn_fish_school, spawn_sproinger, update_crabs, update_fish_schools, update_map_kill_zone,
 };
 use crate::network::{
-    fixed_update_network_client, fixed_update_network_host, update_network_client,
-    update_network_host,
+    fixed_update_network_client, fixed_update_network_host, init_network_host,
+    update_network_client, update_network_host,
 };
 use crate::particles::{draw_particles, update_particle_emitters};
+use crate::stats::{draw_achievement_toasts, init_game_stats, update_stats};
+use dynamic_resolution::{
+    begin_dynamic_resolution, end_dynamic_resolution, init_dynamic_resolution,
+};
+use events::{init_game_event_sounds, update_game_event_sounds, GameEventBus};
+use hitstop::{get_time_scale, init_hitstop, update_hitstop};
 pub use music::{start_music, stop_music};
+use screen_flash::{draw_screen_flash, init_screen_flash, update_screen_flash};
 pub use sound::play_sound_effect;
+use tick::step_fixed_accumulator;
+pub use tick::{get_fixed_delta, get_tick_rate, get_tick_scale, set_tick_rate};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum GameMode {
@@ -60,7 +86,10 @@ pub struct Game {
     players: Vec<Entity>,
     updates: Scheduler,
     fixed_updates: Scheduler,
+    fixed_accumulator: f32,
     draws: Scheduler,
+    /// These are drawn after `draws`, at the native resolution, if dynamic resolution is active
+    hud_draws: Scheduler,
     #[cfg(debug_assertions)]
     debug_draws: Scheduler,
 }
@@ -69,12 +98,35 @@ impl Game {
     pub fn new(mode: GameMode, map: Map, player_params: &[PlayerParams]) -> Result<Game> {
         let mut world = World::default();
 
+        // Network clients use the tick rate of the host, which is applied when joining
+        if mode != GameMode::NetworkClient {
+            let config = storage::get::<Config>();
+            set_tick_rate(config.network.tick_rate)?;
+        }
+
+        if mode == GameMode::NetworkHost {
+            init_network_host(&map)?;
+        }
+
         {
             let camera = GameCamera::new(map.get_size());
             storage::store(camera);
 
             let collision_world = create_collision_world(&map);
             storage::store(collision_world);
+
+            storage::store(GameEventBus::new());
+            init_game_event_sounds();
+            init_game_stats();
+            init_hitstop(&mode);
+            init_screen_flash();
+            init_player_gibs();
+            init_dynamic_resolution();
+
+            #[cfg(feature = "desync-detection")]
+            if mode != GameMode::Local {
+                crate::network::state_hash::init_desync_detector();
+            }
         }
 
         spawn_map_objects(&mut world, &map).unwrap();
@@ -83,13 +135,16 @@ impl Game {
             .iter()
             .cloned()
             .map(|params| {
-                let position = map.get_random_spawn_point();
+                // Players are spawned in the order of the spawn points set in the editor, so that
+                // they start out spread over the map
+                let position = map.get_ordered_spawn_point(params.index as usize);
                 spawn_player(
                     &mut world,
                     params.index,
                     position,
                     params.controller,
                     params.character,
+                    params.team,
                 )
             })
             .collect();
@@ -116,11 +171,13 @@ impl Game {
 
         updates_builder
             .add_system(update_player_controllers)
+            .add_system(update_player_aim_assist)
             .add_system(update_player_camera_box);
 
         if matches!(mode, GameMode::Local | GameMode::NetworkHost) {
             updates_builder
                 .add_system(update_respawning_items)
+                .add_system(update_item_despawn_timers)
                 .add_system(update_map_kill_zone)
                 .add_system(update_player_states)
                 .add_system(update_player_inventory)
@@ -131,33 +188,56 @@ impl Game {
                 .add_system(update_crabs);
 
             fixed_updates_builder
+                .add_system(fixed_update_frozen_players)
                 .add_system(fixed_update_physics_bodies)
                 .add_system(fixed_update_rigid_bodies)
                 .add_system(fixed_update_projectiles)
+                .add_system(fixed_update_melee_swings)
                 .add_system(fixed_update_triggered_effects)
+                .add_system(fixed_update_thrown_items)
+                .add_system(fixed_update_damage_over_time)
                 .add_system(fixed_update_sproingers);
         }
 
         let updates = updates_builder
             .with_system(update_player_animations)
             .with_system(update_animated_sprites)
+            .with_system(update_item_bobs)
             .with_system(update_particle_emitters)
+            .with_system(update_offscreen_indicators)
+            .with_system(update_game_event_sounds)
+            .with_system(update_stats)
+            .with_system(update_hitstop)
+            .with_system(update_screen_flash)
+            .with_system(spawn_player_gibs)
+            .with_system(update_gibs)
             .build();
 
-        let fixed_updates = fixed_updates_builder.build();
+        let fixed_updates = fixed_updates_builder
+            .with_system(fixed_update_trails)
+            .build();
 
         let draws = Scheduler::builder()
+            .with_thread_local(draw_item_glows)
             .with_thread_local(draw_drawables)
-            .with_thread_local(draw_weapons_hud)
             .with_thread_local(draw_particles)
             .build();
 
+        let hud_draws = Scheduler::builder()
+            .with_thread_local(draw_weapons_hud)
+            .with_thread_local(draw_offscreen_indicators)
+            .with_thread_local(draw_screen_flash)
+            .with_thread_local(draw_achievement_toasts)
+            .build();
+
         #[cfg(debug_assertions)]
         let debug_draws = Scheduler::builder()
             .with_thread_local(debug_draw_drawables)
             .with_thread_local(debug_draw_physics_bodies)
             .with_thread_local(debug_draw_rigid_bodies)
             .with_thread_local(debug_draw_active_effects)
+            .with_thread_local(debug_draw_triggered_effects)
+            .with_thread_local(debug_draw_melee_swings)
             .with_thread_local(debug_draw_fish_schools)
             .build();
 
@@ -166,7 +246,9 @@ impl Game {
             players,
             updates,
             fixed_updates,
+            fixed_accumulator: 0.0,
             draws,
+            hud_draws,
             #[cfg(debug_assertions)]
             debug_draws,
         };
@@ -177,6 +259,19 @@ impl Game {
     fn on_update(&mut self) {
         self.updates.execute(&mut self.world);
 
+        // We step the fixed updates ourselves, as the scene graph has a hard coded rate of 60
+        // ticks per second, while we need this to be configurable.
+        // The frame time is scaled by the hitstop time scale, so that the fixed delta is the same
+        // for every tick, while fewer ticks are run during hitstop.
+        {
+            let frame_time = get_frame_time() * get_time_scale();
+            let tick_cnt = step_fixed_accumulator(&mut self.fixed_accumulator, frame_time);
+
+            for _ in 0..tick_cnt {
+                self.on_fixed_update();
+            }
+        }
+
         #[cfg(debug_assertions)]
         if is_key_pressed(macroquad::prelude::KeyCode::U) {
             crate::debug::toggle_debug_draw();
@@ -197,8 +292,16 @@ impl Game {
     }
 
     fn on_draw(&mut self) {
-        let mut camera = storage::get_mut::<GameCamera>();
-        camera.update();
+        let camera = {
+            let mut game_camera = storage::get_mut::<GameCamera>();
+            game_camera.update();
+            game_camera.get_macroquad_camera()
+        };
+
+        let is_dynamic_resolution_active = {
+            let background_color = storage::get::<Map>().background_color;
+            begin_dynamic_resolution(&camera, background_color)
+        };
 
         {
             let map = storage::get::<Map>();
@@ -207,6 +310,12 @@ impl Game {
 
         self.draws.execute(&mut self.world);
 
+        if is_dynamic_resolution_active {
+            end_dynamic_resolution(&camera);
+        }
+
+        self.hud_draws.execute(&mut self.world);
+
         #[cfg(debug_assertions)]
         if debug::is_debug_draw_enabled() {
             self.debug_draws.execute(&mut self.world);
@@ -229,15 +338,59 @@ impl Node for Game {
         node.on_update();
     }
 
-    fn fixed_update(mut node: RefMut<Self>) {
-        node.on_fixed_update();
-    }
-
     fn draw(mut node: RefMut<Self>) {
         node.on_draw();
     }
 }
 
+/// Rebuild the state of a running game around a new map, like when a network client receives the
+/// map of the host. Everything but the players is despawned, the collision world and the camera
+/// are recreated for the new map, and the players are moved to its spawn points, before the
+/// objects of the new map are spawned.
+pub fn reset_game_map(world: &mut World, map: Map) -> Result<()> {
+    let to_despawn = world
+        .query::<()>()
+        .without::<Player>()
+        .iter()
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+
+    for e in to_despawn {
+        world.despawn(e).unwrap();
+    }
+
+    let mut collision_world = create_collision_world(&map);
+
+    for (_, (player, transform, body, inventory)) in world.query_mut::<(
+        &Player,
+        &mut Transform,
+        &mut PhysicsBody,
+        &mut PlayerInventory,
+    )>() {
+        let position = map.get_ordered_spawn_point(player.index as usize);
+
+        // The actors of the old collision world are not valid in the new one
+        body.actor = collision_world.add_actor(position, body.size.x as i32, body.size.y as i32);
+        body.velocity = Vec2::ZERO;
+        transform.position = position;
+
+        // The items in the inventory were despawned with everything else
+        inventory.weapon = None;
+        inventory.items.clear();
+        inventory.hat = None;
+        inventory.pending_weapon_replacement = None;
+    }
+
+    storage::store(collision_world);
+    storage::store(GameCamera::new(map.get_size()));
+
+    spawn_map_objects(world, &map)?;
+
+    storage::store(map);
+
+    Ok(())
+}
+
 pub fn spawn_map_objects(world: &mut World, map: &Map) -> Result<Vec<Entity>> {
     let mut objects = Vec::new();
 
@@ -0,0 +1,229 @@
+//! Screen flashes briefly tint the entire screen, in screen space, on top of everything else, like
+//! a white flash for a flashbang, or a red flash for a big hit. Flashes are triggered by active
+//! effects, through `trigger_screen_flash`, and are managed and drawn centrally, so effects do not
+//! have to draw anything themselves.
+//!
+//! If the `reduce-flashing` accessibility option is set, the intensity of flashes is clamped, and
+//! if the `reduce-effects` option is set, flashes are disabled entirely.
+
+use macroquad::experimental::collections::storage;
+use macroquad::prelude::*;
+
+use hecs::World;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::ease::{Ease, EaseFunction};
+use crate::Config;
+
+/// The maximum intensity of flashes, when the `reduce-flashing` accessibility option is set
+pub const REDUCED_FLASH_MAX_INTENSITY: f32 = 0.2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScreenFlashMetadata {
+    #[serde(
+        with = "core::json::ColorDef",
+        default = "ScreenFlashMetadata::default_color"
+    )]
+    pub color: Color,
+    /// The opacity of the flash, at its peak, from `0.0` to `1.0`. This is multiplied with the
+    /// alpha of `color`.
+    #[serde(default = "ScreenFlashMetadata::default_intensity")]
+    pub intensity: f32,
+    /// The time, in seconds, that it takes for the flash to fade out
+    #[serde(default = "ScreenFlashMetadata::default_duration")]
+    pub duration: f32,
+}
+
+impl ScreenFlashMetadata {
+    pub fn default_color() -> Color {
+        WHITE
+    }
+
+    pub fn default_intensity() -> f32 {
+        1.0
+    }
+
+    pub fn default_duration() -> f32 {
+        0.25
+    }
+}
+
+impl Default for ScreenFlashMetadata {
+    fn default() -> Self {
+        ScreenFlashMetadata {
+            color: Self::default_color(),
+            intensity: Self::default_intensity(),
+            duration: Self::default_duration(),
+        }
+    }
+}
+
+struct Flash {
+    color: Color,
+    intensity: f32,
+    duration: f32,
+    timer: f32,
+}
+
+impl Flash {
+    /// Returns the opacity of the flash, which eases out from its intensity to zero
+    fn get_opacity(&self) -> f32 {
+        let ease = Ease {
+            ease_in: false,
+            ease_out: true,
+            function: EaseFunction::Quadratic,
+            progress: (self.timer / self.duration).clamp(0.0, 1.0),
+        };
+
+        self.intensity * (1.0 - ease.output())
+    }
+}
+
+pub struct ScreenFlash {
+    is_enabled: bool,
+    max_intensity: f32,
+    flashes: Vec<Flash>,
+}
+
+impl ScreenFlash {
+    pub fn new(config: &Config) -> Self {
+        let max_intensity = if config.accessibility.reduce_flashing {
+            REDUCED_FLASH_MAX_INTENSITY
+        } else {
+            1.0
+        };
+
+        ScreenFlash {
+            is_enabled: !config.accessibility.reduce_effects,
+            max_intensity,
+            flashes: Vec::new(),
+        }
+    }
+
+    pub fn trigger(&mut self, meta: &ScreenFlashMetadata) {
+        if self.is_enabled && meta.duration > 0.0 {
+            self.flashes.push(Flash {
+                color: meta.color,
+                intensity: meta.intensity.clamp(0.0, self.max_intensity),
+                duration: meta.duration,
+                timer: 0.0,
+            });
+        }
+    }
+
+    /// Returns the combined color of all active flashes, or `None` if there are no flashes. The
+    /// combined opacity will never exceed the maximum intensity.
+    pub fn get_color(&self) -> Option<Color> {
+        if self.flashes.is_empty() {
+            return None;
+        }
+
+        let mut res = Color::new(0.0, 0.0, 0.0, 0.0);
+
+        for flash in &self.flashes {
+            let a = flash.color.a * flash.get_opacity();
+
+            // Blend each flash over the previous ones
+            let total_a = a + res.a * (1.0 - a);
+            if total_a > 0.0 {
+                res.r = (flash.color.r * a + res.r * res.a * (1.0 - a)) / total_a;
+                res.g = (flash.color.g * a + res.g * res.a * (1.0 - a)) / total_a;
+                res.b = (flash.color.b * a + res.b * res.a * (1.0 - a)) / total_a;
+            }
+
+            res.a = total_a;
+        }
+
+        res.a = res.a.min(self.max_intensity);
+
+        Some(res)
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for flash in &mut self.flashes {
+            flash.timer += dt;
+        }
+
+        self.flashes.retain(|flash| flash.timer < flash.duration);
+    }
+}
+
+/// This should be called when a game is created
+pub fn init_screen_flash() {
+    let screen_flash = {
+        let config = storage::get::<Config>();
+        ScreenFlash::new(&config)
+    };
+
+    storage::store(screen_flash);
+}
+
+/// Trigger a screen flash. This does nothing if no game is running, or if flashes are disabled.
+pub fn trigger_screen_flash(meta: &ScreenFlashMetadata) {
+    if let Some(mut screen_flash) = storage::try_get_mut::<ScreenFlash>() {
+        screen_flash.trigger(meta);
+    }
+}
+
+pub fn update_screen_flash(_world: &mut World) {
+    let mut screen_flash = storage::get_mut::<ScreenFlash>();
+    screen_flash.update(get_frame_time());
+}
+
+pub fn draw_screen_flash(_world: &mut World) {
+    let color = match storage::get::<ScreenFlash>().get_color() {
+        Some(color) => color,
+        None => return,
+    };
+
+    push_camera_state();
+    set_default_camera();
+
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), color);
+
+    pop_camera_state();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screen_flash_fades_out() {
+        let mut config = Config::default();
+
+        let meta = ScreenFlashMetadata {
+            color: RED,
+            intensity: 0.8,
+            duration: 1.0,
+        };
+
+        let mut screen_flash = ScreenFlash::new(&config);
+        assert!(screen_flash.get_color().is_none());
+
+        screen_flash.trigger(&meta);
+        assert_eq!(screen_flash.get_color().unwrap().a, 0.8);
+
+        screen_flash.update(0.5);
+        let a = screen_flash.get_color().unwrap().a;
+        assert!(a > 0.0 && a < 0.4);
+
+        screen_flash.update(0.5);
+        assert!(screen_flash.get_color().is_none());
+
+        config.accessibility.reduce_flashing = true;
+        let mut screen_flash = ScreenFlash::new(&config);
+        screen_flash.trigger(&meta);
+        assert_eq!(
+            screen_flash.get_color().unwrap().a,
+            REDUCED_FLASH_MAX_INTENSITY
+        );
+
+        config.accessibility.reduce_effects = true;
+        let mut screen_flash = ScreenFlash::new(&config);
+        screen_flash.trigger(&meta);
+        assert!(screen_flash.get_color().is_none());
+    }
+}
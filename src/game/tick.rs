@@ -0,0 +1,95 @@
+//! The simulation tick rate. This is set from the config when a local game or a network host is
+//! started, while network clients will use the tick rate communicated by the host, when joining.
+//!
+//! Systems that run in the fixed update should use `get_fixed_delta` in stead of
+//! `get_frame_time`, and values that are expressed per tick, like velocities, should be scaled by
+//! `get_tick_scale`, so that the simulation behaves the same, regardless of tick rate.
+
+use core::config::{verify_tick_rate, DEFAULT_TICK_RATE};
+use core::Result;
+
+/// The maximum number of ticks that will be run in one frame. If the simulation falls further
+/// behind than this, because of a long frame, the remaining time is dropped, in stead of being
+/// caught up on over the following frames, as that would make every frame longer still.
+pub const MAX_TICKS_PER_FRAME: u32 = 8;
+
+static mut TICK_RATE: u32 = DEFAULT_TICK_RATE;
+
+/// Returns the current tick rate, in ticks per second
+pub fn get_tick_rate() -> u32 {
+    unsafe { TICK_RATE }
+}
+
+/// Set the tick rate. This will return an error if the tick rate is out of bounds.
+pub fn set_tick_rate(tick_rate: u32) -> Result<()> {
+    verify_tick_rate(tick_rate)?;
+
+    unsafe { TICK_RATE = tick_rate };
+
+    Ok(())
+}
+
+/// Returns the duration of one simulation tick, in seconds
+pub fn get_fixed_delta() -> f32 {
+    1.0 / get_tick_rate() as f32
+}
+
+/// Returns the ratio between the default tick rate and the current one. Values that are
+/// expressed per tick should be multiplied by this.
+pub fn get_tick_scale() -> f32 {
+    DEFAULT_TICK_RATE as f32 / get_tick_rate() as f32
+}
+
+/// Add `frame_time` to the accumulator and return the number of ticks that should be run this
+/// frame, which is at most `MAX_TICKS_PER_FRAME`
+pub fn step_fixed_accumulator(accumulator: &mut f32, frame_time: f32) -> u32 {
+    let fixed_delta = get_fixed_delta();
+
+    *accumulator += frame_time;
+
+    let mut tick_cnt = 0;
+
+    while *accumulator >= fixed_delta {
+        if tick_cnt == MAX_TICKS_PER_FRAME {
+            *accumulator = 0.0;
+            break;
+        }
+
+        *accumulator -= fixed_delta;
+        tick_cnt += 1;
+    }
+
+    tick_cnt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_fixed_accumulator() {
+        let fixed_delta = get_fixed_delta();
+        let mut accumulator = 0.0;
+
+        assert_eq!(
+            step_fixed_accumulator(&mut accumulator, fixed_delta * 0.5),
+            0
+        );
+        assert_eq!(
+            step_fixed_accumulator(&mut accumulator, fixed_delta * 2.0),
+            2
+        );
+        assert!(accumulator < fixed_delta);
+
+        // A long frame is not caught up on over the following frames
+        assert_eq!(
+            step_fixed_accumulator(&mut accumulator, fixed_delta * 100.0),
+            MAX_TICKS_PER_FRAME
+        );
+        assert_eq!(accumulator, 0.0);
+        assert_eq!(
+            step_fixed_accumulator(&mut accumulator, fixed_delta * 0.5),
+            0
+        );
+    }
+}
@@ -14,7 +14,7 @@ use crate::gui::draw_main_menu_background;
 use crate::resources::{
     map_name_to_filename, MapResource, Resources, MAP_EXPORTS_DEFAULT_DIR, MAP_EXPORTS_EXTENSION,
 };
-use core::input::{is_gamepad_btn_pressed, GamepadContext};
+use core::input::{is_menu_action_pressed, GamepadContext, MenuAction};
 
 enum WindowState {
     None,
@@ -48,10 +48,11 @@ pub async fn show_create_map_menu() -> Result<Option<MapResource>> {
         Path::new(&resources.assets_dir).join(MAP_EXPORTS_DEFAULT_DIR)
     };
 
-    let mut gamepad_system = storage::get_mut::<GamepadContext>();
-
     loop {
-        let _ = gamepad_system.update();
+        {
+            let mut gamepad_system = storage::get_mut::<GamepadContext>();
+            let _ = gamepad_system.update();
+        }
 
         draw_main_menu_background(true);
 
@@ -126,12 +127,9 @@ pub async fn show_create_map_menu() -> Result<Option<MapResource>> {
                 ui.separator();
                 ui.separator();
 
-                let btn_a =
-                    is_gamepad_btn_pressed(Some(&gamepad_system), fishsticks::Button::South);
+                let should_confirm = is_menu_action_pressed(MenuAction::Confirm);
 
-                let enter = is_key_pressed(KeyCode::Enter);
-
-                if ui.button(None, "Confirm") || btn_a || enter {
+                if ui.button(None, "Confirm") || should_confirm {
                     // TODO: Validate input
 
                     let tile_size = vec2(
@@ -154,11 +152,9 @@ pub async fn show_create_map_menu() -> Result<Option<MapResource>> {
 
                 ui.same_line(0.0);
 
-                let btn_b = is_gamepad_btn_pressed(Some(&gamepad_system), fishsticks::Button::East);
-
-                let escape = is_key_pressed(KeyCode::Escape);
+                let should_cancel = is_menu_action_pressed(MenuAction::Cancel);
 
-                if ui.button(None, "Cancel") || btn_b || escape {
+                if ui.button(None, "Cancel") || should_cancel {
                     res = WindowState::Cancel;
                 }
             });
@@ -1,6 +1,5 @@
-use core::input::is_gamepad_btn_pressed;
-use fishsticks::{Button, GamepadContext};
-use macroquad::{experimental::collections::storage, prelude::*};
+use core::input::{is_menu_action_pressed, MenuAction};
+use macroquad::prelude::*;
 use std::path::Path;
 
 const TEXT_X_OFFSET: f32 = 370.0;
@@ -68,8 +67,6 @@ enum LabelType {
 }
 
 pub async fn show_game_credits(assets_dir: &str) {
-    let gamepad_context = storage::get::<GamepadContext>();
-
     let mut delta = 200.0;
     let credits = create_game_credits();
 
@@ -83,9 +80,7 @@ pub async fn show_game_credits(assets_dir: &str) {
     .unwrap();
 
     loop {
-        if is_key_pressed(KeyCode::Escape)
-            || is_gamepad_btn_pressed(Some(&gamepad_context), Button::East)
-        {
+        if is_menu_action_pressed(MenuAction::Cancel) {
             break;
         }
 
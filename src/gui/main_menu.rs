@@ -10,9 +10,13 @@ use fishsticks::{Button, GamepadContext};
 
 use super::{draw_main_menu_background, GuiResources, Menu, MenuEntry, MenuResult, Panel};
 
+use crate::map::DailyMap;
 use crate::player::{PlayerControllerKind, PlayerParams};
-use crate::{gui, EditorInputScheme, Map, Resources};
-use core::input::{is_gamepad_btn_pressed, update_gamepad_context, GameInputScheme};
+use crate::{gui, Config, EditorInputScheme, Map, Resources};
+use core::input::{
+    get_button_label, is_menu_action_pressed, restore_player_input, update_gamepad_context,
+    GameInputScheme, MenuAction, MenuBinding,
+};
 
 const MENU_WIDTH: f32 = 300.0;
 
@@ -26,6 +30,10 @@ pub enum MainMenuResult {
         map: Box<Map>,
         players: Vec<PlayerParams>,
     },
+    DailyMap {
+        daily_map: Box<DailyMap>,
+        players: Vec<PlayerParams>,
+    },
     Editor {
         input_scheme: EditorInputScheme,
         is_new_map: bool,
@@ -38,7 +46,7 @@ pub enum MainMenuResult {
 #[allow(dead_code)]
 enum MainMenuState {
     Root(Menu),
-    LocalGame,
+    LocalGame { is_daily_map: bool },
     Settings,
     Editor(Menu),
     Credits,
@@ -50,6 +58,7 @@ const ROOT_OPTION_EDITOR: usize = 1;
 // const ROOT_OPTION_SETTINGS: usize = 2;
 const ROOT_OPTION_RELOAD_RESOURCES: usize = 3;
 const ROOT_OPTION_CREDITS: usize = 4;
+const ROOT_OPTION_DAILY_MAP: usize = 5;
 
 const LOCAL_GAME_OPTION_SUBMIT: usize = 0;
 
@@ -66,6 +75,11 @@ fn build_main_menu() -> Menu {
                 title: "Local Game".to_string(),
                 ..Default::default()
             },
+            MenuEntry {
+                index: ROOT_OPTION_DAILY_MAP,
+                title: "Daily Map".to_string(),
+                ..Default::default()
+            },
             MenuEntry {
                 index: ROOT_OPTION_EDITOR,
                 title: "Editor".to_string(),
@@ -118,6 +132,9 @@ pub async fn show_main_menu() -> MainMenuResult {
     let mut menu_state = MainMenuState::Root(build_main_menu());
 
     let mut player_input = Vec::new();
+    // This is set if the player input was restored from the config, in which case the players are
+    // asked to confirm it, in stead of the game being started right away
+    let mut is_restored = false;
 
     loop {
         update_gamepad_context(None).unwrap();
@@ -146,7 +163,18 @@ pub async fn show_main_menu() -> MainMenuResult {
                 if let Some(res) = menu_instance.ui(&mut *root_ui()) {
                     match res.into_usize() {
                         ROOT_OPTION_LOCAL_GAME => {
-                            menu_state = MainMenuState::LocalGame;
+                            player_input = get_saved_player_input();
+                            is_restored = player_input.len() == 2;
+
+                            menu_state = MainMenuState::LocalGame {
+                                is_daily_map: false,
+                            };
+                        }
+                        ROOT_OPTION_DAILY_MAP => {
+                            player_input = get_saved_player_input();
+                            is_restored = player_input.len() == 2;
+
+                            menu_state = MainMenuState::LocalGame { is_daily_map: true };
                         }
                         ROOT_OPTION_EDITOR => {
                             menu_state = MainMenuState::Editor(build_editor_menu());
@@ -164,8 +192,10 @@ pub async fn show_main_menu() -> MainMenuResult {
                     }
                 }
             }
-            MainMenuState::LocalGame => {
-                let res = local_game_ui(&mut *root_ui(), &mut player_input);
+            MainMenuState::LocalGame { is_daily_map } => {
+                let is_daily_map = *is_daily_map;
+
+                let res = local_game_ui(&mut *root_ui(), &mut player_input, &mut is_restored);
                 if let Some(res) = res {
                     match res.into_usize() {
                         LOCAL_GAME_OPTION_SUBMIT => {
@@ -179,24 +209,45 @@ pub async fn show_main_menu() -> MainMenuResult {
                             let player_characters =
                                 gui::show_select_characters_menu(&player_input).await;
 
-                            let map_resource = gui::show_select_map_menu().await;
+                            save_player_input(&player_input);
 
                             let mut players = Vec::new();
 
                             for (i, &input_scheme) in player_input.iter().enumerate() {
+                                let index = i as u8;
+
                                 let character = player_characters.get(i).cloned().unwrap();
 
                                 let controller = PlayerControllerKind::LocalInput(input_scheme);
 
+                                let team = storage::get::<Config>()
+                                    .match_settings
+                                    .get_player_team(index);
+
                                 let params = PlayerParams {
-                                    index: i as u8,
+                                    index,
                                     controller,
                                     character,
+                                    team,
                                 };
 
                                 players.push(params);
                             }
 
+                            if is_daily_map {
+                                if let Some(daily_map) = DailyMap::today() {
+                                    return MainMenuResult::DailyMap {
+                                        daily_map: Box::new(daily_map),
+                                        players,
+                                    };
+                                }
+
+                                #[cfg(debug_assertions)]
+                                println!("WARNING: Unable to generate the daily map, as the default tileset is not loaded");
+                            }
+
+                            let map_resource = gui::show_select_map_menu().await;
+
                             return MainMenuResult::LocalGame {
                                 map: Box::new(map_resource.map),
                                 players,
@@ -243,28 +294,79 @@ pub async fn show_main_menu() -> MainMenuResult {
     }
 }
 
-fn local_game_ui(ui: &mut ui::Ui, player_input: &mut Vec<GameInputScheme>) -> Option<MenuResult> {
-    if player_input.len() == 2 {
-        return Some(LOCAL_GAME_OPTION_SUBMIT.into());
-    } else {
+/// Returns the input schemes that were assigned to the player slots in the last local game, for
+/// the input devices that are still available
+fn get_saved_player_input() -> Vec<GameInputScheme> {
+    let connected_gamepads = {
         let gamepad_context = storage::get::<GamepadContext>();
+        gamepad_context
+            .gamepads()
+            .map(|(ix, _)| ix)
+            .collect::<Vec<_>>()
+    };
+
+    let config = storage::get::<Config>();
+    restore_player_input(&config.input.player_slots, &connected_gamepads)
+}
+
+/// Save the input schemes assigned to the player slots to the config file in the user config
+/// directory, so that they can be restored in the next session
+fn save_player_input(player_input: &[GameInputScheme]) {
+    let mut config = storage::get_mut::<Config>();
+    config.input.player_slots = player_input.iter().map(|&scheme| scheme.into()).collect();
 
-        if is_key_pressed(KeyCode::Escape)
-            || is_gamepad_btn_pressed(Some(&gamepad_context), Button::East)
+    let path = crate::get_user_config_path();
+    if let Err(err) = config.save(&path) {
+        println!("Config: Unable to save '{}': {}", path.display(), err);
+    }
+}
+
+fn local_game_ui(
+    ui: &mut ui::Ui,
+    player_input: &mut Vec<GameInputScheme>,
+    is_restored: &mut bool,
+) -> Option<MenuResult> {
+    if *is_restored {
+        if is_menu_action_pressed(MenuAction::Confirm) || is_mouse_button_pressed(MouseButton::Left)
         {
+            *is_restored = false;
+            return Some(LOCAL_GAME_OPTION_SUBMIT.into());
+        }
+
+        // Cancelling clears the restored input, so that the players can reassign their devices
+        if is_menu_action_pressed(MenuAction::Cancel) {
+            *is_restored = false;
+            player_input.clear();
+        }
+    } else if player_input.len() == 2 {
+        return Some(LOCAL_GAME_OPTION_SUBMIT.into());
+    } else {
+        if is_menu_action_pressed(MenuAction::Cancel) {
             return Some(Menu::CANCEL_INDEX.into());
         }
     }
 
     if player_input.len() < 2 {
+        let is_available = |player_input: &[GameInputScheme], scheme: GameInputScheme| {
+            !player_input
+                .iter()
+                .any(|other| other.conflicts_with(&scheme))
+        };
+
         if is_key_pressed(KeyCode::Enter) {
-            if !player_input.contains(&GameInputScheme::KeyboardLeft) {
+            if is_available(player_input, GameInputScheme::KeyboardLeft) {
                 player_input.push(GameInputScheme::KeyboardLeft);
-            } else {
+            } else if is_available(player_input, GameInputScheme::KeyboardRight) {
                 player_input.push(GameInputScheme::KeyboardRight);
             }
         }
 
+        if is_mouse_button_pressed(MouseButton::Left)
+            && is_available(player_input, GameInputScheme::MouseKeyboard)
+        {
+            player_input.push(GameInputScheme::MouseKeyboard);
+        }
+
         let gamepad_context = storage::get_mut::<GamepadContext>();
         for (ix, gamepad) in gamepad_context.gamepads() {
             if gamepad.digital_inputs.activated(fishsticks::Button::Start)
@@ -275,6 +377,23 @@ fn local_game_ui(ui: &mut ui::Ui, player_input: &mut Vec<GameInputScheme>) -> Op
         }
     }
 
+    let start_label = get_button_label(Button::Start);
+    let (confirm_label, cancel_label) = {
+        let config = storage::get::<Config>();
+        let get_label = |binding: &MenuBinding| {
+            binding
+                .buttons
+                .first()
+                .map(|&button| get_button_label(button.into()))
+                .unwrap_or("?")
+        };
+
+        (
+            get_label(&config.input.menu.confirm),
+            get_label(&config.input.menu.cancel),
+        )
+    };
+
     let size = vec2(LOCAL_GAME_MENU_WIDTH, LOCAL_GAME_MENU_HEIGHT);
     let position = (vec2(screen_width(), screen_height()) - size) / 2.0;
 
@@ -290,7 +409,10 @@ fn local_game_ui(ui: &mut ui::Ui, player_input: &mut Vec<GameInputScheme>) -> Op
             if !player_input.is_empty() {
                 ui.label(position, "Player 1: READY");
             } else {
-                ui.label(position, "Player 1: press START or ENTER");
+                ui.label(
+                    position,
+                    &format!("Player 1: press {}, ENTER or CLICK", start_label),
+                );
             }
         }
 
@@ -300,14 +422,34 @@ fn local_game_ui(ui: &mut ui::Ui, player_input: &mut Vec<GameInputScheme>) -> Op
             if player_input.len() > 1 {
                 ui.label(position, "Player 2: READY");
             } else {
-                ui.label(position, "Player 2: press START or ENTER");
+                ui.label(
+                    position,
+                    &format!("Player 2: press {}, ENTER or CLICK", start_label),
+                );
             }
         }
 
-        {
+        if *is_restored {
             let position = vec2(12.0, 108.0);
 
-            ui.label(position, "Press B or ESC to cancel");
+            ui.label(
+                position,
+                &format!("Press {}, ENTER or CLICK to start", confirm_label),
+            );
+
+            let position = vec2(12.0, 140.0);
+
+            ui.label(
+                position,
+                &format!("Press {} or ESC to reassign", cancel_label),
+            );
+        } else {
+            let position = vec2(12.0, 108.0);
+
+            ui.label(
+                position,
+                &format!("Press {} or ESC to cancel", cancel_label),
+            );
         }
 
         ui.pop_skin();
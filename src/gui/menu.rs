@@ -4,13 +4,12 @@ use macroquad::{
     ui::{widgets, Id, Ui},
 };
 
-use fishsticks::{Axis, Button};
-
 use super::{
-    GuiResources, Panel, BUTTON_FONT_SIZE, BUTTON_MARGIN_V, WINDOW_MARGIN_H, WINDOW_MARGIN_V,
+    get_ui_scale, GuiResources, Panel, BUTTON_FONT_SIZE, BUTTON_MARGIN_V, WINDOW_MARGIN_H,
+    WINDOW_MARGIN_V,
 };
 
-use core::input::{is_gamepad_btn_pressed, GamepadContext};
+use core::input::{is_menu_action_down, is_menu_action_pressed, MenuAction};
 
 #[derive(Debug, Copy, Clone)]
 pub enum MenuPosition {
@@ -217,6 +216,12 @@ impl Menu {
 
         let (should_confirm, should_cancel) = self.update_input();
 
+        let scale = get_ui_scale();
+
+        let entry_height = Self::ENTRY_HEIGHT * scale;
+        let entry_margin = Self::ENTRY_MARGIN * scale;
+        let window_margins = vec2(WINDOW_MARGIN_H, WINDOW_MARGIN_V) * scale;
+
         let header_height = if let Some(header) = &self.header {
             let gui_resources = storage::get::<GuiResources>();
             ui.push_skin(&gui_resources.skins.menu_header);
@@ -225,7 +230,7 @@ impl Menu {
 
             ui.pop_skin();
 
-            header_size.y + Self::HEADER_MARGIN
+            header_size.y + (Self::HEADER_MARGIN * scale)
         } else {
             0.0
         };
@@ -233,19 +238,19 @@ impl Menu {
         let size = {
             let height = header_height
                 + if let Some(height) = self.height {
-                    height
+                    height * scale
                 } else {
                     let len = entries.len();
                     let entry_margins = if len > 0 {
-                        (len as f32 * Self::ENTRY_MARGIN) - Self::ENTRY_MARGIN
+                        (len as f32 * entry_margin) - entry_margin
                     } else {
                         0.0
                     };
 
-                    (len as f32 * Self::ENTRY_HEIGHT) + entry_margins + (WINDOW_MARGIN_V * 2.0)
+                    (len as f32 * entry_height) + entry_margins + (window_margins.y * 2.0)
                 };
 
-            vec2(self.width, height)
+            vec2(self.width * scale, height)
         };
 
         let position = match self.position {
@@ -256,7 +261,7 @@ impl Menu {
         };
 
         Panel::new(self.id, size, position).ui(ui, |ui, inner_size| {
-            let entry_size = vec2(size.x - (WINDOW_MARGIN_H * 2.0), Self::ENTRY_HEIGHT);
+            let entry_size = vec2(size.x - (window_margins.x * 2.0), entry_height);
 
             if let Some(header) = &self.header {
                 let gui_resources = storage::get::<GuiResources>();
@@ -296,7 +301,7 @@ impl Menu {
             for (i, entry) in top_entries.iter().enumerate() {
                 let entry_position = entries_position
                     + if i > 0 {
-                        vec2(0.0, i as f32 * (entry_size.y + Self::ENTRY_MARGIN))
+                        vec2(0.0, i as f32 * (entry_size.y + entry_margin))
                     } else {
                         vec2(0.0, 0.0)
                     };
@@ -330,13 +335,13 @@ impl Menu {
             }
 
             let bottom_y = {
-                let top_end = entries_position.y
-                    + (top_entries.len() as f32 * (entry_size.y + Self::ENTRY_MARGIN));
+                let top_end =
+                    entries_position.y + (top_entries.len() as f32 * (entry_size.y + entry_margin));
                 let bottom_height = {
                     let len = bottom_entries.len();
 
                     let entry_margins = if len > 0 {
-                        (len as f32 * Self::ENTRY_MARGIN) - Self::ENTRY_MARGIN
+                        (len as f32 * entry_margin) - entry_margin
                     } else {
                         0.0
                     };
@@ -352,10 +357,8 @@ impl Menu {
             };
 
             for (i, entry) in bottom_entries.iter().enumerate() {
-                let entry_position = vec2(
-                    0.0,
-                    bottom_y + (i as f32 * (entry_size.y + Self::ENTRY_MARGIN)),
-                );
+                let entry_position =
+                    vec2(0.0, bottom_y + (i as f32 * (entry_size.y + entry_margin)));
 
                 let mut is_selected = false;
                 if let Some(current_selection) = self.current_selection {
@@ -402,24 +405,10 @@ impl Menu {
         if self.is_first_draw {
             (false, false)
         } else {
-            let gamepad_context = storage::get::<GamepadContext>();
-
             let mut selection = self.current_selection.map(|s| s as i32);
 
-            let mut gamepad_up = false;
-            let mut gamepad_down = false;
-
-            for (_, gamepad) in gamepad_context.gamepads() {
-                gamepad_up = gamepad_up
-                    || gamepad.digital_inputs.activated(Button::DPadUp)
-                    || gamepad.analog_inputs.digital_value(Axis::LeftStickY) < 0.0;
-                gamepad_down = gamepad_down
-                    || gamepad.digital_inputs.activated(Button::DPadDown)
-                    || gamepad.analog_inputs.digital_value(Axis::LeftStickY) > 0.0;
-            }
-
             if self.up_grace_timer >= Self::NAVIGATION_GRACE_TIME
-                && (gamepad_up || is_key_down(KeyCode::Up) || is_key_down(KeyCode::W))
+                && is_menu_action_down(MenuAction::Up)
             {
                 self.up_grace_timer = 0.0;
 
@@ -429,7 +418,7 @@ impl Menu {
                     Some(0)
                 };
             } else if self.down_grace_timer >= Self::NAVIGATION_GRACE_TIME
-                && (gamepad_down || is_key_down(KeyCode::Down) || is_key_down(KeyCode::S))
+                && is_menu_action_down(MenuAction::Down)
             {
                 self.down_grace_timer = 0.0;
 
@@ -459,11 +448,8 @@ impl Menu {
                 self.current_selection = Some(selection);
             }
 
-            let should_confirm = is_gamepad_btn_pressed(Some(&gamepad_context), Button::South)
-                || is_key_pressed(KeyCode::Enter);
-
-            let should_cancel = is_gamepad_btn_pressed(Some(&gamepad_context), Button::East)
-                || is_key_pressed(KeyCode::Escape);
+            let should_confirm = is_menu_action_pressed(MenuAction::Confirm);
+            let should_cancel = is_menu_action_pressed(MenuAction::Cancel);
 
             (should_confirm, should_cancel)
         }
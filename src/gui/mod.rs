@@ -6,15 +6,18 @@ mod game_menu;
 mod main_menu;
 mod menu;
 mod panel;
+mod scale;
 mod select_character;
 mod select_map;
 mod style;
+mod theme;
 
 use macroquad::prelude::*;
 
 pub use style::{
-    SkinCollection, BUTTON_FONT_SIZE, BUTTON_MARGIN_H, BUTTON_MARGIN_V, LIST_BOX_ENTRY_HEIGHT,
-    SELECTION_HIGHLIGHT_COLOR, WINDOW_BG_COLOR, WINDOW_MARGIN_H, WINDOW_MARGIN_V,
+    SkinCollection, StyleMetrics, BUTTON_FONT_SIZE, BUTTON_MARGIN_H, BUTTON_MARGIN_V,
+    LIST_BOX_ENTRY_HEIGHT, SELECTION_HIGHLIGHT_COLOR, SKIN_IMAGE_IDS, WINDOW_BG_COLOR,
+    WINDOW_MARGIN_H, WINDOW_MARGIN_V,
 };
 
 pub use background::{draw_main_menu_background, Background};
@@ -28,21 +31,30 @@ pub use game_menu::{
 pub use main_menu::{show_main_menu, MainMenuResult};
 pub use menu::{Menu, MenuEntry, MenuResult};
 pub use panel::{NewPanel, Panel};
+pub use scale::{detect_ui_scale, get_ui_scale, scaled, scaled_vec2};
 pub use select_character::show_select_characters_menu;
 pub use select_map::show_select_map_menu;
+pub use theme::{load_gui_theme, GuiTheme};
 
 pub const NO_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.0);
 
 pub const ELEMENT_MARGIN: f32 = 8.0;
 
 pub struct GuiResources {
+    pub theme: GuiTheme,
+    /// The factor that font sizes and widget dimensions are multiplied by
+    pub scale: f32,
     pub skins: SkinCollection,
 }
 
 impl GuiResources {
-    pub fn new() -> GuiResources {
+    pub fn new(theme: GuiTheme, scale: f32) -> GuiResources {
+        let skins = SkinCollection::new(&theme, scale);
+
         GuiResources {
-            skins: SkinCollection::new(),
+            theme,
+            scale,
+            skins,
         }
     }
 }
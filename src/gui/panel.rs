@@ -10,7 +10,7 @@ use macroquad::{
     ui::{widgets, Id, Ui},
 };
 
-use super::{GuiResources, WINDOW_MARGIN_H, WINDOW_MARGIN_V};
+use super::{scaled_vec2, GuiResources, WINDOW_MARGIN_H, WINDOW_MARGIN_V};
 
 pub struct Panel {
     id: Id,
@@ -61,11 +61,13 @@ impl Panel {
             if let Some(background_color) = self.background_color {
                 ui.push_skin(&gui_resources.skins.panel_no_bg);
 
+                let bg_offset = Self::BG_OFFSET * gui_resources.scale;
+
                 draw_rectangle(
-                    self.position.x + Self::BG_OFFSET,
-                    self.position.y + Self::BG_OFFSET,
-                    self.size.x - (Self::BG_OFFSET * 2.0),
-                    self.size.y - (Self::BG_OFFSET * 2.0),
+                    self.position.x + bg_offset,
+                    self.position.y + bg_offset,
+                    self.size.x - (bg_offset * 2.0),
+                    self.size.y - (bg_offset * 2.0),
                     background_color,
                 );
             } else {
@@ -78,7 +80,7 @@ impl Panel {
             .size(self.size)
             .ui(ui);
 
-        let window_margins = vec2(WINDOW_MARGIN_H, WINDOW_MARGIN_V);
+        let window_margins = scaled_vec2(vec2(WINDOW_MARGIN_H, WINDOW_MARGIN_V));
 
         let mut content_position = self.position + window_margins;
         let mut content_size = self.size - (window_margins * 2.0);
@@ -91,7 +93,7 @@ impl Panel {
             let mut label_position = content_position;
 
             if self.is_title_centered {
-                label_position.x += ((self.size.x - label_size.x) / 2.0) - WINDOW_MARGIN_H;
+                label_position.x += ((self.size.x - label_size.x) / 2.0) - window_margins.x;
             }
 
             ui.label(label_position, title);
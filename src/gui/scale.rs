@@ -0,0 +1,72 @@
+//! Scaling of the UI, for high-DPI displays.
+//! All font sizes and widget dimensions in the gui are specified at a scale of `1.0`, and should be
+//! multiplied by the UI scale, using the helpers in this module, before they are used for layout.
+
+use macroquad::{experimental::collections::storage, prelude::*, ui::RectOffset, window};
+
+use core::Config;
+
+use super::GuiResources;
+
+/// Returns the UI scale, either as specified in the config, or as detected from the DPI of the
+/// display
+pub fn detect_ui_scale() -> f32 {
+    let dpi_scale = unsafe { window::get_internal_gl().quad_context.dpi_scale() };
+
+    let config = storage::get::<Config>();
+    config.ui.get_scale(config.window.is_high_dpi, dpi_scale)
+}
+
+/// Returns the current UI scale. This requires `GuiResources` to be loaded.
+pub fn get_ui_scale() -> f32 {
+    storage::get::<GuiResources>().scale
+}
+
+/// Multiply `value` by the current UI scale
+pub fn scaled(value: f32) -> f32 {
+    value * get_ui_scale()
+}
+
+/// Multiply `value` by the current UI scale
+pub fn scaled_vec2(value: Vec2) -> Vec2 {
+    value * get_ui_scale()
+}
+
+/// Multiply a font size by `scale`, rounding to the nearest whole size
+pub fn scale_font_size(size: f32, scale: f32) -> u16 {
+    (size * scale).round() as u16
+}
+
+/// Create a `RectOffset` with all sides multiplied by `scale`
+pub fn scale_offset(scale: f32, left: f32, right: f32, top: f32, bottom: f32) -> RectOffset {
+    RectOffset::new(left * scale, right * scale, top * scale, bottom * scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::gui::{StyleMetrics, BUTTON_FONT_SIZE, BUTTON_MARGIN_H, BUTTON_MARGIN_V};
+
+    #[test]
+    fn test_doubled_scale_doubles_widget_size() {
+        let label_width = 100.0;
+
+        let size = StyleMetrics::button(1.0).get_widget_size(label_width);
+        assert_eq!(
+            size,
+            vec2(
+                label_width + BUTTON_MARGIN_H * 2.0,
+                BUTTON_FONT_SIZE + BUTTON_MARGIN_V * 2.0
+            )
+        );
+
+        // The label is measured in the scaled font, so its width is doubled as well
+        let doubled = StyleMetrics::button(2.0).get_widget_size(label_width * 2.0);
+        assert!((doubled - size * 2.0).abs().max_element() <= 1.0);
+
+        let config = core::UiConfig::default();
+        assert_eq!(config.get_scale(true, 2.0), 2.0);
+        assert_eq!(config.get_scale(false, 2.0), 1.0);
+    }
+}
@@ -111,6 +111,11 @@ pub async fn show_select_characters_menu(
                         should_confirm =
                             is_key_pressed(KeyCode::V) || is_key_pressed(KeyCode::LeftControl);
                     }
+                    GameInputScheme::MouseKeyboard => {
+                        should_navigate_left = can_navigate && is_key_down(KeyCode::A);
+                        should_navigate_right = can_navigate && is_key_down(KeyCode::D);
+                        should_confirm = is_mouse_button_pressed(MouseButton::Left);
+                    }
                     GameInputScheme::Gamepad(gamepad_id) => {
                         let gamepad_context = storage::get::<GamepadContext>();
                         let gamepad = gamepad_context.gamepad(gamepad_id);
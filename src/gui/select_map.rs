@@ -7,6 +7,7 @@ use macroquad::{
 use super::GuiResources;
 
 use crate::{resources::MapResource, GamepadContext, Resources};
+use core::input::{is_menu_action_pressed, MenuAction};
 
 use crate::gui::{draw_main_menu_background, WINDOW_MARGIN_H, WINDOW_MARGIN_V};
 
@@ -15,10 +16,34 @@ const MAP_SELECT_PREVIEW_TARGET_WIDTH: f32 = 250.0;
 const MAP_SELECT_PREVIEW_RATIO: f32 = 10.0 / 16.0;
 const MAP_SELECT_PREVIEW_SHRINK_FACTOR: f32 = 0.8;
 
+/// Returns all the tags of the loaded maps, sorted and without duplicates
+fn get_map_tags(resources: &Resources) -> Vec<String> {
+    let mut res: Vec<String> = Vec::new();
+
+    for map_resource in &resources.maps {
+        for tag in &map_resource.map.info.tags {
+            if !res.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                res.push(tag.clone());
+            }
+        }
+    }
+
+    res.sort_unstable_by_key(|tag| tag.to_lowercase());
+
+    res
+}
+
 pub async fn show_select_map_menu() -> MapResource {
     let mut current_page: i32;
     let mut hovered: i32 = 0;
 
+    let tags = {
+        let resources = storage::get::<Resources>();
+        get_map_tags(&resources)
+    };
+
+    let mut selected_tag: Option<usize> = None;
+
     let mut old_mouse_position = mouse_position();
 
     // skip a frame to let Enter be unpressed from the previous screen
@@ -28,35 +53,38 @@ pub async fn show_select_map_menu() -> MapResource {
         draw_main_menu_background(false);
 
         let gui_resources = storage::get::<GuiResources>();
-        let mut gamepad_system = storage::get_mut::<GamepadContext>();
-
-        let _ = gamepad_system.update();
+        {
+            let mut gamepad_system = storage::get_mut::<GamepadContext>();
+            let _ = gamepad_system.update();
+        }
 
-        let mut up = is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::W);
-        let mut down = is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::S);
+        let mut up = is_menu_action_pressed(MenuAction::Up);
+        let mut down = is_menu_action_pressed(MenuAction::Down);
         let mut right = is_key_pressed(KeyCode::Right) || is_key_pressed(KeyCode::D);
         let mut left = is_key_pressed(KeyCode::Left) || is_key_pressed(KeyCode::A);
-        let mut start = is_key_pressed(KeyCode::Enter);
+        let mut start = is_menu_action_pressed(MenuAction::Confirm);
+        let mut next_tag = is_key_pressed(KeyCode::Tab);
+        let mut prev_tag = false;
 
         let (page_up, page_down) = {
             let mouse_wheel = mouse_wheel();
             (mouse_wheel.1 > 0.0, mouse_wheel.1 < 0.0)
         };
 
+        let gamepad_system = storage::get::<GamepadContext>();
+
         for (_, gamepad) in gamepad_system.gamepads() {
             use fishsticks::{Axis, Button};
 
-            up |= gamepad.digital_inputs.just_activated(Button::DPadUp)
-                || matches!(
-                    gamepad.analog_inputs.just_activated_digital(Axis::LeftStickY),
-                    Some(value) if value < 0.0
-                );
+            up |= matches!(
+                gamepad.analog_inputs.just_activated_digital(Axis::LeftStickY),
+                Some(value) if value < 0.0
+            );
 
-            down |= gamepad.digital_inputs.just_activated(Button::DPadDown)
-                || matches!(
-                    gamepad.analog_inputs.just_activated_digital(Axis::LeftStickY),
-                    Some(value) if value > 0.0
-                );
+            down |= matches!(
+                gamepad.analog_inputs.just_activated_digital(Axis::LeftStickY),
+                Some(value) if value > 0.0
+            );
 
             left |= gamepad.digital_inputs.just_activated(Button::DPadLeft)
                 || matches!(
@@ -70,12 +98,51 @@ pub async fn show_select_map_menu() -> MapResource {
                     Some(value) if value > 0.0
                 );
 
-            start |= gamepad.digital_inputs.just_activated(Button::South)
-                || gamepad.digital_inputs.just_activated(Button::Start);
+            start |= gamepad.digital_inputs.just_activated(Button::Start);
+
+            next_tag |= gamepad.digital_inputs.just_activated(Button::RightBumper);
+            prev_tag |= gamepad.digital_inputs.just_activated(Button::LeftBumper);
+        }
+
+        if !tags.is_empty() && (next_tag || prev_tag) {
+            // Cycle through all tags, with `None` (all maps) between the last and the first tag
+            selected_tag = if next_tag {
+                match selected_tag {
+                    None => Some(0),
+                    Some(i) if i + 1 < tags.len() => Some(i + 1),
+                    Some(_) => None,
+                }
+            } else {
+                match selected_tag {
+                    None => Some(tags.len() - 1),
+                    Some(0) => None,
+                    Some(i) => Some(i - 1),
+                }
+            };
+
+            hovered = 0;
         }
 
         let resources = storage::get::<Resources>();
-        let map_cnt = resources.maps.len();
+
+        let map_indices = resources
+            .maps
+            .iter()
+            .enumerate()
+            .filter_map(|(i, map_resource)| {
+                let is_match = selected_tag
+                    .map(|tag| map_resource.map.info.has_tag(&tags[tag]))
+                    .unwrap_or(true);
+
+                if is_match {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let map_cnt = map_indices.len();
 
         root_ui().push_skin(&gui_resources.skins.map_selection);
 
@@ -185,11 +252,49 @@ pub async fn show_select_map_menu() -> MapResource {
                         .ui(&mut *root_ui());
                 }
 
+                if !tags.is_empty() {
+                    let tag_label = match selected_tag {
+                        Some(i) => format!("tag: {} (tab to cycle)", tags[i]),
+                        None => "tag: all (tab to cycle)".to_string(),
+                    };
+
+                    widgets::Label::new(&tag_label)
+                        .position(vec2(WINDOW_MARGIN_H, WINDOW_MARGIN_V))
+                        .ui(&mut *root_ui());
+                }
+
+                if let Some(map_entry) = map_indices
+                    .get(hovered as usize)
+                    .and_then(|&i| resources.maps.get(i))
+                {
+                    let info = &map_entry.map.info;
+
+                    let mut info_label = map_entry.meta.name.clone();
+
+                    if let Some(author) = &info.author {
+                        info_label = format!("{} by {}", info_label, author);
+                    }
+
+                    if let Some(players) = info.recommended_players {
+                        info_label = format!("{} ({} players)", info_label, players);
+                    }
+
+                    let label_size = root_ui().calc_size(&info_label);
+                    let label_position = vec2(
+                        WINDOW_MARGIN_H,
+                        screen_size.y - WINDOW_MARGIN_V - label_size.y,
+                    );
+
+                    widgets::Label::new(&info_label)
+                        .position(label_position)
+                        .ui(&mut *root_ui());
+                }
+
                 let begin = (current_page as usize * entries_per_page).clamp(0, map_cnt);
                 let end = (begin as usize + entries_per_page).clamp(begin, map_cnt);
 
                 for (pi, i) in (begin..end).enumerate() {
-                    let map_entry = resources.maps.get(i).unwrap();
+                    let map_entry = resources.maps.get(map_indices[i]).unwrap();
                     let is_hovered = hovered == i as i32;
 
                     let mut rect = Rect::new(
@@ -223,7 +328,11 @@ pub async fn show_select_map_menu() -> MapResource {
                         || start
                     {
                         root_ui().pop_skin();
-                        let res = resources.maps.get(hovered as usize).cloned().unwrap();
+                        let res = resources
+                            .maps
+                            .get(map_indices[hovered as usize])
+                            .cloned()
+                            .unwrap();
                         return res;
                     }
                 }
@@ -1,13 +1,15 @@
 use macroquad::{
     color::Color,
     experimental::collections::storage,
-    math::RectOffset,
-    ui::{root_ui, Skin},
+    math::{vec2, RectOffset, Vec2},
+    ui::{root_ui, Skin, StyleBuilder},
 };
 
+use crate::resources::ImageResource;
 use crate::Resources;
 
-use super::{ELEMENT_MARGIN, NO_COLOR};
+use super::scale::{scale_font_size, scale_offset};
+use super::{GuiTheme, ELEMENT_MARGIN, NO_COLOR};
 
 pub const FONT_SIZE: f32 = 18.0;
 
@@ -97,6 +99,82 @@ const EDITBOX_BACKGROUND_CLICKED_IMAGE_ID: &str = "editbox_background_clicked";
 const WINDOW_BACKGROUND_IMAGE_ID: &str = "window_background";
 const WINDOW_BORDER_IMAGE_ID: &str = "window_border";
 
+/// The ids of all the images that the skins are built from. A theme should supply all of these.
+pub const SKIN_IMAGE_IDS: &[&str] = &[
+    BLANK_IMAGE_ID,
+    BUTTON_BACKGROUND_IMAGE_ID,
+    BUTTON_BACKGROUND_CLICKED_IMAGE_ID,
+    BUTTON_BACKGROUND_DISABLED_IMAGE_ID,
+    BUTTON_BACKGROUND_HOVERED_IMAGE_ID,
+    CHECKBOX_BACKGROUND_IMAGE_ID,
+    CHECKBOX_BACKGROUND_CHECKED_IMAGE_ID,
+    CHECKBOX_BACKGROUND_CHECKED_HOVERED_IMAGE_ID,
+    CHECKBOX_BACKGROUND_CLICKED_IMAGE_ID,
+    CHECKBOX_BACKGROUND_HOVERED_IMAGE_ID,
+    COMBOBOX_BACKGROUND_IMAGE_ID,
+    EDITBOX_BACKGROUND_IMAGE_ID,
+    EDITBOX_BACKGROUND_CLICKED_IMAGE_ID,
+    WINDOW_BACKGROUND_IMAGE_ID,
+    WINDOW_BORDER_IMAGE_ID,
+];
+
+/// The margins and font size of a style, at a specific UI scale
+#[derive(Debug, Clone, Copy)]
+pub struct StyleMetrics {
+    pub background_margin: RectOffset,
+    pub margin: RectOffset,
+    pub font_size: u16,
+}
+
+impl StyleMetrics {
+    /// The metrics of the buttons of the default skin
+    pub fn button(scale: f32) -> Self {
+        StyleMetrics {
+            background_margin: scale_offset(
+                scale,
+                BUTTON_BG_MARGIN_H,
+                BUTTON_BG_MARGIN_H,
+                BUTTON_BG_MARGIN_V,
+                BUTTON_BG_MARGIN_V,
+            ),
+            margin: scale_offset(
+                scale,
+                BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
+                BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
+                BUTTON_MARGIN_V - BUTTON_BG_MARGIN_V,
+                BUTTON_MARGIN_V - BUTTON_BG_MARGIN_V,
+            ),
+            font_size: scale_font_size(BUTTON_FONT_SIZE, scale),
+        }
+    }
+
+    /// Returns the size of a widget with a single line label of the specified width. This is the
+    /// size of the label, with the margins added, as the widget will be laid out by the UI.
+    pub fn get_widget_size(&self, label_width: f32) -> Vec2 {
+        let (margin, background_margin) = (self.margin, self.background_margin);
+
+        vec2(
+            label_width
+                + margin.left
+                + margin.right
+                + background_margin.left
+                + background_margin.right,
+            self.font_size as f32
+                + margin.top
+                + margin.bottom
+                + background_margin.top
+                + background_margin.bottom,
+        )
+    }
+
+    fn apply(&self, builder: StyleBuilder) -> StyleBuilder {
+        builder
+            .background_margin(self.background_margin)
+            .margin(self.margin)
+            .font_size(self.font_size)
+    }
+}
+
 pub struct SkinCollection {
     pub default: Skin,
     pub button_disabled: Skin,
@@ -128,66 +206,69 @@ pub struct SkinCollection {
     pub panel_no_bg: Skin,
 }
 
+/// Returns the image with the specified id from `theme`, falling back to the default image, if the
+/// theme does not supply it
+fn get_skin_image<'a>(
+    theme: &'a GuiTheme,
+    resources: &'a Resources,
+    id: &str,
+) -> &'a ImageResource {
+    theme
+        .get_image(&resources.images, id)
+        .unwrap_or_else(|| panic!("SkinCollection: Invalid image ID '{}'", id))
+}
+
 impl SkinCollection {
-    pub fn new() -> SkinCollection {
+    /// Build the skins from the images in `theme`, with all font sizes and margins multiplied by
+    /// `scale`
+    pub fn new(theme: &GuiTheme, scale: f32) -> SkinCollection {
         let resources = storage::get::<Resources>();
 
-        let _blank_image = resources.images.get(BLANK_IMAGE_ID).unwrap();
-
-        let button_background = resources.images.get(BUTTON_BACKGROUND_IMAGE_ID).unwrap();
-        let button_background_clicked = resources
-            .images
-            .get(BUTTON_BACKGROUND_CLICKED_IMAGE_ID)
-            .unwrap();
-        let button_background_disabled = resources
-            .images
-            .get(BUTTON_BACKGROUND_DISABLED_IMAGE_ID)
-            .unwrap();
-        let button_background_hovered = resources
-            .images
-            .get(BUTTON_BACKGROUND_HOVERED_IMAGE_ID)
-            .unwrap();
-
-        let checkbox_background = resources.images.get(CHECKBOX_BACKGROUND_IMAGE_ID).unwrap();
-        let checkbox_background_checked = resources
-            .images
-            .get(CHECKBOX_BACKGROUND_CHECKED_IMAGE_ID)
-            .unwrap();
-        let checkbox_background_checked_hovered = resources
-            .images
-            .get(CHECKBOX_BACKGROUND_CHECKED_HOVERED_IMAGE_ID)
-            .unwrap();
-        let checkbox_background_clicked = resources
-            .images
-            .get(CHECKBOX_BACKGROUND_CLICKED_IMAGE_ID)
-            .unwrap();
-        let checkbox_background_hovered = resources
-            .images
-            .get(CHECKBOX_BACKGROUND_HOVERED_IMAGE_ID)
-            .unwrap();
-
-        let combobox_background = resources.images.get(COMBOBOX_BACKGROUND_IMAGE_ID).unwrap();
-
-        let editbox_background = resources.images.get(EDITBOX_BACKGROUND_IMAGE_ID).unwrap();
-        let editbox_background_clicked = resources
-            .images
-            .get(EDITBOX_BACKGROUND_CLICKED_IMAGE_ID)
-            .unwrap();
-
-        let window_background = resources.images.get(WINDOW_BACKGROUND_IMAGE_ID).unwrap();
-        let window_border = resources.images.get(WINDOW_BORDER_IMAGE_ID).unwrap();
+        let _blank_image = get_skin_image(theme, &resources, BLANK_IMAGE_ID);
+
+        let button_background = get_skin_image(theme, &resources, BUTTON_BACKGROUND_IMAGE_ID);
+        let button_background_clicked =
+            get_skin_image(theme, &resources, BUTTON_BACKGROUND_CLICKED_IMAGE_ID);
+        let button_background_disabled =
+            get_skin_image(theme, &resources, BUTTON_BACKGROUND_DISABLED_IMAGE_ID);
+        let button_background_hovered =
+            get_skin_image(theme, &resources, BUTTON_BACKGROUND_HOVERED_IMAGE_ID);
+
+        let checkbox_background = get_skin_image(theme, &resources, CHECKBOX_BACKGROUND_IMAGE_ID);
+        let checkbox_background_checked =
+            get_skin_image(theme, &resources, CHECKBOX_BACKGROUND_CHECKED_IMAGE_ID);
+        let checkbox_background_checked_hovered = get_skin_image(
+            theme,
+            &resources,
+            CHECKBOX_BACKGROUND_CHECKED_HOVERED_IMAGE_ID,
+        );
+        let checkbox_background_clicked =
+            get_skin_image(theme, &resources, CHECKBOX_BACKGROUND_CLICKED_IMAGE_ID);
+        let checkbox_background_hovered =
+            get_skin_image(theme, &resources, CHECKBOX_BACKGROUND_HOVERED_IMAGE_ID);
+
+        let combobox_background = get_skin_image(theme, &resources, COMBOBOX_BACKGROUND_IMAGE_ID);
+
+        let editbox_background = get_skin_image(theme, &resources, EDITBOX_BACKGROUND_IMAGE_ID);
+        let editbox_background_clicked =
+            get_skin_image(theme, &resources, EDITBOX_BACKGROUND_CLICKED_IMAGE_ID);
+
+        let window_background = get_skin_image(theme, &resources, WINDOW_BACKGROUND_IMAGE_ID);
+        let window_border = get_skin_image(theme, &resources, WINDOW_BORDER_IMAGE_ID);
 
         let default = {
             let window_style = root_ui()
                 .style_builder()
                 .background(window_background.image.clone())
-                .background_margin(RectOffset::new(
+                .background_margin(scale_offset(
+                    scale,
                     WINDOW_BG_MARGIN_H,
                     WINDOW_BG_MARGIN_H,
                     WINDOW_BG_MARGIN_V,
                     WINDOW_BG_MARGIN_V,
                 ))
-                .margin(RectOffset::new(
+                .margin(scale_offset(
+                    scale,
                     WINDOW_MARGIN_H - WINDOW_BG_MARGIN_H,
                     WINDOW_MARGIN_H - WINDOW_BG_MARGIN_H,
                     WINDOW_MARGIN_V - WINDOW_BG_MARGIN_V,
@@ -195,36 +276,25 @@ impl SkinCollection {
                 ))
                 .build();
 
-            let button_style = root_ui()
-                .style_builder()
+            let button_style = StyleMetrics::button(scale)
+                .apply(root_ui().style_builder())
                 .background(button_background.image.clone())
                 .background_hovered(button_background_hovered.image.clone())
                 .background_clicked(button_background_clicked.image.clone())
-                .background_margin(RectOffset::new(
-                    BUTTON_BG_MARGIN_H,
-                    BUTTON_BG_MARGIN_H,
-                    BUTTON_BG_MARGIN_V,
-                    BUTTON_BG_MARGIN_V,
-                ))
-                .margin(RectOffset::new(
-                    BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
-                    BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
-                    BUTTON_MARGIN_V - BUTTON_BG_MARGIN_V,
-                    BUTTON_MARGIN_V - BUTTON_BG_MARGIN_V,
-                ))
                 .text_color(TEXT_COLOR)
-                .font_size(BUTTON_FONT_SIZE as u16)
                 .build();
 
             let group_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(
+                .margin(scale_offset(
+                    scale,
                     GROUP_MARGIN_H - GROUP_BG_MARGIN_H,
                     GROUP_MARGIN_H - GROUP_BG_MARGIN_H,
                     GROUP_MARGIN_V - GROUP_BG_MARGIN_V,
                     GROUP_MARGIN_V - GROUP_BG_MARGIN_V,
                 ))
-                .background_margin(RectOffset::new(
+                .background_margin(scale_offset(
+                    scale,
                     GROUP_MARGIN_H,
                     GROUP_MARGIN_H,
                     GROUP_MARGIN_V,
@@ -237,34 +307,37 @@ impl SkinCollection {
 
             let label_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(
+                .margin(scale_offset(
+                    scale,
                     LABEL_MARGIN_H,
                     LABEL_MARGIN_H,
                     LABEL_MARGIN_V,
                     LABEL_MARGIN_V,
                 ))
                 .text_color(TEXT_COLOR)
-                .font_size(FONT_SIZE as u16)
+                .font_size(scale_font_size(FONT_SIZE, scale))
                 .build();
 
             let editbox_style = root_ui()
                 .style_builder()
                 .background(editbox_background.image.clone())
                 .background_clicked(editbox_background_clicked.image.clone())
-                .background_margin(RectOffset::new(
+                .background_margin(scale_offset(
+                    scale,
                     EDITBOX_BG_MARGIN_H,
                     EDITBOX_BG_MARGIN_H,
                     EDITBOX_BG_MARGIN_V,
                     EDITBOX_BG_MARGIN_V,
                 ))
-                .margin(RectOffset::new(
+                .margin(scale_offset(
+                    scale,
                     EDITBOX_MARGIN_H - EDITBOX_BG_MARGIN_H,
                     EDITBOX_MARGIN_H - EDITBOX_BG_MARGIN_H,
                     EDITBOX_MARGIN_V - EDITBOX_BG_MARGIN_V,
                     EDITBOX_MARGIN_V - EDITBOX_BG_MARGIN_V,
                 ))
                 .text_color(TEXT_COLOR)
-                .font_size(FONT_SIZE as u16)
+                .font_size(scale_font_size(FONT_SIZE, scale))
                 .build();
 
             let checkbox_style = root_ui()
@@ -277,13 +350,15 @@ impl SkinCollection {
             let combobox_style = root_ui()
                 .style_builder()
                 .background(combobox_background.image.clone())
-                .background_margin(RectOffset::new(
+                .background_margin(scale_offset(
+                    scale,
                     COMBOBOX_BG_MARGIN_H,
                     COMBOBOX_BG_MARGIN_H,
                     COMBOBOX_BG_MARGIN_V,
                     COMBOBOX_BG_MARGIN_V,
                 ))
-                .margin(RectOffset::new(
+                .margin(scale_offset(
+                    scale,
                     COMBOBOX_MARGIN_H - COMBOBOX_BG_MARGIN_H,
                     COMBOBOX_MARGIN_H - COMBOBOX_BG_MARGIN_H,
                     COMBOBOX_MARGIN_V - COMBOBOX_BG_MARGIN_V,
@@ -291,7 +366,7 @@ impl SkinCollection {
                 ))
                 .text_color(Color::from_rgba(120, 120, 120, 255))
                 .color(Color::from_rgba(210, 210, 210, 255))
-                .font_size(FONT_SIZE as u16)
+                .font_size(scale_font_size(FONT_SIZE, scale))
                 .build();
 
             let scrollbar_style = root_ui()
@@ -307,7 +382,7 @@ impl SkinCollection {
                 .build();
 
             let scroll_multiplier = 10.0;
-            let margin = ELEMENT_MARGIN;
+            let margin = ELEMENT_MARGIN * scale;
 
             Skin {
                 window_style,
@@ -329,13 +404,15 @@ impl SkinCollection {
             let button_style = root_ui()
                 .style_builder()
                 .background(button_background_disabled.image.clone())
-                .background_margin(RectOffset::new(
+                .background_margin(scale_offset(
+                    scale,
                     BUTTON_BG_MARGIN_H,
                     BUTTON_BG_MARGIN_H,
                     BUTTON_BG_MARGIN_V,
                     BUTTON_BG_MARGIN_V,
                 ))
-                .margin(RectOffset::new(
+                .margin(scale_offset(
+                    scale,
                     BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
                     BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
                     BUTTON_MARGIN_V - BUTTON_BG_MARGIN_V,
@@ -344,7 +421,7 @@ impl SkinCollection {
                 .background_hovered(button_background_disabled.image.clone())
                 .background_clicked(button_background_disabled.image.clone())
                 .text_color(Color::from_rgba(88, 88, 88, 255))
-                .font_size(BUTTON_FONT_SIZE as u16)
+                .font_size(scale_font_size(BUTTON_FONT_SIZE, scale))
                 .build();
 
             Skin {
@@ -356,10 +433,10 @@ impl SkinCollection {
         let window_header = {
             let label_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(8.0, 8.0, 4.0, 16.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 8.0, 8.0, 4.0, 16.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .text_color(TEXT_COLOR)
-                .font_size(HEADER_FONT_SIZE as u16)
+                .font_size(scale_font_size(HEADER_FONT_SIZE, scale))
                 .build();
 
             Skin {
@@ -374,7 +451,7 @@ impl SkinCollection {
                 .background(checkbox_background.image.clone())
                 .background_hovered(checkbox_background_hovered.image.clone())
                 .background_clicked(checkbox_background_clicked.image.clone())
-                .background_margin(RectOffset::new(0.0, 0.0, 4.0, 4.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 4.0, 4.0))
                 .build();
 
             let scrollbar_style = root_ui()
@@ -413,7 +490,7 @@ impl SkinCollection {
                 .background(checkbox_background_checked.image.clone())
                 .background_hovered(checkbox_background_checked_hovered.image.clone())
                 .background_clicked(checkbox_background_clicked.image.clone())
-                .background_margin(RectOffset::new(0.0, 0.0, 4.0, 4.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 4.0, 4.0))
                 .build();
 
             Skin {
@@ -425,10 +502,10 @@ impl SkinCollection {
         let label_button = {
             let button_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(0.0, 0.0, 4.0, 4.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 0.0, 0.0, 4.0, 4.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .text_color(TEXT_COLOR)
-                .font_size(FONT_SIZE as u16)
+                .font_size(scale_font_size(FONT_SIZE, scale))
                 .color(NO_COLOR)
                 .color_hovered(NO_COLOR)
                 .color_clicked(NO_COLOR)
@@ -443,16 +520,16 @@ impl SkinCollection {
         let list_box = {
             let label_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(8.0, 8.0, 4.0, 4.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 8.0, 8.0, 4.0, 4.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .text_color(TEXT_COLOR)
-                .font_size(16)
+                .font_size(scale_font_size(16.0, scale))
                 .build();
 
             let button_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .color(Color::from_rgba(58, 68, 68, 255))
                 .color_hovered(Color::from_rgba(58, 68, 102, 255))
                 .color_clicked(Color::from_rgba(58, 68, 68, 255))
@@ -482,16 +559,16 @@ impl SkinCollection {
         let list_box_selected = {
             let label_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(8.0, 8.0, 4.0, 4.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 8.0, 8.0, 4.0, 4.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .text_color(TEXT_COLOR)
-                .font_size(16)
+                .font_size(scale_font_size(16.0, scale))
                 .build();
 
             let button_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .color(Color::from_rgba(58, 68, 102, 255))
                 .color_hovered(Color::from_rgba(58, 68, 102, 255))
                 .color_clicked(Color::from_rgba(58, 68, 102, 255))
@@ -507,8 +584,8 @@ impl SkinCollection {
         let list_box_no_bg = {
             let button_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .color(Color::from_rgba(0, 0, 0, 0))
                 .color_hovered(Color::from_rgba(58, 68, 102, 255))
                 .color_clicked(Color::from_rgba(58, 68, 68, 255))
@@ -523,8 +600,8 @@ impl SkinCollection {
         let context_menu = {
             let button_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .color(Color::from_rgba(38, 43, 68, 255))
                 .color_hovered(Color::from_rgba(38, 43, 102, 255))
                 .color_clicked(Color::from_rgba(38, 43, 68, 255))
@@ -573,8 +650,8 @@ impl SkinCollection {
         let toolbar_bg = {
             let button_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .color(Color::from_rgba(58, 68, 68, 255))
                 .color_hovered(Color::from_rgba(58, 68, 68, 255))
                 .color_clicked(Color::from_rgba(58, 68, 68, 255))
@@ -589,16 +666,16 @@ impl SkinCollection {
         let toolbar_header_bg = {
             let label_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(8.0, 8.0, 4.0, 4.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 8.0, 8.0, 4.0, 4.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .text_color(TEXT_COLOR)
-                .font_size(18)
+                .font_size(scale_font_size(18.0, scale))
                 .build();
 
             let button_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .color(Color::from_rgba(38, 43, 68, 255))
                 .color_hovered(Color::from_rgba(38, 43, 68, 255))
                 .color_clicked(Color::from_rgba(38, 43, 68, 255))
@@ -617,20 +694,22 @@ impl SkinCollection {
                 .background(button_background.image.clone())
                 .background_hovered(button_background_hovered.image.clone())
                 .background_clicked(button_background_clicked.image.clone())
-                .background_margin(RectOffset::new(
+                .background_margin(scale_offset(
+                    scale,
                     BUTTON_BG_MARGIN_H,
                     BUTTON_BG_MARGIN_H,
                     BUTTON_BG_MARGIN_V,
                     BUTTON_BG_MARGIN_V,
                 ))
-                .margin(RectOffset::new(
+                .margin(scale_offset(
+                    scale,
                     SMALL_BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
                     SMALL_BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
                     SMALL_BUTTON_MARGIN_V - BUTTON_BG_MARGIN_V,
                     SMALL_BUTTON_MARGIN_V - BUTTON_BG_MARGIN_V,
                 ))
                 .text_color(TEXT_COLOR)
-                .font_size(SMALL_BUTTON_FONT_SIZE as u16)
+                .font_size(scale_font_size(SMALL_BUTTON_FONT_SIZE, scale))
                 .build();
 
             Skin {
@@ -645,20 +724,22 @@ impl SkinCollection {
                 .background(button_background_disabled.image.clone())
                 .background_hovered(button_background_disabled.image.clone())
                 .background_clicked(button_background_disabled.image.clone())
-                .background_margin(RectOffset::new(
+                .background_margin(scale_offset(
+                    scale,
                     BUTTON_BG_MARGIN_H,
                     BUTTON_BG_MARGIN_H,
                     BUTTON_BG_MARGIN_V,
                     BUTTON_BG_MARGIN_V,
                 ))
-                .margin(RectOffset::new(
+                .margin(scale_offset(
+                    scale,
                     SMALL_BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
                     SMALL_BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
                     SMALL_BUTTON_MARGIN_V - BUTTON_BG_MARGIN_V,
                     SMALL_BUTTON_MARGIN_V - BUTTON_BG_MARGIN_V,
                 ))
                 .text_color(TEXT_COLOR)
-                .font_size(SMALL_BUTTON_FONT_SIZE as u16)
+                .font_size(scale_font_size(SMALL_BUTTON_FONT_SIZE, scale))
                 .build();
 
             Skin {
@@ -684,8 +765,8 @@ impl SkinCollection {
         let tool_selector_selected = {
             let button_style = root_ui()
                 .style_builder()
-                .background_margin(RectOffset::new(2.0, 2.0, 2.0, 2.0))
-                .margin(RectOffset::new(6.0, 6.0, 6.0, 6.0))
+                .background_margin(scale_offset(scale, 2.0, 2.0, 2.0, 2.0))
+                .margin(scale_offset(scale, 6.0, 6.0, 6.0, 6.0))
                 .color(Color::from_rgba(58, 68, 102, 255))
                 .color_hovered(Color::from_rgba(58, 68, 102, 255))
                 .color_clicked(Color::from_rgba(58, 68, 102, 255))
@@ -700,8 +781,8 @@ impl SkinCollection {
         let tileset_grid = {
             let button_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .color(Color::from_rgba(0, 0, 0, 0))
                 .color_hovered(Color::from_rgba(38, 43, 102, 180))
                 .color_clicked(Color::from_rgba(0, 0, 0, 0))
@@ -716,8 +797,8 @@ impl SkinCollection {
         let tileset_grid_selected = {
             let button_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .color(Color::from_rgba(38, 43, 68, 180))
                 .color_hovered(Color::from_rgba(38, 43, 68, 180))
                 .color_clicked(Color::from_rgba(38, 43, 68, 180))
@@ -732,8 +813,8 @@ impl SkinCollection {
         let tileset_subtile_grid = {
             let button_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .color(Color::from_rgba(0, 0, 0, 0))
                 .color_hovered(Color::from_rgba(98, 43, 38, 200))
                 .color_clicked(Color::from_rgba(0, 0, 0, 0))
@@ -748,8 +829,8 @@ impl SkinCollection {
         let tileset_subtile_grid_selected = {
             let button_style = root_ui()
                 .style_builder()
-                .margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
-                .background_margin(RectOffset::new(0.0, 0.0, 0.0, 0.0))
+                .margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
+                .background_margin(scale_offset(scale, 0.0, 0.0, 0.0, 0.0))
                 .color(Color::from_rgba(98, 43, 38, 200))
                 .color_hovered(Color::from_rgba(98, 43, 38, 200))
                 .color_clicked(Color::from_rgba(98, 43, 38, 200))
@@ -767,7 +848,7 @@ impl SkinCollection {
             let label_style = root_ui()
                 .style_builder()
                 .text_color(TEXT_COLOR)
-                .font_size(HEADER_FONT_SIZE as u16)
+                .font_size(scale_font_size(HEADER_FONT_SIZE, scale))
                 .build();
 
             Skin {
@@ -779,13 +860,15 @@ impl SkinCollection {
         let menu_selected = {
             let button_style = root_ui()
                 .style_builder()
-                .background_margin(RectOffset::new(
+                .background_margin(scale_offset(
+                    scale,
                     BUTTON_BG_MARGIN_H,
                     BUTTON_BG_MARGIN_H,
                     BUTTON_BG_MARGIN_V,
                     BUTTON_BG_MARGIN_V,
                 ))
-                .margin(RectOffset::new(
+                .margin(scale_offset(
+                    scale,
                     BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
                     BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
                     BUTTON_MARGIN_V - BUTTON_BG_MARGIN_V,
@@ -795,7 +878,7 @@ impl SkinCollection {
                 .background_hovered(button_background_hovered.image.clone())
                 .background_clicked(button_background_clicked.image.clone())
                 .text_color(TEXT_COLOR)
-                .font_size(BUTTON_FONT_SIZE as u16)
+                .font_size(scale_font_size(BUTTON_FONT_SIZE, scale))
                 .build();
 
             Skin {
@@ -807,13 +890,15 @@ impl SkinCollection {
         let menu_disabled = {
             let button_style = root_ui()
                 .style_builder()
-                .background_margin(RectOffset::new(
+                .background_margin(scale_offset(
+                    scale,
                     BUTTON_BG_MARGIN_H,
                     BUTTON_BG_MARGIN_H,
                     BUTTON_BG_MARGIN_V,
                     BUTTON_BG_MARGIN_V,
                 ))
-                .margin(RectOffset::new(
+                .margin(scale_offset(
+                    scale,
                     BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
                     BUTTON_MARGIN_H - BUTTON_BG_MARGIN_H,
                     BUTTON_MARGIN_V - BUTTON_BG_MARGIN_V,
@@ -823,7 +908,7 @@ impl SkinCollection {
                 .background_hovered(button_background_disabled.image.clone())
                 .background_clicked(button_background_disabled.image.clone())
                 .text_color(TEXT_COLOR)
-                .font_size(BUTTON_FONT_SIZE as u16)
+                .font_size(scale_font_size(BUTTON_FONT_SIZE, scale))
                 .build();
 
             Skin {
@@ -845,7 +930,7 @@ impl SkinCollection {
                 .background(window_background.image.clone())
                 .background_hovered(window_background.image.clone())
                 .background_clicked(window_background.image.clone())
-                .background_margin(RectOffset::new(52.0, 52.0, 52.0, 52.0))
+                .background_margin(scale_offset(scale, 52.0, 52.0, 52.0, 52.0))
                 .build();
 
             Skin {
@@ -868,7 +953,7 @@ impl SkinCollection {
                 .background(window_border.image.clone())
                 .background_hovered(window_border.image.clone())
                 .background_clicked(window_border.image.clone())
-                .background_margin(RectOffset::new(52.0, 52.0, 52.0, 52.0))
+                .background_margin(scale_offset(scale, 52.0, 52.0, 52.0, 52.0))
                 .build();
 
             Skin {
@@ -882,13 +967,13 @@ impl SkinCollection {
             let button_style = root_ui()
                 .style_builder()
                 .background(window_border.image.clone())
-                .background_margin(RectOffset::new(52.0, 52.0, 52.0, 52.0))
-                .margin(RectOffset::new(-40.0, -40.0, -40.0, -40.0))
+                .background_margin(scale_offset(scale, 52.0, 52.0, 52.0, 52.0))
+                .margin(scale_offset(scale, -40.0, -40.0, -40.0, -40.0))
                 .background_hovered(window_border.image.clone())
                 .background_clicked(window_border.image.clone())
                 .text_color(TEXT_COLOR)
                 .reverse_background_z(true)
-                .font_size(45)
+                .font_size(scale_font_size(45.0, scale))
                 .build();
 
             Skin {
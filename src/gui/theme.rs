@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use macroquad::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+use core::data::deserialize_json_bytes;
+use core::error::ErrorKind;
+use core::text::ToStringHelper;
+use core::{formaterr, Result};
+
+use crate::resources::{ImageMetadata, ImageResource};
+
+use super::SKIN_IMAGE_IDS;
+
+/// Themes are loaded from sub directories of this directory, in the assets directory. The name of
+/// the sub directory is the id of the theme.
+pub const THEMES_DIR: &str = "ui/themes";
+
+/// This is the id of the default theme, which must supply all the skin images
+pub const DEFAULT_THEME_ID: &str = "default";
+
+const THEME_FILE: &str = "theme";
+const THEME_FILE_EXTENSION: &str = "json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GuiThemeMetadata {
+    #[serde(default)]
+    pub name: String,
+    /// The images of the theme. Paths are relative to the directory of the theme.
+    pub images: Vec<ImageMetadata>,
+}
+
+/// A set of images that replace the default images that the menu skins are built from. Any image
+/// that is not supplied by the theme will fall back to the default image with the same id.
+#[derive(Debug, Clone, Default)]
+pub struct GuiTheme {
+    pub id: String,
+    pub images: HashMap<String, ImageResource>,
+}
+
+impl GuiTheme {
+    /// Load the theme with the specified id, from the themes directory in `assets_dir`
+    pub async fn load<P: AsRef<Path>>(assets_dir: P, id: &str) -> Result<GuiTheme> {
+        let theme_dir = assets_dir.as_ref().join(THEMES_DIR).join(id);
+
+        let theme_file_path = theme_dir
+            .join(THEME_FILE)
+            .with_extension(THEME_FILE_EXTENSION);
+
+        let bytes = load_file(&theme_file_path.to_string_helper()).await?;
+        let meta: GuiThemeMetadata = deserialize_json_bytes(&bytes)?;
+
+        let mut images = HashMap::new();
+
+        for meta in meta.images {
+            if !SKIN_IMAGE_IDS.contains(&meta.id.as_str()) {
+                return Err(formaterr!(
+                    ErrorKind::Parsing,
+                    "Theme '{}' has an image with the unknown id '{}'",
+                    id,
+                    &meta.id
+                ));
+            }
+
+            let file_path = theme_dir.join(&meta.path);
+
+            let image = load_image(&file_path.to_string_helper()).await?;
+
+            let meta = ImageMetadata {
+                size: vec2(image.width() as f32, image.height() as f32),
+                ..meta
+            };
+
+            images.insert(meta.id.clone(), ImageResource { image, meta });
+        }
+
+        Ok(GuiTheme {
+            id: id.to_string(),
+            images,
+        })
+    }
+
+    /// Returns the ids of the skin images that this theme does not supply
+    pub fn get_missing_images(&self) -> Vec<&'static str> {
+        SKIN_IMAGE_IDS
+            .iter()
+            .filter(|id| !self.images.contains_key(**id))
+            .copied()
+            .collect()
+    }
+
+    /// Returns an error if this theme does not supply all the skin images. This is required of the
+    /// default theme, as that is what other themes fall back to.
+    pub fn validate(&self) -> Result<()> {
+        let missing = self.get_missing_images();
+
+        if !missing.is_empty() {
+            return Err(formaterr!(
+                ErrorKind::Parsing,
+                "Theme '{}' does not supply the images: {}",
+                &self.id,
+                missing.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the theme image with the specified id, or the image from `defaults`, if the theme
+    /// does not supply it
+    pub fn get_image<'a>(
+        &'a self,
+        defaults: &'a HashMap<String, ImageResource>,
+        id: &str,
+    ) -> Option<&'a ImageResource> {
+        self.images.get(id).or_else(|| defaults.get(id))
+    }
+}
+
+/// Load the theme with the specified id, or the default theme, if no id is specified. If any other
+/// theme than the default can not be loaded, this is reported and the default theme is returned in
+/// stead. Images that other themes do not supply will fall back to the default images.
+pub async fn load_gui_theme(assets_dir: &str, id: Option<&str>) -> Result<GuiTheme> {
+    if let Some(id) = id.filter(|&id| id != DEFAULT_THEME_ID) {
+        match GuiTheme::load(assets_dir, id).await {
+            Ok(theme) => return Ok(theme),
+            Err(err) => println!(
+                "WARNING: Unable to load theme '{}', using the default theme: {}",
+                id, err
+            ),
+        }
+    }
+
+    let theme = GuiTheme::load(assets_dir, DEFAULT_THEME_ID).await?;
+    theme.validate()?;
+
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image_resource(id: &str) -> ImageResource {
+        ImageResource {
+            image: Image::gen_image_color(1, 1, WHITE),
+            meta: ImageMetadata {
+                id: id.to_string(),
+                path: format!("{}.png", id),
+                size: vec2(1.0, 1.0),
+            },
+        }
+    }
+
+    #[test]
+    fn test_missing_theme_images_fall_back() {
+        let defaults = SKIN_IMAGE_IDS
+            .iter()
+            .map(|id| (id.to_string(), image_resource(id)))
+            .collect::<HashMap<_, _>>();
+
+        let mut theme = GuiTheme {
+            id: "test".to_string(),
+            images: HashMap::new(),
+        };
+
+        let mut replacement = image_resource("button_background");
+        replacement.meta.path = "themed_button_background.png".to_string();
+        theme
+            .images
+            .insert("button_background".to_string(), replacement);
+
+        let missing = theme.get_missing_images();
+        assert_eq!(missing.len(), SKIN_IMAGE_IDS.len() - 1);
+        assert!(!missing.contains(&"button_background"));
+
+        // Only the default theme is required to supply all the images
+        assert!(theme.validate().is_err());
+
+        for id in SKIN_IMAGE_IDS {
+            let image = theme.get_image(&defaults, id).unwrap();
+
+            if *id == "button_background" {
+                assert_eq!(image.meta.path, "themed_button_background.png");
+            } else {
+                assert_eq!(image.meta.path, format!("{}.png", id));
+            }
+        }
+    }
+}
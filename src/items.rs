@@ -1,7 +1,9 @@
 //! Things available to spawn from the level editor
 //! Proto-mods, eventually some of the items will move to some sort of a wasm runtime
 
-use hecs::{Entity, World};
+use std::f32::consts::PI;
+
+use hecs::{Entity, With, World, Without};
 use macroquad::audio::{play_sound, PlaySoundParams, Sound};
 use macroquad::experimental::collections::storage;
 use macroquad::prelude::*;
@@ -11,16 +13,19 @@ use serde::{Deserialize, Serialize};
 use crate::game::sound::SOUND_EFFECT_VOLUME;
 use crate::utils::timer::Timer;
 use crate::{
-    ActiveEffectMetadata, AnimatedSprite, AnimatedSpriteMetadata, CollisionWorld, Drawable, Owner,
-    PassiveEffectMetadata, PhysicsBody, QueuedAnimationAction, Resources,
+    ActiveEffectKind, ActiveEffectMetadata, AnimatedSprite, AnimatedSpriteMetadata, CollisionWorld,
+    Config, Drawable, Owner, PassiveEffectMetadata, PhysicsBody, QueuedAnimationAction, Resources,
 };
 
 use core::{Result, Transform};
 
 use crate::effects::active::spawn_active_effect;
-use crate::particles::{ParticleEmitter, ParticleEmitterMetadata};
+use crate::game::play_sound_effect;
+use crate::particles::{ParticleEmitter, ParticleEmitterMetadata, Particles};
 use crate::physics::PhysicsBodyParams;
-use crate::player::{Player, PlayerInventory, IDLE_ANIMATION_ID};
+use crate::player::{
+    on_player_damage, Player, PlayerAnimator, PlayerInventory, PlayerState, IDLE_ANIMATION_ID,
+};
 
 pub const ITEMS_DRAW_ORDER: u32 = 1;
 
@@ -173,6 +178,10 @@ pub struct MapItemMetadata {
     /// The parameters for the `AnimationPlayer` that will be used to draw the item
     #[serde(alias = "animation")]
     pub sprite: AnimatedSpriteMetadata,
+    /// If specified, the item will bob up and down while it is lying on the ground, to make it
+    /// more visible
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_bob: Option<ItemBobMetadata>,
 }
 
 fn default_respawn_delay() -> Option<f32> {
@@ -227,6 +236,10 @@ pub fn spawn_item(world: &mut World, position: Vec2, meta: MapItemMetadata) -> R
 
     let name = meta.name.clone();
 
+    if let Some(idle_bob) = meta.idle_bob {
+        world.insert_one(entity, ItemBob::new(idle_bob, position))?;
+    }
+
     match meta.kind {
         MapItemKind::Item { meta } => {
             let ItemMetadata {
@@ -296,6 +309,11 @@ pub fn spawn_item(world: &mut World, position: Vec2, meta: MapItemMetadata) -> R
                 drop_behavior,
                 deplete_behavior,
                 respawn_info,
+                break_on_empty: meta.break_on_empty,
+                break_particle_effects: meta.break_particle_effects,
+                break_sound_effect_id: meta.break_sound_effect_id,
+                charge: meta.charge,
+                player_animation_id: meta.player_animation_id,
             };
 
             world.insert_one(
@@ -325,6 +343,11 @@ pub struct WeaponParams {
     pub drop_behavior: ItemDropBehavior,
     pub deplete_behavior: ItemDepleteBehavior,
     pub respawn_info: Option<RespawnInfo>,
+    pub break_on_empty: bool,
+    pub break_particle_effects: Vec<String>,
+    pub break_sound_effect_id: Option<String>,
+    pub charge: Option<WeaponChargeMetadata>,
+    pub player_animation_id: Option<String>,
 }
 
 impl Default for WeaponParams {
@@ -339,6 +362,11 @@ impl Default for WeaponParams {
             drop_behavior: Default::default(),
             deplete_behavior: Default::default(),
             respawn_info: None,
+            break_on_empty: false,
+            break_particle_effects: Vec::new(),
+            break_sound_effect_id: None,
+            charge: None,
+            player_animation_id: None,
         }
     }
 }
@@ -360,6 +388,14 @@ pub struct Weapon {
     pub cooldown_timer: f32,
     pub use_cnt: u32,
     pub respawn_info: Option<RespawnInfo>,
+    pub break_on_empty: bool,
+    pub break_particle_effects: Vec<String>,
+    pub break_sound_effect_id: Option<String>,
+    pub charge: Option<WeaponChargeMetadata>,
+    pub charge_emitters: Vec<ParticleEmitter>,
+    pub charge_timer: f32,
+    pub is_charging: bool,
+    pub player_animation_id: Option<String>,
 }
 
 impl Weapon {
@@ -384,12 +420,55 @@ impl Weapon {
             drop_behavior: params.drop_behavior,
             deplete_behavior: params.deplete_behavior,
             respawn_info: params.respawn_info,
+            break_on_empty: params.break_on_empty,
+            break_particle_effects: params.break_particle_effects,
+            break_sound_effect_id: params.break_sound_effect_id,
+            charge_emitters: params
+                .charge
+                .as_ref()
+                .map(|charge| {
+                    charge
+                        .particles
+                        .iter()
+                        .cloned()
+                        .map(ParticleEmitter::new)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            charge: params.charge,
+            charge_timer: 0.0,
+            is_charging: false,
+            player_animation_id: params.player_animation_id,
             cooldown_timer: cooldown,
             use_cnt: 0,
         }
     }
 }
 
+/// This will emit the debris particles and play the sound effect of a breaking weapon, at the
+/// specified position. Despawning the weapon is left to the caller.
+pub fn break_weapon(weapon: &Weapon, position: Vec2) {
+    {
+        let mut particles = storage::get_mut::<Particles>();
+
+        for particle_effect_id in &weapon.break_particle_effects {
+            if let Some(cache) = particles.cache_map.get_mut(particle_effect_id) {
+                cache.spawn(position);
+            } else {
+                #[cfg(debug_assertions)]
+                println!(
+                    "WARNING: Invalid particle effect id '{}'",
+                    particle_effect_id
+                );
+            }
+        }
+    }
+
+    if let Some(sound_effect_id) = &weapon.break_sound_effect_id {
+        play_sound_effect(sound_effect_id, 1.0);
+    }
+}
+
 pub fn fire_weapon(world: &mut World, entity: Entity, owner: Entity) -> Result<()> {
     let mut effects = Vec::new();
 
@@ -427,6 +506,13 @@ pub fn fire_weapon(world: &mut World, entity: Entity, owner: Entity) -> Result<(
 
             player.attack_timer = weapon.attack_duration;
 
+            if let Some(animation_id) = &weapon.player_animation_id {
+                let mut animator = world.get_mut::<PlayerAnimator>(owner).unwrap();
+
+                let duration = Some(weapon.attack_duration).filter(|&duration| duration > 0.0);
+                animator.set_override(animation_id, duration);
+            }
+
             weapon.use_cnt += 1;
 
             weapon.cooldown_timer = 0.0;
@@ -476,6 +562,17 @@ pub fn fire_weapon(world: &mut World, entity: Entity, owner: Entity) -> Result<(
             }
 
             effects = weapon.effects.clone();
+
+            if let Some(charge) = &weapon.charge {
+                let damage = charge.damage(weapon.charge_timer);
+                let speed_factor = charge.speed_factor(weapon.charge_timer);
+
+                for meta in effects.iter_mut() {
+                    charge.apply(meta, damage, speed_factor);
+                }
+            }
+
+            weapon.charge_timer = 0.0;
         }
     }
 
@@ -551,6 +648,118 @@ pub struct WeaponMetadata {
     /// specified.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub effect_sprite: Option<AnimatedSpriteMetadata>,
+    /// If this is `true`, the weapon will break when its `uses` are depleted, in stead of
+    /// following its `deplete_behavior`. A weapon without `uses` will never break.
+    #[serde(default, skip_serializing_if = "core::json::is_false")]
+    pub break_on_empty: bool,
+    /// The ids of the particle effects that will be emitted, as debris, when the weapon breaks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub break_particle_effects: Vec<String>,
+    /// This can specify an id of a sound effect that is played when the weapon breaks
+    #[serde(
+        default,
+        rename = "break_sound_effect",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub break_sound_effect_id: Option<String>,
+    /// If this is specified, the weapon will charge while the fire button is held and fire when
+    /// it is released, with the strength of the attack depending on how long it was held
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charge: Option<WeaponChargeMetadata>,
+    /// This can specify the id of a player animation that is played, in stead of the one picked
+    /// by the player animation state machine, for the `attack_duration`, when the weapon is used
+    #[serde(
+        default,
+        rename = "player_animation",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub player_animation_id: Option<String>,
+}
+
+/// This holds the parameters of a weapon that is charged by holding the fire button.
+/// As players have a single hit point, an attack is only lethal if its damage is at least `1.0`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeaponChargeMetadata {
+    /// The amount of time the fire button must be held before the charge starts to build. If
+    /// released before this, a weak attack, using the minimum values, will be made.
+    #[serde(default)]
+    pub min_charge_time: f32,
+    /// The amount of time the fire button must be held for the attack to be fully charged
+    pub max_charge_time: f32,
+    #[serde(default)]
+    pub min_damage: f32,
+    #[serde(default = "WeaponChargeMetadata::default_max_damage")]
+    pub max_damage: f32,
+    /// The factor that the speed of projectiles will be multiplied by, at minimum charge
+    #[serde(default = "WeaponChargeMetadata::default_min_speed_factor")]
+    pub min_speed_factor: f32,
+    /// The factor that the speed of projectiles will be multiplied by, at maximum charge
+    #[serde(default = "WeaponChargeMetadata::default_max_speed_factor")]
+    pub max_speed_factor: f32,
+    /// Particle effects that will be emitted from the weapon while it is charging
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub particles: Vec<ParticleEmitterMetadata>,
+}
+
+impl WeaponChargeMetadata {
+    const DEFAULT_MAX_DAMAGE: f32 = 1.0;
+    const DEFAULT_MIN_SPEED_FACTOR: f32 = 0.5;
+    const DEFAULT_MAX_SPEED_FACTOR: f32 = 1.0;
+
+    pub fn default_max_damage() -> f32 {
+        Self::DEFAULT_MAX_DAMAGE
+    }
+
+    pub fn default_min_speed_factor() -> f32 {
+        Self::DEFAULT_MIN_SPEED_FACTOR
+    }
+
+    pub fn default_max_speed_factor() -> f32 {
+        Self::DEFAULT_MAX_SPEED_FACTOR
+    }
+
+    /// Returns the charge progress, as a number between 0 and 1, for the specified hold time
+    pub fn progress(&self, hold_time: f32) -> f32 {
+        if hold_time < self.min_charge_time {
+            0.0
+        } else if self.max_charge_time <= self.min_charge_time {
+            1.0
+        } else {
+            ((hold_time - self.min_charge_time) / (self.max_charge_time - self.min_charge_time))
+                .clamp(0.0, 1.0)
+        }
+    }
+
+    pub fn damage(&self, hold_time: f32) -> f32 {
+        let progress = self.progress(hold_time);
+        self.min_damage + (self.max_damage - self.min_damage) * progress
+    }
+
+    pub fn speed_factor(&self, hold_time: f32) -> f32 {
+        let progress = self.progress(hold_time);
+        self.min_speed_factor + (self.max_speed_factor - self.min_speed_factor) * progress
+    }
+
+    /// Modify the specified effect according to the specified damage and speed factor
+    pub fn apply(&self, meta: &mut ActiveEffectMetadata, damage: f32, speed_factor: f32) {
+        let is_damage_lethal = damage >= 1.0;
+
+        match meta.kind.as_mut() {
+            ActiveEffectKind::Projectile {
+                speed, is_lethal, ..
+            } => {
+                *speed *= speed_factor;
+                *is_lethal = *is_lethal && is_damage_lethal;
+            }
+            ActiveEffectKind::CircleCollider { is_lethal, .. }
+            | ActiveEffectKind::RectCollider { is_lethal, .. }
+            | ActiveEffectKind::ArcCollider { is_lethal, .. } => {
+                *is_lethal = *is_lethal && is_damage_lethal;
+            }
+            _ => {}
+        }
+    }
 }
 
 impl Default for WeaponMetadata {
@@ -565,10 +774,281 @@ impl Default for WeaponMetadata {
             attack_duration: 0.0,
             recoil: 0.0,
             effect_sprite: None,
+            break_on_empty: false,
+            break_particle_effects: Vec::new(),
+            break_sound_effect_id: None,
+            charge: None,
+            player_animation_id: None,
+        }
+    }
+}
+
+/// This holds the parameters of the idle animation of an item that is lying on the ground
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemBobMetadata {
+    /// The distance, in pixels, that the item will move up and down, from its resting position
+    #[serde(default = "ItemBobMetadata::default_amplitude")]
+    pub amplitude: f32,
+    /// The number of bob cycles per second
+    #[serde(default = "ItemBobMetadata::default_speed")]
+    pub speed: f32,
+    /// If specified, a pulsing glow of this color will be drawn behind the item
+    #[serde(
+        default,
+        with = "core::json::color_opt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub glow: Option<Color>,
+}
+
+impl ItemBobMetadata {
+    const DEFAULT_AMPLITUDE: f32 = 2.0;
+    const DEFAULT_SPEED: f32 = 0.75;
+
+    pub fn default_amplitude() -> f32 {
+        Self::DEFAULT_AMPLITUDE
+    }
+
+    pub fn default_speed() -> f32 {
+        Self::DEFAULT_SPEED
+    }
+}
+
+impl Default for ItemBobMetadata {
+    fn default() -> Self {
+        ItemBobMetadata {
+            amplitude: Self::DEFAULT_AMPLITUDE,
+            speed: Self::DEFAULT_SPEED,
+            glow: None,
+        }
+    }
+}
+
+/// This is a purely cosmetic component, that makes items bob up and down, while lying on the
+/// ground. It is applied through the offset of the items `Drawable` and is cancelled when the item
+/// is picked up.
+#[derive(Debug, Clone)]
+pub struct ItemBob {
+    pub amplitude: f32,
+    pub speed: f32,
+    pub glow: Option<Color>,
+    /// The phase is derived from the spawn position, so that items placed next to each other will
+    /// not bob in unison, while still being the same on every client
+    pub phase: f32,
+    pub elapsed: f32,
+}
+
+impl ItemBob {
+    pub fn new(meta: ItemBobMetadata, spawn_position: Vec2) -> Self {
+        let hash = (spawn_position.x * 12.9898 + spawn_position.y * 78.233).sin() * 43758.547;
+        let phase = hash.fract().abs() * PI * 2.0;
+
+        ItemBob {
+            amplitude: meta.amplitude,
+            speed: meta.speed,
+            glow: meta.glow,
+            phase,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Returns a value between `-1.0` and `1.0`, representing the current point in the bob cycle
+    pub fn cycle(&self) -> f32 {
+        (self.phase + self.elapsed * self.speed * PI * 2.0).sin()
+    }
+
+    pub fn offset(&self) -> Vec2 {
+        vec2(0.0, self.cycle() * self.amplitude)
+    }
+}
+
+pub fn update_item_bobs(world: &mut World) {
+    let dt = get_frame_time();
+
+    for (_, (bob, drawable, owner)) in
+        world.query_mut::<(&mut ItemBob, &mut Drawable, Option<&Owner>)>()
+    {
+        if owner.is_some() {
+            bob.elapsed = 0.0;
+            drawable.offset = Vec2::ZERO;
+        } else {
+            bob.elapsed += dt;
+            drawable.offset = bob.offset();
+        }
+    }
+}
+
+pub fn draw_item_glows(world: &mut World) {
+    for (_, (bob, transform, body)) in world
+        .query::<Without<Owner, (&ItemBob, &Transform, &PhysicsBody)>>()
+        .iter()
+    {
+        if let Some(mut color) = bob.glow {
+            if body.is_deactivated {
+                continue;
+            }
+
+            let rect = body.as_rect(transform.position);
+            let center = rect.point() + rect.size() / 2.0 + bob.offset();
+            let radius = rect.w.max(rect.h) * 0.75;
+
+            color.a *= 0.5 + bob.cycle() * 0.25;
+
+            draw_circle(center.x, center.y, radius, color);
+        }
+    }
+}
+
+/// This is added to items that are spawned during gameplay, as opposed to being placed on the map,
+/// so that they can be despawned, if they are left lying on the ground, as specified by the
+/// `MatchConfig`, to avoid clutter.
+#[derive(Debug, Clone, Default)]
+pub struct ItemDespawnTimer {
+    pub elapsed: f32,
+    pub is_blinking: bool,
+}
+
+const ITEM_DESPAWN_BLINK_RATE: f32 = 8.0;
+
+fn set_item_sprite_visibility(drawable: &mut Drawable, is_visible: bool) {
+    if let Some(sprite_set) = drawable.get_animated_sprite_set_mut() {
+        if let Some(sprite) = sprite_set.map.get_mut(SPRITE_ANIMATED_SPRITE_ID) {
+            sprite.is_deactivated = !is_visible;
+        }
+    }
+}
+
+/// This should only be run on the host, as it is authoritative on item despawns
+pub fn update_item_despawn_timers(world: &mut World) {
+    let (delay, warning, radius) = {
+        let config = storage::get::<Config>();
+        let settings = &config.match_settings;
+
+        match settings.item_despawn_delay {
+            Some(delay) => (
+                delay,
+                settings.item_despawn_warning,
+                settings.item_despawn_radius,
+            ),
+            None => return,
+        }
+    };
+
+    let dt = get_frame_time();
+
+    let player_positions = world
+        .query::<With<Player, &Transform>>()
+        .iter()
+        .map(|(_, transform)| transform.position)
+        .collect::<Vec<_>>();
+
+    let mut to_despawn = Vec::new();
+
+    for (entity, (timer, transform, drawable, owner)) in world.query_mut::<Without<
+        RespawningItem,
+        (
+            &mut ItemDespawnTimer,
+            &Transform,
+            &mut Drawable,
+            Option<&Owner>,
+        ),
+    >>() {
+        let is_player_nearby = player_positions
+            .iter()
+            .any(|position| position.distance(transform.position) <= radius);
+
+        if owner.is_some() || is_player_nearby {
+            timer.elapsed = 0.0;
+        } else {
+            timer.elapsed += dt;
+        }
+
+        if timer.elapsed >= delay {
+            to_despawn.push(entity);
+        } else if timer.elapsed >= delay - warning {
+            timer.is_blinking = true;
+
+            let is_visible = ((delay - timer.elapsed) * ITEM_DESPAWN_BLINK_RATE) as u32 % 2 == 0;
+            set_item_sprite_visibility(drawable, is_visible);
+        } else if timer.is_blinking {
+            timer.is_blinking = false;
+
+            set_item_sprite_visibility(drawable, true);
+        }
+    }
+
+    for entity in to_despawn {
+        if let Err(err) = world.despawn(entity) {
+            #[cfg(debug_assertions)]
+            println!("WARNING: {}", err);
         }
     }
 }
 
+/// This is added to items that are thrown by a player, if thrown item impacts are enabled in the
+/// `MatchConfig`. It is removed when the item slows down below the impact speed, or hits a player.
+#[derive(Debug, Clone, Copy)]
+pub struct ThrownItem {
+    pub thrower: Entity,
+}
+
+/// This should only be run on the host, as it is authoritative on player damage
+pub fn fixed_update_thrown_items(world: &mut World) {
+    let min_speed = {
+        let config = storage::get::<Config>();
+        config.match_settings.thrown_item_impact_speed
+    };
+
+    let mut to_remove = Vec::new();
+    let mut to_damage = Vec::new();
+
+    let players = world
+        .query::<(&Player, &Transform, &PhysicsBody)>()
+        .iter()
+        .filter_map(|(e, (player, transform, body))| {
+            if player.state == PlayerState::Dead {
+                None
+            } else {
+                Some((e, body.as_rect(transform.position)))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for (entity, (thrown, transform, body)) in world
+        .query::<(&ThrownItem, &Transform, &PhysicsBody)>()
+        .iter()
+    {
+        let is_impact_speed = min_speed
+            .map(|min_speed| body.velocity.length() >= min_speed)
+            .unwrap_or(false);
+
+        if !is_impact_speed {
+            to_remove.push(entity);
+            continue;
+        }
+
+        let rect = body.as_rect(transform.position);
+
+        let target = players
+            .iter()
+            .find(|(pe, player_rect)| *pe != thrown.thrower && player_rect.overlaps(&rect));
+
+        if let Some(&(player_entity, _)) = target {
+            to_damage.push((thrown.thrower, player_entity));
+            to_remove.push(entity);
+        }
+    }
+
+    for entity in to_remove {
+        let _ = world.remove_one::<ThrownItem>(entity);
+    }
+
+    for (thrower, target) in to_damage {
+        on_player_damage(world, thrower, target);
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct RespawnInfo {
     pub position: Vec2,
@@ -613,6 +1093,7 @@ pub fn update_respawning_items(world: &mut World) {
             body.is_deactivated = false;
             match &mut drawable.kind {
                 crate::DrawableKind::Sprite(s) => s.is_deactivated = false,
+                crate::DrawableKind::StaticSprite(s) => s.is_deactivated = false,
                 crate::DrawableKind::SpriteSet(s) => {
                     if let Some(sprite) = s.map.get_mut(SPRITE_ANIMATED_SPRITE_ID) {
                         sprite.is_deactivated = false;
@@ -629,6 +1110,7 @@ pub fn update_respawning_items(world: &mut World) {
         } else {
             match &mut drawable.kind {
                 crate::DrawableKind::Sprite(s) => s.is_deactivated = true,
+                crate::DrawableKind::StaticSprite(s) => s.is_deactivated = true,
                 crate::DrawableKind::SpriteSet(s) => s.deactivate_all(),
                 crate::DrawableKind::AnimatedSprite(s) => s.is_deactivated = true,
                 crate::DrawableKind::AnimatedSpriteSet(s) => s.deactivate_all(),
@@ -654,3 +1136,42 @@ pub fn update_respawning_items(world: &mut World) {
         .unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn charge() -> WeaponChargeMetadata {
+        WeaponChargeMetadata {
+            min_charge_time: 0.25,
+            max_charge_time: 1.25,
+            min_damage: 0.0,
+            max_damage: 2.0,
+            min_speed_factor: 0.5,
+            max_speed_factor: 1.5,
+            particles: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_charge_damage_scales_with_hold_time() {
+        let charge = charge();
+
+        assert_eq!(charge.damage(0.1), 0.0);
+        assert_eq!(charge.damage(0.25), 0.0);
+        assert_eq!(charge.damage(0.75), 1.0);
+        assert_eq!(charge.damage(1.25), 2.0);
+        assert_eq!(charge.damage(5.0), 2.0);
+
+        assert!(charge.damage(0.4) < charge.damage(0.6));
+    }
+
+    #[test]
+    fn test_charge_speed_scales_with_hold_time() {
+        let charge = charge();
+
+        assert_eq!(charge.speed_factor(0.0), 0.5);
+        assert_eq!(charge.speed_factor(0.75), 1.0);
+        assert_eq!(charge.speed_factor(1.25), 1.5);
+    }
+}
@@ -7,7 +7,8 @@ use macroquad::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::map::{
-    Map, MapBackgroundLayer, MapLayer, MapLayerKind, MapObject, MapProperty, MapTile, MapTileset,
+    Map, MapBackgroundLayer, MapBoundaries, MapInfo, MapLayer, MapLayerKind, MapObject,
+    MapProperty, MapTile, MapTileset,
 };
 
 pub use tiled::TiledMap;
@@ -34,6 +35,14 @@ pub(crate) struct MapDef {
     pub properties: HashMap<String, MapProperty>,
     #[serde(default, with = "core::json::vec2_vec")]
     pub spawn_points: Vec<Vec2>,
+    #[serde(default, skip_serializing_if = "MapInfo::is_empty")]
+    pub info: MapInfo,
+    #[serde(default, skip_serializing_if = "MapBoundaries::is_default")]
+    pub boundaries: MapBoundaries,
+    /// The content hash of the map, as returned by `Map::get_content_hash`. This is written when
+    /// the map is saved and verified when it is loaded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
 }
 
 impl From<Map> for MapDef {
@@ -91,11 +100,14 @@ impl From<Map> for MapDef {
             })
             .collect();
 
-        let tilesets = other
+        let mut tilesets = other
             .tilesets
             .into_iter()
             .map(|(_, tileset)| tileset)
-            .collect();
+            .collect::<Vec<_>>();
+
+        // Sort the tilesets, so that the serialized form of a map is always the same
+        tilesets.sort_by(|a, b| a.id.cmp(&b.id));
 
         MapDef {
             background_color: other.background_color,
@@ -107,6 +119,9 @@ impl From<Map> for MapDef {
             tilesets,
             properties: other.properties,
             spawn_points: other.spawn_points,
+            info: other.info,
+            boundaries: other.boundaries,
+            hash: None,
         }
     }
 }
@@ -196,6 +211,8 @@ impl From<MapDef> for Map {
             draw_order,
             properties: def.properties,
             spawn_points: def.spawn_points,
+            info: def.info,
+            boundaries: def.boundaries,
         }
     }
 }
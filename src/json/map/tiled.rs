@@ -198,6 +198,8 @@ impl TiledMap {
                 tile_subdivisions,
                 autotile_mask,
                 tile_attributes,
+                tile_collisions: HashMap::new(),
+                tile_animations: HashMap::new(),
                 properties,
                 bitmasks: None,
             };
@@ -270,6 +272,7 @@ impl TiledMap {
                         kind,
                         position,
                         properties,
+                        group: None,
                     };
 
                     objects.push(object);
@@ -336,6 +339,8 @@ impl TiledMap {
             draw_order,
             properties,
             spawn_points,
+            info: Default::default(),
+            boundaries: Default::default(),
         }
     }
 }
@@ -18,9 +18,11 @@ pub mod json;
 pub mod map;
 pub mod network;
 pub mod particles;
+pub mod paths;
 pub mod physics;
 pub mod player;
 pub mod resources;
+pub mod stats;
 pub mod utils;
 
 pub mod drawables;
@@ -59,6 +61,9 @@ pub use effects::{
 pub type CollisionWorld = macroquad_platformer::World;
 
 const CONFIG_FILE_ENV_VAR: &str = "JUMPY_CONFIG";
+/// If this is set, the game will exit with an error if the config file is invalid, in stead of
+/// replacing it with a default config file
+const STRICT_CONFIG_ENV_VAR: &str = "JUMPY_STRICT_CONFIG";
 const ASSETS_DIR_ENV_VAR: &str = "JUMPY_ASSETS";
 const MODS_DIR_ENV_VAR: &str = "JUMPY_MODS";
 
@@ -79,17 +84,36 @@ pub fn reload_resources() {
     ApplicationEvent::ReloadResources.dispatch()
 }
 
-fn window_conf() -> Conf {
-    let path = env::var(CONFIG_FILE_ENV_VAR)
+/// Returns the path of the config file
+pub fn get_config_path() -> PathBuf {
+    // Debug builds use the config in the repository, so that it can be edited during development
+    #[cfg(debug_assertions)]
+    if env::var(CONFIG_FILE_ENV_VAR).is_err() {
+        return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(paths::CONFIG_FILE_NAME);
+    }
+
+    get_user_config_path()
+}
+
+/// Returns the path of the config file in the user config directory. This is where settings that
+/// are changed in game should be saved, so that the config in the repository is not written to
+/// by debug builds.
+pub fn get_user_config_path() -> PathBuf {
+    env::var(CONFIG_FILE_ENV_VAR)
         .map(PathBuf::from)
         .unwrap_or_else(|_| {
-            #[cfg(debug_assertions)]
-            return PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config.toml");
-            #[cfg(not(debug_assertions))]
-            return PathBuf::from("./config.toml");
-        });
+            paths::get_config_file_path(PathBuf::from(".").join(paths::CONFIG_FILE_NAME))
+        })
+}
 
-    let config = Config::load(&path).unwrap();
+fn window_conf() -> Conf {
+    let path = get_config_path();
+
+    let config = if env::var(STRICT_CONFIG_ENV_VAR).is_ok() {
+        Config::load(&path).unwrap()
+    } else {
+        Config::load_or_recover(&path)
+    };
 
     storage::store(config.clone());
 
@@ -115,6 +139,14 @@ async fn init_game() -> Result<bool> {
 
             start_music("fish_tide");
         }
+        MainMenuResult::DailyMap { daily_map, players } => {
+            map::notify_daily_map_started(&daily_map, players.len());
+
+            let game = Game::new(GameMode::Local, daily_map.map, &players)?;
+            scene::add_node(game);
+
+            start_music("fish_tide");
+        }
         MainMenuResult::Editor {
             input_scheme,
             is_new_map,
@@ -164,6 +196,12 @@ async fn init_game() -> Result<bool> {
 
     Api::init::<ultimate::UltimateApiBackend>(&player_ids[0], true).await?;
 
+    let teams = {
+        let config = storage::get::<Config>();
+
+        [0, 1].map(|index| config.match_settings.get_player_team(index))
+    };
+
     let (map, mut characters) = {
         let resources = storage::get::<Resources>();
 
@@ -182,11 +220,13 @@ async fn init_game() -> Result<bool> {
             index: 0,
             controller: PlayerControllerKind::LocalInput(GameInputScheme::KeyboardLeft).into(),
             character: characters.pop().unwrap(),
+            team: teams[0],
         },
         PlayerParams {
             index: 1,
             controller: PlayerControllerKind::Network(player_ids[1].clone()).into(),
             character: characters.pop().unwrap(),
+            team: teams[1],
         },
     ];
 
@@ -221,6 +261,8 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
 
     init_passive_effects();
 
+    stats::init_stats();
+
     'outer: loop {
         if init_game().await? {
             continue 'outer;
@@ -250,8 +292,12 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         scene::clear();
 
         stop_music();
+
+        stats::save_stats();
     }
 
+    stats::save_stats();
+
     Api::close().await?;
 
     Ok(())
@@ -0,0 +1,214 @@
+//! Compact binary serialization of maps. JSON remains the canonical, human-editable format, but
+//! parsing the tile data of large maps from JSON is slow, so a binary cache is written next to
+//! each map file, when it is loaded. The binary format can also be used to transfer maps over the
+//! network.
+
+use serde::{Deserialize, Serialize};
+
+use core::data::{deserialize_binary_bytes, serialize_binary_bytes};
+use core::Result;
+
+use super::{hash::fnv1a_64, Map};
+
+use crate::json::MapDef;
+
+/// This should be incremented whenever the binary format, or `MapDef`, changes, so that existing
+/// caches are regenerated
+const MAP_CACHE_VERSION: u32 = 3;
+
+/// The extension of the binary cache files, that are written next to the JSON map files
+pub const MAP_CACHE_EXTENSION: &str = "bin";
+
+/// Tile data, which makes up the bulk of a map, is stored as raw tile ids. The rest of the map is
+/// stored as compact JSON, as `MapDef` can not be deserialized by `bincode`: map properties are
+/// untagged enums, which require a self-describing format, and most optional fields are skipped
+/// when they are empty, which a format without field names can not account for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MapBinaryDef {
+    map: String,
+    /// The tiles of each layer, in draw order
+    tiles: Vec<Option<Vec<u32>>>,
+}
+
+impl MapBinaryDef {
+    fn new(map: &Map) -> Result<Self> {
+        let mut def = MapDef::from(map.clone());
+
+        let tiles = def
+            .layers
+            .iter_mut()
+            .map(|layer| layer.tiles.take())
+            .collect();
+
+        let map = serde_json::to_string(&def)?;
+
+        Ok(MapBinaryDef { map, tiles })
+    }
+
+    fn into_map(self) -> Result<Map> {
+        let mut def: MapDef = serde_json::from_str(&self.map)?;
+
+        for (layer, tiles) in def.layers.iter_mut().zip(self.tiles) {
+            layer.tiles = tiles;
+        }
+
+        Ok(Map::from(def))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MapCacheDef {
+    version: u32,
+    /// A hash of the JSON file that the cache was created from
+    source_hash: u64,
+    /// The map, as returned by `Map::to_binary`
+    map: Vec<u8>,
+}
+
+impl Map {
+    /// Serialize the map into the compact binary format, that is used for map caches
+    pub fn to_binary(&self) -> Result<Vec<u8>> {
+        let def = MapBinaryDef::new(self)?;
+        let bytes = serialize_binary_bytes(&def)?;
+
+        Ok(bytes)
+    }
+
+    /// Deserialize a map that was serialized with `Map::to_binary`
+    pub fn from_binary(bytes: &[u8]) -> Result<Map> {
+        let def: MapBinaryDef = deserialize_binary_bytes(bytes)?;
+
+        def.into_map()
+    }
+
+    /// This will return the cached map for the specified JSON map file, if a cache exists and it
+    /// was created from the same version of the file, as specified by `source`.
+    #[cfg(not(target_family = "wasm"))]
+    pub(super) fn load_binary_cache(path: &std::path::Path, source: &[u8]) -> Option<Map> {
+        let bytes = std::fs::read(path.with_extension(MAP_CACHE_EXTENSION)).ok()?;
+
+        let cache: MapCacheDef = deserialize_binary_bytes(&bytes).ok()?;
+
+        if cache.version != MAP_CACHE_VERSION || cache.source_hash != fnv1a_64(source) {
+            return None;
+        }
+
+        Map::from_binary(&cache.map).ok()
+    }
+
+    /// Write a binary cache of the map next to the specified JSON map file. The contents of the
+    /// JSON file, `source`, are hashed, so that stale caches can be detected.
+    #[cfg(not(target_family = "wasm"))]
+    pub(super) fn save_binary_cache(&self, path: &std::path::Path, source: &[u8]) -> Result<()> {
+        let cache = MapCacheDef {
+            version: MAP_CACHE_VERSION,
+            source_hash: fnv1a_64(source),
+            map: self.to_binary()?,
+        };
+
+        let bytes = serialize_binary_bytes(&cache)?;
+        std::fs::write(path.with_extension(MAP_CACHE_EXTENSION), bytes)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Instant;
+
+    use macroquad::prelude::*;
+
+    use crate::map::{MapGenerationParams, GENERATED_MAP_TILE_LAYER_ID};
+
+    fn generate_map(grid_size: UVec2) -> Map {
+        Map::generate(&MapGenerationParams {
+            seed: 1,
+            grid_size,
+            tile_size: vec2(32.0, 32.0),
+            tileset_texture_id: "default_tileset".to_string(),
+            tileset_texture_size: uvec2(224, 288),
+            surface_tile_id: 1,
+            fill_tile_id: 8,
+            platform_tile_id: 58,
+            platform_density: 0.5,
+            item_density: 0.2,
+            item_ids: vec!["sword".to_string(), "musket".to_string()],
+            spawn_point_cnt: 4,
+            is_symmetric: false,
+        })
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let map = generate_map(uvec2(40, 24));
+
+        let bytes = map.to_binary().unwrap();
+        let loaded = Map::from_binary(&bytes).unwrap();
+
+        assert_eq!(
+            loaded.get_content_hash().unwrap(),
+            map.get_content_hash().unwrap()
+        );
+        assert_eq!(
+            loaded.layers[GENERATED_MAP_TILE_LAYER_ID].tiles.len(),
+            map.layers[GENERATED_MAP_TILE_LAYER_ID].tiles.len()
+        );
+    }
+
+    #[test]
+    #[cfg(not(target_family = "wasm"))]
+    fn test_binary_cache() {
+        let map = generate_map(uvec2(20, 12));
+
+        let path = std::env::temp_dir().join("fishfight_test_binary_cache.json");
+        let source = map.to_json_with_hash().unwrap();
+
+        map.save_binary_cache(&path, source.as_bytes()).unwrap();
+
+        let loaded = Map::load_binary_cache(&path, source.as_bytes()).unwrap();
+        assert_eq!(
+            loaded.get_content_hash().unwrap(),
+            map.get_content_hash().unwrap()
+        );
+
+        // The cache is stale once the JSON file that it was created from has changed
+        assert!(Map::load_binary_cache(&path, b"{}").is_none());
+
+        std::fs::remove_file(path.with_extension(MAP_CACHE_EXTENSION)).unwrap();
+    }
+
+    /// Run with `cargo test --release --features binary-maps -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_binary_load_time() {
+        const ITERATIONS: u32 = 20;
+
+        let map = generate_map(uvec2(400, 240));
+
+        let json = serde_json::to_vec(&map).unwrap();
+        let bytes = map.to_binary().unwrap();
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            Map::from_json_with_hash(&json).unwrap();
+        }
+        let json_time = start.elapsed() / ITERATIONS;
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            Map::from_binary(&bytes).unwrap();
+        }
+        let binary_time = start.elapsed() / ITERATIONS;
+
+        println!(
+            "JSON: {} bytes in {:?}, binary: {} bytes in {:?}",
+            json.len(),
+            json_time,
+            bytes.len(),
+            binary_time
+        );
+    }
+}
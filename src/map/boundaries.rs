@@ -0,0 +1,189 @@
+use macroquad::prelude::*;
+
+use serde::{Deserialize, Serialize};
+
+/// This determines what happens to a player that crosses an edge of the playable area of a map
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapBoundaryKind {
+    /// The player is killed, once it is entirely outside of the playable area
+    Kill,
+    /// The player is moved to the opposite edge, once its center crosses the edge
+    Wrap,
+    /// The player is kept inside of the playable area and its velocity is reflected
+    Bounce,
+    /// The player is kept inside of the playable area and its velocity towards the edge is removed
+    Wall,
+}
+
+impl Default for MapBoundaryKind {
+    fn default() -> Self {
+        MapBoundaryKind::Kill
+    }
+}
+
+/// The boundary behavior of each edge of a map
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MapBoundaries {
+    #[serde(default)]
+    pub left: MapBoundaryKind,
+    #[serde(default)]
+    pub right: MapBoundaryKind,
+    #[serde(default)]
+    pub top: MapBoundaryKind,
+    #[serde(default)]
+    pub bottom: MapBoundaryKind,
+}
+
+impl MapBoundaries {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Apply the boundary behavior to a body, with the collider `rect`, at `position`, that is
+    /// moving at `velocity`. The position and velocity will be updated, if the body crossed an
+    /// edge, and `true` will be returned if the body should be killed.
+    pub fn apply(
+        &self,
+        area: &Rect,
+        rect: &Rect,
+        position: &mut Vec2,
+        velocity: &mut Vec2,
+    ) -> bool {
+        let mut offset = Vec2::ZERO;
+
+        // Horizontal edges
+        {
+            let (kind, overshoot, direction) = if rect.x < area.x {
+                (self.left, area.x - rect.x, 1.0)
+            } else if rect.right() > area.right() {
+                (self.right, rect.right() - area.right(), -1.0)
+            } else {
+                (MapBoundaryKind::Kill, 0.0, 0.0)
+            };
+
+            match kind {
+                MapBoundaryKind::Kill => {}
+                MapBoundaryKind::Wrap => {
+                    if overshoot > rect.w / 2.0 {
+                        offset.x = area.w * direction;
+                    }
+                }
+                MapBoundaryKind::Bounce => {
+                    offset.x = overshoot * direction;
+                    velocity.x = velocity.x.abs() * direction;
+                }
+                MapBoundaryKind::Wall => {
+                    offset.x = overshoot * direction;
+                    if velocity.x * direction < 0.0 {
+                        velocity.x = 0.0;
+                    }
+                }
+            }
+        }
+
+        // Vertical edges
+        {
+            let (kind, overshoot, direction) = if rect.y < area.y {
+                (self.top, area.y - rect.y, 1.0)
+            } else if rect.bottom() > area.bottom() {
+                (self.bottom, rect.bottom() - area.bottom(), -1.0)
+            } else {
+                (MapBoundaryKind::Kill, 0.0, 0.0)
+            };
+
+            match kind {
+                MapBoundaryKind::Kill => {}
+                MapBoundaryKind::Wrap => {
+                    if overshoot > rect.h / 2.0 {
+                        offset.y = area.h * direction;
+                    }
+                }
+                MapBoundaryKind::Bounce => {
+                    offset.y = overshoot * direction;
+                    velocity.y = velocity.y.abs() * direction;
+                }
+                MapBoundaryKind::Wall => {
+                    offset.y = overshoot * direction;
+                    if velocity.y * direction < 0.0 {
+                        velocity.y = 0.0;
+                    }
+                }
+            }
+        }
+
+        *position += offset;
+
+        let rect = rect.offset(offset);
+
+        !area.overlaps(&rect)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boundaries(kind: MapBoundaryKind) -> MapBoundaries {
+        MapBoundaries {
+            left: kind,
+            right: kind,
+            top: kind,
+            bottom: kind,
+        }
+    }
+
+    fn apply(kind: MapBoundaryKind, position: Vec2, velocity: Vec2) -> (Vec2, Vec2, bool) {
+        let area = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let rect = Rect::new(position.x, position.y, 10.0, 10.0);
+
+        let mut position = position;
+        let mut velocity = velocity;
+
+        let is_killed = boundaries(kind).apply(&area, &rect, &mut position, &mut velocity);
+
+        (position, velocity, is_killed)
+    }
+
+    #[test]
+    fn test_kill_boundary() {
+        let (position, _, is_killed) =
+            apply(MapBoundaryKind::Kill, vec2(45.0, 95.0), vec2(0.0, 5.0));
+        assert_eq!(position, vec2(45.0, 95.0));
+        assert!(!is_killed);
+
+        let (_, _, is_killed) = apply(MapBoundaryKind::Kill, vec2(45.0, 101.0), vec2(0.0, 5.0));
+        assert!(is_killed);
+    }
+
+    #[test]
+    fn test_wrap_boundary() {
+        let (position, velocity, is_killed) =
+            apply(MapBoundaryKind::Wrap, vec2(45.0, 96.0), vec2(0.0, 5.0));
+        assert_eq!(position, vec2(45.0, -4.0));
+        assert_eq!(velocity, vec2(0.0, 5.0));
+        assert!(!is_killed);
+
+        let (position, _, _) = apply(MapBoundaryKind::Wrap, vec2(-6.0, 45.0), vec2(-5.0, 0.0));
+        assert_eq!(position, vec2(94.0, 45.0));
+    }
+
+    #[test]
+    fn test_bounce_boundary() {
+        let (position, velocity, is_killed) =
+            apply(MapBoundaryKind::Bounce, vec2(95.0, 45.0), vec2(5.0, 2.0));
+        assert_eq!(position, vec2(90.0, 45.0));
+        assert_eq!(velocity, vec2(-5.0, 2.0));
+        assert!(!is_killed);
+    }
+
+    #[test]
+    fn test_wall_boundary() {
+        let (position, velocity, is_killed) =
+            apply(MapBoundaryKind::Wall, vec2(45.0, -5.0), vec2(3.0, -5.0));
+        assert_eq!(position, vec2(45.0, 0.0));
+        assert_eq!(velocity, vec2(3.0, 0.0));
+        assert!(!is_killed);
+    }
+}
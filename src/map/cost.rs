@@ -0,0 +1,196 @@
+//! Estimates of the runtime cost of a map, computed from the map data, so that maps that are likely
+//! to run poorly can be spotted in the editor, before they are shipped.
+//!
+//! The draw call estimate assumes that consecutive draws of the same texture are batched, so it is
+//! the number of times the texture changes while drawing the map, plus one draw for each distinct
+//! texture used by objects and for each distinct particle effect, as these are drawn in batches.
+
+use std::collections::HashSet;
+
+use core::EditorConfig;
+
+use super::{Map, MapLayerKind, MapObject, MapObjectKind};
+
+use crate::items::MapItemKind;
+use crate::Resources;
+
+/// The parts of a map object that add to the cost of a map
+#[derive(Debug, Default, Clone)]
+pub struct MapObjectCost {
+    pub texture_id: Option<String>,
+    /// The ids of the particle effects of the emitters of the object
+    pub particle_effect_ids: Vec<String>,
+}
+
+impl MapObjectCost {
+    pub fn new(object: &MapObject, resources: &Resources) -> Self {
+        let mut res = MapObjectCost::default();
+
+        match object.kind {
+            MapObjectKind::Decoration => {
+                if let Some(meta) = resources.decoration.get(&object.id) {
+                    res.texture_id = Some(meta.sprite.texture_id().to_string());
+                }
+            }
+            MapObjectKind::Item => {
+                if let Some(meta) = resources.items.get(&object.id) {
+                    res.texture_id = Some(meta.sprite.texture_id.clone());
+
+                    if let MapItemKind::Weapon { meta } = &meta.kind {
+                        res.particle_effect_ids = meta
+                            .particles
+                            .iter()
+                            .map(|emitter| emitter.particle_effect_id.clone())
+                            .collect();
+                    }
+                }
+            }
+            MapObjectKind::Environment => {}
+        }
+
+        res
+    }
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct MapCostEstimate {
+    pub tile_cnt: usize,
+    pub decoration_cnt: usize,
+    pub particle_emitter_cnt: usize,
+    pub draw_call_cnt: usize,
+}
+
+impl MapCostEstimate {
+    /// Estimate the cost of `map`, looking up the metadata of objects in `resources`
+    pub fn new(map: &Map, resources: &Resources) -> Self {
+        Self::with_object_costs(map, |object| MapObjectCost::new(object, resources))
+    }
+
+    /// Estimate the cost of `map`, using `get_object_cost` to get the cost of each object
+    pub fn with_object_costs<F>(map: &Map, get_object_cost: F) -> Self
+    where
+        F: Fn(&MapObject) -> MapObjectCost,
+    {
+        let mut res = MapCostEstimate {
+            draw_call_cnt: map.background_layers.len(),
+            ..Default::default()
+        };
+
+        let mut current_texture_id = None;
+
+        let mut object_texture_ids = HashSet::new();
+        let mut particle_effect_ids = HashSet::new();
+
+        // Layers are drawn in reverse order
+        for layer_id in map.draw_order.iter().rev() {
+            let layer = match map.layers.get(layer_id) {
+                Some(layer) if layer.is_visible => layer,
+                _ => continue,
+            };
+
+            match layer.kind {
+                MapLayerKind::TileLayer => {
+                    for tile in layer.tiles.iter().flatten() {
+                        res.tile_cnt += 1;
+
+                        if current_texture_id != Some(&tile.texture_id) {
+                            current_texture_id = Some(&tile.texture_id);
+                            res.draw_call_cnt += 1;
+                        }
+                    }
+                }
+                MapLayerKind::ObjectLayer => {
+                    for object in &layer.objects {
+                        if object.kind == MapObjectKind::Decoration {
+                            res.decoration_cnt += 1;
+                        }
+
+                        let cost = get_object_cost(object);
+
+                        if let Some(texture_id) = cost.texture_id {
+                            object_texture_ids.insert(texture_id);
+                        }
+
+                        res.particle_emitter_cnt += cost.particle_effect_ids.len();
+                        particle_effect_ids.extend(cost.particle_effect_ids);
+                    }
+                }
+            }
+        }
+
+        res.draw_call_cnt += object_texture_ids.len() + particle_effect_ids.len();
+
+        res
+    }
+
+    /// Returns `true` if any part of the estimate exceeds the thresholds in `config`
+    pub fn exceeds(&self, config: &EditorConfig) -> bool {
+        self.tile_cnt > config.max_tiles
+            || self.decoration_cnt > config.max_decorations
+            || self.particle_emitter_cnt > config.max_particle_emitters
+            || self.draw_call_cnt > config.max_draw_calls
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::prelude::*;
+
+    use super::*;
+
+    use crate::map::{MapLayer, MapTile};
+
+    fn tile(texture_id: &str) -> Option<MapTile> {
+        Some(MapTile {
+            tile_id: 0,
+            tileset_id: texture_id.to_string(),
+            texture_id: texture_id.to_string(),
+            texture_coords: Vec2::ZERO,
+            attributes: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_map_cost_estimate() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(4, 1));
+
+        let mut tiles = MapLayer::new("tiles", MapLayerKind::TileLayer, false, map.grid_size);
+        tiles.tiles = vec![tile("a"), tile("a"), None, tile("b")];
+        map.layers.insert(tiles.id.clone(), tiles);
+        map.draw_order.push("tiles".to_string());
+
+        let mut objects = MapLayer::new("objects", MapLayerKind::ObjectLayer, false, map.grid_size);
+        for _ in 0..3 {
+            objects.objects.push(MapObject::new(
+                "seaweed",
+                MapObjectKind::Decoration,
+                Vec2::ZERO,
+            ));
+        }
+        map.layers.insert(objects.id.clone(), objects);
+        map.draw_order.push("objects".to_string());
+
+        let estimate = MapCostEstimate::with_object_costs(&map, |_| MapObjectCost {
+            texture_id: Some("seaweed".to_string()),
+            particle_effect_ids: vec!["bubbles".to_string()],
+        });
+
+        assert_eq!(
+            estimate,
+            MapCostEstimate {
+                tile_cnt: 3,
+                decoration_cnt: 3,
+                particle_emitter_cnt: 3,
+                draw_call_cnt: 4,
+            }
+        );
+
+        let config = EditorConfig {
+            max_draw_calls: 3,
+            ..Default::default()
+        };
+
+        assert!(estimate.exceeds(&config));
+        assert!(!estimate.exceeds(&EditorConfig::default()));
+    }
+}
@@ -9,7 +9,7 @@ use macroquad::{
 
 use crate::{
     player::Player, utils::timer::Timer, Animation, CollisionWorld, Drawable, PhysicsBody,
-    PhysicsBodyParams, Resources,
+    PhysicsBodyParams, PlayDirection, Resources,
 };
 
 pub const CRAB_TEXTURE_ID: &str = "crab";
@@ -72,10 +72,13 @@ pub fn spawn_crab(world: &mut World, spawn_position: Vec2) -> Result<Entity> {
     let crab_animations = &[Animation {
         id: "idle".to_string(),
         row: 0,
+        start_column: 0,
         frames: 2,
         fps: 2,
         tweens: Default::default(),
         is_looping: true,
+        direction: PlayDirection::Forward,
+        frame_durations: Vec::new(),
     }];
 
     Ok(world.spawn((
@@ -0,0 +1,130 @@
+//! The daily map is a generated arena that is seeded from the current date, so that all players
+//! get the same map on any given day.
+
+use macroquad::{experimental::collections::storage, prelude::*};
+
+use super::{
+    generate::MapRng, Map, MapGenerationParams, DEFAULT_GENERATED_MAP_FILL_TILE_ID,
+    DEFAULT_GENERATED_MAP_PLATFORM_TILE_ID, DEFAULT_GENERATED_MAP_SURFACE_TILE_ID,
+    DEFAULT_GENERATED_MAP_TEXTURE_ID,
+};
+
+use crate::Resources;
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// This is mixed into the day number, so that the daily seeds don't overlap with the small
+/// seeds that are typically entered by hand in the editor.
+const DAILY_MAP_SEED_SALT: u64 = 0x6669_7368_6669_6768;
+
+const DAILY_MAP_GRID_WIDTH: u32 = 40;
+const DAILY_MAP_GRID_HEIGHT: u32 = 24;
+const DAILY_MAP_TILE_SIZE: f32 = 32.0;
+const DAILY_MAP_PLATFORM_DENSITY: f32 = 0.4;
+const DAILY_MAP_ITEM_DENSITY: f32 = 0.1;
+const DAILY_MAP_SPAWN_POINT_CNT: u32 = 4;
+
+/// Returns the number of whole days since the Unix epoch, in UTC
+pub fn get_current_day() -> u64 {
+    (macroquad::miniquad::date::now() / SECONDS_PER_DAY).floor() as u64
+}
+
+/// Returns the generator seed for the specified day. This only uses integer arithmetic, so it
+/// will produce the same seed on all platforms.
+pub fn get_daily_map_seed(day: u64) -> u64 {
+    MapRng(day ^ DAILY_MAP_SEED_SALT).next_u64()
+}
+
+#[derive(Debug, Clone)]
+pub struct DailyMap {
+    /// The number of days since the Unix epoch, in UTC
+    pub day: u64,
+    pub seed: u64,
+    pub map: Map,
+}
+
+impl DailyMap {
+    /// Generate the map for the specified day. This will return `None` if the default tileset
+    /// texture is not loaded.
+    pub fn new(day: u64) -> Option<Self> {
+        let seed = get_daily_map_seed(day);
+
+        let params = {
+            let resources = storage::get::<Resources>();
+
+            let texture_res = resources.textures.get(DEFAULT_GENERATED_MAP_TEXTURE_ID)?;
+
+            let tile_size = texture_res
+                .meta
+                .frame_size
+                .unwrap_or_else(|| vec2(DAILY_MAP_TILE_SIZE, DAILY_MAP_TILE_SIZE));
+
+            let tileset_texture_size = uvec2(
+                texture_res.texture.width() as u32,
+                texture_res.texture.height() as u32,
+            );
+
+            let mut item_ids = resources.items.keys().cloned().collect::<Vec<_>>();
+            // Sort the ids, so that the same seed produces the same map, regardless of load order
+            item_ids.sort_unstable();
+
+            MapGenerationParams {
+                seed,
+                grid_size: uvec2(DAILY_MAP_GRID_WIDTH, DAILY_MAP_GRID_HEIGHT),
+                tile_size,
+                tileset_texture_id: DEFAULT_GENERATED_MAP_TEXTURE_ID.to_string(),
+                tileset_texture_size,
+                surface_tile_id: DEFAULT_GENERATED_MAP_SURFACE_TILE_ID,
+                fill_tile_id: DEFAULT_GENERATED_MAP_FILL_TILE_ID,
+                platform_tile_id: DEFAULT_GENERATED_MAP_PLATFORM_TILE_ID,
+                platform_density: DAILY_MAP_PLATFORM_DENSITY,
+                item_density: DAILY_MAP_ITEM_DENSITY,
+                item_ids,
+                spawn_point_cnt: DAILY_MAP_SPAWN_POINT_CNT,
+                is_symmetric: true,
+            }
+        };
+
+        let map = Map::generate(&params);
+
+        Some(DailyMap { day, seed, map })
+    }
+
+    /// Generate the map for the current day
+    pub fn today() -> Option<Self> {
+        Self::new(get_current_day())
+    }
+}
+
+/// Implement this and register it with `set_daily_map_leaderboard` to get notified when a daily
+/// map is played, for example to submit the players to an online leaderboard.
+pub trait DailyMapLeaderboard {
+    fn on_daily_map_started(&mut self, day: u64, seed: u64, player_cnt: usize);
+}
+
+struct DailyMapLeaderboardHook(Box<dyn DailyMapLeaderboard>);
+
+#[allow(dead_code)]
+pub fn set_daily_map_leaderboard<T: 'static + DailyMapLeaderboard>(leaderboard: T) {
+    storage::store(DailyMapLeaderboardHook(Box::new(leaderboard)));
+}
+
+/// This will call the registered leaderboard, if any
+pub fn notify_daily_map_started(daily_map: &DailyMap, player_cnt: usize) {
+    if let Some(mut hook) = storage::try_get_mut::<DailyMapLeaderboardHook>() {
+        hook.0
+            .on_daily_map_started(daily_map.day, daily_map.seed, player_cnt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_map_seed_is_stable() {
+        assert_eq!(get_daily_map_seed(0), 0xd8b5_6e88_30f1_ba9b);
+        assert_eq!(get_daily_map_seed(19000), 0x64bc_92cb_2dbd_d071);
+        assert_ne!(get_daily_map_seed(19000), get_daily_map_seed(19001));
+    }
+}
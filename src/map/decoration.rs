@@ -1,10 +1,15 @@
+use macroquad::color;
 use macroquad::prelude::*;
 
 use hecs::{Entity, World};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{AnimatedSpriteMetadata, Drawable, DrawableKind};
+use crate::resources::TextureResource;
+use crate::{
+    draw_one_static_sprite, AnimatedSpriteMetadata, Drawable, DrawableKind, StaticSprite,
+    StaticSpriteMetadata, BACKGROUND_Z_INDEX,
+};
 use core::Transform;
 
 const DECORATION_DRAW_ORDER: u32 = 0;
@@ -13,7 +18,35 @@ const DECORATION_DRAW_ORDER: u32 = 0;
 #[serde(deny_unknown_fields)]
 pub struct DecorationMetadata {
     pub id: String,
-    pub sprite: AnimatedSpriteMetadata,
+    pub sprite: DecorationSpriteMetadata,
+    /// The layer that the decoration is drawn in. By default, decorations are drawn behind
+    /// players, items and effects.
+    #[serde(default = "DecorationMetadata::default_z_index")]
+    pub z_index: i32,
+}
+
+impl DecorationMetadata {
+    pub fn default_z_index() -> i32 {
+        BACKGROUND_Z_INDEX
+    }
+}
+
+/// Decorations that are not animated can use a static sprite, which can also be tiled, instead of
+/// an animation with a single frame. A static sprite is used if no animations are specified.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DecorationSpriteMetadata {
+    Animated(AnimatedSpriteMetadata),
+    Static(StaticSpriteMetadata),
+}
+
+impl DecorationSpriteMetadata {
+    pub fn texture_id(&self) -> &str {
+        match self {
+            DecorationSpriteMetadata::Animated(meta) => &meta.texture_id,
+            DecorationSpriteMetadata::Static(meta) => &meta.texture_id,
+        }
+    }
 }
 
 pub struct Decoration {
@@ -27,14 +60,116 @@ impl Decoration {
 }
 
 pub fn spawn_decoration(world: &mut World, position: Vec2, meta: DecorationMetadata) -> Entity {
-    let sprite = meta.sprite.into();
+    let kind = match meta.sprite {
+        DecorationSpriteMetadata::Animated(meta) => DrawableKind::AnimatedSprite(meta.into()),
+        DecorationSpriteMetadata::Static(meta) => {
+            let texture_id = meta.texture_id.clone();
+            DrawableKind::StaticSprite(StaticSprite::new(&texture_id, meta.into()))
+        }
+    };
 
     world.spawn((
         Decoration::new(&meta.id),
         Transform::from(position),
         Drawable {
+            z_index: meta.z_index,
             draw_order: DECORATION_DRAW_ORDER,
-            kind: DrawableKind::AnimatedSprite(sprite),
+            kind,
+            offset: Vec2::ZERO,
         },
     ))
 }
+
+/// Draws the first frame of a decoration, without spawning it, multiplying the alpha of its tint
+/// by `alpha`. This is used to draw decorations in the editor.
+pub fn draw_decoration_preview(
+    texture_res: &TextureResource,
+    sprite: &DecorationSpriteMetadata,
+    position: Vec2,
+    alpha: f32,
+) {
+    match sprite {
+        DecorationSpriteMetadata::Animated(meta) => {
+            let position = position + meta.offset;
+
+            let mut tint = meta.tint.unwrap_or(color::WHITE);
+            tint.a *= alpha;
+
+            let frame_size = texture_res.frame_size();
+
+            let source = meta
+                .animations
+                .first()
+                .map(|a| Rect::new(0.0, a.row as f32 * frame_size.y, frame_size.x, frame_size.y));
+
+            draw_texture_ex(
+                texture_res.texture,
+                position.x,
+                position.y,
+                tint,
+                DrawTextureParams {
+                    dest_size: meta.scale.map(|s| s * frame_size),
+                    source,
+                    ..Default::default()
+                },
+            );
+        }
+        DecorationSpriteMetadata::Static(meta) => {
+            let mut sprite = StaticSprite::from_texture(texture_res.texture, meta.clone().into());
+            sprite.tint.a *= alpha;
+
+            draw_one_static_sprite(&Transform::from(position), &sprite);
+        }
+    }
+}
+
+/// Returns the size of the first frame of a decoration, or of the whole tiled area, if it uses a
+/// static sprite
+pub fn get_decoration_size(
+    texture_res: &TextureResource,
+    sprite: &DecorationSpriteMetadata,
+) -> Vec2 {
+    match sprite {
+        DecorationSpriteMetadata::Animated(meta) => {
+            texture_res.frame_size() * meta.scale.unwrap_or(1.0)
+        }
+        DecorationSpriteMetadata::Static(meta) => {
+            StaticSprite::from_texture(texture_res.texture, meta.clone().into()).size()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoration_sprite_kind() {
+        let json = r#"{
+            "id": "rock",
+            "sprite": {
+                "texture": "rocks",
+                "tiling": { "x": 2.5, "y": 1.0 }
+            }
+        }"#;
+
+        let meta: DecorationMetadata = serde_json::from_str(json).unwrap();
+        assert_eq!(meta.sprite.texture_id(), "rocks");
+        assert!(matches!(
+            meta.sprite,
+            DecorationSpriteMetadata::Static(StaticSpriteMetadata { tiling: Some(tiling), .. })
+                if tiling == vec2(2.5, 1.0)
+        ));
+
+        let json = r#"{
+            "id": "seaweed",
+            "sprite": {
+                "texture": "default_decoration",
+                "animations": []
+            }
+        }"#;
+
+        let meta: DecorationMetadata = serde_json::from_str(json).unwrap();
+        assert!(matches!(meta.sprite, DecorationSpriteMetadata::Animated(_)));
+    }
+}
@@ -10,7 +10,7 @@ use macroquad::{
 use crate::{
     player::Player,
     utils::{ease::Ease, timer::Timer},
-    AnimatedSpriteParams, Animation, Drawable, PhysicsBody, Resources, RigidBody,
+    AnimatedSpriteParams, Animation, Drawable, PhysicsBody, PlayDirection, Resources, RigidBody,
 };
 
 /// The texture of the fish school icon ( used in the editor to represent a school of fish )
@@ -123,10 +123,13 @@ pub fn spawn_fish_school(world: &mut World, spawn_position: Vec2) -> Result<Enti
                 &[Animation {
                     id: "default".to_string(),
                     row: 0,
+                    start_column: 0,
                     frames: 4,
                     fps: 3,
                     tweens: Default::default(),
                     is_looping: true,
+                    direction: PlayDirection::Forward,
+                    frame_durations: Vec::new(),
                 }],
                 AnimatedSpriteParams {
                     is_flipped_x: rand_bool(),
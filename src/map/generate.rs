@@ -0,0 +1,326 @@
+//! Procedural generation of randomized arenas. The generated maps are meant as a starting point,
+//! that can be refined in the editor.
+
+use macroquad::prelude::*;
+
+use core::noise::NoiseGenerator;
+
+use super::{
+    Map, MapLayer, MapLayerKind, MapObject, MapObjectKind, MapTile, MapTileCollision, MapTileset,
+};
+
+pub const GENERATED_MAP_TILESET_ID: &str = "tileset";
+pub const GENERATED_MAP_TILE_LAYER_ID: &str = "main layer";
+pub const GENERATED_MAP_ITEMS_LAYER_ID: &str = "items";
+
+// These match the terrain and platform tiles of the default tileset
+pub const DEFAULT_GENERATED_MAP_TEXTURE_ID: &str = "default_tileset";
+pub const DEFAULT_GENERATED_MAP_SURFACE_TILE_ID: u32 = 1;
+pub const DEFAULT_GENERATED_MAP_FILL_TILE_ID: u32 = 8;
+pub const DEFAULT_GENERATED_MAP_PLATFORM_TILE_ID: u32 = 58;
+
+const HEIGHTMAP_FREQUENCY: f32 = 0.08;
+const HEIGHTMAP_OCTAVES: u32 = 4;
+const HEIGHTMAP_LACUNARITY: f32 = 2.0;
+const HEIGHTMAP_PERSISTENCE: f32 = 0.5;
+
+/// The base height of the terrain, as a factor of the maps grid height
+const TERRAIN_BASE_HEIGHT_FACTOR: f32 = 0.2;
+/// The maximum deviation from the base height of the terrain, as a factor of the maps grid height
+const TERRAIN_AMPLITUDE_FACTOR: f32 = 0.15;
+
+const PLATFORM_MIN_LENGTH: u32 = 3;
+const PLATFORM_MAX_LENGTH: u32 = 6;
+const PLATFORM_MIN_GAP: u32 = 2;
+/// The minimum number of rows between platforms, and between platforms and the terrain
+const PLATFORM_CLEARANCE: u32 = 3;
+
+/// The number of rows above the terrain that spawn points are placed at
+const SPAWN_POINT_HEIGHT: u32 = 2;
+
+#[derive(Debug, Clone)]
+pub struct MapGenerationParams {
+    /// The same seed and parameters will always produce the same map
+    pub seed: u64,
+    pub grid_size: UVec2,
+    pub tile_size: Vec2,
+    pub tileset_texture_id: String,
+    pub tileset_texture_size: UVec2,
+    /// The tile used for the top row of the terrain
+    pub surface_tile_id: u32,
+    /// The tile used below the top row of the terrain
+    pub fill_tile_id: u32,
+    pub platform_tile_id: u32,
+    /// The chance, from `0.0` to `1.0`, that a platform is placed at each possible location
+    pub platform_density: f32,
+    /// The chance, from `0.0` to `1.0`, that an item is placed in each column
+    pub item_density: f32,
+    pub item_ids: Vec<String>,
+    pub spawn_point_cnt: u32,
+    /// If this is `true` the map will be mirrored around its vertical center line
+    pub is_symmetric: bool,
+}
+
+/// A small SplitMix64 generator. This is used in stead of the global generator, so that the
+/// generated maps only depend on the seed and are identical on all platforms.
+pub(crate) struct MapRng(pub(crate) u64);
+
+impl MapRng {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in the range `0.0..1.0`
+    pub(crate) fn gen_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns a value in the range `low..high`
+    pub(crate) fn gen_range(&mut self, low: u32, high: u32) -> u32 {
+        if high <= low {
+            return low;
+        }
+
+        low + (self.next_u64() % (high - low) as u64) as u32
+    }
+}
+
+impl Map {
+    /// Generate a randomized arena, with terrain from a noise heightmap, scattered platforms,
+    /// spawn points and items.
+    pub fn generate(params: &MapGenerationParams) -> Map {
+        let mut rng = MapRng(params.seed);
+        let mut noise = NoiseGenerator::new((rng.next_u64() & 0xffff) as i32);
+
+        let mut map = Map::new(params.tile_size, params.grid_size);
+
+        let width = params.grid_size.x;
+        let height = params.grid_size.y;
+
+        let mut tileset = MapTileset::new(
+            GENERATED_MAP_TILESET_ID,
+            &params.tileset_texture_id,
+            params.tileset_texture_size,
+            params.tile_size,
+            1,
+        );
+
+        tileset
+            .tile_collisions
+            .insert(params.platform_tile_id, MapTileCollision::Platform);
+
+        let mut tile_layer = MapLayer::new(
+            GENERATED_MAP_TILE_LAYER_ID,
+            MapLayerKind::TileLayer,
+            true,
+            params.grid_size,
+        );
+
+        let mut items_layer = MapLayer::new(
+            GENERATED_MAP_ITEMS_LAYER_ID,
+            MapLayerKind::ObjectLayer,
+            false,
+            params.grid_size,
+        );
+
+        // When the map is symmetric, only the left half is generated and then mirrored
+        let get_mirrored = |x: u32| -> Option<u32> {
+            let mirrored = width - 1 - x;
+            if params.is_symmetric && mirrored != x {
+                Some(mirrored)
+            } else {
+                None
+            }
+        };
+
+        let generated_width = if params.is_symmetric {
+            width / 2
+        } else {
+            width
+        };
+
+        let surfaces = {
+            let base = height as f32 * TERRAIN_BASE_HEIGHT_FACTOR;
+            let amplitude = height as f32 * TERRAIN_AMPLITUDE_FACTOR;
+
+            let max_height = (height / 2).max(1);
+
+            (0..width)
+                .map(|x| {
+                    let sample_x = if params.is_symmetric {
+                        x.min(width - 1 - x)
+                    } else {
+                        x
+                    };
+
+                    let value = noise.fractal_2d(
+                        sample_x as f32 * HEIGHTMAP_FREQUENCY,
+                        0.5,
+                        HEIGHTMAP_OCTAVES,
+                        HEIGHTMAP_LACUNARITY,
+                        HEIGHTMAP_PERSISTENCE,
+                    );
+
+                    // The noise is in the range -0.5..0.5
+                    let terrain_height = (base + value * 2.0 * amplitude).round() as u32;
+
+                    height - terrain_height.clamp(1, max_height)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let set_tile = |layer: &mut MapLayer, x: u32, y: u32, tile_id: u32| {
+            let index = (y * width + x) as usize;
+
+            layer.tiles[index] = Some(MapTile {
+                tile_id,
+                tileset_id: tileset.id.clone(),
+                texture_id: tileset.texture_id.clone(),
+                texture_coords: tileset.get_texture_coords(tile_id),
+                attributes: Vec::new(),
+            });
+        };
+
+        for (x, &surface) in surfaces.iter().enumerate() {
+            for y in surface..height {
+                let tile_id = if y == surface {
+                    params.surface_tile_id
+                } else {
+                    params.fill_tile_id
+                };
+
+                set_tile(&mut tile_layer, x as u32, y, tile_id);
+            }
+        }
+
+        // The topmost solid row of each column, updated as platforms are placed
+        let mut tops = surfaces.clone();
+
+        let highest_surface = surfaces.iter().copied().min().unwrap_or(height);
+
+        let mut y = highest_surface.saturating_sub(PLATFORM_CLEARANCE);
+        while y >= PLATFORM_CLEARANCE {
+            let mut x = 1 + rng.gen_range(0, PLATFORM_MIN_GAP);
+
+            while x + PLATFORM_MIN_LENGTH < generated_width {
+                let len = rng
+                    .gen_range(PLATFORM_MIN_LENGTH, PLATFORM_MAX_LENGTH + 1)
+                    .min(generated_width - x);
+
+                let has_clearance = (x..x + len).all(|px| {
+                    y + PLATFORM_CLEARANCE <= tops[px as usize]
+                        && get_mirrored(px)
+                            .map(|mx| y + PLATFORM_CLEARANCE <= tops[mx as usize])
+                            .unwrap_or(true)
+                });
+
+                if rng.gen_f32() < params.platform_density && has_clearance {
+                    for px in x..x + len {
+                        for column in [Some(px), get_mirrored(px)].into_iter().flatten() {
+                            set_tile(&mut tile_layer, column, y, params.platform_tile_id);
+                            tops[column as usize] = y;
+                        }
+                    }
+                }
+
+                x += len + PLATFORM_MIN_GAP + rng.gen_range(0, PLATFORM_MIN_GAP);
+            }
+
+            y = y.saturating_sub(PLATFORM_CLEARANCE + rng.gen_range(0, 2));
+        }
+
+        if !params.item_ids.is_empty() {
+            for x in 1..generated_width.saturating_sub(1) {
+                if rng.gen_f32() < params.item_density {
+                    let i = rng.gen_range(0, params.item_ids.len() as u32) as usize;
+                    let id = &params.item_ids[i];
+
+                    for column in [Some(x), get_mirrored(x)].into_iter().flatten() {
+                        let top = tops[column as usize];
+                        if top > 0 {
+                            let position = map.to_position(uvec2(column, top - 1));
+                            let object = MapObject::new(id, MapObjectKind::Item, position);
+                            items_layer.objects.push(object);
+                        }
+                    }
+                }
+            }
+        }
+
+        for i in 0..params.spawn_point_cnt {
+            let x = (((i as f32 + 0.5) * width as f32) / params.spawn_point_cnt as f32) as u32;
+            let x = x.min(width - 1);
+
+            let y = surfaces[x as usize].saturating_sub(SPAWN_POINT_HEIGHT);
+
+            map.spawn_points.push(map.to_position(uvec2(x, y)));
+        }
+
+        map.tilesets.insert(tileset.id.clone(), tileset);
+
+        for layer in [tile_layer, items_layer] {
+            map.draw_order.push(layer.id.clone());
+            map.layers.insert(layer.id.clone(), layer);
+        }
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(seed: u64, is_symmetric: bool) -> MapGenerationParams {
+        MapGenerationParams {
+            seed,
+            grid_size: uvec2(40, 24),
+            tile_size: vec2(32.0, 32.0),
+            tileset_texture_id: "default_tileset".to_string(),
+            tileset_texture_size: uvec2(224, 288),
+            surface_tile_id: 1,
+            fill_tile_id: 8,
+            platform_tile_id: 58,
+            platform_density: 0.5,
+            item_density: 0.2,
+            item_ids: vec!["sword".to_string(), "musket".to_string()],
+            spawn_point_cnt: 4,
+            is_symmetric,
+        }
+    }
+
+    fn get_tile_ids(map: &Map) -> Vec<Option<u32>> {
+        map.layers[GENERATED_MAP_TILE_LAYER_ID]
+            .tiles
+            .iter()
+            .map(|tile| tile.as_ref().map(|tile| tile.tile_id))
+            .collect()
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let a = Map::generate(&params(42, false));
+        let b = Map::generate(&params(42, false));
+
+        assert_eq!(get_tile_ids(&a), get_tile_ids(&b));
+        assert_eq!(a.spawn_points, b.spawn_points);
+        assert_eq!(a.spawn_points.len(), 4);
+    }
+
+    #[test]
+    fn test_symmetric_generation_is_mirrored() {
+        let map = Map::generate(&params(7, true));
+        let tiles = get_tile_ids(&map);
+
+        let width = map.grid_size.x as usize;
+        for row in tiles.chunks(width) {
+            let mirrored = row.iter().rev().copied().collect::<Vec<_>>();
+            assert_eq!(row, mirrored.as_slice());
+        }
+    }
+}
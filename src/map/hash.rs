@@ -0,0 +1,96 @@
+//! Content hashing of maps, used to detect corrupted or modified map files and to verify that
+//! networked clients have loaded the same map as the host.
+
+use core::Result;
+
+use super::Map;
+
+use crate::json::MapDef;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A 64-bit FNV-1a hash. This is used in stead of the hasher in `std`, as that is not guaranteed
+/// to produce the same output across platforms and releases.
+pub(crate) fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+impl Map {
+    /// Returns a hash of the canonical serialized form of the map, excluding the hash field.
+    /// The map is serialized through `serde_json::Value`, which sorts the keys of all objects, so
+    /// the order of the hash maps that the map holds does not affect the result.
+    pub fn get_content_hash(&self) -> Result<String> {
+        let def = MapDef::from(self.clone());
+
+        let value = serde_json::to_value(&def)?;
+        let canonical = serde_json::to_string(&value)?;
+
+        Ok(format!("{:016x}", fnv1a_64(canonical.as_bytes())))
+    }
+
+    /// Serialize the map, with its content hash included
+    pub(crate) fn to_json_with_hash(&self) -> Result<String> {
+        let mut def = MapDef::from(self.clone());
+        def.hash = Some(self.get_content_hash()?);
+
+        let json = serde_json::to_string_pretty(&def)?;
+
+        Ok(json)
+    }
+
+    /// Deserialize a map and verify its content hash, if one is present. A mismatch will not
+    /// prevent the map from loading, but a warning will be printed, as the file has either been
+    /// edited by hand or is corrupted.
+    pub(crate) fn from_json_with_hash(bytes: &[u8]) -> Result<Map> {
+        let def: MapDef = serde_json::from_slice(bytes)?;
+        let hash = def.hash.clone();
+
+        let map = Map::from(def);
+
+        if let Some(hash) = hash {
+            let content_hash = map.get_content_hash()?;
+            if hash != content_hash {
+                #[cfg(debug_assertions)]
+                println!(
+                    "WARNING: Map hash mismatch (expected '{}', got '{}'). The map file has been modified or is corrupted!",
+                    hash, content_hash
+                );
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use macroquad::prelude::*;
+
+    #[test]
+    fn test_content_hash_excludes_hash_field() {
+        let map = Map::new(vec2(32.0, 32.0), uvec2(10, 10));
+
+        let json = map.to_json_with_hash().unwrap();
+        assert!(json.contains(&map.get_content_hash().unwrap()));
+
+        let loaded = Map::from_json_with_hash(json.as_bytes()).unwrap();
+        assert_eq!(
+            loaded.get_content_hash().unwrap(),
+            map.get_content_hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_content_hash_detects_changes() {
+        let a = Map::new(vec2(32.0, 32.0), uvec2(10, 10));
+        let mut b = a.clone();
+        b.spawn_points.push(vec2(64.0, 64.0));
+
+        assert_ne!(a.get_content_hash().unwrap(), b.get_content_hash().unwrap());
+    }
+}
@@ -4,17 +4,36 @@ use macroquad::{color, experimental::collections::storage, prelude::*};
 
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "binary-maps")]
+mod binary;
+mod boundaries;
+mod cost;
 mod crab;
+mod daily;
 mod decoration;
 mod fish_school;
+mod generate;
+mod hash;
 mod player_interaction;
 mod sproinger;
+mod thumbnail;
+mod tile_animation;
 
+#[cfg(feature = "binary-maps")]
+pub use binary::*;
+pub use boundaries::*;
+pub use cost::*;
 pub use crab::*;
+pub use daily::*;
 pub use decoration::*;
 pub use fish_school::*;
+pub use generate::*;
 pub use player_interaction::*;
 pub use sproinger::*;
+pub use thumbnail::*;
+pub use tile_animation::*;
+
+pub(crate) use hash::fnv1a_64;
 
 use core::math::URect;
 use core::text::ToStringHelper;
@@ -66,6 +85,10 @@ pub struct Map {
     pub properties: HashMap<String, MapProperty>,
     #[serde(default, with = "core::json::vec2_vec")]
     pub spawn_points: Vec<Vec2>,
+    #[serde(default, skip_serializing_if = "MapInfo::is_empty")]
+    pub info: MapInfo,
+    #[serde(default, skip_serializing_if = "MapBoundaries::is_default")]
+    pub boundaries: MapBoundaries,
 }
 
 impl Map {
@@ -90,6 +113,8 @@ impl Map {
             draw_order: Vec::new(),
             properties: HashMap::new(),
             spawn_points: Vec::new(),
+            info: MapInfo::default(),
+            boundaries: MapBoundaries::default(),
         }
     }
 
@@ -97,7 +122,19 @@ impl Map {
         let path = path.as_ref();
 
         let bytes = load_file(&path.to_string_helper()).await?;
-        let map = serde_json::from_slice(&bytes).unwrap();
+
+        #[cfg(all(feature = "binary-maps", not(target_family = "wasm")))]
+        if let Some(map) = Map::load_binary_cache(path, &bytes) {
+            return Ok(map);
+        }
+
+        let map = Map::from_json_with_hash(&bytes).unwrap();
+
+        #[cfg(all(feature = "binary-maps", not(target_family = "wasm")))]
+        if let Err(err) = map.save_binary_cache(path, &bytes) {
+            #[cfg(debug_assertions)]
+            println!("WARNING: Unable to write map cache: {}", err);
+        }
 
         Ok(map)
     }
@@ -126,7 +163,8 @@ impl Map {
 
     /// Get the playable map area.
     ///
-    /// Any player that doesn't overlap the play area will be killed.
+    /// What happens to players that leave the play area is determined by `boundaries`, which
+    /// defaults to killing them.
     pub fn get_playable_area(&self) -> Rect {
         let size = self.get_size();
         Rect::new(
@@ -215,13 +253,15 @@ impl Map {
 
         let mut collisions = Vec::new();
 
-        let platform_attr = Self::PLATFORM_TILE_ATTRIBUTE.to_string();
-
         for layer in self.layers.values() {
             if layer.is_visible && layer.has_collision {
                 for (x, y, tile) in self.get_tiles(&layer.id, Some(grid)) {
                     if let Some(tile) = tile {
-                        if !(should_ignore_platforms && tile.attributes.contains(&platform_attr)) {
+                        let collision = self.get_tile_collision(tile);
+
+                        if collision == MapTileCollision::Solid
+                            || (collision == MapTileCollision::Platform && !should_ignore_platforms)
+                        {
                             let tile_position = self.to_position(uvec2(x, y));
 
                             let tile_rect = Rect::new(
@@ -252,10 +292,11 @@ impl Map {
         for layer in self.layers.values() {
             if layer.is_visible && layer.has_collision {
                 if let Some(Some(tile)) = layer.tiles.get(index) {
-                    return !(should_ignore_platforms
-                        && tile
-                            .attributes
-                            .contains(&Self::PLATFORM_TILE_ATTRIBUTE.to_string()));
+                    match self.get_tile_collision(tile) {
+                        MapTileCollision::Solid => return true,
+                        MapTileCollision::Platform => return !should_ignore_platforms,
+                        MapTileCollision::None => {}
+                    }
                 }
             }
         }
@@ -263,6 +304,25 @@ impl Map {
         false
     }
 
+    /// Returns the collision type of a tile on a layer that has collision. This is read from the
+    /// tiles tileset, falling back to the tiles attributes for tiles that have no collision
+    /// type specified in their tileset.
+    pub fn get_tile_collision(&self, tile: &MapTile) -> MapTileCollision {
+        self.tilesets
+            .get(&tile.tileset_id)
+            .and_then(|tileset| tileset.tile_collisions.get(&tile.tile_id).copied())
+            .unwrap_or_else(|| {
+                if tile
+                    .attributes
+                    .contains(&Self::PLATFORM_TILE_ATTRIBUTE.to_string())
+                {
+                    MapTileCollision::Platform
+                } else {
+                    MapTileCollision::Solid
+                }
+            })
+    }
+
     fn background_parallax(texture: Texture2D, depth: f32, camera_pos: Vec2) -> Rect {
         let w = texture.width();
         let h = texture.height();
@@ -349,6 +409,9 @@ impl Map {
         let mut draw_order = self.draw_order.clone();
         draw_order.reverse();
 
+        // All animated tiles are driven by this clock, so that they stay in sync
+        let time = get_time();
+
         let resources = storage::get::<Resources>();
         for layer_id in draw_order {
             if let Some(layer) = self.layers.get(&layer_id) {
@@ -363,6 +426,14 @@ impl Map {
                                     panic!("No texture with id '{}'!", tile.texture_id)
                                 });
 
+                            let texture_coords = match self.tilesets.get(&tile.tileset_id) {
+                                Some(tileset) if !tileset.tile_animations.is_empty() => {
+                                    let tile_id = tileset.get_animated_tile_id(tile.tile_id, time);
+                                    tileset.get_texture_coords(tile_id)
+                                }
+                                _ => tile.texture_coords,
+                            };
+
                             draw_texture_ex(
                                 texture_entry.texture,
                                 world_position.x,
@@ -370,10 +441,10 @@ impl Map {
                                 color::WHITE,
                                 DrawTextureParams {
                                     source: Some(Rect::new(
-                                        tile.texture_coords.x, // + 0.1,
-                                        tile.texture_coords.y, // + 0.1,
-                                        self.tile_size.x,      // - 0.2,
-                                        self.tile_size.y,      // - 0.2,
+                                        texture_coords.x, // + 0.1,
+                                        texture_coords.y, // + 0.1,
+                                        self.tile_size.x, // - 0.2,
+                                        self.tile_size.y, // - 0.2,
                                     )),
                                     dest_size: Some(vec2(self.tile_size.x, self.tile_size.y)),
                                     ..Default::default()
@@ -400,7 +471,7 @@ impl Map {
 
     #[cfg(any(target_family = "unix", target_family = "windows"))]
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let json = serde_json::to_string_pretty(self)?;
+        let json = self.to_json_with_hash()?;
         std::fs::write(path, json)?;
         Ok(())
     }
@@ -410,10 +481,69 @@ impl Map {
         Ok(())
     }
 
+    /// Returns a random spawn point, or the center of the map if it has no spawn points
     pub fn get_random_spawn_point(&self) -> Vec2 {
+        if self.spawn_points.is_empty() {
+            return self.get_fallback_spawn_point();
+        }
+
         let i = rand::gen_range(0, self.spawn_points.len()) as usize;
         self.spawn_points[i]
     }
+
+    /// Returns the spawn point at the specified position in the spawn order, wrapping around if
+    /// there are fewer spawn points than `i`. This is used to assign spawns in a round robin
+    /// fashion, in the order set in the editor. If the map has no spawn points, the center of the
+    /// map is returned.
+    pub fn get_ordered_spawn_point(&self, i: usize) -> Vec2 {
+        if self.spawn_points.is_empty() {
+            return self.get_fallback_spawn_point();
+        }
+
+        self.spawn_points[i % self.spawn_points.len()]
+    }
+
+    fn get_fallback_spawn_point(&self) -> Vec2 {
+        self.world_offset + self.get_size() / 2.0
+    }
+}
+
+/// This holds authoring information about a map, like who made it and how many players it is
+/// designed for. This is shown when browsing maps and the tags can be used to filter them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MapInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recommended_players: Option<u32>,
+}
+
+impl MapInfo {
+    pub fn is_empty(&self) -> bool {
+        *self == MapInfo::default()
+    }
+
+    /// Returns `true` if the map has a tag that matches the specified tag, ignoring case
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Returns `true` if any of the maps tags contains the specified filter, ignoring case.
+    /// An empty filter will match all maps.
+    pub fn matches_tag_filter(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+
+        filter.is_empty()
+            || self
+                .tags
+                .iter()
+                .any(|tag| tag.to_lowercase().contains(&filter))
+    }
 }
 
 pub struct MapTileIterator<'a> {
@@ -536,6 +666,51 @@ impl MapLayer {
             ..Default::default()
         }
     }
+
+    /// Returns `true` if the layer holds no tiles and no objects
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty() && self.tiles.iter().all(|tile| tile.is_none())
+    }
+
+    /// Returns `true` if the layer can be converted to the specified kind without losing any data.
+    /// This is only the case for empty layers, as tiles and objects can not be converted.
+    pub fn can_convert_to(&self, kind: MapLayerKind) -> bool {
+        self.kind != kind && self.is_empty()
+    }
+
+    /// Returns the indices of all the objects that are in the specified group
+    pub fn get_group_members(&self, group: u32) -> Vec<usize> {
+        self.objects
+            .iter()
+            .enumerate()
+            .filter_map(|(i, object)| {
+                if object.group == Some(group) {
+                    Some(i)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the index of the object at `index`, along with the indices of any objects that
+    /// share its group
+    pub fn get_object_with_group(&self, index: usize) -> Vec<usize> {
+        match self.objects.get(index).and_then(|object| object.group) {
+            Some(group) => self.get_group_members(group),
+            None => vec![index],
+        }
+    }
+
+    /// Returns a group id that is not used by any objects on the layer
+    pub fn get_next_group_id(&self) -> u32 {
+        self.objects
+            .iter()
+            .filter_map(|object| object.group)
+            .max()
+            .map(|group| group + 1)
+            .unwrap_or_default()
+    }
 }
 
 impl Default for MapLayer {
@@ -565,6 +740,32 @@ pub struct MapTile {
     pub attributes: Vec<String>,
 }
 
+/// The collision type of a tile, when it is placed on a layer that has collision
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapTileCollision {
+    Solid,
+    Platform,
+    None,
+}
+
+impl MapTileCollision {
+    /// Returns the next collision type, used when cycling through them in the editor
+    pub fn next(self) -> Self {
+        match self {
+            Self::Solid => Self::Platform,
+            Self::Platform => Self::None,
+            Self::None => Self::Solid,
+        }
+    }
+}
+
+impl Default for MapTileCollision {
+    fn default() -> Self {
+        Self::Solid
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 #[serde(rename_all = "snake_case")]
@@ -646,6 +847,10 @@ pub struct MapObject {
     pub position: Vec2,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub properties: HashMap<String, MapProperty>,
+    /// Objects that share a group id, on the same layer, are selected, moved, copied and deleted
+    /// together, in the editor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<u32>,
 }
 
 impl MapObject {
@@ -655,6 +860,7 @@ impl MapObject {
             kind,
             position,
             properties: HashMap::new(),
+            group: None,
         }
     }
 }
@@ -680,6 +886,13 @@ pub struct MapTileset {
     pub autotile_mask: Vec<bool>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub tile_attributes: HashMap<u32, Vec<String>>,
+    /// The collision types of the tiles in the tileset, by tile id. Tiles that are not in this
+    /// map will be solid, unless they have the platform attribute.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tile_collisions: HashMap<u32, MapTileCollision>,
+    /// The animations of animated tiles in the tileset, by the id of the tile that is placed
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tile_animations: HashMap<u32, MapTileAnimation>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub properties: HashMap<String, MapProperty>,
     #[serde(skip)]
@@ -719,6 +932,8 @@ impl MapTileset {
             tile_subdivisions,
             autotile_mask,
             tile_attributes: HashMap::new(),
+            tile_collisions: HashMap::new(),
+            tile_animations: HashMap::new(),
             properties: HashMap::new(),
             bitmasks: None,
         }
@@ -730,6 +945,15 @@ impl MapTileset {
         vec2(x, y)
     }
 
+    /// Returns the id of the tile that should be drawn in place of the specified tile, at the
+    /// specified time on the shared clock. This is the tile itself, if it is not animated.
+    pub fn get_animated_tile_id(&self, tile_id: u32, time: f64) -> u32 {
+        self.tile_animations
+            .get(&tile_id)
+            .and_then(|animation| animation.get_tile_id(time))
+            .unwrap_or(tile_id)
+    }
+
     pub fn default_tile_subdivisions() -> UVec2 {
         uvec2(3, 3)
     }
@@ -770,3 +994,25 @@ impl MapTileset {
         Some(bitmasks)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordered_spawn_points() {
+        let mut map = Map::new(vec2(16.0, 16.0), uvec2(10, 8));
+
+        // Maps without spawn points fall back to the center of the map
+        assert_eq!(map.get_ordered_spawn_point(3), vec2(80.0, 64.0));
+        assert_eq!(map.get_random_spawn_point(), vec2(80.0, 64.0));
+
+        map.spawn_points = vec![vec2(1.0, 0.0), vec2(2.0, 0.0), vec2(3.0, 0.0)];
+
+        let spawn_points = (0..5)
+            .map(|i| map.get_ordered_spawn_point(i).x)
+            .collect::<Vec<_>>();
+
+        assert_eq!(spawn_points, vec![1.0, 2.0, 3.0, 1.0, 2.0]);
+    }
+}
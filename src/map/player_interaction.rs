@@ -4,6 +4,7 @@ use hecs::{Entity, World};
 use macroquad::prelude::collections::storage;
 
 use crate::{
+    game::{dispatch_game_event, GameEvent},
     items::{RespawnInfo, RespawningItem, RespawningItemKind, Weapon},
     player::{Player, PlayerState},
     utils::timer::Timer,
@@ -15,19 +16,35 @@ use super::Map;
 pub fn update_map_kill_zone(world: &mut World) {
     let map = storage::get::<Map>();
 
-    // Kill players out of bounds
-    for (_, (player, transform, body)) in world
-        .query::<(&mut Player, &Transform, &PhysicsBody)>()
+    // Apply the map boundaries to players, killing them if they are out of bounds
+    for (entity, (player, transform, body)) in world
+        .query::<(&mut Player, &mut Transform, &mut PhysicsBody)>()
         .iter()
     {
         let player: &mut Player = player;
-        let transform: &Transform = transform;
-        let body: &PhysicsBody = body;
+        let transform: &mut Transform = transform;
+        let body: &mut PhysicsBody = body;
+
+        if player.state == PlayerState::Dead {
+            continue;
+        }
 
         let player_rect = body.as_rect(transform.position);
 
-        if !map.get_playable_area().overlaps(&player_rect) {
+        let is_out_of_bounds = map.boundaries.apply(
+            &map.get_playable_area(),
+            &player_rect,
+            &mut transform.position,
+            &mut body.velocity,
+        );
+
+        if is_out_of_bounds {
             player.state = PlayerState::Dead;
+
+            dispatch_game_event(GameEvent::PlayerDied {
+                player: entity,
+                killed_by: None,
+            });
         }
     }
 
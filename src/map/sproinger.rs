@@ -6,8 +6,8 @@ use hecs::{Entity, World};
 use core::Result;
 use core::Transform;
 
-use crate::game::play_sound_effect;
-use crate::{Animation, Drawable, PhysicsBody, QueuedAnimationAction};
+use crate::game::{get_fixed_delta, play_sound_effect};
+use crate::{Animation, Drawable, PhysicsBody, PlayDirection, QueuedAnimationAction};
 
 const SPROINGER_DRAW_ORDER: u32 = 2;
 
@@ -44,26 +44,35 @@ pub fn spawn_sproinger(world: &mut World, position: Vec2) -> Result<Entity> {
         Animation {
             id: IDLE_ANIMATION_ID.to_string(),
             row: 0,
+            start_column: 0,
             frames: 1,
             fps: 1,
             tweens: HashMap::new(),
             is_looping: false,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
         },
         Animation {
             id: EXPAND_ANIMATION_ID.to_string(),
             row: 1,
+            start_column: 0,
             frames: 2,
             fps: 8,
             tweens: HashMap::new(),
             is_looping: false,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
         },
         Animation {
             id: CONTRACT_ANIMATION_ID.to_string(),
             row: 2,
+            start_column: 0,
             frames: 2,
             fps: 4,
             tweens: HashMap::new(),
             is_looping: false,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
         },
     ];
 
@@ -82,7 +91,7 @@ pub fn spawn_sproinger(world: &mut World, position: Vec2) -> Result<Entity> {
 }
 
 pub fn fixed_update_sproingers(world: &mut World) {
-    let dt = get_frame_time();
+    let dt = get_fixed_delta();
 
     let bodies = world
         .query::<(&Transform, &PhysicsBody)>()
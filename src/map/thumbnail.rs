@@ -0,0 +1,146 @@
+//! Thumbnails of maps that have no preview image. Generating a thumbnail requires drawing the
+//! whole map, so thumbnails are cached to disk, in the user data directory, keyed by the content
+//! hash of the map. A thumbnail is regenerated when the map changes, as its hash will no longer
+//! match the cached file, and the stale file is removed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use macroquad::prelude::*;
+
+use super::Map;
+
+use crate::paths::get_thumbnails_dir;
+use crate::resources::map_name_to_filename;
+
+/// The width of generated thumbnails, in pixels. The height is determined by the aspect ratio of
+/// the map.
+pub const MAP_THUMBNAIL_WIDTH: f32 = 256.0;
+
+const MAP_THUMBNAIL_EXTENSION: &str = "png";
+
+/// Returns the prefix of the thumbnail files for the map at the specified path
+fn get_thumbnail_prefix(map_path: &str) -> String {
+    format!(
+        "{}_",
+        map_name_to_filename(&map_path.replace(['/', '\\'], "_"))
+    )
+}
+
+/// Returns `true` if `file_name` is a thumbnail with the specified prefix, regardless of hash
+fn is_thumbnail_file(file_name: &str, prefix: &str) -> bool {
+    file_name
+        .strip_prefix(prefix)
+        .and_then(|rest| rest.strip_suffix(&format!(".{}", MAP_THUMBNAIL_EXTENSION)))
+        .map(|hash| hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        .unwrap_or(false)
+}
+
+/// Returns the path of the cached thumbnail for the map at the specified path, with the specified
+/// content hash. This will return `None` if the thumbnails directory can not be determined.
+pub fn get_thumbnail_path(map_path: &str, hash: &str) -> Option<PathBuf> {
+    get_thumbnails_dir().map(|dir| {
+        dir.join(format!("{}{}", get_thumbnail_prefix(map_path), hash))
+            .with_extension(MAP_THUMBNAIL_EXTENSION)
+    })
+}
+
+/// Remove all cached thumbnails for the map at the specified path, except for `current`
+fn remove_stale_thumbnails(map_path: &str, current: &Path) {
+    let dir = match current.parent() {
+        Some(dir) => dir,
+        None => return,
+    };
+
+    let prefix = get_thumbnail_prefix(map_path);
+
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            let is_stale = path != current
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map(|name| is_thumbnail_file(name, &prefix))
+                    .unwrap_or(false);
+
+            if is_stale {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// Draw `map` to a new texture, `MAP_THUMBNAIL_WIDTH` pixels wide
+pub fn generate_map_thumbnail(map: &Map) -> Texture2D {
+    let map_size = map.grid_size.as_f32() * map.tile_size;
+
+    let width = MAP_THUMBNAIL_WIDTH;
+    let height = (width / map_size.x * map_size.y).max(1.0);
+
+    let render_target = render_target(width as u32, height as u32);
+
+    push_camera_state();
+
+    // The zoom is not flipped on the y-axis, as the texture of a render target is upside down,
+    // relative to the screen
+    set_camera(&Camera2D {
+        target: map.world_offset + map_size / 2.0,
+        zoom: vec2(2.0 / map_size.x, 2.0 / map_size.y),
+        render_target: Some(render_target),
+        ..Default::default()
+    });
+
+    clear_background(map.background_color);
+    map.draw(None, true);
+
+    pop_camera_state();
+
+    render_target.texture
+}
+
+/// Returns the thumbnail of `map`, loading it from the cache if it exists. On a cache miss, the
+/// thumbnail is generated and written to the cache.
+pub fn load_or_generate_map_thumbnail(map: &Map, map_path: &str) -> Texture2D {
+    let path = map
+        .get_content_hash()
+        .ok()
+        .and_then(|hash| get_thumbnail_path(map_path, &hash));
+
+    if let Some(path) = &path {
+        if let Ok(bytes) = fs::read(path) {
+            return Texture2D::from_file_with_format(&bytes, Some(ImageFormat::Png));
+        }
+    }
+
+    let texture = generate_map_thumbnail(map);
+
+    if let Some(path) = &path {
+        remove_stale_thumbnails(map_path, path);
+
+        texture
+            .get_texture_data()
+            .export_png(&path.to_string_lossy());
+    }
+
+    texture
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_thumbnail_matching() {
+        let prefix = get_thumbnail_prefix("maps/beach.json");
+
+        let current = format!("{}0123456789abcdef.png", prefix);
+        assert!(is_thumbnail_file(&current, &prefix));
+
+        let other_map = format!("{}island_0123456789abcdef.png", prefix);
+        assert!(!is_thumbnail_file(&other_map, &prefix));
+
+        assert!(!is_thumbnail_file("0123456789abcdef.png", &prefix));
+    }
+}
@@ -0,0 +1,76 @@
+//! Animated tiles cycle through a sequence of tiles, from the same tileset, when they are drawn.
+//! The current frame is derived from one shared clock, at draw time, in stead of being stored per
+//! placed tile, so all instances of an animated tile, including the different tiles of autotiled
+//! terrain, like the edges of a body of water, always animate in sync.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MapTileAnimation {
+    /// The ids of the tiles that make up the frames of the animation
+    pub frames: Vec<u32>,
+    pub fps: f32,
+}
+
+impl MapTileAnimation {
+    /// Returns the index of the frame that is displayed at the specified time, in seconds, on the
+    /// shared clock
+    pub fn get_frame_index(&self, time: f64) -> usize {
+        if self.frames.is_empty() || self.fps <= 0.0 {
+            return 0;
+        }
+
+        (time.max(0.0) * self.fps as f64) as usize % self.frames.len()
+    }
+
+    /// Returns the id of the tile that is displayed at the specified time, in seconds, on the
+    /// shared clock
+    pub fn get_tile_id(&self, time: f64) -> Option<u32> {
+        self.frames.get(self.get_frame_index(time)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use macroquad::prelude::*;
+
+    use super::*;
+
+    use crate::map::MapTileset;
+
+    #[test]
+    fn test_animated_tiles_are_in_sync() {
+        let mut tileset = MapTileset::new("water", "water", uvec2(64, 32), vec2(16.0, 16.0), 1);
+
+        // Two autotiled edge tiles, each with their own frames in the tileset
+        tileset.tile_animations.insert(
+            0,
+            MapTileAnimation {
+                frames: vec![0, 4],
+                fps: 4.0,
+            },
+        );
+
+        tileset.tile_animations.insert(
+            1,
+            MapTileAnimation {
+                frames: vec![1, 5],
+                fps: 4.0,
+            },
+        );
+
+        for &time in &[0.0, 0.3, 1.1, 17.6] {
+            let left = tileset.tile_animations[&0].get_frame_index(time);
+            let right = tileset.tile_animations[&1].get_frame_index(time);
+
+            assert_eq!(left, right);
+
+            assert_eq!(tileset.get_animated_tile_id(0, time), 4 * left as u32);
+            assert_eq!(tileset.get_animated_tile_id(1, time), 1 + 4 * right as u32);
+        }
+
+        // Tiles without an animation are not affected
+        assert_eq!(tileset.get_animated_tile_id(2, 0.3), 2);
+    }
+}
@@ -1,21 +1,166 @@
 //! This module holds the networking core, used
 
+use macroquad::experimental::collections::storage;
+
 use hecs::World;
 
+use core::network::{Api, Lobby, LobbyMapInfo, NetworkEvent, NetworkMessage, PlayerId};
+use core::Result;
+
+use crate::game::{get_tick_rate, reset_game_map, set_tick_rate};
+use crate::map::{Map, MapInfo};
+use crate::Config;
+
+#[cfg(feature = "desync-detection")]
+pub mod state_hash;
+
+/// This should be called by the host when a network game is created, after the tick rate has been
+/// set, so that the lobby holds the settings that clients should adopt when they join, the hash
+/// that they verify their map against, as well as the information about the map that is shown
+/// when browsing lobbies
+pub fn init_network_host(map: &Map) -> Result<()> {
+    Api::dispatch_message(NetworkMessage::UpdateLobbySettings {
+        tick_rate: get_tick_rate(),
+        map_hash: Some(map.get_content_hash()?),
+        map_info: get_lobby_map_info(&map.info),
+    })
+}
+
+/// Returns the information that is shown about a map in lobby listings, if it has any
+pub fn get_lobby_map_info(info: &MapInfo) -> Option<LobbyMapInfo> {
+    if info.is_empty() {
+        return None;
+    }
+
+    Some(LobbyMapInfo {
+        author: info.author.clone(),
+        description: info.description.clone(),
+        tags: info.tags.clone(),
+        recommended_players: info.recommended_players,
+    })
+}
+
+/// This is called by clients when joining a lobby, and whenever it changes, as the host is
+/// authoritative on the tick rate. It will return an error if the tick rate of the lobby is out
+/// of bounds.
+pub fn apply_lobby_tick_rate(lobby: &Lobby) -> Result<()> {
+    set_tick_rate(lobby.tick_rate)?;
+
+    lobby.verify_tick_rate(get_tick_rate())
+}
+
+/// This should be called by clients when joining a lobby, after the map has been loaded. It will
+/// print a warning if the map does not match the one of the host, and return `true` if the client
+/// should request a map transfer, with `NetworkMessage::RequestMap`.
+pub fn verify_lobby_map(lobby: &Lobby, map: &Map) -> Result<bool> {
+    let map_hash = map.get_content_hash()?;
+
+    if let Err(err) = lobby.verify_map_hash(&map_hash) {
+        #[cfg(debug_assertions)]
+        println!("WARNING: {}", err);
+
+        let config = storage::get::<Config>();
+        return Ok(config.network.is_map_transfer_enabled);
+    }
+
+    Ok(false)
+}
+
+/// This is called by clients when they join a lobby, and whenever it changes. It will request
+/// the map from the host if the local map does not match it.
+fn on_lobby_changed(lobby: &Lobby) -> Result<()> {
+    if let Err(err) = apply_lobby_tick_rate(lobby) {
+        #[cfg(debug_assertions)]
+        println!("WARNING: {}", err);
+    }
+
+    let should_request_map = {
+        let map = storage::get::<Map>();
+        verify_lobby_map(lobby, &map)?
+    };
+
+    if should_request_map {
+        Api::dispatch_message(NetworkMessage::RequestMap)?;
+    }
+
+    Ok(())
+}
+
+/// This is called by the host when a client requests the map, with `NetworkMessage::RequestMap`
+fn send_map(player_id: PlayerId) -> Result<()> {
+    let map = storage::get::<Map>().to_json_with_hash()?;
+
+    Api::dispatch_message(NetworkMessage::TransferMap { player_id, map })
+}
+
+/// This replaces the local map with the map that was sent by the host in response to
+/// `NetworkMessage::RequestMap`, rebuilding the game state around it
+fn apply_transferred_map(world: &mut World, map: &str) -> Result<()> {
+    let map = Map::from_json_with_hash(map.as_bytes())?;
+
+    reset_game_map(world, map)
+}
+
 pub fn update_network_client(world: &mut World) {
     update_network_common(world);
+
+    while let Some(event) = Api::next_event() {
+        let res = match event {
+            NetworkEvent::LobbyChanged { lobby } => on_lobby_changed(&lobby),
+            NetworkEvent::MessageReceived {
+                message: NetworkMessage::TransferMap { map, .. },
+                ..
+            } => apply_transferred_map(world, &map),
+            #[cfg(feature = "desync-detection")]
+            NetworkEvent::MessageReceived {
+                message: NetworkMessage::StateHash { tick, hash },
+                ..
+            } => {
+                state_hash::on_state_hash_received(tick, hash);
+                Ok(())
+            }
+            _ => Ok(()),
+        };
+
+        if let Err(err) = res {
+            #[cfg(debug_assertions)]
+            println!("WARNING: {}", err);
+        }
+    }
 }
 
 pub fn fixed_update_network_client(world: &mut World) {
     fixed_update_network_common(world);
+
+    #[cfg(feature = "desync-detection")]
+    state_hash::fixed_update_state_hash_client(world);
 }
 
 pub fn update_network_host(world: &mut World) {
     update_network_common(world);
+
+    while let Some(event) = Api::next_event() {
+        if let NetworkEvent::MessageReceived {
+            player_id,
+            message: NetworkMessage::RequestMap,
+        } = event
+        {
+            if let Err(err) = send_map(player_id) {
+                #[cfg(debug_assertions)]
+                println!("WARNING: {}", err);
+            }
+        }
+    }
 }
 
 pub fn fixed_update_network_host(world: &mut World) {
     fixed_update_network_common(world);
+
+    #[cfg(feature = "desync-detection")]
+    if let Err(err) = state_hash::fixed_update_state_hash_host(world) {
+        #[cfg(debug_assertions)]
+        println!("WARNING: {}", err);
+    }
 }
 
 fn update_network_common(_world: &mut World) {}
@@ -0,0 +1,322 @@
+//! Hashing of the game state, used to detect desyncs between network clients and the host. A
+//! checksum of the state that is shared by all peers is recorded every tick, and clients compare
+//! their checksums to the ones sent by the host, to find the first tick where their states
+//! diverged.
+//!
+//! This is only compiled with the `desync-detection` feature, as it is a debugging tool.
+
+use std::collections::VecDeque;
+
+use macroquad::experimental::collections::storage;
+use macroquad::prelude::*;
+
+use hecs::World;
+
+use core::network::{Api, NetworkMessage};
+use core::{Result, Transform};
+
+use crate::map::fnv1a_64;
+use crate::player::Player;
+
+/// The number of ticks that the hashes of the local state are kept for, to be compared with the
+/// hashes of the host, which will arrive some ticks later
+const HISTORY_LEN: usize = 600;
+
+/// The number of ticks between each state hash that the host sends to the clients
+const STATE_HASH_INTERVAL: u64 = 60;
+
+/// This collects the values that make up the game state in a fixed byte order, so that the hash
+/// will be the same on all platforms
+#[derive(Default)]
+struct StateHasher {
+    bytes: Vec<u8>,
+}
+
+impl StateHasher {
+    fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_f32(&mut self, value: f32) {
+        self.write_u32(value.to_bits());
+    }
+
+    fn write_bool(&mut self, value: bool) {
+        self.bytes.push(value as u8);
+    }
+
+    fn write_vec2(&mut self, value: Vec2) {
+        self.write_f32(value.x);
+        self.write_f32(value.y);
+    }
+
+    fn finish(&self) -> u64 {
+        fnv1a_64(&self.bytes)
+    }
+}
+
+/// Returns a hash of the game state that is shared by all peers. Clients do not simulate physics,
+/// items or effects, so this only includes the state of the players, which is the index, state,
+/// position and facing of each. Players are ordered by index, so the result does not depend on the
+/// iteration order of the world.
+pub fn get_state_hash(world: &World) -> u64 {
+    let mut hasher = StateHasher::default();
+
+    let mut players = world
+        .query::<(&Player, &Transform)>()
+        .iter()
+        .map(|(_, (player, transform))| {
+            (
+                player.index,
+                player.state as u32,
+                transform.position,
+                player.is_facing_left,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    players.sort_by_key(|&(index, ..)| index);
+
+    for (index, state, position, is_facing_left) in players {
+        hasher.write_u32(index as u32);
+        hasher.write_u32(state);
+        hasher.write_vec2(position);
+        hasher.write_bool(is_facing_left);
+    }
+
+    hasher.finish()
+}
+
+/// This keeps the state hashes of the last ticks, so that they can be compared with the hashes of
+/// the host, as they arrive, and holds the first tick where a desync was detected.
+pub struct DesyncDetector {
+    tick: u64,
+    /// This is `false`, on clients, until the first state hash is received from the host, at which
+    /// point the local tick is set to the tick of the host
+    is_tick_synced: bool,
+    history: VecDeque<(u64, u64)>,
+    /// The state hashes received from the host, that are waiting for the local state of the same
+    /// tick to be recorded
+    remote_hashes: VecDeque<(u64, u64)>,
+    first_desync_tick: Option<u64>,
+}
+
+impl DesyncDetector {
+    pub fn new() -> Self {
+        DesyncDetector {
+            tick: 0,
+            is_tick_synced: false,
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            remote_hashes: VecDeque::new(),
+            first_desync_tick: None,
+        }
+    }
+
+    /// Record the state hash of the current tick and advance to the next one. This returns the
+    /// tick that the hash was recorded for.
+    pub fn record(&mut self, hash: u64) -> u64 {
+        let tick = self.tick;
+
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+
+        self.history.push_back((tick, hash));
+        self.tick += 1;
+
+        tick
+    }
+
+    /// Returns the local state hash of the specified tick, if it is still held
+    pub fn get_hash(&self, tick: u64) -> Option<u64> {
+        self.history
+            .iter()
+            .find(|&&(recorded_tick, _)| recorded_tick == tick)
+            .map(|&(_, hash)| hash)
+    }
+
+    /// Compare a state hash received from the host with the local one of the same tick. This will
+    /// return `false` if they differ, and log the tick if it is the first desync that is found.
+    /// Ticks that are no longer, or not yet, held are ignored.
+    pub fn verify(&mut self, tick: u64, remote_hash: u64) -> bool {
+        let is_in_sync = match self.get_hash(tick) {
+            Some(hash) => hash == remote_hash,
+            None => return true,
+        };
+
+        if !is_in_sync && self.first_desync_tick.map_or(true, |first| tick < first) {
+            println!(
+                "WARNING: Desync detected at tick {} (local hash {:016x}, host hash {:016x})",
+                tick,
+                self.get_hash(tick).unwrap(),
+                remote_hash,
+            );
+
+            self.first_desync_tick = Some(tick);
+        }
+
+        is_in_sync
+    }
+
+    /// Queue a state hash received from the host, to be verified by `verify_remote_hashes`, once
+    /// the local state of the same tick has been recorded.
+    ///
+    /// The ticks of clients are counted from when they joined, so the local tick is set to the tick
+    /// of the first hash that is received, discarding what was recorded before. The state of the
+    /// host reaches clients with the same latency as the hashes, so the next state that is
+    /// recorded corresponds to that tick of the host.
+    pub fn queue_remote_hash(&mut self, tick: u64, remote_hash: u64) {
+        if !self.is_tick_synced {
+            self.tick = tick;
+            self.history.clear();
+            self.is_tick_synced = true;
+        }
+
+        self.remote_hashes.push_back((tick, remote_hash));
+    }
+
+    /// Verify the queued state hashes of the host, for all the ticks that have been recorded
+    pub fn verify_remote_hashes(&mut self) {
+        while let Some(&(tick, remote_hash)) = self.remote_hashes.front() {
+            if tick >= self.tick {
+                break;
+            }
+
+            self.remote_hashes.pop_front();
+            self.verify(tick, remote_hash);
+        }
+    }
+}
+
+impl Default for DesyncDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// This should be called when a network game is created
+pub fn init_desync_detector() {
+    storage::store(DesyncDetector::new());
+}
+
+/// This should be called by the host, every fixed update. The state hash is recorded every tick,
+/// and sent to the clients every `STATE_HASH_INTERVAL` ticks.
+pub fn fixed_update_state_hash_host(world: &World) -> Result<()> {
+    let hash = get_state_hash(world);
+    let tick = storage::get_mut::<DesyncDetector>().record(hash);
+
+    if tick % STATE_HASH_INTERVAL == 0 {
+        Api::dispatch_message(NetworkMessage::StateHash { tick, hash })?;
+    }
+
+    Ok(())
+}
+
+/// This should be called by clients, every fixed update. The state hash is recorded, and compared
+/// to the hashes of the same ticks that have been received from the host.
+pub fn fixed_update_state_hash_client(world: &World) {
+    let hash = get_state_hash(world);
+
+    let mut detector = storage::get_mut::<DesyncDetector>();
+    detector.record(hash);
+    detector.verify_remote_hashes();
+}
+
+/// This should be called by clients, when a `NetworkMessage::StateHash` is received from the host
+pub fn on_state_hash_received(tick: u64, hash: u64) {
+    storage::get_mut::<DesyncDetector>().queue_remote_hash(tick, hash);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_hash_is_deterministic() {
+        let mut a = World::default();
+        let mut b = World::default();
+
+        a.spawn((
+            Player::new(0, Vec2::ZERO),
+            Transform::from(vec2(10.0, 20.0)),
+        ));
+        a.spawn((
+            Player::new(1, Vec2::ZERO),
+            Transform::from(vec2(30.0, 40.0)),
+        ));
+
+        // The same state, spawned in a different order
+        b.spawn((
+            Player::new(1, Vec2::ZERO),
+            Transform::from(vec2(30.0, 40.0)),
+        ));
+        let player = b.spawn((
+            Player::new(0, Vec2::ZERO),
+            Transform::from(vec2(10.0, 20.0)),
+        ));
+
+        assert_eq!(get_state_hash(&a), get_state_hash(&b));
+
+        b.get_mut::<Transform>(player).unwrap().position.x += 0.001;
+
+        assert_ne!(get_state_hash(&a), get_state_hash(&b));
+    }
+
+    #[test]
+    fn test_first_desync_tick() {
+        let mut detector = DesyncDetector::new();
+
+        for hash in [1, 2, 3, 4] {
+            detector.record(hash);
+        }
+
+        assert!(detector.verify(0, 1));
+        assert!(detector.verify(1, 2));
+        assert!(!detector.verify(3, 5));
+        assert!(!detector.verify(2, 6));
+        assert!(!detector.verify(3, 7));
+
+        assert_eq!(detector.first_desync_tick, Some(2));
+
+        // Ticks that are not held can not be compared
+        assert!(detector.verify(10, 0));
+    }
+
+    #[test]
+    fn test_remote_hashes_wait_for_local_state() {
+        let mut detector = DesyncDetector::new();
+
+        // The client joined late, so it has counted fewer ticks than the host
+        for _ in 0..100 {
+            detector.record(1);
+        }
+
+        // The first hash of the host sets the local tick, so it is not compared with older state
+        detector.queue_remote_hash(600, 1);
+        detector.verify_remote_hashes();
+
+        assert_eq!(detector.remote_hashes.len(), 1);
+        assert_eq!(detector.get_hash(0), None);
+
+        assert_eq!(detector.record(1), 600);
+        detector.verify_remote_hashes();
+
+        assert!(detector.remote_hashes.is_empty());
+        assert_eq!(detector.first_desync_tick, None);
+
+        // Later hashes are held until the same tick has been recorded locally
+        detector.queue_remote_hash(660, 3);
+        detector.verify_remote_hashes();
+
+        assert_eq!(detector.remote_hashes.len(), 1);
+
+        for _ in 0..60 {
+            detector.record(2);
+        }
+
+        detector.verify_remote_hashes();
+
+        assert!(detector.remote_hashes.is_empty());
+        assert_eq!(detector.first_desync_tick, Some(660));
+    }
+}
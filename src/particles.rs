@@ -1,4 +1,5 @@
 use macroquad::experimental::collections::storage;
+use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation};
 use macroquad::prelude::*;
 use std::collections::HashMap;
 
@@ -13,6 +14,159 @@ use core::Transform;
 
 use crate::{AnimatedSpriteMetadata, Resources};
 
+const ADDITIVE_VERTEX_SHADER: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}"#;
+
+const ADDITIVE_FRAGMENT_SHADER: &str = r#"#version 100
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D Texture;
+
+void main() {
+    gl_FragColor = color * texture2D(Texture, uv);
+}"#;
+
+/// This determines how particles are blended with what has already been drawn
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticleBlendMode {
+    /// Regular alpha blending
+    Alpha,
+    /// The color of the particles is added to the background, which makes them glow. This is
+    /// useful for fire and energy effects.
+    Additive,
+}
+
+impl ParticleBlendMode {
+    pub fn is_alpha(&self) -> bool {
+        *self == ParticleBlendMode::Alpha
+    }
+}
+
+impl Default for ParticleBlendMode {
+    fn default() -> Self {
+        ParticleBlendMode::Alpha
+    }
+}
+
+/// The shape that particles are spawned from, relative to the offset of the emitter. Spawn
+/// positions are not random, so an emitter will spawn the same particles on every client.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ParticleEmitterShape {
+    /// All particles are spawned at the offset of the emitter
+    Point,
+    /// Particles are spread evenly along a line, from the offset of the emitter to `end`,
+    /// relative to the offset
+    Line {
+        #[serde(with = "core::json::vec2_def")]
+        end: Vec2,
+    },
+    /// Particles are spread evenly around a circle, centered on the offset of the emitter
+    Circle { radius: f32 },
+    /// Particles are spread over a box, centered on the offset of the emitter
+    Box {
+        #[serde(with = "core::json::vec2_def")]
+        size: Vec2,
+    },
+}
+
+impl ParticleEmitterShape {
+    pub fn is_point(&self) -> bool {
+        *self == ParticleEmitterShape::Point
+    }
+
+    /// Returns the position of particle number `index` out of `count`, in emission number
+    /// `emission`. The emission is only used for boxes, so that consecutive emissions fill the box
+    /// rather than repeat the same positions.
+    pub fn get_position(&self, index: u32, count: u32, emission: u32) -> Vec2 {
+        match *self {
+            ParticleEmitterShape::Point => Vec2::ZERO,
+            ParticleEmitterShape::Line { end } => {
+                let t = if count > 1 {
+                    index as f32 / (count - 1) as f32
+                } else {
+                    0.5
+                };
+
+                end * t
+            }
+            ParticleEmitterShape::Circle { radius } => {
+                let angle = std::f32::consts::TAU * index as f32 / count.max(1) as f32;
+
+                vec2(angle.cos(), angle.sin()) * radius
+            }
+            ParticleEmitterShape::Box { size } => {
+                // This is an additive recurrence (R2) sequence, which fills the box evenly
+                // without clumping, the way random positions would
+                const ALPHA_X: f32 = 0.754_877_7;
+                const ALPHA_Y: f32 = 0.569_840_3;
+
+                let n = emission.wrapping_mul(count.max(1)).wrapping_add(index) as f32;
+                let t = vec2((0.5 + n * ALPHA_X).fract(), (0.5 + n * ALPHA_Y).fract());
+
+                (t - vec2(0.5, 0.5)) * size
+            }
+        }
+    }
+
+    /// Returns the direction away from the shape, at the position of particle number `index`, as
+    /// returned by `get_position`. For lines this is the normal that points up, for a line that
+    /// goes from left to right, and for boxes it is the normal of the nearest edge. Points have no
+    /// normal, so this will return `None` for those.
+    pub fn get_normal(&self, index: u32, count: u32, emission: u32) -> Option<Vec2> {
+        match *self {
+            ParticleEmitterShape::Point => None,
+            ParticleEmitterShape::Line { end } => {
+                let normal = vec2(end.y, -end.x).normalize_or_zero();
+
+                if normal == Vec2::ZERO {
+                    None
+                } else {
+                    Some(normal)
+                }
+            }
+            ParticleEmitterShape::Circle { .. } => {
+                let angle = std::f32::consts::TAU * index as f32 / count.max(1) as f32;
+
+                Some(vec2(angle.cos(), angle.sin()))
+            }
+            ParticleEmitterShape::Box { size } => {
+                let position = self.get_position(index, count, emission);
+                let distance = size / 2.0 - position.abs();
+
+                if distance.x < distance.y {
+                    Some(vec2(position.x.signum(), 0.0))
+                } else {
+                    Some(vec2(0.0, position.y.signum()))
+                }
+            }
+        }
+    }
+}
+
+impl Default for ParticleEmitterShape {
+    fn default() -> Self {
+        ParticleEmitterShape::Point
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ParticleEmitterMetadata {
@@ -35,6 +189,19 @@ pub struct ParticleEmitterMetadata {
     /// Amount of emissions per activation. If set to `None` it will emit indefinitely
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub emissions: Option<u32>,
+    /// The shape that particles are spawned from
+    #[serde(default, skip_serializing_if = "ParticleEmitterShape::is_point")]
+    pub shape: ParticleEmitterShape,
+    /// If this is set to `true` particles will move away from the shape, along its normal at the
+    /// position they are spawned at, in stead of in the initial direction of the particle effect
+    #[serde(default, skip_serializing_if = "core::json::is_false")]
+    pub is_velocity_normal: bool,
+    /// The amount of particle effects spawned on the shape, for each emission
+    #[serde(
+        default = "ParticleEmitterMetadata::default_count",
+        skip_serializing_if = "ParticleEmitterMetadata::is_default_count"
+    )]
+    pub count: u32,
     /// This is a temporary hack that enables texture based effects until we add texture support
     /// to our macroquad-particles fork
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -42,6 +209,18 @@ pub struct ParticleEmitterMetadata {
     /// If this is set to `true` the `ParticleController` will start to emit automatically
     #[serde(default, skip_serializing_if = "core::json::is_false")]
     pub should_autostart: bool,
+    #[serde(default, skip_serializing_if = "ParticleBlendMode::is_alpha")]
+    pub blend_mode: ParticleBlendMode,
+}
+
+impl ParticleEmitterMetadata {
+    pub fn default_count() -> u32 {
+        1
+    }
+
+    pub fn is_default_count(count: &u32) -> bool {
+        *count == Self::default_count()
+    }
 }
 
 impl Default for ParticleEmitterMetadata {
@@ -51,23 +230,32 @@ impl Default for ParticleEmitterMetadata {
             offset: Vec2::ZERO,
             delay: 0.0,
             emissions: None,
+            shape: ParticleEmitterShape::default(),
+            is_velocity_normal: false,
+            count: Self::default_count(),
             interval: 0.0,
             animations: None,
             should_autostart: false,
+            blend_mode: ParticleBlendMode::default(),
         }
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct ParticleEmitter {
     pub particle_effect_id: String,
     pub offset: Vec2,
     pub delay: f32,
     pub emissions: Option<u32>,
     pub interval: f32,
+    pub shape: ParticleEmitterShape,
+    pub is_velocity_normal: bool,
+    pub count: u32,
     pub emission_cnt: u32,
     pub delay_timer: f32,
     pub interval_timer: f32,
     pub is_active: bool,
+    pub blend_mode: ParticleBlendMode,
 }
 
 impl ParticleEmitter {
@@ -78,10 +266,14 @@ impl ParticleEmitter {
             delay: meta.delay,
             interval: meta.interval,
             emissions: meta.emissions,
+            shape: meta.shape,
+            is_velocity_normal: meta.is_velocity_normal,
+            count: meta.count,
             emission_cnt: 0,
             delay_timer: 0.0,
             interval_timer: meta.interval,
             is_active: meta.should_autostart,
+            blend_mode: meta.blend_mode,
         }
     }
 
@@ -113,11 +305,21 @@ impl From<ParticleEmitterMetadata> for ParticleEmitter {
     }
 }
 
-pub fn update_one_particle_emitter(
-    mut position: Vec2,
-    rotation: f32,
-    emitter: &mut ParticleEmitter,
-) {
+fn rotate_vector(vector: Vec2, rotation: f32) -> Vec2 {
+    if rotation == 0.0 {
+        return vector;
+    }
+
+    let sin = rotation.sin();
+    let cos = rotation.cos();
+
+    vec2(
+        cos * vector.x - sin * vector.y,
+        sin * vector.x + cos * vector.y,
+    )
+}
+
+pub fn update_one_particle_emitter(position: Vec2, rotation: f32, emitter: &mut ParticleEmitter) {
     let dt = get_frame_time();
 
     if emitter.is_active {
@@ -130,34 +332,41 @@ pub fn update_one_particle_emitter(
         if emitter.delay_timer >= emitter.delay && emitter.interval_timer >= emitter.interval {
             emitter.interval_timer = 0.0;
 
-            if rotation == 0.0 {
-                position += emitter.offset;
-            } else {
-                let offset_position = position + emitter.offset;
-
-                let sin = rotation.sin();
-                let cos = rotation.cos();
-
-                position = Vec2::new(
-                    cos * (offset_position.x - position.x) - sin * (offset_position.y - position.y)
-                        + position.x,
-                    sin * (offset_position.x - position.x)
-                        + cos * (offset_position.y - position.y)
-                        + position.y,
-                );
-            }
-
             let mut particles = storage::get_mut::<Particles>();
-            let cache = particles
-                .cache_map
-                .get_mut(&emitter.particle_effect_id)
+
+            let count = emitter.count.max(1);
+
+            for i in 0..count {
+                let offset =
+                    emitter.offset + emitter.shape.get_position(i, count, emitter.emission_cnt);
+
+                let direction = if emitter.is_velocity_normal {
+                    emitter
+                        .shape
+                        .get_normal(i, count, emitter.emission_cnt)
+                        .map(|normal| rotate_vector(normal, rotation))
+                } else {
+                    None
+                };
+
+                let cache = match direction {
+                    Some(direction) => particles.get_directed_cache_mut(
+                        &emitter.particle_effect_id,
+                        emitter.blend_mode,
+                        direction,
+                    ),
+                    None => {
+                        particles.get_cache_mut(&emitter.particle_effect_id, emitter.blend_mode)
+                    }
+                }
                 .unwrap();
 
-            cache.spawn(position);
+                cache.spawn(position + rotate_vector(offset, rotation));
+            }
 
-            if let Some(emissions) = emitter.emissions {
-                emitter.emission_cnt += 1;
+            emitter.emission_cnt = emitter.emission_cnt.wrapping_add(1);
 
+            if let Some(emissions) = emitter.emissions {
                 if emissions > 0 && emitter.emission_cnt >= emissions {
                     emitter.is_active = false;
                 }
@@ -178,17 +387,55 @@ pub fn update_particle_emitters(world: &mut World) {
     }
 }
 
+/// Draw all particles. Particles are batched by blend mode, so that the blend state is only
+/// switched once per frame.
 pub fn draw_particles(_world: &mut World) {
     let mut particles = storage::get_mut::<Particles>();
 
     for cache in particles.cache_map.values_mut() {
         cache.draw();
     }
+
+    for ((_, blend_mode, _), cache) in particles.directed_cache_map.iter_mut() {
+        if blend_mode.is_alpha() {
+            cache.draw();
+        }
+    }
+
+    let has_additive = !particles.additive_cache_map.is_empty()
+        || particles
+            .directed_cache_map
+            .keys()
+            .any(|(_, blend_mode, _)| !blend_mode.is_alpha());
+
+    if has_additive {
+        gl_use_material(particles.additive_material);
+
+        for cache in particles.additive_cache_map.values_mut() {
+            cache.draw();
+        }
+
+        for ((_, blend_mode, _), cache) in particles.directed_cache_map.iter_mut() {
+            if !blend_mode.is_alpha() {
+                cache.draw();
+            }
+        }
+
+        gl_use_default_material();
+    }
 }
 
-#[derive(Default)]
 pub struct Particles {
+    /// The caches of particles that use alpha blending
     pub cache_map: HashMap<String, EmittersCache>,
+    /// The caches of particles that use additive blending. These are created the first time an
+    /// emitter with additive blending is used for an effect.
+    pub additive_cache_map: HashMap<String, EmittersCache>,
+    /// The caches of particles that are emitted in a direction other than the initial direction
+    /// of their effect, keyed by effect, blend mode and direction, in whole degrees. These are
+    /// created the first time a particle is emitted in that direction.
+    pub directed_cache_map: HashMap<(String, ParticleBlendMode, i32), EmittersCache>,
+    additive_material: Material,
 }
 
 impl Particles {
@@ -203,6 +450,151 @@ impl Particles {
             cache_map.insert(id.clone(), EmittersCache::new(config));
         }
 
-        Particles { cache_map }
+        let additive_material = load_material(
+            ADDITIVE_VERTEX_SHADER,
+            ADDITIVE_FRAGMENT_SHADER,
+            MaterialParams {
+                pipeline_params: PipelineParams {
+                    color_blend: Some(BlendState::new(
+                        Equation::Add,
+                        BlendFactor::Value(BlendValue::SourceAlpha),
+                        BlendFactor::One,
+                    )),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        Particles {
+            cache_map,
+            additive_cache_map: HashMap::new(),
+            directed_cache_map: HashMap::new(),
+            additive_material,
+        }
+    }
+
+    /// Returns the cache of the specified particle effect, for the specified blend mode, or `None`
+    /// if the particle effect does not exist
+    pub fn get_cache_mut(
+        &mut self,
+        particle_effect_id: &str,
+        blend_mode: ParticleBlendMode,
+    ) -> Option<&mut EmittersCache> {
+        match blend_mode {
+            ParticleBlendMode::Alpha => self.cache_map.get_mut(particle_effect_id),
+            ParticleBlendMode::Additive => {
+                if !self.additive_cache_map.contains_key(particle_effect_id) {
+                    let resources = storage::get::<Resources>();
+                    let config = resources
+                        .particle_effects
+                        .get(particle_effect_id)
+                        .cloned()?;
+
+                    self.additive_cache_map
+                        .insert(particle_effect_id.to_string(), EmittersCache::new(config));
+                }
+
+                self.additive_cache_map.get_mut(particle_effect_id)
+            }
+        }
+    }
+
+    /// Returns the cache of the specified particle effect, for the specified blend mode, with the
+    /// initial direction of the effect replaced by `direction`, or `None` if the particle effect
+    /// does not exist
+    pub fn get_directed_cache_mut(
+        &mut self,
+        particle_effect_id: &str,
+        blend_mode: ParticleBlendMode,
+        direction: Vec2,
+    ) -> Option<&mut EmittersCache> {
+        let degrees = direction.y.atan2(direction.x).to_degrees().round() as i32;
+        let key = (particle_effect_id.to_string(), blend_mode, degrees);
+
+        if !self.directed_cache_map.contains_key(&key) {
+            let resources = storage::get::<Resources>();
+            let mut config = resources
+                .particle_effects
+                .get(particle_effect_id)
+                .cloned()?;
+
+            let angle = (degrees as f32).to_radians();
+            config.initial_direction = vec2(angle.cos(), angle.sin());
+
+            self.directed_cache_map
+                .insert(key.clone(), EmittersCache::new(config));
+        }
+
+        self.directed_cache_map.get_mut(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emitter_shape_positions() {
+        let circle = ParticleEmitterShape::Circle { radius: 16.0 };
+        for i in 0..8 {
+            let position = circle.get_position(i, 8, 0);
+            assert!((position.length() - 16.0).abs() < 0.001);
+        }
+
+        let line = ParticleEmitterShape::Line {
+            end: vec2(32.0, 0.0),
+        };
+        assert_eq!(line.get_position(0, 3, 0), Vec2::ZERO);
+        assert_eq!(line.get_position(1, 3, 0), vec2(16.0, 0.0));
+        assert_eq!(line.get_position(2, 3, 0), vec2(32.0, 0.0));
+
+        let size = vec2(64.0, 32.0);
+        let shape = ParticleEmitterShape::Box { size };
+        for emission in 0..4 {
+            for i in 0..4 {
+                let position = shape.get_position(i, 4, emission);
+                assert!(position.abs().cmple(size / 2.0).all());
+                assert_eq!(position, shape.get_position(i, 4, emission));
+            }
+        }
+
+        assert_eq!(
+            ParticleEmitterShape::Point.get_position(3, 4, 2),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn test_emitter_shape_normals() {
+        let circle = ParticleEmitterShape::Circle { radius: 16.0 };
+        for i in 0..8 {
+            let position = circle.get_position(i, 8, 0);
+            let normal = circle.get_normal(i, 8, 0).unwrap();
+            assert!((normal - position / 16.0).length() < 0.001);
+        }
+
+        let line = ParticleEmitterShape::Line {
+            end: vec2(32.0, 0.0),
+        };
+        assert_eq!(line.get_normal(1, 3, 0), Some(vec2(0.0, -1.0)));
+
+        let line = ParticleEmitterShape::Line { end: Vec2::ZERO };
+        assert_eq!(line.get_normal(0, 1, 0), None);
+
+        // Particles on a box move away from the center, through the nearest edge
+        let size = vec2(64.0, 8.0);
+        let shape = ParticleEmitterShape::Box { size };
+        for emission in 0..4 {
+            for i in 0..4 {
+                let position = shape.get_position(i, 4, emission);
+                let normal = shape.get_normal(i, 4, emission).unwrap();
+                assert_eq!(normal.length(), 1.0);
+                assert!(normal.dot(position) >= 0.0);
+            }
+        }
+
+        assert_eq!(ParticleEmitterShape::Point.get_normal(0, 1, 0), None);
     }
 }
@@ -0,0 +1,119 @@
+//! Resolution of the per-user directories that the config, saves, thumbnails, screenshots and
+//! user made maps are stored in. The directories are resolved per platform, using the
+//! `directories` crate, and created if they do not exist. All the functions in this module will
+//! return `None` if the directory can not be determined or created, for example on WASM, in which
+//! case the caller should fall back to a location relative to the working directory.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// If this is set, it will be used as the user data directory, in stead of the platform default
+pub const DATA_DIR_ENV_VAR: &str = "JUMPY_DATA";
+
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+const SAVES_DIR_NAME: &str = "saves";
+const SCREENSHOTS_DIR_NAME: &str = "screenshots";
+const USER_MAPS_DIR_NAME: &str = "maps";
+const THUMBNAILS_DIR_NAME: &str = "thumbnails";
+
+#[cfg(not(target_family = "wasm"))]
+fn get_project_dirs() -> Option<directories::ProjectDirs> {
+    directories::ProjectDirs::from("org", "Fish Fight", "Jumpy")
+}
+
+/// Create the specified directory, if it does not exist, and return it
+fn ensure_dir(path: PathBuf) -> Option<PathBuf> {
+    if path.is_dir() {
+        return Some(path);
+    }
+
+    match std::fs::create_dir_all(&path) {
+        Ok(()) => Some(path),
+        Err(err) => {
+            #[cfg(debug_assertions)]
+            println!(
+                "WARNING: Unable to create directory '{}': {}",
+                path.display(),
+                err
+            );
+
+            None
+        }
+    }
+}
+
+pub fn get_config_dir() -> Option<PathBuf> {
+    #[cfg(not(target_family = "wasm"))]
+    return get_project_dirs().and_then(|dirs| ensure_dir(dirs.config_dir().to_path_buf()));
+
+    #[cfg(target_family = "wasm")]
+    return None;
+}
+
+pub fn get_data_dir() -> Option<PathBuf> {
+    if let Ok(dir) = env::var(DATA_DIR_ENV_VAR) {
+        return ensure_dir(PathBuf::from(dir));
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    return get_project_dirs().and_then(|dirs| ensure_dir(dirs.data_dir().to_path_buf()));
+
+    #[cfg(target_family = "wasm")]
+    return None;
+}
+
+pub fn get_saves_dir() -> Option<PathBuf> {
+    get_data_dir().and_then(|dir| ensure_dir(dir.join(SAVES_DIR_NAME)))
+}
+
+#[allow(dead_code)]
+pub fn get_screenshots_dir() -> Option<PathBuf> {
+    get_data_dir().and_then(|dir| ensure_dir(dir.join(SCREENSHOTS_DIR_NAME)))
+}
+
+#[allow(dead_code)]
+pub fn get_user_maps_dir() -> Option<PathBuf> {
+    get_data_dir().and_then(|dir| ensure_dir(dir.join(USER_MAPS_DIR_NAME)))
+}
+
+pub fn get_thumbnails_dir() -> Option<PathBuf> {
+    get_data_dir().and_then(|dir| ensure_dir(dir.join(THUMBNAILS_DIR_NAME)))
+}
+
+/// Returns the path of the config file in the config directory. If there is no config file there
+/// yet, but there is one at `legacy_path`, it will be copied over, so that existing settings are
+/// kept. If the config directory can not be determined, `legacy_path` is returned.
+pub fn get_config_file_path<P: AsRef<Path>>(legacy_path: P) -> PathBuf {
+    let legacy_path = legacy_path.as_ref();
+
+    let path = match get_config_dir() {
+        Some(dir) => dir.join(CONFIG_FILE_NAME),
+        None => return legacy_path.to_path_buf(),
+    };
+
+    if !path.exists() && legacy_path.exists() {
+        match std::fs::copy(legacy_path, &path) {
+            Ok(_) => {
+                #[cfg(debug_assertions)]
+                println!(
+                    "Config: Migrated '{}' to '{}'",
+                    legacy_path.display(),
+                    path.display()
+                );
+            }
+            Err(err) => {
+                #[cfg(debug_assertions)]
+                println!(
+                    "WARNING: Unable to migrate config file '{}': {}",
+                    legacy_path.display(),
+                    err
+                );
+
+                return legacy_path.to_path_buf();
+            }
+        }
+    }
+
+    path
+}
@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 
 use hecs::World;
 
+use crate::game::{get_fixed_delta, get_tick_scale};
+use crate::map::MapTileCollision;
 use crate::{CollisionWorld, Map};
 use core::{
     math::{deg_to_rad, IsZero},
@@ -29,13 +31,10 @@ pub fn create_collision_world(map: &Map) -> CollisionWorld {
         if layer.has_collision {
             for (i, (_, _, tile)) in map.get_tiles(layer_id, None).enumerate() {
                 if let Some(tile) = tile {
-                    if tile
-                        .attributes
-                        .contains(&Map::PLATFORM_TILE_ATTRIBUTE.to_string())
-                    {
-                        static_colliders[i] = Tile::JumpThrough;
-                    } else {
-                        static_colliders[i] = Tile::Solid;
+                    match map.get_tile_collision(tile) {
+                        MapTileCollision::Solid => static_colliders[i] = Tile::Solid,
+                        MapTileCollision::Platform => static_colliders[i] = Tile::JumpThrough,
+                        MapTileCollision::None => {}
                     }
                 }
             }
@@ -103,6 +102,9 @@ pub struct PhysicsBody {
     pub has_friction: bool,
     pub can_rotate: bool,
     pub bouncyness: f32,
+    /// The number of times the body has bounced off solid tiles, which it will do if
+    /// `bouncyness` is above zero
+    pub bounce_cnt: u32,
     pub is_deactivated: bool,
     pub gravity: f32,
 }
@@ -128,6 +130,7 @@ impl PhysicsBody {
             has_friction: params.has_friction,
             can_rotate: params.can_rotate,
             bouncyness: params.bouncyness,
+            bounce_cnt: 0,
             is_deactivated: false,
             gravity: params.gravity,
         }
@@ -143,65 +146,88 @@ pub fn fixed_update_physics_bodies(world: &mut World) {
     let mut collision_world = storage::get_mut::<CollisionWorld>();
 
     for (_, (transform, body)) in world.query_mut::<(&mut Transform, &mut PhysicsBody)>() {
-        collision_world.set_actor_position(body.actor, transform.position + body.offset);
+        fixed_update_physics_body(&mut collision_world, transform, body);
+    }
+}
 
-        if !body.is_deactivated {
-            let position = collision_world.actor_pos(body.actor);
+/// Advance the simulation of a single body by one tick. The body's actor must belong to
+/// `collision_world`.
+pub fn fixed_update_physics_body(
+    collision_world: &mut CollisionWorld,
+    transform: &mut Transform,
+    body: &mut PhysicsBody,
+) {
+    collision_world.set_actor_position(body.actor, transform.position + body.offset);
 
-            {
-                let position = position + vec2(0.0, 1.0);
+    if !body.is_deactivated {
+        let position = collision_world.actor_pos(body.actor);
 
-                body.was_on_ground = body.is_on_ground;
+        {
+            let position = position + vec2(0.0, 1.0);
 
-                body.is_on_ground = collision_world.collide_check(body.actor, position);
+            body.was_on_ground = body.is_on_ground;
 
-                // FIXME: Using this to set `is_on_ground` caused weird glitching behavior when jumping up through platforms
-                let tile = collision_world.collide_solids(
-                    position,
-                    body.size.x as i32,
-                    body.size.y as i32,
-                );
+            body.is_on_ground = collision_world.collide_check(body.actor, position);
 
-                body.is_on_platform = tile == Tile::JumpThrough;
-            }
+            // FIXME: Using this to set `is_on_ground` caused weird glitching behavior when jumping up through platforms
+            let tile =
+                collision_world.collide_solids(position, body.size.x as i32, body.size.y as i32);
 
-            if !body.is_on_ground && body.has_mass {
-                body.velocity.y += body.gravity;
+            body.is_on_platform = tile == Tile::JumpThrough;
+        }
 
-                if body.velocity.y > TERMINAL_VELOCITY {
-                    body.velocity.y = TERMINAL_VELOCITY;
-                }
-            }
+        let tick_scale = get_tick_scale();
 
-            if !collision_world.move_h(body.actor, body.velocity.x) {
-                body.velocity.x *= -body.bouncyness;
-            }
+        if !body.is_on_ground && body.has_mass {
+            body.velocity.y += body.gravity * tick_scale;
 
-            if !collision_world.move_v(body.actor, body.velocity.y) {
-                body.velocity.y *= -body.bouncyness;
+            if body.velocity.y > TERMINAL_VELOCITY {
+                body.velocity.y = TERMINAL_VELOCITY;
             }
+        }
 
-            if body.can_rotate {
-                apply_rotation(
-                    transform,
-                    &mut body.velocity,
-                    body.angular_velocity,
-                    body.is_on_ground,
-                );
-            }
+        if !collision_world.move_h(body.actor, body.velocity.x * tick_scale)
+            && bounce(&mut body.velocity.x, body.bouncyness)
+        {
+            body.bounce_cnt += 1;
+        }
 
-            if body.is_on_ground && body.has_friction {
-                body.velocity.x *= FRICTION_LERP;
-                if body.velocity.x.abs() <= STOP_THRESHOLD {
-                    body.velocity.x = 0.0;
-                }
-            }
+        if !collision_world.move_v(body.actor, body.velocity.y * tick_scale)
+            && bounce(&mut body.velocity.y, body.bouncyness)
+        {
+            body.bounce_cnt += 1;
+        }
+
+        if body.can_rotate {
+            apply_rotation(
+                transform,
+                &mut body.velocity,
+                body.angular_velocity,
+                body.is_on_ground,
+            );
+        }
 
-            transform.position = collision_world.actor_pos(body.actor) - body.offset;
+        if body.is_on_ground && body.has_friction {
+            body.velocity.x *= FRICTION_LERP.powf(tick_scale);
+            if body.velocity.x.abs() <= STOP_THRESHOLD {
+                body.velocity.x = 0.0;
+            }
         }
+
+        transform.position = collision_world.actor_pos(body.actor) - body.offset;
     }
 }
 
+// Reflect a velocity component, that was stopped by a collision, scaled by `bouncyness`. This
+// returns `true` if it was a bounce, and not just the body coming to rest against a tile.
+fn bounce(velocity: &mut f32, bouncyness: f32) -> bool {
+    let is_bounce = bouncyness > 0.0 && velocity.abs() > STOP_THRESHOLD;
+
+    *velocity *= -bouncyness;
+
+    is_bounce
+}
+
 pub fn debug_draw_physics_bodies(world: &mut World) {
     for (_, (transform, body)) in world.query::<(&Transform, &PhysicsBody)>().iter() {
         if !body.is_deactivated {
@@ -271,7 +297,7 @@ impl RigidBody {
 
 pub fn fixed_update_rigid_bodies(world: &mut World) {
     for (_, (transform, body)) in world.query_mut::<(&mut Transform, &mut RigidBody)>() {
-        transform.position += body.velocity;
+        transform.position += body.velocity * get_tick_scale();
 
         if body.can_rotate {
             apply_rotation(transform, &mut body.velocity, 0.0, false);
@@ -294,9 +320,10 @@ fn apply_rotation(
     is_on_ground: bool,
 ) {
     if !angular_velocity.is_zero() {
-        transform.rotation += deg_to_rad(angular_velocity * get_frame_time());
+        transform.rotation += deg_to_rad(angular_velocity * get_fixed_delta());
     } else if !is_on_ground {
-        transform.rotation += velocity.x.abs() * 0.00045 + velocity.y.abs() * 0.00015;
+        transform.rotation +=
+            (velocity.x.abs() * 0.00045 + velocity.y.abs() * 0.00015) * get_tick_scale();
     } else {
         transform.rotation %= std::f32::consts::PI * 2.0;
 
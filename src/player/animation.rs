@@ -7,12 +7,16 @@ use serde::{Deserialize, Serialize};
 use crate::player::{
     Player, PlayerInventory, PlayerState, BODY_ANIMATED_SPRITE_ID, CROUCH_ANIMATION_ID,
     DEATH_BACK_ANIMATION_ID, DEATH_FORWARD_ANIMATION_ID, FALL_ANIMATION_ID, HAT_MOUNT_TWEEN_ID,
-    IDLE_ANIMATION_ID, ITEM_MOUNT_TWEEN_ID, JUMP_ANIMATION_ID, MOVE_ANIMATION_ID,
+    HURT_ANIMATION_ID, IDLE_ANIMATION_ID, ITEM_MOUNT_TWEEN_ID, JUMP_ANIMATION_ID,
+    LEFT_FIN_ANIMATED_SPRITE_ID, MOVE_ANIMATION_ID, RIGHT_FIN_ANIMATED_SPRITE_ID,
     SLIDE_ANIMATION_ID, WEAPON_MOUNT_TWEEN_ID,
 };
-use crate::{AnimatedSpriteMetadata, AnimationMetadata, Keyframe, TweenMetadata};
+use crate::{AnimatedSpriteMetadata, AnimationMetadata, Keyframe, PlayDirection, TweenMetadata};
 use crate::{Drawable, PhysicsBody};
 
+/// The tint that is blended with the sprites of a frozen player
+const FROZEN_TINT: Color = Color::new(0.55, 0.8, 1.0, 1.0);
+
 /// This is used in stead of `AnimationParams`, as we have different data requirements, in the case
 /// of a player character, compared to most other use cases. We want to have a default animation
 /// set, for instance, that corresponds with the way the core game characters are animated, but
@@ -74,6 +78,8 @@ pub struct PlayerAnimations {
     pub crouch: AnimationMetadata,
     #[serde(default = "PlayerAnimations::default_slide_animation")]
     pub slide: AnimationMetadata,
+    #[serde(default = "PlayerAnimations::default_hurt_animation")]
+    pub hurt: AnimationMetadata,
     #[serde(default = "PlayerAnimations::default_death_back_animation")]
     pub death_back: AnimationMetadata,
     #[serde(default = "PlayerAnimations::default_death_forward_animation")]
@@ -104,6 +110,7 @@ impl PlayerAnimations {
         AnimationMetadata {
             id: IDLE_ANIMATION_ID.to_string(),
             row: 0,
+            start_column: 0,
             frames: 14,
             fps: 12,
             tweens: vec![
@@ -121,6 +128,8 @@ impl PlayerAnimations {
                 },
             ],
             is_looping: true,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
         }
     }
 
@@ -143,6 +152,7 @@ impl PlayerAnimations {
         AnimationMetadata {
             id: MOVE_ANIMATION_ID.to_string(),
             row: 1,
+            start_column: 0,
             frames: 6,
             fps: 10,
             tweens: vec![
@@ -173,6 +183,8 @@ impl PlayerAnimations {
                 },
             ],
             is_looping: true,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
         }
     }
 
@@ -180,10 +192,13 @@ impl PlayerAnimations {
         AnimationMetadata {
             id: JUMP_ANIMATION_ID.to_string(),
             row: 2,
+            start_column: 0,
             frames: 1,
             fps: 5,
             tweens: Vec::new(),
             is_looping: false,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
         }
     }
 
@@ -191,10 +206,13 @@ impl PlayerAnimations {
         AnimationMetadata {
             id: FALL_ANIMATION_ID.to_string(),
             row: 3,
+            start_column: 0,
             frames: 1,
             fps: 8,
             tweens: Vec::new(),
             is_looping: true,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
         }
     }
 
@@ -207,6 +225,7 @@ impl PlayerAnimations {
         AnimationMetadata {
             id: CROUCH_ANIMATION_ID.to_string(),
             row: 4,
+            start_column: 0,
             frames: 1,
             fps: 8,
             tweens: vec![
@@ -224,6 +243,8 @@ impl PlayerAnimations {
                 },
             ],
             is_looping: false,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
         }
     }
 
@@ -231,10 +252,27 @@ impl PlayerAnimations {
         AnimationMetadata {
             id: SLIDE_ANIMATION_ID.to_string(),
             row: 5,
+            start_column: 0,
+            frames: 1,
+            fps: 1,
+            tweens: Vec::new(),
+            is_looping: false,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
+        }
+    }
+
+    pub fn default_hurt_animation() -> AnimationMetadata {
+        AnimationMetadata {
+            id: HURT_ANIMATION_ID.to_string(),
+            row: 5,
+            start_column: 0,
             frames: 1,
             fps: 1,
             tweens: Vec::new(),
             is_looping: false,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
         }
     }
 
@@ -242,10 +280,13 @@ impl PlayerAnimations {
         AnimationMetadata {
             id: DEATH_BACK_ANIMATION_ID.to_string(),
             row: 5,
+            start_column: 0,
             frames: 7,
             fps: 10,
             tweens: Vec::new(),
             is_looping: false,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
         }
     }
 
@@ -253,10 +294,13 @@ impl PlayerAnimations {
         AnimationMetadata {
             id: DEATH_FORWARD_ANIMATION_ID.to_string(),
             row: 6,
+            start_column: 0,
             frames: 7,
             fps: 10,
             tweens: Vec::new(),
             is_looping: false,
+            direction: PlayDirection::Forward,
+            frame_durations: Vec::new(),
         }
     }
 }
@@ -270,6 +314,7 @@ impl Default for PlayerAnimations {
             fall: Self::default_fall_animation(),
             crouch: Self::default_crouch_animation(),
             slide: Self::default_slide_animation(),
+            hurt: Self::default_hurt_animation(),
             death_back: Self::default_death_back_animation(),
             death_forward: Self::default_death_forward_animation(),
         }
@@ -309,6 +354,11 @@ impl From<Vec<AnimationMetadata>> for PlayerAnimations {
                 .find(|&anim| anim.id == *SLIDE_ANIMATION_ID)
                 .cloned()
                 .unwrap(),
+            hurt: vec
+                .iter()
+                .find(|&anim| anim.id == *HURT_ANIMATION_ID)
+                .cloned()
+                .unwrap_or_else(Self::default_hurt_animation),
             death_back: vec
                 .iter()
                 .find(|&anim| anim.id == *DEATH_BACK_ANIMATION_ID)
@@ -332,6 +382,7 @@ impl PlayerAnimations {
             self.fall,
             self.crouch,
             self.slide,
+            self.hurt,
             self.death_back,
             self.death_forward,
         ]
@@ -345,52 +396,274 @@ impl PlayerAnimations {
             self.fall.clone(),
             self.crouch.clone(),
             self.slide.clone(),
+            self.hurt.clone(),
             self.death_back.clone(),
             self.death_forward.clone(),
         ]
     }
 }
 
-pub fn update_player_animations(world: &mut World) {
-    for (_, (player, inventory, body, drawable)) in
-        world.query_mut::<(&Player, &mut PlayerInventory, &PhysicsBody, &mut Drawable)>()
-    {
-        let sprite_set = drawable.get_animated_sprite_set_mut().unwrap();
+/// The states of the player animation state machine. Each state corresponds to one of the
+/// animations in `PlayerAnimations`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PlayerAnimationState {
+    Idle,
+    Moving,
+    Jumping,
+    Falling,
+    Crouching,
+    Sliding,
+    Hurt,
+    DeadBack,
+    DeadForward,
+}
 
-        sprite_set.flip_all_x(player.is_facing_left);
-        sprite_set.flip_all_y(player.is_upside_down);
+impl PlayerAnimationState {
+    pub fn animation_id(&self) -> &'static str {
+        match self {
+            Self::Idle => IDLE_ANIMATION_ID,
+            Self::Moving => MOVE_ANIMATION_ID,
+            Self::Jumping => JUMP_ANIMATION_ID,
+            Self::Falling => FALL_ANIMATION_ID,
+            Self::Crouching => CROUCH_ANIMATION_ID,
+            Self::Sliding => SLIDE_ANIMATION_ID,
+            Self::Hurt => HURT_ANIMATION_ID,
+            Self::DeadBack => DEATH_BACK_ANIMATION_ID,
+            Self::DeadForward => DEATH_FORWARD_ANIMATION_ID,
+        }
+    }
+}
+
+impl Default for PlayerAnimationState {
+    fn default() -> Self {
+        PlayerAnimationState::Idle
+    }
+}
+
+/// This holds the thresholds used when transitioning between the states of the player animation
+/// state machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlayerAnimationTransitions {
+    /// The horizontal speed a grounded player must exceed to transition into the moving state
+    #[serde(default)]
+    pub move_threshold: f32,
+    /// The vertical velocity an airborne player must reach to transition from the jumping state
+    /// into the falling state
+    #[serde(default)]
+    pub fall_threshold: f32,
+    /// The amount of time a manual override will take priority over the state machine, if no
+    /// other duration is specified when it is set
+    #[serde(default = "PlayerAnimationTransitions::default_override_duration")]
+    pub override_duration: f32,
+}
+
+impl PlayerAnimationTransitions {
+    const DEFAULT_OVERRIDE_DURATION: f32 = 0.25;
+
+    pub fn default_override_duration() -> f32 {
+        Self::DEFAULT_OVERRIDE_DURATION
+    }
+}
+
+impl Default for PlayerAnimationTransitions {
+    fn default() -> Self {
+        PlayerAnimationTransitions {
+            move_threshold: 0.0,
+            fall_threshold: 0.0,
+            override_duration: Self::DEFAULT_OVERRIDE_DURATION,
+        }
+    }
+}
+
+/// This component drives the player animations, by picking an animation state from the player
+/// state and the velocity and grounded state of the players `PhysicsBody`.
+/// Gameplay code can set a manual override, which will take priority over the state machine until
+/// it expires, unless the player is hurt or dead.
+#[derive(Debug, Clone, Default)]
+pub struct PlayerAnimator {
+    pub state: PlayerAnimationState,
+    pub transitions: PlayerAnimationTransitions,
+    override_id: Option<String>,
+    override_timer: f32,
+    /// This is `true` once the body sprite has been asked to notify when the override animation
+    /// completes, so that a non-looping override can end before its timer expires
+    is_override_watched: bool,
+    /// The tints of the sprites, from before the player was frozen. This is `Some` while the
+    /// frozen tint is applied.
+    tints_before_freeze: Option<Vec<(String, Color)>>,
+    /// This is `true` while the sprites are hidden, because the player has been gibbed
+    is_hidden: bool,
+}
+
+impl PlayerAnimator {
+    pub fn new(transitions: PlayerAnimationTransitions) -> Self {
+        PlayerAnimator {
+            transitions,
+            ..Default::default()
+        }
+    }
+
+    /// Play the specified animation in stead of the one picked by the state machine, for the
+    /// specified duration, or until the animation completes, if it is not looping. If no duration
+    /// is specified, the `override_duration` of the transitions will be used.
+    pub fn set_override(&mut self, animation_id: &str, duration: Option<f32>) {
+        self.override_id = Some(animation_id.to_string());
+        self.override_timer = duration.unwrap_or(self.transitions.override_duration);
+        self.is_override_watched = false;
+    }
+
+    pub fn clear_override(&mut self) {
+        self.override_id = None;
+        self.override_timer = 0.0;
+        self.is_override_watched = false;
+    }
+
+    pub fn has_override(&self) -> bool {
+        self.override_id.is_some()
+    }
 
-        let animation_id = match player.state {
+    /// Get the next state, based on the current state of the player and its physics body
+    pub fn next_state(&self, player: &Player, body: &PhysicsBody) -> PlayerAnimationState {
+        match player.state {
             PlayerState::Dead => {
                 if player.is_facing_left == player.damage_from_left {
-                    DEATH_BACK_ANIMATION_ID
+                    PlayerAnimationState::DeadBack
                 } else {
-                    DEATH_FORWARD_ANIMATION_ID
+                    PlayerAnimationState::DeadForward
                 }
             }
-            PlayerState::Incapacitated => {
-                // TODO: implement incapacitated
-                unimplemented!();
-            }
-            PlayerState::Sliding => SLIDE_ANIMATION_ID,
-            PlayerState::Crouching => CROUCH_ANIMATION_ID,
+            PlayerState::Incapacitated => PlayerAnimationState::Hurt,
+            PlayerState::Sliding => PlayerAnimationState::Sliding,
+            PlayerState::Crouching => PlayerAnimationState::Crouching,
             _ => {
                 if body.is_on_ground {
-                    if !player.is_attacking && body.velocity.x != 0.0 {
-                        MOVE_ANIMATION_ID
+                    if !player.is_attacking
+                        && body.velocity.x.abs() > self.transitions.move_threshold
+                    {
+                        PlayerAnimationState::Moving
                     } else {
-                        IDLE_ANIMATION_ID
+                        PlayerAnimationState::Idle
                     }
-                } else if body.velocity.y < 0.0 {
-                    JUMP_ANIMATION_ID
+                } else if body.velocity.y < self.transitions.fall_threshold {
+                    PlayerAnimationState::Jumping
                 } else {
-                    FALL_ANIMATION_ID
+                    PlayerAnimationState::Falling
                 }
             }
+        }
+    }
+}
+
+pub fn update_player_animations(world: &mut World) {
+    let dt = get_frame_time();
+
+    for (_, (player, animator, inventory, body, drawable)) in world.query_mut::<(
+        &Player,
+        &mut PlayerAnimator,
+        &mut PlayerInventory,
+        &PhysicsBody,
+        &mut Drawable,
+    )>() {
+        let sprite_set = drawable.get_animated_sprite_set_mut().unwrap();
+
+        sprite_set.flip_all_x(player.is_facing_left);
+        sprite_set.flip_all_y(player.is_upside_down);
+
+        // The fin on the side that faces the viewer is drawn in front of the body, and the other
+        // behind it, so they swap layers when the player turns around
+        let (near_fin_id, far_fin_id) = if player.is_facing_left {
+            (LEFT_FIN_ANIMATED_SPRITE_ID, RIGHT_FIN_ANIMATED_SPRITE_ID)
+        } else {
+            (RIGHT_FIN_ANIMATED_SPRITE_ID, LEFT_FIN_ANIMATED_SPRITE_ID)
         };
 
+        if sprite_set.map.contains_key(near_fin_id) {
+            sprite_set.set_z_index(near_fin_id, 1);
+        }
+
+        if sprite_set.map.contains_key(far_fin_id) {
+            sprite_set.set_z_index(far_fin_id, -1);
+        }
+
+        animator.state = animator.next_state(player, body);
+
+        if player.is_gibbed != animator.is_hidden {
+            if player.is_gibbed {
+                sprite_set.deactivate_all();
+            } else {
+                sprite_set.activate_all();
+            }
+
+            animator.is_hidden = player.is_gibbed;
+        }
+
+        if animator.has_override() {
+            animator.override_timer -= dt;
+
+            let is_interrupted = matches!(
+                animator.state,
+                PlayerAnimationState::Hurt
+                    | PlayerAnimationState::DeadBack
+                    | PlayerAnimationState::DeadForward
+            );
+
+            let is_completed = {
+                let sprite = sprite_set.map.get_mut(BODY_ANIMATED_SPRITE_ID).unwrap();
+                animator.is_override_watched && sprite.take_completed() == animator.override_id
+            };
+
+            if is_interrupted || is_completed || animator.override_timer <= 0.0 {
+                animator.clear_override();
+            }
+        }
+
+        let animation_id = animator
+            .override_id
+            .as_deref()
+            .unwrap_or_else(|| animator.state.animation_id());
+
         sprite_set.set_all(animation_id, false);
 
+        if animator.has_override() && !animator.is_override_watched {
+            let sprite = sprite_set.map.get_mut(BODY_ANIMATED_SPRITE_ID).unwrap();
+
+            // Discard any completion left over from an override that ended on its timer
+            sprite.take_completed();
+            sprite.notify_on_complete(animation_id);
+
+            animator.is_override_watched = true;
+        }
+
+        if player.is_frozen() {
+            if animator.tints_before_freeze.is_none() {
+                let mut tints = Vec::new();
+
+                for (id, sprite) in sprite_set.map.iter_mut() {
+                    tints.push((id.clone(), sprite.tint));
+
+                    sprite.tint = Color::new(
+                        sprite.tint.r * FROZEN_TINT.r,
+                        sprite.tint.g * FROZEN_TINT.g,
+                        sprite.tint.b * FROZEN_TINT.b,
+                        sprite.tint.a * FROZEN_TINT.a,
+                    );
+                }
+
+                animator.tints_before_freeze = Some(tints);
+            }
+
+            sprite_set.stop_all();
+        } else if let Some(tints) = animator.tints_before_freeze.take() {
+            for (id, tint) in tints {
+                if let Some(sprite) = sprite_set.map.get_mut(&id) {
+                    sprite.tint = tint;
+                }
+            }
+
+            sprite_set.play_all();
+        }
+
         let sprite = sprite_set.map.get(BODY_ANIMATED_SPRITE_ID).unwrap();
         let animation = sprite.current_animation();
 
@@ -7,7 +7,7 @@ use macroquad::prelude::*;
 
 use serde::{Deserialize, Serialize};
 
-use crate::player::PlayerAnimationMetadata;
+use crate::player::{PlayerAnimationMetadata, PlayerAnimationTransitions, PlayerGibsMetadata};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -25,6 +25,9 @@ pub struct PlayerCharacterMetadata {
     /// `PlayerCharacterMetadata` entry.
     #[serde(flatten, alias = "animation")]
     pub sprite: PlayerAnimationMetadata,
+    /// This holds the thresholds used by the animation state machine, when picking animations
+    #[serde(default)]
+    pub animation_transitions: PlayerAnimationTransitions,
     /// The size of the players collider.
     /// This should, in general, be smaller than the sprite size
     #[serde(
@@ -79,6 +82,9 @@ pub struct PlayerCharacterMetadata {
     /// This is the gravity of the player character
     #[serde(default = "PlayerCharacterMetadata::default_gravity")]
     pub gravity: f32,
+    /// This holds the parameters of the pieces that the player character breaks into, when it dies
+    #[serde(default)]
+    pub gibs: PlayerGibsMetadata,
 }
 
 impl PlayerCharacterMetadata {
@@ -1,10 +1,21 @@
 use hecs::World;
 
+use macroquad::experimental::collections::storage;
 use macroquad::prelude::*;
 
 use core::network::PlayerId;
 
-use core::input::{collect_local_input, GameInputScheme, PlayerInput};
+use core::input::{
+    collect_local_input, get_analog_stick_value, GameInputScheme, GamepadContext, PlayerInput,
+};
+use core::math::{deg_to_rad, rotate_vector};
+use core::MatchConfig;
+use core::Transform;
+
+use fishsticks::Axis;
+
+use crate::player::{is_same_team, Player, PlayerState};
+use crate::{Config, GameCamera, PhysicsBody};
 
 #[derive(Debug, Clone)]
 pub enum PlayerControllerKind {
@@ -31,6 +42,13 @@ pub struct PlayerController {
     pub should_pickup: bool,
     pub should_attack: bool,
     pub should_slide: bool,
+
+    /// The position in the world that aimed weapons should aim towards. This is only set for
+    /// input schemes that aim with the mouse cursor.
+    pub aim_position: Option<Vec2>,
+    /// The normalized direction that aimed weapons should aim towards. This is set by the right
+    /// stick of gamepads, and it may be adjusted by aim assist.
+    pub aim_direction: Option<Vec2>,
 }
 
 impl From<PlayerControllerKind> for PlayerController {
@@ -44,6 +62,8 @@ impl From<PlayerControllerKind> for PlayerController {
             should_pickup: false,
             should_attack: false,
             should_slide: false,
+            aim_position: None,
+            aim_direction: None,
         }
     }
 }
@@ -57,6 +77,17 @@ impl PlayerController {
         self.should_pickup = false;
         self.should_attack = false;
         self.should_slide = false;
+        self.aim_position = None;
+        self.aim_direction = None;
+    }
+
+    /// Returns the normalized direction from `origin` towards the aim position, if any, or the
+    /// aim direction, if that is set in stead
+    pub fn get_aim_direction(&self, origin: Vec2) -> Option<Vec2> {
+        self.aim_position
+            .map(|position| (position - origin).normalize_or_zero())
+            .filter(|direction| *direction != Vec2::ZERO)
+            .or(self.aim_direction)
     }
 
     pub fn apply_input(&mut self, input: PlayerInput) {
@@ -87,5 +118,174 @@ pub fn update_player_controllers(world: &mut World) {
         };
 
         controller.apply_input(input);
+
+        if let PlayerControllerKind::LocalInput(input_scheme) = &controller.kind {
+            if input_scheme.has_mouse_aim() {
+                let camera = storage::get::<GameCamera>();
+                controller.aim_position = Some(camera.get_cursor_world_position());
+            } else if let GameInputScheme::Gamepad(ix) = input_scheme {
+                let gamepad_context = storage::get::<GamepadContext>();
+
+                if let Some(gamepad) = gamepad_context.gamepad(*ix) {
+                    let stick =
+                        get_analog_stick_value(gamepad, Axis::RightStickX, Axis::RightStickY);
+
+                    if stick != Vec2::ZERO {
+                        controller.aim_direction = Some(stick.normalize());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns `direction` rotated towards the closest of `targets` that is within the aim assist cone
+/// and range, as configured in `config`. The correction is a fraction of the angle to the target,
+/// capped by the max correction, so the aim is never fully locked on to a target.
+pub fn get_assisted_aim_direction(
+    origin: Vec2,
+    direction: Vec2,
+    targets: &[Vec2],
+    config: &MatchConfig,
+) -> Vec2 {
+    if !config.is_aim_assist_enabled || direction == Vec2::ZERO {
+        return direction;
+    }
+
+    let direction = direction.normalize();
+    let cone = deg_to_rad(config.aim_assist_cone);
+
+    let mut closest: Option<(f32, f32)> = None;
+
+    for &target in targets {
+        let to_target = target - origin;
+        let distance = to_target.length();
+
+        if distance == 0.0 || distance > config.aim_assist_range {
+            continue;
+        }
+
+        // This is the signed angle from the aim to the target
+        let angle = direction
+            .perp_dot(to_target)
+            .atan2(direction.dot(to_target));
+
+        if angle.abs() > cone {
+            continue;
+        }
+
+        if closest.map(|(d, _)| distance < d).unwrap_or(true) {
+            closest = Some((distance, angle));
+        }
+    }
+
+    if let Some((_, angle)) = closest {
+        let strength = config
+            .aim_assist_strength
+            .clamp(0.0, MatchConfig::MAX_AIM_ASSIST_STRENGTH);
+
+        let max_correction = deg_to_rad(config.aim_assist_max_correction.max(0.0));
+
+        let correction = (angle * strength).clamp(-max_correction, max_correction);
+
+        return rotate_vector(direction, correction);
+    }
+
+    direction
+}
+
+/// Apply aim assist to the aim of local gamepad players, using the living players that are not on
+/// their team as targets
+pub fn update_player_aim_assist(world: &mut World) {
+    let config = storage::get::<Config>().match_settings.clone();
+
+    if !config.is_aim_assist_enabled {
+        return;
+    }
+
+    let players = world
+        .query::<(&Player, &Transform, &PhysicsBody)>()
+        .iter()
+        .filter(|(_, (player, _, _))| player.state != PlayerState::Dead)
+        .map(|(e, (player, transform, body))| {
+            (e, player.team, body.as_rect(transform.position).center())
+        })
+        .collect::<Vec<_>>();
+
+    for (e, (player, controller, transform, body)) in world
+        .query::<(&Player, &mut PlayerController, &Transform, &PhysicsBody)>()
+        .iter()
+    {
+        let is_gamepad = matches!(
+            controller.kind,
+            PlayerControllerKind::LocalInput(GameInputScheme::Gamepad(..))
+        );
+
+        if let (true, Some(direction)) = (is_gamepad, controller.aim_direction) {
+            let targets = players
+                .iter()
+                .filter(|(other, team, _)| *other != e && !is_same_team(player.team, *team))
+                .map(|(_, _, position)| *position)
+                .collect::<Vec<_>>();
+
+            let origin = body.as_rect(transform.position).center();
+
+            controller.aim_direction = Some(get_assisted_aim_direction(
+                origin, direction, &targets, &config,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aim_assist_is_capped() {
+        let config = MatchConfig {
+            aim_assist_strength: 1.0,
+            aim_assist_cone: 45.0,
+            aim_assist_max_correction: 10.0,
+            ..Default::default()
+        };
+
+        let origin = Vec2::ZERO;
+        let direction = vec2(1.0, 0.0);
+
+        // The target is 30 degrees off, so the correction should be capped at 10 degrees
+        let target = rotate_vector(direction, deg_to_rad(30.0)) * 100.0;
+
+        let res = get_assisted_aim_direction(origin, direction, &[target], &config);
+        let angle = direction.perp_dot(res).atan2(direction.dot(res));
+
+        assert!(angle > 0.0);
+        assert!(angle <= deg_to_rad(10.0) + 0.0001);
+
+        // With a lower strength, the aim is only nudged, even when the correction is below the cap
+        let config = MatchConfig {
+            aim_assist_strength: 0.5,
+            aim_assist_max_correction: 90.0,
+            ..config
+        };
+
+        let res = get_assisted_aim_direction(origin, direction, &[target], &config);
+        let angle = direction.perp_dot(res).atan2(direction.dot(res));
+
+        assert!((angle - deg_to_rad(15.0)).abs() < 0.0001);
+
+        // Targets outside of the cone, or when aim assist is disabled, are ignored
+        let target = rotate_vector(direction, deg_to_rad(60.0)) * 100.0;
+        let res = get_assisted_aim_direction(origin, direction, &[target], &config);
+        assert_eq!(res, direction);
+
+        let config = MatchConfig {
+            is_aim_assist_enabled: false,
+            ..config
+        };
+
+        let target = vec2(100.0, 10.0);
+        let res = get_assisted_aim_direction(origin, direction, &[target], &config);
+        assert_eq!(res, direction);
     }
 }
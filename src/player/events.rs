@@ -1,9 +1,21 @@
 use hecs::{Entity, World};
+use macroquad::color::{Color, RED};
 use macroquad::time::get_frame_time;
 
+use crate::game::{dispatch_game_event, trigger_hitstop, GameEvent};
 use crate::player::{Player, PlayerState};
+use crate::Drawable;
 use serde::{Deserialize, Serialize};
 
+/// The factor that the hitstop duration is multiplied by, when damage from another player is
+/// blocked, as that is less impactful than a hit that lands
+const BLOCKED_HITSTOP_FACTOR: f32 = 0.5;
+
+/// The color that the sprites of a player flash, when damage is received
+const DAMAGE_FLASH_COLOR: Color = RED;
+/// The duration, in seconds, of the flash when damage is received
+const DAMAGE_FLASH_DURATION: f32 = 0.25;
+
 #[derive(Default)]
 pub struct PlayerEventQueue {
     pub queue: Vec<PlayerEvent>,
@@ -68,7 +80,9 @@ impl From<&PlayerEvent> for PlayerEventKind {
 }
 
 pub fn update_player_events(world: &mut World) {
-    for (_, (player, events)) in world.query_mut::<(&mut Player, &mut PlayerEventQueue)>() {
+    for (entity, (player, events, mut drawable)) in
+        world.query_mut::<(&mut Player, &mut PlayerEventQueue, Option<&mut Drawable>)>()
+    {
         let dt = get_frame_time();
 
         events.queue.push(PlayerEvent::Update { dt });
@@ -84,12 +98,38 @@ pub fn update_player_events(world: &mut World) {
         }
 
         while let Some(event) = events.queue.pop() {
-            if let PlayerEvent::ReceiveDamage { is_from_left, .. } = event {
+            if let PlayerEvent::ReceiveDamage {
+                is_from_left,
+                damage_from,
+            } = event
+            {
+                let is_from_other_player = matches!(damage_from, Some(from) if from != entity);
+
                 if (is_from_left && !damage_blocked_left)
                     || (!is_from_left && !damage_blocked_right)
                 {
+                    if let Some(sprite_set) = drawable
+                        .as_deref_mut()
+                        .and_then(|drawable| drawable.get_animated_sprite_set_mut())
+                    {
+                        sprite_set.flash_all(DAMAGE_FLASH_COLOR, DAMAGE_FLASH_DURATION);
+                    }
+
+                    if player.state != PlayerState::Dead {
+                        dispatch_game_event(GameEvent::PlayerDied {
+                            player: entity,
+                            killed_by: damage_from,
+                        });
+
+                        if is_from_other_player {
+                            trigger_hitstop(1.0);
+                        }
+                    }
+
                     player.state = PlayerState::Dead;
                     player.damage_from_left = is_from_left;
+                } else if is_from_other_player {
+                    trigger_hitstop(BLOCKED_HITSTOP_FACTOR);
                 }
             }
         }
@@ -0,0 +1,334 @@
+//! Gibs are the pieces that a player character breaks into, when it dies. The pieces are cut from
+//! the frame that the character was drawn with, at the time of death, so no extra textures are
+//! required. They fly outward, collide with the map, settle and fade out, while the player is
+//! hidden until it respawns.
+//!
+//! The pieces are generated from a seed derived from the player index and the position of death,
+//! so that all peers spawn the same pieces. If the `reduce-gore` accessibility option is set, the
+//! player will disappear in a puff of smoke, in stead.
+
+use macroquad::experimental::collections::storage;
+use macroquad::prelude::*;
+
+use hecs::World;
+
+use serde::{Deserialize, Serialize};
+
+use core::math::{deg_to_rad, rotate_vector};
+use core::Transform;
+
+use crate::game::events::{drain_game_events, subscribe_game_events, GameEventSubscriber};
+use crate::game::GameEvent;
+use crate::map::MapRng;
+use crate::particles::{ParticleBlendMode, Particles};
+use crate::physics::PhysicsBodyParams;
+use crate::player::{Player, PlayerAttributes, BODY_ANIMATED_SPRITE_ID};
+use crate::{CollisionWorld, Config, Drawable, DrawableKind, PhysicsBody, Sprite, DEFAULT_Z_INDEX};
+
+/// The particle effect that is spawned in stead of gibs, if gore is reduced
+pub const GIBS_FALLBACK_PARTICLE_EFFECT_ID: &str = "smoke";
+
+const GIB_DRAW_ORDER: u32 = 3;
+
+/// The time, in seconds, at the end of their lifetime, that gibs spend fading out
+const GIB_FADE_DURATION: f32 = 0.5;
+
+const GIB_BOUNCYNESS: f32 = 0.3;
+
+/// The maximum angular velocity of gibs, in degrees per tick
+const GIB_MAX_ANGULAR_VELOCITY: f32 = 12.0;
+
+/// This is the fraction of the velocity of the player, at the time of death, that is added to the
+/// velocity of each gib
+const GIB_INHERITED_VELOCITY_FACTOR: f32 = 0.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlayerGibsMetadata {
+    /// The number of pieces that the character breaks into
+    #[serde(default = "PlayerGibsMetadata::default_count")]
+    pub count: u32,
+    /// The arc, in degrees, centered on straight up, that pieces are launched within
+    #[serde(default = "PlayerGibsMetadata::default_spread")]
+    pub spread: f32,
+    /// The maximum launch speed of pieces. Each piece is launched at between half of this and the
+    /// full speed.
+    #[serde(default = "PlayerGibsMetadata::default_speed")]
+    pub speed: f32,
+    /// The time, in seconds, before pieces are despawned, including the time they spend fading out
+    #[serde(default = "PlayerGibsMetadata::default_lifetime")]
+    pub lifetime: f32,
+}
+
+impl PlayerGibsMetadata {
+    pub fn default_count() -> u32 {
+        6
+    }
+
+    pub fn default_spread() -> f32 {
+        150.0
+    }
+
+    pub fn default_speed() -> f32 {
+        8.0
+    }
+
+    pub fn default_lifetime() -> f32 {
+        3.0
+    }
+}
+
+impl Default for PlayerGibsMetadata {
+    fn default() -> Self {
+        PlayerGibsMetadata {
+            count: Self::default_count(),
+            spread: Self::default_spread(),
+            speed: Self::default_speed(),
+            lifetime: Self::default_lifetime(),
+        }
+    }
+}
+
+/// A single piece of a player character, as cut from its frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct GibPiece {
+    /// The part of the frame that the piece is drawn with
+    pub source_rect: Rect,
+    /// The position of the center of the piece, relative to the top left corner of the frame
+    pub offset: Vec2,
+    pub velocity: Vec2,
+    pub angular_velocity: f32,
+}
+
+/// Returns the seed used to generate the gibs of the player with the specified index, dying at the
+/// specified position. This is the same on all peers.
+pub fn get_gibs_seed(player_index: u8, position: Vec2) -> u64 {
+    ((player_index as u64) << 56)
+        ^ ((position.x.to_bits() as u64) << 24)
+        ^ position.y.to_bits() as u64
+}
+
+/// Cut `frame` into pieces, on a grid with enough cells to hold `meta.count` pieces, and pick a
+/// launch velocity for each of them
+pub fn get_gib_pieces(meta: &PlayerGibsMetadata, frame: Rect, seed: u64) -> Vec<GibPiece> {
+    let count = meta.count.max(1);
+
+    let cols = (count as f32).sqrt().ceil() as u32;
+    let rows = (count + cols - 1) / cols;
+
+    let size = vec2(frame.w / cols as f32, frame.h / rows as f32);
+
+    let spread = deg_to_rad(meta.spread) / 2.0;
+
+    let mut rng = MapRng(seed);
+
+    (0..count)
+        .map(|i| {
+            let cell = vec2((i % cols) as f32, (i / cols) as f32) * size;
+
+            let angle = (rng.gen_f32() * 2.0 - 1.0) * spread;
+            let speed = meta.speed * (0.5 + rng.gen_f32() * 0.5);
+
+            GibPiece {
+                source_rect: Rect::new(frame.x + cell.x, frame.y + cell.y, size.x, size.y),
+                offset: cell + size / 2.0,
+                velocity: rotate_vector(vec2(0.0, -speed), angle),
+                angular_velocity: (rng.gen_f32() * 2.0 - 1.0) * GIB_MAX_ANGULAR_VELOCITY,
+            }
+        })
+        .collect()
+}
+
+/// This is added to the entities of spawned gibs
+pub struct Gib {
+    timer: f32,
+    lifetime: f32,
+}
+
+struct PlayerGibs {
+    subscriber: GameEventSubscriber,
+    is_gore_reduced: bool,
+}
+
+/// This should be called when a game is created, after the event bus has been created
+pub fn init_player_gibs() {
+    let is_gore_reduced = {
+        let config = storage::get::<Config>();
+        config.accessibility.reduce_gore
+    };
+
+    storage::store(PlayerGibs {
+        subscriber: subscribe_game_events(),
+        is_gore_reduced,
+    });
+}
+
+pub fn spawn_player_gibs(world: &mut World) {
+    let (subscriber, is_gore_reduced) = {
+        let gibs = storage::get::<PlayerGibs>();
+        (gibs.subscriber, gibs.is_gore_reduced)
+    };
+
+    for event in drain_game_events(subscriber) {
+        let player_entity = match event {
+            GameEvent::PlayerDied { player, .. } => player,
+            _ => continue,
+        };
+
+        let mut query = match world.query_one::<(
+            &mut Player,
+            &Transform,
+            &PlayerAttributes,
+            &PhysicsBody,
+            &Drawable,
+        )>(player_entity)
+        {
+            Ok(query) => query,
+            Err(_) => continue,
+        };
+
+        let (player, transform, attributes, body, drawable) = match query.get() {
+            Some(components) => components,
+            None => continue,
+        };
+
+        let sprite = match drawable
+            .get_animated_sprite_set()
+            .and_then(|sprite_set| sprite_set.map.get(BODY_ANIMATED_SPRITE_ID))
+        {
+            Some(sprite) => sprite.clone(),
+            None => continue,
+        };
+
+        player.is_gibbed = true;
+
+        let frame_position = transform.position + sprite.offset;
+
+        if is_gore_reduced {
+            let position = frame_position + sprite.size() / 2.0;
+
+            let mut particles = storage::get_mut::<Particles>();
+            if let Some(cache) =
+                particles.get_cache_mut(GIBS_FALLBACK_PARTICLE_EFFECT_ID, ParticleBlendMode::Alpha)
+            {
+                cache.spawn(position);
+            }
+
+            continue;
+        }
+
+        let seed = get_gibs_seed(player.index, transform.position);
+        let pieces = get_gib_pieces(&attributes.gibs, sprite.source_rect(), seed);
+
+        let inherited_velocity = body.velocity * GIB_INHERITED_VELOCITY_FACTOR;
+        let lifetime = attributes.gibs.lifetime;
+
+        drop(query);
+
+        for piece in pieces {
+            let size = piece.source_rect.size() * sprite.scale;
+
+            let mut offset = piece.offset * sprite.scale;
+            if sprite.is_flipped_x {
+                offset.x = sprite.size().x - offset.x;
+            }
+
+            if sprite.is_flipped_y {
+                offset.y = sprite.size().y - offset.y;
+            }
+
+            let position = frame_position + offset;
+
+            let actor = {
+                let mut collision_world = storage::get_mut::<CollisionWorld>();
+                collision_world.add_actor(position, (size.x as i32).max(1), (size.y as i32).max(1))
+            };
+
+            let drawable = Drawable {
+                z_index: DEFAULT_Z_INDEX,
+                draw_order: GIB_DRAW_ORDER,
+                kind: DrawableKind::Sprite(Sprite {
+                    texture: sprite.texture,
+                    source_rect: piece.source_rect,
+                    tint: sprite.tint,
+                    scale: sprite.scale,
+                    offset: -size / 2.0,
+                    pivot: None,
+                    is_flipped_x: sprite.is_flipped_x,
+                    is_flipped_y: sprite.is_flipped_y,
+                    is_deactivated: false,
+                }),
+                offset: Vec2::ZERO,
+            };
+
+            world.spawn((
+                Gib {
+                    timer: 0.0,
+                    lifetime,
+                },
+                Transform::from(position),
+                PhysicsBody::new(
+                    actor,
+                    piece.velocity + inherited_velocity,
+                    PhysicsBodyParams {
+                        offset: -size / 2.0,
+                        size,
+                        angular_velocity: piece.angular_velocity,
+                        bouncyness: GIB_BOUNCYNESS,
+                        ..Default::default()
+                    },
+                ),
+                drawable,
+            ));
+        }
+    }
+}
+
+pub fn update_gibs(world: &mut World) {
+    let dt = get_frame_time();
+
+    let mut to_despawn = Vec::new();
+
+    for (entity, (gib, drawable)) in world.query_mut::<(&mut Gib, &mut Drawable)>() {
+        gib.timer += dt;
+
+        if gib.timer >= gib.lifetime {
+            to_despawn.push(entity);
+        } else if let Some(sprite) = drawable.get_sprite_mut() {
+            sprite.tint.a = ((gib.lifetime - gib.timer) / GIB_FADE_DURATION).clamp(0.0, 1.0);
+        }
+    }
+
+    for entity in to_despawn {
+        if let Err(err) = world.despawn(entity) {
+            #[cfg(debug_assertions)]
+            println!("WARNING: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gib_pieces_are_deterministic() {
+        let meta = PlayerGibsMetadata::default();
+        let frame = Rect::new(64.0, 0.0, 64.0, 80.0);
+
+        let seed = get_gibs_seed(1, vec2(120.5, 300.0));
+        let pieces = get_gib_pieces(&meta, frame, seed);
+
+        assert_eq!(pieces.len(), meta.count as usize);
+        assert_eq!(pieces, get_gib_pieces(&meta, frame, seed));
+
+        for piece in &pieces {
+            assert!(frame.contains(piece.source_rect.point()));
+            assert!(piece.velocity.y < 0.0);
+            assert!(piece.velocity.length() <= meta.speed + 0.001);
+        }
+
+        let other_seed = get_gibs_seed(2, vec2(120.5, 300.0));
+        assert_ne!(pieces, get_gib_pieces(&meta, frame, other_seed));
+    }
+}
@@ -0,0 +1,138 @@
+use macroquad::experimental::collections::storage;
+use macroquad::prelude::*;
+
+use hecs::World;
+
+use core::Transform;
+
+use crate::{Config, GameCamera, PhysicsBody};
+
+use super::Player;
+
+/// The colors of the offscreen indicators, by team, or by player index for players that are not on
+/// a team
+const INDICATOR_COLORS: &[Color] = &[
+    Color::new(0.96, 0.36, 0.26, 1.0),
+    Color::new(0.26, 0.62, 0.96, 1.0),
+    Color::new(0.40, 0.86, 0.36, 1.0),
+    Color::new(0.98, 0.82, 0.24, 1.0),
+];
+
+/// The distance between an indicator and the edge of the screen, in screen space
+const INDICATOR_MARGIN: f32 = 24.0;
+/// The length of an indicator arrow, in screen space
+const INDICATOR_SIZE: f32 = 20.0;
+/// How fast indicators fade in and out, in opacity per second
+const INDICATOR_FADE_SPEED: f32 = 4.0;
+
+/// This keeps track of the fade of the indicator that is drawn at the edge of the screen, when
+/// a player is offscreen
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OffscreenIndicator {
+    pub opacity: f32,
+}
+
+/// Returns the color of the indicator of `player`, which is the color of its team, so that
+/// teammates share a color, or the color of its index, if it is not on a team
+fn get_indicator_color(player: &Player) -> Color {
+    let i = player.team.unwrap_or(player.index) as usize;
+
+    INDICATOR_COLORS[i % INDICATOR_COLORS.len()]
+}
+
+pub fn update_offscreen_indicators(world: &mut World) {
+    let view_rect = storage::get::<GameCamera>().get_view_rect();
+
+    let dt = get_frame_time();
+
+    for (_, (transform, body, indicator)) in
+        world.query_mut::<(&Transform, &PhysicsBody, &mut OffscreenIndicator)>()
+    {
+        let rect = body.as_rect(transform.position);
+
+        if view_rect.overlaps(&rect) {
+            indicator.opacity = (indicator.opacity - INDICATOR_FADE_SPEED * dt).max(0.0);
+        } else {
+            indicator.opacity = (indicator.opacity + INDICATOR_FADE_SPEED * dt).min(1.0);
+        }
+    }
+}
+
+pub fn draw_offscreen_indicators(world: &mut World) {
+    {
+        let config = storage::get::<Config>();
+        if !config.match_settings.has_offscreen_indicators {
+            return;
+        }
+    }
+
+    let view_rect = storage::get::<GameCamera>().get_view_rect();
+
+    // The indicators are drawn in world space, so they are scaled to keep a constant size on screen
+    let scale = view_rect.w / screen_width();
+    let margin = INDICATOR_MARGIN * scale;
+    let size = INDICATOR_SIZE * scale;
+
+    let view_center = view_rect.point() + view_rect.size() / 2.0;
+
+    // The area that the tips of the indicators are clamped to
+    let min = (view_rect.point() + vec2(margin, margin)).min(view_center);
+    let max = (view_rect.point() + view_rect.size() - vec2(margin, margin)).max(view_center);
+
+    for (_, (transform, body, player, indicator)) in world
+        .query::<(&Transform, &PhysicsBody, &Player, &OffscreenIndicator)>()
+        .iter()
+    {
+        if indicator.opacity <= 0.0 {
+            continue;
+        }
+
+        let rect = body.as_rect(transform.position);
+        let target = rect.point() + rect.size() / 2.0;
+
+        let direction = (target - view_center).normalize_or_zero();
+        if direction == Vec2::ZERO {
+            continue;
+        }
+
+        let tip = target.max(min).min(max);
+
+        let base = tip - direction * size;
+        let side = direction.perp() * size * 0.5;
+
+        let mut color = get_indicator_color(player);
+        color.a = indicator.opacity;
+
+        draw_triangle(tip, base + side, base - side, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indicator_color() {
+        let player = |index: u8, team: Option<u8>| Player {
+            team,
+            ..Player::new(index, Vec2::ZERO)
+        };
+
+        // Teammates share the color of their team
+        assert_eq!(
+            get_indicator_color(&player(0, Some(1))),
+            get_indicator_color(&player(2, Some(1)))
+        );
+        assert_ne!(
+            get_indicator_color(&player(0, Some(0))),
+            get_indicator_color(&player(1, Some(1)))
+        );
+
+        // Players without a team fall back to the color of their index
+        assert_eq!(get_indicator_color(&player(1, None)), INDICATOR_COLORS[1]);
+        assert_ne!(
+            get_indicator_color(&player(0, None)),
+            get_indicator_color(&player(1, None))
+        );
+    }
+}
@@ -1,18 +1,20 @@
+use macroquad::experimental::collections::storage;
 use macroquad::prelude::*;
 
 use hecs::{Entity, With, Without, World};
 
-use core::Transform;
+use core::{PickupMode, Transform};
 
+use crate::game::{dispatch_game_event, GameEvent};
 use crate::items::{
-    fire_weapon, ItemDepleteBehavior, ItemDropBehavior, RespawnInfo, RespawningItem,
-    RespawningItemKind, Weapon, EFFECT_ANIMATED_SPRITE_ID, GROUND_ANIMATION_ID, ITEMS_DRAW_ORDER,
-    SPRITE_ANIMATED_SPRITE_ID,
+    break_weapon, fire_weapon, ItemDepleteBehavior, ItemDropBehavior, RespawnInfo, RespawningItem,
+    RespawningItemKind, ThrownItem, Weapon, EFFECT_ANIMATED_SPRITE_ID, GROUND_ANIMATION_ID,
+    ITEMS_DRAW_ORDER, SPRITE_ANIMATED_SPRITE_ID,
 };
-use crate::particles::ParticleEmitter;
+use crate::particles::{update_one_particle_emitter, ParticleEmitter};
 use crate::player::{Player, PlayerController, PlayerState, IDLE_ANIMATION_ID, PICKUP_GRACE_TIME};
 use crate::utils::timer::Timer;
-use crate::{Drawable, Item, Owner, PassiveEffectInstance, PhysicsBody};
+use crate::{Config, Drawable, Item, Owner, PassiveEffectInstance, PhysicsBody};
 
 const THROW_FORCE: f32 = 5.0;
 
@@ -58,7 +60,37 @@ impl PlayerInventory {
     }
 }
 
+/// Returns `true` if an item with the specified collider is close enough to a player to be
+/// picked up
+pub fn is_in_pickup_range(player_rect: &Rect, item_rect: &Rect, pickup_radius: f32) -> bool {
+    let range = Rect::new(
+        player_rect.x - pickup_radius,
+        player_rect.y - pickup_radius,
+        player_rect.w + pickup_radius * 2.0,
+        player_rect.h + pickup_radius * 2.0,
+    );
+
+    range.overlaps(item_rect)
+}
+
+/// Returns `true` if a player without a weapon should try to pick one up
+pub fn should_grab_weapon(pickup_mode: PickupMode, should_pickup: bool) -> bool {
+    should_pickup || pickup_mode == PickupMode::Auto
+}
+
+/// This should only be run on the host, as it is authoritative on item pickups
 pub fn update_player_inventory(world: &mut World) {
+    let (pickup_radius, pickup_mode, has_thrown_item_impacts) = {
+        let config = storage::get::<Config>();
+        let settings = &config.match_settings;
+
+        (
+            settings.pickup_radius,
+            settings.pickup_mode,
+            settings.thrown_item_impact_speed.is_some(),
+        )
+    };
+
     let mut item_colliders = world
         .query::<With<Item, Without<Owner, (&Transform, &PhysicsBody)>>>()
         .iter()
@@ -74,6 +106,7 @@ pub fn update_player_inventory(world: &mut World) {
     let mut picked_up = Vec::new();
 
     let mut to_drop = Vec::new();
+    let mut to_throw = Vec::new();
     let mut to_fire = Vec::new();
     struct ToDestroy {
         entity: Entity,
@@ -106,7 +139,7 @@ pub fn update_player_inventory(world: &mut World) {
             while i < item_colliders.len() {
                 let &(item_entity, rect) = item_colliders.get(i).unwrap();
 
-                if player_rect.overlaps(&rect) {
+                if is_in_pickup_range(&player_rect, &rect, pickup_radius) {
                     let item = world.get::<Item>(item_entity).unwrap();
 
                     if item.is_hat {
@@ -151,26 +184,36 @@ pub fn update_player_inventory(world: &mut World) {
                 }
 
                 weapon_entity_to_pick_up = Some(we);
-            } else if controller.should_pickup {
-                if let Some(weapon_entity) = inventory.weapon.take() {
-                    to_drop.push(weapon_entity);
+            } else if controller.should_pickup && inventory.weapon.is_some() {
+                let weapon_entity = inventory.weapon.take().unwrap();
 
-                    let velocity = if player.is_facing_left {
-                        vec2(-THROW_FORCE, 0.0)
-                    } else {
-                        vec2(THROW_FORCE, 0.0)
-                    };
-
-                    let mut body = world.get_mut::<PhysicsBody>(weapon_entity).unwrap();
-
-                    body.velocity = velocity;
-                } else if player.pickup_grace_timer >= PICKUP_GRACE_TIME {
-                    for (i, &(we, rect)) in weapon_colliders.iter().enumerate() {
-                        if player_rect.overlaps(&rect) {
-                            weapon_colliders.remove(i);
-                            weapon_entity_to_pick_up = Some(we);
-                            break;
-                        }
+                to_drop.push(weapon_entity);
+
+                let velocity = if player.is_facing_left {
+                    vec2(-THROW_FORCE, 0.0)
+                } else {
+                    vec2(THROW_FORCE, 0.0)
+                };
+
+                let mut body = world.get_mut::<PhysicsBody>(weapon_entity).unwrap();
+
+                body.velocity = velocity;
+
+                if has_thrown_item_impacts {
+                    to_throw.push((weapon_entity, entity));
+                }
+
+                // This prevents the weapon from being picked up again immediately, in auto mode
+                player.pickup_grace_timer = 0.0;
+            } else if inventory.weapon.is_none()
+                && should_grab_weapon(pickup_mode, controller.should_pickup)
+                && player.pickup_grace_timer >= PICKUP_GRACE_TIME
+            {
+                for (i, &(we, rect)) in weapon_colliders.iter().enumerate() {
+                    if is_in_pickup_range(&player_rect, &rect, pickup_radius) {
+                        weapon_colliders.remove(i);
+                        weapon_entity_to_pick_up = Some(we);
+                        break;
                     }
                 }
             }
@@ -250,7 +293,16 @@ pub fn update_player_inventory(world: &mut World) {
                     .map(|uses| weapon.use_cnt >= uses)
                     .unwrap_or_default();
 
-                if is_depleted {
+                if is_depleted && weapon.break_on_empty {
+                    let position = weapon_transform.position + frame_size / 2.0;
+                    break_weapon(&weapon, position);
+
+                    to_destroy.push(ToDestroy {
+                        entity: weapon_entity,
+                        respawn_info: weapon.respawn_info,
+                    });
+                    inventory.weapon = None;
+                } else if is_depleted {
                     match weapon.deplete_behavior {
                         ItemDepleteBehavior::Destroy => {
                             to_destroy.push(ToDestroy {
@@ -265,6 +317,42 @@ pub fn update_player_inventory(world: &mut World) {
                         }
                         _ => {}
                     }
+                } else if let Some(charge) = weapon.charge.clone() {
+                    if controller.should_attack && weapon.cooldown_timer >= weapon.cooldown {
+                        if !weapon.is_charging {
+                            weapon.is_charging = true;
+                            weapon.charge_timer = 0.0;
+
+                            for emitter in weapon.charge_emitters.iter_mut() {
+                                emitter.activate();
+                            }
+                        }
+
+                        weapon.charge_timer += get_frame_time();
+
+                        let progress = charge.progress(weapon.charge_timer);
+                        let sprite_set = drawable.get_animated_sprite_set_mut().unwrap();
+                        if let Some(sprite) = sprite_set.map.get_mut(SPRITE_ANIMATED_SPRITE_ID) {
+                            sprite.tint = Color::new(1.0, 1.0 - progress, 1.0 - progress, 1.0);
+                        }
+                    } else if weapon.is_charging {
+                        weapon.is_charging = false;
+
+                        for emitter in weapon.charge_emitters.iter_mut() {
+                            emitter.is_active = false;
+                        }
+
+                        let sprite_set = drawable.get_animated_sprite_set_mut().unwrap();
+                        if let Some(sprite) = sprite_set.map.get_mut(SPRITE_ANIMATED_SPRITE_ID) {
+                            sprite.tint = WHITE;
+                        }
+
+                        to_fire.push((weapon_entity, entity));
+                    }
+
+                    for emitter in weapon.charge_emitters.iter_mut() {
+                        update_one_particle_emitter(weapon_transform.position, 0.0, emitter);
+                    }
                 } else if controller.should_attack {
                     to_fire.push((weapon_entity, entity));
                 }
@@ -363,6 +451,14 @@ pub fn update_player_inventory(world: &mut World) {
     for (player_entity, item_entity) in picked_up {
         world.insert_one(item_entity, Owner(player_entity)).unwrap();
 
+        dispatch_game_event(GameEvent::ItemPickedUp {
+            player: player_entity,
+            item: item_entity,
+        });
+
+        // Items can be caught while they are still flying
+        let _ = world.remove_one::<ThrownItem>(item_entity);
+
         let player_draw_order = world
             .get::<Drawable>(player_entity)
             .map(|drawable| drawable.draw_order)
@@ -486,6 +582,13 @@ pub fn update_player_inventory(world: &mut World) {
         }
     }
 
+    for (entity, thrower) in to_throw {
+        // The item might have been destroyed when it was dropped
+        if world.get::<Weapon>(entity).is_ok() {
+            world.insert_one(entity, ThrownItem { thrower }).unwrap();
+        }
+    }
+
     for (entity, owner) in to_fire.drain(0..) {
         if let Err(err) = fire_weapon(world, entity, owner) {
             #[cfg(debug_assertions)]
@@ -549,8 +652,8 @@ pub fn draw_weapons_hud(world: &mut World) {
     for (_, (transform, inventory)) in world.query::<(&Transform, &PlayerInventory)>().iter() {
         if let Some(Ok(weapon)) = inventory.weapon.map(|entity| world.get::<Weapon>(entity)) {
             if let Some(uses) = weapon.uses {
-                let is_destroyed_on_depletion =
-                    weapon.deplete_behavior == ItemDepleteBehavior::Destroy;
+                let is_destroyed_on_depletion = weapon.break_on_empty
+                    || weapon.deplete_behavior == ItemDepleteBehavior::Destroy;
 
                 if !is_destroyed_on_depletion || uses > 1 {
                     let remaining = uses - weapon.use_cnt;
@@ -623,3 +726,21 @@ pub fn flip_offset<S: Into<Option<Vec2>>>(
 
     corrected
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_pickup_within_radius() {
+        let player_rect = Rect::new(0.0, 0.0, 32.0, 60.0);
+        let item_rect = Rect::new(48.0, 30.0, 16.0, 16.0);
+
+        assert!(!is_in_pickup_range(&player_rect, &item_rect, 0.0));
+        assert!(is_in_pickup_range(&player_rect, &item_rect, 24.0));
+
+        assert!(should_grab_weapon(PickupMode::Auto, false));
+        assert!(!should_grab_weapon(PickupMode::Manual, false));
+        assert!(should_grab_weapon(PickupMode::Manual, true));
+    }
+}
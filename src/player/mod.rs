@@ -5,6 +5,7 @@ use hecs::{Entity, World};
 
 use core::Transform;
 
+use crate::effects::passive::is_frozen;
 use crate::{
     AnimatedSprite, AnimatedSpriteMetadata, AnimatedSpriteParams, CollisionWorld, Drawable,
     GameCamera, PassiveEffectInstance, PhysicsBody, Resources,
@@ -14,6 +15,8 @@ mod animation;
 mod character;
 mod controller;
 mod events;
+mod gibs;
+mod indicator;
 mod inventory;
 mod state;
 
@@ -21,6 +24,8 @@ pub use animation::*;
 pub use character::*;
 pub use controller::*;
 pub use events::*;
+pub use gibs::*;
+pub use indicator::*;
 pub use inventory::*;
 pub use state::*;
 
@@ -36,6 +41,7 @@ pub const JUMP_ANIMATION_ID: &str = "jump";
 pub const FALL_ANIMATION_ID: &str = "fall";
 pub const CROUCH_ANIMATION_ID: &str = "crouch";
 pub const SLIDE_ANIMATION_ID: &str = "slide";
+pub const HURT_ANIMATION_ID: &str = "hurt";
 pub const DEATH_BACK_ANIMATION_ID: &str = "death_back";
 pub const DEATH_FORWARD_ANIMATION_ID: &str = "death_forward";
 
@@ -47,6 +53,11 @@ pub const JUMP_SOUND_ID: &str = "jump";
 pub const LAND_SOUND_ID: &str = "land";
 
 pub const RESPAWN_DELAY: f32 = 2.5;
+
+/// The weight of dead players, when the camera is fitted to the players, so that it favors the
+/// players that are still in play, while dead players wait to respawn
+pub const DEAD_PLAYER_CAMERA_WEIGHT: f32 = 0.25;
+
 pub const PICKUP_GRACE_TIME: f32 = 0.25;
 
 #[derive(Debug, Clone)]
@@ -54,10 +65,14 @@ pub struct PlayerParams {
     pub index: u8,
     pub controller: PlayerControllerKind,
     pub character: PlayerCharacterMetadata,
+    /// Players on the same team are teammates. If this is `None`, the player is not on a team.
+    pub team: Option<u8>,
 }
 
 pub struct Player {
     pub index: u8,
+    /// Players on the same team are teammates. If this is `None`, the player is not on a team.
+    pub team: Option<u8>,
     pub state: PlayerState,
     pub damage_from_left: bool,
     pub is_facing_left: bool,
@@ -71,6 +86,9 @@ pub struct Player {
     pub camera_box: Rect,
     pub passive_effects: Vec<PassiveEffectInstance>,
     pub was_on_ground: bool,
+    /// This is `true` if the player has been broken into gibs, or has disappeared in a puff of
+    /// smoke, if gore is reduced. The player will be hidden until it respawns.
+    pub is_gibbed: bool,
 }
 
 impl Player {
@@ -79,6 +97,7 @@ impl Player {
 
         Player {
             index,
+            team: None,
             state: PlayerState::None,
             damage_from_left: false,
             is_facing_left: false,
@@ -92,8 +111,20 @@ impl Player {
             respawn_timer: 0.0,
             camera_box,
             passive_effects: Vec::new(),
+            is_gibbed: false,
         }
     }
+
+    /// Returns `true` if the player is frozen by a passive effect. A frozen player ignores input.
+    pub fn is_frozen(&self) -> bool {
+        self.state != PlayerState::Dead && is_frozen(&self.passive_effects)
+    }
+}
+
+/// Returns `true` if `team` and `other_team` are the same team. Players that are not on a team have
+/// no teammates.
+pub fn is_same_team(team: Option<u8>, other_team: Option<u8>) -> bool {
+    team.is_some() && team == other_team
 }
 
 pub fn update_player_camera_box(world: &mut World) {
@@ -116,8 +147,15 @@ pub fn update_player_camera_box(world: &mut World) {
             player.camera_box.y = rect.y + rect.h - player.camera_box.h;
         }
 
+        let weight = if player.state == PlayerState::Dead {
+            DEAD_PLAYER_CAMERA_WEIGHT
+        } else {
+            1.0
+        };
+
         let mut camera = storage::get_mut::<GameCamera>();
-        camera.add_player_rect(player.camera_box);
+        camera.set_player_weight(player.index, weight);
+        camera.add_player_rect(player.index, player.camera_box);
     }
 }
 
@@ -131,6 +169,7 @@ pub struct PlayerAttributes {
     pub slide_speed_factor: f32,
     pub incapacitation_duration: f32,
     pub float_gravity_factor: f32,
+    pub gibs: PlayerGibsMetadata,
 }
 
 impl From<&PlayerCharacterMetadata> for PlayerAttributes {
@@ -144,6 +183,7 @@ impl From<&PlayerCharacterMetadata> for PlayerAttributes {
             slide_speed_factor: params.slide_speed_factor,
             incapacitation_duration: params.incapacitation_duration,
             float_gravity_factor: params.float_gravity_factor,
+            gibs: params.gibs.clone(),
         }
     }
 }
@@ -160,6 +200,7 @@ pub fn spawn_player(
     position: Vec2,
     controller: PlayerControllerKind,
     character: PlayerCharacterMetadata,
+    team: Option<u8>,
 ) -> Entity {
     let weapon_mount = character.weapon_mount;
     let item_mount = character.item_mount;
@@ -213,14 +254,21 @@ pub fn spawn_player(
         ..Default::default()
     };
 
+    let player = Player {
+        team,
+        ..Player::new(index, position)
+    };
+
     world.spawn((
-        Player::new(index, position),
+        player,
         Transform::from(position),
         PlayerController::from(controller),
         PlayerAttributes::from(&character),
+        PlayerAnimator::new(character.animation_transitions.clone()),
         PlayerInventory::new(weapon_mount, item_mount, hat_mount),
         PlayerEventQueue::new(),
         Drawable::new_animated_sprite_set(draw_order, &sprites),
         PhysicsBody::new(actor, None, body_params),
+        OffscreenIndicator::default(),
     ))
 }
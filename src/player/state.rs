@@ -3,18 +3,20 @@ use macroquad::prelude::*;
 
 use hecs::{Entity, World};
 
-use core::Transform;
+use core::{MatchConfig, Transform};
 
-use crate::game::play_sound_effect;
+use crate::game::{dispatch_game_event, get_tick_scale, play_sound_effect, GameEvent};
 use crate::player::{
-    Player, PlayerAttributes, PlayerController, PlayerEventQueue, JUMP_SOUND_ID, LAND_SOUND_ID,
-    RESPAWN_DELAY,
+    is_same_team, Player, PlayerAttributes, PlayerController, PlayerEventKind, PlayerEventQueue,
+    JUMP_SOUND_ID, LAND_SOUND_ID, RESPAWN_DELAY,
 };
-use crate::{CollisionWorld, Drawable, DrawableKind, Item, Map, PhysicsBody, PlayerEvent};
+use crate::{CollisionWorld, Config, Drawable, DrawableKind, Item, Map, PhysicsBody, PlayerEvent};
 
 const SLIDE_STOP_THRESHOLD: f32 = 2.0;
 const JUMP_FRAME_COUNT: u16 = 8;
 const PLATFORM_JUMP_FORCE_MULTIPLIER: f32 = 0.2;
+/// This is the extra gravity applied to a frozen player, as a factor of the gravity of its body
+const FROZEN_EXTRA_GRAVITY_FACTOR: f32 = 0.5;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum PlayerState {
@@ -37,7 +39,7 @@ pub fn update_player_states(world: &mut World) {
     let query = world.query_mut::<(
         &mut Transform,
         &mut Player,
-        &PlayerController,
+        &mut PlayerController,
         &PlayerAttributes,
         &mut PhysicsBody,
     )>();
@@ -54,6 +56,8 @@ pub fn update_player_states(world: &mut World) {
 
         player.pickup_grace_timer += dt;
 
+        let is_frozen = apply_player_freeze(player, controller);
+
         if player.state == PlayerState::Crouching && !controller.should_crouch {
             player.state = PlayerState::None;
         }
@@ -66,6 +70,7 @@ pub fn update_player_states(world: &mut World) {
             if player.respawn_timer >= RESPAWN_DELAY {
                 player.state = PlayerState::None;
                 player.respawn_timer = 0.0;
+                player.is_gibbed = false;
 
                 let map = storage::get::<Map>();
                 transform.position = map.get_random_spawn_point();
@@ -86,6 +91,7 @@ pub fn update_player_states(world: &mut World) {
 
         // Integration
         if player.is_attacking
+            || is_frozen
             || matches!(
                 player.state,
                 PlayerState::Dead | PlayerState::Incapacitated | PlayerState::Sliding
@@ -189,6 +195,36 @@ pub fn update_player_states(world: &mut World) {
     }
 }
 
+/// Returns `true` if `player` is frozen by a passive effect. Frozen players ignore all input, so the
+/// controller is cleared, in stead of skipping input handling, so that the inventory will not act
+/// on the input either. A freeze also replaces any stun, as it is distinct from it, lasting longer
+/// and being broken by hits, in stead.
+pub fn apply_player_freeze(player: &mut Player, controller: &mut PlayerController) -> bool {
+    let is_frozen = player.is_frozen();
+
+    if is_frozen {
+        controller.clear();
+
+        if player.state == PlayerState::Incapacitated {
+            player.state = PlayerState::None;
+            player.incapacitation_timer = 0.0;
+        }
+    }
+
+    is_frozen
+}
+
+/// Frozen players fall faster. As gravity, this is applied per tick, before the bodies are moved.
+pub fn fixed_update_frozen_players(world: &mut World) {
+    let tick_scale = get_tick_scale();
+
+    for (_, (player, body)) in world.query_mut::<(&Player, &mut PhysicsBody)>() {
+        if player.is_frozen() && !body.is_on_ground && body.has_mass && !body.is_deactivated {
+            body.velocity.y += body.gravity * FROZEN_EXTRA_GRAVITY_FACTOR * tick_scale;
+        }
+    }
+}
+
 pub fn update_player_passive_effects(world: &mut World) {
     let mut function_calls = Vec::new();
 
@@ -215,8 +251,10 @@ pub fn update_player_passive_effects(world: &mut World) {
                 let sprite_entity = world.reserve_entity();
 
                 let drawable = Drawable {
+                    z_index: player_drawable.z_index,
                     draw_order: player_drawable.draw_order + 1,
                     kind: DrawableKind::AnimatedSprite(sprite),
+                    offset: Vec2::ZERO,
                 };
 
                 sprites_to_spawn.push((sprite_entity, drawable, player_transform.position));
@@ -242,10 +280,25 @@ pub fn update_player_passive_effects(world: &mut World) {
         for event in events.queue.iter() {
             let kind = event.into();
 
+            if kind == PlayerEventKind::ReceiveDamage {
+                for effect in &mut player.passive_effects {
+                    effect.on_hit();
+                }
+            }
+
             for effect in &mut player.passive_effects {
                 if effect.activated_on.contains(&kind) {
                     effect.use_cnt += 1;
 
+                    // Effects that are activated on update would flood the event bus
+                    if kind != PlayerEventKind::Update {
+                        dispatch_game_event(GameEvent::EffectTriggered {
+                            player: entity,
+                            item: effect.item,
+                            effect: effect.name.clone(),
+                        });
+                    }
+
                     if let Some(item_entity) = effect.item {
                         let mut item = world.get_mut::<Item>(item_entity).unwrap();
 
@@ -275,7 +328,54 @@ pub fn update_player_passive_effects(world: &mut World) {
     }
 }
 
+/// Returns `true` if the player `damage_from_entity` is allowed to damage the player
+/// `damage_to_entity`, according to the self-damage and friendly-fire settings
+pub fn can_damage_player(
+    world: &World,
+    settings: &MatchConfig,
+    damage_from_entity: Entity,
+    damage_to_entity: Entity,
+) -> bool {
+    if damage_from_entity == damage_to_entity {
+        return settings.is_self_damage_enabled;
+    }
+
+    if !settings.is_friendly_fire_enabled {
+        let damage_from = world.get::<Player>(damage_from_entity);
+        let damage_to = world.get::<Player>(damage_to_entity);
+
+        if let (Ok(damage_from), Ok(damage_to)) = (damage_from, damage_to) {
+            if is_same_team(damage_from.team, damage_to.team) {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
 pub fn on_player_damage(world: &mut World, damage_from_entity: Entity, damage_to_entity: Entity) {
+    let config = storage::get::<Config>();
+    damage_player(
+        world,
+        &config.match_settings,
+        damage_from_entity,
+        damage_to_entity,
+    );
+}
+
+/// Queue the events for damage dealt by one player to another, unless the damage is disallowed by
+/// the match settings, as checked by `can_damage_player`
+pub fn damage_player(
+    world: &mut World,
+    settings: &MatchConfig,
+    damage_from_entity: Entity,
+    damage_to_entity: Entity,
+) {
+    if !can_damage_player(world, settings, damage_from_entity, damage_to_entity) {
+        return;
+    }
+
     let mut is_from_left = false;
 
     if let Ok(owner_transform) = world.get::<Transform>(damage_from_entity) {
@@ -303,3 +403,190 @@ pub fn on_player_damage(world: &mut World, damage_from_entity: Entity, damage_to
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::input::{GameInputScheme, PlayerInput};
+
+    use crate::effects::passive::{PassiveEffectInstance, PassiveEffectMetadata};
+    use crate::player::PlayerControllerKind;
+
+    fn freeze_effect(duration: f32) -> PassiveEffectInstance {
+        PassiveEffectInstance::new(
+            None,
+            PassiveEffectMetadata {
+                name: "frozen".to_string(),
+                function_id: None,
+                activated_on: vec![PlayerEventKind::ReceiveDamage],
+                particle_effect_id: None,
+                event_particle_effect_id: None,
+                blocks_damage: false,
+                freezes_player: true,
+                breaks_on_hit: true,
+                uses: None,
+                duration: Some(duration),
+                damage_over_time: None,
+                sprite: None,
+            },
+        )
+    }
+
+    fn get_controller(player: &mut Player) -> PlayerController {
+        let mut controller = PlayerController::from(PlayerControllerKind::LocalInput(
+            GameInputScheme::KeyboardRight,
+        ));
+
+        controller.apply_input(PlayerInput {
+            right: true,
+            jump: true,
+            fire: true,
+            ..Default::default()
+        });
+
+        apply_player_freeze(player, &mut controller);
+
+        controller
+    }
+
+    #[test]
+    fn test_freeze_ignores_input_until_expired() {
+        let mut player = Player::new(0, Vec2::ZERO);
+        player.passive_effects.push(freeze_effect(2.0));
+
+        // The freeze replaces the stun
+        player.state = PlayerState::Incapacitated;
+        player.incapacitation_timer = 0.5;
+
+        let controller = get_controller(&mut player);
+        assert_eq!(controller.move_direction, Vec2::ZERO);
+        assert!(!controller.should_jump);
+        assert!(!controller.should_attack);
+        assert_eq!(player.state, PlayerState::None);
+        assert_eq!(player.incapacitation_timer, 0.0);
+
+        player.passive_effects[0].update(1.0);
+        assert!(!get_controller(&mut player).should_jump);
+
+        player.passive_effects[0].update(1.0);
+
+        let controller = get_controller(&mut player);
+        assert_eq!(controller.move_direction, vec2(1.0, 0.0));
+        assert!(controller.should_jump);
+        assert!(controller.should_attack);
+    }
+
+    #[test]
+    fn test_freeze_breaks_on_hit() {
+        let mut player = Player::new(0, Vec2::ZERO);
+        player.passive_effects.push(freeze_effect(2.0));
+
+        assert!(!get_controller(&mut player).should_jump);
+
+        player.passive_effects[0].on_hit();
+        assert!(get_controller(&mut player).should_jump);
+
+        // Dead players are not frozen, so they can not keep a stale freeze through a respawn
+        player.passive_effects.push(freeze_effect(2.0));
+        player.state = PlayerState::Dead;
+        assert!(!player.is_frozen());
+    }
+
+    #[test]
+    fn test_friendly_fire() {
+        let mut world = World::default();
+
+        let mut spawn_player = |index: u8, team: Option<u8>| {
+            world.spawn((Player {
+                team,
+                ..Player::new(index, Vec2::ZERO)
+            },))
+        };
+
+        let owner = spawn_player(0, Some(0));
+        let teammate = spawn_player(1, Some(0));
+        let opponent = spawn_player(2, Some(1));
+        let loner = spawn_player(3, None);
+        let other_loner = spawn_player(4, None);
+
+        let settings = MatchConfig {
+            is_friendly_fire_enabled: false,
+            ..Default::default()
+        };
+
+        // A teammate in the blast takes no damage, while everyone else does
+        assert!(!can_damage_player(&world, &settings, owner, teammate));
+        assert!(can_damage_player(&world, &settings, owner, opponent));
+        assert!(can_damage_player(&world, &settings, owner, loner));
+        assert!(can_damage_player(&world, &settings, loner, other_loner));
+        assert!(can_damage_player(&world, &settings, owner, owner));
+
+        let settings = MatchConfig {
+            is_self_damage_enabled: false,
+            ..Default::default()
+        };
+
+        assert!(can_damage_player(&world, &settings, owner, teammate));
+        assert!(!can_damage_player(&world, &settings, owner, owner));
+    }
+
+    #[test]
+    fn test_friendly_fire_damage() {
+        let mut world = World::default();
+
+        let mut spawn_player = |index: u8, team: Option<u8>, x: f32| {
+            world.spawn((
+                Player {
+                    team,
+                    ..Player::new(index, Vec2::ZERO)
+                },
+                Transform::from(vec2(x, 0.0)),
+                PlayerEventQueue::new(),
+            ))
+        };
+
+        let owner = spawn_player(0, Some(0), 0.0);
+        let teammate = spawn_player(1, Some(0), 32.0);
+        let opponent = spawn_player(2, Some(1), -32.0);
+
+        let settings = MatchConfig {
+            is_friendly_fire_enabled: false,
+            ..Default::default()
+        };
+
+        // A blast that hits everyone around the owner
+        for target in [owner, teammate, opponent] {
+            damage_player(&mut world, &settings, owner, target);
+        }
+
+        let received_damage = |world: &World, entity: Entity| {
+            let events = world.get::<PlayerEventQueue>(entity).unwrap();
+            events
+                .queue
+                .iter()
+                .filter_map(|event| match event {
+                    PlayerEvent::ReceiveDamage {
+                        is_from_left,
+                        damage_from,
+                    } => Some((*is_from_left, *damage_from)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert!(received_damage(&world, teammate).is_empty());
+        assert_eq!(received_damage(&world, opponent), [(false, Some(owner))]);
+        assert_eq!(received_damage(&world, owner), [(false, Some(owner))]);
+
+        let given_damage_cnt = world
+            .get::<PlayerEventQueue>(owner)
+            .unwrap()
+            .queue
+            .iter()
+            .filter(|event| matches!(event, PlayerEvent::GiveDamage { .. }))
+            .count();
+
+        assert_eq!(given_damage_cnt, 2);
+    }
+}
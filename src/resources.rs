@@ -1,4 +1,8 @@
-use std::{collections::HashMap, fs, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
 
 use macroquad::{
     audio::{load_sound, Sound},
@@ -10,16 +14,17 @@ use ff_particles::EmitterConfig;
 
 use serde::{Deserialize, Serialize};
 
-use core::data::{deserialize_json_bytes, deserialize_json_file};
+use core::data::{deserialize_file, deserialize_json_bytes, deserialize_json_file};
 use core::error::ErrorKind;
 use core::text::ToStringHelper;
 use core::{formaterr, Result};
 
-use crate::gui::GuiResources;
-use crate::map::DecorationMetadata;
+use crate::effects::active::triggered::{load_trigger_groups, TriggeredEffectTriggerParams};
+use crate::gui::{detect_ui_scale, load_gui_theme, GuiResources};
+use crate::map::{DecorationMetadata, MapTileCollision};
 
 use crate::player::PlayerCharacterMetadata;
-use crate::{items::MapItemMetadata, map::Map};
+use crate::{items::MapItemMetadata, map::Map, Config};
 
 const PARTICLE_EFFECTS_DIR: &str = "particle_effects";
 const SOUNDS_FILE: &str = "sounds";
@@ -28,6 +33,8 @@ const TEXTURES_FILE: &str = "textures";
 const IMAGES_FILE: &str = "images";
 const MAPS_FILE: &str = "maps";
 const DECORATION_FILE: &str = "decoration";
+const TRIGGER_GROUPS_FILE: &str = "trigger_groups";
+const TILESETS_FILE: &str = "tilesets";
 const ITEMS_FILE: &str = "items";
 const PLAYER_CHARACTERS_FILE: &str = "player_characters";
 
@@ -66,6 +73,15 @@ pub enum TextureKind {
     Spritesheet,
 }
 
+/// Properties of a tileset texture, that are shared by all the maps that use it. These are keyed
+/// by texture id, in the tilesets file, and edits made in the editor are written back to it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TilesetMetadata {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tile_collisions: BTreeMap<u32, MapTileCollision>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct TextureMetadata {
@@ -164,7 +180,7 @@ async fn load_resources_from<P: AsRef<Path>>(path: P, resources: &mut Resources)
             for meta in metadata {
                 let file_path = path.join(&meta.path);
 
-                let cfg: EmitterConfig = deserialize_json_file(&file_path).await?;
+                let cfg: EmitterConfig = deserialize_file(&file_path).await?;
 
                 resources.particle_effects.insert(meta.id, cfg);
             }
@@ -272,6 +288,18 @@ async fn load_resources_from<P: AsRef<Path>>(path: P, resources: &mut Resources)
         }
     }
 
+    {
+        let tilesets_file_path = path
+            .join(TILESETS_FILE)
+            .with_extension(RESOURCE_FILES_EXTENSION);
+
+        if let Ok(bytes) = load_file(&tilesets_file_path.to_string_helper()).await {
+            let metadata: HashMap<String, TilesetMetadata> = deserialize_json_bytes(&bytes)?;
+
+            resources.tilesets.extend(metadata);
+        }
+    }
+
     {
         let maps_file_path = path
             .join(MAPS_FILE)
@@ -310,13 +338,28 @@ async fn load_resources_from<P: AsRef<Path>>(path: P, resources: &mut Resources)
             for decoration_path in decoration_paths {
                 let path = path.join(&decoration_path);
 
-                let params: DecorationMetadata = deserialize_json_file(&path).await?;
+                let params: DecorationMetadata = deserialize_file(&path).await?;
 
                 resources.decoration.insert(params.id.clone(), params);
             }
         }
     }
 
+    {
+        let trigger_groups_file_path = path
+            .join(TRIGGER_GROUPS_FILE)
+            .with_extension(RESOURCE_FILES_EXTENSION);
+
+        // This must be loaded before the items, as the groups are expanded when the metadata of
+        // triggered effects is deserialized
+        if let Ok(bytes) = load_file(&trigger_groups_file_path.to_string_helper()).await {
+            let groups: HashMap<String, Vec<TriggeredEffectTriggerParams>> =
+                deserialize_json_bytes(&bytes)?;
+
+            load_trigger_groups(groups)?;
+        }
+    }
+
     {
         let items_file_path = path
             .join(ITEMS_FILE)
@@ -328,7 +371,7 @@ async fn load_resources_from<P: AsRef<Path>>(path: P, resources: &mut Resources)
             for item_path in item_paths {
                 let path = path.join(&item_path);
 
-                let params: MapItemMetadata = deserialize_json_file(&path).await?;
+                let params: MapItemMetadata = deserialize_file(&path).await?;
 
                 resources.items.insert(params.id.clone(), params);
             }
@@ -363,6 +406,7 @@ pub struct Resources {
     pub music: HashMap<String, Sound>,
     pub textures: HashMap<String, TextureResource>,
     pub images: HashMap<String, ImageResource>,
+    pub tilesets: HashMap<String, TilesetMetadata>,
     pub maps: Vec<MapResource>,
     pub decoration: HashMap<String, DecorationMetadata>,
     pub items: HashMap<String, MapItemMetadata>,
@@ -384,6 +428,7 @@ impl Resources {
             textures: HashMap::new(),
             decoration: HashMap::new(),
             images: HashMap::new(),
+            tilesets: HashMap::new(),
             maps: Vec::new(),
             items: HashMap::new(),
             player_characters: HashMap::new(),
@@ -481,6 +526,30 @@ impl Resources {
         Ok(())
     }
 
+    /// Replace the metadata of the specified tileset textures and write all tileset metadata back
+    /// to the tilesets file, so that the changes apply to every map that uses the textures
+    pub fn save_tileset_metadata(
+        &mut self,
+        metadata: Vec<(String, TilesetMetadata)>,
+    ) -> Result<()> {
+        if metadata.is_empty() {
+            return Ok(());
+        }
+
+        self.tilesets.extend(metadata);
+
+        let tilesets_file_path = Path::new(&self.assets_dir)
+            .join(TILESETS_FILE)
+            .with_extension(RESOURCE_FILES_EXTENSION);
+
+        let metadata: BTreeMap<&String, &TilesetMetadata> = self.tilesets.iter().collect();
+
+        let str = serde_json::to_string_pretty(&metadata)?;
+        fs::write(tilesets_file_path, &str)?;
+
+        Ok(())
+    }
+
     fn save_maps_file(&self) -> Result<()> {
         let maps_file_path = Path::new(&self.assets_dir)
             .join(MAPS_FILE)
@@ -540,7 +609,10 @@ pub async fn load_resources(assets_dir: &str, mods_dir: &str) -> Result<()> {
     }
 
     {
-        let gui_resources = GuiResources::new();
+        let theme_id = storage::get::<Config>().ui.theme.clone();
+        let theme = load_gui_theme(assets_dir, theme_id.as_deref()).await?;
+
+        let gui_resources = GuiResources::new(theme, detect_ui_scale());
         storage::store(gui_resources);
     }
 
@@ -579,7 +651,10 @@ pub async fn load_resources(assets_dir: &str, mods_dir: &str) -> Result<()> {
     }
 
     {
-        let gui_resources = GuiResources::new();
+        let theme_id = storage::get::<Config>().ui.theme.clone();
+        let theme = load_gui_theme(assets_dir, theme_id.as_deref()).await?;
+
+        let gui_resources = GuiResources::new(theme, detect_ui_scale());
         storage::store(gui_resources);
     }
 
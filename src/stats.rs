@@ -0,0 +1,496 @@
+//! Persistent player statistics and achievements. The stats are updated from the gameplay event
+//! bus and stored as JSON in the user data directory, so they persist across sessions.
+//! Stats are optional, so if the data directory can not be determined, or the stats file can not
+//! be read or written, a warning is printed and the game continues as normal.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use macroquad::experimental::collections::storage;
+use macroquad::prelude::*;
+
+use hecs::{Entity, World};
+use serde::{Deserialize, Serialize};
+
+use core::data::{deserialize_json_bytes, serialize_json_string};
+use core::{Result, Transform};
+
+use crate::game::events::{drain_game_events, subscribe_game_events, GameEventSubscriber};
+use crate::game::GameEvent;
+use crate::paths::get_data_dir;
+use crate::player::{Player, PlayerController, PlayerState};
+
+const STATS_FILE_NAME: &str = "stats.json";
+
+/// Movement above this distance, in a single frame, is considered a teleport, like a respawn, and
+/// will not be added to the distance traveled
+const MAX_DISTANCE_PER_FRAME: f32 = 64.0;
+
+const TOAST_DURATION: f32 = 4.0;
+const TOAST_FADE_DURATION: f32 = 0.5;
+const TOAST_WIDTH: f32 = 320.0;
+const TOAST_HEIGHT: f32 = 56.0;
+const TOAST_MARGIN: f32 = 16.0;
+const TOAST_BACKGROUND_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.75);
+const TOAST_TITLE_COLOR: Color = Color::new(0.98, 0.82, 0.24, 1.0);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub kills: u32,
+    pub deaths: u32,
+    /// The number of times a local player was the last one left alive, when another player died
+    pub wins: u32,
+    pub maps_played: u32,
+    /// The distance traveled by all local players, in pixels
+    pub distance_traveled: f32,
+    pub unlocked_achievements: Vec<String>,
+}
+
+pub struct Achievement {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub get_value: fn(&Stats) -> f32,
+    /// The achievement is unlocked when the value returned by `get_value` reaches this
+    pub threshold: f32,
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first_blood",
+        name: "First Blood",
+        description: "Take out another fish",
+        get_value: |stats| stats.kills as f32,
+        threshold: 1.0,
+    },
+    Achievement {
+        id: "fish_fighter",
+        name: "Fish Fighter",
+        description: "Take out 100 fish",
+        get_value: |stats| stats.kills as f32,
+        threshold: 100.0,
+    },
+    Achievement {
+        id: "champion",
+        name: "Champion",
+        description: "Be the last fish standing",
+        get_value: |stats| stats.wins as f32,
+        threshold: 1.0,
+    },
+    Achievement {
+        id: "explorer",
+        name: "Explorer",
+        description: "Play 10 maps",
+        get_value: |stats| stats.maps_played as f32,
+        threshold: 10.0,
+    },
+    Achievement {
+        id: "long_swim",
+        name: "Long Swim",
+        description: "Travel a distance of 100 000 pixels",
+        get_value: |stats| stats.distance_traveled,
+        threshold: 100_000.0,
+    },
+];
+
+pub struct StatsStore {
+    path: PathBuf,
+    pub stats: Stats,
+    is_dirty: bool,
+}
+
+impl StatsStore {
+    /// Load the stats from the specified data directory. If the stats file does not exist, or it
+    /// can not be parsed, the stats will start out empty.
+    pub fn load<P: AsRef<Path>>(data_dir: P) -> Self {
+        let path = data_dir.as_ref().join(STATS_FILE_NAME);
+
+        let stats = match std::fs::read(&path) {
+            Ok(bytes) => deserialize_json_bytes(&bytes).unwrap_or_else(|err| {
+                #[cfg(debug_assertions)]
+                println!("WARNING: Unable to parse stats file: {}", err);
+
+                Stats::default()
+            }),
+            Err(_) => Stats::default(),
+        };
+
+        StatsStore {
+            path,
+            stats,
+            is_dirty: false,
+        }
+    }
+
+    /// Write the stats to disk, if they have changed since they were last saved
+    pub fn save(&mut self) -> Result<()> {
+        if self.is_dirty {
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+
+            let json = serialize_json_string(&self.stats)?;
+            std::fs::write(&self.path, json)?;
+
+            self.is_dirty = false;
+        }
+
+        Ok(())
+    }
+
+    /// Apply a change to the stats and return any achievements that were unlocked by it
+    pub fn update<F: FnOnce(&mut Stats)>(&mut self, f: F) -> Vec<&'static Achievement> {
+        f(&mut self.stats);
+        self.is_dirty = true;
+
+        let mut unlocked = Vec::new();
+
+        for achievement in ACHIEVEMENTS {
+            let is_unlocked = self
+                .stats
+                .unlocked_achievements
+                .iter()
+                .any(|id| id == achievement.id);
+
+            if !is_unlocked && (achievement.get_value)(&self.stats) >= achievement.threshold {
+                self.stats
+                    .unlocked_achievements
+                    .push(achievement.id.to_string());
+
+                unlocked.push(achievement);
+            }
+        }
+
+        unlocked
+    }
+}
+
+/// Load the stats and store them, so that they can be updated during games. If the data
+/// directory can not be determined, stats will be disabled.
+pub fn init_stats() {
+    if let Some(data_dir) = get_data_dir() {
+        storage::store(StatsStore::load(data_dir));
+    } else {
+        #[cfg(debug_assertions)]
+        println!("WARNING: Unable to determine user data directory, stats are disabled");
+    }
+}
+
+/// Save the stats, if they are enabled. Errors are printed but otherwise ignored.
+pub fn save_stats() {
+    if let Some(mut store) = storage::try_get_mut::<StatsStore>() {
+        if let Err(err) = store.save() {
+            #[cfg(debug_assertions)]
+            println!("WARNING: Unable to save stats: {}", err);
+        }
+    }
+}
+
+struct AchievementToast {
+    name: &'static str,
+    description: &'static str,
+    timer: f32,
+}
+
+/// This holds the per game state of the stats tracking
+struct GameStats {
+    subscriber: GameEventSubscriber,
+    last_positions: HashMap<Entity, Vec2>,
+    toasts: Vec<AchievementToast>,
+}
+
+impl GameStats {
+    fn push_toasts(&mut self, unlocked: &[&'static Achievement]) {
+        for achievement in unlocked {
+            self.toasts.push(AchievementToast {
+                name: achievement.name,
+                description: achievement.description,
+                timer: 0.0,
+            });
+        }
+    }
+}
+
+/// This should be called when a game is created, after the event bus has been created
+pub fn init_game_stats() {
+    let unlocked = match storage::try_get_mut::<StatsStore>() {
+        Some(mut store) => store.update(|stats| stats.maps_played += 1),
+        None => return,
+    };
+
+    storage::store(GameStats {
+        subscriber: subscribe_game_events(),
+        last_positions: HashMap::new(),
+        toasts: Vec::new(),
+    });
+
+    show_achievement_toasts(&unlocked);
+}
+
+fn show_achievement_toasts(unlocked: &[&'static Achievement]) {
+    if let Some(mut game_stats) = storage::try_get_mut::<GameStats>() {
+        game_stats.push_toasts(unlocked);
+    }
+}
+
+/// Returns `true` if `entity` is a player that is controlled on this machine. Network players are
+/// not counted, as they have stats of their own.
+fn is_local_player(world: &World, entity: Entity) -> bool {
+    world
+        .get::<PlayerController>(entity)
+        .map(|controller| controller.kind.is_local())
+        .unwrap_or(false)
+}
+
+/// Returns the number of kills made by, and deaths of, local players, in the specified events
+fn count_local_kills_and_deaths(world: &World, events: &[GameEvent]) -> (u32, u32) {
+    let mut kills = 0;
+    let mut deaths = 0;
+
+    for event in events {
+        if let GameEvent::PlayerDied { player, killed_by } = *event {
+            if is_local_player(world, player) {
+                deaths += 1;
+            }
+
+            if let Some(killer) = killed_by {
+                if killer != player && is_local_player(world, killer) {
+                    kills += 1;
+                }
+            }
+        }
+    }
+
+    (kills, deaths)
+}
+
+/// Returns `true` if a player died in the specified events, leaving a local player as the only
+/// one left alive. There are no rounds, as dead players respawn, so this is counted as a win.
+fn is_local_win(world: &World, events: &[GameEvent]) -> bool {
+    let has_death = events
+        .iter()
+        .any(|event| matches!(event, GameEvent::PlayerDied { .. }));
+
+    if !has_death {
+        return false;
+    }
+
+    let mut player_cnt = 0;
+    let mut alive = Vec::new();
+
+    for (entity, player) in world.query::<&Player>().iter() {
+        player_cnt += 1;
+
+        if player.state != PlayerState::Dead {
+            alive.push(entity);
+        }
+    }
+
+    player_cnt > 1 && alive.len() == 1 && is_local_player(world, alive[0])
+}
+
+pub fn update_stats(world: &mut World) {
+    let mut store = match storage::try_get_mut::<StatsStore>() {
+        Some(store) => store,
+        None => return,
+    };
+
+    let mut game_stats = storage::get_mut::<GameStats>();
+
+    let events = drain_game_events(game_stats.subscriber);
+    let (kills, deaths) = count_local_kills_and_deaths(world, &events);
+    let wins = if is_local_win(world, &events) { 1 } else { 0 };
+
+    let mut distance = 0.0;
+
+    for (entity, (player, controller, transform)) in
+        world.query_mut::<(&Player, &PlayerController, &Transform)>()
+    {
+        if !controller.kind.is_local() {
+            continue;
+        }
+
+        if player.state == PlayerState::Dead {
+            game_stats.last_positions.remove(&entity);
+            continue;
+        }
+
+        if let Some(last_position) = game_stats.last_positions.insert(entity, transform.position) {
+            let delta = transform.position.distance(last_position);
+            if delta <= MAX_DISTANCE_PER_FRAME {
+                distance += delta;
+            }
+        }
+    }
+
+    if kills > 0 || deaths > 0 || wins > 0 || distance > 0.0 {
+        let unlocked = store.update(|stats| {
+            stats.kills += kills;
+            stats.deaths += deaths;
+            stats.wins += wins;
+            stats.distance_traveled += distance;
+        });
+
+        game_stats.push_toasts(&unlocked);
+    }
+
+    let dt = get_frame_time();
+
+    for toast in game_stats.toasts.iter_mut() {
+        toast.timer += dt;
+    }
+
+    game_stats
+        .toasts
+        .retain(|toast| toast.timer < TOAST_DURATION);
+}
+
+/// Draw toasts for recently unlocked achievements, in screen space
+pub fn draw_achievement_toasts(_world: &mut World) {
+    let game_stats = match storage::try_get::<GameStats>() {
+        Some(game_stats) => game_stats,
+        None => return,
+    };
+
+    if game_stats.toasts.is_empty() {
+        return;
+    }
+
+    push_camera_state();
+    set_default_camera();
+
+    let x = (screen_width() - TOAST_WIDTH) / 2.0;
+
+    for (i, toast) in game_stats.toasts.iter().enumerate() {
+        let y = TOAST_MARGIN + i as f32 * (TOAST_HEIGHT + TOAST_MARGIN);
+
+        let opacity = ((TOAST_DURATION - toast.timer) / TOAST_FADE_DURATION).clamp(0.0, 1.0);
+
+        let mut background_color = TOAST_BACKGROUND_COLOR;
+        background_color.a *= opacity;
+
+        let mut title_color = TOAST_TITLE_COLOR;
+        title_color.a = opacity;
+
+        let mut text_color = WHITE;
+        text_color.a = opacity;
+
+        draw_rectangle(x, y, TOAST_WIDTH, TOAST_HEIGHT, background_color);
+
+        draw_text(
+            &format!("Achievement unlocked: {}", toast.name),
+            x + 12.0,
+            y + 22.0,
+            22.0,
+            title_color,
+        );
+
+        draw_text(toast.description, x + 12.0, y + 44.0, 18.0, text_color);
+    }
+
+    pop_camera_state();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::input::GameInputScheme;
+
+    use crate::player::PlayerControllerKind;
+
+    #[test]
+    fn test_achievements_unlock_once() {
+        let mut store = StatsStore {
+            path: PathBuf::from(STATS_FILE_NAME),
+            stats: Stats::default(),
+            is_dirty: false,
+        };
+
+        let unlocked = store.update(|stats| stats.kills += 1);
+        assert_eq!(unlocked.len(), 1);
+        assert_eq!(unlocked[0].id, "first_blood");
+
+        let unlocked = store.update(|stats| stats.kills += 1);
+        assert!(unlocked.is_empty());
+        assert!(store.is_dirty);
+    }
+
+    #[test]
+    fn test_only_local_players_are_counted() {
+        let mut world = World::new();
+
+        let local = world.spawn((PlayerController::from(PlayerControllerKind::LocalInput(
+            GameInputScheme::KeyboardLeft,
+        )),));
+
+        let remote = world.spawn((PlayerController::from(PlayerControllerKind::Network(
+            "remote".to_string(),
+        )),));
+
+        let events = [
+            GameEvent::PlayerDied {
+                player: remote,
+                killed_by: Some(local),
+            },
+            GameEvent::PlayerDied {
+                player: local,
+                killed_by: Some(remote),
+            },
+            GameEvent::PlayerDied {
+                player: remote,
+                killed_by: Some(remote),
+            },
+            GameEvent::PlayerDied {
+                player: local,
+                killed_by: Some(local),
+            },
+        ];
+
+        // The local player killed the remote one once and died twice, once by its own hand
+        assert_eq!(count_local_kills_and_deaths(&world, &events), (1, 2));
+    }
+
+    #[test]
+    fn test_last_local_player_alive_wins() {
+        let mut world = World::new();
+
+        let local = world.spawn((
+            Player::new(0, Vec2::ZERO),
+            PlayerController::from(PlayerControllerKind::LocalInput(
+                GameInputScheme::KeyboardLeft,
+            )),
+        ));
+
+        let remote = world.spawn((
+            Player::new(1, Vec2::ZERO),
+            PlayerController::from(PlayerControllerKind::Network("remote".to_string())),
+        ));
+
+        let events = [GameEvent::PlayerDied {
+            player: remote,
+            killed_by: Some(local),
+        }];
+
+        // Both players are still alive, so nobody has won yet
+        assert!(!is_local_win(&world, &events));
+
+        world.get_mut::<Player>(remote).unwrap().state = PlayerState::Dead;
+        assert!(is_local_win(&world, &events));
+
+        // A win is only counted on the frame that a player died
+        assert!(!is_local_win(&world, &[]));
+
+        world.get_mut::<Player>(remote).unwrap().state = PlayerState::None;
+        world.get_mut::<Player>(local).unwrap().state = PlayerState::Dead;
+
+        let events = [GameEvent::PlayerDied {
+            player: local,
+            killed_by: Some(remote),
+        }];
+
+        // The remote player is the last one left, which is not a local win
+        assert!(!is_local_win(&world, &events));
+    }
+}
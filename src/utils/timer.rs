@@ -43,6 +43,12 @@ impl Timer {
     pub fn tick_frame_time(&mut self) {
         self.elapsed += macroquad::time::get_frame_time();
     }
+
+    /// Advance the elapsed time by the duration of one simulation tick. This should be used in
+    /// stead of `tick_frame_time` when the timer is updated in a fixed update.
+    pub fn tick_fixed_delta(&mut self) {
+        self.elapsed += crate::game::get_fixed_delta();
+    }
 }
 
 #[cfg(test)]